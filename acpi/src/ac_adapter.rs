@@ -0,0 +1,57 @@
+use crate::{device, AcpiError, AcpiResult};
+use alloc::vec::Vec;
+use aml::{value::Args, AmlContext, AmlName};
+
+/// What changed about an AC adapter, in response to a `Notify` on it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AcAdapterNotification {
+    /// `Notify(adapter, 0x80)`: the adapter has been plugged in or unplugged. Contains whether it is now online.
+    StatusChanged(bool),
+    /// The notification value isn't one defined for AC adapters.
+    Other(u64),
+}
+
+/// An AC adapter, described by an `ACPI0003` device in the namespace.
+#[derive(Clone, Debug)]
+pub struct AcAdapter {
+    path: AmlName,
+    online: bool,
+}
+
+impl AcAdapter {
+    /// Find the AC adapters in the namespace (devices with a `_HID` of `ACPI0003`), and read their status.
+    pub fn enumerate(context: &mut AmlContext) -> AcpiResult<Vec<AcAdapter>> {
+        device::find_devices_with_hid(context, "ACPI0003")?
+            .into_iter()
+            .map(|path| {
+                let mut adapter = AcAdapter { path, online: false };
+                adapter.refresh(context)?;
+                Ok(adapter)
+            })
+            .collect()
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// Whether the adapter was supplying power when its status was last read.
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+
+    /// Re-read whether the adapter is supplying power, by evaluating `_PSR`.
+    pub fn refresh(&mut self, context: &mut AmlContext) -> AcpiResult<bool> {
+        let psr = device::evaluate(context, &self.path, "_PSR", Args::EMPTY)?;
+        self.online = psr.as_integer(context).map_err(AcpiError::Aml)? != 0;
+        Ok(self.online)
+    }
+
+    /// Handle a `Notify` on the adapter, by re-reading its status if it has changed.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, value: u64) -> AcpiResult<AcAdapterNotification> {
+        match value {
+            0x80 => Ok(AcAdapterNotification::StatusChanged(self.refresh(context)?)),
+            other => Ok(AcAdapterNotification::Other(other)),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 //! ACPI defines a Generic Address Structure (GAS), which provides a versatile way to describe register locations
 //! in a wide range of address spaces.
 
-use crate::AcpiError;
-use core::convert::TryFrom;
+use crate::{AcpiError, AcpiHandler, AcpiResult};
+use bit_field::BitField;
+use core::{convert::TryFrom, ops::Range, ptr};
 
 /// This is the raw form of a Generic Address Structure, and follows the layout found in the ACPI tables. It does
 /// not form part of the public API, and should be turned into a `GenericAddress` for most use-cases.
@@ -16,6 +17,39 @@ pub(crate) struct RawGenericAddress {
     pub address: u64,
 }
 
+impl RawGenericAddress {
+    /// Parse an AML Generic Register Descriptor (the resource descriptor used by objects such as `_CST` and
+    /// `_PCT` to describe registers), which contains a Generic Address Structure. Returns `None` if `bytes`
+    /// doesn't start with a Generic Register Descriptor.
+    pub(crate) fn from_register_descriptor(bytes: &[u8]) -> Option<RawGenericAddress> {
+        /*
+         * Generic Register Descriptor:
+         * Byte 0       Descriptor type (0x82)
+         * Bytes 1-2    Length (0x0c)
+         * Byte 3       Address space ID
+         * Byte 4       Register bit width
+         * Byte 5       Register bit offset
+         * Byte 6       Access size
+         * Bytes 7-14   Register address
+         */
+        const GENERIC_REGISTER_DESCRIPTOR: u8 = 0x82;
+
+        if bytes.len() < 15 || bytes[0] != GENERIC_REGISTER_DESCRIPTOR {
+            return None;
+        }
+
+        let mut address = [0; 8];
+        address.copy_from_slice(&bytes[7..15]);
+        Some(RawGenericAddress {
+            address_space: bytes[3],
+            bit_width: bytes[4],
+            bit_offset: bytes[5],
+            access_size: bytes[6],
+            address: u64::from_le_bytes(address),
+        })
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum AddressSpace {
     SystemMemory,
@@ -75,7 +109,7 @@ pub struct GenericAddress {
 }
 
 impl GenericAddress {
-    pub(crate) fn from_raw(raw: RawGenericAddress) -> crate::AcpiResult<GenericAddress> {
+    pub(crate) fn from_raw(raw: RawGenericAddress) -> AcpiResult<GenericAddress> {
         let address_space = match raw.address_space {
             0x00 => AddressSpace::SystemMemory,
             0x01 => AddressSpace::SystemIo,
@@ -102,4 +136,268 @@ impl GenericAddress {
             address: raw.address,
         })
     }
+
+    /// Convert the address back into the layout found in the ACPI tables.
+    pub(crate) fn to_raw(self) -> RawGenericAddress {
+        let address_space = match self.address_space {
+            AddressSpace::SystemMemory => 0x00,
+            AddressSpace::SystemIo => 0x01,
+            AddressSpace::PciConfigSpace => 0x02,
+            AddressSpace::EmbeddedController => 0x03,
+            AddressSpace::SMBus => 0x04,
+            AddressSpace::SystemCmos => 0x05,
+            AddressSpace::PciBarTarget => 0x06,
+            AddressSpace::Ipmi => 0x07,
+            AddressSpace::GeneralIo => 0x08,
+            AddressSpace::GenericSerialBus => 0x09,
+            AddressSpace::PlatformCommunicationsChannel => 0x0a,
+            AddressSpace::FunctionalFixedHardware => 0x7f,
+            AddressSpace::OemDefined(space) => space,
+        };
+        let access_size = match self.access_size {
+            AccessSize::Undefined => 0,
+            AccessSize::ByteAccess => 1,
+            AccessSize::WordAccess => 2,
+            AccessSize::DWordAccess => 3,
+            AccessSize::QWordAccess => 4,
+        };
+
+        RawGenericAddress {
+            address_space,
+            bit_width: self.bit_width,
+            bit_offset: self.bit_offset,
+            access_size,
+            address: self.address,
+        }
+    }
+}
+
+/// An [`AcpiHandler`] that can also access the system I/O space and PCI configuration space. This is needed by
+/// the parts of this crate that access the hardware registers described by the tables (e.g. to execute the
+/// actions of the WDAT), rather than just parsing the tables. Registers in system memory are accessed by mapping
+/// them with [`AcpiHandler::map_physical_region`].
+pub trait RegisterHandler: AcpiHandler {
+    fn read_io_u8(&self, port: u16) -> u8;
+    fn read_io_u16(&self, port: u16) -> u16;
+    fn read_io_u32(&self, port: u16) -> u32;
+
+    fn write_io_u8(&self, port: u16, value: u8);
+    fn write_io_u16(&self, port: u16, value: u16);
+    fn write_io_u32(&self, port: u16, value: u32);
+
+    fn read_pci_u8(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16) -> u8;
+    fn read_pci_u16(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16) -> u16;
+    fn read_pci_u32(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16) -> u32;
+
+    fn write_pci_u8(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16, value: u8);
+    fn write_pci_u16(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16, value: u16);
+    fn write_pci_u32(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16, value: u32);
+}
+
+impl GenericAddress {
+    /// The width, in bits, of each access to the register. If the access size is undefined, this is inferred from
+    /// the width of the register.
+    pub(crate) fn access_width(&self) -> AcpiResult<u8> {
+        match self.access_size {
+            AccessSize::ByteAccess => Ok(8),
+            AccessSize::WordAccess => Ok(16),
+            AccessSize::DWordAccess => Ok(32),
+            AccessSize::QWordAccess => Ok(64),
+            AccessSize::Undefined => match self.bit_offset + self.bit_width {
+                0..=8 => Ok(8),
+                9..=16 => Ok(16),
+                17..=32 => Ok(32),
+                33..=64 => Ok(64),
+                _ => Err(AcpiError::InvalidGenericAddress),
+            },
+        }
+    }
+
+    /// Read the whole register, with a single access of `access_width` bits. The bit offset and width of the
+    /// register are not applied.
+    pub(crate) fn read_register<H>(&self, handler: &H) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        self.read_access(handler, self.address, self.access_width()?)
+    }
+
+    /// Write the whole register, with a single access of `access_width` bits. The bit offset and width of the
+    /// register are not applied.
+    pub(crate) fn write_register<H>(&self, handler: &H, value: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.write_access(handler, self.address, self.access_width()?, value)
+    }
+
+    /// Read the value of the register, which is `bit_width` bits wide and starts `bit_offset` bits into the
+    /// register block. A register that is wider than the access size (e.g. a GPE block described with byte
+    /// accesses) is read with an access to each of the consecutive addresses it covers, which are combined in
+    /// little-endian order. If `bit_width` is zero, the register extends to the end of the first access.
+    pub fn read<H>(&self, handler: &H) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        let (access_width, field) = self.field_bits()?;
+
+        let mut value = 0;
+        for (address, access_bits) in self.accesses(access_width, &field) {
+            let part = self.read_access(handler, address, access_width)?;
+            let start = usize::max(field.start, access_bits.start);
+            let end = usize::min(field.end, access_bits.end);
+            let access_part = (start - access_bits.start)..(end - access_bits.start);
+            value.set_bits((start - field.start)..(end - field.start), part.get_bits(access_part));
+        }
+
+        Ok(value)
+    }
+
+    /// Write `value` to the register, which is `bit_width` bits wide and starts `bit_offset` bits into the
+    /// register block (see [`GenericAddress::read`]). Bits of `value` that don't fit in the register are
+    /// ignored. Accesses that only partially contain the register are read first, so that the bits around it are
+    /// preserved.
+    pub fn write<H>(&self, value: u64, handler: &H) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let (access_width, field) = self.field_bits()?;
+
+        for (address, access_bits) in self.accesses(access_width, &field) {
+            let start = usize::max(field.start, access_bits.start);
+            let end = usize::min(field.end, access_bits.end);
+            let mut part = if start == access_bits.start && end == access_bits.end {
+                0
+            } else {
+                self.read_access(handler, address, access_width)?
+            };
+            let field_part = (start - field.start)..(end - field.start);
+            part.set_bits((start - access_bits.start)..(end - access_bits.start), value.get_bits(field_part));
+            self.write_access(handler, address, access_width, part)?;
+        }
+
+        Ok(())
+    }
+
+    /// The width of each access to the register, and the bits of the register block (starting from the first
+    /// access) that the register occupies.
+    fn field_bits(&self) -> AcpiResult<(u8, Range<usize>)> {
+        let access_width = self.access_width()?;
+        let start = self.bit_offset as usize;
+        let end = if self.bit_width == 0 { access_width as usize } else { start + self.bit_width as usize };
+
+        // The register has to fit in the `u64` it's read into
+        if start >= end || end - start > 64 {
+            return Err(AcpiError::InvalidGenericAddress);
+        }
+        Ok((access_width, start..end))
+    }
+
+    /// The address of each access that contains part of the register in `field`, and the bits of the register
+    /// block that the access covers.
+    fn accesses(&self, access_width: u8, field: &Range<usize>) -> impl Iterator<Item = (u64, Range<usize>)> {
+        let access_width = access_width as usize;
+        let base = self.address;
+        (field.start / access_width..field.end.div_ceil(access_width)).map(move |index| {
+            let address = base + (index * access_width / 8) as u64;
+            (address, (index * access_width)..((index + 1) * access_width))
+        })
+    }
+
+    /// Read `width` bits from `address`, in the register's address space, with a single access.
+    fn read_access<H>(&self, handler: &H, address: u64, width: u8) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        match self.address_space {
+            AddressSpace::SystemMemory => {
+                let mapping = unsafe { handler.map_physical_region::<u8>(address as usize, width as usize / 8) };
+                let pointer = mapping.virtual_start().as_ptr();
+
+                Ok(unsafe {
+                    match width {
+                        8 => ptr::read_volatile(pointer) as u64,
+                        16 => ptr::read_volatile(pointer as *const u16) as u64,
+                        32 => ptr::read_volatile(pointer as *const u32) as u64,
+                        _ => ptr::read_volatile(pointer as *const u64),
+                    }
+                })
+            }
+
+            AddressSpace::SystemIo => {
+                let port = u16::try_from(address).map_err(|_| AcpiError::InvalidGenericAddress)?;
+                match width {
+                    8 => Ok(handler.read_io_u8(port) as u64),
+                    16 => Ok(handler.read_io_u16(port) as u64),
+                    32 => Ok(handler.read_io_u32(port) as u64),
+                    _ => Err(AcpiError::InvalidGenericAddress),
+                }
+            }
+
+            AddressSpace::PciConfigSpace => {
+                let (device, function, offset) = pci_config_location(address);
+                match width {
+                    8 => Ok(handler.read_pci_u8(0, 0, device, function, offset) as u64),
+                    16 => Ok(handler.read_pci_u16(0, 0, device, function, offset) as u64),
+                    32 => Ok(handler.read_pci_u32(0, 0, device, function, offset) as u64),
+                    _ => Err(AcpiError::InvalidGenericAddress),
+                }
+            }
+
+            address_space => Err(AcpiError::UnsupportedAddressSpace(address_space)),
+        }
+    }
+
+    /// Write `width` bits to `address`, in the register's address space, with a single access.
+    fn write_access<H>(&self, handler: &H, address: u64, width: u8, value: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        match self.address_space {
+            AddressSpace::SystemMemory => {
+                let mapping = unsafe { handler.map_physical_region::<u8>(address as usize, width as usize / 8) };
+                let pointer = mapping.virtual_start().as_ptr();
+
+                unsafe {
+                    match width {
+                        8 => ptr::write_volatile(pointer, value as u8),
+                        16 => ptr::write_volatile(pointer as *mut u16, value as u16),
+                        32 => ptr::write_volatile(pointer as *mut u32, value as u32),
+                        _ => ptr::write_volatile(pointer as *mut u64, value),
+                    }
+                }
+                Ok(())
+            }
+
+            AddressSpace::SystemIo => {
+                let port = u16::try_from(address).map_err(|_| AcpiError::InvalidGenericAddress)?;
+                match width {
+                    8 => handler.write_io_u8(port, value as u8),
+                    16 => handler.write_io_u16(port, value as u16),
+                    32 => handler.write_io_u32(port, value as u32),
+                    _ => return Err(AcpiError::InvalidGenericAddress),
+                }
+                Ok(())
+            }
+
+            AddressSpace::PciConfigSpace => {
+                let (device, function, offset) = pci_config_location(address);
+                match width {
+                    8 => handler.write_pci_u8(0, 0, device, function, offset, value as u8),
+                    16 => handler.write_pci_u16(0, 0, device, function, offset, value as u16),
+                    32 => handler.write_pci_u32(0, 0, device, function, offset, value as u32),
+                    _ => return Err(AcpiError::InvalidGenericAddress),
+                }
+                Ok(())
+            }
+
+            address_space => Err(AcpiError::UnsupportedAddressSpace(address_space)),
+        }
+    }
+}
+
+/// Get the device, function, and offset of a register in PCI configuration space (see
+/// [`AddressSpace::PciConfigSpace`]).
+fn pci_config_location(address: u64) -> (u8, u8, u16) {
+    (address.get_bits(32..48) as u8, address.get_bits(16..32) as u8, address.get_bits(0..16) as u16)
 }
@@ -0,0 +1,429 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, slice};
+
+/// Represents the ARM Error Source Table (AEST), which describes the RAS error nodes of an ARM platform: the
+/// components (processors, memory, SMMUs, GICs, and vendor-defined devices) that record errors, the interface
+/// used to access their error records, and the interrupts they raise. The layout of this table is defined by ARM
+/// (DEN0085), and this describes version 1.0.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Aest {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid AEST.
+unsafe impl AcpiTable for Aest {
+    const SIGNATURE: Signature = Signature::AEST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Aest {
+    pub fn nodes(&self) -> AestNodeIter<'_> {
+        AestNodeIter {
+            pointer: unsafe { (self as *const Aest as *const u8).add(mem::size_of::<Aest>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Aest>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AestNodeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for AestNodeIter<'a> {
+    type Item = &'a AestNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<AestNode>() {
+            return None;
+        }
+
+        let node = unsafe { &*(self.pointer as *const AestNode) };
+        let length = node.length as usize;
+        if length < mem::size_of::<AestNode>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(node)
+    }
+}
+
+/// The header of an error node. The node-specific data, the interface, and the interrupts of the node are found
+/// at offsets from the start of this structure, and can be accessed with [`AestNode::data`],
+/// [`AestNode::interface`], and [`AestNode::interrupts`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AestNode {
+    pub node_type: u8,
+    pub length: u16,
+    _reserved0: u8,
+    pub node_data_offset: u32,
+    pub node_interface_offset: u32,
+    pub node_interrupt_array_offset: u32,
+    pub node_interrupt_count: u32,
+    /// The frequency, in Hz, of the timestamps in the error records of this node, or `0` if they are not
+    /// timestamped.
+    pub timestamp_rate: u64,
+    _reserved1: u64,
+    /// The frequency, in Hz, of the countdown used to inject errors, or `0` if the node doesn't support error
+    /// injection.
+    pub error_injection_countdown_rate: u64,
+}
+
+#[derive(Debug)]
+pub enum AestNodeData<'a> {
+    Processor(&'a ProcessorNodeData),
+    Memory(&'a MemoryNodeData),
+    Smmu(&'a SmmuNodeData),
+    VendorDefined(&'a VendorDefinedNodeData),
+    Gic(&'a GicNodeData),
+}
+
+impl AestNode {
+    /// Get `length` bytes at `offset` from the start of this node, if they are within it.
+    fn bytes_at(&self, offset: u32, length: usize) -> Option<*const u8> {
+        let offset = offset as usize;
+        if offset < mem::size_of::<AestNode>() || offset + length > self.length as usize {
+            return None;
+        }
+
+        Some(unsafe { (self as *const AestNode as *const u8).add(offset) })
+    }
+
+    /// The node-specific data. Returns `None` if the node is of a reserved type, or its data is malformed.
+    pub fn data(&self) -> Option<AestNodeData<'_>> {
+        macro_rules! node_data {
+            ($variant:path, $type:ty) => {
+                $variant(unsafe {
+                    &*(self.bytes_at(self.node_data_offset, mem::size_of::<$type>())? as *const $type)
+                })
+            };
+        }
+
+        Some(match self.node_type {
+            0 => node_data!(AestNodeData::Processor, ProcessorNodeData),
+            1 => node_data!(AestNodeData::Memory, MemoryNodeData),
+            2 => node_data!(AestNodeData::Smmu, SmmuNodeData),
+            3 => node_data!(AestNodeData::VendorDefined, VendorDefinedNodeData),
+            4 => node_data!(AestNodeData::Gic, GicNodeData),
+            _ => return None,
+        })
+    }
+
+    pub fn interface(&self) -> Option<&NodeInterface> {
+        let pointer = self.bytes_at(self.node_interface_offset, mem::size_of::<NodeInterface>())?;
+        Some(unsafe { &*(pointer as *const NodeInterface) })
+    }
+
+    pub fn interrupts(&self) -> &[NodeInterrupt] {
+        let count = self.node_interrupt_count as usize;
+        match self.bytes_at(self.node_interrupt_array_offset, count * mem::size_of::<NodeInterrupt>()) {
+            Some(pointer) if count > 0 => unsafe { slice::from_raw_parts(pointer as *const NodeInterrupt, count) },
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessorResource {
+    /// A cache, identified by the offset of its cache type structure in the PPTT.
+    Cache {
+        pptt_offset: u32,
+    },
+    Tlb {
+        level: u32,
+    },
+    Generic {
+        data: u32,
+    },
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ProcessorNodeData {
+    /// The ACPI processor UID of the processor this node belongs to, if it is not shared (see
+    /// [`ProcessorNodeData::is_global`]).
+    pub acpi_processor_id: u32,
+    resource_type: u8,
+    _reserved0: u8,
+    pub flags: u8,
+    pub revision: u8,
+    /// The affinity level of the resource, if it is shared between processors, as an MPIDR affinity level.
+    pub processor_affinity_level_indicator: u64,
+    resource_data: u32,
+    _reserved1: u32,
+}
+
+impl ProcessorNodeData {
+    /// If true, the node is a global resource, shared by all of the processors of the platform.
+    pub fn is_global(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the node is shared between the processors at `processor_affinity_level_indicator`.
+    pub fn is_shared(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+
+    pub fn resource(&self) -> ProcessorResource {
+        match self.resource_type {
+            0 => ProcessorResource::Cache { pptt_offset: self.resource_data },
+            1 => ProcessorResource::Tlb { level: self.resource_data },
+            2 => ProcessorResource::Generic { data: self.resource_data },
+            other => ProcessorResource::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryNodeData {
+    pub srat_proximity_domain: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SmmuNodeData {
+    /// The offset of the SMMU's node in the IORT.
+    pub iort_node_reference: u32,
+    pub subcomponent_reference: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct VendorDefinedNodeData {
+    /// The `_HID` of the device, as a compressed EISA ID.
+    pub hardware_id: u32,
+    pub unique_id: u32,
+    pub vendor_specific_data: [u8; 16],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GicInterfaceType {
+    CpuInterface,
+    Distributor,
+    Redistributor,
+    Its,
+    Reserved(u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GicNodeData {
+    interface_type: u32,
+    pub instance_identifier: u32,
+}
+
+impl GicNodeData {
+    pub fn interface_type(&self) -> GicInterfaceType {
+        match self.interface_type {
+            0 => GicInterfaceType::CpuInterface,
+            1 => GicInterfaceType::Distributor,
+            2 => GicInterfaceType::Redistributor,
+            3 => GicInterfaceType::Its,
+            other => GicInterfaceType::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeInterfaceType {
+    /// The error records are accessed with the `ERR<n>*` system registers.
+    SystemRegister,
+    /// The error records are accessed through a memory-mapped group of registers at `base_address`.
+    MemoryMapped,
+    Reserved(u8),
+}
+
+/// Describes how the error records of a node are accessed.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeInterface {
+    interface_type: u8,
+    _reserved: [u8; 3],
+    pub flags: u32,
+    pub base_address: u64,
+    pub start_error_record_index: u32,
+    pub number_of_error_records: u32,
+    /// A bitmap of which of the node's error records are implemented. A set bit means the record is **not**
+    /// implemented.
+    pub error_record_implemented: u64,
+    /// A bitmap of which of the node's error records support reporting through the `ERRGSR` status register.
+    pub error_record_status_reporting_supported: u64,
+    pub addressing_mode: u64,
+}
+
+impl NodeInterface {
+    pub fn interface_type(&self) -> NodeInterfaceType {
+        match self.interface_type {
+            0 => NodeInterfaceType::SystemRegister,
+            1 => NodeInterfaceType::MemoryMapped,
+            other => NodeInterfaceType::Reserved(other),
+        }
+    }
+
+    /// If true, the interface is shared between processors, rather than being private to one.
+    pub fn is_shared(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    /// If true, the OS should clear the `ERR<n>MISC` registers of the node's error records when it initializes
+    /// them.
+    pub fn clear_misc_registers(&self) -> bool {
+        { self.flags }.get_bit(1)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeInterruptType {
+    FaultHandling,
+    ErrorRecovery,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeInterrupt {
+    interrupt_type: u8,
+    _reserved0: u16,
+    pub flags: u8,
+    pub gsiv: u32,
+    /// The identifier of the ITS group this interrupt is routed through, if it is an MSI.
+    pub its_group_reference: u8,
+    _reserved1: [u8; 3],
+}
+
+impl NodeInterrupt {
+    pub fn interrupt_type(&self) -> NodeInterruptType {
+        match self.interrupt_type {
+            0 => NodeInterruptType::FaultHandling,
+            1 => NodeInterruptType::ErrorRecovery,
+            other => NodeInterruptType::Reserved(other),
+        }
+    }
+
+    pub fn is_level_triggered(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    /// Build a node with its data, a memory-mapped interface, and `interrupts`, in that order.
+    fn node(node_type: u8, data: &[u8], interrupts: &[NodeInterrupt]) -> Vec<u8> {
+        let data_offset = mem::size_of::<AestNode>();
+        let interface_offset = data_offset + data.len();
+        let interrupts_offset = interface_offset + mem::size_of::<NodeInterface>();
+        let length = interrupts_offset + mem::size_of_val(interrupts);
+
+        let mut bytes = bytes_of(&AestNode {
+            node_type,
+            length: length as u16,
+            node_data_offset: data_offset as u32,
+            node_interface_offset: interface_offset as u32,
+            node_interrupt_array_offset: interrupts_offset as u32,
+            node_interrupt_count: interrupts.len() as u32,
+            ..unsafe { zeroed() }
+        });
+        bytes.extend(data);
+        bytes.extend(bytes_of(&NodeInterface {
+            interface_type: 1,
+            flags: 0b10,
+            base_address: 0x2a40_0000,
+            number_of_error_records: 2,
+            ..unsafe { zeroed() }
+        }));
+        for interrupt in interrupts {
+            bytes.extend(bytes_of(interrupt));
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_nodes() {
+        let processor = ProcessorNodeData {
+            acpi_processor_id: 3,
+            resource_type: 0,
+            flags: 0,
+            revision: 1,
+            resource_data: 0x24,
+            ..unsafe { zeroed() }
+        };
+        let interrupts = [
+            NodeInterrupt { interrupt_type: 1, flags: 0b1, gsiv: 64, ..unsafe { zeroed() } },
+            NodeInterrupt { interrupt_type: 0, flags: 0, gsiv: 65, ..unsafe { zeroed() } },
+        ];
+        let mut body = node(0, &bytes_of(&processor), &interrupts);
+        body.extend(node(4, &bytes_of(&GicNodeData { interface_type: 1, instance_identifier: 0 }), &[]));
+        // A memory node whose data is past the end of the node
+        let mut memory = node(1, &[], &[]);
+        let length = memory.len() as u32;
+        memory[4..8].copy_from_slice(&length.to_le_bytes());
+        body.extend(memory);
+
+        let buffer = TableBuffer::<Aest>::new(&table(b"AEST", 1, &body));
+        let aest = buffer.get();
+        assert!(aest.validate().is_ok());
+
+        let nodes: Vec<_> = aest.nodes().collect();
+        assert_eq!(nodes.len(), 3);
+        match nodes[0].data() {
+            Some(AestNodeData::Processor(data)) => {
+                assert_eq!({ data.acpi_processor_id }, 3);
+                assert_eq!(data.resource(), ProcessorResource::Cache { pptt_offset: 0x24 });
+                assert!(!data.is_global());
+            }
+            ref other => panic!("unexpected node data: {:?}", other),
+        }
+        let interface = nodes[0].interface().unwrap();
+        assert_eq!(interface.interface_type(), NodeInterfaceType::MemoryMapped);
+        assert!(interface.clear_misc_registers());
+        assert_eq!({ interface.base_address }, 0x2a40_0000);
+        let interrupts = nodes[0].interrupts();
+        assert_eq!(interrupts.len(), 2);
+        assert_eq!(interrupts[0].interrupt_type(), NodeInterruptType::ErrorRecovery);
+        assert!(interrupts[0].is_level_triggered());
+        assert_eq!({ interrupts[1].gsiv }, 65);
+
+        match nodes[1].data() {
+            Some(AestNodeData::Gic(data)) => assert_eq!(data.interface_type(), GicInterfaceType::Distributor),
+            ref other => panic!("unexpected node data: {:?}", other),
+        }
+        assert!(nodes[1].interrupts().is_empty());
+        assert!(nodes[2].data().is_none());
+    }
+
+    #[test]
+    fn test_truncated_node() {
+        // An interrupt array that runs past the end of the node isn't read
+        let interrupt = NodeInterrupt { gsiv: 64, ..unsafe { zeroed() } };
+        let mut bytes = node(1, &[0; 4], &[interrupt]);
+        bytes[16..20].copy_from_slice(&2u32.to_le_bytes());
+        let buffer = TableBuffer::<Aest>::new(&table(b"AEST", 1, &bytes));
+        assert!(buffer.get().nodes().next().unwrap().interrupts().is_empty());
+
+        // A node that runs past the end of the table ends the list
+        let bytes = node(1, &[0; 4], &[]);
+        let buffer = TableBuffer::<Aest>::new(&table(b"AEST", 1, &bytes[..bytes.len() - 1]));
+        assert_eq!(buffer.get().nodes().count(), 0);
+    }
+}
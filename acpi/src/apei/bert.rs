@@ -0,0 +1,76 @@
+use super::GenericErrorStatusBlock;
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiHandler,
+    AcpiTable,
+    PhysicalMapping,
+};
+use core::mem;
+
+/// Represents the Boot Error Record Table (BERT), which points to the Boot Error Region: a
+/// [`GenericErrorStatusBlock`] describing the errors that occurred during the previous boot, which the platform
+/// couldn't report at the time (e.g. fatal errors that caused a reset).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Bert {
+    pub header: SdtHeader,
+    pub boot_error_region_length: u32,
+    /// The physical address of the Boot Error Region.
+    pub boot_error_region: u64,
+}
+
+/// ### Safety: Implementation properly represents a valid BERT.
+unsafe impl AcpiTable for Bert {
+    const SIGNATURE: Signature = Signature::BERT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Bert {
+    /// Map the Boot Error Region. Returns `None` if the table doesn't describe a region large enough to contain a
+    /// status block. The entries of the block can be iterated with
+    /// [`GenericErrorStatusBlock::entries_within`], passing the `region_length` of the returned mapping.
+    pub fn map_boot_error_region<H>(&self, handler: &H) -> Option<PhysicalMapping<H, GenericErrorStatusBlock>>
+    where
+        H: AcpiHandler,
+    {
+        let length = self.boot_error_region_length as usize;
+        if self.boot_error_region == 0 || length < mem::size_of::<GenericErrorStatusBlock>() {
+            return None;
+        }
+
+        Some(unsafe {
+            handler.map_physical_region::<GenericErrorStatusBlock>(self.boot_error_region as usize, length)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, table, TableBuffer, TestHandler};
+
+    fn bert(boot_error_region_length: u32, boot_error_region: u64) -> TableBuffer<Bert> {
+        let body = body_of(&Bert { header: blank_header(), boot_error_region_length, boot_error_region });
+        TableBuffer::new(&table(b"BERT", 1, &body))
+    }
+
+    #[test]
+    fn test_map_boot_error_region() {
+        let region = [0u32, 0, 0, 0, 1];
+        let buffer = bert(20, region.as_ptr() as u64);
+        assert!(buffer.get().validate().is_ok());
+
+        let handler = TestHandler::default();
+        let block = buffer.get().map_boot_error_region(&handler).unwrap();
+        assert_eq!(block.region_length(), 20);
+        assert!(!block.has_errors());
+        assert_eq!(block.entries_within(block.region_length()).count(), 0);
+
+        // The region is too short to hold a status block, or is missing
+        assert!(bert(19, region.as_ptr() as u64).get().map_boot_error_region(&handler).is_none());
+        assert!(bert(20, 0).get().map_boot_error_region(&handler).is_none());
+    }
+}
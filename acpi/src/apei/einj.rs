@@ -0,0 +1,128 @@
+use super::InstructionEntry;
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{mem, slice};
+
+/// Represents the Error Injection Table (EINJ), which describes how the OS can inject hardware errors, to test
+/// its error handling. Each step of an injection is described as an injection action, which is a list of
+/// [`InstructionEntry`]s.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Einj {
+    pub header: SdtHeader,
+    pub injection_header_size: u32,
+    pub injection_flags: u8,
+    _reserved: [u8; 3],
+    pub injection_entry_count: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid EINJ.
+unsafe impl AcpiTable for Einj {
+    const SIGNATURE: Signature = Signature::EINJ;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Einj {
+    pub fn entries(&self) -> &[InstructionEntry] {
+        let available = (self.header.length as usize).saturating_sub(mem::size_of::<Einj>())
+            / mem::size_of::<InstructionEntry>();
+        let count = usize::min(self.injection_entry_count as usize, available);
+        if count == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let pointer = (self as *const Einj as *const u8).add(mem::size_of::<Einj>());
+            slice::from_raw_parts(pointer as *const InstructionEntry, count)
+        }
+    }
+
+    /// The instructions that make up `action`, in the order they should be executed.
+    pub fn instructions_for(&self, action: InjectionAction) -> impl Iterator<Item = &InstructionEntry> {
+        self.entries().iter().filter(move |entry| InjectionAction::from(entry.action) == action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InjectionAction {
+    BeginInjectionOperation,
+    /// Returns the physical address of the Trigger Error Action Table, which contains the instructions that
+    /// trigger the injected error.
+    GetTriggerErrorActionTable,
+    SetErrorType,
+    GetErrorType,
+    EndOperation,
+    ExecuteOperation,
+    CheckBusyStatus,
+    GetCommandStatus,
+    SetErrorTypeWithAddress,
+    GetExecuteOperationTimings,
+    /// Only used in the Trigger Error Action Table.
+    TriggerError,
+    Reserved(u8),
+}
+
+impl From<u8> for InjectionAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x00 => InjectionAction::BeginInjectionOperation,
+            0x01 => InjectionAction::GetTriggerErrorActionTable,
+            0x02 => InjectionAction::SetErrorType,
+            0x03 => InjectionAction::GetErrorType,
+            0x04 => InjectionAction::EndOperation,
+            0x05 => InjectionAction::ExecuteOperation,
+            0x06 => InjectionAction::CheckBusyStatus,
+            0x07 => InjectionAction::GetCommandStatus,
+            0x08 => InjectionAction::SetErrorTypeWithAddress,
+            0x09 => InjectionAction::GetExecuteOperationTimings,
+            0xff => InjectionAction::TriggerError,
+            other => InjectionAction::Reserved(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, finish_table, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn einj(injection_entry_count: u32, actions: &[u8]) -> Vec<u8> {
+        let mut body = body_of(&Einj {
+            header: blank_header(),
+            injection_header_size: 12,
+            injection_flags: 0,
+            _reserved: [0; 3],
+            injection_entry_count,
+        });
+        for &action in actions {
+            body.extend(bytes_of(&InstructionEntry { action, instruction: 0x03, ..unsafe { zeroed() } }));
+        }
+        table(b"EINJ", 1, &body)
+    }
+
+    #[test]
+    fn test_entries() {
+        let buffer = TableBuffer::<Einj>::new(&einj(4, &[0x00, 0x02, 0x05, 0x02]));
+        let einj = buffer.get();
+        assert!(einj.validate().is_ok());
+        assert_eq!(einj.entries().len(), 4);
+        assert_eq!(einj.entries()[0].instruction(), crate::apei::Instruction::WriteRegisterValue);
+        assert_eq!(einj.instructions_for(InjectionAction::SetErrorType).count(), 2);
+        assert_eq!(einj.instructions_for(InjectionAction::TriggerError).count(), 0);
+    }
+
+    #[test]
+    fn test_truncated_entries() {
+        let mut bytes = einj(3, &[0x00, 0x05]);
+        bytes.truncate(bytes.len() - 1);
+        finish_table(&mut bytes);
+        let buffer = TableBuffer::<Einj>::new(&bytes);
+        assert_eq!(buffer.get().entries().len(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use super::InstructionEntry;
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{mem, slice};
+
+/// Represents the Error Record Serialization Table (ERST), which describes how the OS can save error records to,
+/// and retrieve them from, persistent storage managed by the platform. Each operation is described as a sequence
+/// of serialization actions, each of which is a list of [`InstructionEntry`]s.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Erst {
+    pub header: SdtHeader,
+    pub serialization_header_length: u32,
+    _reserved: u32,
+    pub instruction_entry_count: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid ERST.
+unsafe impl AcpiTable for Erst {
+    const SIGNATURE: Signature = Signature::ERST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Erst {
+    pub fn entries(&self) -> &[InstructionEntry] {
+        let available = (self.header.length as usize).saturating_sub(mem::size_of::<Erst>())
+            / mem::size_of::<InstructionEntry>();
+        let count = usize::min(self.instruction_entry_count as usize, available);
+        if count == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let pointer = (self as *const Erst as *const u8).add(mem::size_of::<Erst>());
+            slice::from_raw_parts(pointer as *const InstructionEntry, count)
+        }
+    }
+
+    /// The instructions that make up `action`, in the order they should be executed.
+    pub fn instructions_for(&self, action: SerializationAction) -> impl Iterator<Item = &InstructionEntry> {
+        self.entries().iter().filter(move |entry| SerializationAction::from(entry.action) == action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SerializationAction {
+    BeginWriteOperation,
+    BeginReadOperation,
+    BeginClearOperation,
+    EndOperation,
+    SetRecordOffset,
+    ExecuteOperation,
+    CheckBusyStatus,
+    GetCommandStatus,
+    GetRecordIdentifier,
+    SetRecordIdentifier,
+    GetRecordCount,
+    BeginDummyWriteOperation,
+    GetErrorLogAddressRange,
+    GetErrorLogAddressRangeLength,
+    GetErrorLogAddressRangeAttributes,
+    GetExecuteOperationTimings,
+    Reserved(u8),
+}
+
+impl From<u8> for SerializationAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x00 => SerializationAction::BeginWriteOperation,
+            0x01 => SerializationAction::BeginReadOperation,
+            0x02 => SerializationAction::BeginClearOperation,
+            0x03 => SerializationAction::EndOperation,
+            0x04 => SerializationAction::SetRecordOffset,
+            0x05 => SerializationAction::ExecuteOperation,
+            0x06 => SerializationAction::CheckBusyStatus,
+            0x07 => SerializationAction::GetCommandStatus,
+            0x08 => SerializationAction::GetRecordIdentifier,
+            0x09 => SerializationAction::SetRecordIdentifier,
+            0x0a => SerializationAction::GetRecordCount,
+            0x0b => SerializationAction::BeginDummyWriteOperation,
+            0x0d => SerializationAction::GetErrorLogAddressRange,
+            0x0e => SerializationAction::GetErrorLogAddressRangeLength,
+            0x0f => SerializationAction::GetErrorLogAddressRangeAttributes,
+            0x10 => SerializationAction::GetExecuteOperationTimings,
+            other => SerializationAction::Reserved(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, finish_table, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn erst(instruction_entry_count: u32, actions: &[u8]) -> Vec<u8> {
+        let mut body = body_of(&Erst {
+            header: blank_header(),
+            serialization_header_length: 12,
+            _reserved: 0,
+            instruction_entry_count,
+        });
+        for &action in actions {
+            body.extend(bytes_of(&InstructionEntry { action, instruction: 0x00, ..unsafe { zeroed() } }));
+        }
+        table(b"ERST", 1, &body)
+    }
+
+    #[test]
+    fn test_entries() {
+        let buffer = TableBuffer::<Erst>::new(&erst(3, &[0x00, 0x0d, 0x0d]));
+        let erst = buffer.get();
+        assert!(erst.validate().is_ok());
+        assert_eq!(erst.entries().len(), 3);
+        assert_eq!(erst.instructions_for(SerializationAction::GetErrorLogAddressRange).count(), 2);
+        assert_eq!(erst.instructions_for(SerializationAction::BeginReadOperation).count(), 0);
+        assert_eq!(SerializationAction::from(0x0c), SerializationAction::Reserved(0x0c));
+    }
+
+    #[test]
+    fn test_truncated_entries() {
+        // Only the entries that fit in the table are read
+        let buffer = TableBuffer::<Erst>::new(&erst(5, &[0x00, 0x0d]));
+        assert_eq!(buffer.get().entries().len(), 2);
+
+        let mut bytes = erst(2, &[0x00, 0x0d]);
+        bytes.truncate(bytes.len() - 1);
+        finish_table(&mut bytes);
+        let buffer = TableBuffer::<Erst>::new(&bytes);
+        assert_eq!(buffer.get().entries().len(), 1);
+    }
+}
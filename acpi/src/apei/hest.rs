@@ -0,0 +1,562 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+    AcpiResult,
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, slice};
+
+/// Represents the Hardware Error Source Table (HEST), which describes the sources of hardware errors on the
+/// platform, and how each of them notifies the OS of an error. You can iterate over the error sources with
+/// [`Hest::error_sources`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Hest {
+    pub header: SdtHeader,
+    pub error_source_count: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid HEST.
+unsafe impl AcpiTable for Hest {
+    const SIGNATURE: Signature = Signature::HEST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Hest {
+    pub fn error_sources(&self) -> ErrorSourceIter<'_> {
+        ErrorSourceIter {
+            pointer: unsafe { (self as *const Hest as *const u8).add(mem::size_of::<Hest>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Hest>()),
+            remaining_sources: self.error_source_count,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ErrorSourceIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_sources: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum ErrorSource<'a> {
+    MachineCheckException(&'a MachineCheckException),
+    CorrectedMachineCheck(&'a CorrectedMachineCheck),
+    Nmi(&'a NmiErrorSource),
+    PcieRootPortAer(&'a PcieRootPortAer),
+    PcieDeviceAer(&'a PcieDeviceAer),
+    PcieBridgeAer(&'a PcieBridgeAer),
+    GenericHardwareErrorSource(&'a GenericHardwareErrorSource),
+    GenericHardwareErrorSourceV2(&'a GenericHardwareErrorSourceV2),
+    DeferredMachineCheck(&'a CorrectedMachineCheck),
+}
+
+impl<'a> Iterator for ErrorSourceIter<'a> {
+    type Item = ErrorSource<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_sources == 0 || self.remaining_length < mem::size_of::<u16>() {
+            return None;
+        }
+
+        /*
+         * Error source structures don't have a length field, so we have to work out the length of each one from
+         * its type (and, for the machine check sources, the number of banks that follow it). If we don't know how
+         * long a structure is, we can't find the next one, so we stop.
+         */
+        let source_type = unsafe { core::ptr::read_unaligned(self.pointer as *const u16) };
+        let (fixed_length, banks_offset) = match source_type {
+            0 => (mem::size_of::<MachineCheckException>(), Some(32)),
+            1 | 11 => (mem::size_of::<CorrectedMachineCheck>(), Some(44)),
+            2 => (mem::size_of::<NmiErrorSource>(), None),
+            6 => (mem::size_of::<PcieRootPortAer>(), None),
+            7 => (mem::size_of::<PcieDeviceAer>(), None),
+            8 => (mem::size_of::<PcieBridgeAer>(), None),
+            9 => (mem::size_of::<GenericHardwareErrorSource>(), None),
+            10 => (mem::size_of::<GenericHardwareErrorSourceV2>(), None),
+            _ => {
+                self.remaining_length = 0;
+                return None;
+            }
+        };
+        if fixed_length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let length = match banks_offset {
+            Some(offset) => {
+                let number_of_banks = unsafe { *self.pointer.add(offset) } as usize;
+                fixed_length + number_of_banks * mem::size_of::<MachineCheckBank>()
+            }
+            None => fixed_length,
+        };
+        if length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let source_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_sources -= 1;
+
+        macro_rules! construct_source {
+            ($(($value:pat => $variant:path as $type:ty)),*) => {
+                match source_type {
+                    $(
+                        $value => $variant(unsafe { &*(source_pointer as *const $type) }),
+                     )*
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        #[rustfmt::skip]
+        let source = construct_source!(
+            (0 => ErrorSource::MachineCheckException as MachineCheckException),
+            (1 => ErrorSource::CorrectedMachineCheck as CorrectedMachineCheck),
+            (2 => ErrorSource::Nmi as NmiErrorSource),
+            (6 => ErrorSource::PcieRootPortAer as PcieRootPortAer),
+            (7 => ErrorSource::PcieDeviceAer as PcieDeviceAer),
+            (8 => ErrorSource::PcieBridgeAer as PcieBridgeAer),
+            (9 => ErrorSource::GenericHardwareErrorSource as GenericHardwareErrorSource),
+            (10 => ErrorSource::GenericHardwareErrorSourceV2 as GenericHardwareErrorSourceV2),
+            (11 => ErrorSource::DeferredMachineCheck as CorrectedMachineCheck)
+        );
+
+        Some(source)
+    }
+}
+
+/// Describes the bank of machine check registers of an IA-32 machine check error source.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MachineCheckBank {
+    pub bank_number: u8,
+    /// If non-zero, the OS should clear the bank's status register when it initializes the bank.
+    pub clear_status_on_initialization: u8,
+    /// The format of the data in the status register: `0` for IA-32 MCA, `1` for Intel 64 MCA, and `2` for
+    /// AMD64 MCA.
+    pub status_data_format: u8,
+    _reserved: u8,
+    pub control_register_msr: u32,
+    pub control_init_data: u64,
+    pub status_register_msr: u32,
+    pub address_register_msr: u32,
+    pub misc_register_msr: u32,
+}
+
+/// Get the machine check banks that follow a machine check error source structure.
+fn banks_of<T>(source: &T, number_of_banks: u8) -> &[MachineCheckBank] {
+    unsafe {
+        let pointer = (source as *const T as *const u8).add(mem::size_of::<T>());
+        slice::from_raw_parts(pointer as *const MachineCheckBank, number_of_banks as usize)
+    }
+}
+
+/// An IA-32 Machine Check Exception error source (type 0), which reports errors with the machine check exception.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MachineCheckException {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The value the OS should write to the `IA32_MCG_CAP` MSR.
+    pub global_capability_init_data: u64,
+    /// The value the OS should write to the `IA32_MCG_CTL` MSR.
+    pub global_control_init_data: u64,
+    pub number_of_hardware_banks: u8,
+    _reserved1: [u8; 7],
+}
+
+impl MachineCheckException {
+    /// If true, the firmware handles errors from this source first, and then reports them to the OS.
+    pub fn is_firmware_first(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the firmware provides additional information about errors through a generic hardware error
+    /// source that refers to this source.
+    pub fn ghes_assist(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    pub fn banks(&self) -> &[MachineCheckBank] {
+        banks_of(self, self.number_of_hardware_banks)
+    }
+}
+
+/// An IA-32 Corrected Machine Check error source (type 1), or an IA-32 Deferred Machine Check error source (type
+/// 11). These have the same layout.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct CorrectedMachineCheck {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub notification: HardwareErrorNotification,
+    pub number_of_hardware_banks: u8,
+    _reserved1: [u8; 3],
+}
+
+impl CorrectedMachineCheck {
+    pub fn is_firmware_first(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    pub fn ghes_assist(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    pub fn banks(&self) -> &[MachineCheckBank] {
+        banks_of(self, self.number_of_hardware_banks)
+    }
+}
+
+/// An IA-32 architectural NMI error source (type 2), which reports errors with a non-maskable interrupt.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NmiErrorSource {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved: u32,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub max_raw_data_length: u32,
+}
+
+/// A PCI Express Root Port AER error source (type 6).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieRootPortAer {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The PCI segment of the device in bits `8..24`, and its bus in bits `0..8`.
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+    pub root_error_command: u32,
+}
+
+/// A PCI Express Device AER error source (type 7).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieDeviceAer {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+}
+
+/// A PCI Express/PCI-X Bridge AER error source (type 8).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieBridgeAer {
+    pub source_type: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+    pub secondary_uncorrectable_error_mask: u32,
+    pub secondary_uncorrectable_error_severity: u32,
+    pub secondary_advanced_error_capabilities_and_control: u32,
+}
+
+macro_rules! aer_flags {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                pub fn is_firmware_first(&self) -> bool {
+                    self.flags.get_bit(0)
+                }
+
+                /// If true, the settings of this structure apply to all devices of its type, and the `bus`,
+                /// `device`, and `function` fields should be ignored.
+                pub fn is_global(&self) -> bool {
+                    self.flags.get_bit(1)
+                }
+
+                pub fn is_enabled(&self) -> bool {
+                    self.enabled != 0
+                }
+            }
+        )*
+    };
+}
+
+aer_flags!(PcieRootPortAer, PcieDeviceAer, PcieBridgeAer);
+
+/// A Generic Hardware Error Source (type 9). The platform reports errors from this source by writing a
+/// [`GenericErrorStatusBlock`](super::GenericErrorStatusBlock) to memory, and notifying the OS as described by
+/// `notification`.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericHardwareErrorSource {
+    pub source_type: u16,
+    pub source_id: u16,
+    /// The source ID of the error source this one provides additional information for (see
+    /// `MachineCheckException::ghes_assist`), or `0xffff` if it doesn't.
+    pub related_source_id: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub max_raw_data_length: u32,
+    error_status_address: RawGenericAddress,
+    pub notification: HardwareErrorNotification,
+    pub error_status_block_length: u32,
+}
+
+impl GenericHardwareErrorSource {
+    /// The register that contains the physical address of the error status block. Note that this is the location
+    /// of the address of the block, not of the block itself.
+    pub fn error_status_address(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.error_status_address)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+}
+
+/// A Generic Hardware Error Source, version 2 (type 10). This is the same as a [`GenericHardwareErrorSource`], but
+/// the OS must acknowledge that it has read the error status block by writing to `read_ack_register`.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericHardwareErrorSourceV2 {
+    pub source_type: u16,
+    pub source_id: u16,
+    pub related_source_id: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub number_of_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub max_raw_data_length: u32,
+    error_status_address: RawGenericAddress,
+    pub notification: HardwareErrorNotification,
+    pub error_status_block_length: u32,
+    read_ack_register: RawGenericAddress,
+    /// The bits of the read ack register that must be preserved when acknowledging an error.
+    pub read_ack_preserve: u64,
+    /// The bits to set in the read ack register to acknowledge an error.
+    pub read_ack_write: u64,
+}
+
+impl GenericHardwareErrorSourceV2 {
+    pub fn error_status_address(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.error_status_address)
+    }
+
+    pub fn read_ack_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.read_ack_register)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NotificationType {
+    Polled,
+    ExternalInterrupt,
+    LocalInterrupt,
+    Sci,
+    Nmi,
+    Cmci,
+    Mce,
+    GpioSignal,
+    ArmSea,
+    ArmSei,
+    Gsiv,
+    SoftwareDelegatedException,
+    Reserved(u8),
+}
+
+/// Describes how an error source notifies the OS that an error has occurred.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HardwareErrorNotification {
+    notification_type: u8,
+    pub length: u8,
+    /// Which of the other fields the OS is allowed to change.
+    pub configuration_write_enable: u16,
+    /// For polled sources, the interval, in milliseconds, at which the OS should poll the source.
+    pub poll_interval: u32,
+    /// The interrupt vector (e.g. the GSIV for external interrupts) used by the notification.
+    pub vector: u32,
+    pub switch_to_polling_threshold_value: u32,
+    pub switch_to_polling_threshold_window: u32,
+    pub error_threshold_value: u32,
+    pub error_threshold_window: u32,
+}
+
+impl HardwareErrorNotification {
+    pub fn notification_type(&self) -> NotificationType {
+        match self.notification_type {
+            0 => NotificationType::Polled,
+            1 => NotificationType::ExternalInterrupt,
+            2 => NotificationType::LocalInterrupt,
+            3 => NotificationType::Sci,
+            4 => NotificationType::Nmi,
+            5 => NotificationType::Cmci,
+            6 => NotificationType::Mce,
+            7 => NotificationType::GpioSignal,
+            8 => NotificationType::ArmSea,
+            9 => NotificationType::ArmSei,
+            10 => NotificationType::Gsiv,
+            11 => NotificationType::SoftwareDelegatedException,
+            other => NotificationType::Reserved(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn bank(bank_number: u8) -> MachineCheckBank {
+        MachineCheckBank {
+            bank_number,
+            status_register_msr: 0x401 + 4 * bank_number as u32,
+            ..unsafe { zeroed() }
+        }
+    }
+
+    fn hest(error_source_count: u32, sources: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend(error_source_count.to_le_bytes());
+        for source in sources {
+            body.extend(source);
+        }
+        table(b"HEST", 1, &body)
+    }
+
+    #[test]
+    fn test_error_sources() {
+        let mut mce = bytes_of(&MachineCheckException {
+            source_type: 0,
+            source_id: 1,
+            flags: 0b1,
+            enabled: 1,
+            number_of_hardware_banks: 2,
+            ..unsafe { zeroed() }
+        });
+        mce.extend(bytes_of(&bank(0)));
+        mce.extend(bytes_of(&bank(1)));
+        let nmi = bytes_of(&NmiErrorSource { source_type: 2, source_id: 2, ..unsafe { zeroed() } });
+        let mut ghes =
+            GenericHardwareErrorSourceV2 { source_type: 10, source_id: 3, enabled: 1, ..unsafe { zeroed() } };
+        ghes.error_status_address = RawGenericAddress {
+            address_space: 0,
+            bit_width: 64,
+            bit_offset: 0,
+            access_size: 4,
+            address: 0x7f00_0000,
+        };
+        ghes.notification.notification_type = 3;
+        let ghes = bytes_of(&ghes);
+
+        let buffer = TableBuffer::<Hest>::new(&hest(3, &[mce, nmi, ghes]));
+        let hest = buffer.get();
+        assert!(hest.validate().is_ok());
+
+        let sources: Vec<_> = hest.error_sources().collect();
+        assert_eq!(sources.len(), 3);
+        match sources[0] {
+            ErrorSource::MachineCheckException(mce) => {
+                assert!(mce.is_firmware_first());
+                assert!(mce.is_enabled());
+                assert_eq!(mce.banks().len(), 2);
+                assert_eq!({ mce.banks()[1].status_register_msr }, 0x405);
+            }
+            ref other => panic!("unexpected error source: {:?}", other),
+        }
+        assert!(matches!(sources[1], ErrorSource::Nmi(nmi) if { nmi.source_id } == 2));
+        match sources[2] {
+            ErrorSource::GenericHardwareErrorSourceV2(ghes) => {
+                assert_eq!(ghes.error_status_address().unwrap().address, 0x7f00_0000);
+                assert_eq!(ghes.notification.notification_type(), NotificationType::Sci);
+            }
+            ref other => panic!("unexpected error source: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_error_sources() {
+        // The sources after one of an unknown type can't be found, because its length isn't known
+        let unknown = 3u16.to_le_bytes().to_vec();
+        let nmi = bytes_of(&NmiErrorSource { source_type: 2, ..unsafe { zeroed() } });
+        let buffer = TableBuffer::<Hest>::new(&hest(2, &[unknown, nmi.clone()]));
+        assert_eq!(buffer.get().error_sources().count(), 0);
+
+        // A machine check source whose banks don't fit in the table
+        let mce = bytes_of(&MachineCheckException { number_of_hardware_banks: 1, ..unsafe { zeroed() } });
+        let buffer = TableBuffer::<Hest>::new(&hest(2, &[nmi.clone(), mce]));
+        assert_eq!(buffer.get().error_sources().count(), 1);
+
+        // Only `error_source_count` sources are read
+        let buffer = TableBuffer::<Hest>::new(&hest(1, &[nmi.clone(), nmi]));
+        assert_eq!(buffer.get().error_sources().count(), 1);
+    }
+}
@@ -0,0 +1,433 @@
+//! The ACPI Platform Error Interfaces (APEI) are a group of tables that describe how the platform reports
+//! hardware errors to the OS, and how the OS can interact with the platform's error handling:
+//!    - The Hardware Error Source Table ([`Hest`]) describes the sources of hardware errors, and how each of them
+//!      notifies the OS of an error.
+//!    - The Boot Error Record Table ([`Bert`]) points to the errors that occurred during the previous boot.
+//!    - The Error Record Serialization Table ([`Erst`]) describes how to save error records to persistent storage.
+//!    - The Error Injection Table ([`Einj`]) describes how to inject errors, to test error handling.
+//!
+//! The ERST and EINJ describe their operations as lists of [`InstructionEntry`]s, which share the same format.
+//! The BERT and generic error sources in the HEST report errors with a [`GenericErrorStatusBlock`].
+
+pub mod bert;
+pub mod einj;
+pub mod erst;
+pub mod hest;
+
+pub use bert::Bert;
+pub use einj::Einj;
+pub use erst::Erst;
+pub use hest::Hest;
+
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    AcpiResult,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// The instructions that can be used by the ERST and EINJ. The EINJ only uses the first five.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    /// Read the register, and return the masked value.
+    ReadRegister,
+    /// Read the register, and return whether the masked value is equal to `value`.
+    ReadRegisterValue,
+    /// Write the action's parameter to the register.
+    WriteRegister,
+    /// Write `value` to the register.
+    WriteRegisterValue,
+    Noop,
+    LoadVar1,
+    LoadVar2,
+    StoreVar1,
+    Add,
+    Subtract,
+    AddValue,
+    SubtractValue,
+    Stall,
+    StallWhileTrue,
+    SkipNextInstructionIfTrue,
+    Goto,
+    SetSrcAddressWithValue,
+    SetDstAddressWithValue,
+    MoveData,
+    Reserved(u8),
+}
+
+impl From<u8> for Instruction {
+    fn from(instruction: u8) -> Self {
+        match instruction {
+            0x00 => Instruction::ReadRegister,
+            0x01 => Instruction::ReadRegisterValue,
+            0x02 => Instruction::WriteRegister,
+            0x03 => Instruction::WriteRegisterValue,
+            0x04 => Instruction::Noop,
+            0x05 => Instruction::LoadVar1,
+            0x06 => Instruction::LoadVar2,
+            0x07 => Instruction::StoreVar1,
+            0x08 => Instruction::Add,
+            0x09 => Instruction::Subtract,
+            0x0a => Instruction::AddValue,
+            0x0b => Instruction::SubtractValue,
+            0x0c => Instruction::Stall,
+            0x0d => Instruction::StallWhileTrue,
+            0x0e => Instruction::SkipNextInstructionIfTrue,
+            0x0f => Instruction::Goto,
+            0x10 => Instruction::SetSrcAddressWithValue,
+            0x11 => Instruction::SetDstAddressWithValue,
+            0x12 => Instruction::MoveData,
+            other => Instruction::Reserved(other),
+        }
+    }
+}
+
+/// A single instruction of an ERST serialization action or EINJ injection action. The meaning of `action`
+/// depends on the table the entry came from.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct InstructionEntry {
+    pub action: u8,
+    instruction: u8,
+    pub flags: u8,
+    _reserved: u8,
+    register_region: RawGenericAddress,
+    pub value: u64,
+    pub mask: u64,
+}
+
+impl InstructionEntry {
+    pub fn instruction(&self) -> Instruction {
+        Instruction::from(self.instruction)
+    }
+
+    /// If true, write instructions must preserve the bits of the register outside of `mask`.
+    pub fn preserve_register(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    pub fn register_region(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.register_region)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorSeverity {
+    Recoverable,
+    Fatal,
+    Corrected,
+    None,
+    Reserved(u32),
+}
+
+impl From<u32> for ErrorSeverity {
+    fn from(severity: u32) -> Self {
+        match severity {
+            0 => ErrorSeverity::Recoverable,
+            1 => ErrorSeverity::Fatal,
+            2 => ErrorSeverity::Corrected,
+            3 => ErrorSeverity::None,
+            other => ErrorSeverity::Reserved(other),
+        }
+    }
+}
+
+/// Reports the errors recorded by a generic error source (or, in the BERT, the errors of the previous boot). It
+/// is followed in memory by `data_length` bytes of [`GenericErrorDataEntry`]s, which can be iterated with
+/// [`GenericErrorStatusBlock::entries`], and then by the raw error data.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericErrorStatusBlock {
+    pub block_status: u32,
+    /// The offset of the raw error data from the start of this block.
+    pub raw_data_offset: u32,
+    pub raw_data_length: u32,
+    /// The length, in bytes, of the generic error data entries.
+    pub data_length: u32,
+    error_severity: u32,
+}
+
+impl GenericErrorStatusBlock {
+    pub fn uncorrectable_error_valid(&self) -> bool {
+        { self.block_status }.get_bit(0)
+    }
+
+    pub fn correctable_error_valid(&self) -> bool {
+        { self.block_status }.get_bit(1)
+    }
+
+    pub fn multiple_uncorrectable_errors(&self) -> bool {
+        { self.block_status }.get_bit(2)
+    }
+
+    pub fn multiple_correctable_errors(&self) -> bool {
+        { self.block_status }.get_bit(3)
+    }
+
+    pub fn error_data_entry_count(&self) -> u32 {
+        { self.block_status }.get_bits(4..14)
+    }
+
+    /// If false, the block doesn't currently contain any errors.
+    pub fn has_errors(&self) -> bool {
+        self.block_status != 0
+    }
+
+    pub fn error_severity(&self) -> ErrorSeverity {
+        ErrorSeverity::from(self.error_severity)
+    }
+
+    /// Iterate over the error data entries in the block.
+    ///
+    /// ### Safety
+    /// The `data_length` bytes after this block must be mapped, and must not be longer than the region the block
+    /// was found in. Use [`GenericErrorStatusBlock::entries_within`] if the size of the region is known.
+    pub unsafe fn entries(&self) -> GenericErrorDataEntryIter<'_> {
+        GenericErrorDataEntryIter {
+            pointer: unsafe { (self as *const GenericErrorStatusBlock as *const u8).add(mem::size_of::<Self>()) },
+            remaining_length: self.data_length as usize,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over the error data entries in the block, where `region_length` is the total number of bytes
+    /// mapped at the start of the block. Entries that don't fit within the region are not returned.
+    pub fn entries_within(&self, region_length: usize) -> GenericErrorDataEntryIter<'_> {
+        let available = region_length.saturating_sub(mem::size_of::<Self>());
+        let mut iter = unsafe { self.entries() };
+        iter.remaining_length = usize::min(iter.remaining_length, available);
+        iter
+    }
+}
+
+#[derive(Debug)]
+pub struct GenericErrorDataEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for GenericErrorDataEntryIter<'a> {
+    type Item = &'a GenericErrorDataEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<GenericErrorDataEntry>() {
+            return None;
+        }
+
+        let entry = unsafe { &*(self.pointer as *const GenericErrorDataEntry) };
+        let length = entry.length();
+        if length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        Some(entry)
+    }
+}
+
+/// The type of the error section in a [`GenericErrorDataEntry`], decoded from its section type GUID. These are
+/// the section types defined by the UEFI Common Platform Error Record (CPER) format.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SectionType {
+    ProcessorGeneric,
+    ProcessorIa32X64,
+    ProcessorArm,
+    PlatformMemory,
+    PciExpress,
+    /// A section type we don't recognise. Contains the GUID, in the byte order it appears in the table.
+    Unknown([u8; 16]),
+}
+
+impl SectionType {
+    /*
+     * These are the GUIDs in the mixed-endian byte order that they appear in the table, so they can be compared
+     * directly.
+     */
+    const PROCESSOR_GENERIC: [u8; 16] =
+        [0xad, 0xcc, 0x76, 0x98, 0xb4, 0x47, 0xdb, 0x4b, 0xb6, 0x5e, 0x16, 0xf1, 0x93, 0xc4, 0xf3, 0xdb];
+    const PROCESSOR_IA32_X64: [u8; 16] =
+        [0xb0, 0xa0, 0x3e, 0xdc, 0x44, 0xa1, 0x97, 0x47, 0xb9, 0x5b, 0x53, 0xfa, 0x24, 0x2b, 0x6e, 0x1d];
+    const PROCESSOR_ARM: [u8; 16] =
+        [0x16, 0x3d, 0x9e, 0xe1, 0x11, 0xbc, 0xe4, 0x11, 0x9c, 0xaa, 0xc2, 0x05, 0x1d, 0x5d, 0x46, 0xb0];
+    const PLATFORM_MEMORY: [u8; 16] =
+        [0x14, 0x11, 0xbc, 0xa5, 0x64, 0x6f, 0xde, 0x4e, 0xb8, 0x63, 0x3e, 0x83, 0xed, 0x7c, 0x83, 0xb1];
+    const PCI_EXPRESS: [u8; 16] =
+        [0x54, 0xe9, 0x95, 0xd9, 0xc1, 0xbb, 0x0f, 0x43, 0xad, 0x91, 0xb4, 0x4d, 0xcb, 0x3c, 0x6f, 0x35];
+
+    fn from_guid(guid: [u8; 16]) -> SectionType {
+        match guid {
+            Self::PROCESSOR_GENERIC => SectionType::ProcessorGeneric,
+            Self::PROCESSOR_IA32_X64 => SectionType::ProcessorIa32X64,
+            Self::PROCESSOR_ARM => SectionType::ProcessorArm,
+            Self::PLATFORM_MEMORY => SectionType::PlatformMemory,
+            Self::PCI_EXPRESS => SectionType::PciExpress,
+            other => SectionType::Unknown(other),
+        }
+    }
+}
+
+/// Describes a single error, as a section in the CPER format. The section's data follows this entry in memory,
+/// after the timestamp if the entry has one.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericErrorDataEntry {
+    section_type: [u8; 16],
+    error_severity: u32,
+    pub revision: u16,
+    pub validation_bits: u8,
+    pub flags: u8,
+    pub error_data_length: u32,
+    /// Only valid if `fru_id_valid` is set.
+    pub fru_id: [u8; 16],
+    fru_text: [u8; 20],
+}
+
+impl GenericErrorDataEntry {
+    pub fn section_type(&self) -> SectionType {
+        SectionType::from_guid(self.section_type)
+    }
+
+    pub fn error_severity(&self) -> ErrorSeverity {
+        ErrorSeverity::from(self.error_severity)
+    }
+
+    pub fn fru_id_valid(&self) -> bool {
+        self.validation_bits.get_bit(0)
+    }
+
+    pub fn fru_text_valid(&self) -> bool {
+        self.validation_bits.get_bit(1)
+    }
+
+    pub fn timestamp_valid(&self) -> bool {
+        self.validation_bits.get_bit(2)
+    }
+
+    /// A human-readable name for the field-replaceable unit that reported the error. Returns `None` if the FRU
+    /// text isn't valid, or isn't valid ASCII.
+    pub fn fru_text(&self) -> Option<&str> {
+        if !self.fru_text_valid() {
+            return None;
+        }
+
+        let bytes = match self.fru_text.iter().position(|&byte| byte == 0) {
+            Some(nul) => &self.fru_text[..nul],
+            None => &self.fru_text[..],
+        };
+        core::str::from_utf8(bytes).ok()
+    }
+
+    /// The time at which the error was recorded, in the CPER timestamp format. Only present in entries of revision
+    /// `0x300` and later.
+    pub fn timestamp(&self) -> Option<u64> {
+        if !self.has_timestamp() || !self.timestamp_valid() {
+            return None;
+        }
+
+        Some(unsafe {
+            core::ptr::read_unaligned(
+                (self as *const GenericErrorDataEntry as *const u8).add(mem::size_of::<Self>()) as *const u64,
+            )
+        })
+    }
+
+    /// The error section's data. The format of the data depends on [`GenericErrorDataEntry::section_type`].
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                (self as *const GenericErrorDataEntry as *const u8).add(self.header_length()),
+                self.error_data_length as usize,
+            )
+        }
+    }
+
+    fn has_timestamp(&self) -> bool {
+        self.revision >= 0x300
+    }
+
+    fn header_length(&self) -> usize {
+        mem::size_of::<Self>() + if self.has_timestamp() { mem::size_of::<u64>() } else { 0 }
+    }
+
+    /// The total length of this entry, including its data.
+    pub fn length(&self) -> usize {
+        self.header_length() + self.error_data_length as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, TableBuffer};
+    use std::vec::Vec;
+
+    fn data_entry(revision: u16, data: &[u8]) -> Vec<u8> {
+        let mut fru_text = [0; 20];
+        fru_text[..5].copy_from_slice(b"DIMM0");
+        let mut bytes = bytes_of(&GenericErrorDataEntry {
+            section_type: SectionType::PLATFORM_MEMORY,
+            error_severity: 2,
+            revision,
+            validation_bits: 0b110,
+            flags: 0,
+            error_data_length: data.len() as u32,
+            fru_id: [0; 16],
+            fru_text,
+        });
+        if revision >= 0x300 {
+            bytes.extend(0x2026_1014u64.to_le_bytes());
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    fn status_block(entries: &[Vec<u8>]) -> Vec<u8> {
+        let data_length: usize = entries.iter().map(Vec::len).sum();
+        let mut bytes = bytes_of(&GenericErrorStatusBlock {
+            block_status: 0b10 | ((entries.len() as u32) << 4),
+            raw_data_offset: 0,
+            raw_data_length: 0,
+            data_length: data_length as u32,
+            error_severity: 2,
+        });
+        for entry in entries {
+            bytes.extend(entry);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_status_block() {
+        let bytes = status_block(&[data_entry(0x300, &[1, 2, 3, 4]), data_entry(0x201, &[5; 8])]);
+        let buffer = TableBuffer::<GenericErrorStatusBlock>::new(&bytes);
+        let block = buffer.get();
+        assert!(block.has_errors());
+        assert!(block.correctable_error_valid());
+        assert!(!block.uncorrectable_error_valid());
+        assert_eq!(block.error_data_entry_count(), 2);
+        assert_eq!(block.error_severity(), ErrorSeverity::Corrected);
+
+        let entries: Vec<_> = block.entries_within(bytes.len()).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].section_type(), SectionType::PlatformMemory);
+        assert_eq!(entries[0].error_severity(), ErrorSeverity::Corrected);
+        assert_eq!(entries[0].fru_text(), Some("DIMM0"));
+        assert_eq!(entries[0].timestamp(), Some(0x2026_1014));
+        assert_eq!(entries[0].data(), &[1, 2, 3, 4]);
+        assert_eq!(entries[1].timestamp(), None);
+        assert_eq!(entries[1].data(), &[5; 8]);
+    }
+
+    #[test]
+    fn test_truncated_status_block() {
+        // Entries that run past the end of the region aren't returned
+        let bytes = status_block(&[data_entry(0x201, &[1; 4]), data_entry(0x201, &[2; 4])]);
+        let buffer = TableBuffer::<GenericErrorStatusBlock>::new(&bytes);
+        assert_eq!(buffer.get().entries_within(bytes.len() - 1).count(), 1);
+        assert_eq!(buffer.get().entries_within(mem::size_of::<GenericErrorStatusBlock>() + 4).count(), 0);
+    }
+}
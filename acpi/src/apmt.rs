@@ -0,0 +1,196 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// Represents the ARM Performance Monitoring Unit Table (APMT), which describes the system PMUs (those that
+/// follow the CoreSight PMU architecture) of an ARM platform: where their MMIO pages are, which component they
+/// monitor, and their overflow interrupt. The layout of this table is defined by ARM (DEN0117).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Apmt {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid APMT.
+unsafe impl AcpiTable for Apmt {
+    const SIGNATURE: Signature = Signature::APMT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Apmt {
+    pub fn nodes(&self) -> ApmtNodeIter<'_> {
+        ApmtNodeIter {
+            pointer: unsafe { (self as *const Apmt as *const u8).add(mem::size_of::<Apmt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Apmt>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ApmtNodeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for ApmtNodeIter<'a> {
+    type Item = &'a ApmtNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<ApmtNode>() {
+            return None;
+        }
+
+        let node = unsafe { &*(self.pointer as *const ApmtNode) };
+        let length = node.length as usize;
+        if length < mem::size_of::<ApmtNode>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(node)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PmuNodeType {
+    /// A memory controller, identified by the SRAT proximity domain of its memory in `node_instance_primary`.
+    MemoryController,
+    /// An SMMU, identified by the offset of its node in the IORT in `node_instance_primary`.
+    Smmu,
+    /// A PCIe root complex, identified by its segment in `node_instance_primary`, and its root port in
+    /// `node_instance_secondary`.
+    PcieRootComplex,
+    /// A device in the namespace, identified by its `_HID` in `node_instance_primary`, and its `_UID` in
+    /// `node_instance_secondary`.
+    AcpiDevice,
+    /// A CPU cache, identified by the offset of its cache type structure in the PPTT in `node_instance_primary`.
+    CpuCache,
+    Reserved(u8),
+}
+
+/// Describes a single PMU.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ApmtNode {
+    pub length: u16,
+    pub flags: u8,
+    node_type: u8,
+    pub identifier: u32,
+    pub node_instance_primary: u64,
+    pub node_instance_secondary: u32,
+    /// The physical address of the PMU's first page of registers.
+    pub base_address0: u64,
+    base_address1: u64,
+    /// The GSIV of the PMU's overflow interrupt, or `0` if it doesn't have one.
+    pub overflow_interrupt: u32,
+    _reserved: u32,
+    pub overflow_interrupt_flags: u32,
+    /// The processor (or processor container) the PMU's overflow interrupt is affine to. This is a processor UID
+    /// or a processor container UID, depending on [`ApmtNode::is_affine_to_processor_container`].
+    pub processor_affinity: u32,
+    /// The value of the PMU's `PMIIDR` register.
+    pub implementation_id: u32,
+}
+
+impl ApmtNode {
+    pub fn node_type(&self) -> PmuNodeType {
+        match self.node_type {
+            0 => PmuNodeType::MemoryController,
+            1 => PmuNodeType::Smmu,
+            2 => PmuNodeType::PcieRootComplex,
+            3 => PmuNodeType::AcpiDevice,
+            4 => PmuNodeType::CpuCache,
+            other => PmuNodeType::Reserved(other),
+        }
+    }
+
+    /// The physical address of the PMU's second page of registers, if it implements the dual-page extension.
+    pub fn base_address1(&self) -> Option<u64> {
+        if self.flags.get_bit(0) {
+            Some(self.base_address1)
+        } else {
+            None
+        }
+    }
+
+    /// If true, `processor_affinity` is the UID of a processor container. Otherwise, it is the UID of a processor.
+    pub fn is_affine_to_processor_container(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+
+    /// If true, the PMU's 64-bit registers can be accessed with single 64-bit accesses.
+    pub fn supports_64bit_atomic(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+
+    pub fn overflow_interrupt_is_edge_triggered(&self) -> bool {
+        { self.overflow_interrupt_flags }.get_bit(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn node(identifier: u32, node_type: u8, flags: u8) -> ApmtNode {
+        ApmtNode {
+            length: mem::size_of::<ApmtNode>() as u16,
+            flags,
+            node_type,
+            identifier,
+            base_address0: 0x1000_0000 + identifier as u64 * 0x2000,
+            base_address1: 0x1000_1000 + identifier as u64 * 0x2000,
+            overflow_interrupt: 200 + identifier,
+            overflow_interrupt_flags: 0b1,
+            ..unsafe { zeroed() }
+        }
+    }
+
+    #[test]
+    fn test_nodes() {
+        let mut body = Vec::new();
+        body.extend(bytes_of(&node(0, 0, 0b001)));
+        body.extend(bytes_of(&node(1, 4, 0b110)));
+        body.extend(bytes_of(&node(2, 9, 0)));
+
+        let buffer = TableBuffer::<Apmt>::new(&table(b"APMT", 0, &body));
+        let apmt = buffer.get();
+        assert!(apmt.validate().is_ok());
+
+        let nodes: Vec<_> = apmt.nodes().collect();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].node_type(), PmuNodeType::MemoryController);
+        assert_eq!(nodes[0].base_address1(), Some(0x1000_1000));
+        assert!(nodes[0].overflow_interrupt_is_edge_triggered());
+        assert_eq!(nodes[1].node_type(), PmuNodeType::CpuCache);
+        assert_eq!(nodes[1].base_address1(), None);
+        assert!(nodes[1].is_affine_to_processor_container());
+        assert!(nodes[1].supports_64bit_atomic());
+        assert_eq!(nodes[2].node_type(), PmuNodeType::Reserved(9));
+    }
+
+    #[test]
+    fn test_truncated_node() {
+        let mut body = bytes_of(&node(0, 0, 0));
+        let mut short = node(1, 0, 0);
+        short.length = 16;
+        body.extend(bytes_of(&short));
+        let buffer = TableBuffer::<Apmt>::new(&table(b"APMT", 0, &body));
+        assert_eq!(buffer.get().nodes().count(), 1);
+
+        let buffer = TableBuffer::<Apmt>::new(&table(b"APMT", 0, &body[..body.len() / 2 - 1]));
+        assert_eq!(buffer.get().nodes().count(), 0);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{device, AcpiError, AcpiResult};
+use alloc::{string::String, vec::Vec};
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+use bit_field::BitField;
+
+/// The value batteries report for a field that is unknown.
+const BATTERY_VALUE_UNKNOWN: u64 = 0xffff_ffff;
+
+#[derive(Debug)]
+pub enum BatteryError {
+    /// The `_BIF`, `_BIX`, or `_BST` object of the battery isn't a package of the expected form.
+    InvalidObject(&'static str),
+}
+
+/// The unit the battery's capacities (and rates) are reported in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUnit {
+    /// Capacities are in mWh, and rates in mW.
+    MilliWatts,
+    /// Capacities are in mAh, and rates in mA.
+    MilliAmps,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BatteryTechnology {
+    /// A primary (non-rechargeable) battery.
+    Primary,
+    /// A secondary (rechargeable) battery.
+    Secondary,
+}
+
+/// The static information about a battery, from its `_BIX` object (or its `_BIF` object, for firmware that
+/// doesn't implement `_BIX`). Values that the battery reports as unknown are `None`.
+#[derive(Clone, Debug)]
+pub struct BatteryInfo {
+    pub power_unit: PowerUnit,
+    pub design_capacity: Option<u32>,
+    pub last_full_charge_capacity: Option<u32>,
+    pub technology: BatteryTechnology,
+    /// In mV.
+    pub design_voltage: Option<u32>,
+    /// The capacity at which the OS should warn the user that the battery is low.
+    pub design_capacity_of_warning: u32,
+    /// The capacity at which the OS should put the system to sleep.
+    pub design_capacity_of_low: u32,
+    /// The number of charge/discharge cycles the battery has experienced. Only provided by `_BIX`.
+    pub cycle_count: Option<u32>,
+    /// The accuracy of the battery's measurements, in thousandths of a percent. Only provided by `_BIX`.
+    pub measurement_accuracy: Option<u32>,
+    /// The granularity of the capacity between the low and warning capacities.
+    pub capacity_granularity_1: u32,
+    /// The granularity of the capacity between the warning and full capacities.
+    pub capacity_granularity_2: u32,
+    pub model_number: String,
+    pub serial_number: String,
+    pub battery_type: String,
+    pub oem_information: String,
+}
+
+/// The current state of a battery, from its `_BST` object. Values that the battery reports as unknown are `None`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BatteryStatus {
+    pub discharging: bool,
+    pub charging: bool,
+    /// The battery's capacity is critically low.
+    pub critical: bool,
+    /// The battery is being limited to a partial charge.
+    pub charge_limiting: bool,
+    /// The rate the battery is charging or discharging at, in the battery's [`PowerUnit`].
+    pub present_rate: Option<u32>,
+    pub remaining_capacity: Option<u32>,
+    /// In mV.
+    pub present_voltage: Option<u32>,
+}
+
+/// What changed about a battery, in response to a `Notify` on it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BatteryNotification {
+    /// `Notify(battery, 0x80)`: the battery's status has changed, and has been re-read.
+    StatusChanged,
+    /// `Notify(battery, 0x81)`: the battery's static information has changed (e.g. the battery has been
+    /// replaced), and has been re-read.
+    InfoChanged,
+    /// The notification value isn't one defined for batteries.
+    Other(u64),
+}
+
+/// A Control Method Battery: a battery described by a `PNP0C0A` device in the namespace, which is managed through
+/// its `_BIX`/`_BIF` and `_BST` objects.
+#[derive(Clone, Debug)]
+pub struct Battery {
+    path: AmlName,
+    info: Option<BatteryInfo>,
+    status: Option<BatteryStatus>,
+}
+
+impl Battery {
+    /// Find the batteries in the namespace (devices with a `_HID` of `PNP0C0A`), and read their information and
+    /// status.
+    pub fn enumerate(context: &mut AmlContext) -> AcpiResult<Vec<Battery>> {
+        device::find_devices_with_hid(context, "PNP0C0A")?
+            .into_iter()
+            .map(|path| {
+                let mut battery = Battery { path, info: None, status: None };
+                battery.refresh(context)?;
+                Ok(battery)
+            })
+            .collect()
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// The battery's information, or `None` if the battery is not present (e.g. it has been removed from its
+    /// slot).
+    pub fn info(&self) -> Option<&BatteryInfo> {
+        self.info.as_ref()
+    }
+
+    /// The battery's status when it was last read, or `None` if the battery is not present.
+    pub fn status(&self) -> Option<BatteryStatus> {
+        self.status
+    }
+
+    /// Check whether the battery is present, by evaluating its `_STA`.
+    pub fn is_present(&self, context: &mut AmlContext) -> AcpiResult<bool> {
+        match device::evaluate_optional(context, &self.path, "_STA", Args::EMPTY)? {
+            Some(status) => Ok(status.as_status().map_err(AcpiError::Aml)?.battery_present),
+            None => Ok(true),
+        }
+    }
+
+    /// Re-read the battery's information and status.
+    pub fn refresh(&mut self, context: &mut AmlContext) -> AcpiResult<()> {
+        if self.is_present(context)? {
+            self.info = Some(self.read_info(context)?);
+            self.status = Some(self.read_status(context)?);
+        } else {
+            self.info = None;
+            self.status = None;
+        }
+        Ok(())
+    }
+
+    /// Re-read the battery's status, by evaluating `_BST`. Returns `None` if the battery is not present.
+    pub fn refresh_status(&mut self, context: &mut AmlContext) -> AcpiResult<Option<BatteryStatus>> {
+        self.status = if self.info.is_some() { Some(self.read_status(context)?) } else { None };
+        Ok(self.status)
+    }
+
+    /// Handle a `Notify` on the battery, by re-reading whatever the notification says has changed.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, value: u64) -> AcpiResult<BatteryNotification> {
+        match value {
+            0x80 => {
+                self.refresh_status(context)?;
+                Ok(BatteryNotification::StatusChanged)
+            }
+            0x81 => {
+                self.refresh(context)?;
+                Ok(BatteryNotification::InfoChanged)
+            }
+            other => Ok(BatteryNotification::Other(other)),
+        }
+    }
+
+    fn read_info(&self, context: &mut AmlContext) -> AcpiResult<BatteryInfo> {
+        if let Some(bix) = device::evaluate_optional(context, &self.path, "_BIX", Args::EMPTY)? {
+            return parse_bix(context, &bix);
+        }
+
+        let bif = device::evaluate(context, &self.path, "_BIF", Args::EMPTY)?;
+        parse_bif(context, &bif)
+    }
+
+    fn read_status(&self, context: &mut AmlContext) -> AcpiResult<BatteryStatus> {
+        let bst = device::evaluate(context, &self.path, "_BST", Args::EMPTY)?;
+        let elements = package_elements(&bst, 4, "_BST")?;
+        let state = integer(context, &elements[0])?;
+
+        Ok(BatteryStatus {
+            discharging: state.get_bit(0),
+            charging: state.get_bit(1),
+            critical: state.get_bit(2),
+            charge_limiting: state.get_bit(3),
+            present_rate: optional_integer(context, &elements[1])?,
+            remaining_capacity: optional_integer(context, &elements[2])?,
+            present_voltage: optional_integer(context, &elements[3])?,
+        })
+    }
+}
+
+fn parse_bif(context: &AmlContext, bif: &AmlValue) -> AcpiResult<BatteryInfo> {
+    /*
+     * _BIF := Package {
+     *     Power Unit, Design Capacity, Last Full Charge Capacity, Battery Technology, Design Voltage,
+     *     Design Capacity of Warning, Design Capacity of Low, Battery Capacity Granularity 1,
+     *     Battery Capacity Granularity 2, Model Number, Serial Number, Battery Type, OEM Information
+     * }
+     */
+    let e = package_elements(bif, 13, "_BIF")?;
+
+    Ok(BatteryInfo {
+        power_unit: power_unit(context, &e[0])?,
+        design_capacity: optional_integer(context, &e[1])?,
+        last_full_charge_capacity: optional_integer(context, &e[2])?,
+        technology: technology(context, &e[3])?,
+        design_voltage: optional_integer(context, &e[4])?,
+        design_capacity_of_warning: integer(context, &e[5])? as u32,
+        design_capacity_of_low: integer(context, &e[6])? as u32,
+        cycle_count: None,
+        measurement_accuracy: None,
+        capacity_granularity_1: integer(context, &e[7])? as u32,
+        capacity_granularity_2: integer(context, &e[8])? as u32,
+        model_number: string(context, &e[9]),
+        serial_number: string(context, &e[10]),
+        battery_type: string(context, &e[11]),
+        oem_information: string(context, &e[12]),
+    })
+}
+
+fn parse_bix(context: &AmlContext, bix: &AmlValue) -> AcpiResult<BatteryInfo> {
+    /*
+     * _BIX := Package {
+     *     Revision, Power Unit, Design Capacity, Last Full Charge Capacity, Battery Technology, Design Voltage,
+     *     Design Capacity of Warning, Design Capacity of Low, Cycle Count, Measurement Accuracy,
+     *     Max Sampling Time, Min Sampling Time, Max Averaging Interval, Min Averaging Interval,
+     *     Battery Capacity Granularity 1, Battery Capacity Granularity 2, Model Number, Serial Number,
+     *     Battery Type, OEM Information, [Battery Swapping Capability (revision 1)]
+     * }
+     */
+    let e = package_elements(bix, 20, "_BIX")?;
+
+    Ok(BatteryInfo {
+        power_unit: power_unit(context, &e[1])?,
+        design_capacity: optional_integer(context, &e[2])?,
+        last_full_charge_capacity: optional_integer(context, &e[3])?,
+        technology: technology(context, &e[4])?,
+        design_voltage: optional_integer(context, &e[5])?,
+        design_capacity_of_warning: integer(context, &e[6])? as u32,
+        design_capacity_of_low: integer(context, &e[7])? as u32,
+        cycle_count: optional_integer(context, &e[8])?,
+        measurement_accuracy: Some(integer(context, &e[9])? as u32),
+        capacity_granularity_1: integer(context, &e[14])? as u32,
+        capacity_granularity_2: integer(context, &e[15])? as u32,
+        model_number: string(context, &e[16]),
+        serial_number: string(context, &e[17]),
+        battery_type: string(context, &e[18]),
+        oem_information: string(context, &e[19]),
+    })
+}
+
+fn package_elements<'a>(
+    value: &'a AmlValue,
+    min_length: usize,
+    object: &'static str,
+) -> AcpiResult<&'a [AmlValue]> {
+    match value {
+        AmlValue::Package(elements) if elements.len() >= min_length => Ok(elements),
+        _ => Err(AcpiError::Battery(BatteryError::InvalidObject(object))),
+    }
+}
+
+fn integer(context: &AmlContext, value: &AmlValue) -> AcpiResult<u64> {
+    value.as_integer(context).map_err(AcpiError::Aml)
+}
+
+fn optional_integer(context: &AmlContext, value: &AmlValue) -> AcpiResult<Option<u32>> {
+    match integer(context, value)? {
+        BATTERY_VALUE_UNKNOWN => Ok(None),
+        value => Ok(Some(value as u32)),
+    }
+}
+
+fn power_unit(context: &AmlContext, value: &AmlValue) -> AcpiResult<PowerUnit> {
+    match integer(context, value)? {
+        0 => Ok(PowerUnit::MilliWatts),
+        _ => Ok(PowerUnit::MilliAmps),
+    }
+}
+
+fn technology(context: &AmlContext, value: &AmlValue) -> AcpiResult<BatteryTechnology> {
+    match integer(context, value)? {
+        0 => Ok(BatteryTechnology::Primary),
+        _ => Ok(BatteryTechnology::Secondary),
+    }
+}
+
+/// Decode one of the battery's string fields, which firmware provides either as a string, or as a buffer
+/// containing a (possibly null-terminated) string. Fields that can't be decoded are returned as empty strings.
+fn string(context: &AmlContext, value: &AmlValue) -> String {
+    match value {
+        AmlValue::Buffer(bytes) => {
+            let bytes = bytes.lock();
+            let length = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..length]).into_owned()
+        }
+        other => other.as_string(context).unwrap_or_default(),
+    }
+}
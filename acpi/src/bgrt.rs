@@ -1,5 +1,6 @@
 use crate::{
     sdt::{SdtHeader, Signature},
+    AcpiError,
     AcpiTable,
 };
 use bit_field::BitField;
@@ -25,6 +26,13 @@ unsafe impl AcpiTable for Bgrt {
     fn header(&self) -> &SdtHeader {
         &self.header
     }
+
+    fn find_content_problems(&self, report: &mut dyn FnMut(AcpiError)) {
+        // Only the displayed flag and the orientation offset are defined in the status field
+        if { self.status }.get_bits(3..8) != 0 {
+            report(AcpiError::SdtReservedFieldSet(Self::SIGNATURE));
+        }
+    }
 }
 
 impl Bgrt {
@@ -0,0 +1,110 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+
+/// Represents the Simple Boot Flag Table (BOOT), which gives the index of the CMOS register that holds the Simple
+/// Boot Flag. The OS uses the flag to tell the firmware whether the previous boot was successful, and whether it
+/// should run diagnostics on the next boot.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Boot {
+    pub header: SdtHeader,
+    /// The index of the boot flag register in CMOS memory.
+    pub cmos_index: u8,
+    _reserved: [u8; 3],
+}
+
+/// ### Safety: Implementation properly represents a valid BOOT.
+unsafe impl AcpiTable for Boot {
+    const SIGNATURE: Signature = Signature::BOOT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+/// The contents of the boot flag register. The register must have odd parity, which is maintained by
+/// [`BootFlags::with_parity`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct BootFlags(pub u8);
+
+impl BootFlags {
+    /// If true, the OS is Plug-and-Play capable, and the firmware should only configure the devices needed to
+    /// boot.
+    pub fn pnp_os(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    /// Set by the firmware at the start of a boot. If it is still set at the next boot, the previous boot didn't
+    /// complete.
+    pub fn booting(&self) -> bool {
+        self.0.get_bit(1)
+    }
+
+    /// If true, the firmware should run diagnostics on the next boot.
+    pub fn diagnostics(&self) -> bool {
+        self.0.get_bit(2)
+    }
+
+    pub fn set_pnp_os(&mut self, pnp_os: bool) {
+        self.0.set_bit(0, pnp_os);
+    }
+
+    pub fn set_booting(&mut self, booting: bool) {
+        self.0.set_bit(1, booting);
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: bool) {
+        self.0.set_bit(2, diagnostics);
+    }
+
+    /// If false, the contents of the register are invalid, and should be ignored.
+    pub fn has_valid_parity(&self) -> bool {
+        self.0.count_ones() & 1 == 1
+    }
+
+    /// Set the parity bit (bit 7) so that the register has the odd parity the firmware expects. This should be
+    /// done before the flags are written back to CMOS.
+    pub fn with_parity(mut self) -> BootFlags {
+        self.0.set_bit(7, false);
+        let parity = self.0.count_ones() & 1 == 0;
+        self.0.set_bit(7, parity);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{table, TableBuffer};
+
+    #[test]
+    fn test_boot() {
+        let buffer = TableBuffer::<Boot>::new(&table(b"BOOT", 1, &[0x45, 0, 0, 0]));
+        assert!(buffer.get().validate().is_ok());
+        assert_eq!(buffer.get().cmos_index, 0x45);
+    }
+
+    #[test]
+    fn test_boot_flags() {
+        let mut flags = BootFlags(0b1000_0011);
+        assert!(flags.has_valid_parity());
+        assert!(flags.pnp_os());
+        assert!(flags.booting());
+        assert!(!flags.diagnostics());
+
+        flags.set_booting(false);
+        assert!(!flags.has_valid_parity());
+        let flags = flags.with_parity();
+        assert_eq!(flags, BootFlags(0b0000_0001));
+        assert!(flags.has_valid_parity());
+
+        let mut flags = BootFlags(0);
+        flags.set_pnp_os(true);
+        flags.set_diagnostics(true);
+        assert_eq!(flags.with_parity(), BootFlags(0b1000_0101));
+    }
+}
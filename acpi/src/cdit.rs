@@ -0,0 +1,113 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiError,
+    AcpiResult,
+    AcpiTable,
+};
+use core::{mem, slice};
+
+/// Represents the Component Distance Information Table (CDIT), which AMD platforms provide alongside the CRAT.
+/// Like the SLIT, it contains a matrix of the relative distances between each pair of proximity domains, but the
+/// domains are those described by the CRAT, which include GPU compute units as well as processors.
+///
+/// Distances are relative to the distance from a domain to itself, which is always `10`.
+#[repr(C, packed)]
+pub struct Cdit {
+    header: SdtHeader,
+    number_of_domains: u32,
+    // Followed by a `number_of_domains * number_of_domains` matrix of `u8` distances
+}
+
+/// ### Safety: Implementation properly represents a valid CDIT.
+unsafe impl AcpiTable for Cdit {
+    const SIGNATURE: Signature = Signature::CDIT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+
+    fn validate_contents(&self) -> AcpiResult<()> {
+        // Make sure the distance matrix fits inside the table, so we never read past the end of it
+        let domains = self.number_of_domains as usize;
+        let matrix_size = domains.checked_mul(domains).ok_or(AcpiError::SdtInvalidLength(Self::SIGNATURE))?;
+        if mem::size_of::<Cdit>() + matrix_size > self.header.length as usize {
+            return Err(AcpiError::SdtInvalidLength(Self::SIGNATURE));
+        }
+
+        Ok(())
+    }
+}
+
+impl Cdit {
+    pub fn number_of_domains(&self) -> u32 {
+        self.number_of_domains
+    }
+
+    /// Get the relative distance from domain `from` to domain `to`. Returns `None` if either domain is not
+    /// described by the table.
+    pub fn distance(&self, from: u32, to: u32) -> Option<u8> {
+        if from >= self.number_of_domains || to >= self.number_of_domains {
+            return None;
+        }
+
+        self.matrix().get(from as usize * self.number_of_domains as usize + to as usize).copied()
+    }
+
+    /// Returns an iterator over each row of the distance matrix. The `n`th item is a slice of the distances from
+    /// domain `n` to every domain in the system.
+    pub fn domains(&self) -> slice::ChunksExact<'_, u8> {
+        // `chunks_exact` panics on a zero chunk size, but the matrix is empty in that case anyway
+        self.matrix().chunks_exact(usize::max(self.number_of_domains as usize, 1))
+    }
+
+    fn matrix(&self) -> &[u8] {
+        let domains = self.number_of_domains as usize;
+
+        unsafe {
+            let pointer = (self as *const Cdit as *const u8).add(mem::size_of::<Cdit>());
+            slice::from_raw_parts(pointer, domains * domains)
+        }
+    }
+}
+
+impl core::fmt::Debug for Cdit {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("Cdit")
+            .field("header", &self.header)
+            .field("number_of_domains", &{ self.number_of_domains })
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{finish_table, table, TableBuffer};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_distances() {
+        let mut body = 2u32.to_le_bytes().to_vec();
+        body.extend([10, 40, 40, 10]);
+        let buffer = TableBuffer::<Cdit>::new(&table(b"CDIT", 1, &body));
+        let cdit = buffer.get();
+        assert!(cdit.validate().is_ok());
+
+        assert_eq!(cdit.number_of_domains(), 2);
+        assert_eq!(cdit.distance(0, 1), Some(40));
+        assert_eq!(cdit.distance(1, 1), Some(10));
+        assert_eq!(cdit.distance(0, 2), None);
+        let rows: Vec<&[u8]> = cdit.domains().collect();
+        assert_eq!(rows, [&[10, 40][..], &[40, 10]]);
+    }
+
+    #[test]
+    fn test_truncated_matrix() {
+        let mut bytes = table(b"CDIT", 1, &[2, 0, 0, 0, 10, 40, 40, 10]);
+        bytes.pop();
+        finish_table(&mut bytes);
+        let buffer = TableBuffer::<Cdit>::new(&bytes);
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::CDIT))));
+    }
+}
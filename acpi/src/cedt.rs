@@ -0,0 +1,322 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, ptr};
+
+/// Represents the CXL Early Discovery Table (CEDT), which describes the CXL host bridges of the platform, and the
+/// fixed memory windows (ranges of host physical address space) that are decoded to CXL memory devices below them.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Cedt {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid CEDT.
+unsafe impl AcpiTable for Cedt {
+    const SIGNATURE: Signature = Signature::CEDT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Cedt {
+    pub fn entries(&self) -> CedtEntryIter<'_> {
+        CedtEntryIter {
+            pointer: unsafe { (self as *const Cedt as *const u8).add(mem::size_of::<Cedt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Cedt>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn host_bridges(&self) -> impl Iterator<Item = &HostBridgeStructure> {
+        self.entries().filter_map(|entry| match entry {
+            CedtEntry::HostBridge(chbs) => Some(chbs),
+            _ => None,
+        })
+    }
+
+    pub fn fixed_memory_windows(&self) -> impl Iterator<Item = &FixedMemoryWindowStructure> {
+        self.entries().filter_map(|entry| match entry {
+            CedtEntry::FixedMemoryWindow(cfmws) => Some(cfmws),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CedtEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum CedtEntry<'a> {
+    HostBridge(&'a HostBridgeStructure),
+    FixedMemoryWindow(&'a FixedMemoryWindowStructure),
+    /// A structure of a type we don't parse yet.
+    Other(&'a EntryHeader),
+}
+
+impl<'a> Iterator for CedtEntryIter<'a> {
+    type Item = CedtEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<EntryHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const EntryHeader) };
+        let length = header.record_length as usize;
+        if length < mem::size_of::<EntryHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let entry_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        match header.entry_type {
+            0 if length >= mem::size_of::<HostBridgeStructure>() => {
+                Some(CedtEntry::HostBridge(unsafe { &*(entry_pointer as *const HostBridgeStructure) }))
+            }
+            1 if length >= mem::size_of::<FixedMemoryWindowStructure>() => {
+                Some(CedtEntry::FixedMemoryWindow(unsafe {
+                    &*(entry_pointer as *const FixedMemoryWindowStructure)
+                }))
+            }
+            _ => Some(CedtEntry::Other(header)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u8,
+    _reserved: u8,
+    pub record_length: u16,
+}
+
+/// The CXL Host Bridge Structure (CHBS) describes a CXL host bridge, and where its component registers are.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HostBridgeStructure {
+    pub header: EntryHeader,
+    /// Matches the `_UID` of the host bridge's object in the namespace.
+    pub uid: u32,
+    /// `0` for a CXL 1.1 host bridge, or `1` for a CXL 2.0 (or later) host bridge.
+    pub cxl_version: u32,
+    _reserved: u32,
+    /// For a CXL 1.1 host bridge, the base address of the RCRB (Root Complex Register Block). For a CXL 2.0
+    /// host bridge, the base address of the CHBCR (CXL Host Bridge Component Registers).
+    pub base: u64,
+    pub length: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InterleaveArithmetic {
+    /// The target is selected with the standard modulo arithmetic.
+    Modulo,
+    /// The target is selected with modulo arithmetic combined with an XOR of address bits, as described by a
+    /// CXIMS structure with the same interleave granularity.
+    ModuloXor,
+    Reserved(u8),
+}
+
+/// The CXL Fixed Memory Window Structure (CFMWS) describes a range of host physical address space that is decoded
+/// to CXL memory, and which host bridges the range is interleaved across.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FixedMemoryWindowStructure {
+    pub header: EntryHeader,
+    _reserved0: u32,
+    pub base_hpa: u64,
+    pub window_size: u64,
+    encoded_interleave_ways: u8,
+    interleave_arithmetic: u8,
+    _reserved1: u16,
+    encoded_interleave_granularity: u32,
+    pub window_restrictions: u16,
+    /// The QoS Throttling Group this window belongs to, which can be matched with the `_DSM` of the ACPI0017
+    /// device.
+    pub qtg_id: u16,
+    // Followed by the UIDs of the targets of the interleave
+}
+
+impl FixedMemoryWindowStructure {
+    /// The number of host bridges this window is interleaved across. Returns `None` if the encoded value is
+    /// reserved.
+    pub fn interleave_ways(&self) -> Option<u32> {
+        match self.encoded_interleave_ways {
+            0 => Some(1),
+            1 => Some(2),
+            2 => Some(4),
+            3 => Some(8),
+            4 => Some(16),
+            8 => Some(3),
+            9 => Some(6),
+            10 => Some(12),
+            _ => None,
+        }
+    }
+
+    /// The encoded number of interleave ways, as it should be programmed into an HDM decoder.
+    pub fn encoded_interleave_ways(&self) -> u8 {
+        self.encoded_interleave_ways
+    }
+
+    pub fn interleave_arithmetic(&self) -> InterleaveArithmetic {
+        match self.interleave_arithmetic {
+            0 => InterleaveArithmetic::Modulo,
+            1 => InterleaveArithmetic::ModuloXor,
+            other => InterleaveArithmetic::Reserved(other),
+        }
+    }
+
+    /// The number of consecutive bytes that are sent to the same host bridge before moving to the next one.
+    /// Returns `None` if the encoded value is reserved.
+    pub fn interleave_granularity(&self) -> Option<u64> {
+        match self.encoded_interleave_granularity {
+            granularity @ 0..=6 => Some(256 << granularity),
+            _ => None,
+        }
+    }
+
+    /// The encoded interleave granularity, as it should be programmed into an HDM decoder.
+    pub fn encoded_interleave_granularity(&self) -> u32 {
+        self.encoded_interleave_granularity
+    }
+
+    /// If true, this window can be used for CXL Type 2 (accelerator) memory that is device-coherent.
+    pub fn allows_device_coherent(&self) -> bool {
+        { self.window_restrictions }.get_bit(0)
+    }
+
+    /// If true, this window can be used for CXL Type 3 (memory expander) memory that is host-only coherent.
+    pub fn allows_host_only_coherent(&self) -> bool {
+        { self.window_restrictions }.get_bit(1)
+    }
+
+    pub fn allows_volatile(&self) -> bool {
+        { self.window_restrictions }.get_bit(2)
+    }
+
+    pub fn allows_persistent(&self) -> bool {
+        { self.window_restrictions }.get_bit(3)
+    }
+
+    /// If true, the configuration of this window is fixed, and the OS should not try to change the HDM decoders
+    /// that it covers.
+    pub fn is_fixed_device_configuration(&self) -> bool {
+        { self.window_restrictions }.get_bit(4)
+    }
+
+    /// The UIDs of the host bridges (matching the `uid` of a [`HostBridgeStructure`]) this window is interleaved
+    /// across, in interleave order.
+    pub fn interleave_targets(&self) -> impl Iterator<Item = u32> + '_ {
+        let available =
+            (self.header.record_length as usize).saturating_sub(mem::size_of::<Self>()) / mem::size_of::<u32>();
+        let count = usize::min(self.interleave_ways().unwrap_or(0) as usize, available);
+        let base = unsafe { (self as *const Self as *const u8).add(mem::size_of::<Self>()) } as *const u32;
+
+        (0..count).map(move |i| unsafe { ptr::read_unaligned(base.add(i)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn host_bridge(uid: u32) -> HostBridgeStructure {
+        HostBridgeStructure {
+            header: EntryHeader {
+                entry_type: 0,
+                _reserved: 0,
+                record_length: mem::size_of::<HostBridgeStructure>() as u16,
+            },
+            uid,
+            cxl_version: 1,
+            _reserved: 0,
+            base: 0xfe00_0000 + uid as u64 * 0x1_0000,
+            length: 0x1_0000,
+        }
+    }
+
+    fn fixed_memory_window(encoded_interleave_ways: u8, targets: &[u32]) -> Vec<u8> {
+        let mut bytes = bytes_of(&FixedMemoryWindowStructure {
+            header: EntryHeader {
+                entry_type: 1,
+                _reserved: 0,
+                record_length: (mem::size_of::<FixedMemoryWindowStructure>() + targets.len() * 4) as u16,
+            },
+            _reserved0: 0,
+            base_hpa: 0x100_0000_0000,
+            window_size: 0x10_0000_0000,
+            encoded_interleave_ways,
+            interleave_arithmetic: 0,
+            _reserved1: 0,
+            encoded_interleave_granularity: 2,
+            window_restrictions: 0b1110,
+            qtg_id: 0,
+        });
+        for target in targets {
+            bytes.extend(target.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body = Vec::new();
+        body.extend(bytes_of(&host_bridge(0)));
+        body.extend(bytes_of(&host_bridge(1)));
+        body.extend(fixed_memory_window(1, &[1, 0]));
+        // The window claims 4 targets, but only has room for 3
+        body.extend(fixed_memory_window(2, &[0, 1, 0]));
+        // A CXIMS structure, which isn't parsed
+        body.extend(bytes_of(&EntryHeader { entry_type: 2, _reserved: 0, record_length: 16 }));
+        body.extend([0; 12]);
+
+        let buffer = TableBuffer::<Cedt>::new(&table(b"CEDT", 1, &body));
+        let cedt = buffer.get();
+        assert!(cedt.validate().is_ok());
+        assert_eq!(cedt.entries().count(), 5);
+        assert!(matches!(cedt.entries().last(), Some(CedtEntry::Other(header)) if header.entry_type == 2));
+
+        let host_bridges: Vec<_> = cedt.host_bridges().map(|chbs| (chbs.uid, chbs.base)).collect();
+        assert_eq!(host_bridges, [(0, 0xfe00_0000), (1, 0xfe01_0000)]);
+
+        let windows: Vec<_> = cedt.fixed_memory_windows().collect();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].interleave_ways(), Some(2));
+        assert_eq!(windows[0].interleave_granularity(), Some(1024));
+        assert_eq!(windows[0].interleave_arithmetic(), InterleaveArithmetic::Modulo);
+        assert!(windows[0].allows_volatile());
+        assert!(!windows[0].allows_device_coherent());
+        assert!(windows[0].interleave_targets().eq([1, 0]));
+        assert!(windows[1].interleave_targets().eq([0, 1, 0]));
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        // A window too short to be read as one is returned as an unknown structure
+        let mut window = fixed_memory_window(0, &[]);
+        window.truncate(window.len() - 4);
+        let length = window.len() as u16;
+        window[2..4].copy_from_slice(&length.to_le_bytes());
+        let buffer = TableBuffer::<Cedt>::new(&table(b"CEDT", 1, &window));
+        assert!(matches!(buffer.get().entries().next(), Some(CedtEntry::Other(_))));
+
+        // A host bridge that runs past the end of the table ends the list
+        let host_bridge = bytes_of(&host_bridge(0));
+        let buffer = TableBuffer::<Cedt>::new(&table(b"CEDT", 1, &host_bridge[..host_bridge.len() - 1]));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
@@ -0,0 +1,535 @@
+use crate::{
+    address::GenericAddress,
+    fadt::{ArmBootArchFlags, Fadt, FixedFeatureFlags, IaPcBootArchFlags},
+    madt::{
+        EntryHeader,
+        InterruptSourceOverrideEntry,
+        IoApicEntry,
+        LocalApicEntry,
+        LocalApicNmiEntry,
+        LocalX2ApicEntry,
+        Madt,
+        NmiSourceEntry,
+        X2ApicNmiEntry,
+    },
+    mcfg::{Mcfg, McfgEntry},
+    sdt::{SdtHeader, Signature},
+    AcpiError,
+    AcpiResult,
+    PowerProfile,
+};
+use core::{
+    convert::{TryFrom, TryInto},
+    mem,
+    ptr,
+};
+use rsdp::Rsdp;
+
+/// The alignment of each table in a [`TableImage`]. Tables don't have to be aligned, but OSs may expect them to
+/// be.
+const TABLE_ALIGNMENT: usize = 8;
+/// The RSDP must be on a 16-byte boundary, so that it can be found when searching for it.
+const RSDP_ALIGNMENT: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComposeError {
+    /// The buffer doesn't have enough space left for the table.
+    BufferTooSmall,
+    /// The table is longer than can be described by the `length` field of its header.
+    TableTooLarge,
+    /// A table passed to [`TableImage::push_table`] is shorter than its header, or than its header says it is.
+    InvalidTable,
+}
+
+/// The identification of the OEM and the tool that created the tables, which is included in the header of each
+/// table.
+#[derive(Clone, Copy, Debug)]
+pub struct OemInfo {
+    pub oem_id: [u8; 6],
+    pub oem_table_id: [u8; 8],
+    pub oem_revision: u32,
+    pub creator_id: u32,
+    pub creator_revision: u32,
+}
+
+/// Builds a set of tables into a caller-provided buffer, which will be at `physical_base` in the physical address
+/// space that the tables are read from (e.g. a virtual machine's guest physical memory). Each `push_*` method adds
+/// a table after the previous one, with a valid checksum, and returns its physical address, which can be used to
+/// link it to the tables that point to it. The tables are usually added from the leaves to the root:
+///
+/// ```ignore
+/// let mut image = TableImage::new(&mut buffer, physical_base, oem);
+/// let dsdt = image.push_table(&dsdt_aml)?;
+/// let fadt = image.push_fadt(&FadtBuilder { dsdt_address: dsdt, ..Default::default() })?;
+/// let mut madt = image.begin_madt(0xfee0_0000, 0)?;
+/// madt.local_apic(0, 0, 1)?;
+/// madt.io_apic(0, 0xfec0_0000, 0)?;
+/// let madt = madt.finish()?;
+/// let mcfg = image.push_mcfg(&[McfgEntry::new(0xb000_0000, 0, 0, 255)])?;
+/// let xsdt = image.push_xsdt(&[fadt, madt, mcfg])?;
+/// let rsdp = image.push_rsdp(xsdt)?;
+/// ```
+///
+/// The tables are built from the same structures that are used to read them, so they can be read back by
+/// [`AcpiTables`](crate::AcpiTables).
+pub struct TableImage<'a> {
+    buffer: &'a mut [u8],
+    physical_base: u64,
+    used: usize,
+    oem: OemInfo,
+}
+
+impl<'a> TableImage<'a> {
+    pub fn new(buffer: &'a mut [u8], physical_base: u64, oem: OemInfo) -> TableImage<'a> {
+        TableImage { buffer, physical_base, used: 0, oem }
+    }
+
+    /// The number of bytes of the buffer that have been used by the tables so far.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Add a table that has already been built, such as a DSDT or SSDT produced by an AML compiler. The table is
+    /// copied as-is, except that its checksum is recalculated.
+    pub fn push_table(&mut self, table: &[u8]) -> AcpiResult<u64> {
+        if table.len() < mem::size_of::<SdtHeader>() {
+            return Err(AcpiError::Compose(ComposeError::InvalidTable));
+        }
+        // Safety: `table` is long enough to contain a header, which is valid for any bytes.
+        let header = unsafe { ptr::read_unaligned(table.as_ptr().cast::<SdtHeader>()) };
+        let length = header.length as usize;
+        if length < mem::size_of::<SdtHeader>() || length > table.len() {
+            return Err(AcpiError::Compose(ComposeError::InvalidTable));
+        }
+
+        let (offset, address) = self.allocate(length, TABLE_ALIGNMENT)?;
+        self.buffer[offset..(offset + length)].copy_from_slice(&table[..length]);
+        self.finish_table(offset);
+        Ok(address)
+    }
+
+    pub fn push_fadt(&mut self, fadt: &FadtBuilder) -> AcpiResult<u64> {
+        let header = self.header::<Fadt>(Signature::FADT, 6);
+        self.push_structure(Fadt::new(header, fadt))
+    }
+
+    /// Start building a MADT, with the physical address of the local APIC, and the MADT's flags. Entries are then
+    /// added with the methods of the returned [`MadtWriter`], and the table is completed with
+    /// [`MadtWriter::finish`].
+    pub fn begin_madt(&mut self, local_apic_address: u32, flags: u32) -> AcpiResult<MadtWriter<'_, 'a>> {
+        let header = self.header::<Madt>(Signature::MADT, 5);
+        let (offset, _) = self.allocate(mem::size_of::<Madt>(), TABLE_ALIGNMENT)?;
+        self.write(offset, Madt { header, local_apic_address, flags });
+        Ok(MadtWriter { image: self, offset })
+    }
+
+    pub fn push_mcfg(&mut self, entries: &[McfgEntry]) -> AcpiResult<u64> {
+        let length = mem::size_of::<Mcfg>() + mem::size_of_val(entries);
+        let (offset, address) = self.allocate(length, TABLE_ALIGNMENT)?;
+
+        let mut header = self.header::<Mcfg>(Signature::MCFG, 1);
+        header.length = u32::try_from(length).map_err(|_| AcpiError::Compose(ComposeError::TableTooLarge))?;
+        self.write(offset, Mcfg::new(header));
+        for (i, &entry) in entries.iter().enumerate() {
+            self.write(offset + mem::size_of::<Mcfg>() + i * mem::size_of::<McfgEntry>(), entry);
+        }
+
+        self.finish_table(offset);
+        Ok(address)
+    }
+
+    /// Add an XSDT, which points to the tables at each of the physical addresses in `tables`. The DSDT and FACS
+    /// are pointed to by the FADT, and so shouldn't be included.
+    pub fn push_xsdt(&mut self, tables: &[u64]) -> AcpiResult<u64> {
+        let length = mem::size_of::<SdtHeader>() + mem::size_of_val(tables);
+        let (offset, address) = self.allocate(length, TABLE_ALIGNMENT)?;
+
+        let mut header = self.header::<SdtHeader>(Signature::XSDT, 1);
+        header.length = u32::try_from(length).map_err(|_| AcpiError::Compose(ComposeError::TableTooLarge))?;
+        self.write(offset, header);
+        for (i, &table) in tables.iter().enumerate() {
+            self.write(offset + mem::size_of::<SdtHeader>() + i * mem::size_of::<u64>(), table.to_le());
+        }
+
+        self.finish_table(offset);
+        Ok(address)
+    }
+
+    /// Add an RSDP that points to the XSDT at `xsdt_address`. If the RSDP has to be at a particular address
+    /// (e.g. in the BIOS area, where it's searched for), it can instead be built with [`Rsdp::new`] and written
+    /// there.
+    pub fn push_rsdp(&mut self, xsdt_address: u64) -> AcpiResult<u64> {
+        let (offset, address) = self.allocate(mem::size_of::<Rsdp>(), RSDP_ALIGNMENT)?;
+        self.write(offset, Rsdp::new(self.oem.oem_id, 0, xsdt_address));
+        Ok(address)
+    }
+
+    /// Allocate `length` zeroed bytes from the buffer, aligned to `alignment` in the physical address space.
+    /// Returns the offset of the allocation in the buffer, and its physical address.
+    fn allocate(&mut self, length: usize, alignment: usize) -> AcpiResult<(usize, u64)> {
+        let unaligned = self
+            .physical_base
+            .checked_add(self.used as u64)
+            .ok_or(AcpiError::Compose(ComposeError::BufferTooSmall))?;
+        let padding = (unaligned.wrapping_neg() & (alignment as u64 - 1)) as usize;
+        let offset = self.used + padding;
+        let end = offset.checked_add(length).ok_or(AcpiError::Compose(ComposeError::BufferTooSmall))?;
+        if end > self.buffer.len() {
+            return Err(AcpiError::Compose(ComposeError::BufferTooSmall));
+        }
+
+        self.buffer[offset..end].fill(0);
+        self.used = end;
+        Ok((offset, self.physical_base + offset as u64))
+    }
+
+    /// Write `value` into the buffer at `offset`. The space must already have been allocated.
+    fn write<T>(&mut self, offset: usize, value: T) {
+        let bytes = &mut self.buffer[offset..(offset + mem::size_of::<T>())];
+        // Safety: `bytes` is large enough to hold a `T`, and the write doesn't need to be aligned.
+        unsafe { ptr::write_unaligned(bytes.as_mut_ptr().cast::<T>(), value) };
+    }
+
+    /// Add a table that is represented entirely by `table`, without any trailing entries.
+    fn push_structure<T>(&mut self, table: T) -> AcpiResult<u64> {
+        let (offset, address) = self.allocate(mem::size_of::<T>(), TABLE_ALIGNMENT)?;
+        self.write(offset, table);
+        self.finish_table(offset);
+        Ok(address)
+    }
+
+    /// A header for a table represented by `T`, with a length of `T`'s size and no checksum.
+    fn header<T>(&self, signature: Signature, revision: u8) -> SdtHeader {
+        SdtHeader {
+            signature,
+            length: mem::size_of::<T>() as u32,
+            revision,
+            checksum: 0,
+            oem_id: self.oem.oem_id,
+            oem_table_id: self.oem.oem_table_id,
+            oem_revision: self.oem.oem_revision,
+            creator_id: self.oem.creator_id,
+            creator_revision: self.oem.creator_revision,
+        }
+    }
+
+    /// Fill in the checksum of the table at `offset`, once the rest of it has been written.
+    fn finish_table(&mut self, offset: usize) {
+        let checksum_offset = offset + mem::offset_of!(SdtHeader, checksum);
+        let length_offset = offset + mem::offset_of!(SdtHeader, length);
+        let length = u32::from_le_bytes(self.buffer[length_offset..(length_offset + 4)].try_into().unwrap());
+
+        self.buffer[checksum_offset] = 0;
+        let sum =
+            self.buffer[offset..(offset + length as usize)].iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        self.buffer[checksum_offset] = sum.wrapping_neg();
+    }
+}
+
+/// Adds entries to a MADT started by [`TableImage::begin_madt`]. Each entry is added after the previous one, and
+/// the MADT must be completed with [`MadtWriter::finish`] before any other tables can be added.
+pub struct MadtWriter<'i, 'a> {
+    image: &'i mut TableImage<'a>,
+    offset: usize,
+}
+
+impl MadtWriter<'_, '_> {
+    /// Add a Processor Local APIC entry. Bit 0 of `flags` marks the processor as enabled, and bit 1 marks it as
+    /// able to be enabled by the OS.
+    pub fn local_apic(&mut self, processor_id: u8, apic_id: u8, flags: u32) -> AcpiResult<()> {
+        self.push(LocalApicEntry::new(processor_id, apic_id, flags))
+    }
+
+    pub fn io_apic(
+        &mut self,
+        io_apic_id: u8,
+        io_apic_address: u32,
+        global_system_interrupt_base: u32,
+    ) -> AcpiResult<()> {
+        self.push(IoApicEntry::new(io_apic_id, io_apic_address, global_system_interrupt_base))
+    }
+
+    /// Add an Interrupt Source Override entry, which maps the ISA `irq` to `global_system_interrupt`. `flags` are
+    /// the MPS INTI flags, which describe the polarity and trigger mode of the interrupt.
+    pub fn interrupt_source_override(
+        &mut self,
+        irq: u8,
+        global_system_interrupt: u32,
+        flags: u16,
+    ) -> AcpiResult<()> {
+        self.push(InterruptSourceOverrideEntry::new(irq, global_system_interrupt, flags))
+    }
+
+    pub fn nmi_source(&mut self, global_system_interrupt: u32, flags: u16) -> AcpiResult<()> {
+        self.push(NmiSourceEntry::new(flags, global_system_interrupt))
+    }
+
+    /// Add a Local APIC NMI entry, which describes which LINT pin (`nmi_line`) of a processor's local APIC the NMI
+    /// is connected to. A `processor_id` of `0xff` applies to all processors.
+    pub fn local_apic_nmi(&mut self, processor_id: u8, nmi_line: u8, flags: u16) -> AcpiResult<()> {
+        self.push(LocalApicNmiEntry::new(processor_id, flags, nmi_line))
+    }
+
+    /// Add a Processor Local x2APIC entry, for processors with APIC IDs that don't fit in a Processor Local APIC
+    /// entry. `flags` are the same as for [`MadtWriter::local_apic`].
+    pub fn local_x2apic(&mut self, x2apic_id: u32, processor_uid: u32, flags: u32) -> AcpiResult<()> {
+        self.push(LocalX2ApicEntry::new(x2apic_id, flags, processor_uid))
+    }
+
+    /// Add a Local x2APIC NMI entry. A `processor_uid` of `0xffffffff` applies to all processors.
+    pub fn x2apic_nmi(&mut self, processor_uid: u32, nmi_line: u8, flags: u16) -> AcpiResult<()> {
+        self.push(X2ApicNmiEntry::new(flags, processor_uid, nmi_line))
+    }
+
+    /// Add an entry that doesn't have its own method, such as one of the entries of the GIC or RISC-V interrupt
+    /// models. `body` is the entry without its two-byte header.
+    pub fn raw_entry(&mut self, entry_type: u8, body: &[u8]) -> AcpiResult<()> {
+        let length = mem::size_of::<EntryHeader>() + body.len();
+        let header = EntryHeader {
+            entry_type,
+            length: u8::try_from(length).map_err(|_| AcpiError::Compose(ComposeError::TableTooLarge))?,
+        };
+
+        let (offset, _) = self.image.allocate(length, 1)?;
+        self.image.write(offset, header);
+        self.image.buffer[(offset + mem::size_of::<EntryHeader>())..(offset + length)].copy_from_slice(body);
+        Ok(())
+    }
+
+    /// Complete the MADT, filling in its length and checksum. Returns the physical address of the MADT.
+    pub fn finish(self) -> AcpiResult<u64> {
+        let length = u32::try_from(self.image.used - self.offset)
+            .map_err(|_| AcpiError::Compose(ComposeError::TableTooLarge))?;
+        let length_offset = self.offset + mem::offset_of!(SdtHeader, length);
+        self.image.buffer[length_offset..(length_offset + 4)].copy_from_slice(&length.to_le_bytes());
+
+        self.image.finish_table(self.offset);
+        Ok(self.image.physical_base + self.offset as u64)
+    }
+
+    fn push<T>(&mut self, entry: T) -> AcpiResult<()> {
+        // Entries follow each other directly, so aren't aligned
+        let (offset, _) = self.image.allocate(mem::size_of::<T>(), 1)?;
+        self.image.write(offset, entry);
+        Ok(())
+    }
+}
+
+/// Describes the contents of a FADT, for [`TableImage::push_fadt`]. Register blocks that are `None` are not
+/// implemented; a hardware-reduced platform (which sets `HW_REDUCED_ACPI` in `flags`) implements none of them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FadtBuilder {
+    pub dsdt_address: u64,
+    /// The physical address of the FACS. This should be `0` on hardware-reduced platforms, which don't need one.
+    pub facs_address: u64,
+    pub power_profile: PowerProfile,
+    pub sci_interrupt: u16,
+    pub smi_cmd_port: u32,
+    pub acpi_enable: u8,
+    pub acpi_disable: u8,
+    pub pm1a_event_block: Option<GenericAddress>,
+    pub pm1b_event_block: Option<GenericAddress>,
+    pub pm1a_control_block: Option<GenericAddress>,
+    pub pm1b_control_block: Option<GenericAddress>,
+    pub pm2_control_block: Option<GenericAddress>,
+    pub pm_timer_block: Option<GenericAddress>,
+    pub gpe0_block: Option<GenericAddress>,
+    pub gpe1_block: Option<GenericAddress>,
+    pub gpe1_base: u8,
+    /// The index of the century in the RTC's CMOS RAM, or `0` if it isn't supported.
+    pub century: u8,
+    pub iapc_boot_arch: IaPcBootArchFlags,
+    pub flags: FixedFeatureFlags,
+    pub reset_register: Option<GenericAddress>,
+    pub reset_value: u8,
+    pub arm_boot_arch: ArmBootArchFlags,
+    pub sleep_control_register: Option<GenericAddress>,
+    pub sleep_status_register: Option<GenericAddress>,
+    pub hypervisor_vendor_id: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        madt::MadtEntry,
+        mcfg::{Mcfg, PciConfigRegions},
+        slit::Slit,
+        AcpiHandler,
+        AcpiTables,
+        PhysicalMapping,
+    };
+    use core::ptr::NonNull;
+
+    /// Maps physical memory at the same virtual address, so that tables built in a buffer can be read back.
+    #[derive(Clone)]
+    struct IdentityHandler;
+
+    impl AcpiHandler for IdentityHandler {
+        unsafe fn map_physical_region<T>(&self, address: usize, size: usize) -> PhysicalMapping<Self, T> {
+            unsafe {
+                PhysicalMapping::new(
+                    address,
+                    NonNull::new(address as *mut T).unwrap(),
+                    size,
+                    size,
+                    IdentityHandler,
+                )
+            }
+        }
+
+        fn unmap_physical_region<T>(_region: &PhysicalMapping<Self, T>) {}
+    }
+
+    #[repr(C, align(16))]
+    struct Buffer([u8; 4096]);
+
+    /// Write the signature, and a length of the whole of `table`, to the start of `table`.
+    fn write_header(table: &mut [u8], signature: &[u8; 4]) {
+        let length = table.len() as u32;
+        table[0..4].copy_from_slice(signature);
+        table[4..8].copy_from_slice(&length.to_le_bytes());
+    }
+
+    #[test]
+    fn test_read_back_image() {
+        let mut buffer = Buffer([0; 4096]);
+        let physical_base = buffer.0.as_ptr() as u64;
+        let oem = OemInfo {
+            oem_id: *b"RUSTOS",
+            oem_table_id: *b"COMPOSE ",
+            oem_revision: 1,
+            creator_id: u32::from_le_bytes(*b"ACPI"),
+            creator_revision: 1,
+        };
+        let mut image = TableImage::new(&mut buffer.0, physical_base, oem);
+
+        let mut dsdt = [0u8; mem::size_of::<SdtHeader>()];
+        dsdt[0..4].copy_from_slice(b"DSDT");
+        dsdt[4..8].copy_from_slice(&(mem::size_of::<SdtHeader>() as u32).to_le_bytes());
+        let dsdt = image.push_table(&dsdt).unwrap();
+        let fadt = image.push_fadt(&FadtBuilder { dsdt_address: dsdt, ..Default::default() }).unwrap();
+
+        let mut madt = image.begin_madt(0xfee0_0000, 1).unwrap();
+        madt.local_apic(0, 0, 1).unwrap();
+        madt.local_apic(1, 2, 1).unwrap();
+        madt.io_apic(4, 0xfec0_0000, 0).unwrap();
+        madt.interrupt_source_override(0, 2, 0).unwrap();
+        madt.local_apic_nmi(0xff, 1, 0).unwrap();
+        let madt = madt.finish().unwrap();
+
+        let mcfg = image.push_mcfg(&[McfgEntry::new(0xb000_0000, 0, 0, 255)]).unwrap();
+        let xsdt = image.push_xsdt(&[fadt, madt, mcfg]).unwrap();
+        let rsdp = image.push_rsdp(xsdt).unwrap();
+
+        let tables = unsafe { AcpiTables::from_rsdp(IdentityHandler, rsdp as usize) }.unwrap();
+        assert_eq!(tables.revision(), 2);
+        assert_eq!(tables.find_table::<Fadt>().unwrap().dsdt_address().unwrap(), dsdt as usize);
+        assert_eq!(tables.dsdt().unwrap().address, dsdt as usize + mem::size_of::<SdtHeader>());
+
+        let madt = tables.find_table::<Madt>().unwrap();
+        assert_eq!({ madt.local_apic_address }, 0xfee0_0000);
+        let mut entries = madt.entries();
+        assert!(matches!(entries.next(), Some(MadtEntry::LocalApic(entry)) if entry.processor_id == 0));
+        assert!(matches!(entries.next(), Some(MadtEntry::LocalApic(entry)) if entry.apic_id == 2));
+        assert!(matches!(
+            entries.next(),
+            Some(MadtEntry::IoApic(entry)) if entry.io_apic_id == 4 && { entry.io_apic_address } == 0xfec0_0000
+        ));
+        assert!(matches!(
+            entries.next(),
+            Some(MadtEntry::InterruptSourceOverride(entry))
+                if entry.irq == 0 && { entry.global_system_interrupt } == 2
+        ));
+        assert!(matches!(entries.next(), Some(MadtEntry::LocalApicNmi(entry)) if entry.nmi_line == 1));
+        assert!(entries.next().is_none());
+
+        let regions = PciConfigRegions::new(&tables, std::alloc::Global).unwrap();
+        assert_eq!(regions.physical_address(0, 0, 0, 0), Some(0xb000_0000));
+        assert_eq!(regions.physical_address(0, 1, 2, 3), Some(0xb000_0000 + (1 << 20) + (2 << 15) + (3 << 12)));
+        assert_eq!(regions.physical_address(1, 0, 0, 0), None);
+    }
+
+    #[test]
+    fn test_short_table_rejected() {
+        let mut buffer = Buffer([0; 4096]);
+        let physical_base = buffer.0.as_ptr() as u64;
+        let oem = OemInfo {
+            oem_id: *b"RUSTOS",
+            oem_table_id: *b"COMPOSE ",
+            oem_revision: 1,
+            creator_id: u32::from_le_bytes(*b"ACPI"),
+            creator_revision: 1,
+        };
+        let mut image = TableImage::new(&mut buffer.0, physical_base, oem);
+
+        // A MADT that ends after its header, without the local APIC address and flags
+        let mut madt = [0u8; mem::size_of::<SdtHeader>()];
+        madt[0..4].copy_from_slice(b"APIC");
+        madt[4..8].copy_from_slice(&(mem::size_of::<SdtHeader>() as u32).to_le_bytes());
+        let madt = image.push_table(&madt).unwrap();
+        let xsdt = image.push_xsdt(&[madt]).unwrap();
+        let rsdp = image.push_rsdp(xsdt).unwrap();
+
+        let tables = unsafe {
+            AcpiTables::from_rsdp_with_policy(IdentityHandler, rsdp as usize, crate::ValidationPolicy::RELAXED)
+        }
+        .unwrap();
+        // Invalid tables are skipped, so the MADT isn't found
+        assert!(matches!(tables.find_table::<Madt>(), Err(AcpiError::TableMissing(Signature::MADT))));
+    }
+
+    #[test]
+    fn test_content_problems() {
+        let mut buffer = Buffer([0; 4096]);
+        let physical_base = buffer.0.as_ptr() as u64;
+        let oem = OemInfo {
+            oem_id: *b"RUSTOS",
+            oem_table_id: *b"COMPOSE ",
+            oem_revision: 1,
+            creator_id: u32::from_le_bytes(*b"ACPI"),
+            creator_revision: 1,
+        };
+        let mut image = TableImage::new(&mut buffer.0, physical_base, oem);
+
+        // A SLIT with a byte left over after its distance matrix
+        let mut slit = [0u8; mem::size_of::<SdtHeader>() + 8 + 2];
+        write_header(&mut slit, b"SLIT");
+        slit[36] = 1;
+        slit[44] = 10;
+        let slit = image.push_table(&slit).unwrap();
+
+        // An MCFG with its reserved field set
+        let mut mcfg = [0u8; mem::size_of::<SdtHeader>() + 8 + mem::size_of::<McfgEntry>()];
+        write_header(&mut mcfg, b"MCFG");
+        mcfg[36] = 1;
+        let mcfg = image.push_table(&mcfg).unwrap();
+
+        // An XSDT with half an entry after its entries
+        let mut xsdt = [0u8; mem::size_of::<SdtHeader>() + 2 * 8 + 4];
+        write_header(&mut xsdt, b"XSDT");
+        xsdt[36..44].copy_from_slice(&slit.to_le_bytes());
+        xsdt[44..52].copy_from_slice(&mcfg.to_le_bytes());
+        let xsdt = image.push_table(&xsdt).unwrap();
+        let rsdp = image.push_rsdp(xsdt).unwrap();
+
+        assert!(matches!(
+            unsafe { AcpiTables::from_rsdp(IdentityHandler, rsdp as usize) },
+            Err(AcpiError::SdtLengthMismatch(Signature::XSDT))
+        ));
+
+        let policy = crate::ValidationPolicy { allow_length_mismatches: true, ..crate::ValidationPolicy::STRICT };
+        let mut tables =
+            unsafe { AcpiTables::from_rsdp_with_policy(IdentityHandler, rsdp as usize, policy) }.unwrap();
+        assert!(tables.find_table::<Slit>().is_ok());
+        assert!(matches!(tables.find_table::<Mcfg>(), Err(AcpiError::TableMissing(Signature::MCFG))));
+        tables.set_validation_policy(crate::ValidationPolicy { allow_reserved_fields: true, ..policy });
+        assert!(tables.find_table::<Mcfg>().is_ok());
+
+        let report: std::vec::Vec<_> = tables.validation_report().map(|warning| warning.error).collect();
+        assert_eq!(report.len(), 3);
+        assert!(matches!(report[0], AcpiError::SdtLengthMismatch(Signature::XSDT)));
+        assert!(matches!(report[1], AcpiError::SdtLengthMismatch(Signature::SLIT)));
+        assert!(matches!(report[2], AcpiError::SdtReservedFieldSet(Signature::MCFG)));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{
+    address::{AccessSize, AddressSpace, GenericAddress, RawGenericAddress, RegisterHandler},
+    device,
+    pcct::{PccChannel, Pcct},
+    sdt::Signature,
+    AcpiError,
+    AcpiResult,
+};
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+use bit_field::BitField;
+use core::ops::Range;
+
+/// The PCC command that makes the platform update the CPPC registers in the communication space.
+const PCC_CMD_READ: u32 = 0x00;
+/// The PCC command that tells the platform that the CPPC registers in the communication space have been written.
+const PCC_CMD_WRITE: u32 = 0x01;
+
+#[derive(Debug)]
+pub enum CppcError {
+    /// The processor's `_CPC` object isn't a package of the expected form.
+    InvalidCpc,
+    /// The `_CPC` object has registers in more than one PCC subspace.
+    MultiplePccSubspaces,
+    /// The register is not implemented by the platform.
+    RegisterNotSupported,
+    /// The register is described by a constant integer, and so can't be written.
+    RegisterNotWritable,
+}
+
+/// A register of a processor's CPPC descriptor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CppcRegister {
+    /// The register isn't implemented, and was described by an empty register descriptor.
+    Unsupported,
+    /// The register has a constant value.
+    Integer(u64),
+    /// The register is a hardware register. If it is in the `FunctionalFixedHardware` space, it is accessed in a
+    /// processor-specific way (e.g. through MSRs on x86), and can't be accessed by [`Cppc::read`] or
+    /// [`Cppc::write`].
+    Register(GenericAddress),
+    /// The register is in the communication space of a PCC subspace, and is accessed through the PCC mailbox.
+    Pcc {
+        subspace: u8,
+        /// The offset of the register in the communication space, in bytes.
+        offset: u64,
+        bit_offset: u8,
+        bit_width: u8,
+    },
+}
+
+/// The performance capabilities of a processor, read from its CPPC descriptor. Performance values are on an
+/// abstract, platform-defined scale.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CppcCapabilities {
+    /// The maximum performance the processor can reach, if conditions allow (e.g. with turbo).
+    pub highest: u64,
+    /// The maximum sustained performance of the processor.
+    pub nominal: u64,
+    /// The lowest performance at which the processor is still power-efficient.
+    pub lowest_nonlinear: u64,
+    pub lowest: u64,
+    /// The performance the platform can currently guarantee, if it provides it.
+    pub guaranteed: Option<u64>,
+    /// The performance at which the reference performance counter counts. This is `nominal` if the platform
+    /// doesn't provide it.
+    pub reference: u64,
+    /// The frequency corresponding to `lowest`, in MHz, if the platform provides it.
+    pub lowest_frequency: Option<u64>,
+    /// The frequency corresponding to `nominal`, in MHz, if the platform provides it.
+    pub nominal_frequency: Option<u64>,
+}
+
+/// A processor's Collaborative Processor Performance Control (CPPC) descriptor, from its `_CPC` object. CPPC
+/// describes the performance of a processor on an abstract scale, and the OS requests performance levels by
+/// writing to the desired performance register, rather than by choosing from a list of P-states.
+#[derive(Clone, Debug)]
+pub struct Cppc {
+    processor: AmlName,
+    pub revision: u8,
+    pub highest_performance: CppcRegister,
+    pub nominal_performance: CppcRegister,
+    pub lowest_nonlinear_performance: CppcRegister,
+    pub lowest_performance: CppcRegister,
+    pub guaranteed_performance: CppcRegister,
+    pub desired_performance: CppcRegister,
+    pub minimum_performance: CppcRegister,
+    pub maximum_performance: CppcRegister,
+    pub performance_reduction_tolerance: CppcRegister,
+    pub time_window: CppcRegister,
+    pub counter_wraparound_time: CppcRegister,
+    pub reference_performance_counter: CppcRegister,
+    pub delivered_performance_counter: CppcRegister,
+    pub performance_limited: CppcRegister,
+    pub cppc_enable: CppcRegister,
+    pub autonomous_selection_enable: CppcRegister,
+    pub autonomous_activity_window: CppcRegister,
+    pub energy_performance_preference: CppcRegister,
+    pub reference_performance: CppcRegister,
+    pub lowest_frequency: CppcRegister,
+    pub nominal_frequency: CppcRegister,
+    channel: Option<PccChannel>,
+}
+
+impl Cppc {
+    /// Read the CPPC descriptor of the processor at `processor` (see [`device::processors`]), by evaluating its
+    /// `_CPC`. Returns `None` if the processor doesn't support CPPC. If any of the registers are in PCC space,
+    /// `pcct` must be provided, so that they can be accessed through the PCC mailbox.
+    pub fn new(context: &mut AmlContext, processor: &AmlName, pcct: Option<&Pcct>) -> AcpiResult<Option<Cppc>> {
+        let cpc = match device::evaluate_optional(context, processor, "_CPC", Args::EMPTY)? {
+            Some(cpc) => cpc,
+            None => return Ok(None),
+        };
+
+        /*
+         * _CPC := Package { NumEntries, Revision, Register, ... }
+         * Revision 2 has 21 entries (in total), and revision 3 adds the lowest and nominal frequencies, for 23.
+         */
+        let entries = match cpc {
+            AmlValue::Package(ref entries) if entries.len() >= 21 => entries,
+            _ => return Err(AcpiError::Cppc(CppcError::InvalidCpc)),
+        };
+        let revision = entries[1].as_integer(context).map_err(AcpiError::Aml)? as u8;
+
+        let mut registers = [CppcRegister::Unsupported; 21];
+        for (register, entry) in registers.iter_mut().zip(entries[2..].iter()) {
+            *register = parse_register(context, entry)?;
+        }
+
+        let mut subspace = None;
+        for register in registers.iter() {
+            if let CppcRegister::Pcc { subspace: register_subspace, .. } = *register {
+                match subspace {
+                    Some(subspace) if subspace != register_subspace => {
+                        return Err(AcpiError::Cppc(CppcError::MultiplePccSubspaces));
+                    }
+                    _ => subspace = Some(register_subspace),
+                }
+            }
+        }
+        let channel = match subspace {
+            Some(subspace) => {
+                let pcct = pcct.ok_or(AcpiError::TableMissing(Signature::PCCT))?;
+                Some(PccChannel::new(pcct, subspace)?)
+            }
+            None => None,
+        };
+
+        Ok(Some(Cppc {
+            processor: processor.clone(),
+            revision,
+            highest_performance: registers[0],
+            nominal_performance: registers[1],
+            lowest_nonlinear_performance: registers[2],
+            lowest_performance: registers[3],
+            guaranteed_performance: registers[4],
+            desired_performance: registers[5],
+            minimum_performance: registers[6],
+            maximum_performance: registers[7],
+            performance_reduction_tolerance: registers[8],
+            time_window: registers[9],
+            counter_wraparound_time: registers[10],
+            reference_performance_counter: registers[11],
+            delivered_performance_counter: registers[12],
+            performance_limited: registers[13],
+            cppc_enable: registers[14],
+            autonomous_selection_enable: registers[15],
+            autonomous_activity_window: registers[16],
+            energy_performance_preference: registers[17],
+            reference_performance: registers[18],
+            lowest_frequency: registers[19],
+            nominal_frequency: registers[20],
+            channel,
+        }))
+    }
+
+    pub fn processor(&self) -> &AmlName {
+        &self.processor
+    }
+
+    /// The PCC channel used to access the registers in PCC space, if there are any.
+    pub fn pcc_channel(&self) -> Option<&PccChannel> {
+        self.channel.as_ref()
+    }
+
+    /// Read the processor's performance capabilities.
+    pub fn capabilities<H>(&self, handler: &H) -> AcpiResult<CppcCapabilities>
+    where
+        H: RegisterHandler,
+    {
+        let nominal = self.read(handler, &self.nominal_performance)?;
+        Ok(CppcCapabilities {
+            highest: self.read(handler, &self.highest_performance)?,
+            nominal,
+            lowest_nonlinear: self.read(handler, &self.lowest_nonlinear_performance)?,
+            lowest: self.read(handler, &self.lowest_performance)?,
+            guaranteed: self.read_optional(handler, &self.guaranteed_performance)?,
+            reference: self.read_optional(handler, &self.reference_performance)?.unwrap_or(nominal),
+            lowest_frequency: self.read_optional(handler, &self.lowest_frequency)?,
+            nominal_frequency: self.read_optional(handler, &self.nominal_frequency)?,
+        })
+    }
+
+    /// Request that the processor runs at `performance`, which should be between the lowest and highest
+    /// performance in the processor's [capabilities](Cppc::capabilities).
+    pub fn set_desired_performance<H>(&self, handler: &H, performance: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.write(handler, &self.desired_performance, performance)
+    }
+
+    /// Read the reference and delivered performance counters. The performance the processor delivered over an
+    /// interval is the reference performance, scaled by the ratio of the increases of the delivered and reference
+    /// counters.
+    pub fn feedback_counters<H>(&self, handler: &H) -> AcpiResult<(u64, u64)>
+    where
+        H: RegisterHandler,
+    {
+        Ok((
+            self.read(handler, &self.reference_performance_counter)?,
+            self.read(handler, &self.delivered_performance_counter)?,
+        ))
+    }
+
+    /// Read one of the processor's CPPC registers.
+    pub fn read<H>(&self, handler: &H, register: &CppcRegister) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        self.read_optional(handler, register)?.ok_or(AcpiError::Cppc(CppcError::RegisterNotSupported))
+    }
+
+    /// Write one of the processor's CPPC registers.
+    pub fn write<H>(&self, handler: &H, register: &CppcRegister, value: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        match *register {
+            CppcRegister::Unsupported => Err(AcpiError::Cppc(CppcError::RegisterNotSupported)),
+            CppcRegister::Integer(_) => Err(AcpiError::Cppc(CppcError::RegisterNotWritable)),
+            CppcRegister::Register(ref address) => address.write(value, handler),
+            CppcRegister::Pcc { offset, bit_offset, bit_width, .. } => {
+                let channel = self.channel.as_ref().ok_or(AcpiError::TableMissing(Signature::PCCT))?;
+                let width = pcc_access_width(bit_offset, bit_width)?;
+                let bits = pcc_field_bits(bit_offset, bit_width, width)?;
+
+                let value = if bits.start == 0 && bits.end >= width as usize {
+                    value
+                } else {
+                    channel.send_command(handler, PCC_CMD_READ)?;
+                    let mut register = channel.read(handler, offset, width)?;
+                    register.set_bits(bits.clone(), value.get_bits(0..bits.len()));
+                    register
+                };
+                channel.write(handler, offset, width, value)?;
+                channel.send_command(handler, PCC_CMD_WRITE)
+            }
+        }
+    }
+
+    fn read_optional<H>(&self, handler: &H, register: &CppcRegister) -> AcpiResult<Option<u64>>
+    where
+        H: RegisterHandler,
+    {
+        match *register {
+            CppcRegister::Unsupported => Ok(None),
+            CppcRegister::Integer(value) => Ok(Some(value)),
+            CppcRegister::Register(ref address) => Ok(Some(address.read(handler)?)),
+            CppcRegister::Pcc { offset, bit_offset, bit_width, .. } => {
+                let channel = self.channel.as_ref().ok_or(AcpiError::TableMissing(Signature::PCCT))?;
+                let width = pcc_access_width(bit_offset, bit_width)?;
+                let bits = pcc_field_bits(bit_offset, bit_width, width)?;
+                channel.send_command(handler, PCC_CMD_READ)?;
+
+                let value = channel.read(handler, offset, width)?;
+                Ok(Some(value.get_bits(bits)))
+            }
+        }
+    }
+}
+
+fn parse_register(context: &AmlContext, entry: &AmlValue) -> AcpiResult<CppcRegister> {
+    let bytes = match entry {
+        AmlValue::Buffer(bytes) => bytes.lock(),
+        other => return Ok(CppcRegister::Integer(other.as_integer(context).map_err(AcpiError::Aml)?)),
+    };
+    let raw = RawGenericAddress::from_register_descriptor(&bytes).ok_or(AcpiError::Cppc(CppcError::InvalidCpc))?;
+
+    // Empty registers are described as a zeroed-out system memory register
+    if raw.address_space == 0x00 && { raw.address } == 0 && raw.bit_width == 0 {
+        return Ok(CppcRegister::Unsupported);
+    }
+
+    // For registers in PCC space, the access size is the index of the subspace, rather than an access size
+    if raw.address_space == 0x0a {
+        return Ok(CppcRegister::Pcc {
+            subspace: raw.access_size,
+            offset: raw.address,
+            bit_offset: raw.bit_offset,
+            bit_width: raw.bit_width,
+        });
+    }
+
+    Ok(CppcRegister::Register(GenericAddress::from_raw(raw)?))
+}
+
+/// The width of the accesses to a register in the PCC communication space, which is inferred from its bits.
+fn pcc_access_width(bit_offset: u8, bit_width: u8) -> AcpiResult<u8> {
+    GenericAddress {
+        address_space: AddressSpace::PlatformCommunicationsChannel,
+        bit_width,
+        bit_offset,
+        access_size: AccessSize::Undefined,
+        address: 0,
+    }
+    .access_width()
+}
+
+/// The bits of the value read from the PCC communication space that hold a register. A `bit_width` of `0` means
+/// the register extends to the end of the access. Like the field of a [`GenericAddress`], the register must be
+/// non-empty and fit in the `u64` it's read into.
+fn pcc_field_bits(bit_offset: u8, bit_width: u8, access_width: u8) -> AcpiResult<Range<usize>> {
+    let start = bit_offset as usize;
+    let end = if bit_width == 0 { access_width as usize } else { start + bit_width as usize };
+
+    if start >= end || end > 64 {
+        return Err(AcpiError::InvalidGenericAddress);
+    }
+    Ok(start..end)
+}
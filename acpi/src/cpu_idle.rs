@@ -0,0 +1,203 @@
+use crate::{
+    address::{AccessSize, AddressSpace, GenericAddress, RawGenericAddress},
+    device,
+    fadt::Fadt,
+    AcpiError,
+    AcpiResult,
+    ManagedSlice,
+    ManagedVec,
+};
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+use core::alloc::Allocator;
+
+#[derive(Debug)]
+pub enum CpuIdleError {
+    /// The processor's `_CST` object isn't a package of the expected form.
+    InvalidCst,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum CStateType {
+    C1,
+    C2,
+    C3,
+}
+
+/// How the processor enters a C-state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CStateEntry {
+    /// Execute the processor's native halt instruction (e.g. `HLT` on x86).
+    Halt,
+    /// Read from the register. If it is in the `FunctionalFixedHardware` space, the state is entered in a
+    /// processor-specific way (e.g. with `MWAIT` on x86, with the hint in the address).
+    Register(GenericAddress),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CState {
+    pub typ: CStateType,
+    pub entry: CStateEntry,
+    /// The worst-case latency to enter and exit the state, in microseconds.
+    pub latency: u32,
+    /// The average power consumption of the processor in this state, in milliwatts. This is `0` if it is
+    /// unknown.
+    pub power: u32,
+}
+
+/// Where a processor's C-states were found.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CStateSource {
+    Cst,
+    /// The processor doesn't have a `_CST`, so its C-states are described by the FADT's latencies, and the
+    /// `P_LVL2` and `P_LVL3` registers of the processor's `P_BLK`.
+    Fadt,
+}
+
+/// The C-states (idle states) supported by a processor, in order of increasing latency.
+#[derive(Debug)]
+pub struct ProcessorIdleStates<A>
+where
+    A: Allocator,
+{
+    pub processor: AmlName,
+    pub source: CStateSource,
+    pub states: ManagedSlice<CState, A>,
+}
+
+impl<A> ProcessorIdleStates<A>
+where
+    A: Allocator,
+{
+    /// Find the C-states of the processor at `processor` (see [`device::processors`]), by evaluating its
+    /// `_CST`. If it doesn't have one, the states are instead described by the FADT: `C1` is always supported,
+    /// and `C2` and `C3` are supported if the FADT's worst-case latencies allow them, and the processor's
+    /// `P_BLK` is long enough to contain their registers.
+    pub fn new_in(
+        context: &mut AmlContext,
+        fadt: &Fadt,
+        processor: &AmlName,
+        allocator: A,
+    ) -> AcpiResult<ProcessorIdleStates<A>> {
+        match device::evaluate_optional(context, processor, "_CST", Args::EMPTY)? {
+            Some(cst) => Ok(ProcessorIdleStates {
+                processor: processor.clone(),
+                source: CStateSource::Cst,
+                states: parse_cst(context, &cst, allocator)?,
+            }),
+            None => Ok(ProcessorIdleStates {
+                processor: processor.clone(),
+                source: CStateSource::Fadt,
+                states: fadt_states(context, fadt, processor, allocator)?,
+            }),
+        }
+    }
+
+    /// The deepest state with a latency of at most `max_latency` microseconds.
+    pub fn deepest_within(&self, max_latency: u32) -> Option<&CState> {
+        self.states.iter().filter(|state| state.latency <= max_latency).max_by_key(|state| state.typ)
+    }
+}
+
+fn parse_cst<A>(context: &AmlContext, cst: &AmlValue, allocator: A) -> AcpiResult<ManagedSlice<CState, A>>
+where
+    A: Allocator,
+{
+    /*
+     * _CST := Package { Count, CState, ... }
+     * CState := Package { Register (ResourceTemplate), Type, Latency, Power }
+     */
+    let entries = match cst {
+        AmlValue::Package(elements) if !elements.is_empty() => &elements[1..],
+        _ => return Err(AcpiError::CpuIdle(CpuIdleError::InvalidCst)),
+    };
+
+    // Entries with types other than C1-C3 are reserved, and are skipped
+    let mut states = ManagedVec::new_in(allocator);
+    for entry in entries {
+        if let Some(state) = parse_cst_entry(context, entry)? {
+            states.try_push(state).map_err(|_| AcpiError::AllocError)?;
+        }
+    }
+
+    states.into_managed_slice().map_err(|_| AcpiError::AllocError)
+}
+
+fn parse_cst_entry(context: &AmlContext, entry: &AmlValue) -> AcpiResult<Option<CState>> {
+    let fields = match entry {
+        AmlValue::Package(fields) if fields.len() >= 4 => fields,
+        _ => return Err(AcpiError::CpuIdle(CpuIdleError::InvalidCst)),
+    };
+    let integer = |value: &AmlValue| value.as_integer(context).map_err(AcpiError::Aml);
+
+    let typ = match integer(&fields[1])? {
+        1 => CStateType::C1,
+        2 => CStateType::C2,
+        3 => CStateType::C3,
+        _ => return Ok(None),
+    };
+    let register = match &fields[0] {
+        AmlValue::Buffer(bytes) => match RawGenericAddress::from_register_descriptor(&bytes.lock()) {
+            Some(raw) => GenericAddress::from_raw(raw)?,
+            None => return Err(AcpiError::CpuIdle(CpuIdleError::InvalidCst)),
+        },
+        _ => return Err(AcpiError::CpuIdle(CpuIdleError::InvalidCst)),
+    };
+
+    Ok(Some(CState {
+        typ,
+        entry: CStateEntry::Register(register),
+        latency: integer(&fields[2])? as u32,
+        power: integer(&fields[3])? as u32,
+    }))
+}
+
+const C1_HALT: CState = CState { typ: CStateType::C1, entry: CStateEntry::Halt, latency: 0, power: 0 };
+
+fn fadt_states<A>(
+    context: &AmlContext,
+    fadt: &Fadt,
+    processor: &AmlName,
+    allocator: A,
+) -> AcpiResult<ManagedSlice<CState, A>>
+where
+    A: Allocator,
+{
+    let mut fadt_states = [Some(C1_HALT), None, None];
+
+    // Only legacy `Processor` objects have a `P_BLK`
+    if let Ok(AmlValue::Processor { pblk_address, pblk_len, .. }) = context.namespace.get_by_path(processor) {
+        let level_register = |offset: u32| GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width: 8,
+            bit_offset: 0,
+            access_size: AccessSize::ByteAccess,
+            address: (pblk_address + offset) as u64,
+        };
+        let (c2_latency, c3_latency) = (fadt.worst_c2_latency, fadt.worst_c3_latency);
+
+        if pblk_address != 0 && pblk_len >= 5 && c2_latency <= 100 {
+            fadt_states[1] = Some(CState {
+                typ: CStateType::C2,
+                entry: CStateEntry::Register(level_register(4)),
+                latency: c2_latency as u32,
+                power: 0,
+            });
+        }
+        if pblk_address != 0 && pblk_len >= 6 && c3_latency <= 1000 {
+            fadt_states[2] = Some(CState {
+                typ: CStateType::C3,
+                entry: CStateEntry::Register(level_register(5)),
+                latency: c3_latency as u32,
+                power: 0,
+            });
+        }
+    }
+
+    let mut states =
+        ManagedVec::with_capacity_in(fadt_states.len(), allocator).map_err(|_| AcpiError::AllocError)?;
+    for &fadt_state in fadt_states.iter().flatten() {
+        states.try_push(fadt_state).map_err(|_| AcpiError::AllocError)?;
+    }
+
+    states.into_managed_slice().map_err(|_| AcpiError::AllocError)
+}
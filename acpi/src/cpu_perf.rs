@@ -0,0 +1,202 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress, RegisterHandler},
+    device,
+    AcpiError,
+    AcpiResult,
+};
+use alloc::vec::Vec;
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+
+#[derive(Debug)]
+pub enum CpuPerfError {
+    /// The `_PSS`, `_PCT`, or `_PPC` object of the processor isn't of the expected form.
+    InvalidObject(&'static str),
+    /// The performance state doesn't exist, or is currently not available because of the processor's `_PPC`
+    /// limit.
+    StateNotAvailable(usize),
+}
+
+/// A performance state (P-state) of a processor, from its `_PSS` object.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PerformanceState {
+    /// The core frequency of the processor in this state, in MHz.
+    pub core_frequency: u32,
+    /// The typical power dissipation of the processor in this state, in milliwatts.
+    pub power: u32,
+    /// The worst-case latency of switching to this state from any other, in microseconds.
+    pub transition_latency: u32,
+    /// The worst-case time bus masters are prevented from accessing memory while switching to this state, in
+    /// microseconds.
+    pub bus_master_latency: u32,
+    /// The value to write to the control register to switch to this state.
+    pub control: u64,
+    /// The value the status register reads as once the processor has switched to this state.
+    pub status: u64,
+}
+
+/// What changed about a processor's performance states, in response to a `Notify` on it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PerformanceNotification {
+    /// `Notify(processor, 0x80)`: the processor's `_PPC` limit has changed, and has been re-read. The OS should
+    /// switch the processor to an available state if its current state is now unavailable.
+    LimitChanged(usize),
+    /// The notification value isn't one concerning performance states.
+    Other(u64),
+}
+
+/// The performance states (P-states) of a processor, and the registers used to switch between them.
+#[derive(Clone, Debug)]
+pub struct ProcessorPerformance {
+    processor: AmlName,
+    control_register: GenericAddress,
+    status_register: GenericAddress,
+    states: Vec<PerformanceState>,
+    limit: usize,
+}
+
+impl ProcessorPerformance {
+    /// Read the performance states of the processor at `processor` (see [`device::processors`]), by evaluating
+    /// its `_PSS`, `_PCT`, and `_PPC` objects. Returns `None` if the processor doesn't support performance states.
+    pub fn new(context: &mut AmlContext, processor: &AmlName) -> AcpiResult<Option<ProcessorPerformance>> {
+        let pss = match device::evaluate_optional(context, processor, "_PSS", Args::EMPTY)? {
+            Some(pss) => pss,
+            None => return Ok(None),
+        };
+        let states = parse_pss(context, &pss)?;
+
+        /*
+         * _PCT := Package { ControlRegister (ResourceTemplate), StatusRegister (ResourceTemplate) }
+         */
+        let pct = device::evaluate(context, processor, "_PCT", Args::EMPTY)?;
+        let (control_register, status_register) = match pct {
+            AmlValue::Package(ref registers) if registers.len() >= 2 => {
+                (parse_register(&registers[0])?, parse_register(&registers[1])?)
+            }
+            _ => return Err(AcpiError::CpuPerf(CpuPerfError::InvalidObject("_PCT"))),
+        };
+
+        let mut performance = ProcessorPerformance {
+            processor: processor.clone(),
+            control_register,
+            status_register,
+            states,
+            limit: 0,
+        };
+        performance.refresh_limit(context)?;
+        Ok(Some(performance))
+    }
+
+    pub fn processor(&self) -> &AmlName {
+        &self.processor
+    }
+
+    /// The register to write a state's `control` value to, to switch to it. If this is in the
+    /// `FunctionalFixedHardware` space, states are switched in a processor-specific way (e.g. by writing the
+    /// `IA32_PERF_CTL` MSR on x86), and [`ProcessorPerformance::set_state`] can't be used.
+    pub fn control_register(&self) -> &GenericAddress {
+        &self.control_register
+    }
+
+    /// The register to read to find the current state of the processor.
+    pub fn status_register(&self) -> &GenericAddress {
+        &self.status_register
+    }
+
+    /// All of the processor's performance states, in order of decreasing performance (and power).
+    pub fn states(&self) -> &[PerformanceState] {
+        &self.states
+    }
+
+    /// The index of the highest-performance state the OS is currently allowed to use, from `_PPC`.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// The states the OS is currently allowed to use. Their indices into [`ProcessorPerformance::states`] start
+    /// at [`ProcessorPerformance::limit`].
+    pub fn available_states(&self) -> &[PerformanceState] {
+        &self.states[self.limit..]
+    }
+
+    /// Re-read the processor's `_PPC` limit. Processors without a `_PPC` can use all of their states.
+    pub fn refresh_limit(&mut self, context: &mut AmlContext) -> AcpiResult<usize> {
+        let limit = match device::evaluate_optional(context, &self.processor, "_PPC", Args::EMPTY)? {
+            Some(ppc) => ppc.as_integer(context).map_err(AcpiError::Aml)? as usize,
+            None => 0,
+        };
+
+        if limit >= self.states.len() {
+            return Err(AcpiError::CpuPerf(CpuPerfError::InvalidObject("_PPC")));
+        }
+        self.limit = limit;
+        Ok(limit)
+    }
+
+    /// Handle a `Notify` on the processor, by re-reading its `_PPC` limit if it has changed.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, value: u64) -> AcpiResult<PerformanceNotification> {
+        match value {
+            0x80 => Ok(PerformanceNotification::LimitChanged(self.refresh_limit(context)?)),
+            other => Ok(PerformanceNotification::Other(other)),
+        }
+    }
+
+    /// Switch the processor to the state with index `state`, by writing its `control` value to the control
+    /// register. The state must be one of the [available states](ProcessorPerformance::available_states).
+    pub fn set_state<H>(&self, handler: &H, state: usize) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        if state < self.limit || state >= self.states.len() {
+            return Err(AcpiError::CpuPerf(CpuPerfError::StateNotAvailable(state)));
+        }
+        self.control_register.write_register(handler, self.states[state].control)
+    }
+
+    /// Find the current state of the processor, by reading the status register. Returns `None` if the status
+    /// doesn't match any of the processor's states (e.g. because a transition is still in progress).
+    pub fn current_state<H>(&self, handler: &H) -> AcpiResult<Option<usize>>
+    where
+        H: RegisterHandler,
+    {
+        let status = self.status_register.read_register(handler)?;
+        Ok(self.states.iter().position(|state| state.status == status))
+    }
+}
+
+fn parse_pss(context: &AmlContext, pss: &AmlValue) -> AcpiResult<Vec<PerformanceState>> {
+    /*
+     * _PSS := Package { PState, ... }
+     * PState := Package { CoreFrequency, Power, Latency, BusMasterLatency, Control, Status }
+     */
+    let invalid = || AcpiError::CpuPerf(CpuPerfError::InvalidObject("_PSS"));
+    let entries = match pss {
+        AmlValue::Package(entries) if !entries.is_empty() => entries,
+        _ => return Err(invalid()),
+    };
+    let integer = |value: &AmlValue| value.as_integer(context).map_err(AcpiError::Aml);
+
+    entries
+        .iter()
+        .map(|entry| match entry {
+            AmlValue::Package(fields) if fields.len() >= 6 => Ok(PerformanceState {
+                core_frequency: integer(&fields[0])? as u32,
+                power: integer(&fields[1])? as u32,
+                transition_latency: integer(&fields[2])? as u32,
+                bus_master_latency: integer(&fields[3])? as u32,
+                control: integer(&fields[4])?,
+                status: integer(&fields[5])?,
+            }),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+fn parse_register(value: &AmlValue) -> AcpiResult<GenericAddress> {
+    match value {
+        AmlValue::Buffer(bytes) => match RawGenericAddress::from_register_descriptor(&bytes.lock()) {
+            Some(raw) => GenericAddress::from_raw(raw),
+            None => Err(AcpiError::CpuPerf(CpuPerfError::InvalidObject("_PCT"))),
+        },
+        _ => Err(AcpiError::CpuPerf(CpuPerfError::InvalidObject("_PCT"))),
+    }
+}
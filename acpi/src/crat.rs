@@ -0,0 +1,333 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// Represents the Component Resource Attribute Table (CRAT), which AMD platforms use to describe the topology of
+/// their heterogeneous (HSA) compute resources: the compute units (CPU cores and GPU SIMDs), memory, and caches
+/// of each proximity domain. The distances between the domains are described by the
+/// [`Cdit`](crate::cdit::Cdit).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Crat {
+    pub header: SdtHeader,
+    pub total_entries: u32,
+    pub number_of_domains: u16,
+    _reserved: [u8; 6],
+}
+
+/// ### Safety: Implementation properly represents a valid CRAT.
+unsafe impl AcpiTable for Crat {
+    const SIGNATURE: Signature = Signature::CRAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Crat {
+    pub fn entries(&self) -> CratEntryIter<'_> {
+        CratEntryIter {
+            pointer: unsafe { (self as *const Crat as *const u8).add(mem::size_of::<Crat>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Crat>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CratEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum CratEntry<'a> {
+    ComputeUnit(&'a ComputeUnitEntry),
+    Memory(&'a MemoryEntry),
+    Cache(&'a CacheEntry),
+    /// An entry of a type we don't parse yet (e.g. TLB or IO link entries).
+    Other(&'a EntryHeader),
+}
+
+impl<'a> Iterator for CratEntryIter<'a> {
+    type Item = CratEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<EntryHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const EntryHeader) };
+        let length = header.length as usize;
+        if length < mem::size_of::<EntryHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let entry_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        macro_rules! construct_entry {
+            ($(($value:expr => $variant:path as $type:ty)),*) => {
+                match header.entry_type {
+                    $(
+                        $value if length >= mem::size_of::<$type>() => {
+                            $variant(unsafe { &*(entry_pointer as *const $type) })
+                        }
+                     )*
+                    _ => CratEntry::Other(header),
+                }
+            }
+        }
+
+        #[rustfmt::skip]
+        let entry = construct_entry!(
+            (0 => CratEntry::ComputeUnit as ComputeUnitEntry),
+            (1 => CratEntry::Memory as MemoryEntry),
+            (2 => CratEntry::Cache as CacheEntry)
+        );
+
+        Some(entry)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u8,
+    pub length: u8,
+    _reserved: u16,
+    pub flags: u32,
+}
+
+impl EntryHeader {
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+}
+
+/// Describes the compute resources of a proximity domain: either a set of CPU cores, or a set of GPU SIMDs.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ComputeUnitEntry {
+    pub header: EntryHeader,
+    pub proximity_domain: u32,
+    /// The APIC ID of the first CPU core, or the ID of the first GPU compute unit.
+    pub processor_id_low: u32,
+    pub number_of_cpu_cores: u16,
+    pub number_of_simd_cores: u16,
+    pub max_waves_per_simd: u16,
+    pub io_count: u16,
+    pub hsa_capability: u32,
+    /// The size of the local data store of each compute unit, in KiB.
+    pub lds_size: u32,
+    pub wave_front_size: u8,
+    pub number_of_banks: u8,
+    pub micro_engine_id: u16,
+    pub number_of_arrays: u8,
+    pub compute_units_per_array: u8,
+    pub simds_per_compute_unit: u8,
+    pub max_slots_scratch_per_compute_unit: u8,
+    _reserved: [u8; 4],
+}
+
+impl ComputeUnitEntry {
+    pub fn is_hot_pluggable(&self) -> bool {
+        { self.header.flags }.get_bit(1)
+    }
+
+    pub fn has_cpu_cores(&self) -> bool {
+        { self.header.flags }.get_bit(2)
+    }
+
+    pub fn has_gpu_simds(&self) -> bool {
+        { self.header.flags }.get_bit(3)
+    }
+
+    pub fn has_iommu_v2(&self) -> bool {
+        { self.header.flags }.get_bit(4)
+    }
+}
+
+/// Describes a range of memory that belongs to a proximity domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryEntry {
+    pub header: EntryHeader,
+    pub proximity_domain: u32,
+    base_address_low: u32,
+    base_address_high: u32,
+    length_low: u32,
+    length_high: u32,
+    /// The width of the memory interface, in bits.
+    pub width: u32,
+    pub visibility_type: u8,
+    _reserved: [u8; 7],
+}
+
+impl MemoryEntry {
+    pub fn base_address(&self) -> u64 {
+        (self.base_address_high as u64) << 32 | self.base_address_low as u64
+    }
+
+    pub fn length(&self) -> u64 {
+        (self.length_high as u64) << 32 | self.length_low as u64
+    }
+
+    pub fn is_hot_pluggable(&self) -> bool {
+        { self.header.flags }.get_bit(1)
+    }
+
+    pub fn is_non_volatile(&self) -> bool {
+        { self.header.flags }.get_bit(2)
+    }
+}
+
+/// Describes a cache, and which compute units share it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct CacheEntry {
+    pub header: EntryHeader,
+    /// The ID of the first compute unit (or CPU core) that shares this cache.
+    pub processor_id_low: u32,
+    /// A bitmap of which compute units, relative to `processor_id_low`, share this cache.
+    pub sibling_map: [u8; 32],
+    /// The size of the cache, in KiB.
+    pub cache_size: u32,
+    pub cache_level: u8,
+    pub lines_per_tag: u8,
+    pub cache_line_size: u16,
+    pub associativity: u8,
+    pub cache_properties: u8,
+    /// The latency of the cache, in nanoseconds.
+    pub cache_latency: u16,
+    _reserved: [u8; 8],
+}
+
+impl CacheEntry {
+    pub fn is_data_cache(&self) -> bool {
+        { self.header.flags }.get_bit(1)
+    }
+
+    pub fn is_instruction_cache(&self) -> bool {
+        { self.header.flags }.get_bit(2)
+    }
+
+    pub fn is_cpu_cache(&self) -> bool {
+        { self.header.flags }.get_bit(3)
+    }
+
+    pub fn is_simd_cache(&self) -> bool {
+        { self.header.flags }.get_bit(4)
+    }
+
+    /// Check if the compute unit with ID `processor_id` shares this cache.
+    pub fn is_shared_by(&self, processor_id: u32) -> bool {
+        match processor_id.checked_sub(self.processor_id_low) {
+            Some(bit) if bit < 256 => self.sibling_map[bit as usize / 8].get_bit(bit as usize % 8),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn header<T>(entry_type: u8, flags: u32) -> EntryHeader {
+        EntryHeader { entry_type, length: mem::size_of::<T>() as u8, _reserved: 0, flags }
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body =
+            body_of(&Crat { header: blank_header(), total_entries: 4, number_of_domains: 1, _reserved: [0; 6] });
+        body.extend(bytes_of(&ComputeUnitEntry {
+            header: header::<ComputeUnitEntry>(0, 0b00101),
+            proximity_domain: 0,
+            processor_id_low: 0,
+            number_of_cpu_cores: 8,
+            ..unsafe { zeroed() }
+        }));
+        body.extend(bytes_of(&MemoryEntry {
+            header: header::<MemoryEntry>(1, 0b101),
+            base_address_low: 0x0000_0000,
+            base_address_high: 0x1,
+            length_low: 0x8000_0000,
+            length_high: 0x3,
+            width: 64,
+            ..unsafe { zeroed() }
+        }));
+        let mut sibling_map = [0; 32];
+        sibling_map[0] = 0b0000_0110;
+        body.extend(bytes_of(&CacheEntry {
+            header: header::<CacheEntry>(2, 0b01011),
+            processor_id_low: 4,
+            sibling_map,
+            cache_size: 512,
+            cache_level: 2,
+            ..unsafe { zeroed() }
+        }));
+        // A TLB entry, and a cache entry that is too short to be read as one
+        body.extend(bytes_of(&EntryHeader { entry_type: 3, length: 40, _reserved: 0, flags: 1 }));
+        body.extend([0; 32]);
+        body.extend(bytes_of(&EntryHeader { entry_type: 2, length: 16, _reserved: 0, flags: 1 }));
+        body.extend([0; 8]);
+
+        let buffer = TableBuffer::<Crat>::new(&table(b"CRAT", 1, &body));
+        let crat = buffer.get();
+        assert!(crat.validate().is_ok());
+
+        let entries: Vec<_> = crat.entries().collect();
+        assert_eq!(entries.len(), 5);
+        match entries[0] {
+            CratEntry::ComputeUnit(entry) => {
+                assert!(entry.header.is_enabled());
+                assert!(entry.has_cpu_cores());
+                assert!(!entry.has_gpu_simds());
+                assert_eq!({ entry.number_of_cpu_cores }, 8);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            CratEntry::Memory(entry) => {
+                assert_eq!(entry.base_address(), 0x1_0000_0000);
+                assert_eq!(entry.length(), 0x3_8000_0000);
+                assert!(entry.is_non_volatile());
+                assert!(!entry.is_hot_pluggable());
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[2] {
+            CratEntry::Cache(entry) => {
+                assert!(entry.is_data_cache());
+                assert!(entry.is_cpu_cache());
+                assert!(!entry.is_shared_by(4));
+                assert!(entry.is_shared_by(5));
+                assert!(entry.is_shared_by(6));
+                assert!(!entry.is_shared_by(3));
+                assert!(!entry.is_shared_by(4 + 256));
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        assert!(matches!(entries[3], CratEntry::Other(header) if header.entry_type == 3));
+        assert!(matches!(entries[4], CratEntry::Other(header) if header.entry_type == 2));
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        let mut body =
+            body_of(&Crat { header: blank_header(), total_entries: 1, number_of_domains: 1, _reserved: [0; 6] });
+        let entry = bytes_of(&MemoryEntry { header: header::<MemoryEntry>(1, 1), ..unsafe { zeroed() } });
+        body.extend(&entry[..entry.len() - 1]);
+        let buffer = TableBuffer::<Crat>::new(&table(b"CRAT", 1, &body));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{marker::PhantomData, mem, slice};
+
+/// Represents the Core System Resource Table (CSRT), which describes platform resources that are shared between
+/// devices, but that aren't described in the namespace, such as DMA controllers, timers, and interrupt
+/// controllers. Resources are organised into groups (usually one per vendor device), each of which contains a set
+/// of resource descriptors. Most of the contents are vendor-defined, so they are exposed as byte slices.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Csrt {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid CSRT.
+unsafe impl AcpiTable for Csrt {
+    const SIGNATURE: Signature = Signature::CSRT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Csrt {
+    pub fn resource_groups(&self) -> ResourceGroupIter<'_> {
+        ResourceGroupIter {
+            pointer: unsafe { (self as *const Csrt as *const u8).add(mem::size_of::<Csrt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Csrt>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ResourceGroupIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for ResourceGroupIter<'a> {
+    type Item = &'a ResourceGroup;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<ResourceGroup>() {
+            return None;
+        }
+
+        let group = unsafe { &*(self.pointer as *const ResourceGroup) };
+        let length = group.length as usize;
+        if length < mem::size_of::<ResourceGroup>() + group.shared_info_length as usize
+            || length > self.remaining_length
+        {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(group)
+    }
+}
+
+/// A group of resources, which are usually all provided by the same device. The group header is followed by
+/// information shared by all of the resources in the group, and then the resource descriptors.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ResourceGroup {
+    pub length: u32,
+    pub vendor_id: u32,
+    pub subvendor_id: u32,
+    pub device_id: u16,
+    pub subdevice_id: u16,
+    pub revision: u16,
+    _reserved: u16,
+    pub shared_info_length: u32,
+}
+
+impl ResourceGroup {
+    /// The vendor-defined information shared by all of the resources in the group.
+    pub fn shared_info(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                (self as *const ResourceGroup as *const u8).add(mem::size_of::<ResourceGroup>()),
+                self.shared_info_length as usize,
+            )
+        }
+    }
+
+    pub fn descriptors(&self) -> ResourceDescriptorIter<'_> {
+        let offset = mem::size_of::<ResourceGroup>() + self.shared_info_length as usize;
+
+        ResourceDescriptorIter {
+            pointer: unsafe { (self as *const ResourceGroup as *const u8).add(offset) },
+            remaining_length: (self.length as usize).saturating_sub(offset),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ResourceDescriptorIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for ResourceDescriptorIter<'a> {
+    type Item = &'a ResourceDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<ResourceDescriptor>() {
+            return None;
+        }
+
+        let descriptor = unsafe { &*(self.pointer as *const ResourceDescriptor) };
+        let length = descriptor.length as usize;
+        if length < mem::size_of::<ResourceDescriptor>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(descriptor)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourceType {
+    InterruptLine,
+    InterruptController,
+    Timer,
+    DmaChannel,
+    DmaController,
+    Reserved { resource_type: u16, resource_subtype: u16 },
+}
+
+/// Describes a single resource. The descriptor header is followed by vendor-defined data.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ResourceDescriptor {
+    pub length: u32,
+    pub resource_type: u16,
+    pub resource_subtype: u16,
+    /// Identifies the resource within its group. Devices in the namespace usually refer to a resource with this,
+    /// through a `FixedDMA` descriptor or similar.
+    pub uid: u32,
+}
+
+impl ResourceDescriptor {
+    pub fn kind(&self) -> ResourceType {
+        match (self.resource_type, self.resource_subtype) {
+            (1, 0) => ResourceType::InterruptLine,
+            (1, 1) => ResourceType::InterruptController,
+            (2, 0) => ResourceType::Timer,
+            (3, 0) => ResourceType::DmaChannel,
+            (3, 1) => ResourceType::DmaController,
+            (resource_type, resource_subtype) => ResourceType::Reserved { resource_type, resource_subtype },
+        }
+    }
+
+    pub fn vendor_data(&self) -> &[u8] {
+        unsafe {
+            slice::from_raw_parts(
+                (self as *const ResourceDescriptor as *const u8).add(mem::size_of::<ResourceDescriptor>()),
+                self.length as usize - mem::size_of::<ResourceDescriptor>(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn descriptor(resource_type: u16, resource_subtype: u16, uid: u32, vendor_data: &[u8]) -> Vec<u8> {
+        let mut bytes = bytes_of(&ResourceDescriptor {
+            length: (mem::size_of::<ResourceDescriptor>() + vendor_data.len()) as u32,
+            resource_type,
+            resource_subtype,
+            uid,
+        });
+        bytes.extend(vendor_data);
+        bytes
+    }
+
+    fn group(shared_info: &[u8], descriptors: &[Vec<u8>]) -> Vec<u8> {
+        let length =
+            mem::size_of::<ResourceGroup>() + shared_info.len() + descriptors.iter().map(Vec::len).sum::<usize>();
+        let mut bytes = bytes_of(&ResourceGroup {
+            length: length as u32,
+            vendor_id: u32::from_le_bytes(*b"INTL"),
+            subvendor_id: 0,
+            device_id: 0x9c60,
+            subdevice_id: 0,
+            revision: 1,
+            _reserved: 0,
+            shared_info_length: shared_info.len() as u32,
+        });
+        bytes.extend(shared_info);
+        for descriptor in descriptors {
+            bytes.extend(descriptor);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_resource_groups() {
+        let mut body =
+            group(&[0xaa; 8], &[descriptor(3, 1, 0, &[1, 2]), descriptor(3, 0, 1, &[]), descriptor(3, 0, 2, &[])]);
+        body.extend(group(&[], &[descriptor(7, 0, 0, &[])]));
+
+        let buffer = TableBuffer::<Csrt>::new(&table(b"CSRT", 0, &body));
+        let csrt = buffer.get();
+        assert!(csrt.validate().is_ok());
+
+        let groups: Vec<_> = csrt.resource_groups().collect();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].shared_info(), &[0xaa; 8]);
+
+        let descriptors: Vec<_> = groups[0].descriptors().collect();
+        assert_eq!(descriptors.len(), 3);
+        assert_eq!(descriptors[0].kind(), ResourceType::DmaController);
+        assert_eq!(descriptors[0].vendor_data(), &[1, 2]);
+        assert_eq!(descriptors[2].kind(), ResourceType::DmaChannel);
+        assert_eq!({ descriptors[2].uid }, 2);
+        assert_eq!(
+            groups[1].descriptors().next().unwrap().kind(),
+            ResourceType::Reserved { resource_type: 7, resource_subtype: 0 }
+        );
+    }
+
+    #[test]
+    fn test_truncated_structures() {
+        // The group is too short for its shared info, so the list of groups ends
+        let mut bytes = group(&[0; 8], &[]);
+        bytes[0..4].copy_from_slice(&(mem::size_of::<ResourceGroup>() as u32 + 4).to_le_bytes());
+        let buffer = TableBuffer::<Csrt>::new(&table(b"CSRT", 0, &bytes[..mem::size_of::<ResourceGroup>() + 4]));
+        assert_eq!(buffer.get().resource_groups().count(), 0);
+
+        // A descriptor that runs past the end of its group ends the list of descriptors
+        let mut bytes = group(&[], &[descriptor(3, 0, 0, &[]), descriptor(3, 0, 1, &[0; 4])]);
+        let length = bytes.len() as u32 - 1;
+        bytes[0..4].copy_from_slice(&length.to_le_bytes());
+        let buffer = TableBuffer::<Csrt>::new(&table(b"CSRT", 0, &bytes));
+        let group = buffer.get().resource_groups().next().unwrap();
+        assert_eq!(group.descriptors().count(), 1);
+    }
+}
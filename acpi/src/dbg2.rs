@@ -0,0 +1,268 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+    spcr::SerialInterfaceType,
+    AcpiResult,
+    AcpiTable,
+};
+use core::{marker::PhantomData, mem, ptr, slice, str};
+
+/// Represents the Debug Port Table 2 (DBG2), which describes the debug devices (serial ports, USB controllers,
+/// etc.) available on the platform. You can iterate over the devices in the table with [`Dbg2::devices`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Dbg2 {
+    pub header: SdtHeader,
+    /// The offset of the first debug device information structure from the start of the table.
+    pub device_info_offset: u32,
+    pub number_of_devices: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid DBG2.
+unsafe impl AcpiTable for Dbg2 {
+    const SIGNATURE: Signature = Signature::DBG2;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Dbg2 {
+    pub fn devices(&self) -> DebugDeviceInfoIter<'_> {
+        let offset = self.device_info_offset as usize;
+        let table_length = self.header.length as usize;
+
+        DebugDeviceInfoIter {
+            pointer: unsafe { (self as *const Dbg2 as *const u8).add(offset) },
+            remaining_length: if offset >= mem::size_of::<Dbg2>() {
+                table_length.saturating_sub(offset)
+            } else {
+                0
+            },
+            remaining_devices: self.number_of_devices,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DebugDeviceInfoIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_devices: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for DebugDeviceInfoIter<'a> {
+    type Item = &'a DebugDeviceInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_devices == 0 || self.remaining_length < mem::size_of::<DebugDeviceInfo>() {
+            return None;
+        }
+
+        let device = unsafe { &*(self.pointer as *const DebugDeviceInfo) };
+        let length = device.length as usize;
+        if length < mem::size_of::<DebugDeviceInfo>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_devices -= 1;
+
+        Some(device)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DebugPortType {
+    Serial(SerialInterfaceType),
+    Ieee1394,
+    UsbXhci,
+    UsbEhci,
+    /// A network debug port. The subtype of these ports is the PCI vendor ID of the network controller.
+    Net(u16),
+    Reserved {
+        port_type: u16,
+        port_subtype: u16,
+    },
+}
+
+/// Describes a single debug device. The offsets in this structure are relative to the start of the structure.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DebugDeviceInfo {
+    pub revision: u8,
+    pub length: u16,
+    pub number_of_generic_address_registers: u8,
+    pub namespace_string_length: u16,
+    pub namespace_string_offset: u16,
+    pub oem_data_length: u16,
+    pub oem_data_offset: u16,
+    port_type: u16,
+    port_subtype: u16,
+    _reserved: u16,
+    pub base_address_register_offset: u16,
+    pub address_size_offset: u16,
+}
+
+impl DebugDeviceInfo {
+    pub fn port_type(&self) -> DebugPortType {
+        match (self.port_type, self.port_subtype) {
+            (0x8000, subtype) => DebugPortType::Serial(SerialInterfaceType::from(subtype)),
+            (0x8001, 0x0000) => DebugPortType::Ieee1394,
+            (0x8002, 0x0000) => DebugPortType::UsbXhci,
+            (0x8002, 0x0001) => DebugPortType::UsbEhci,
+            (0x8003, vendor_id) => DebugPortType::Net(vendor_id),
+            (port_type, port_subtype) => DebugPortType::Reserved { port_type, port_subtype },
+        }
+    }
+
+    /// The registers of the device. Devices have one Generic Address Structure for each of their Base Address
+    /// Registers.
+    pub fn base_address_registers(&self) -> impl Iterator<Item = AcpiResult<GenericAddress>> + '_ {
+        let offset = self.base_address_register_offset as usize;
+        let count = self.array_len(offset, mem::size_of::<RawGenericAddress>());
+
+        (0..count).map(move |i| {
+            let raw =
+                unsafe { self.read_at::<RawGenericAddress>(offset + i * mem::size_of::<RawGenericAddress>()) };
+            GenericAddress::from_raw(raw)
+        })
+    }
+
+    /// The size, in bytes, of the region described by each of the base address registers.
+    pub fn address_sizes(&self) -> impl Iterator<Item = u32> + '_ {
+        let offset = self.address_size_offset as usize;
+        let count = self.array_len(offset, mem::size_of::<u32>());
+
+        (0..count).map(move |i| unsafe { self.read_at::<u32>(offset + i * mem::size_of::<u32>()) })
+    }
+
+    /// The fully-qualified path of the device in the ACPI namespace, or `"."` if the device isn't described in the
+    /// namespace. Returns `None` if the name isn't valid ASCII.
+    pub fn namespace_string(&self) -> Option<&str> {
+        let bytes = self.bytes_at(self.namespace_string_offset, self.namespace_string_length)?;
+        let bytes = match bytes.iter().position(|&byte| byte == 0) {
+            Some(nul) => &bytes[..nul],
+            None => bytes,
+        };
+
+        str::from_utf8(bytes).ok()
+    }
+
+    /// Returns the OEM-specific data for the device, if present.
+    pub fn oem_data(&self) -> Option<&[u8]> {
+        if self.oem_data_offset == 0 {
+            return None;
+        }
+
+        self.bytes_at(self.oem_data_offset, self.oem_data_length)
+    }
+
+    /// Work out how many array elements of `element_size` can be read from `offset`, without reading past the end
+    /// of the structure.
+    fn array_len(&self, offset: usize, element_size: usize) -> usize {
+        let available = (self.length as usize).saturating_sub(offset) / element_size;
+        usize::min(self.number_of_generic_address_registers as usize, available)
+    }
+
+    fn bytes_at(&self, offset: u16, length: u16) -> Option<&[u8]> {
+        let (offset, length) = (offset as usize, length as usize);
+        if offset < mem::size_of::<DebugDeviceInfo>() || offset + length > self.length as usize {
+            return None;
+        }
+
+        Some(unsafe { slice::from_raw_parts((self as *const DebugDeviceInfo as *const u8).add(offset), length) })
+    }
+
+    /// Read a `T` from `offset` bytes after the start of this structure.
+    ///
+    /// ### Safety: `offset + size_of::<T>()` must be within the length of this structure.
+    unsafe fn read_at<T: Copy>(&self, offset: usize) -> T {
+        unsafe { ptr::read_unaligned((self as *const DebugDeviceInfo as *const u8).add(offset) as *const T) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn device(port_type: u16, port_subtype: u16, name: &[u8]) -> Vec<u8> {
+        let registers_offset = mem::size_of::<DebugDeviceInfo>();
+        let sizes_offset = registers_offset + mem::size_of::<RawGenericAddress>();
+        let name_offset = sizes_offset + mem::size_of::<u32>();
+
+        let mut bytes = bytes_of(&DebugDeviceInfo {
+            revision: 0,
+            length: (name_offset + name.len()) as u16,
+            number_of_generic_address_registers: 1,
+            namespace_string_length: name.len() as u16,
+            namespace_string_offset: name_offset as u16,
+            oem_data_length: 0,
+            oem_data_offset: 0,
+            port_type,
+            port_subtype,
+            _reserved: 0,
+            base_address_register_offset: registers_offset as u16,
+            address_size_offset: sizes_offset as u16,
+        });
+        bytes.extend(bytes_of(&RawGenericAddress {
+            address_space: 0,
+            bit_width: 32,
+            bit_offset: 0,
+            access_size: 3,
+            address: 0x900_0000,
+        }));
+        bytes.extend(0x1000u32.to_le_bytes());
+        bytes.extend(name);
+        bytes
+    }
+
+    #[test]
+    fn test_devices() {
+        let mut body = Vec::new();
+        body.extend((mem::size_of::<Dbg2>() as u32).to_le_bytes());
+        body.extend(3u32.to_le_bytes());
+        body.extend(device(0x8000, 0x0003, b"\\_SB.COM0\0"));
+        body.extend(device(0x8002, 0x0000, b".\0"));
+        // A device that claims to be shorter than the structure ends the list
+        let mut short = device(0x8000, 0x0000, b"");
+        short[1..3].copy_from_slice(&10u16.to_le_bytes());
+        body.extend(short);
+
+        let buffer = TableBuffer::<Dbg2>::new(&table(b"DBG2", 0, &body));
+        let dbg2 = buffer.get();
+        assert!(dbg2.validate().is_ok());
+
+        let devices: Vec<_> = dbg2.devices().collect();
+        assert_eq!(devices.len(), 2);
+
+        assert_eq!(devices[0].port_type(), DebugPortType::Serial(SerialInterfaceType::ArmPl011));
+        assert_eq!(devices[0].namespace_string(), Some("\\_SB.COM0"));
+        assert_eq!(devices[0].oem_data(), None);
+        let registers: Vec<_> = devices[0].base_address_registers().collect();
+        assert_eq!(registers.len(), 1);
+        assert_eq!(registers[0].as_ref().unwrap().address, 0x900_0000);
+        assert!(devices[0].address_sizes().eq([0x1000]));
+
+        assert_eq!(devices[1].port_type(), DebugPortType::UsbXhci);
+        assert_eq!(devices[1].namespace_string(), Some("."));
+    }
+
+    #[test]
+    fn test_truncated_device() {
+        let mut body = Vec::new();
+        body.extend((mem::size_of::<Dbg2>() as u32).to_le_bytes());
+        body.extend(1u32.to_le_bytes());
+        let device = device(0x8000, 0x0000, b"\\_SB.COM0\0");
+        body.extend(&device[..device.len() - 1]);
+
+        let buffer = TableBuffer::<Dbg2>::new(&table(b"DBG2", 0, &body));
+        assert_eq!(buffer.get().devices().count(), 0);
+    }
+}
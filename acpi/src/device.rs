@@ -0,0 +1,221 @@
+//! Helpers for finding devices in the namespace, and evaluating their objects.
+
+use crate::{AcpiError, AcpiResult};
+use alloc::{vec, vec::Vec};
+use aml::{
+    device::DeviceMatcher,
+    value::{AmlType, Args},
+    AmlContext,
+    AmlError,
+    AmlName,
+    AmlValue,
+    LevelType,
+};
+
+/// Find the devices in the namespace with a `_HID` of `hid`.
+pub(crate) fn find_devices_with_hid(context: &mut AmlContext, hid: &str) -> AcpiResult<Vec<AmlName>> {
+    Ok(context
+        .find_devices(&DeviceMatcher::Hid(hid))
+        .map_err(AcpiError::Aml)?
+        .into_iter()
+        .map(|device| device.path)
+        .collect())
+}
+
+/// Find the processors in the namespace: both legacy `Processor` objects, and processor devices (with a `_HID` of
+/// `ACPI0007`).
+pub fn processors(context: &mut AmlContext) -> AcpiResult<Vec<AmlName>> {
+    let mut processors = Vec::new();
+    context
+        .namespace
+        .traverse(|path, level| match level.typ {
+            LevelType::Processor => {
+                processors.push(path.clone());
+                Ok(false)
+            }
+            LevelType::Scope | LevelType::Device => Ok(true),
+            _ => Ok(false),
+        })
+        .map_err(AcpiError::Aml)?;
+
+    processors.extend(find_devices_with_hid(context, "ACPI0007")?);
+    Ok(processors)
+}
+
+/// Evaluate the object `name` of `device`, with the given arguments. Returns `None` if the object doesn't exist.
+pub(crate) fn evaluate_optional(
+    context: &mut AmlContext,
+    device: &AmlName,
+    name: &str,
+    args: Args,
+) -> AcpiResult<Option<AmlValue>> {
+    let path = AmlName::from_str(name).map_err(AcpiError::Aml)?.resolve(device).map_err(AcpiError::Aml)?;
+    match context.invoke_method(&path, args) {
+        Ok(value) => Ok(Some(value)),
+        Err(AmlError::ValueDoesNotExist(_)) => Ok(None),
+        Err(err) => Err(AcpiError::Aml(err)),
+    }
+}
+
+/// Evaluate the object `name` of `device`, with the given arguments.
+pub(crate) fn evaluate(
+    context: &mut AmlContext,
+    device: &AmlName,
+    name: &str,
+    args: Args,
+) -> AcpiResult<AmlValue> {
+    let path = AmlName::from_str(name).map_err(AcpiError::Aml)?.resolve(device).map_err(AcpiError::Aml)?;
+    context.invoke_method(&path, args).map_err(AcpiError::Aml)
+}
+
+/// Evaluate the object `name` of `device`, which should return a package of references to other objects (e.g. the
+/// `_ALx` objects of a thermal zone, or the `_PRx` objects of a device). Returns `None` if the object doesn't
+/// exist.
+pub(crate) fn evaluate_references(
+    context: &mut AmlContext,
+    device: &AmlName,
+    name: &str,
+) -> AcpiResult<Option<Vec<AmlName>>> {
+    let elements = match evaluate_optional(context, device, name, Args::EMPTY)? {
+        Some(AmlValue::Package(elements)) => elements,
+        Some(other) => {
+            return Err(AcpiError::Aml(AmlError::IncompatibleValueConversion {
+                current: other.type_of(),
+                target: AmlType::Package,
+            }))
+        }
+        None => return Ok(None),
+    };
+
+    let mut references = Vec::new();
+    for element in elements.iter() {
+        if let AmlValue::String(name) = element {
+            let name = AmlName::from_str(name).map_err(AcpiError::Aml)?;
+            let path = match context.namespace.search(&name, device) {
+                Ok((path, _)) => path,
+                Err(_) => name.resolve(device).map_err(AcpiError::Aml)?,
+            };
+            references.push(path);
+        }
+    }
+
+    Ok(Some(references))
+}
+
+/// A UUID, such as those used to identify the interfaces of `_DSM` objects.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Uuid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Uuid {
+    pub const fn new(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Uuid {
+        Uuid { data1, data2, data3, data4 }
+    }
+
+    /// Parse a UUID from its string form, e.g. `"e5c937d0-3553-4d7a-9117-ea4d19c3434d"`, which is the form used
+    /// by `ToUUID` in ASL.
+    pub fn parse(uuid: &str) -> Option<Uuid> {
+        let bytes = uuid.as_bytes();
+        if bytes.len() != 36 || [8, 13, 18, 23].iter().any(|&i| bytes[i] != b'-') {
+            return None;
+        }
+
+        let hex = |range: core::ops::Range<usize>| u64::from_str_radix(uuid.get(range)?, 16).ok();
+        let mut data4 = [0; 8];
+        for (i, byte) in data4.iter_mut().enumerate() {
+            // The first two bytes of `data4` are before the last hyphen
+            let start = if i < 2 { 19 + i * 2 } else { 24 + (i - 2) * 2 };
+            *byte = hex(start..(start + 2))? as u8;
+        }
+
+        Some(Uuid { data1: hex(0..8)? as u32, data2: hex(9..13)? as u16, data3: hex(14..18)? as u16, data4 })
+    }
+
+    /// The UUID in the mixed-endian byte order used by AML buffers (and produced by `ToUUID`).
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[0..4].copy_from_slice(&self.data1.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.data2.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.data3.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.data4);
+        bytes
+    }
+}
+
+/// The functions a `_DSM` supports for a UUID and revision, from the bitmask returned by function 0.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DsmFunctions {
+    mask: Vec<u8>,
+}
+
+impl DsmFunctions {
+    /// Check whether `function` is supported. If bit 0 of the mask is clear, no functions are supported (not even
+    /// function 0).
+    pub fn supports(&self, function: u64) -> bool {
+        let bit = |index: u64| {
+            self.mask.get((index / 8) as usize).map(|byte| byte & (1 << (index % 8)) != 0).unwrap_or(false)
+        };
+        bit(0) && bit(function)
+    }
+
+    /// The supported functions, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..(self.mask.len() as u64 * 8)).filter(move |&function| self.supports(function))
+    }
+}
+
+/// A device (or other object with sub-objects, such as a processor) in the namespace.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Device {
+    path: AmlName,
+}
+
+impl Device {
+    pub fn new(path: AmlName) -> Device {
+        Device { path }
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// Evaluate function `function` of the device's `_DSM` (Device Specific Method), for the interface
+    /// identified by `uuid` and `revision`. `args` are passed to the function as the package in `Arg3`. Returns
+    /// `None` if the device doesn't have a `_DSM`.
+    ///
+    /// Functions other than function 0 should only be evaluated if [`Device::dsm_functions`] says that they are
+    /// supported.
+    pub fn evaluate_dsm(
+        &self,
+        context: &mut AmlContext,
+        uuid: &Uuid,
+        revision: u64,
+        function: u64,
+        args: Vec<AmlValue>,
+    ) -> AcpiResult<Option<AmlValue>> {
+        let args = Args::from_list(vec![
+            AmlValue::buffer(uuid.to_bytes().to_vec()),
+            AmlValue::Integer(revision),
+            AmlValue::Integer(function),
+            AmlValue::package(args),
+        ])
+        .unwrap();
+        evaluate_optional(context, &self.path, "_DSM", args)
+    }
+
+    /// Find the functions of the device's `_DSM` that are supported for `uuid` and `revision`, by evaluating
+    /// function 0. If the device doesn't have a `_DSM`, no functions are supported.
+    pub fn dsm_functions(&self, context: &mut AmlContext, uuid: &Uuid, revision: u64) -> AcpiResult<DsmFunctions> {
+        let mask = match self.evaluate_dsm(context, uuid, revision, 0, Vec::new())? {
+            Some(AmlValue::Buffer(bytes)) => bytes.lock().clone(),
+            // Some firmware returns the mask as an integer
+            Some(AmlValue::Integer(value)) => value.to_le_bytes().to_vec(),
+            Some(_) | None => Vec::new(),
+        };
+        Ok(DsmFunctions { mask })
+    }
+}
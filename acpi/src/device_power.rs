@@ -0,0 +1,290 @@
+use crate::{device, AcpiError, AcpiResult};
+use alloc::{collections::BTreeMap, format, vec::Vec};
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+
+#[derive(Debug)]
+pub enum DevicePowerError {
+    /// The device doesn't support the power state.
+    StateNotSupported(DevicePowerState),
+    /// A `_PRx` object of the device refers to an object that isn't a `PowerResource`.
+    NotAPowerResource(AmlName),
+    /// The power resource was released more times than it was acquired.
+    PowerResourceNotAcquired(AmlName),
+}
+
+/// The power states of a device, from fully on (`D0`) to fully off (`D3Cold`).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum DevicePowerState {
+    D0,
+    D1,
+    D2,
+    /// The device is off, but its power resources for `D3Hot` (in `_PR3`) are still on, so it is still
+    /// enumerable.
+    D3Hot,
+    /// The device is off, and all of its power resources are off.
+    D3Cold,
+}
+
+impl DevicePowerState {
+    /// The index of the `_PSx` and `_PRx` objects of the state.
+    fn object_index(self) -> usize {
+        match self {
+            DevicePowerState::D0 => 0,
+            DevicePowerState::D1 => 1,
+            DevicePowerState::D2 => 2,
+            DevicePowerState::D3Hot | DevicePowerState::D3Cold => 3,
+        }
+    }
+}
+
+/// A `PowerResource` object, which represents a power plane or clock that can be shared between several devices.
+#[derive(Clone, Debug)]
+pub struct PowerResource {
+    path: AmlName,
+    /// The deepest system sleep state that the system can enter while the resource is on.
+    pub system_level: u8,
+    /// Power resources must be turned on in increasing order, and off in decreasing order, of `resource_order`.
+    pub resource_order: u16,
+    references: usize,
+}
+
+impl PowerResource {
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// The number of devices that currently need the resource to be on.
+    pub fn references(&self) -> usize {
+        self.references
+    }
+
+    /// Check whether the resource is on, by evaluating its `_STA`.
+    pub fn is_on(&self, context: &mut AmlContext) -> AcpiResult<bool> {
+        let status = device::evaluate(context, &self.path, "_STA", Args::EMPTY)?;
+        Ok(status.as_integer(context).map_err(AcpiError::Aml)? & 1 != 0)
+    }
+}
+
+/// The power resources of the system, which are reference counted so that a resource shared by several devices is
+/// only turned off once none of them need it.
+#[derive(Clone, Debug, Default)]
+pub struct PowerResources {
+    resources: BTreeMap<AmlName, PowerResource>,
+}
+
+impl PowerResources {
+    pub fn new() -> PowerResources {
+        PowerResources { resources: BTreeMap::new() }
+    }
+
+    /// The power resources that have been referenced by a device so far.
+    pub fn resources(&self) -> impl Iterator<Item = &PowerResource> {
+        self.resources.values()
+    }
+
+    pub fn get(&self, path: &AmlName) -> Option<&PowerResource> {
+        self.resources.get(path)
+    }
+
+    /// Take a reference to each of the resources in `paths`, turning on (with `_ON`) those that aren't already on.
+    /// Resources are turned on in order of increasing `resource_order`.
+    pub fn acquire(&mut self, context: &mut AmlContext, paths: &[AmlName]) -> AcpiResult<()> {
+        for path in self.sorted(context, paths)? {
+            let resource = self.resources.get_mut(&path).unwrap();
+            if resource.references == 0 && !resource.is_on(context)? {
+                device::evaluate(context, &path, "_ON", Args::EMPTY)?;
+            }
+            resource.references += 1;
+        }
+        Ok(())
+    }
+
+    /// Release a reference to each of the resources in `paths`, turning off (with `_OFF`) those that are no
+    /// longer referenced. Resources are turned off in order of decreasing `resource_order`.
+    pub fn release(&mut self, context: &mut AmlContext, paths: &[AmlName]) -> AcpiResult<()> {
+        for path in self.sorted(context, paths)?.into_iter().rev() {
+            let resource = self.resources.get_mut(&path).unwrap();
+            if resource.references == 0 {
+                return Err(AcpiError::DevicePower(DevicePowerError::PowerResourceNotAcquired(path)));
+            }
+
+            resource.references -= 1;
+            if resource.references == 0 {
+                device::evaluate(context, &path, "_OFF", Args::EMPTY)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether all of the resources in `paths` are on.
+    fn all_on(&mut self, context: &mut AmlContext, paths: &[AmlName]) -> AcpiResult<bool> {
+        for path in self.sorted(context, paths)? {
+            if !self.resources[&path].is_on(context)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Find the resources in `paths` (adding them to the set of known resources, if they haven't been seen
+    /// before), and sort them by their `resource_order`.
+    fn sorted(&mut self, context: &AmlContext, paths: &[AmlName]) -> AcpiResult<Vec<AmlName>> {
+        for path in paths {
+            if !self.resources.contains_key(path) {
+                let (system_level, resource_order) = match context.namespace.get_by_path(path) {
+                    Ok(AmlValue::PowerResource { system_level, resource_order }) => (system_level, resource_order),
+                    _ => return Err(AcpiError::DevicePower(DevicePowerError::NotAPowerResource(path.clone()))),
+                };
+                self.resources.insert(
+                    path.clone(),
+                    PowerResource { path: path.clone(), system_level, resource_order, references: 0 },
+                );
+            }
+        }
+
+        let mut sorted = paths.to_vec();
+        sorted.sort_by_key(|path| self.resources[path].resource_order);
+        Ok(sorted)
+    }
+}
+
+/// The power management objects of a device: the power resources it needs in each power state (`_PR0` to `_PR3`),
+/// and whether it has methods to put it into each state (`_PS0` to `_PS3`).
+#[derive(Clone, Debug)]
+pub struct DevicePower {
+    device: AmlName,
+    resources: [Option<Vec<AmlName>>; 4],
+    methods: [bool; 4],
+    state: Option<DevicePowerState>,
+}
+
+impl DevicePower {
+    /// Read the power management objects of `device`, and find its current power state (from `_PSC`, or
+    /// otherwise from which of its power resources are on). The device takes references to the power resources
+    /// of its current state.
+    pub fn new(
+        context: &mut AmlContext,
+        resources: &mut PowerResources,
+        device: AmlName,
+    ) -> AcpiResult<DevicePower> {
+        let mut power =
+            DevicePower { device, resources: [None, None, None, None], methods: [false; 4], state: None };
+        for index in 0..4 {
+            power.resources[index] =
+                device::evaluate_references(context, &power.device, &format!("_PR{}", index))?;
+
+            let method = AmlName::from_str(&format!("_PS{}", index)).unwrap().resolve(&power.device).unwrap();
+            power.methods[index] = context.namespace.get_by_path(&method).is_ok();
+        }
+
+        power.state = power.read_state(context, resources)?;
+        if let Some(state) = power.state {
+            resources.acquire(context, power.state_resources(state))?;
+        }
+        Ok(power)
+    }
+
+    pub fn device(&self) -> &AmlName {
+        &self.device
+    }
+
+    /// The power state the device was last put into, or the state it was found in. This is `None` if the state
+    /// of the device couldn't be determined, and it hasn't been put into a state since.
+    pub fn state(&self) -> Option<DevicePowerState> {
+        self.state
+    }
+
+    /// The power resources the device needs in `state`.
+    pub fn state_resources(&self, state: DevicePowerState) -> &[AmlName] {
+        match state {
+            DevicePowerState::D3Cold => &[],
+            state => self.resources[state.object_index()].as_deref().unwrap_or(&[]),
+        }
+    }
+
+    /// Check whether the device can be put into `state`. Every device supports `D0` and `D3Cold`, and `D1`,
+    /// `D2`, and `D3Hot` are supported if the device has power resources (or a `_PSx` method, for `D1` and `D2`)
+    /// for them.
+    pub fn supports(&self, state: DevicePowerState) -> bool {
+        match state {
+            DevicePowerState::D0 | DevicePowerState::D3Cold => true,
+            DevicePowerState::D1 | DevicePowerState::D2 => {
+                self.methods[state.object_index()] || self.resources[state.object_index()].is_some()
+            }
+            DevicePowerState::D3Hot => self.resources[3].is_some(),
+        }
+    }
+
+    /// Put the device into `state`. When moving to a higher-power state, the power resources of the new state are
+    /// turned on before `_PSx` is evaluated, and when moving to a lower-power state, `_PSx` is evaluated first.
+    /// The resources of the old state that aren't needed by the new state are then turned off.
+    pub fn set_power_state(
+        &mut self,
+        context: &mut AmlContext,
+        resources: &mut PowerResources,
+        state: DevicePowerState,
+    ) -> AcpiResult<()> {
+        if !self.supports(state) {
+            return Err(AcpiError::DevicePower(DevicePowerError::StateNotSupported(state)));
+        }
+        if self.state == Some(state) {
+            return Ok(());
+        }
+
+        // If the current state is unknown, the device is treated as if it were moving to a higher-power state
+        let powering_up = self.state.map(|current| state < current).unwrap_or(true);
+        if !powering_up {
+            self.evaluate_ps(context, state)?;
+        }
+
+        resources.acquire(context, self.state_resources(state))?;
+        if powering_up {
+            self.evaluate_ps(context, state)?;
+        }
+        if let Some(current) = self.state {
+            resources.release(context, self.state_resources(current))?;
+        }
+
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn evaluate_ps(&self, context: &mut AmlContext, state: DevicePowerState) -> AcpiResult<()> {
+        if self.methods[state.object_index()] {
+            device::evaluate(context, &self.device, &format!("_PS{}", state.object_index()), Args::EMPTY)?;
+        }
+        Ok(())
+    }
+
+    fn read_state(
+        &self,
+        context: &mut AmlContext,
+        resources: &mut PowerResources,
+    ) -> AcpiResult<Option<DevicePowerState>> {
+        if let Some(psc) = device::evaluate_optional(context, &self.device, "_PSC", Args::EMPTY)? {
+            return Ok(match psc.as_integer(context).map_err(AcpiError::Aml)? {
+                0 => Some(DevicePowerState::D0),
+                1 => Some(DevicePowerState::D1),
+                2 => Some(DevicePowerState::D2),
+                _ if self.resources[3].is_some() => Some(DevicePowerState::D3Hot),
+                _ => Some(DevicePowerState::D3Cold),
+            });
+        }
+
+        /*
+         * Without `_PSC`, the device is in the highest-power state whose power resources are all on. If it has no
+         * power resources, its state can't be determined.
+         */
+        if self.resources.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+        for state in [DevicePowerState::D0, DevicePowerState::D1, DevicePowerState::D2, DevicePowerState::D3Hot] {
+            if let Some(ref state_resources) = self.resources[state.object_index()] {
+                if resources.all_on(context, state_resources)? {
+                    return Ok(Some(state));
+                }
+            }
+        }
+        Ok(Some(DevicePowerState::D3Cold))
+    }
+}
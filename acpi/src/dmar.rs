@@ -0,0 +1,443 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, slice, str};
+
+/// Represents the DMA Remapping Table (DMAR), which describes the Intel VT-d DMA and interrupt remapping hardware
+/// on the platform. You can iterate over the remapping structures in the table with [`Dmar::entries`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Dmar {
+    pub header: SdtHeader,
+    /// The maximum DMA physical addressability supported by the platform, minus one. For example, a value of `38`
+    /// means the platform supports 39-bit DMA addresses.
+    pub host_address_width: u8,
+    pub flags: u8,
+    _reserved: [u8; 10],
+}
+
+/// ### Safety: Implementation properly represents a valid DMAR.
+unsafe impl AcpiTable for Dmar {
+    const SIGNATURE: Signature = Signature::DMAR;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Dmar {
+    pub fn entries(&self) -> DmarEntryIter<'_> {
+        DmarEntryIter {
+            pointer: unsafe { (self as *const Dmar as *const u8).add(mem::size_of::<Dmar>()) },
+            remaining_length: self.header.length - mem::size_of::<Dmar>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn supports_interrupt_remapping(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    pub fn x2apic_opt_out(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+
+    pub fn dma_control_opt_in(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+}
+
+#[derive(Debug)]
+pub struct DmarEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum DmarEntry<'a> {
+    Drhd(&'a DrhdEntry),
+    Rmrr(&'a RmrrEntry),
+    Atsr(&'a AtsrEntry),
+    Rhsa(&'a RhsaEntry),
+    Andd(&'a AnddEntry),
+    Satc(&'a SatcEntry),
+    Sidp(&'a SidpEntry),
+}
+
+impl<'a> Iterator for DmarEntryIter<'a> {
+    type Item = DmarEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length == 0 || header.length as u32 > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length as u32;
+
+            macro_rules! construct_entry {
+                ($entry_type:expr,
+                 $entry_pointer:expr,
+                 $(($value:expr => $variant:path as $type:ty)),*
+                ) => {
+                    match $entry_type {
+                        $(
+                            $value if header.length as usize >= mem::size_of::<$type>() => {
+                                return Some($variant(unsafe {
+                                    &*($entry_pointer as *const $type)
+                                }))
+                            }
+                         )*
+
+                        /*
+                         * These entry types are reserved by the VT-d specification. We should skip them if they
+                         * appear in a real DMAR, along with entries that are too short to be read as their type.
+                         */
+                        _ => {}
+                    }
+                }
+            }
+
+            #[rustfmt::skip]
+            construct_entry!(
+                header.entry_type,
+                entry_pointer,
+                (0x0 => DmarEntry::Drhd as DrhdEntry),
+                (0x1 => DmarEntry::Rmrr as RmrrEntry),
+                (0x2 => DmarEntry::Atsr as AtsrEntry),
+                (0x3 => DmarEntry::Rhsa as RhsaEntry),
+                (0x4 => DmarEntry::Andd as AnddEntry),
+                (0x5 => DmarEntry::Satc as SatcEntry),
+                (0x6 => DmarEntry::Sidp as SidpEntry)
+            );
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u16,
+    pub length: u16,
+}
+
+/// Constructs an iterator over the device scope entries that follow the fixed part of a remapping structure.
+fn device_scopes_of<T>(entry: &T, length: u16) -> DeviceScopeIter<'_> {
+    DeviceScopeIter {
+        pointer: unsafe { (entry as *const T as *const u8).add(mem::size_of::<T>()) },
+        remaining_length: (length as usize).saturating_sub(mem::size_of::<T>()),
+        _phantom: PhantomData,
+    }
+}
+
+/// DMA Remapping Hardware Unit Definition. Describes a single remapping hardware unit, and the devices that are
+/// behind it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DrhdEntry {
+    pub header: EntryHeader,
+    pub flags: u8,
+    /// The size of the register set, encoded as `2^size` 4KiB pages.
+    pub size: u8,
+    pub segment_number: u16,
+    pub register_base_address: u64,
+}
+
+impl DrhdEntry {
+    /// If this is set, this remapping unit handles all PCI devices in its segment that aren't reported under
+    /// another unit. Its device scopes then only list I/O APICs and HPETs.
+    pub fn include_pci_all(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// The size of the register set, in bytes.
+    pub fn register_set_size(&self) -> u64 {
+        0x1000 << self.size.get_bits(0..4)
+    }
+
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        device_scopes_of(self, self.header.length)
+    }
+}
+
+/// Reserved Memory Region Reporting. Describes a region of memory that is used by devices for DMA before the OS
+/// takes over (e.g. for USB legacy emulation), and so must be identity-mapped for those devices.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RmrrEntry {
+    pub header: EntryHeader,
+    _reserved: u16,
+    pub segment_number: u16,
+    pub base_address: u64,
+    /// The last address of the region (inclusive).
+    pub limit_address: u64,
+}
+
+impl RmrrEntry {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        device_scopes_of(self, self.header.length)
+    }
+}
+
+/// Root Port ATS Capability Reporting. Describes which root ports in a segment support Address Translation
+/// Services.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AtsrEntry {
+    pub header: EntryHeader,
+    pub flags: u8,
+    _reserved: u8,
+    pub segment_number: u16,
+}
+
+impl AtsrEntry {
+    /// If this is set, all root ports in the segment support ATS, and there are no device scopes.
+    pub fn all_ports(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        device_scopes_of(self, self.header.length)
+    }
+}
+
+/// Remapping Hardware Static Affinity. Associates a remapping unit with a proximity domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RhsaEntry {
+    pub header: EntryHeader,
+    _reserved: u32,
+    pub register_base_address: u64,
+    pub proximity_domain: u32,
+}
+
+/// ACPI Name-space Device Declaration. Associates an ACPI namespace device with an enumeration ID, which is then
+/// used by device scope entries of type `AcpiNamespaceDevice`.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AnddEntry {
+    pub header: EntryHeader,
+    _reserved: [u8; 3],
+    pub acpi_device_number: u8,
+    // Followed by a null-terminated ASCII string containing the fully-qualified namespace path of the device
+}
+
+impl AnddEntry {
+    /// The fully-qualified path of the device in the ACPI namespace. Returns `None` if the name isn't valid ASCII.
+    pub fn object_name(&self) -> Option<&str> {
+        let length = (self.header.length as usize).saturating_sub(mem::size_of::<AnddEntry>());
+        let bytes = unsafe {
+            slice::from_raw_parts((self as *const AnddEntry as *const u8).add(mem::size_of::<AnddEntry>()), length)
+        };
+        let bytes = match bytes.iter().position(|&byte| byte == 0) {
+            Some(nul) => &bytes[..nul],
+            None => bytes,
+        };
+
+        str::from_utf8(bytes).ok()
+    }
+}
+
+/// SoC Integrated Address Translation Cache. Describes devices that are integrated into the SoC and have an
+/// address translation cache.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SatcEntry {
+    pub header: EntryHeader,
+    pub flags: u8,
+    _reserved: u8,
+    pub segment_number: u16,
+}
+
+impl SatcEntry {
+    pub fn atc_required(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        device_scopes_of(self, self.header.length)
+    }
+}
+
+/// SoC Integrated Device Property Reporting. Describes properties of devices integrated into the SoC.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SidpEntry {
+    pub header: EntryHeader,
+    _reserved: u16,
+    pub segment_number: u16,
+}
+
+impl SidpEntry {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        device_scopes_of(self, self.header.length)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceScopeType {
+    PciEndpoint,
+    PciSubHierarchy,
+    IoApic,
+    MsiCapableHpet,
+    AcpiNamespaceDevice,
+    Reserved(u8),
+}
+
+/// Identifies a device, or hierarchy of devices, under a remapping structure. The device is found by starting at
+/// `start_bus_number` and following each element of `path` through PCI-PCI bridges.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DeviceScope {
+    pub scope_type: u8,
+    pub length: u8,
+    pub flags: u8,
+    _reserved: u8,
+    /// For I/O APICs, HPETs, and ACPI namespace devices, this is the I/O APIC ID, HPET number, or ACPI device
+    /// number of the device respectively.
+    pub enumeration_id: u8,
+    pub start_bus_number: u8,
+}
+
+impl DeviceScope {
+    pub fn scope_type(&self) -> DeviceScopeType {
+        match self.scope_type {
+            1 => DeviceScopeType::PciEndpoint,
+            2 => DeviceScopeType::PciSubHierarchy,
+            3 => DeviceScopeType::IoApic,
+            4 => DeviceScopeType::MsiCapableHpet,
+            5 => DeviceScopeType::AcpiNamespaceDevice,
+            other => DeviceScopeType::Reserved(other),
+        }
+    }
+
+    pub fn path(&self) -> &[DeviceScopePathEntry] {
+        let length = (self.length as usize).saturating_sub(mem::size_of::<DeviceScope>());
+
+        unsafe {
+            let pointer = (self as *const DeviceScope as *const u8).add(mem::size_of::<DeviceScope>());
+            slice::from_raw_parts(
+                pointer as *const DeviceScopePathEntry,
+                length / mem::size_of::<DeviceScopePathEntry>(),
+            )
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DeviceScopePathEntry {
+    pub device: u8,
+    pub function: u8,
+}
+
+#[derive(Debug)]
+pub struct DeviceScopeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for DeviceScopeIter<'a> {
+    type Item = &'a DeviceScope;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<DeviceScope>() {
+            return None;
+        }
+
+        let scope = unsafe { &*(self.pointer as *const DeviceScope) };
+        if (scope.length as usize) < mem::size_of::<DeviceScope>() || scope.length as usize > self.remaining_length
+        {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(scope.length as usize) };
+        self.remaining_length -= scope.length as usize;
+
+        Some(scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_entries() {
+        let mut body = [38, 0b101, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&DrhdEntry {
+            header: EntryHeader { entry_type: 0, length: 16 + 10 + 6 + 6 },
+            flags: 0,
+            size: 1,
+            segment_number: 0,
+            register_base_address: 0xfed9_0000,
+        }));
+        // A PCI endpoint behind the root port at 00:1c.0, and an I/O APIC
+        body.extend(bytes_of(&DeviceScope {
+            scope_type: 1,
+            length: 10,
+            flags: 0,
+            _reserved: 0,
+            enumeration_id: 0,
+            start_bus_number: 0,
+        }));
+        body.extend([0x1c, 0, 0x00, 0]);
+        body.extend([3, 8, 0, 0, 2, 0xf0]);
+        // A scope that runs off the end of the DRHD entry ends the iteration of its scopes
+        body.extend([1, 8, 0, 0, 0, 0]);
+        // An RMRR entry that's too short, and a reserved entry, which are skipped
+        body.extend([1, 0, 8, 0, 0, 0, 0, 0]);
+        body.extend([0x20, 0, 4, 0]);
+        body.extend(bytes_of(&AnddEntry {
+            header: EntryHeader { entry_type: 4, length: 8 + 10 },
+            _reserved: [0; 3],
+            acpi_device_number: 1,
+        }));
+        body.extend(b"\\_SB.UAR1\0");
+
+        let buffer = TableBuffer::<Dmar>::new(&table(b"DMAR", 1, &body));
+        let dmar = buffer.get();
+        assert!(dmar.validate().is_ok());
+        assert!(dmar.supports_interrupt_remapping() && !dmar.x2apic_opt_out() && dmar.dma_control_opt_in());
+
+        let entries: Vec<_> = dmar.entries().collect();
+        assert_eq!(entries.len(), 2);
+        match entries[0] {
+            DmarEntry::Drhd(drhd) => {
+                assert_eq!({ drhd.register_base_address }, 0xfed9_0000);
+                assert_eq!(drhd.register_set_size(), 0x2000);
+                assert!(!drhd.include_pci_all());
+
+                let scopes: Vec<_> = drhd.device_scopes().collect();
+                assert_eq!(scopes.len(), 2);
+                assert_eq!(scopes[0].scope_type(), DeviceScopeType::PciEndpoint);
+                assert_eq!(scopes[1].scope_type(), DeviceScopeType::IoApic);
+                assert_eq!(scopes[1].enumeration_id, 2);
+                let path: Vec<_> = scopes[0].path().iter().map(|entry| (entry.device, entry.function)).collect();
+                assert_eq!(path, [(0x1c, 0), (0, 0)]);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            DmarEntry::Andd(andd) => {
+                assert_eq!(andd.acpi_device_number, 1);
+                assert_eq!(andd.object_name(), Some("\\_SB.UAR1"));
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+}
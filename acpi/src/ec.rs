@@ -0,0 +1,374 @@
+use crate::{
+    address::{AccessSize, AddressSpace, GenericAddress, RegisterHandler},
+    device,
+    ecdt::Ecdt,
+    AcpiError,
+    AcpiResult,
+};
+use alloc::{boxed::Box, format, sync::Arc, vec::Vec};
+use aml::{
+    resource::{resource_descriptor_list, Resource},
+    value::{Args, RegionSpace},
+    AmlContext,
+    AmlError,
+    AmlName,
+    AmlValue,
+    RegionHandler,
+};
+use bit_field::BitField;
+use core::{
+    hint,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The number of times the status register is polled while waiting for the EC, before giving up.
+const EC_POLL_LIMIT: usize = 1_000_000;
+
+const EC_STATUS_OBF: usize = 0;
+const EC_STATUS_IBF: usize = 1;
+const EC_STATUS_BURST: usize = 4;
+const EC_STATUS_SCI_EVT: usize = 5;
+
+const EC_COMMAND_READ: u8 = 0x80;
+const EC_COMMAND_WRITE: u8 = 0x81;
+const EC_COMMAND_BURST_ENABLE: u8 = 0x82;
+const EC_COMMAND_BURST_DISABLE: u8 = 0x83;
+const EC_COMMAND_QUERY: u8 = 0x84;
+
+/// The value the EC returns to acknowledge the burst enable command.
+const EC_BURST_ACK: u8 = 0x90;
+
+#[derive(Debug)]
+pub enum EcError {
+    /// The namespace doesn't contain an embedded controller device (with a `_HID` of `PNP0C09`).
+    NotFound,
+    /// The `_CRS` of the embedded controller doesn't contain its data and command ports.
+    InvalidResources,
+    /// The EC didn't respond to a command in time.
+    Timeout,
+    /// The EC didn't acknowledge the burst enable command.
+    BurstNotAcknowledged,
+    /// The namespace path of the EC in the ECDT is not a valid path.
+    InvalidEcdtPath,
+}
+
+/// Drives an ACPI embedded controller (EC): a microcontroller that implements platform functions such as battery
+/// management and thermal control, and that is accessed by the AML through `EmbeddedControl` operation regions.
+/// The EC has a 256-byte address space, which is accessed through a command/status port and a data port.
+///
+/// The OS should install the EC as the handler of the `EmbeddedControl` address space with
+/// [`EmbeddedController::install_region_handler`], and call [`EmbeddedController::handle_query_events`] when
+/// the EC's GPE ([`EmbeddedController::gpe`]) fires.
+pub struct EmbeddedController<H>
+where
+    H: RegisterHandler,
+{
+    handler: H,
+    command: GenericAddress,
+    data: GenericAddress,
+    device: AmlName,
+    gpe: Option<u32>,
+    /// Held for the duration of each transaction, so transactions from the AML and the OS don't interleave.
+    lock: AtomicBool,
+}
+
+impl<H> EmbeddedController<H>
+where
+    H: RegisterHandler,
+{
+    /// Create a driver for the EC with the given command/status and data registers. `device` is the path of the
+    /// EC's device in the namespace, which contains its `_Qxx` methods.
+    pub fn new(
+        handler: H,
+        command: GenericAddress,
+        data: GenericAddress,
+        device: AmlName,
+        gpe: Option<u32>,
+    ) -> EmbeddedController<H> {
+        EmbeddedController {
+            handler,
+            command: GenericAddress { access_size: AccessSize::ByteAccess, ..command },
+            data: GenericAddress { access_size: AccessSize::ByteAccess, ..data },
+            device,
+            gpe,
+            lock: AtomicBool::new(false),
+        }
+    }
+
+    /// Find the EC in the namespace, by looking for a device with a `_HID` of `PNP0C09`. Its data and command
+    /// ports are found from the first and second I/O port descriptors of its `_CRS`, and its GPE from its
+    /// `_GPE`.
+    pub fn discover(handler: H, context: &mut AmlContext) -> AcpiResult<EmbeddedController<H>> {
+        let device = device::find_devices_with_hid(context, "PNP0C09")?
+            .into_iter()
+            .next()
+            .ok_or(AcpiError::EmbeddedController(EcError::NotFound))?;
+
+        let resources = resource_descriptor_list(&device::evaluate(context, &device, "_CRS", Args::EMPTY)?)
+            .map_err(AcpiError::Aml)?;
+        let ports: Vec<u16> = resources
+            .iter()
+            .filter_map(|resource| match resource {
+                Resource::IOPort(descriptor) => Some(descriptor.memory_range.0),
+                _ => None,
+            })
+            .collect();
+        let (data, command) = match ports.as_slice() {
+            [data, command, ..] => (*data, *command),
+            _ => return Err(AcpiError::EmbeddedController(EcError::InvalidResources)),
+        };
+
+        let gpe = match device::evaluate_optional(context, &device, "_GPE", Args::EMPTY)? {
+            Some(value) => Some(value.as_integer(context).map_err(AcpiError::Aml)? as u32),
+            None => None,
+        };
+
+        Ok(EmbeddedController::new(handler, io_port(command), io_port(data), device, gpe))
+    }
+
+    /// Create a driver for the EC described by the ECDT. This can be used before the namespace has been parsed,
+    /// so that the EC's operation regions can be accessed while the DSDT and SSDTs are loaded.
+    pub fn from_ecdt(handler: H, ecdt: &Ecdt) -> AcpiResult<EmbeddedController<H>> {
+        let device = ecdt.ec_id().ok_or(AcpiError::EmbeddedController(EcError::InvalidEcdtPath))?;
+        let device =
+            AmlName::from_str(device).map_err(|_| AcpiError::EmbeddedController(EcError::InvalidEcdtPath))?;
+
+        Ok(EmbeddedController::new(
+            handler,
+            ecdt.ec_control()?,
+            ecdt.ec_data()?,
+            device,
+            Some(ecdt.gpe_bit as u32),
+        ))
+    }
+
+    /// The path of the EC's device in the namespace.
+    pub fn device(&self) -> &AmlName {
+        &self.device
+    }
+
+    /// The GPE the EC raises when it has a query event (or an output buffer full event, if the OS is using it).
+    pub fn gpe(&self) -> Option<u32> {
+        self.gpe
+    }
+
+    /// Read a byte from the EC's address space.
+    pub fn read(&self, address: u8) -> AcpiResult<u8> {
+        self.transaction(|ec| {
+            ec.send_command(EC_COMMAND_READ)?;
+            ec.write_data(address)?;
+            ec.read_data()
+        })
+    }
+
+    /// Write a byte to the EC's address space.
+    pub fn write(&self, address: u8, value: u8) -> AcpiResult<()> {
+        self.transaction(|ec| {
+            ec.send_command(EC_COMMAND_WRITE)?;
+            ec.write_data(address)?;
+            ec.write_data(value)
+        })
+    }
+
+    /// Ask the EC which event caused it to set `SCI_EVT`. Returns `None` if there is no outstanding event.
+    pub fn query(&self) -> AcpiResult<Option<u8>> {
+        self.transaction(|ec| {
+            ec.send_command(EC_COMMAND_QUERY)?;
+            match ec.read_data()? {
+                0 => Ok(None),
+                event => Ok(Some(event)),
+            }
+        })
+    }
+
+    /// Put the EC in burst mode, in which it dedicates itself to the OS's accesses until burst mode is disabled.
+    /// This makes sequences of accesses faster, and should be used sparingly.
+    pub fn enable_burst(&self) -> AcpiResult<()> {
+        self.transaction(|ec| {
+            ec.send_command(EC_COMMAND_BURST_ENABLE)?;
+            match ec.read_data()? {
+                EC_BURST_ACK => Ok(()),
+                _ => Err(AcpiError::EmbeddedController(EcError::BurstNotAcknowledged)),
+            }
+        })
+    }
+
+    pub fn disable_burst(&self) -> AcpiResult<()> {
+        self.transaction(|ec| {
+            ec.send_command(EC_COMMAND_BURST_DISABLE)?;
+            ec.wait_for_status(EC_STATUS_IBF, false)
+        })
+    }
+
+    pub fn is_burst_enabled(&self) -> AcpiResult<bool> {
+        Ok(self.status()?.get_bit(EC_STATUS_BURST))
+    }
+
+    /// Perform a sequence of accesses with the EC in burst mode. Burst mode is disabled afterwards, even if `f`
+    /// fails.
+    pub fn with_burst<F, R>(&self, f: F) -> AcpiResult<R>
+    where
+        F: FnOnce(&Self) -> AcpiResult<R>,
+    {
+        self.enable_burst()?;
+        let result = f(self);
+        self.disable_burst()?;
+        result
+    }
+
+    /// Handle the EC's query events, by querying the EC for each outstanding event, and invoking the
+    /// corresponding `_Qxx` method of the EC device. This should be called when the EC's GPE fires. Events
+    /// without a `_Qxx` method are ignored.
+    pub fn handle_query_events(&self, context: &mut AmlContext) -> AcpiResult<()> {
+        while self.status()?.get_bit(EC_STATUS_SCI_EVT) {
+            let event = match self.query()? {
+                Some(event) => event,
+                None => break,
+            };
+
+            let method = AmlName::from_str(&format!("_Q{:02X}", event)).unwrap().resolve(&self.device).unwrap();
+            match context.invoke_method(&method, Args::EMPTY) {
+                Ok(_) | Err(AmlError::ValueDoesNotExist(_)) => (),
+                Err(err) => return Err(AcpiError::Aml(err)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn status(&self) -> AcpiResult<u8> {
+        Ok(self.command.read_register(&self.handler)? as u8)
+    }
+
+    fn transaction<F, R>(&self, f: F) -> AcpiResult<R>
+    where
+        F: FnOnce(&Self) -> AcpiResult<R>,
+    {
+        while self.lock.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            hint::spin_loop();
+        }
+        let result = f(self);
+        self.lock.store(false, Ordering::Release);
+        result
+    }
+
+    fn send_command(&self, command: u8) -> AcpiResult<()> {
+        self.wait_for_status(EC_STATUS_IBF, false)?;
+        self.command.write_register(&self.handler, command as u64)
+    }
+
+    fn write_data(&self, value: u8) -> AcpiResult<()> {
+        self.wait_for_status(EC_STATUS_IBF, false)?;
+        self.data.write_register(&self.handler, value as u64)
+    }
+
+    fn read_data(&self) -> AcpiResult<u8> {
+        self.wait_for_status(EC_STATUS_OBF, true)?;
+        Ok(self.data.read_register(&self.handler)? as u8)
+    }
+
+    fn wait_for_status(&self, bit: usize, value: bool) -> AcpiResult<()> {
+        for _ in 0..EC_POLL_LIMIT {
+            if self.status()?.get_bit(bit) == value {
+                return Ok(());
+            }
+            hint::spin_loop();
+        }
+
+        Err(AcpiError::EmbeddedController(EcError::Timeout))
+    }
+}
+
+impl<H> EmbeddedController<H>
+where
+    H: RegisterHandler + Send + Sync + 'static,
+{
+    /// Install the EC as the handler for the `EmbeddedControl` address space, and then invoke the `_REG` method of
+    /// the EC device (if it has one), to tell the AML that it can access the EC's operation regions.
+    pub fn install_region_handler(self: &Arc<Self>, context: &mut AmlContext) -> AcpiResult<()> {
+        context.install_region_handler(RegionSpace::EmbeddedControl, Box::new(EcRegionHandler(self.clone())));
+
+        const EMBEDDED_CONTROL_SPACE: u64 = 3;
+        let reg = AmlName::from_str("_REG").unwrap().resolve(&self.device).unwrap();
+        let args =
+            Args::from_list(alloc::vec![AmlValue::Integer(EMBEDDED_CONTROL_SPACE), AmlValue::Integer(1)]).unwrap();
+        match context.invoke_method(&reg, args) {
+            Ok(_) | Err(AmlError::ValueDoesNotExist(_)) => Ok(()),
+            Err(err) => Err(AcpiError::Aml(err)),
+        }
+    }
+}
+
+impl<H> core::fmt::Debug for EmbeddedController<H>
+where
+    H: RegisterHandler,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmbeddedController")
+            .field("command", &self.command)
+            .field("data", &self.data)
+            .field("device", &self.device)
+            .field("gpe", &self.gpe)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Accesses `EmbeddedControl` operation regions through the EC. Accesses wider than a byte are split into
+/// byte accesses, which are done in burst mode.
+struct EcRegionHandler<H>(Arc<EmbeddedController<H>>)
+where
+    H: RegisterHandler;
+
+impl<H> EcRegionHandler<H>
+where
+    H: RegisterHandler,
+{
+    fn access<F>(&self, address: u64, length: u64, mut f: F) -> Result<(), AmlError>
+    where
+        F: FnMut(&EmbeddedController<H>, usize, u8) -> AcpiResult<()>,
+    {
+        let bytes = length.div_ceil(8);
+        if address + bytes > 0x100 {
+            return Err(AmlError::FieldInvalidAddress);
+        }
+
+        let ec = &self.0;
+        let mut access_bytes = |ec: &EmbeddedController<H>| {
+            for i in 0..bytes {
+                f(ec, i as usize, (address + i) as u8)?;
+            }
+            Ok(())
+        };
+        let result = if bytes > 1 { ec.with_burst(access_bytes) } else { access_bytes(ec) };
+
+        result.map_err(|_| AmlError::RegionAccessFailed(RegionSpace::EmbeddedControl))
+    }
+}
+
+impl<H> RegionHandler for EcRegionHandler<H>
+where
+    H: RegisterHandler + Send + Sync,
+{
+    fn read(&self, address: u64, length: u64) -> Result<u64, AmlError> {
+        let mut value = 0u64;
+        self.access(address, length, |ec, i, address| {
+            value |= (ec.read(address)? as u64) << (i * 8);
+            Ok(())
+        })?;
+        Ok(value)
+    }
+
+    fn write(&self, address: u64, length: u64, value: u64) -> Result<(), AmlError> {
+        self.access(address, length, |ec, i, address| ec.write(address, (value >> (i * 8)) as u8))
+    }
+}
+
+fn io_port(port: u16) -> GenericAddress {
+    GenericAddress {
+        address_space: AddressSpace::SystemIo,
+        bit_width: 8,
+        bit_offset: 0,
+        access_size: AccessSize::ByteAccess,
+        address: port as u64,
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+    AcpiResult,
+    AcpiTable,
+};
+use core::{mem, slice, str};
+
+/// Represents the Embedded Controller Boot Resources Table (ECDT). This describes the embedded controller that is
+/// used by the AML, so that the OS can start driving it before the namespace has been parsed (e.g. because the
+/// AML accesses the EC's operation regions while the tables are being loaded).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ecdt {
+    pub header: SdtHeader,
+    ec_control: RawGenericAddress,
+    ec_data: RawGenericAddress,
+    /// The unique ID of the EC, which is the same as the `_UID` of its device in the namespace.
+    pub uid: u32,
+    /// The GPE the EC raises its SCI on, which is the same as the value of its device's `_GPE` object.
+    pub gpe_bit: u8,
+    // Followed by the null-terminated namespace path of the EC device
+}
+
+/// ### Safety: Implementation properly represents a valid ECDT.
+unsafe impl AcpiTable for Ecdt {
+    const SIGNATURE: Signature = Signature::ECDT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Ecdt {
+    /// The EC's command/status register.
+    pub fn ec_control(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.ec_control)
+    }
+
+    /// The EC's data register.
+    pub fn ec_data(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.ec_data)
+    }
+
+    /// The fully-qualified namespace path of the EC's device (e.g. `\_SB.PCI0.LPC0.EC0`). Returns `None` if the
+    /// path isn't valid UTF-8.
+    pub fn ec_id(&self) -> Option<&str> {
+        let length = (self.header.length as usize).saturating_sub(mem::size_of::<Ecdt>());
+        let bytes = unsafe {
+            slice::from_raw_parts((self as *const Ecdt as *const u8).add(mem::size_of::<Ecdt>()), length)
+        };
+        let path = bytes.split(|&byte| byte == 0).next().unwrap_or(&[]);
+
+        str::from_utf8(path).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        address::{AccessSize, AddressSpace},
+        test_utils::{blank_header, body_of, table, TableBuffer},
+        AcpiError,
+    };
+
+    fn io_port(port: u64) -> RawGenericAddress {
+        RawGenericAddress { address_space: 1, bit_width: 8, bit_offset: 0, access_size: 1, address: port }
+    }
+
+    fn ecdt_body(ec_id: &[u8]) -> std::vec::Vec<u8> {
+        let mut body = body_of(&Ecdt {
+            header: blank_header(),
+            ec_control: io_port(0x66),
+            ec_data: io_port(0x62),
+            uid: 1,
+            gpe_bit: 0x17,
+        });
+        body.extend(ec_id);
+        body
+    }
+
+    #[test]
+    fn test_ecdt() {
+        let buffer = TableBuffer::<Ecdt>::new(&table(b"ECDT", 1, &ecdt_body(b"\\_SB.PCI0.LPCB.EC0\0")));
+        let ecdt = buffer.get();
+        assert!(ecdt.validate().is_ok());
+
+        let ec_control = ecdt.ec_control().unwrap();
+        assert_eq!(ec_control.address_space, AddressSpace::SystemIo);
+        assert_eq!(ec_control.access_size, AccessSize::ByteAccess);
+        assert_eq!(ec_control.address, 0x66);
+        assert_eq!(ecdt.ec_data().unwrap().address, 0x62);
+        assert_eq!({ ecdt.gpe_bit }, 0x17);
+        assert_eq!(ecdt.ec_id(), Some("\\_SB.PCI0.LPCB.EC0"));
+    }
+
+    #[test]
+    fn test_truncated_ec_id() {
+        // The path is cut off by the end of the table, rather than being null-terminated
+        let buffer = TableBuffer::<Ecdt>::new(&table(b"ECDT", 1, &ecdt_body(b"\\_SB.EC0")));
+        assert_eq!(buffer.get().ec_id(), Some("\\_SB.EC0"));
+
+        let buffer = TableBuffer::<Ecdt>::new(&table(b"ECDT", 1, &ecdt_body(&[0xff, 0xfe, 0])));
+        assert_eq!(buffer.get().ec_id(), None);
+
+        let body = ecdt_body(&[]);
+        let buffer = TableBuffer::<Ecdt>::new(&table(b"ECDT", 1, &body[..body.len() - 1]));
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::ECDT))));
+    }
+}
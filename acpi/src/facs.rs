@@ -0,0 +1,216 @@
+use crate::{sdt::Signature, AcpiError, AcpiHandler, AcpiResult, PhysicalMapping};
+use bit_field::BitField;
+use core::{
+    cell::Cell,
+    mem,
+    ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// Represents the Firmware ACPI Control Structure (FACS). This is found through the FADT (see
+/// [`Fadt::facs_address`](crate::fadt::Fadt::facs_address)), and is not listed in the RSDT/XSDT. Unlike the
+/// other tables, it is in read-write memory, as it is used to communicate with the firmware: it contains the
+/// waking vectors the firmware jumps to when resuming from a sleep state, and the Global Lock.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Facs {
+    pub signature: Signature,
+    pub length: u32,
+    /// A value calculated by the firmware from the hardware configuration of the platform. If this changes
+    /// across a resume from S4, the hardware configuration has changed, and the saved image may not be usable.
+    pub hardware_signature: u32,
+    firmware_waking_vector: Cell<u32>,
+    global_lock: AtomicU32,
+    pub flags: u32,
+    x_firmware_waking_vector: Cell<u64>,
+    pub version: u8,
+    _reserved0: [u8; 3],
+    ospm_flags: Cell<u32>,
+    _reserved1: [u8; 24],
+}
+
+impl Facs {
+    /// Map the FACS at the given physical address, and check that it is valid.
+    ///
+    /// ### Safety
+    /// `address` must be the physical address of the FACS (e.g. as returned by
+    /// [`Fadt::facs_address`](crate::fadt::Fadt::facs_address)).
+    pub unsafe fn map<H>(handler: &H, address: usize) -> AcpiResult<PhysicalMapping<H, Facs>>
+    where
+        H: AcpiHandler,
+    {
+        let facs = unsafe { handler.map_physical_region::<Facs>(address, mem::size_of::<Facs>()) };
+        if facs.signature != Signature::FACS {
+            return Err(AcpiError::SdtInvalidSignature(Signature::FACS));
+        }
+        if (facs.length as usize) < mem::size_of::<Facs>() {
+            return Err(AcpiError::SdtInvalidLength(Signature::FACS));
+        }
+
+        Ok(facs)
+    }
+
+    /// If true, the platform supports the `S4BIOS` sleep state, which is entered by writing `S4BIOS_REQ` to the
+    /// SMI command port.
+    pub fn supports_s4bios(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the firmware can jump to the 64-bit waking vector in 64-bit mode (see
+    /// [`Facs::set_x_firmware_waking_vector`]).
+    pub fn supports_64bit_wake(&self) -> bool {
+        self.version >= 1 && self.flags.get_bit(1)
+    }
+
+    /// The 32-bit physical address the firmware jumps to (in real mode) when resuming from a sleep state. This is
+    /// only used if the 64-bit waking vector is `0`.
+    pub fn firmware_waking_vector(&self) -> u32 {
+        unsafe { ptr::read_volatile(self.firmware_waking_vector.as_ptr()) }
+    }
+
+    pub fn set_firmware_waking_vector(&self, vector: u32) {
+        unsafe { ptr::write_volatile(self.firmware_waking_vector.as_ptr(), vector) }
+    }
+
+    /// The 64-bit physical address the firmware jumps to when resuming from a sleep state, if the FACS has one.
+    /// If this is non-zero, it is used instead of the 32-bit waking vector.
+    pub fn x_firmware_waking_vector(&self) -> Option<u64> {
+        if self.version >= 1 {
+            Some(unsafe { ptr::read_volatile(self.x_firmware_waking_vector.as_ptr()) })
+        } else {
+            None
+        }
+    }
+
+    /// Set the 64-bit waking vector. If `wake_in_64bit_mode` is true (which is only allowed if
+    /// [`Facs::supports_64bit_wake`]), the firmware will jump to it in 64-bit mode, with identity-mapped paging;
+    /// otherwise it will jump to it in 32-bit protected mode, with paging disabled.
+    ///
+    /// Does nothing if the FACS is too old to have a 64-bit waking vector.
+    pub fn set_x_firmware_waking_vector(&self, vector: u64, wake_in_64bit_mode: bool) {
+        if self.version < 1 {
+            return;
+        }
+
+        unsafe {
+            ptr::write_volatile(self.x_firmware_waking_vector.as_ptr(), vector);
+
+            // The OSPM flags were only added in version 2
+            if self.version >= 2 {
+                let mut ospm_flags = ptr::read_volatile(self.ospm_flags.as_ptr());
+                ospm_flags.set_bit(0, wake_in_64bit_mode && self.supports_64bit_wake());
+                ptr::write_volatile(self.ospm_flags.as_ptr(), ospm_flags);
+            }
+        }
+    }
+
+    /// The Global Lock, which is used to synchronise access to hardware shared between the OS and the firmware.
+    /// Bit 0 is the pending flag, and bit 1 is the owned flag, and it must only be updated atomically.
+    pub fn global_lock(&self) -> &AtomicU32 {
+        &self.global_lock
+    }
+
+    /// Try to acquire the Global Lock, using the sequence given by the spec. Returns `true` if it was acquired. If
+    /// it wasn't, the firmware owns it, and has been asked to release it: it will raise an SCI (setting
+    /// `GBL_STS`) when it does, after which this should be called again.
+    pub fn try_acquire_global_lock(&self) -> bool {
+        let mut lock = self.global_lock.load(Ordering::Acquire);
+        loop {
+            // Set the owned flag, and set the pending flag if it was already owned
+            let new = (lock & !0b11) | 0b10 | ((lock >> 1) & 1);
+            match self.global_lock.compare_exchange_weak(lock, new, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return new & 0b11 != 0b11,
+                Err(current) => lock = current,
+            }
+        }
+    }
+
+    /// Release the Global Lock. Returns `true` if the firmware was waiting for it, in which case the OS must tell
+    /// the firmware it's been released by setting `GBL_RLS` in the PM1 control register.
+    pub fn release_global_lock(&self) -> bool {
+        let old = self.global_lock.fetch_and(!0b11, Ordering::AcqRel);
+        old.get_bit(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TableBuffer, TestHandler};
+    use std::{vec, vec::Vec};
+
+    fn facs(version: u8, flags: u32) -> Vec<u8> {
+        let mut bytes = vec![0; mem::size_of::<Facs>()];
+        bytes[0..4].copy_from_slice(b"FACS");
+        bytes[4..8].copy_from_slice(&(mem::size_of::<Facs>() as u32).to_le_bytes());
+        bytes[20..24].copy_from_slice(&flags.to_le_bytes());
+        bytes[32] = version;
+        bytes
+    }
+
+    #[test]
+    fn test_map() {
+        let handler = TestHandler::default();
+        let buffer = TableBuffer::<Facs>::new(&facs(2, 0b11));
+        let facs = unsafe { Facs::map(&handler, buffer.get() as *const Facs as usize) }.unwrap();
+        assert!(facs.supports_s4bios());
+        assert!(facs.supports_64bit_wake());
+
+        let mut bytes = self::facs(2, 0);
+        bytes[0..4].copy_from_slice(b"FACP");
+        let buffer = TableBuffer::<Facs>::new(&bytes);
+        assert!(matches!(
+            unsafe { Facs::map(&handler, buffer.get() as *const Facs as usize) },
+            Err(AcpiError::SdtInvalidSignature(Signature::FACS))
+        ));
+
+        let mut bytes = self::facs(2, 0);
+        bytes[4..8].copy_from_slice(&32u32.to_le_bytes());
+        let buffer = TableBuffer::<Facs>::new(&bytes);
+        assert!(matches!(
+            unsafe { Facs::map(&handler, buffer.get() as *const Facs as usize) },
+            Err(AcpiError::SdtInvalidLength(Signature::FACS))
+        ));
+    }
+
+    #[test]
+    fn test_waking_vectors() {
+        let buffer = TableBuffer::<Facs>::new(&facs(2, 0b10));
+        let facs = buffer.get();
+        facs.set_firmware_waking_vector(0x8000);
+        assert_eq!(facs.firmware_waking_vector(), 0x8000);
+        facs.set_x_firmware_waking_vector(0x1_0000_0000, true);
+        assert_eq!(facs.x_firmware_waking_vector(), Some(0x1_0000_0000));
+        assert!(facs.ospm_flags.get().get_bit(0));
+
+        // The 64-bit waking vector isn't in version 0, and 64-bit wake can't be requested if it's not supported
+        let buffer = TableBuffer::<Facs>::new(&self::facs(0, 0b10));
+        buffer.get().set_x_firmware_waking_vector(0x1_0000_0000, true);
+        assert_eq!(buffer.get().x_firmware_waking_vector(), None);
+        let buffer = TableBuffer::<Facs>::new(&self::facs(2, 0));
+        buffer.get().set_x_firmware_waking_vector(0x1_0000_0000, true);
+        assert!(!buffer.get().ospm_flags.get().get_bit(0));
+    }
+
+    #[test]
+    fn test_global_lock() {
+        let buffer = TableBuffer::<Facs>::new(&facs(2, 0));
+        let facs = buffer.get();
+        assert!(facs.try_acquire_global_lock());
+        assert_eq!(facs.global_lock().load(Ordering::Relaxed), 0b10);
+        assert!(!facs.release_global_lock());
+
+        // While the firmware owns the lock, acquiring it fails and sets the pending flag
+        facs.global_lock().store(0b10, Ordering::Relaxed);
+        assert!(!facs.try_acquire_global_lock());
+        assert_eq!(facs.global_lock().load(Ordering::Relaxed), 0b11);
+        facs.global_lock().store(0b01, Ordering::Relaxed);
+        assert!(facs.try_acquire_global_lock());
+        assert_eq!(facs.global_lock().load(Ordering::Relaxed), 0b10);
+
+        // The firmware asks for the lock while the OS owns it
+        facs.global_lock().store(0b11, Ordering::Relaxed);
+        assert!(facs.release_global_lock());
+        assert_eq!(facs.global_lock().load(Ordering::Relaxed), 0);
+    }
+}
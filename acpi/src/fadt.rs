@@ -1,13 +1,21 @@
 use crate::{
-    address::{AccessSize, AddressSpace, GenericAddress, RawGenericAddress},
+    address::{AccessSize, AddressSpace, GenericAddress, RawGenericAddress, RegisterHandler},
+    compose::FadtBuilder,
     sdt::{ExtendedField, SdtHeader, Signature},
     AcpiError,
     AcpiTable,
 };
 use bit_field::BitField;
+use core::{convert::TryFrom, mem, ptr};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How long the firmware is given to transfer control of the ACPI hardware, in microseconds, and how often the
+/// `SCI_EN` bit is polled while waiting for it.
+const ACPI_MODE_TIMEOUT_MICROS: u64 = 3_000_000;
+const ACPI_MODE_POLL_INTERVAL_MICROS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PowerProfile {
+    #[default]
     Unspecified,
     Desktop,
     Mobile,
@@ -20,13 +28,71 @@ pub enum PowerProfile {
     Reserved(u8),
 }
 
+impl PowerProfile {
+    fn to_raw(self) -> u8 {
+        match self {
+            PowerProfile::Unspecified => 0,
+            PowerProfile::Desktop => 1,
+            PowerProfile::Mobile => 2,
+            PowerProfile::Workstation => 3,
+            PowerProfile::EnterpriseServer => 4,
+            PowerProfile::SohoServer => 5,
+            PowerProfile::AppliancePc => 6,
+            PowerProfile::PerformanceServer => 7,
+            PowerProfile::Tablet => 8,
+            PowerProfile::Reserved(other) => other,
+        }
+    }
+}
+
+/// How to choose between the legacy 32-bit address and the extended 64-bit (`X_*`) address of a register block,
+/// or of the FACS or DSDT, when the FADT provides both of them and they disagree. This is common on real
+/// hardware, where one of them is often left over from an older version of the firmware.
+///
+/// If only one of the addresses is provided (the other is zero), or they are the same, it is always used, whatever
+/// the policy is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddressPolicy {
+    /// Use the extended address. This is what the specification requires, as the legacy address should be
+    /// ignored when the extended one is provided.
+    #[default]
+    PreferExtended,
+    /// Use the legacy address. This is for firmware that fills the extended addresses with garbage.
+    PreferLegacy,
+    /// Return [`AcpiError::FadtAddressMismatch`].
+    Error,
+}
+
+impl AddressPolicy {
+    /// Whether the extended address should be used instead of the legacy address, where either of them is zero if
+    /// it isn't provided.
+    fn use_extended(self, extended: u64, legacy: u64) -> Result<bool, AcpiError> {
+        if extended == 0 || legacy == 0 || extended == legacy {
+            return Ok(extended != 0);
+        }
+
+        match self {
+            AddressPolicy::PreferExtended => {
+                log::warn!("FADT's legacy address {:#x} disagrees with extended address {:#x}", legacy, extended);
+                Ok(true)
+            }
+            AddressPolicy::PreferLegacy => {
+                log::warn!("FADT's extended address {:#x} disagrees with legacy address {:#x}", extended, legacy);
+                Ok(false)
+            }
+            AddressPolicy::Error => Err(AcpiError::FadtAddressMismatch),
+        }
+    }
+}
+
 /// Represents the Fixed ACPI Description Table (FADT). This table contains various fixed hardware
 /// details, such as the addresses of the hardware register blocks. It also contains a pointer to
 /// the Differentiated Definition Block (DSDT).
 ///
 /// In cases where the FADT contains both a 32-bit and 64-bit field for the same address, we should
 /// always prefer the 64-bit one. Only if it's zero or the CPU will not allow us to access that
-/// address should the 32-bit one be used.
+/// address should the 32-bit one be used. If both are provided but they disagree, the accessors
+/// choose between them according to an [`AddressPolicy`].
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
 pub struct Fadt {
@@ -76,8 +142,10 @@ pub struct Fadt {
     pm1_control_length: u8,
     pm2_control_length: u8,
     pm_timer_length: u8,
-    gpe0_block_length: u8,
-    gpe1_block_length: u8,
+    /// The length of the GPE0 block in bytes, which is split evenly between the status and enable registers.
+    pub gpe0_block_length: u8,
+    /// The length of the GPE1 block in bytes, which is split evenly between the status and enable registers.
+    pub gpe1_block_length: u8,
     pub gpe1_base: u8,
     pub c_state_control: u8,
     /// The worst-case latency to enter and exit the C2 state, in microseconds. A value `>100` indicates that the
@@ -96,8 +164,8 @@ pub struct Fadt {
     pub iapc_boot_arch: IaPcBootArchFlags,
     _reserved2: u8, // must be 0
     pub flags: FixedFeatureFlags,
-    reset_reg: RawGenericAddress,
-    pub reset_value: u8,
+    reset_reg: ExtendedField<RawGenericAddress, 2>,
+    reset_value: ExtendedField<u8, 2>,
     pub arm_boot_arch: ArmBootArchFlags,
     fadt_minor_version: u8,
     x_firmware_ctrl: ExtendedField<u64, 2>,
@@ -118,6 +186,8 @@ pub struct Fadt {
 /// ### Safety: Implementation properly represents a valid FADT.
 unsafe impl AcpiTable for Fadt {
     const SIGNATURE: Signature = Signature::FADT;
+    /// The ACPI 1.0 FADT ends before the reset register.
+    const MIN_LENGTH: usize = mem::offset_of!(Fadt, reset_reg);
 
     fn header(&self) -> &SdtHeader {
         &self.header
@@ -125,32 +195,251 @@ unsafe impl AcpiTable for Fadt {
 }
 
 impl Fadt {
+    /// Construct an ACPI 6.5 FADT from the description in `builder`, for
+    /// [`TableImage::push_fadt`](crate::compose::TableImage::push_fadt). Register blocks in the system I/O space
+    /// are also described by the 32-bit fields if they fit, for older OSs, and the C2 and C3 states are reported
+    /// as unsupported, as they should be described by `_CST` objects instead.
+    pub(crate) fn new(header: SdtHeader, builder: &FadtBuilder) -> Fadt {
+        fn legacy_block(block: Option<GenericAddress>) -> u32 {
+            match block {
+                Some(block) if block.address_space == AddressSpace::SystemIo => {
+                    u32::try_from(block.address).unwrap_or(0)
+                }
+                _ => 0,
+            }
+        }
+        fn block_length(block: Option<GenericAddress>) -> u8 {
+            block.map_or(0, |block| block.bit_width / 8)
+        }
+        fn raw(block: Option<GenericAddress>) -> ExtendedField<RawGenericAddress, 2> {
+            ExtendedField::new(raw_or_zero(block))
+        }
+        fn raw_or_zero(block: Option<GenericAddress>) -> RawGenericAddress {
+            block.map_or(
+                RawGenericAddress { address_space: 0, bit_width: 0, bit_offset: 0, access_size: 0, address: 0 },
+                GenericAddress::to_raw,
+            )
+        }
+
+        Fadt {
+            header,
+            firmware_ctrl: u32::try_from(builder.facs_address).unwrap_or(0),
+            dsdt_address: u32::try_from(builder.dsdt_address).unwrap_or(0),
+            _reserved: 0,
+            preferred_pm_profile: builder.power_profile.to_raw(),
+            sci_interrupt: builder.sci_interrupt,
+            smi_cmd_port: builder.smi_cmd_port,
+            acpi_enable: builder.acpi_enable,
+            acpi_disable: builder.acpi_disable,
+            s4bios_req: 0,
+            pstate_control: 0,
+            pm1a_event_block: legacy_block(builder.pm1a_event_block),
+            pm1b_event_block: legacy_block(builder.pm1b_event_block),
+            pm1a_control_block: legacy_block(builder.pm1a_control_block),
+            pm1b_control_block: legacy_block(builder.pm1b_control_block),
+            pm2_control_block: legacy_block(builder.pm2_control_block),
+            pm_timer_block: legacy_block(builder.pm_timer_block),
+            gpe0_block: legacy_block(builder.gpe0_block),
+            gpe1_block: legacy_block(builder.gpe1_block),
+            pm1_event_length: block_length(builder.pm1a_event_block),
+            pm1_control_length: block_length(builder.pm1a_control_block),
+            pm2_control_length: block_length(builder.pm2_control_block),
+            pm_timer_length: block_length(builder.pm_timer_block),
+            gpe0_block_length: block_length(builder.gpe0_block),
+            gpe1_block_length: block_length(builder.gpe1_block),
+            gpe1_base: builder.gpe1_base,
+            c_state_control: 0,
+            worst_c2_latency: 101,
+            worst_c3_latency: 1001,
+            flush_size: 0,
+            flush_stride: 0,
+            duty_offset: 0,
+            duty_width: 0,
+            day_alarm: 0,
+            month_alarm: 0,
+            century: builder.century,
+            iapc_boot_arch: builder.iapc_boot_arch,
+            _reserved2: 0,
+            flags: builder.flags,
+            reset_reg: ExtendedField::new(raw_or_zero(builder.reset_register)),
+            reset_value: ExtendedField::new(builder.reset_value),
+            arm_boot_arch: builder.arm_boot_arch,
+            fadt_minor_version: 5,
+            x_firmware_ctrl: ExtendedField::new(builder.facs_address),
+            x_dsdt_address: ExtendedField::new(builder.dsdt_address),
+            x_pm1a_event_block: raw(builder.pm1a_event_block),
+            x_pm1b_event_block: raw(builder.pm1b_event_block),
+            x_pm1a_control_block: raw(builder.pm1a_control_block),
+            x_pm1b_control_block: raw(builder.pm1b_control_block),
+            x_pm2_control_block: raw(builder.pm2_control_block),
+            x_pm_timer_block: raw(builder.pm_timer_block),
+            x_gpe0_block: raw(builder.gpe0_block),
+            x_gpe1_block: raw(builder.gpe1_block),
+            sleep_control_reg: raw(builder.sleep_control_register),
+            sleep_status_reg: raw(builder.sleep_status_register),
+            hypervisor_vendor_id: ExtendedField::new(builder.hypervisor_vendor_id),
+        }
+    }
+
     pub fn validate(&self) -> Result<(), AcpiError> {
         self.header.validate(crate::sdt::Signature::FADT)
     }
 
     pub fn facs_address(&self) -> Result<usize, AcpiError> {
-        unsafe {
-            { self.x_firmware_ctrl }
-                .access(self.header.revision)
-                .filter(|&p| p != 0)
-                .or(Some(self.firmware_ctrl as u64))
-                .filter(|&p| p != 0)
-                .map(|p| p as usize)
-                .ok_or(AcpiError::InvalidFacsAddress)
+        self.facs_address_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::facs_address`], but using `policy` to choose between the 32-bit and 64-bit addresses.
+    pub fn facs_address_with_policy(&self, policy: AddressPolicy) -> Result<usize, AcpiError> {
+        let extended = self.header.extended_field(ptr::addr_of!(self.x_firmware_ctrl)).unwrap_or(0);
+        let legacy = self.firmware_ctrl as u64;
+        let address = if policy.use_extended(extended, legacy)? { extended } else { legacy };
+        if address != 0 {
+            Ok(address as usize)
+        } else {
+            Err(AcpiError::InvalidFacsAddress)
         }
     }
 
     pub fn dsdt_address(&self) -> Result<usize, AcpiError> {
-        unsafe {
-            { self.x_dsdt_address }
-                .access(self.header.revision)
-                .filter(|&p| p != 0)
-                .or(Some(self.dsdt_address as u64))
-                .filter(|&p| p != 0)
-                .map(|p| p as usize)
-                .ok_or(AcpiError::InvalidDsdtAddress)
+        self.dsdt_address_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::dsdt_address`], but using `policy` to choose between the 32-bit and 64-bit addresses.
+    pub fn dsdt_address_with_policy(&self, policy: AddressPolicy) -> Result<usize, AcpiError> {
+        let extended = self.header.extended_field(ptr::addr_of!(self.x_dsdt_address)).unwrap_or(0);
+        let legacy = self.dsdt_address as u64;
+        let address = if policy.use_extended(extended, legacy)? { extended } else { legacy };
+        if address != 0 {
+            Ok(address as usize)
+        } else {
+            Err(AcpiError::InvalidDsdtAddress)
+        }
+    }
+
+    /// If true, the platform is a hardware-reduced ACPI platform, which doesn't implement the fixed hardware
+    /// register blocks (PM1, PM2, PM timer, and GPE blocks) or the SMI command port. Instead, the sleep control
+    /// and status registers are used to enter sleep states, and the other fixed features are implemented in AML.
+    pub fn is_hardware_reduced(&self) -> bool {
+        // The flag was only added in ACPI 5.0 (FADT revision 5)
+        self.header.revision >= 5 && { self.flags }.system_is_hw_reduced_acpi()
+    }
+
+    /// If true, the OS must transfer control of the ACPI hardware from the firmware, by writing `acpi_enable` to
+    /// the SMI command port, before it can use it. This is never the case on hardware-reduced platforms, or on
+    /// platforms that start in ACPI mode.
+    pub fn requires_acpi_enable(&self) -> bool {
+        !self.is_hardware_reduced() && self.smi_cmd_port != 0 && (self.acpi_enable != 0 || self.acpi_disable != 0)
+    }
+
+    /// The version of the ACPI specification the FADT conforms to, as `(major, minor)`. The major version is the
+    /// revision of the FADT, and so is not necessarily the major version of the specification.
+    pub fn version(&self) -> (u8, u8) {
+        let has_minor_version =
+            self.header.revision >= 5 && self.header.length as usize > mem::offset_of!(Fadt, fadt_minor_version);
+        let minor = if has_minor_version { self.fadt_minor_version.get_bits(0..4) } else { 0 };
+        (self.header.revision, minor)
+    }
+
+    /// The ARM boot architecture flags. These were added in ACPI 5.1, and so are empty on older platforms.
+    pub fn arm_boot_arch(&self) -> ArmBootArchFlags {
+        if self.version() >= (5, 1) {
+            self.arm_boot_arch
+        } else {
+            ArmBootArchFlags(0)
+        }
+    }
+
+    /// The conduit used to make PSCI calls, if the platform implements PSCI. On ARM platforms that don't, the
+    /// processors are started using the parking protocol (see
+    /// [`GicCpuInterface::parking_protocol_version`](crate::platform::interrupt::GicCpuInterface)).
+    pub fn psci_conduit(&self) -> Option<PsciConduit> {
+        let flags = self.arm_boot_arch();
+        if !flags.implements_psci() {
+            return None;
+        }
+
+        Some(if flags.use_hvc_as_psci_conduit() { PsciConduit::Hvc } else { PsciConduit::Smc })
+    }
+
+    /// If true, the platform is in ACPI mode: the `SCI_EN` bit is set in the PM1 control register, so the ACPI
+    /// hardware is controlled by the OS. This is always the case on hardware-reduced platforms.
+    pub fn acpi_mode_enabled<H>(&self, handler: &H) -> Result<bool, AcpiError>
+    where
+        H: RegisterHandler,
+    {
+        if self.is_hardware_reduced() {
+            return Ok(true);
+        }
+
+        Ok(self.pm1a_control_block()?.read_register(handler)?.get_bit(0))
+    }
+
+    /// Put the platform into ACPI mode, by writing `acpi_enable` to the SMI command port and waiting for the
+    /// firmware to set `SCI_EN`. This does nothing if the platform is already in ACPI mode, or doesn't need to be
+    /// put into it (see [`Fadt::requires_acpi_enable`]). Returns [`AcpiError::AcpiModeTimeout`] if the firmware
+    /// doesn't respond within three seconds.
+    ///
+    /// `stall` is called to busy-wait for the given number of microseconds between polls of `SCI_EN`. The PM timer
+    /// can be used for this, with `|micros| pm_timer.stall(handler, micros)` (see
+    /// [`PmTimer::stall`](crate::platform::PmTimer::stall)).
+    pub fn enable_acpi<H, F>(&self, handler: &H, stall: F) -> Result<(), AcpiError>
+    where
+        H: RegisterHandler,
+        F: FnMut(u64) -> Result<(), AcpiError>,
+    {
+        if !self.requires_acpi_enable() || self.acpi_mode_enabled(handler)? {
+            return Ok(());
         }
+
+        self.write_smi_command(handler, self.acpi_enable)?;
+        self.wait_for_acpi_mode(handler, true, stall)
+    }
+
+    /// Return the platform to legacy mode, by writing `acpi_disable` to the SMI command port and waiting for the
+    /// firmware to clear `SCI_EN`, using `stall` like [`Fadt::enable_acpi`]. The OS should mask the SCI before
+    /// doing this. Returns [`AcpiError::HardwareReduced`] on hardware-reduced platforms, which are always in ACPI
+    /// mode.
+    pub fn disable_acpi<H, F>(&self, handler: &H, stall: F) -> Result<(), AcpiError>
+    where
+        H: RegisterHandler,
+        F: FnMut(u64) -> Result<(), AcpiError>,
+    {
+        if self.is_hardware_reduced() {
+            return Err(AcpiError::HardwareReduced);
+        }
+        if !self.requires_acpi_enable() || !self.acpi_mode_enabled(handler)? {
+            return Ok(());
+        }
+
+        self.write_smi_command(handler, self.acpi_disable)?;
+        self.wait_for_acpi_mode(handler, false, stall)
+    }
+
+    fn write_smi_command<H>(&self, handler: &H, value: u8) -> Result<(), AcpiError>
+    where
+        H: RegisterHandler,
+    {
+        let port = u16::try_from(self.smi_cmd_port).map_err(|_| AcpiError::InvalidGenericAddress)?;
+        handler.write_io_u8(port, value);
+        Ok(())
+    }
+
+    fn wait_for_acpi_mode<H, F>(&self, handler: &H, enabled: bool, mut stall: F) -> Result<(), AcpiError>
+    where
+        H: RegisterHandler,
+        F: FnMut(u64) -> Result<(), AcpiError>,
+    {
+        let mut waited = 0;
+        while self.acpi_mode_enabled(handler)? != enabled {
+            if waited >= ACPI_MODE_TIMEOUT_MICROS {
+                return Err(AcpiError::AcpiModeTimeout);
+            }
+            stall(ACPI_MODE_POLL_INTERVAL_MICROS)?;
+            waited += ACPI_MODE_POLL_INTERVAL_MICROS;
+        }
+
+        Ok(())
     }
 
     pub fn power_profile(&self) -> PowerProfile {
@@ -168,190 +457,284 @@ impl Fadt {
         }
     }
 
+    /// Get the PM1a event register block. Returns [`AcpiError::HardwareReduced`] on hardware-reduced platforms,
+    /// which don't have one.
     pub fn pm1a_event_block(&self) -> Result<GenericAddress, AcpiError> {
-        if let Some(raw) = unsafe { self.x_pm1a_event_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return GenericAddress::from_raw(raw);
-            }
+        self.pm1a_event_block_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::pm1a_event_block`], but using `policy` to choose between the legacy and extended blocks.
+    pub fn pm1a_event_block_with_policy(&self, policy: AddressPolicy) -> Result<GenericAddress, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Err(AcpiError::HardwareReduced);
         }
 
-        Ok(GenericAddress {
-            address_space: AddressSpace::SystemIo,
-            bit_width: self.pm1_event_length * 8,
-            bit_offset: 0,
-            access_size: AccessSize::Undefined,
-            address: self.pm1a_event_block.into(),
-        })
+        let bit_width = self.pm1_event_length * 8;
+        Ok(self
+            .select_block(ptr::addr_of!(self.x_pm1a_event_block), self.pm1a_event_block, bit_width, policy)?
+            .unwrap_or_else(|| Fadt::legacy_block(self.pm1a_event_block, bit_width)))
     }
 
     pub fn pm1b_event_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.x_pm1b_event_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
-            }
-        }
+        self.pm1b_event_block_with_policy(AddressPolicy::default())
+    }
 
-        if self.pm1b_event_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: self.pm1_event_length * 8,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.pm1b_event_block.into(),
-            }))
-        } else {
-            Ok(None)
+    pub fn pm1b_event_block_with_policy(
+        &self,
+        policy: AddressPolicy,
+    ) -> Result<Option<GenericAddress>, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Ok(None);
         }
+
+        self.select_block(
+            ptr::addr_of!(self.x_pm1b_event_block),
+            self.pm1b_event_block,
+            self.pm1_event_length * 8,
+            policy,
+        )
     }
 
+    /// Get the PM1a control register block. Returns [`AcpiError::HardwareReduced`] on hardware-reduced
+    /// platforms, which don't have one.
     pub fn pm1a_control_block(&self) -> Result<GenericAddress, AcpiError> {
-        if let Some(raw) = unsafe { self.x_pm1a_control_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return GenericAddress::from_raw(raw);
-            }
+        self.pm1a_control_block_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::pm1a_control_block`], but using `policy` to choose between the legacy and extended blocks.
+    pub fn pm1a_control_block_with_policy(&self, policy: AddressPolicy) -> Result<GenericAddress, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Err(AcpiError::HardwareReduced);
         }
 
-        Ok(GenericAddress {
-            address_space: AddressSpace::SystemIo,
-            bit_width: self.pm1_control_length * 8,
-            bit_offset: 0,
-            access_size: AccessSize::Undefined,
-            address: self.pm1a_control_block.into(),
-        })
+        let bit_width = self.pm1_control_length * 8;
+        Ok(self
+            .select_block(ptr::addr_of!(self.x_pm1a_control_block), self.pm1a_control_block, bit_width, policy)?
+            .unwrap_or_else(|| Fadt::legacy_block(self.pm1a_control_block, bit_width)))
     }
 
     pub fn pm1b_control_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.x_pm1b_control_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
-            }
-        }
+        self.pm1b_control_block_with_policy(AddressPolicy::default())
+    }
 
-        if self.pm1b_control_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: self.pm1_control_length * 8,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.pm1b_control_block.into(),
-            }))
-        } else {
-            Ok(None)
+    pub fn pm1b_control_block_with_policy(
+        &self,
+        policy: AddressPolicy,
+    ) -> Result<Option<GenericAddress>, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Ok(None);
         }
+
+        self.select_block(
+            ptr::addr_of!(self.x_pm1b_control_block),
+            self.pm1b_control_block,
+            self.pm1_control_length * 8,
+            policy,
+        )
     }
 
     pub fn pm2_control_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.x_pm2_control_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
-            }
-        }
+        self.pm2_control_block_with_policy(AddressPolicy::default())
+    }
 
-        if self.pm2_control_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: self.pm2_control_length * 8,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.pm2_control_block.into(),
-            }))
-        } else {
-            Ok(None)
+    pub fn pm2_control_block_with_policy(
+        &self,
+        policy: AddressPolicy,
+    ) -> Result<Option<GenericAddress>, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Ok(None);
         }
+
+        self.select_block(
+            ptr::addr_of!(self.x_pm2_control_block),
+            self.pm2_control_block,
+            self.pm2_control_length * 8,
+            policy,
+        )
     }
 
     /// Attempts to parse the FADT's PWM timer blocks, first returning the extended block, and falling back to
     /// parsing the legacy block into a `GenericAddress`.
     pub fn pm_timer_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
+        self.pm_timer_block_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::pm_timer_block`], but using `policy` to choose between the legacy and extended blocks.
+    pub fn pm_timer_block_with_policy(&self, policy: AddressPolicy) -> Result<Option<GenericAddress>, AcpiError> {
         // ACPI spec indicates `PM_TMR_LEN` should be 4, or otherwise the PM_TMR is not supported.
-        if self.pm_timer_length != 4 {
+        if self.pm_timer_length != 4 || self.is_hardware_reduced() {
             return Ok(None);
         }
 
-        if let Some(raw) = unsafe { self.x_pm_timer_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
-            }
-        }
-
-        if self.pm_timer_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: 32,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.pm_timer_block.into(),
-            }))
-        } else {
-            Ok(None)
-        }
+        self.select_block(ptr::addr_of!(self.x_pm_timer_block), self.pm_timer_block, 32, policy)
     }
 
     pub fn gpe0_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.x_gpe0_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
-            }
-        }
+        self.gpe0_block_with_policy(AddressPolicy::default())
+    }
 
-        if self.gpe0_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: self.gpe0_block_length * 8,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.gpe0_block.into(),
-            }))
-        } else {
-            Ok(None)
+    pub fn gpe0_block_with_policy(&self, policy: AddressPolicy) -> Result<Option<GenericAddress>, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Ok(None);
         }
+
+        self.select_block(ptr::addr_of!(self.x_gpe0_block), self.gpe0_block, self.gpe0_block_length * 8, policy)
     }
 
     pub fn gpe1_block(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.x_gpe1_block.access(self.header().revision) } {
-            if raw.address != 0x0 {
-                return Ok(Some(GenericAddress::from_raw(raw)?));
+        self.gpe1_block_with_policy(AddressPolicy::default())
+    }
+
+    pub fn gpe1_block_with_policy(&self, policy: AddressPolicy) -> Result<Option<GenericAddress>, AcpiError> {
+        if self.is_hardware_reduced() {
+            return Ok(None);
+        }
+
+        self.select_block(ptr::addr_of!(self.x_gpe1_block), self.gpe1_block, self.gpe1_block_length * 8, policy)
+    }
+
+    /// Choose between the extended and legacy addresses of a register block, returning `None` if neither is
+    /// provided.
+    fn select_block(
+        &self,
+        extended: *const ExtendedField<RawGenericAddress, 2>,
+        legacy: u32,
+        bit_width: u8,
+        policy: AddressPolicy,
+    ) -> Result<Option<GenericAddress>, AcpiError> {
+        let extended = self.header.extended_field(extended);
+        let extended_address = extended.map_or(0, |raw| raw.address);
+
+        match extended {
+            Some(raw) if policy.use_extended(extended_address, legacy.into())? => {
+                Ok(Some(GenericAddress::from_raw(raw)?))
             }
+            _ if legacy != 0 => Ok(Some(Fadt::legacy_block(legacy, bit_width))),
+            _ => Ok(None),
         }
+    }
 
-        if self.gpe1_block != 0 {
-            Ok(Some(GenericAddress {
-                address_space: AddressSpace::SystemIo,
-                bit_width: self.gpe1_block_length * 8,
-                bit_offset: 0,
-                access_size: AccessSize::Undefined,
-                address: self.gpe1_block.into(),
-            }))
-        } else {
-            Ok(None)
+    /// The legacy register blocks are always in the I/O space.
+    fn legacy_block(address: u32, bit_width: u8) -> GenericAddress {
+        GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width,
+            bit_offset: 0,
+            access_size: AccessSize::Undefined,
+            address: address.into(),
         }
     }
 
+    /// Get the reset register. This was added in ACPI 2.0, so returns [`AcpiError::ResetNotSupported`] on
+    /// platforms with an older FADT.
     pub fn reset_register(&self) -> Result<GenericAddress, AcpiError> {
-        GenericAddress::from_raw(self.reset_reg)
+        match self.header.extended_field(ptr::addr_of!(self.reset_reg)) {
+            Some(raw) => GenericAddress::from_raw(raw),
+            None => Err(AcpiError::ResetNotSupported),
+        }
+    }
+
+    /// The value to write to the reset register to reset the system, if the FADT has a reset register.
+    pub fn reset_value(&self) -> Option<u8> {
+        self.header.extended_field(ptr::addr_of!(self.reset_value))
+    }
+
+    /// Reset the system, by writing `reset_value` to the reset register. Returns
+    /// [`AcpiError::ResetNotSupported`] if the FADT doesn't describe a usable reset register. If the reset
+    /// succeeds, this doesn't return, but the OS should be prepared to try another method of resetting the system
+    /// if it does.
+    pub fn reset<H>(&self, handler: &H) -> Result<(), AcpiError>
+    where
+        H: RegisterHandler,
+    {
+        if !{ self.flags }.supports_system_reset_via_fadt() {
+            return Err(AcpiError::ResetNotSupported);
+        }
+        let (reset_register, reset_value) = match (self.reset_register(), self.reset_value()) {
+            (Ok(reset_register), Some(reset_value)) if reset_register.address != 0 => {
+                (reset_register, reset_value)
+            }
+            _ => return Err(AcpiError::ResetNotSupported),
+        };
+
+        /*
+         * The reset register must be accessed as a single byte, so we override the access size in case the
+         * firmware didn't specify it.
+         */
+        let reset_register = GenericAddress { access_size: AccessSize::ByteAccess, ..reset_register };
+        match reset_register.address_space {
+            AddressSpace::SystemMemory | AddressSpace::SystemIo | AddressSpace::PciConfigSpace => {
+                reset_register.write_register(handler, reset_value as u64)
+            }
+            address_space => Err(AcpiError::UnsupportedAddressSpace(address_space)),
+        }
     }
 
     pub fn sleep_control_register(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.sleep_control_reg.access(self.header().revision) } {
-            Ok(Some(GenericAddress::from_raw(raw)?))
-        } else {
-            Ok(None)
+        match self.header.extended_field(ptr::addr_of!(self.sleep_control_reg)) {
+            Some(raw) if raw.address != 0x0 => Ok(Some(GenericAddress::from_raw(raw)?)),
+            _ => Ok(None),
         }
     }
 
     pub fn sleep_status_register(&self) -> Result<Option<GenericAddress>, AcpiError> {
-        if let Some(raw) = unsafe { self.sleep_status_reg.access(self.header().revision) } {
-            Ok(Some(GenericAddress::from_raw(raw)?))
+        match self.header.extended_field(ptr::addr_of!(self.sleep_status_reg)) {
+            Some(raw) if raw.address != 0x0 => Ok(Some(GenericAddress::from_raw(raw)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Get the registers used to enter sleep states, and to check whether the platform has woken. These are the
+    /// PM1 control and status registers, or the sleep control and status registers on hardware-reduced
+    /// platforms.
+    pub fn sleep_registers(&self) -> Result<SleepRegisters, AcpiError> {
+        self.sleep_registers_with_policy(AddressPolicy::default())
+    }
+
+    /// Like [`Fadt::sleep_registers`], but choosing between the legacy and extended addresses of the PM1 blocks
+    /// according to `policy`.
+    pub fn sleep_registers_with_policy(&self, policy: AddressPolicy) -> Result<SleepRegisters, AcpiError> {
+        if self.is_hardware_reduced() {
+            match (self.sleep_control_register()?, self.sleep_status_register()?) {
+                (Some(control), Some(status)) => Ok(SleepRegisters::SleepControl { control, status }),
+                _ => Err(AcpiError::HardwareReduced),
+            }
         } else {
-            Ok(None)
+            Ok(SleepRegisters::Pm1 {
+                pm1a_control: self.pm1a_control_block_with_policy(policy)?,
+                pm1b_control: self.pm1b_control_block_with_policy(policy)?,
+                pm1a_event: self.pm1a_event_block_with_policy(policy)?,
+                pm1b_event: self.pm1b_event_block_with_policy(policy)?,
+            })
         }
     }
 }
 
+/// The registers used to enter sleep states. See [`Fadt::sleep_registers`].
 #[derive(Clone, Copy, Debug)]
+pub enum SleepRegisters {
+    /// The PM1 register blocks. The `SLP_TYP` and `SLP_EN` fields are in the control blocks, and the `WAK_STS`
+    /// field is in the status register at the start of the event blocks. Each field must be written to both the
+    /// `a` and `b` blocks, if the `b` block exists.
+    Pm1 {
+        pm1a_control: GenericAddress,
+        pm1b_control: Option<GenericAddress>,
+        pm1a_event: GenericAddress,
+        pm1b_event: Option<GenericAddress>,
+    },
+    /// The sleep control and status registers of a hardware-reduced platform, which contain the `SLP_TYPx`,
+    /// `SLP_EN`, and `WAK_STS` fields in a single 8-bit register each.
+    SleepControl { control: GenericAddress, status: GenericAddress },
+}
+
+#[derive(Clone, Copy, Debug, Default)]
 pub struct FixedFeatureFlags(u32);
 
 impl FixedFeatureFlags {
+    pub const fn from_bits(bits: u32) -> FixedFeatureFlags {
+        FixedFeatureFlags(bits)
+    }
+
     /// If true, an equivalent to the x86 [WBINVD](https://www.felixcloutier.com/x86/wbinvd) instruction is supported.
     /// All caches will be flushed and invalidated upon completion of this instruction,
     /// and memory coherency is properly maintained. The cache *SHALL* only contain what OSPM references or allows to be cached.
@@ -468,10 +851,14 @@ impl FixedFeatureFlags {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct IaPcBootArchFlags(u16);
 
 impl IaPcBootArchFlags {
+    pub const fn from_bits(bits: u16) -> IaPcBootArchFlags {
+        IaPcBootArchFlags(bits)
+    }
+
     /// If true, legacy user-accessible devices are available on the LPC and/or ISA buses.
     pub fn legacy_devices_are_accessible(&self) -> bool {
         self.0.get_bit(0)
@@ -505,10 +892,23 @@ impl IaPcBootArchFlags {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct ArmBootArchFlags(u16);
 
+/// The instruction used to make PSCI calls to the firmware (or hypervisor).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PsciConduit {
+    /// PSCI calls are made using `SMC`, and handled by the secure firmware.
+    Smc,
+    /// PSCI calls are made using `HVC`, and handled by the hypervisor.
+    Hvc,
+}
+
 impl ArmBootArchFlags {
+    pub const fn from_bits(bits: u16) -> ArmBootArchFlags {
+        ArmBootArchFlags(bits)
+    }
+
     /// If true, the system implements PSCI.
     pub fn implements_psci(&self) -> bool {
         self.0.get_bit(0)
@@ -519,3 +919,168 @@ impl ArmBootArchFlags {
         self.0.get_bit(1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, finish_table, HandlerState, TableBuffer, TestHandler};
+    use std::vec::Vec;
+
+    fn io_block(port: u64, bit_width: u8) -> Option<GenericAddress> {
+        Some(GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width,
+            bit_offset: 0,
+            access_size: AccessSize::Undefined,
+            address: port,
+        })
+    }
+
+    /// The bytes of a FADT described by `builder`, with the given revision, truncated to `length` bytes.
+    fn fadt_bytes(builder: &FadtBuilder, revision: u8, length: usize) -> Vec<u8> {
+        let header = SdtHeader {
+            signature: Signature::FADT,
+            length: 0,
+            revision,
+            checksum: 0,
+            oem_id: *b"RUSTOS",
+            oem_table_id: *b"TESTTBL ",
+            oem_revision: 1,
+            creator_id: 0,
+            creator_revision: 0,
+        };
+        let mut bytes = bytes_of(&Fadt::new(header, builder));
+        bytes.truncate(length);
+        finish_table(&mut bytes);
+        bytes
+    }
+
+    fn builder() -> FadtBuilder {
+        FadtBuilder {
+            dsdt_address: 0x1000,
+            pm1a_event_block: io_block(0x400, 32),
+            pm1a_control_block: io_block(0x404, 16),
+            pm_timer_block: io_block(0x408, 32),
+            flags: FixedFeatureFlags::from_bits(1 << 10),
+            reset_register: io_block(0xcf9, 8),
+            reset_value: 0x06,
+            smi_cmd_port: 0xb2,
+            acpi_enable: 0xa0,
+            acpi_disable: 0xa1,
+            ..Default::default()
+        }
+    }
+
+    /// Simulates firmware that sets and clears `SCI_EN` when it's asked to over the SMI command port.
+    fn respond_to_smi_command(state: &mut HandlerState, port: u16, value: u32) {
+        if port == 0xb2 {
+            let pm1a_control = state.io.entry(0x404).or_insert(0);
+            match value {
+                0xa0 => *pm1a_control |= 1,
+                0xa1 => *pm1a_control &= !1,
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn test_acpi_1_fadt() {
+        // The ACPI 1.0 FADT is 116 bytes long, and ends before the reset register. Everything after it reads as
+        // `0xff`, so reads of the missing fields would be noticed.
+        let handler = TestHandler::default();
+        for revision in [1, 3] {
+            let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), revision, 116));
+            let fadt = table.get();
+            assert!(AcpiTable::validate(fadt).is_ok());
+
+            assert!(matches!(fadt.reset_register(), Err(AcpiError::ResetNotSupported)));
+            assert_eq!(fadt.reset_value(), None);
+            assert!(matches!(fadt.reset(&handler), Err(AcpiError::ResetNotSupported)));
+            assert_eq!(fadt.dsdt_address().unwrap(), 0x1000);
+            assert_eq!(fadt.pm1a_event_block().unwrap().address, 0x400);
+            assert_eq!(fadt.pm_timer_block().unwrap().unwrap().address, 0x408);
+            assert_eq!(fadt.sleep_control_register().unwrap(), None);
+            assert_eq!(fadt.version(), (revision, 0));
+        }
+        assert!(handler.state().io_writes.is_empty());
+
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), 1, 115));
+        assert!(matches!(AcpiTable::validate(table.get()), Err(AcpiError::SdtInvalidLength(Signature::FADT))));
+    }
+
+    #[test]
+    fn test_reset() {
+        let handler = TestHandler::new(HandlerState::default());
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), 6, mem::size_of::<Fadt>()));
+        let fadt = table.get();
+        assert_eq!(fadt.reset_value(), Some(0x06));
+        fadt.reset(&handler).unwrap();
+        assert_eq!(handler.state().io_writes, [(0xcf9, 0x06)]);
+
+        // A reset register in an invalid address space is treated as missing
+        let mut bytes = fadt_bytes(&builder(), 6, mem::size_of::<Fadt>());
+        bytes[mem::offset_of!(Fadt, reset_reg)] = 0x55;
+        finish_table(&mut bytes);
+        let table = TableBuffer::<Fadt>::new(&bytes);
+        assert!(matches!(table.get().reset(&handler), Err(AcpiError::ResetNotSupported)));
+
+        let builder = FadtBuilder { flags: FixedFeatureFlags::from_bits(0), ..builder() };
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder, 6, mem::size_of::<Fadt>()));
+        assert!(matches!(table.get().reset(&handler), Err(AcpiError::ResetNotSupported)));
+        assert_eq!(handler.state().io_writes.len(), 1);
+    }
+
+    #[test]
+    fn test_enable_acpi() {
+        let handler =
+            TestHandler::new(HandlerState { on_io_write: Some(respond_to_smi_command), ..Default::default() });
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), 6, mem::size_of::<Fadt>()));
+        let fadt = table.get();
+        let mut stalled = 0;
+        let mut stall = |micros| {
+            stalled += micros;
+            Ok(())
+        };
+
+        assert!(!fadt.acpi_mode_enabled(&handler).unwrap());
+        fadt.enable_acpi(&handler, &mut stall).unwrap();
+        assert!(fadt.acpi_mode_enabled(&handler).unwrap());
+        fadt.enable_acpi(&handler, &mut stall).unwrap();
+        fadt.disable_acpi(&handler, &mut stall).unwrap();
+        assert!(!fadt.acpi_mode_enabled(&handler).unwrap());
+        assert_eq!(handler.state().io_writes.iter().filter(|&&(port, _)| port == 0xb2).count(), 2);
+        assert_eq!(stalled, 0);
+    }
+
+    #[test]
+    fn test_enable_acpi_timeout() {
+        let handler = TestHandler::default();
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), 6, mem::size_of::<Fadt>()));
+        let fadt = table.get();
+        let mut stalled = 0;
+        let result = fadt.enable_acpi(&handler, |micros| {
+            stalled += micros;
+            Ok(())
+        });
+        assert!(matches!(result, Err(AcpiError::AcpiModeTimeout)));
+        assert_eq!(stalled, ACPI_MODE_TIMEOUT_MICROS);
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_enable_acpi_timeout_with_pm_timer() {
+        use crate::platform::PmTimer;
+
+        // Each read of the simulated PM timer advances it by about 10 microseconds.
+        let handler = TestHandler::new(HandlerState { pm_timer: Some((0x408, 36)), ..Default::default() });
+        let table = TableBuffer::<Fadt>::new(&fadt_bytes(&builder(), 6, mem::size_of::<Fadt>()));
+        let fadt = table.get();
+        let pm_timer = PmTimer::new(fadt).unwrap().unwrap();
+
+        let start = pm_timer.read(&handler).unwrap();
+        let result = fadt.enable_acpi(&handler, |micros| pm_timer.stall(&handler, micros));
+        let elapsed = pm_timer.ticks_between(start, pm_timer.read(&handler).unwrap());
+        assert!(matches!(result, Err(AcpiError::AcpiModeTimeout)));
+        assert!(elapsed as u64 >= PmTimer::micros_to_ticks(ACPI_MODE_TIMEOUT_MICROS));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    device,
+    device_power::{DevicePower, DevicePowerState, PowerResources},
+    AcpiError,
+    AcpiResult,
+};
+use alloc::{vec, vec::Vec};
+use aml::{
+    value::{AmlValue, Args},
+    AmlContext,
+    AmlName,
+};
+
+/// The value fans report for a field that is unknown.
+const FAN_VALUE_UNKNOWN: u64 = 0xffff_ffff;
+
+#[derive(Debug)]
+pub enum FanError {
+    /// The `_FIF`, `_FPS`, or `_FST` object of the fan isn't a package of the expected form.
+    InvalidObject(&'static str),
+    /// The fan's speed can't be controlled, because it doesn't implement `_FSL`.
+    SpeedControlNotSupported,
+    /// The level isn't valid for the fan: it isn't a percentage for a fan with fine-grain control, or the control
+    /// value of one of its performance states otherwise.
+    InvalidLevel(u64),
+}
+
+/// The capabilities of a fan that supports the ACPI 4.0 fan extensions, from its `_FIF` object.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FanInfo {
+    /// If true, the fan's speed is set as a percentage (from 0 to 100) of its maximum, rather than with the
+    /// control value of one of its performance states.
+    pub fine_grain_control: bool,
+    /// The recommended step size, in percent, between the levels the OS sets a fan with fine-grain control to.
+    pub step_size: u8,
+    /// If true, the fan will `Notify` the OS when its speed drops below that of the level that was set for it.
+    pub low_speed_notification: bool,
+}
+
+/// A performance state of a fan, from its `_FPS` object. Values that the fan reports as unknown are `None`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FanPerformanceState {
+    /// The value to pass to `_FSL` to put the fan into this state.
+    pub control: u64,
+    /// The index of the active cooling trip point (`_ACx`) at which the fan should be in this state.
+    pub trip_point: Option<u64>,
+    /// In RPM.
+    pub speed: Option<u64>,
+    /// In tenths of a decibel.
+    pub noise_level: Option<u64>,
+    /// In milliwatts.
+    pub power: Option<u64>,
+}
+
+/// The current state of a fan, from its `_FST` object.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FanStatus {
+    /// The control value of the fan's current level.
+    pub control: u64,
+    /// In RPM, if the fan reports it.
+    pub speed: Option<u64>,
+}
+
+/// A fan device (with a `_HID` of `PNP0C0B`, or `INT3404` for fans managed through Intel's DPTF). All fans can be
+/// turned on or off through their power state, and fans that support the ACPI 4.0 fan extensions can also have
+/// their speed controlled.
+#[derive(Clone, Debug)]
+pub struct Fan {
+    path: AmlName,
+    power: DevicePower,
+    info: Option<FanInfo>,
+    performance_states: Vec<FanPerformanceState>,
+}
+
+impl Fan {
+    /// Find the fans in the namespace, and read their capabilities.
+    pub fn enumerate(context: &mut AmlContext, resources: &mut PowerResources) -> AcpiResult<Vec<Fan>> {
+        let mut paths = device::find_devices_with_hid(context, "PNP0C0B")?;
+        paths.extend(device::find_devices_with_hid(context, "INT3404")?);
+
+        paths.into_iter().map(|path| Fan::new(context, resources, path)).collect()
+    }
+
+    /// Create a fan for the fan device at `path`, and read its capabilities.
+    pub fn new(context: &mut AmlContext, resources: &mut PowerResources, path: AmlName) -> AcpiResult<Fan> {
+        let power = DevicePower::new(context, resources, path.clone())?;
+
+        /*
+         * _FIF := Package { Revision, FineGrainControl, StepSize, LowSpeedNotificationSupport }
+         */
+        let info = match device::evaluate_optional(context, &path, "_FIF", Args::EMPTY)? {
+            Some(fif) => {
+                let fields = package_elements(&fif, 4, "_FIF")?;
+                Some(FanInfo {
+                    fine_grain_control: integer(context, &fields[1])? != 0,
+                    step_size: integer(context, &fields[2])? as u8,
+                    low_speed_notification: integer(context, &fields[3])? != 0,
+                })
+            }
+            None => None,
+        };
+
+        /*
+         * _FPS := Package { Revision, FanPState, ... }
+         * FanPState := Package { Control, TripPoint, Speed, NoiseLevel, Power }
+         */
+        let mut performance_states = Vec::new();
+        if let Some(fps) = device::evaluate_optional(context, &path, "_FPS", Args::EMPTY)? {
+            for state in package_elements(&fps, 1, "_FPS")?[1..].iter() {
+                let fields = package_elements(state, 5, "_FPS")?;
+                performance_states.push(FanPerformanceState {
+                    control: integer(context, &fields[0])?,
+                    trip_point: optional_integer(context, &fields[1])?,
+                    speed: optional_integer(context, &fields[2])?,
+                    noise_level: optional_integer(context, &fields[3])?,
+                    power: optional_integer(context, &fields[4])?,
+                });
+            }
+        }
+
+        Ok(Fan { path, power, info, performance_states })
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// The fan's capabilities, if it supports the ACPI 4.0 fan extensions.
+    pub fn info(&self) -> Option<&FanInfo> {
+        self.info.as_ref()
+    }
+
+    pub fn performance_states(&self) -> &[FanPerformanceState] {
+        &self.performance_states
+    }
+
+    /// Whether the fan is on, as far as its power state is known.
+    pub fn is_on(&self) -> bool {
+        matches!(self.power.state(), Some(DevicePowerState::D0) | None)
+    }
+
+    /// Turn the fan on or off, by putting it into `D0` or `D3Cold`.
+    pub fn set_on(
+        &mut self,
+        context: &mut AmlContext,
+        resources: &mut PowerResources,
+        on: bool,
+    ) -> AcpiResult<()> {
+        let state = if on { DevicePowerState::D0 } else { DevicePowerState::D3Cold };
+        self.power.set_power_state(context, resources, state)
+    }
+
+    /// Read the fan's current status, by evaluating `_FST`. Returns `None` if the fan doesn't implement `_FST`.
+    pub fn status(&self, context: &mut AmlContext) -> AcpiResult<Option<FanStatus>> {
+        /*
+         * _FST := Package { Revision, Control, Speed }
+         */
+        let fst = match device::evaluate_optional(context, &self.path, "_FST", Args::EMPTY)? {
+            Some(fst) => fst,
+            None => return Ok(None),
+        };
+        let fields = package_elements(&fst, 3, "_FST")?;
+
+        Ok(Some(FanStatus {
+            control: integer(context, &fields[1])?,
+            speed: optional_integer(context, &fields[2])?,
+        }))
+    }
+
+    /// Set the fan's speed, by evaluating `_FSL`. For fans with fine-grain control, `level` is a percentage of the
+    /// fan's maximum speed; otherwise, it must be the control value of one of the fan's performance states.
+    pub fn set_level(&self, context: &mut AmlContext, level: u64) -> AcpiResult<()> {
+        let valid = match self.info {
+            Some(FanInfo { fine_grain_control: true, .. }) => level <= 100,
+            _ => self.performance_states.iter().any(|state| state.control == level),
+        };
+        if !valid {
+            return Err(AcpiError::Fan(FanError::InvalidLevel(level)));
+        }
+
+        let args = Args::from_list(vec![AmlValue::Integer(level)]).unwrap();
+        match device::evaluate_optional(context, &self.path, "_FSL", args)? {
+            Some(_) => Ok(()),
+            None => Err(AcpiError::Fan(FanError::SpeedControlNotSupported)),
+        }
+    }
+}
+
+fn package_elements<'a>(
+    value: &'a AmlValue,
+    min_length: usize,
+    object: &'static str,
+) -> AcpiResult<&'a [AmlValue]> {
+    match value {
+        AmlValue::Package(elements) if elements.len() >= min_length => Ok(elements),
+        _ => Err(AcpiError::Fan(FanError::InvalidObject(object))),
+    }
+}
+
+fn integer(context: &AmlContext, value: &AmlValue) -> AcpiResult<u64> {
+    value.as_integer(context).map_err(AcpiError::Aml)
+}
+
+fn optional_integer(context: &AmlContext, value: &AmlValue) -> AcpiResult<Option<u64>> {
+    match integer(context, value)? {
+        FAN_VALUE_UNKNOWN => Ok(None),
+        value => Ok(Some(value)),
+    }
+}
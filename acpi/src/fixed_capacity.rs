@@ -0,0 +1,252 @@
+use crate::{AcpiError, AcpiResult};
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::{Cell, UnsafeCell},
+    fmt,
+    mem::MaybeUninit,
+    ops::Deref,
+    ptr::NonNull,
+    slice,
+};
+
+/// Fixed-capacity storage for the results of parsing tables, for environments that don't have an allocator yet
+/// (e.g. early in boot, when the memory map is still being worked out from the tables). It holds `N` bytes inline,
+/// so it can be put on the stack or in a `static`, and a reference to it can be used anywhere the crate takes an
+/// allocator - e.g. `tables.platform_info_in(&storage)`, or `PciConfigRegions::new(&tables, &storage)`. Parsing
+/// fails with `AcpiError::AllocError` if the results don't fit. Only references to it are allocators, so the
+/// storage can't be moved while anything allocated from it is alive.
+///
+/// Memory is handed out from the start of the storage, and is only reclaimed when the most recent allocation is
+/// freed (or grows or shrinks in place), so it suits the short-lived vectors used while parsing. Everything can be
+/// reclaimed at once with [`FixedCapacity::reset`], once the results have been dropped.
+pub struct FixedCapacity<const N: usize> {
+    storage: UnsafeCell<[MaybeUninit<u8>; N]>,
+    used: Cell<usize>,
+}
+
+impl<const N: usize> FixedCapacity<N> {
+    pub const fn new() -> FixedCapacity<N> {
+        FixedCapacity { storage: UnsafeCell::new([MaybeUninit::uninit(); N]), used: Cell::new(0) }
+    }
+
+    /// The number of bytes that have been handed out, including any padding needed to align them.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    pub fn remaining(&self) -> usize {
+        N - self.used.get()
+    }
+
+    /// Reclaim all of the storage. This takes `&mut self`, so nothing allocated from it can still be alive.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+    }
+
+    fn base(&self) -> *mut u8 {
+        self.storage.get().cast()
+    }
+
+    /// Whether `ptr` is the start of the most recent allocation, which is `size` bytes long.
+    fn is_last(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        ptr.as_ptr() as usize + size == self.base() as usize + self.used.get()
+    }
+}
+
+impl<const N: usize> Default for FixedCapacity<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety: Memory is only handed out once until it's freed, and is within `storage`. The allocator is a reference
+// to the `FixedCapacity`, so the storage can't be moved or dropped while anything allocated from it is alive.
+unsafe impl<const N: usize> Allocator for &FixedCapacity<N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            // Zero-sized allocations don't need any storage, just a well-aligned pointer
+            let dangling = NonNull::new(layout.align() as *mut u8).unwrap();
+            return Ok(NonNull::slice_from_raw_parts(dangling, 0));
+        }
+
+        let start = self.base() as usize + self.used.get();
+        let padding = start.wrapping_neg() & (layout.align() - 1);
+        let end = self.used.get().checked_add(padding).and_then(|offset| offset.checked_add(layout.size()));
+        match end {
+            Some(end) if end <= N => {
+                // Safety: The allocation is within `storage`.
+                let ptr = unsafe { self.base().add(self.used.get() + padding) };
+                self.used.set(end);
+                Ok(NonNull::slice_from_raw_parts(NonNull::new(ptr).unwrap(), layout.size()))
+            }
+            _ => Err(AllocError),
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 && self.is_last(ptr, layout.size()) {
+            self.used.set(self.used.get() - layout.size());
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        /*
+         * The most recent allocation can be grown in place, if it's aligned well enough and there's space after it.
+         * Zero-sized allocations have a dangling pointer that isn't in the storage, so they're never grown in place.
+         */
+        if old_layout.size() != 0
+            && self.is_last(ptr, old_layout.size())
+            && (ptr.as_ptr() as usize).is_multiple_of(new_layout.align())
+        {
+            let offset = ptr.as_ptr() as usize - self.base() as usize;
+            if offset + new_layout.size() <= N {
+                self.used.set(offset + new_layout.size());
+                return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+            }
+        }
+
+        let new = self.allocate(new_layout)?;
+        // Safety: The old allocation is valid for reads of its size, and doesn't overlap the new one.
+        unsafe {
+            core::ptr::copy_nonoverlapping(ptr.as_ptr(), new.as_mut_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !(ptr.as_ptr() as usize).is_multiple_of(new_layout.align()) {
+            return Err(AllocError);
+        }
+
+        // The memory is shrunk in place. Only the most recent allocation can give back what it no longer needs.
+        if old_layout.size() != 0 && self.is_last(ptr, old_layout.size()) {
+            self.used.set(self.used.get() - (old_layout.size() - new_layout.size()));
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+}
+
+/// A vector with space for `N` elements inline, which backs the structures that hold the results of parsing tables
+/// without an allocator (e.g. [`FixedPlatformInfo`](crate::platform::FixedPlatformInfo)). Pushing more than `N`
+/// elements fails with `AcpiError::AllocError`.
+#[derive(Clone, Copy)]
+pub struct FixedVec<T, const N: usize>
+where
+    T: Copy,
+{
+    elements: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> FixedVec<T, N>
+where
+    T: Copy,
+{
+    pub const fn new() -> FixedVec<T, N> {
+        FixedVec { elements: [MaybeUninit::uninit(); N], len: 0 }
+    }
+
+    /// Copy the elements of `elements` into a new vector.
+    pub fn from_slice(elements: &[T]) -> AcpiResult<FixedVec<T, N>> {
+        let mut vec = FixedVec::new();
+        for &element in elements {
+            vec.push(element)?;
+        }
+        Ok(vec)
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn push(&mut self, value: T) -> AcpiResult<()> {
+        let slot = self.elements.get_mut(self.len).ok_or(AcpiError::AllocError)?;
+        slot.write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // Safety: The first `len` elements have been initialized.
+        unsafe { slice::from_raw_parts(self.elements.as_ptr().cast(), self.len) }
+    }
+}
+
+impl<T, const N: usize> Default for FixedVec<T, N>
+where
+    T: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for FixedVec<T, N>
+where
+    T: Copy,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for FixedVec<T, N>
+where
+    T: Copy + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManagedVec;
+
+    #[test]
+    fn test_grow_zero_sized() {
+        let storage = FixedCapacity::<64>::new();
+        let allocator = &storage;
+
+        // The pointer of a zero-sized allocation is below the storage, so this can't be grown in place
+        let empty = allocator.allocate(Layout::new::<()>()).unwrap();
+        let grown = unsafe { allocator.grow(empty.as_non_null_ptr(), Layout::new::<()>(), Layout::new::<u64>()) };
+        assert_eq!(grown.unwrap().len(), 8);
+        assert_eq!(storage.used(), 8);
+    }
+
+    #[test]
+    fn test_vec_in_storage() {
+        let storage = FixedCapacity::<64>::new();
+        let mut vec = ManagedVec::<u32, _>::new_in(&storage);
+        for i in 0..16 {
+            vec.try_push(i).unwrap();
+        }
+        assert_eq!(vec.iter().copied().sum::<u32>(), 120);
+        assert!(vec.try_push(16).is_err());
+    }
+
+    #[test]
+    fn test_fixed_vec() {
+        let mut vec = FixedVec::<u8, 2>::new();
+        vec.push(1).unwrap();
+        vec.push(2).unwrap();
+        assert!(matches!(vec.push(3), Err(AcpiError::AllocError)));
+        assert_eq!(*vec, [1, 2]);
+        assert!(matches!(FixedVec::<u8, 2>::from_slice(&[1, 2, 3]), Err(AcpiError::AllocError)));
+    }
+}
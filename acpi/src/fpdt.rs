@@ -0,0 +1,356 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiHandler,
+    AcpiTable,
+    PhysicalMapping,
+};
+use core::{marker::PhantomData, mem};
+
+/// Represents the Firmware Performance Data Table (FPDT). This doesn't contain any performance data itself, but
+/// points to the Firmware Basic Boot Performance Table ([`Fbpt`]), which records the timings of the last boot,
+/// and the S3 Performance Table ([`S3pt`]), which records the timings of the last suspend and resume. These
+/// tables are not listed in the RSDT/XSDT, and so must be mapped with [`Fpdt::map_fbpt`] and
+/// [`Fpdt::map_s3pt`].
+///
+/// All timings are in nanoseconds, measured from the reset of the processor.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Fpdt {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid FPDT.
+unsafe impl AcpiTable for Fpdt {
+    const SIGNATURE: Signature = Signature::FPDT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Fpdt {
+    pub fn records(&self) -> PerformanceRecordIter<'_> {
+        PerformanceRecordIter {
+            pointer: unsafe { (self as *const Fpdt as *const u8).add(mem::size_of::<Fpdt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Fpdt>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The physical address of the FBPT, if the FPDT points to one.
+    pub fn fbpt_address(&self) -> Option<u64> {
+        self.pointer_record(0)
+    }
+
+    /// The physical address of the S3PT, if the FPDT points to one.
+    pub fn s3pt_address(&self) -> Option<u64> {
+        self.pointer_record(1)
+    }
+
+    pub fn map_fbpt<H>(&self, handler: &H) -> Option<PhysicalMapping<H, Fbpt>>
+    where
+        H: AcpiHandler,
+    {
+        unsafe { map_performance_table(handler, self.fbpt_address()?, Fbpt::SIGNATURE) }
+    }
+
+    pub fn map_s3pt<H>(&self, handler: &H) -> Option<PhysicalMapping<H, S3pt>>
+    where
+        H: AcpiHandler,
+    {
+        unsafe { map_performance_table(handler, self.s3pt_address()?, S3pt::SIGNATURE) }
+    }
+
+    fn pointer_record(&self, record_type: u16) -> Option<u64> {
+        self.records()
+            .filter(|record| record.record_type == record_type)
+            .find_map(|record| record.as_record::<PointerRecord>())
+            .map(|record| record.address)
+            .filter(|&address| address != 0)
+    }
+}
+
+/// ### Safety: `address` must be the physical address of a performance table.
+unsafe fn map_performance_table<H, T>(
+    handler: &H,
+    address: u64,
+    signature: [u8; 4],
+) -> Option<PhysicalMapping<H, T>>
+where
+    H: AcpiHandler,
+{
+    let address = address as usize;
+    let header = unsafe {
+        handler.map_physical_region::<PerformanceTableHeader>(address, mem::size_of::<PerformanceTableHeader>())
+    };
+    let length = header.length as usize;
+    if header.signature != signature || length < mem::size_of::<T>() {
+        return None;
+    }
+
+    Some(unsafe { handler.map_physical_region::<T>(address, length) })
+}
+
+#[derive(Debug)]
+pub struct PerformanceRecordIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for PerformanceRecordIter<'a> {
+    type Item = &'a PerformanceRecordHeader;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<PerformanceRecordHeader>() {
+            return None;
+        }
+
+        let record = unsafe { &*(self.pointer as *const PerformanceRecordHeader) };
+        let length = record.length as usize;
+        if length < mem::size_of::<PerformanceRecordHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(record)
+    }
+}
+
+/// The header of a performance record. The meaning of `record_type` depends on which table the record is in.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PerformanceRecordHeader {
+    pub record_type: u16,
+    pub length: u8,
+    pub revision: u8,
+}
+
+impl PerformanceRecordHeader {
+    /// Interpret the record as a `T`, if it is long enough to be one.
+    fn as_record<T>(&self) -> Option<&T> {
+        if (self.length as usize) < mem::size_of::<T>() {
+            return None;
+        }
+
+        Some(unsafe { &*(self as *const PerformanceRecordHeader as *const T) })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+struct PointerRecord {
+    header: PerformanceRecordHeader,
+    _reserved: u32,
+    address: u64,
+}
+
+/// The header shared by the FBPT and S3PT. Unlike an `SdtHeader`, this doesn't have a checksum.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PerformanceTableHeader {
+    pub signature: [u8; 4],
+    pub length: u32,
+}
+
+/// Represents the Firmware Basic Boot Performance Table (FBPT).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Fbpt {
+    pub header: PerformanceTableHeader,
+}
+
+impl Fbpt {
+    pub const SIGNATURE: [u8; 4] = *b"FBPT";
+
+    pub fn records(&self) -> PerformanceRecordIter<'_> {
+        PerformanceRecordIter {
+            pointer: unsafe { (self as *const Fbpt as *const u8).add(mem::size_of::<Fbpt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Fbpt>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn basic_boot_record(&self) -> Option<&BasicBootPerformanceRecord> {
+        self.records().filter(|record| record.record_type == 2).find_map(|record| record.as_record())
+    }
+}
+
+/// Records the timings of the stages of the last boot. A timing of `0` means it was not recorded.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct BasicBootPerformanceRecord {
+    pub header: PerformanceRecordHeader,
+    _reserved: u32,
+    /// When the firmware finished executing the reset vector (i.e. when it started running after reset).
+    pub reset_end: u64,
+    /// When the OS loader was loaded into memory.
+    pub os_loader_load_image_start: u64,
+    /// When the OS loader started executing.
+    pub os_loader_start_image_start: u64,
+    /// When the OS loader called `ExitBootServices`.
+    pub exit_boot_services_entry: u64,
+    /// When `ExitBootServices` returned to the OS loader.
+    pub exit_boot_services_exit: u64,
+}
+
+/// Represents the S3 Performance Table (S3PT).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct S3pt {
+    pub header: PerformanceTableHeader,
+}
+
+impl S3pt {
+    pub const SIGNATURE: [u8; 4] = *b"S3PT";
+
+    pub fn records(&self) -> PerformanceRecordIter<'_> {
+        PerformanceRecordIter {
+            pointer: unsafe { (self as *const S3pt as *const u8).add(mem::size_of::<S3pt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<S3pt>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn resume_record(&self) -> Option<&BasicS3ResumeRecord> {
+        self.records().filter(|record| record.record_type == 0).find_map(|record| record.as_record())
+    }
+
+    pub fn suspend_record(&self) -> Option<&BasicS3SuspendRecord> {
+        self.records().filter(|record| record.record_type == 1).find_map(|record| record.as_record())
+    }
+}
+
+/// Records the timings of resuming from S3, which are updated by the firmware on every resume.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct BasicS3ResumeRecord {
+    pub header: PerformanceRecordHeader,
+    /// The number of times the platform has resumed from S3 since the last full boot.
+    pub resume_count: u32,
+    /// How long the last resume took, from the reset vector to the firmware handing control to the OS waking
+    /// vector.
+    pub full_resume: u64,
+    /// The average of `full_resume` across all of the resumes since the last full boot.
+    pub average_resume: u64,
+}
+
+/// Records the timings of the last suspend to S3. Unlike the other timings, these are written by the OS, and
+/// measured from the same timebase as the resume timings.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct BasicS3SuspendRecord {
+    pub header: PerformanceRecordHeader,
+    /// When the OS started the suspend (i.e. when it began executing `_PTS`).
+    pub suspend_start: u64,
+    /// When the OS finished the suspend (i.e. just before it wrote `SLP_EN`).
+    pub suspend_end: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, zeroed, TableBuffer, TestHandler};
+    use std::vec::Vec;
+
+    fn pointer_record(record_type: u16, address: u64) -> Vec<u8> {
+        bytes_of(&PointerRecord {
+            header: PerformanceRecordHeader {
+                record_type,
+                length: mem::size_of::<PointerRecord>() as u8,
+                revision: 1,
+            },
+            _reserved: 0,
+            address,
+        })
+    }
+
+    fn performance_table(signature: &[u8; 4], records: &[Vec<u8>]) -> Vec<u8> {
+        let length = mem::size_of::<PerformanceTableHeader>() + records.iter().map(Vec::len).sum::<usize>();
+        let mut bytes = bytes_of(&PerformanceTableHeader { signature: *signature, length: length as u32 });
+        for record in records {
+            bytes.extend(record);
+        }
+        bytes
+    }
+
+    fn record_header<T>(record_type: u16) -> PerformanceRecordHeader {
+        PerformanceRecordHeader { record_type, length: mem::size_of::<T>() as u8, revision: 1 }
+    }
+
+    #[test]
+    fn test_map_performance_tables() {
+        let fbpt = TableBuffer::<Fbpt>::new(&performance_table(
+            b"FBPT",
+            &[bytes_of(&BasicBootPerformanceRecord {
+                header: record_header::<BasicBootPerformanceRecord>(2),
+                reset_end: 1_000,
+                exit_boot_services_exit: 5_000,
+                ..unsafe { zeroed() }
+            })],
+        ));
+        let s3pt = TableBuffer::<S3pt>::new(&performance_table(
+            b"S3PT",
+            &[
+                bytes_of(&BasicS3ResumeRecord {
+                    header: record_header::<BasicS3ResumeRecord>(0),
+                    resume_count: 3,
+                    full_resume: 200,
+                    average_resume: 150,
+                }),
+                bytes_of(&BasicS3SuspendRecord {
+                    header: record_header::<BasicS3SuspendRecord>(1),
+                    suspend_start: 10,
+                    suspend_end: 20,
+                }),
+            ],
+        ));
+
+        let mut body = pointer_record(0, fbpt.get() as *const Fbpt as u64);
+        body.extend(pointer_record(1, s3pt.get() as *const S3pt as u64));
+        let buffer = TableBuffer::<Fpdt>::new(&table(b"FPDT", 1, &body));
+        let fpdt = buffer.get();
+        assert!(fpdt.validate().is_ok());
+        assert_eq!(fpdt.records().count(), 2);
+
+        let handler = TestHandler::default();
+        let fbpt = fpdt.map_fbpt(&handler).unwrap();
+        let boot = fbpt.basic_boot_record().unwrap();
+        assert_eq!({ boot.reset_end }, 1_000);
+        assert_eq!({ boot.exit_boot_services_exit }, 5_000);
+
+        let s3pt = fpdt.map_s3pt(&handler).unwrap();
+        assert_eq!({ s3pt.resume_record().unwrap().resume_count }, 3);
+        assert_eq!({ s3pt.suspend_record().unwrap().suspend_end }, 20);
+    }
+
+    #[test]
+    fn test_missing_and_truncated_records() {
+        // A null S3PT pointer, and an FBPT pointer to a table with the wrong signature
+        let not_fbpt = TableBuffer::<Fbpt>::new(&performance_table(b"S3PT", &[]));
+        let mut body = pointer_record(0, not_fbpt.get() as *const Fbpt as u64);
+        body.extend(pointer_record(1, 0));
+        let buffer = TableBuffer::<Fpdt>::new(&table(b"FPDT", 1, &body));
+        assert!(buffer.get().s3pt_address().is_none());
+        assert!(buffer.get().map_fbpt(&TestHandler::default()).is_none());
+
+        // A pointer record that runs past the end of the table
+        let body = pointer_record(0, 0x1000);
+        let buffer = TableBuffer::<Fpdt>::new(&table(b"FPDT", 1, &body[..body.len() - 1]));
+        assert_eq!(buffer.get().records().count(), 0);
+        assert!(buffer.get().fbpt_address().is_none());
+
+        // A boot record that is too short is skipped
+        let mut record = bytes_of(&BasicBootPerformanceRecord {
+            header: record_header::<BasicBootPerformanceRecord>(2),
+            ..unsafe { zeroed() }
+        });
+        record[2] = 16;
+        record.truncate(16);
+        let fbpt = TableBuffer::<Fbpt>::new(&performance_table(b"FBPT", &[record]));
+        assert_eq!(fbpt.get().records().count(), 1);
+        assert!(fbpt.get().basic_boot_record().is_none());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    sdt::{ExtendedField, SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, ptr, slice};
+
+/// Represents the Generic Timer Description Table (GTDT), which describes the ARM Generic Timer: the interrupts
+/// used by the per-processor timers, and any memory-mapped platform timers (GT blocks and SBSA watchdogs). You can
+/// iterate over the platform timers with [`Gtdt::platform_timers`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Gtdt {
+    pub header: SdtHeader,
+    /// The physical address of the `CNTControlBase` frame, or `0xffff_ffff_ffff_ffff` if not provided.
+    pub cnt_control_base_address: u64,
+    _reserved: u32,
+    pub secure_el1_timer_gsiv: u32,
+    pub secure_el1_timer_flags: TimerFlags,
+    pub non_secure_el1_timer_gsiv: u32,
+    pub non_secure_el1_timer_flags: TimerFlags,
+    pub virtual_el1_timer_gsiv: u32,
+    pub virtual_el1_timer_flags: TimerFlags,
+    pub el2_timer_gsiv: u32,
+    pub el2_timer_flags: TimerFlags,
+    /// The physical address of the `CNTReadBase` frame, or `0xffff_ffff_ffff_ffff` if not provided.
+    pub cnt_read_base_address: u64,
+    pub platform_timer_count: u32,
+    /// The offset of the platform timer structures from the start of the table.
+    pub platform_timer_offset: u32,
+    virtual_el2_timer_gsiv: ExtendedField<u32, 3>,
+    virtual_el2_timer_flags: ExtendedField<TimerFlags, 3>,
+}
+
+/// ### Safety: Implementation properly represents a valid GTDT.
+unsafe impl AcpiTable for Gtdt {
+    const SIGNATURE: Signature = Signature::GTDT;
+    const MIN_LENGTH: usize = mem::offset_of!(Gtdt, virtual_el2_timer_gsiv);
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Gtdt {
+    /// The GSIV of the virtual EL2 timer. Returns `None` on tables older than revision 3, which do not describe
+    /// this timer.
+    pub fn virtual_el2_timer_gsiv(&self) -> Option<u32> {
+        self.header.extended_field(ptr::addr_of!(self.virtual_el2_timer_gsiv))
+    }
+
+    pub fn virtual_el2_timer_flags(&self) -> Option<TimerFlags> {
+        self.header.extended_field(ptr::addr_of!(self.virtual_el2_timer_flags))
+    }
+
+    pub fn platform_timers(&self) -> PlatformTimerIter<'_> {
+        let offset = self.platform_timer_offset as usize;
+        let table_length = self.header.length as usize;
+
+        PlatformTimerIter {
+            pointer: unsafe { (self as *const Gtdt as *const u8).add(offset) },
+            remaining_length: if offset >= mem::size_of::<SdtHeader>() {
+                table_length.saturating_sub(offset)
+            } else {
+                0
+            },
+            remaining_timers: self.platform_timer_count,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct TimerFlags(u32);
+
+impl TimerFlags {
+    /// If true, the timer interrupt is edge-triggered. Otherwise, it is level-triggered.
+    pub fn is_edge_triggered(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    /// If true, the timer interrupt is active-low. Otherwise, it is active-high.
+    pub fn is_active_low(&self) -> bool {
+        self.0.get_bit(1)
+    }
+
+    /// If true, the timer keeps running and can generate interrupts in all processor power states.
+    pub fn is_always_on(&self) -> bool {
+        self.0.get_bit(2)
+    }
+}
+
+#[derive(Debug)]
+pub struct PlatformTimerIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_timers: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum PlatformTimer<'a> {
+    GtBlock(&'a GtBlock),
+    SbsaWatchdog(&'a SbsaWatchdog),
+}
+
+impl<'a> Iterator for PlatformTimerIter<'a> {
+    type Item = PlatformTimer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_timers > 0 && self.remaining_length >= mem::size_of::<PlatformTimerHeader>() {
+            let timer_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const PlatformTimerHeader) };
+
+            if header.length == 0 || header.length as usize > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length as usize;
+            self.remaining_timers -= 1;
+
+            match header.timer_type {
+                0 if header.length as usize >= mem::size_of::<GtBlock>() => {
+                    return Some(PlatformTimer::GtBlock(unsafe { &*(timer_pointer as *const GtBlock) }))
+                }
+                1 if header.length as usize >= mem::size_of::<SbsaWatchdog>() => {
+                    return Some(PlatformTimer::SbsaWatchdog(unsafe { &*(timer_pointer as *const SbsaWatchdog) }))
+                }
+
+                /*
+                 * Other types are reserved by the ACPI standard. We should skip them if they appear in a real
+                 * GTDT, along with timers that are too short to be read as their type.
+                 */
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PlatformTimerHeader {
+    pub timer_type: u8,
+    pub length: u16,
+}
+
+/// Describes a memory-mapped Generic Timer block, which contains up to 8 timer frames.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GtBlock {
+    pub header: PlatformTimerHeader,
+    _reserved: u8,
+    /// The physical address of the `CNTCTLBase` frame of the block.
+    pub cnt_ctl_base: u64,
+    pub timer_count: u32,
+    /// The offset of the timer structures from the start of this structure.
+    pub timer_offset: u32,
+}
+
+impl GtBlock {
+    pub fn timers(&self) -> &[GtBlockTimer] {
+        let offset = self.timer_offset as usize;
+        let available = (self.header.length as usize).saturating_sub(offset) / mem::size_of::<GtBlockTimer>();
+        let count = usize::min(self.timer_count as usize, available);
+        if count == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let pointer = (self as *const GtBlock as *const u8).add(offset);
+            slice::from_raw_parts(pointer as *const GtBlockTimer, count)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GtBlockTimer {
+    pub frame_number: u8,
+    _reserved: [u8; 3],
+    /// The physical address of the `CNTBaseN` frame of the timer.
+    pub cnt_base: u64,
+    /// The physical address of the `CNTEL0BaseN` frame of the timer, or `0xffff_ffff_ffff_ffff` if not provided.
+    pub cnt_el0_base: u64,
+    pub physical_timer_gsiv: u32,
+    pub physical_timer_flags: TimerFlags,
+    pub virtual_timer_gsiv: u32,
+    pub virtual_timer_flags: TimerFlags,
+    pub common_flags: u32,
+}
+
+impl GtBlockTimer {
+    /// If true, the timer is secure. Otherwise, it is non-secure.
+    pub fn is_secure(&self) -> bool {
+        { self.common_flags }.get_bit(0)
+    }
+
+    pub fn is_always_on(&self) -> bool {
+        { self.common_flags }.get_bit(1)
+    }
+}
+
+/// Describes an SBSA (Server Base System Architecture) Generic Watchdog.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SbsaWatchdog {
+    pub header: PlatformTimerHeader,
+    _reserved: u8,
+    pub refresh_frame_address: u64,
+    pub control_frame_address: u64,
+    pub watchdog_timer_gsiv: u32,
+    pub watchdog_timer_flags: u32,
+}
+
+impl SbsaWatchdog {
+    pub fn is_edge_triggered(&self) -> bool {
+        { self.watchdog_timer_flags }.get_bit(0)
+    }
+
+    pub fn is_active_low(&self) -> bool {
+        { self.watchdog_timer_flags }.get_bit(1)
+    }
+
+    pub fn is_secure(&self) -> bool {
+        { self.watchdog_timer_flags }.get_bit(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{blank_header, body_of, bytes_of, table, TableBuffer},
+        AcpiError,
+    };
+    use std::vec::Vec;
+
+    fn gtdt(platform_timer_count: u32, platform_timer_offset: usize) -> Gtdt {
+        Gtdt {
+            header: blank_header(),
+            cnt_control_base_address: u64::MAX,
+            _reserved: 0,
+            secure_el1_timer_gsiv: 29,
+            secure_el1_timer_flags: TimerFlags(0b100),
+            non_secure_el1_timer_gsiv: 30,
+            non_secure_el1_timer_flags: TimerFlags(0b100),
+            virtual_el1_timer_gsiv: 27,
+            virtual_el1_timer_flags: TimerFlags(0b110),
+            el2_timer_gsiv: 26,
+            el2_timer_flags: TimerFlags(0b100),
+            cnt_read_base_address: u64::MAX,
+            platform_timer_count,
+            platform_timer_offset: platform_timer_offset as u32,
+            virtual_el2_timer_gsiv: ExtendedField::new(28),
+            virtual_el2_timer_flags: ExtendedField::new(TimerFlags(0b001)),
+        }
+    }
+
+    #[test]
+    fn test_platform_timers() {
+        let mut body = body_of(&gtdt(3, mem::size_of::<Gtdt>()));
+        body.extend(bytes_of(&GtBlock {
+            header: PlatformTimerHeader { timer_type: 0, length: 20 + 40 },
+            _reserved: 0,
+            cnt_ctl_base: 0x2a81_0000,
+            timer_count: 1,
+            timer_offset: 20,
+        }));
+        body.extend(bytes_of(&GtBlockTimer {
+            frame_number: 0,
+            _reserved: [0; 3],
+            cnt_base: 0x2a82_0000,
+            cnt_el0_base: u64::MAX,
+            physical_timer_gsiv: 92,
+            physical_timer_flags: TimerFlags(0),
+            virtual_timer_gsiv: 0,
+            virtual_timer_flags: TimerFlags(0),
+            common_flags: 0b10,
+        }));
+        // A watchdog that's too short to be read is skipped
+        body.extend([1, 8, 0, 0, 0, 0, 0, 0]);
+        body.extend(bytes_of(&SbsaWatchdog {
+            header: PlatformTimerHeader { timer_type: 1, length: 28 },
+            _reserved: 0,
+            refresh_frame_address: 0x2a44_0000,
+            control_frame_address: 0x2a45_0000,
+            watchdog_timer_gsiv: 93,
+            watchdog_timer_flags: 0b101,
+        }));
+
+        let buffer = TableBuffer::<Gtdt>::new(&table(b"GTDT", 3, &body));
+        let gtdt = buffer.get();
+        assert!(gtdt.validate().is_ok());
+        assert_eq!({ gtdt.virtual_el1_timer_gsiv }, 27);
+        assert!({ gtdt.virtual_el1_timer_flags }.is_active_low());
+        assert_eq!(gtdt.virtual_el2_timer_gsiv(), Some(28));
+        assert!(gtdt.virtual_el2_timer_flags().unwrap().is_edge_triggered());
+
+        let timers: Vec<_> = gtdt.platform_timers().collect();
+        assert_eq!(timers.len(), 2);
+        match timers[0] {
+            PlatformTimer::GtBlock(block) => {
+                assert_eq!({ block.cnt_ctl_base }, 0x2a81_0000);
+                let frames = block.timers();
+                assert_eq!(frames.len(), 1);
+                assert_eq!({ frames[0].physical_timer_gsiv }, 92);
+                assert!(frames[0].is_always_on() && !frames[0].is_secure());
+            }
+            ref other => panic!("unexpected timer: {:?}", other),
+        }
+        match timers[1] {
+            PlatformTimer::SbsaWatchdog(watchdog) => {
+                assert_eq!({ watchdog.watchdog_timer_gsiv }, 93);
+                assert!(watchdog.is_edge_triggered() && watchdog.is_secure() && !watchdog.is_active_low());
+            }
+            ref other => panic!("unexpected timer: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_revision_3_with_revision_2_length() {
+        // Some firmware reports revision 3 without adding the virtual EL2 timer fields, which mustn't be read
+        let mut body = body_of(&gtdt(0, Gtdt::MIN_LENGTH));
+        body.truncate(Gtdt::MIN_LENGTH - mem::size_of::<SdtHeader>());
+
+        let buffer = TableBuffer::<Gtdt>::new(&table(b"GTDT", 3, &body));
+        let gtdt = buffer.get();
+        assert!(gtdt.validate().is_ok());
+        assert_eq!(gtdt.virtual_el2_timer_gsiv(), None);
+        assert!(gtdt.virtual_el2_timer_flags().is_none());
+        assert_eq!(gtdt.platform_timers().count(), 0);
+
+        let buffer = TableBuffer::<Gtdt>::new(&table(b"GTDT", 3, &body[..body.len() - 1]));
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::GTDT))));
+    }
+}
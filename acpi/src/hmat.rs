@@ -0,0 +1,453 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, ptr};
+
+/// Represents the Heterogeneous Memory Attribute Table (HMAT). This describes the memory attributes (such as
+/// latency and bandwidth) between initiator and memory proximity domains, as well as any memory-side caches. It is
+/// used on systems with persistent memory, CXL-attached memory, or other memory that performs differently from
+/// normal system RAM.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Hmat {
+    pub header: SdtHeader,
+    _reserved: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid HMAT.
+unsafe impl AcpiTable for Hmat {
+    const SIGNATURE: Signature = Signature::HMAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Hmat {
+    pub fn entries(&self) -> HmatEntryIter<'_> {
+        HmatEntryIter {
+            pointer: unsafe { (self as *const Hmat as *const u8).add(mem::size_of::<Hmat>()) },
+            remaining_length: self.header.length - mem::size_of::<Hmat>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HmatEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum HmatEntry<'a> {
+    MemoryProximityDomainAttributes(&'a MemoryProximityDomainAttributes),
+    LatencyBandwidthInfo(&'a LatencyBandwidthInfo),
+    MemorySideCacheInfo(&'a MemorySideCacheInfo),
+}
+
+impl<'a> Iterator for HmatEntryIter<'a> {
+    type Item = HmatEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length == 0 || header.length > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length;
+
+            match header.entry_type {
+                0 if header.length as usize >= mem::size_of::<MemoryProximityDomainAttributes>() => {
+                    return Some(HmatEntry::MemoryProximityDomainAttributes(unsafe {
+                        &*(entry_pointer as *const MemoryProximityDomainAttributes)
+                    }))
+                }
+                1 if header.length as usize >= mem::size_of::<LatencyBandwidthInfo>() => {
+                    return Some(HmatEntry::LatencyBandwidthInfo(unsafe {
+                        &*(entry_pointer as *const LatencyBandwidthInfo)
+                    }))
+                }
+                2 if header.length as usize >= mem::size_of::<MemorySideCacheInfo>() => {
+                    return Some(HmatEntry::MemorySideCacheInfo(unsafe {
+                        &*(entry_pointer as *const MemorySideCacheInfo)
+                    }))
+                }
+
+                /*
+                 * Other entry types are reserved by the ACPI standard. We should skip them if they appear in a
+                 * real HMAT, along with entries that are too short to be read as their type.
+                 */
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u16,
+    _reserved: u16,
+    pub length: u32,
+}
+
+/// Describes the attributes of memory in a proximity domain, and optionally which proximity domain contains the
+/// initiator (e.g. processor) that is attached to that memory.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryProximityDomainAttributes {
+    pub header: EntryHeader,
+    pub flags: u16,
+    _reserved1: u16,
+    initiator_proximity_domain: u32,
+    pub memory_proximity_domain: u32,
+    _reserved2: u32,
+    _reserved3: u64,
+    _reserved4: u64,
+}
+
+impl MemoryProximityDomainAttributes {
+    /// The proximity domain of the initiator attached to this memory, if the firmware reported one.
+    pub fn initiator_proximity_domain(&self) -> Option<u32> {
+        if { self.flags }.get_bit(0) {
+            Some(self.initiator_proximity_domain)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryHierarchy {
+    Memory,
+    /// A memory-side cache of the given level (`1` to `3`).
+    Cache(u8),
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LatencyBandwidthDataType {
+    AccessLatency,
+    ReadLatency,
+    WriteLatency,
+    AccessBandwidth,
+    ReadBandwidth,
+    WriteBandwidth,
+    Reserved(u8),
+}
+
+impl LatencyBandwidthDataType {
+    /// Latencies are reported in picoseconds; bandwidths are reported in MB/s.
+    pub fn is_latency(&self) -> bool {
+        matches!(self, Self::AccessLatency | Self::ReadLatency | Self::WriteLatency)
+    }
+}
+
+/// Describes the latency or bandwidth between each of a set of initiator proximity domains and each of a set of
+/// target proximity domains. The structure is followed by the list of initiator domains, the list of target
+/// domains, and a matrix of `u16` entries that are multiplied by `entry_base_unit` to get the actual value.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct LatencyBandwidthInfo {
+    pub header: EntryHeader,
+    pub flags: u8,
+    data_type: u8,
+    /// The minimum transfer size, in bytes, used to measure the values. Only valid if bit 4 of `flags` is set.
+    pub min_transfer_size: u8,
+    _reserved1: u8,
+    pub number_of_initiator_proximity_domains: u32,
+    pub number_of_target_proximity_domains: u32,
+    _reserved2: u32,
+    pub entry_base_unit: u64,
+}
+
+impl LatencyBandwidthInfo {
+    pub fn memory_hierarchy(&self) -> MemoryHierarchy {
+        match self.flags.get_bits(0..4) {
+            0 => MemoryHierarchy::Memory,
+            level @ 1..=3 => MemoryHierarchy::Cache(level),
+            other => MemoryHierarchy::Reserved(other),
+        }
+    }
+
+    pub fn data_type(&self) -> LatencyBandwidthDataType {
+        match self.data_type {
+            0 => LatencyBandwidthDataType::AccessLatency,
+            1 => LatencyBandwidthDataType::ReadLatency,
+            2 => LatencyBandwidthDataType::WriteLatency,
+            3 => LatencyBandwidthDataType::AccessBandwidth,
+            4 => LatencyBandwidthDataType::ReadBandwidth,
+            5 => LatencyBandwidthDataType::WriteBandwidth,
+            other => LatencyBandwidthDataType::Reserved(other),
+        }
+    }
+
+    pub fn initiator_proximity_domains(&self) -> impl Iterator<Item = u32> + '_ {
+        let count = if self.entries_fit() { self.number_of_initiator_proximity_domains } else { 0 };
+        (0..count as usize).map(move |i| unsafe { self.read_at::<u32>(i * mem::size_of::<u32>()) })
+    }
+
+    pub fn target_proximity_domains(&self) -> impl Iterator<Item = u32> + '_ {
+        let count = if self.entries_fit() { self.number_of_target_proximity_domains } else { 0 };
+        let base = self.number_of_initiator_proximity_domains as usize * mem::size_of::<u32>();
+        (0..count as usize).map(move |i| unsafe { self.read_at::<u32>(base + i * mem::size_of::<u32>()) })
+    }
+
+    /// Get the raw matrix entry for the initiator and target at the given indices into
+    /// `initiator_proximity_domains` and `target_proximity_domains`.
+    pub fn raw_entry(&self, initiator_index: u32, target_index: u32) -> Option<u16> {
+        if !self.entries_fit()
+            || initiator_index >= self.number_of_initiator_proximity_domains
+            || target_index >= self.number_of_target_proximity_domains
+        {
+            return None;
+        }
+
+        let domains =
+            self.number_of_initiator_proximity_domains as usize + self.number_of_target_proximity_domains as usize;
+        let index =
+            initiator_index as usize * self.number_of_target_proximity_domains as usize + target_index as usize;
+        Some(unsafe { self.read_at::<u16>(domains * mem::size_of::<u32>() + index * mem::size_of::<u16>()) })
+    }
+
+    /// Get the latency (in picoseconds) or bandwidth (in MB/s) between the given initiator and target proximity
+    /// domains. Returns `None` if either domain is not described by this structure, or if the firmware did not
+    /// provide a value for this pair.
+    pub fn value(&self, initiator_proximity_domain: u32, target_proximity_domain: u32) -> Option<u64> {
+        let initiator_index =
+            self.initiator_proximity_domains().position(|domain| domain == initiator_proximity_domain)?;
+        let target_index = self.target_proximity_domains().position(|domain| domain == target_proximity_domain)?;
+
+        match self.raw_entry(initiator_index as u32, target_index as u32)? {
+            0 => None,
+            entry => Some(entry as u64 * self.entry_base_unit),
+        }
+    }
+
+    /// Checks that the domain lists and entry matrix are contained within the length of this structure.
+    fn entries_fit(&self) -> bool {
+        let initiators = self.number_of_initiator_proximity_domains as u64;
+        let targets = self.number_of_target_proximity_domains as u64;
+        let required = mem::size_of::<LatencyBandwidthInfo>() as u64
+            + (initiators + targets) * mem::size_of::<u32>() as u64
+            + initiators * targets * mem::size_of::<u16>() as u64;
+
+        required <= self.header.length as u64
+    }
+
+    /// Read a `T` from `offset` bytes after the end of the fixed part of this structure.
+    ///
+    /// ### Safety: `offset + size_of::<T>()` must be within the length of this structure.
+    unsafe fn read_at<T: Copy>(&self, offset: usize) -> T {
+        unsafe {
+            let pointer = (self as *const Self as *const u8).add(mem::size_of::<Self>() + offset);
+            ptr::read_unaligned(pointer as *const T)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheAssociativity {
+    None,
+    DirectMapped,
+    ComplexCacheIndexing,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheWritePolicy {
+    None,
+    WriteBack,
+    WriteThrough,
+    Reserved(u8),
+}
+
+/// Describes a memory-side cache in front of the memory in a proximity domain. The structure is followed by
+/// `number_of_smbios_handles` `u16` handles of SMBIOS Type 17 (Memory Device) structures that make up the cache.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemorySideCacheInfo {
+    pub header: EntryHeader,
+    pub memory_proximity_domain: u32,
+    _reserved1: u32,
+    /// The size of the memory-side cache in bytes.
+    pub memory_side_cache_size: u64,
+    pub cache_attributes: u32,
+    _reserved2: u16,
+    pub number_of_smbios_handles: u16,
+}
+
+impl MemorySideCacheInfo {
+    pub fn total_cache_levels(&self) -> u8 {
+        { self.cache_attributes }.get_bits(0..4) as u8
+    }
+
+    pub fn cache_level(&self) -> u8 {
+        { self.cache_attributes }.get_bits(4..8) as u8
+    }
+
+    pub fn associativity(&self) -> CacheAssociativity {
+        match { self.cache_attributes }.get_bits(8..12) as u8 {
+            0 => CacheAssociativity::None,
+            1 => CacheAssociativity::DirectMapped,
+            2 => CacheAssociativity::ComplexCacheIndexing,
+            other => CacheAssociativity::Reserved(other),
+        }
+    }
+
+    pub fn write_policy(&self) -> CacheWritePolicy {
+        match { self.cache_attributes }.get_bits(12..16) as u8 {
+            0 => CacheWritePolicy::None,
+            1 => CacheWritePolicy::WriteBack,
+            2 => CacheWritePolicy::WriteThrough,
+            other => CacheWritePolicy::Reserved(other),
+        }
+    }
+
+    /// The cache line size in bytes.
+    pub fn cache_line_size(&self) -> u16 {
+        { self.cache_attributes }.get_bits(16..32) as u16
+    }
+
+    pub fn smbios_handles(&self) -> impl Iterator<Item = u16> + '_ {
+        let available =
+            (self.header.length as usize).saturating_sub(mem::size_of::<Self>()) / mem::size_of::<u16>();
+        let count = usize::min(self.number_of_smbios_handles as usize, available);
+
+        (0..count).map(move |i| unsafe {
+            let pointer =
+                (self as *const Self as *const u8).add(mem::size_of::<Self>() + i * mem::size_of::<u16>());
+            ptr::read_unaligned(pointer as *const u16)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn entry_header<T>(entry_type: u16, extra_length: usize) -> EntryHeader {
+        EntryHeader { entry_type, _reserved: 0, length: (mem::size_of::<T>() + extra_length) as u32 }
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body = [0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&MemoryProximityDomainAttributes {
+            header: entry_header::<MemoryProximityDomainAttributes>(0, 0),
+            flags: 1,
+            _reserved1: 0,
+            initiator_proximity_domain: 0,
+            memory_proximity_domain: 1,
+            _reserved2: 0,
+            _reserved3: 0,
+            _reserved4: 0,
+        }));
+        // A Memory Proximity Domain Attributes structure that's too short to be read is skipped
+        body.extend(bytes_of(&EntryHeader { entry_type: 0, _reserved: 0, length: 12 }));
+        body.extend([0; 4]);
+        body.extend(bytes_of(&LatencyBandwidthInfo {
+            header: entry_header::<LatencyBandwidthInfo>(1, 3 * 4 + 2 * 2),
+            flags: 0,
+            data_type: 1,
+            min_transfer_size: 0,
+            _reserved1: 0,
+            number_of_initiator_proximity_domains: 1,
+            number_of_target_proximity_domains: 2,
+            _reserved2: 0,
+            entry_base_unit: 100,
+        }));
+        // Initiator domain 0, and target domains 0 and 1
+        for domain in [0u32, 0, 1] {
+            body.extend(domain.to_le_bytes());
+        }
+        body.extend([5, 0, 0, 0]);
+        // The cache claims to have two SMBIOS handles, but there's only room for one
+        body.extend(bytes_of(&MemorySideCacheInfo {
+            header: entry_header::<MemorySideCacheInfo>(2, 2),
+            memory_proximity_domain: 1,
+            _reserved1: 0,
+            memory_side_cache_size: 0x4000_0000,
+            cache_attributes: 64 << 16 | 1 << 12 | 1 << 8 | 1 << 4 | 1,
+            _reserved2: 0,
+            number_of_smbios_handles: 2,
+        }));
+        body.extend(0x1234u16.to_le_bytes());
+
+        let buffer = TableBuffer::<Hmat>::new(&table(b"HMAT", 2, &body));
+        let hmat = buffer.get();
+        assert!(hmat.validate().is_ok());
+        let entries: Vec<_> = hmat.entries().collect();
+        assert_eq!(entries.len(), 3);
+
+        match entries[0] {
+            HmatEntry::MemoryProximityDomainAttributes(attributes) => {
+                assert_eq!(attributes.initiator_proximity_domain(), Some(0));
+                assert_eq!({ attributes.memory_proximity_domain }, 1);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            HmatEntry::LatencyBandwidthInfo(info) => {
+                assert_eq!(info.memory_hierarchy(), MemoryHierarchy::Memory);
+                assert_eq!(info.data_type(), LatencyBandwidthDataType::ReadLatency);
+                assert!(info.data_type().is_latency());
+                assert_eq!(info.target_proximity_domains().collect::<Vec<_>>(), [0, 1]);
+                assert_eq!(info.value(0, 0), Some(500));
+                assert_eq!(info.value(0, 1), None);
+                assert_eq!(info.value(1, 0), None);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[2] {
+            HmatEntry::MemorySideCacheInfo(cache) => {
+                assert_eq!(cache.cache_level(), 1);
+                assert_eq!(cache.associativity(), CacheAssociativity::DirectMapped);
+                assert_eq!(cache.write_policy(), CacheWritePolicy::WriteBack);
+                assert_eq!(cache.cache_line_size(), 64);
+                assert_eq!(cache.smbios_handles().collect::<Vec<_>>(), [0x1234]);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_matrix() {
+        // The matrix of a latency and bandwidth structure doesn't fit in it, so it doesn't return any values
+        let mut body = [0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&LatencyBandwidthInfo {
+            header: entry_header::<LatencyBandwidthInfo>(1, 2 * 4),
+            flags: 0,
+            data_type: 3,
+            min_transfer_size: 0,
+            _reserved1: 0,
+            number_of_initiator_proximity_domains: 1,
+            number_of_target_proximity_domains: 1,
+            _reserved2: 0,
+            entry_base_unit: 1,
+        }));
+        body.extend([0; 8]);
+
+        let buffer = TableBuffer::<Hmat>::new(&table(b"HMAT", 2, &body));
+        match buffer.get().entries().next() {
+            Some(HmatEntry::LatencyBandwidthInfo(info)) => {
+                assert_eq!(info.initiator_proximity_domains().count(), 0);
+                assert_eq!(info.raw_entry(0, 0), None);
+            }
+            other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+}
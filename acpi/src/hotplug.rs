@@ -0,0 +1,570 @@
+//! Hotplug of devices described in the namespace - processors, memory devices, and PCI slots. The firmware tells
+//! the OS about hotplug events by `Notify`ing a device with a bus check, device check, or eject request, and
+//! [`Hotplug::handle_notify`] should be called from the OS's notify handler to respond to them. It re-evaluates
+//! the `_STA` of the affected devices, ejects devices with `_EJ0`, reports the outcome to the firmware with
+//! `_OST`, and calls the OS's [`HotplugHandler`] to bring up or tear down the devices themselves.
+
+use crate::{device, AcpiError, AcpiResult};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use aml::{
+    device::id_matches,
+    value::{Args, StatusObject},
+    AmlContext,
+    AmlError,
+    AmlName,
+    AmlValue,
+    LevelType,
+    NamespaceItem,
+};
+
+/// `Notify(device, 0x00)`: the bus the device is on should be re-enumerated, as devices may have been added to or
+/// removed from it.
+const NOTIFY_BUS_CHECK: u8 = 0x00;
+/// `Notify(device, 0x01)`: the device may have been inserted or removed.
+const NOTIFY_DEVICE_CHECK: u8 = 0x01;
+/// `Notify(device, 0x03)`: the user has asked for the device to be ejected.
+const NOTIFY_EJECT_REQUEST: u8 = 0x03;
+/// The `_OST` source event for an ejection initiated by the OS, rather than requested with a notification.
+const OST_EJECTION_PROCESSING: u32 = 0x103;
+
+#[derive(Debug)]
+pub enum HotplugError {
+    /// The device that was asked to be ejected doesn't have an `_EJ0` object.
+    NotEjectable(AmlName),
+    /// The device was still present after its `_EJ0` was evaluated.
+    EjectFailed(AmlName),
+}
+
+/// The kind of a hotpluggable device, which determines how the OS brings it up or tears it down.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HotplugKind {
+    /// A `Processor` object, or a processor device (with a `_HID` of `ACPI0007`).
+    Processor,
+    /// A memory device (with a `_HID` of `PNP0C80`), whose memory ranges are described by its `_CRS`.
+    Memory,
+    /// A device with an `_ADR` beneath a PCI host bridge, which is a PCI slot or a device in one.
+    PciSlot,
+    Other,
+}
+
+/// The status reported to the firmware with `_OST`, after the OS has processed a hotplug event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OstStatus {
+    Success,
+    Failure,
+    /// The OS doesn't recognise the notification.
+    Unrecognized,
+    /// A status code specific to the kind of notification, in the range `0x80..=0xff`. The codes defined by the
+    /// spec are provided as associated constants.
+    Specific(u8),
+}
+
+impl OstStatus {
+    /// The OS doesn't support ejecting the device.
+    pub const EJECT_NOT_SUPPORTED: OstStatus = OstStatus::Specific(0x80);
+    /// The device can't be ejected, because it's in use by an application.
+    pub const EJECT_DEVICE_IN_USE: OstStatus = OstStatus::Specific(0x81);
+    /// The device can't be ejected, because it's busy.
+    pub const EJECT_DEVICE_BUSY: OstStatus = OstStatus::Specific(0x82);
+    /// The device can't be ejected, because a device that depends on it is busy.
+    pub const EJECT_DEPENDENCY_BUSY: OstStatus = OstStatus::Specific(0x83);
+    /// The device is being ejected, and the OS will report the outcome later.
+    pub const EJECT_IN_PROGRESS: OstStatus = OstStatus::Specific(0x84);
+    /// The device is being brought up, and the OS will report the outcome later.
+    pub const INSERTION_IN_PROGRESS: OstStatus = OstStatus::Specific(0x80);
+    /// The driver of the inserted device failed to load.
+    pub const INSERTION_DRIVER_LOAD_FAILURE: OstStatus = OstStatus::Specific(0x81);
+    /// The OS doesn't support inserting the device.
+    pub const INSERTION_NOT_SUPPORTED: OstStatus = OstStatus::Specific(0x82);
+
+    fn code(&self) -> u64 {
+        match self {
+            OstStatus::Success => 0x00,
+            OstStatus::Failure => 0x01,
+            OstStatus::Unrecognized => 0x02,
+            OstStatus::Specific(code) => *code as u64,
+        }
+    }
+}
+
+/// Implemented by the OS to bring up and tear down hotplugged devices. The methods are called while
+/// [`Hotplug::handle_notify`] is handling the event, and are passed the context so they can evaluate objects of
+/// the device (e.g. the `_CRS` of a memory device, or the `_MAT` of a processor).
+pub trait HotplugHandler {
+    /// Called when a device has become present. The OS should bring it up (e.g. start the processor, add the
+    /// memory device's ranges to the allocator, or configure the device in the PCI slot), and return the status
+    /// to report to the firmware.
+    fn device_arrived(&mut self, context: &mut AmlContext, device: &AmlName, kind: HotplugKind) -> OstStatus;
+
+    /// Called when the user has asked for a device to be ejected. The OS should quiesce the device, so that
+    /// `_EJ0` can remove it, and return `OstStatus::Success`, or a status explaining why it can't be ejected.
+    fn prepare_eject(&mut self, context: &mut AmlContext, device: &AmlName, kind: HotplugKind) -> OstStatus;
+
+    /// Called when a device is no longer present, either because it has been ejected, or because it was removed
+    /// without being ejected first (a "surprise removal").
+    fn device_removed(&mut self, context: &mut AmlContext, device: &AmlName, kind: HotplugKind);
+
+    /// Called after a bus check on `device`, once the devices in the namespace below it have been checked, so
+    /// that the OS can re-enumerate the bus it provides (e.g. scan the secondary bus of a PCI bridge for devices
+    /// that aren't described in the namespace).
+    fn rescan_bus(&mut self, _context: &mut AmlContext, _device: &AmlName) {}
+}
+
+/// Tracks which devices are present, and handles the notifications that report hotplug events.
+#[derive(Debug)]
+pub struct Hotplug<H>
+where
+    H: HotplugHandler,
+{
+    handler: H,
+    present: BTreeMap<AmlName, bool>,
+}
+
+impl<H> Hotplug<H>
+where
+    H: HotplugHandler,
+{
+    /// Record which of the devices in the namespace are currently present, so that later hotplug events only
+    /// report the devices whose presence has changed. This doesn't call the handler for the devices that are
+    /// already present, which the OS should have found through its normal enumeration.
+    pub fn new(handler: H, context: &mut AmlContext) -> AcpiResult<Hotplug<H>> {
+        let mut hotplug = Hotplug { handler, present: BTreeMap::new() };
+        for device in devices_under(context, &AmlName::root())? {
+            let present = is_present(context, &device)?;
+            hotplug.present.insert(device, present);
+        }
+        Ok(hotplug)
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Whether the device was present when it was last checked.
+    pub fn is_present(&self, device: &AmlName) -> bool {
+        self.present.get(device).copied().unwrap_or(false)
+    }
+
+    /// Handle a `Notify` on `device`. Returns `false` if the notification isn't a hotplug event (bus check,
+    /// device check, or eject request), in which case it should be handled elsewhere.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, device: &AmlName, value: u8) -> AcpiResult<bool> {
+        match value {
+            NOTIFY_BUS_CHECK => {
+                let mut devices = vec![device.clone()];
+                devices.extend(devices_under(context, device)?);
+                let status = self.check_devices(context, &devices)?;
+                self.handler.rescan_bus(context, device);
+                report_status(context, device, value as u32, status)?;
+            }
+
+            NOTIFY_DEVICE_CHECK => {
+                /*
+                 * The devices beneath a device that has just arrived may have become present along with it, but
+                 * they don't need checking if it was already present.
+                 */
+                let mut devices = vec![device.clone()];
+                if !self.is_present(device) {
+                    devices.extend(devices_under(context, device)?);
+                }
+                let status = self.check_devices(context, &devices)?;
+                report_status(context, device, value as u32, status)?;
+            }
+
+            NOTIFY_EJECT_REQUEST => self.eject_device(context, device, NOTIFY_EJECT_REQUEST as u32)?,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Eject a device on the OS's behalf (e.g. when the user asks for it to be removed through the OS, rather than
+    /// through the firmware). The handler is asked to prepare the device first, and the outcome is reported to the
+    /// firmware with `_OST`.
+    pub fn eject(&mut self, context: &mut AmlContext, device: &AmlName) -> AcpiResult<()> {
+        self.eject_device(context, device, OST_EJECTION_PROCESSING)
+    }
+
+    /// Eject a device, reporting the outcome to the firmware as the result of the `_OST` source `event`.
+    fn eject_device(&mut self, context: &mut AmlContext, device: &AmlName, event: u32) -> AcpiResult<()> {
+        let ej0 = AmlName::from_str("_EJ0").unwrap().resolve(device).map_err(AcpiError::Aml)?;
+        match context.namespace.get_by_path(&ej0) {
+            Ok(_) => (),
+            Err(AmlError::ValueDoesNotExist(_)) | Err(AmlError::LevelDoesNotExist(_)) => {
+                report_status(context, device, event, OstStatus::EJECT_NOT_SUPPORTED)?;
+                return Err(AcpiError::Hotplug(HotplugError::NotEjectable(device.clone())));
+            }
+            Err(err) => return Err(AcpiError::Aml(err)),
+        }
+
+        let kind = kind_of(context, device)?;
+        let status = self.handler.prepare_eject(context, device, kind);
+        if status != OstStatus::Success {
+            return report_status(context, device, event, status);
+        }
+
+        // `_EJ0` takes `1` to eject the device, rather than `0` to cancel an ejection in progress
+        let args = Args::from_list(vec![AmlValue::Integer(1)]).unwrap();
+        if let Err(err) = device::evaluate(context, device, "_EJ0", args) {
+            report_status(context, device, event, OstStatus::Failure)?;
+            return Err(err);
+        }
+
+        if is_present(context, device)? {
+            report_status(context, device, event, OstStatus::Failure)?;
+            return Err(AcpiError::Hotplug(HotplugError::EjectFailed(device.clone())));
+        }
+
+        // The devices beneath the ejected device are removed along with it, so they're torn down first
+        let mut devices = devices_under(context, device)?;
+        devices.push(device.clone());
+        for removed in devices {
+            if self.present.insert(removed.clone(), false) == Some(true) {
+                let kind = kind_of(context, &removed)?;
+                self.handler.device_removed(context, &removed, kind);
+            }
+        }
+
+        report_status(context, device, event, OstStatus::Success)
+    }
+
+    /// Re-evaluate the `_STA` of each of `devices`, and call the handler for those that have arrived or been
+    /// removed. Returns the status to report for the event, which is the first failure reported by the handler.
+    fn check_devices(&mut self, context: &mut AmlContext, devices: &[AmlName]) -> AcpiResult<OstStatus> {
+        let mut status = OstStatus::Success;
+
+        for device in devices {
+            let present = is_present(context, device)?;
+            let was_present = self.present.insert(device.clone(), present).unwrap_or(false);
+
+            match (was_present, present) {
+                (false, true) => {
+                    let kind = kind_of(context, device)?;
+                    let arrival_status = self.handler.device_arrived(context, device, kind);
+                    if status == OstStatus::Success {
+                        status = arrival_status;
+                    }
+                }
+                (true, false) => {
+                    let kind = kind_of(context, device)?;
+                    self.handler.device_removed(context, device, kind);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(status)
+    }
+}
+
+/// Report the outcome of processing a notification to the firmware, by evaluating the device's `_OST` (if it has
+/// one). `event` is the notification value, or one of the source events for processing initiated by the OS.
+fn report_status(context: &mut AmlContext, device: &AmlName, event: u32, status: OstStatus) -> AcpiResult<()> {
+    let args = Args::from_list(vec![
+        AmlValue::Integer(event as u64),
+        AmlValue::Integer(status.code()),
+        AmlValue::buffer(Vec::new()),
+    ])
+    .unwrap();
+    device::evaluate_optional(context, device, "_OST", args)?;
+    Ok(())
+}
+
+/// Whether the device is present, according to its `_STA`. Devices without a `_STA` are always present.
+fn is_present(context: &mut AmlContext, device: &AmlName) -> AcpiResult<bool> {
+    let status = match device::evaluate_optional(context, device, "_STA", Args::EMPTY)? {
+        Some(status) => status.as_status().map_err(AcpiError::Aml)?,
+        None => StatusObject::default(),
+    };
+    Ok(status.present)
+}
+
+/// Find the devices and processors in the namespace below `scope`.
+fn devices_under(context: &AmlContext, scope: &AmlName) -> AcpiResult<Vec<AmlName>> {
+    Ok(context
+        .namespace
+        .iter_under(scope)
+        .map_err(AcpiError::Aml)?
+        .filter_map(|(path, item)| match item {
+            NamespaceItem::Level(LevelType::Device) | NamespaceItem::Level(LevelType::Processor) => Some(path),
+            _ => None,
+        })
+        .collect())
+}
+
+fn kind_of(context: &mut AmlContext, device: &AmlName) -> AcpiResult<HotplugKind> {
+    if let Ok(AmlValue::Processor { .. }) = context.namespace.get_by_path(device) {
+        return Ok(HotplugKind::Processor);
+    }
+
+    match device::evaluate_optional(context, device, "_HID", Args::EMPTY)? {
+        Some(hid) if id_matches(&hid, "ACPI0007") => return Ok(HotplugKind::Processor),
+        Some(hid) if id_matches(&hid, "PNP0C80") => return Ok(HotplugKind::Memory),
+        _ => (),
+    }
+
+    /*
+     * A device with an `_ADR` is a PCI slot if it's anywhere beneath a PCI host bridge, as there can be PCI-to-PCI
+     * bridges in between.
+     */
+    if device::evaluate_optional(context, device, "_ADR", Args::EMPTY)?.is_some() {
+        let mut ancestor = device.parent();
+        while let Ok(bridge) = ancestor {
+            let hid = device::evaluate_optional(context, &bridge, "_HID", Args::EMPTY)?;
+            let cid = device::evaluate_optional(context, &bridge, "_CID", Args::EMPTY)?;
+            let is_host_bridge = |id: &str| {
+                hid.as_ref().is_some_and(|hid| id_matches(hid, id))
+                    || match &cid {
+                        Some(AmlValue::Package(cids)) => cids.iter().any(|cid| id_matches(cid, id)),
+                        Some(cid) => id_matches(cid, id),
+                        None => false,
+                    }
+            };
+            if is_host_bridge("PNP0A03") || is_host_bridge("PNP0A08") {
+                return Ok(HotplugKind::PciSlot);
+            }
+            ancestor = bridge.parent();
+        }
+    }
+
+    Ok(HotplugKind::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{boxed::Box, sync::Arc};
+    use aml::{build::*, DebugVerbosity};
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    /// The devices in these tests are only described by methods, so the AML never accesses the hardware.
+    struct NullHandler;
+
+    impl aml::Handler for NullHandler {
+        fn read_u8(&self, _address: usize) -> u8 {
+            unimplemented!()
+        }
+        fn read_u16(&self, _address: usize) -> u16 {
+            unimplemented!()
+        }
+        fn read_u32(&self, _address: usize) -> u32 {
+            unimplemented!()
+        }
+        fn read_u64(&self, _address: usize) -> u64 {
+            unimplemented!()
+        }
+
+        fn write_u8(&self, _address: usize, _value: u8) {
+            unimplemented!()
+        }
+        fn write_u16(&self, _address: usize, _value: u16) {
+            unimplemented!()
+        }
+        fn write_u32(&self, _address: usize, _value: u32) {
+            unimplemented!()
+        }
+        fn write_u64(&self, _address: usize, _value: u64) {
+            unimplemented!()
+        }
+
+        fn read_io_u8(&self, _port: u16) -> u8 {
+            unimplemented!()
+        }
+        fn read_io_u16(&self, _port: u16) -> u16 {
+            unimplemented!()
+        }
+        fn read_io_u32(&self, _port: u16) -> u32 {
+            unimplemented!()
+        }
+
+        fn write_io_u8(&self, _port: u16, _value: u8) {
+            unimplemented!()
+        }
+        fn write_io_u16(&self, _port: u16, _value: u16) {
+            unimplemented!()
+        }
+        fn write_io_u32(&self, _port: u16, _value: u32) {
+            unimplemented!()
+        }
+
+        fn read_pci_u8(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u8 {
+            unimplemented!()
+        }
+        fn read_pci_u16(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u16 {
+            unimplemented!()
+        }
+        fn read_pci_u32(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u32 {
+            unimplemented!()
+        }
+        fn write_pci_u8(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u8) {
+            unimplemented!()
+        }
+        fn write_pci_u16(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u16) {
+            unimplemented!()
+        }
+        fn write_pci_u32(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u32) {
+            unimplemented!()
+        }
+
+        fn nanos_since_boot(&self) -> u64 {
+            0
+        }
+        fn stall(&self, _microseconds: u64) {}
+        fn sleep(&self, _milliseconds: u64) {}
+    }
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        arrived: Vec<AmlName>,
+        removed: Vec<AmlName>,
+    }
+
+    impl HotplugHandler for RecordingHandler {
+        fn device_arrived(
+            &mut self,
+            _context: &mut AmlContext,
+            device: &AmlName,
+            _kind: HotplugKind,
+        ) -> OstStatus {
+            self.arrived.push(device.clone());
+            OstStatus::Success
+        }
+
+        fn prepare_eject(
+            &mut self,
+            _context: &mut AmlContext,
+            _device: &AmlName,
+            _kind: HotplugKind,
+        ) -> OstStatus {
+            OstStatus::Success
+        }
+
+        fn device_removed(&mut self, _context: &mut AmlContext, device: &AmlName, _kind: HotplugKind) {
+            self.removed.push(device.clone());
+        }
+    }
+
+    fn name(path: &str) -> AmlName {
+        AmlName::from_str(path).unwrap()
+    }
+
+    /// Make a context with a slot, `\_SB.SLT0`, whose `_STA` reports whether it's `present`. Its `_OST` stores
+    /// the event and status it's passed in `\OSTE` and `\OSTS`.
+    fn make_context(present: &Arc<AtomicBool>) -> AmlContext {
+        let mut context = AmlContext::new(Box::new(NullHandler), DebugVerbosity::None);
+        let table = DefinitionBlock::new("SSDT", "RUST", "TEST", 1)
+            .with(Name::new("OSTE", 0xffu8))
+            .with(Name::new("OSTS", 0xffu8))
+            .with(
+                Scope::new("\\_SB").with(
+                    Device::new("SLT0").with(
+                        Method::new("_OST", 3)
+                            .with(Store::new(Arg(0), Path::new("\\OSTE")))
+                            .with(Store::new(Arg(1), Path::new("\\OSTS"))),
+                    ),
+                ),
+            )
+            .to_bytes()
+            .unwrap();
+        context.parse_table(&table[36..]).unwrap();
+
+        let sta_present = present.clone();
+        context
+            .namespace
+            .add_value(
+                name("\\_SB.SLT0._STA"),
+                AmlValue::native_method(0, false, 0, move |_| {
+                    Ok(AmlValue::Integer(if sta_present.load(Ordering::Relaxed) { 0x0f } else { 0x00 }))
+                }),
+            )
+            .unwrap();
+        context
+    }
+
+    /// The event and status that were last reported with `_OST`.
+    fn last_ost(context: &AmlContext) -> (u64, u64) {
+        let read = |path| context.namespace.get_by_path(&name(path)).unwrap().as_integer(context).unwrap();
+        (read("\\OSTE"), read("\\OSTS"))
+    }
+
+    #[test]
+    fn test_device_check() {
+        let present = Arc::new(AtomicBool::new(false));
+        let mut context = make_context(&present);
+        let mut hotplug = Hotplug::new(RecordingHandler::default(), &mut context).unwrap();
+        let slot = name("\\_SB.SLT0");
+        assert!(!hotplug.is_present(&slot));
+
+        present.store(true, Ordering::Relaxed);
+        assert!(hotplug.handle_notify(&mut context, &slot, NOTIFY_DEVICE_CHECK).unwrap());
+        assert!(hotplug.is_present(&slot));
+        assert_eq!(hotplug.handler().arrived, core::slice::from_ref(&slot));
+        assert_eq!(last_ost(&context), (NOTIFY_DEVICE_CHECK as u64, 0x00));
+
+        // Checking the device again doesn't report it arriving again
+        assert!(hotplug.handle_notify(&mut context, &slot, NOTIFY_DEVICE_CHECK).unwrap());
+        assert_eq!(hotplug.handler().arrived, [slot]);
+    }
+
+    #[test]
+    fn test_surprise_removal() {
+        let present = Arc::new(AtomicBool::new(true));
+        let mut context = make_context(&present);
+        let mut hotplug = Hotplug::new(RecordingHandler::default(), &mut context).unwrap();
+        let slot = name("\\_SB.SLT0");
+
+        present.store(false, Ordering::Relaxed);
+        assert!(hotplug.handle_notify(&mut context, &slot, NOTIFY_DEVICE_CHECK).unwrap());
+        assert!(!hotplug.is_present(&slot));
+        assert!(hotplug.handler().arrived.is_empty());
+        assert_eq!(hotplug.handler().removed, [slot]);
+        assert_eq!(last_ost(&context), (NOTIFY_DEVICE_CHECK as u64, 0x00));
+    }
+
+    #[test]
+    fn test_eject() {
+        let present = Arc::new(AtomicBool::new(true));
+        let mut context = make_context(&present);
+        let ej0_present = present.clone();
+        context
+            .namespace
+            .add_value(
+                name("\\_SB.SLT0._EJ0"),
+                AmlValue::native_method(1, false, 0, move |_| {
+                    ej0_present.store(false, Ordering::Relaxed);
+                    Ok(AmlValue::Integer(0))
+                }),
+            )
+            .unwrap();
+        let mut hotplug = Hotplug::new(RecordingHandler::default(), &mut context).unwrap();
+        let slot = name("\\_SB.SLT0");
+
+        hotplug.eject(&mut context, &slot).unwrap();
+        assert!(!present.load(Ordering::Relaxed));
+        assert_eq!(hotplug.handler().removed, [slot]);
+        assert_eq!(last_ost(&context), (OST_EJECTION_PROCESSING as u64, 0x00));
+    }
+
+    #[test]
+    fn test_eject_failure() {
+        let present = Arc::new(AtomicBool::new(true));
+        let mut context = make_context(&present);
+        context
+            .namespace
+            .add_value(
+                name("\\_SB.SLT0._EJ0"),
+                AmlValue::native_method(1, false, 0, |_| Err(AmlError::FieldInvalidAddress)),
+            )
+            .unwrap();
+        let mut hotplug = Hotplug::new(RecordingHandler::default(), &mut context).unwrap();
+        let slot = name("\\_SB.SLT0");
+
+        assert!(hotplug.handle_notify(&mut context, &slot, NOTIFY_EJECT_REQUEST).is_err());
+        assert!(hotplug.is_present(&slot));
+        assert!(hotplug.handler().removed.is_empty());
+        assert_eq!(last_ost(&context), (NOTIFY_EJECT_REQUEST as u64, 0x01));
+    }
+}
@@ -96,4 +96,11 @@ unsafe impl AcpiTable for HpetTable {
     fn header(&self) -> &SdtHeader {
         &self.header
     }
+
+    fn find_content_problems(&self, report: &mut dyn FnMut(AcpiError)) {
+        // Bit 14 of the event timer block ID is reserved
+        if { self.event_timer_block_id }.get_bit(14) {
+            report(AcpiError::SdtReservedFieldSet(Self::SIGNATURE));
+        }
+    }
 }
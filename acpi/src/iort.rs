@@ -0,0 +1,406 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, ptr, slice, str};
+
+/// Represents the I/O Remapping Table (IORT), which describes the I/O topology of ARM systems: how PCI root
+/// complexes and other devices are connected to SMMUs and GIC Interrupt Translation Services (ITSs), and how
+/// their requester IDs are mapped as they pass through each of these. You can iterate over the nodes in the table
+/// with [`Iort::nodes`], and follow the ID mappings between them with [`Iort::map_id`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Iort {
+    pub header: SdtHeader,
+    pub number_of_nodes: u32,
+    /// The offset of the first node from the start of the table.
+    pub node_array_offset: u32,
+    _reserved: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid IORT.
+unsafe impl AcpiTable for Iort {
+    const SIGNATURE: Signature = Signature::IORT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Iort {
+    pub fn nodes(&self) -> IortNodeIter<'_> {
+        IortNodeIter { iort: self, offset: self.node_array_offset, remaining_nodes: self.number_of_nodes }
+    }
+
+    /// Get the node at `offset` bytes from the start of the table. This is how nodes reference each other, for
+    /// example in the `output_reference` field of an [`IdMapping`].
+    pub fn node_at_offset(&self, offset: u32) -> Option<IortNode<'_>> {
+        let table_length = self.header.length as usize;
+        let offset = offset as usize;
+        if offset < mem::size_of::<Iort>() || offset + mem::size_of::<NodeHeader>() > table_length {
+            return None;
+        }
+
+        let pointer = unsafe { (self as *const Iort as *const u8).add(offset) };
+        let header = unsafe { &*(pointer as *const NodeHeader) };
+        if (header.length as usize) < mem::size_of::<NodeHeader>()
+            || offset + header.length as usize > table_length
+        {
+            return None;
+        }
+
+        /*
+         * Nodes that are too short to be read as their type are returned as `Other`, so the rest of the nodes can
+         * still be found.
+         */
+        let fits = |size| header.length as usize >= size;
+        Some(match header.node_type {
+            0 if fits(mem::size_of::<ItsGroupNode>()) => {
+                IortNode::ItsGroup(unsafe { &*(pointer as *const ItsGroupNode) })
+            }
+            1 if fits(mem::size_of::<NamedComponentNode>()) => {
+                IortNode::NamedComponent(unsafe { &*(pointer as *const NamedComponentNode) })
+            }
+            2 if fits(mem::size_of::<RootComplexNode>()) => {
+                IortNode::RootComplex(unsafe { &*(pointer as *const RootComplexNode) })
+            }
+            4 if fits(mem::size_of::<SmmuV3Node>()) => {
+                IortNode::SmmuV3(unsafe { &*(pointer as *const SmmuV3Node) })
+            }
+            _ => IortNode::Other(header),
+        })
+    }
+
+    /// Map `id` through the ID mappings of `node`, returning the node the ID is output to and the translated ID.
+    /// Returns `None` if none of the node's mappings cover `id`. For example, this can be used to map a PCI
+    /// requester ID through a root complex node to the stream ID of an SMMU, and then through the SMMU node to the
+    /// device ID of an ITS.
+    pub fn map_id<'a>(&'a self, node: &IortNode<'a>, id: u32) -> Option<(IortNode<'a>, u32)> {
+        node.header().id_mappings().iter().find_map(|mapping| {
+            let output_id = mapping.map(id)?;
+            let output_node = self.node_at_offset(mapping.output_reference)?;
+            Some((output_node, output_id))
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct IortNodeIter<'a> {
+    iort: &'a Iort,
+    offset: u32,
+    remaining_nodes: u32,
+}
+
+impl<'a> Iterator for IortNodeIter<'a> {
+    type Item = IortNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_nodes == 0 {
+            return None;
+        }
+
+        let node = self.iort.node_at_offset(self.offset)?;
+        self.offset += node.header().length as u32;
+        self.remaining_nodes -= 1;
+
+        Some(node)
+    }
+}
+
+#[derive(Debug)]
+pub enum IortNode<'a> {
+    ItsGroup(&'a ItsGroupNode),
+    NamedComponent(&'a NamedComponentNode),
+    RootComplex(&'a RootComplexNode),
+    SmmuV3(&'a SmmuV3Node),
+    /// A node of a type that we don't parse yet (SMMUv1/v2, PMCG, and RMR nodes). Its ID mappings can still be
+    /// accessed through the header.
+    Other(&'a NodeHeader),
+}
+
+impl<'a> IortNode<'a> {
+    pub fn header(&self) -> &'a NodeHeader {
+        match self {
+            IortNode::ItsGroup(node) => &node.header,
+            IortNode::NamedComponent(node) => &node.header,
+            IortNode::RootComplex(node) => &node.header,
+            IortNode::SmmuV3(node) => &node.header,
+            IortNode::Other(header) => header,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeHeader {
+    pub node_type: u8,
+    pub length: u16,
+    pub revision: u8,
+    /// A unique identifier for the node. This was reserved (and so will be `0`) in older revisions of the IORT.
+    pub identifier: u32,
+    pub number_of_id_mappings: u32,
+    /// The offset of the ID mapping array from the start of this node.
+    pub id_array_offset: u32,
+}
+
+impl NodeHeader {
+    pub fn id_mappings(&self) -> &[IdMapping] {
+        let array_offset = self.id_array_offset as usize;
+        let available = (self.length as usize).saturating_sub(array_offset) / mem::size_of::<IdMapping>();
+        let count = usize::min(self.number_of_id_mappings as usize, available);
+        if count == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let pointer = (self as *const NodeHeader as *const u8).add(array_offset);
+            slice::from_raw_parts(pointer as *const IdMapping, count)
+        }
+    }
+
+    /// Read a `T` from `offset` bytes after the start of this node.
+    ///
+    /// ### Safety: `offset + size_of::<T>()` must be within the length of this node.
+    unsafe fn read_at<T: Copy>(&self, offset: usize) -> T {
+        unsafe { ptr::read_unaligned((self as *const NodeHeader as *const u8).add(offset) as *const T) }
+    }
+}
+
+/// Describes a range of input IDs (e.g. PCI requester IDs) that are mapped to a range of output IDs (e.g. SMMU
+/// stream IDs) on another node.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IdMapping {
+    pub input_base: u32,
+    /// The number of IDs in the range, minus one.
+    pub number_of_ids: u32,
+    pub output_base: u32,
+    /// The offset, from the start of the IORT, of the node that IDs are output to.
+    pub output_reference: u32,
+    pub flags: u32,
+}
+
+impl IdMapping {
+    /// If this is set, the mapping maps a single device that doesn't generate an input ID (e.g. the SMMU's own
+    /// MSIs) to `output_base`, and the input range is ignored.
+    pub fn is_single_mapping(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    /// Map an input ID through this mapping. Returns `None` if `id` isn't covered by this mapping.
+    pub fn map(&self, id: u32) -> Option<u32> {
+        if self.is_single_mapping() {
+            return None;
+        }
+
+        let offset = id.checked_sub(self.input_base)?;
+        if offset > self.number_of_ids {
+            return None;
+        }
+
+        self.output_base.checked_add(offset)
+    }
+}
+
+/// Describes a group of GIC ITSs, by their IDs in the MADT. This is the final destination of ID mappings for
+/// devices that generate MSIs.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ItsGroupNode {
+    pub header: NodeHeader,
+    pub number_of_its: u32,
+}
+
+impl ItsGroupNode {
+    pub fn its_identifiers(&self) -> impl Iterator<Item = u32> + '_ {
+        let available =
+            (self.header.length as usize).saturating_sub(mem::size_of::<ItsGroupNode>()) / mem::size_of::<u32>();
+        let count = usize::min(self.number_of_its as usize, available);
+
+        (0..count).map(move |i| unsafe {
+            self.header.read_at::<u32>(mem::size_of::<ItsGroupNode>() + i * mem::size_of::<u32>())
+        })
+    }
+}
+
+/// Describes a device that is described in the ACPI namespace, rather than being discovered through PCI.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NamedComponentNode {
+    pub header: NodeHeader,
+    pub node_flags: u32,
+    pub memory_access_properties: u64,
+    /// The number of bits of physical address the device is able to access.
+    pub device_memory_address_size_limit: u8,
+    // Followed by a null-terminated ASCII string containing the full namespace path of the device
+}
+
+impl NamedComponentNode {
+    /// The fully-qualified path of the device in the ACPI namespace. Returns `None` if the name isn't valid ASCII.
+    pub fn device_object_name(&self) -> Option<&str> {
+        let start = mem::size_of::<NamedComponentNode>();
+        let end = if self.header.number_of_id_mappings > 0 && self.header.id_array_offset as usize > start {
+            self.header.id_array_offset as usize
+        } else {
+            self.header.length as usize
+        };
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                (self as *const NamedComponentNode as *const u8).add(start),
+                end.saturating_sub(start),
+            )
+        };
+        let bytes = match bytes.iter().position(|&byte| byte == 0) {
+            Some(nul) => &bytes[..nul],
+            None => bytes,
+        };
+
+        str::from_utf8(bytes).ok()
+    }
+}
+
+/// Describes a PCI root complex. The input IDs of its mappings are PCI requester IDs.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RootComplexNode {
+    pub header: NodeHeader,
+    pub memory_access_properties: u64,
+    pub ats_attribute: u32,
+    pub pci_segment_number: u32,
+    /// The number of bits of physical address the root complex is able to access.
+    pub memory_address_size_limit: u8,
+}
+
+impl RootComplexNode {
+    pub fn supports_ats(&self) -> bool {
+        { self.ats_attribute }.get_bit(0)
+    }
+}
+
+/// Describes an ARM SMMUv3. The input IDs of its mappings are SMMU stream IDs.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SmmuV3Node {
+    pub header: NodeHeader,
+    pub base_address: u64,
+    pub flags: u32,
+    _reserved: u32,
+    pub vatos_address: u64,
+    pub model: u32,
+    pub event_gsiv: u32,
+    pub pri_gsiv: u32,
+    pub gerr_gsiv: u32,
+    pub sync_gsiv: u32,
+    /// Only valid if bit 3 of `flags` is set.
+    pub proximity_domain: u32,
+    /// The index of the ID mapping used for the SMMU's own MSIs. Only valid if all of the SMMU's interrupts are
+    /// MSIs (the GSIVs are all zero).
+    pub device_id_mapping_index: u32,
+}
+
+impl SmmuV3Node {
+    pub fn coherent_access_override(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn hit_tables_override(&self) -> u8 {
+        { self.flags }.get_bits(1..3) as u8
+    }
+
+    pub fn proximity_domain_valid(&self) -> bool {
+        { self.flags }.get_bit(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn node_header(
+        node_type: u8,
+        length: usize,
+        id_array_offset: usize,
+        number_of_id_mappings: u32,
+    ) -> NodeHeader {
+        NodeHeader {
+            node_type,
+            length: length as u16,
+            revision: 0,
+            identifier: 0,
+            number_of_id_mappings,
+            id_array_offset: id_array_offset as u32,
+        }
+    }
+
+    fn mapping(input_base: u32, number_of_ids: u32, output_base: u32, output_reference: usize) -> IdMapping {
+        IdMapping { input_base, number_of_ids, output_base, output_reference: output_reference as u32, flags: 0 }
+    }
+
+    #[test]
+    fn test_nodes() {
+        const ITS_GROUP: usize = 48;
+        const SMMU: usize = ITS_GROUP + 24;
+        const ROOT_COMPLEX: usize = SMMU + 88;
+
+        let mut body = [4, 0, 0, 0, ITS_GROUP as u8, 0, 0, 0, 0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&ItsGroupNode { header: node_header(0, 24, 0, 0), number_of_its: 1 }));
+        body.extend(5u32.to_le_bytes());
+
+        body.extend(bytes_of(&SmmuV3Node {
+            header: node_header(4, 88, 68, 1),
+            base_address: 0x2b40_0000,
+            flags: 0b1001,
+            _reserved: 0,
+            vatos_address: 0,
+            model: 0,
+            event_gsiv: 0x16a,
+            pri_gsiv: 0,
+            gerr_gsiv: 0x16c,
+            sync_gsiv: 0x16d,
+            proximity_domain: 1,
+            device_id_mapping_index: 0,
+        }));
+        body.extend(bytes_of(&mapping(0, 0xffff, 0x1_0000, ITS_GROUP)));
+
+        body.extend(bytes_of(&RootComplexNode {
+            header: node_header(2, 56, 36, 1),
+            memory_access_properties: 1,
+            ats_attribute: 1,
+            pci_segment_number: 0,
+            memory_address_size_limit: 48,
+        }));
+        body.extend([0; 3]);
+        body.extend(bytes_of(&mapping(0, 0xffff, 0x8000, SMMU)));
+
+        // A root complex node that's too short to be read is returned as an unknown node
+        body.extend(bytes_of(&node_header(2, 20, 0, 0)));
+        body.extend([0; 4]);
+
+        let buffer = TableBuffer::<Iort>::new(&table(b"IORT", 5, &body));
+        let iort = buffer.get();
+        assert!(iort.validate().is_ok());
+
+        let nodes: Vec<_> = iort.nodes().collect();
+        assert_eq!(nodes.len(), 4);
+        assert!(matches!(nodes[0], IortNode::ItsGroup(its) if its.its_identifiers().eq([5])));
+        assert!(matches!(nodes[1], IortNode::SmmuV3(smmu) if smmu.proximity_domain_valid()));
+        assert!(matches!(nodes[2], IortNode::RootComplex(root_complex) if root_complex.supports_ats()));
+        assert!(matches!(nodes[3], IortNode::Other(header) if header.length == 20));
+
+        // A requester ID is mapped to a stream ID by the root complex, and then to a device ID by the SMMU
+        let (smmu, stream_id) = iort.map_id(&nodes[2], 0x0108).unwrap();
+        assert!(matches!(smmu, IortNode::SmmuV3(_)));
+        assert_eq!(stream_id, 0x8108);
+        let (its_group, device_id) = iort.map_id(&smmu, stream_id).unwrap();
+        assert!(matches!(its_group, IortNode::ItsGroup(_)));
+        assert_eq!(device_id, 0x1_8108);
+        assert!(iort.map_id(&nodes[2], 0x1_0000).is_none());
+
+        assert!(iort.node_at_offset(ROOT_COMPLEX as u32).is_some());
+        assert!(iort.node_at_offset(ROOT_COMPLEX as u32 + 56 + 20).is_none());
+        assert!(iort.node_at_offset(8).is_none());
+    }
+}
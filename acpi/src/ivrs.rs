@@ -0,0 +1,532 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, slice};
+
+/// Represents the I/O Virtualization Reporting Structure (IVRS), which describes the AMD IOMMUs (AMD-Vi) on the
+/// platform, the devices behind them, and any memory that requires special treatment by them. You can iterate
+/// over the IVHD and IVMD blocks in the table with [`Ivrs::entries`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ivrs {
+    pub header: SdtHeader,
+    pub iv_info: u32,
+    _reserved: u64,
+}
+
+/// ### Safety: Implementation properly represents a valid IVRS.
+unsafe impl AcpiTable for Ivrs {
+    const SIGNATURE: Signature = Signature::IVRS;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Ivrs {
+    pub fn entries(&self) -> IvrsEntryIter<'_> {
+        IvrsEntryIter {
+            pointer: unsafe { (self as *const Ivrs as *const u8).add(mem::size_of::<Ivrs>()) },
+            remaining_length: self.header.length - mem::size_of::<Ivrs>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Whether the IOMMUs' Extended Feature Register images are reported in type `0x11` and `0x40` IVHD blocks.
+    pub fn efr_supported(&self) -> bool {
+        { self.iv_info }.get_bit(0)
+    }
+
+    pub fn dma_remap_support(&self) -> bool {
+        { self.iv_info }.get_bit(1)
+    }
+
+    /// The maximum guest virtual address size supported, encoded as described by the AMD IOMMU specification.
+    pub fn guest_virtual_address_size(&self) -> u8 {
+        { self.iv_info }.get_bits(5..8) as u8
+    }
+
+    /// The maximum physical address size supported, in bits.
+    pub fn physical_address_size(&self) -> u8 {
+        { self.iv_info }.get_bits(8..15) as u8
+    }
+
+    /// The maximum virtual address size supported, in bits.
+    pub fn virtual_address_size(&self) -> u8 {
+        { self.iv_info }.get_bits(15..22) as u8
+    }
+}
+
+#[derive(Debug)]
+pub struct IvrsEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum IvrsEntry<'a> {
+    /// I/O Virtualization Hardware Definition (types `0x10`, `0x11`, and `0x40`).
+    Ivhd(&'a IvhdEntry),
+    /// I/O Virtualization Memory Definition (types `0x20`, `0x21`, and `0x22`).
+    Ivmd(&'a IvmdEntry),
+}
+
+impl<'a> Iterator for IvrsEntryIter<'a> {
+    type Item = IvrsEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length == 0 || header.length as u32 > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length as u32;
+
+            match header.entry_type {
+                0x10 | 0x11 | 0x40 if header.length as usize >= mem::size_of::<IvhdEntry>() => {
+                    return Some(IvrsEntry::Ivhd(unsafe { &*(entry_pointer as *const IvhdEntry) }))
+                }
+                0x20..=0x22 if header.length as usize >= mem::size_of::<IvmdEntry>() => {
+                    return Some(IvrsEntry::Ivmd(unsafe { &*(entry_pointer as *const IvmdEntry) }))
+                }
+
+                /*
+                 * Other block types are reserved by the AMD IOMMU specification, or describe hardware we don't
+                 * know about. We should skip them if they appear in a real IVRS, along with blocks that are too
+                 * short to be read as their type.
+                 */
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u8,
+    pub flags: u8,
+    pub length: u16,
+}
+
+/// Describes a single IOMMU, and the devices it manages. This structure represents the common part of type
+/// `0x10`, `0x11`, and `0x40` IVHD blocks; the Extended Feature Register image present in the latter two can be
+/// accessed with [`IvhdEntry::efr_register_image`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IvhdEntry {
+    pub header: EntryHeader,
+    /// The PCI device ID (bus, device, and function) of the IOMMU itself.
+    pub device_id: u16,
+    /// The offset of the IOMMU's capability block within its PCI configuration space.
+    pub capability_offset: u16,
+    /// The physical base address of the IOMMU's MMIO register space.
+    pub iommu_base_address: u64,
+    pub pci_segment_group: u16,
+    pub iommu_info: u16,
+    /// For type `0x10` blocks, this is the IOMMU Feature Reporting field. For type `0x11` and `0x40` blocks, this
+    /// is the IOMMU Attributes field.
+    pub iommu_feature_info: u32,
+}
+
+impl IvhdEntry {
+    pub fn block_type(&self) -> u8 {
+        self.header.entry_type
+    }
+
+    pub fn ht_tunnel_enable(&self) -> bool {
+        self.header.flags.get_bit(0)
+    }
+
+    pub fn pass_posted_write(&self) -> bool {
+        self.header.flags.get_bit(1)
+    }
+
+    pub fn response_pass_posted_write(&self) -> bool {
+        self.header.flags.get_bit(2)
+    }
+
+    pub fn isochronous(&self) -> bool {
+        self.header.flags.get_bit(3)
+    }
+
+    pub fn iotlb_supported(&self) -> bool {
+        self.header.flags.get_bit(4)
+    }
+
+    pub fn coherent(&self) -> bool {
+        self.header.flags.get_bit(5)
+    }
+
+    pub fn prefetch_supported(&self) -> bool {
+        self.header.flags.get_bit(6)
+    }
+
+    pub fn ppr_supported(&self) -> bool {
+        self.header.flags.get_bit(7)
+    }
+
+    /// The MSI message number used by the IOMMU for its event log, PPR log, and GA log interrupts.
+    pub fn msi_number(&self) -> u8 {
+        { self.iommu_info }.get_bits(0..5) as u8
+    }
+
+    /// The HyperTransport unit ID of the IOMMU.
+    pub fn unit_id(&self) -> u8 {
+        { self.iommu_info }.get_bits(8..13) as u8
+    }
+
+    /// The image of the IOMMU's Extended Feature Register. Returns `None` for type `0x10` blocks, which do not
+    /// include it.
+    pub fn efr_register_image(&self) -> Option<u64> {
+        if self.block_type() == 0x10 || (self.header.length as usize) < IVHD_EXTENDED_HEADER_LENGTH {
+            return None;
+        }
+
+        let bytes = self.bytes();
+        let mut efr = [0; 8];
+        efr.copy_from_slice(&bytes[mem::size_of::<IvhdEntry>()..(mem::size_of::<IvhdEntry>() + 8)]);
+        Some(u64::from_le_bytes(efr))
+    }
+
+    pub fn device_entries(&self) -> IvhdDeviceEntryIter<'_> {
+        let header_length =
+            if self.block_type() == 0x10 { mem::size_of::<IvhdEntry>() } else { IVHD_EXTENDED_HEADER_LENGTH };
+        let bytes = self.bytes();
+
+        IvhdDeviceEntryIter { bytes: bytes.get(header_length..).unwrap_or(&[]) }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const IvhdEntry as *const u8, self.header.length as usize) }
+    }
+}
+
+/// Type `0x11` and `0x40` IVHD blocks have a larger header than type `0x10` blocks, which includes the EFR image.
+const IVHD_EXTENDED_HEADER_LENGTH: usize = 40;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpecialDeviceVariety {
+    IoApic,
+    Hpet,
+    Reserved(u8),
+}
+
+/// A device entry from an IVHD block. Each entry describes a device, or range of devices, managed by the IOMMU.
+/// The `dte_setting` of each entry holds the settings the OS should program into the Device Table Entries of the
+/// described devices.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IvhdDeviceEntry<'a> {
+    /// All devices on the IOMMU's segment, from `0x0000` to `0xffff`.
+    All {
+        dte_setting: u8,
+    },
+    Select {
+        device_id: u16,
+        dte_setting: u8,
+    },
+    /// The start of a range of devices, which extends up to the `device_id` of the next `RangeEnd` entry.
+    RangeStart {
+        device_id: u16,
+        dte_setting: u8,
+    },
+    RangeEnd {
+        device_id: u16,
+    },
+    /// A device whose requests appear to the IOMMU to come from `source_device_id`.
+    AliasSelect {
+        device_id: u16,
+        dte_setting: u8,
+        source_device_id: u16,
+    },
+    /// The start of a range of devices whose requests appear to come from `source_device_id`. The range extends up
+    /// to the `device_id` of the next `RangeEnd` entry.
+    AliasRangeStart {
+        device_id: u16,
+        dte_setting: u8,
+        source_device_id: u16,
+    },
+    ExtendedSelect {
+        device_id: u16,
+        dte_setting: u8,
+        extended_setting: u32,
+    },
+    ExtendedRangeStart {
+        device_id: u16,
+        dte_setting: u8,
+        extended_setting: u32,
+    },
+    /// An I/O APIC or HPET. `handle` is the I/O APIC ID or HPET number, as reported in the MADT or HPET table, and
+    /// `device_id` is the PCI device ID it uses to make requests.
+    Special {
+        handle: u8,
+        device_id: u16,
+        dte_setting: u8,
+        variety: SpecialDeviceVariety,
+    },
+    /// An ACPI namespace device, identified by its `_HID`, `_CID`, and `_UID`.
+    AcpiHid {
+        device_id: u16,
+        dte_setting: u8,
+        hid: [u8; 8],
+        cid: [u8; 8],
+        uid_format: u8,
+        uid: &'a [u8],
+    },
+}
+
+#[derive(Debug)]
+pub struct IvhdDeviceEntryIter<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for IvhdDeviceEntryIter<'a> {
+    type Item = IvhdDeviceEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry_type = *self.bytes.first()?;
+
+            /*
+             * The length of each entry is encoded in the top two bits of its type, apart from type 240 (ACPI HID
+             * devices), which is variable-length.
+             */
+            let length = match entry_type {
+                0..=63 => 4,
+                64..=127 => 8,
+                240 => 22 + *self.bytes.get(21)? as usize,
+                128..=191 => 16,
+                192..=255 => 32,
+            };
+            if self.bytes.len() < length {
+                self.bytes = &[];
+                return None;
+            }
+
+            let (entry, remaining) = self.bytes.split_at(length);
+            self.bytes = remaining;
+
+            let device_id = u16::from_le_bytes([entry[1], entry[2]]);
+            let dte_setting = entry[3];
+
+            return Some(match entry_type {
+                1 => IvhdDeviceEntry::All { dte_setting },
+                2 => IvhdDeviceEntry::Select { device_id, dte_setting },
+                3 => IvhdDeviceEntry::RangeStart { device_id, dte_setting },
+                4 => IvhdDeviceEntry::RangeEnd { device_id },
+                66 => IvhdDeviceEntry::AliasSelect {
+                    device_id,
+                    dte_setting,
+                    source_device_id: u16::from_le_bytes([entry[5], entry[6]]),
+                },
+                67 => IvhdDeviceEntry::AliasRangeStart {
+                    device_id,
+                    dte_setting,
+                    source_device_id: u16::from_le_bytes([entry[5], entry[6]]),
+                },
+                70 => IvhdDeviceEntry::ExtendedSelect {
+                    device_id,
+                    dte_setting,
+                    extended_setting: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                },
+                71 => IvhdDeviceEntry::ExtendedRangeStart {
+                    device_id,
+                    dte_setting,
+                    extended_setting: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                },
+                72 => IvhdDeviceEntry::Special {
+                    handle: entry[4],
+                    device_id: u16::from_le_bytes([entry[5], entry[6]]),
+                    dte_setting,
+                    variety: match entry[7] {
+                        1 => SpecialDeviceVariety::IoApic,
+                        2 => SpecialDeviceVariety::Hpet,
+                        other => SpecialDeviceVariety::Reserved(other),
+                    },
+                },
+                240 => {
+                    let mut hid = [0; 8];
+                    let mut cid = [0; 8];
+                    hid.copy_from_slice(&entry[4..12]);
+                    cid.copy_from_slice(&entry[12..20]);
+
+                    IvhdDeviceEntry::AcpiHid {
+                        device_id,
+                        dte_setting,
+                        hid,
+                        cid,
+                        uid_format: entry[20],
+                        uid: &entry[22..],
+                    }
+                }
+
+                /*
+                 * Type 0 is padding, and the rest of the types are reserved. Skip them.
+                 */
+                _ => continue,
+            });
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IvmdDevices {
+    /// The memory block applies to all devices (type `0x20`).
+    All,
+    /// The memory block applies to a single device (type `0x21`).
+    Select(u16),
+    /// The memory block applies to an inclusive range of devices (type `0x22`).
+    Range(u16, u16),
+}
+
+/// Describes a block of memory that requires special treatment by the IOMMU, such as memory that must be
+/// identity-mapped for some devices.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IvmdEntry {
+    pub header: EntryHeader,
+    pub device_id: u16,
+    /// For type `0x22` blocks, this is the last device ID of the range.
+    pub auxiliary_data: u16,
+    _reserved: u64,
+    pub start_address: u64,
+    pub memory_block_length: u64,
+}
+
+impl IvmdEntry {
+    pub fn devices(&self) -> IvmdDevices {
+        match self.header.entry_type {
+            0x21 => IvmdDevices::Select(self.device_id),
+            0x22 => IvmdDevices::Range(self.device_id, self.auxiliary_data),
+            _ => IvmdDevices::All,
+        }
+    }
+
+    /// Whether the block must be identity-mapped for the devices it applies to.
+    pub fn unity(&self) -> bool {
+        self.header.flags.get_bit(0)
+    }
+
+    pub fn read_permission(&self) -> bool {
+        self.header.flags.get_bit(1)
+    }
+
+    pub fn write_permission(&self) -> bool {
+        self.header.flags.get_bit(2)
+    }
+
+    pub fn exclusion_range(&self) -> bool {
+        self.header.flags.get_bit(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_entries() {
+        let iv_info: u32 = 40 << 15 | 48 << 8 | 1;
+        let mut body = iv_info.to_le_bytes().to_vec();
+        body.extend([0; 8]);
+
+        let mut device_entries = [1, 0, 0, 0x00].to_vec();
+        device_entries.extend([72, 0, 0, 0xd7, 0x21, 0xa0, 0x00, 1]);
+        device_entries.extend([240, 0x10, 0x00, 0x00]);
+        device_entries.extend(b"AMDI0020PNP0C09\0");
+        device_entries.extend([1, 2, b'U', b'1']);
+        device_entries.extend([0, 0, 0, 0]);
+        // An entry that runs off the end of the block ends the iteration
+        device_entries.extend([2, 0]);
+        body.extend(bytes_of(&IvhdEntry {
+            header: EntryHeader {
+                entry_type: 0x11,
+                flags: 0b1011_0000,
+                length: (IVHD_EXTENDED_HEADER_LENGTH + device_entries.len()) as u16,
+            },
+            device_id: 0x0002,
+            capability_offset: 0x40,
+            iommu_base_address: 0xfd20_0000,
+            pci_segment_group: 0,
+            iommu_info: 0x13 << 8 | 4,
+            iommu_feature_info: 0,
+        }));
+        body.extend(0x0000_0000_0220_0000u64.to_le_bytes());
+        body.extend([0; 8]);
+        body.extend(device_entries);
+        // An IVHD block that's too short to be read is skipped
+        body.extend([0x10, 0, 8, 0, 0, 0, 0, 0]);
+        body.extend(bytes_of(&IvmdEntry {
+            header: EntryHeader { entry_type: 0x21, flags: 0b0111, length: 32 },
+            device_id: 0x00a0,
+            auxiliary_data: 0,
+            _reserved: 0,
+            start_address: 0x9d00_0000,
+            memory_block_length: 0x10_0000,
+        }));
+
+        let buffer = TableBuffer::<Ivrs>::new(&table(b"IVRS", 2, &body));
+        let ivrs = buffer.get();
+        assert!(ivrs.validate().is_ok());
+        assert!(ivrs.efr_supported() && !ivrs.dma_remap_support());
+        assert_eq!(ivrs.physical_address_size(), 48);
+        assert_eq!(ivrs.virtual_address_size(), 40);
+
+        let entries: Vec<_> = ivrs.entries().collect();
+        assert_eq!(entries.len(), 2);
+        match entries[0] {
+            IvrsEntry::Ivhd(ivhd) => {
+                assert_eq!(ivhd.block_type(), 0x11);
+                assert!(ivhd.iotlb_supported() && ivhd.coherent() && ivhd.ppr_supported());
+                assert!(!ivhd.prefetch_supported());
+                assert_eq!(ivhd.msi_number(), 4);
+                assert_eq!(ivhd.unit_id(), 0x13);
+                assert_eq!(ivhd.efr_register_image(), Some(0x0220_0000));
+
+                let devices: Vec<_> = ivhd.device_entries().collect();
+                assert_eq!(
+                    devices,
+                    [
+                        IvhdDeviceEntry::All { dte_setting: 0 },
+                        IvhdDeviceEntry::Special {
+                            handle: 0x21,
+                            device_id: 0x00a0,
+                            dte_setting: 0xd7,
+                            variety: SpecialDeviceVariety::IoApic,
+                        },
+                        IvhdDeviceEntry::AcpiHid {
+                            device_id: 0x0010,
+                            dte_setting: 0,
+                            hid: *b"AMDI0020",
+                            cid: *b"PNP0C09\0",
+                            uid_format: 1,
+                            uid: b"U1",
+                        },
+                    ]
+                );
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            IvrsEntry::Ivmd(ivmd) => {
+                assert_eq!(ivmd.devices(), IvmdDevices::Select(0x00a0));
+                assert!(ivmd.unity() && ivmd.read_permission() && ivmd.write_permission());
+                assert!(!ivmd.exclusion_range());
+                assert_eq!({ ivmd.start_address }, 0x9d00_0000);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+}
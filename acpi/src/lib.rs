@@ -9,7 +9,9 @@
 //!
 //! This crate requires `alloc` to make heap allocations. If you are trying to find the RSDP in an environment that
 //! does not have a heap (e.g. a bootloader), you can use the `rsdp` crate. The types from that crate are
-//! compatible with `acpi`.
+//! compatible with `acpi`. If you need the MADT or MCFG before a heap is available, the `fixed_capacity` feature
+//! provides `FixedCapacity`, a fixed-size buffer that can be used in place of an allocator, and
+//! `FixedPlatformInfo`, which holds the processors and interrupt controllers in fixed-capacity arrays.
 //!
 //! ### Usage
 //! To use the library, you will need to provide an implementation of the `AcpiHandler` trait, which allows the
@@ -21,8 +23,10 @@
 //! * Use `AcpiTables::from_rsdt` if you have the physical address of the RSDT/XSDT
 //! * Use `AcpiTables::search_for_rsdp_bios` if you don't have the address of either, but **you know you are
 //! running on BIOS, not UEFI**
-//! * Use `AcpiTables::from_tables_direct` if you are using the library in an unusual setting, such as in usermode,
-//!   and have a custom method to enumerate and access the tables.
+//! * Use `AcpiTables::from_efi_system_table` if you are running on UEFI, and have the EFI System Table
+//! * Use `AcpiTables::from_tables` (or an `AcpiTablesBuilder`) if there is no RSDP to find, such as when the
+//!   tables are passed by a bootloader or hypervisor, or if you are using the library in an unusual setting, such
+//!   as in usermode, and have a custom method to enumerate and access the tables.
 //!
 //! `AcpiTables` stores the addresses of all of the tables detected on a platform. The SDTs are parsed by this
 //! library, or can be accessed directly with `from_sdt`, while the `DSDT` and any `SSDTs` should be parsed with
@@ -36,6 +40,11 @@
 //!      Precision Event Timer.
 //!    - [`PciConfigRegions`](crate::mcfg::PciConfigRegions) parses the MCFG and tells you how PCIe configuration
 //!      space is mapped into physical memory.
+//!    - [`NumaInfo`](crate::platform::NumaInfo) parses the SRAT and tells you which proximity domain each
+//!      processor and range of physical memory belongs to.
+//!
+//! Firmware that describes the platform incorrectly can be worked around with the [`quirks`] module, which
+//! `PlatformInfo` uses to apply known workarounds for the platform's firmware.
 
 /*
  * Contributing notes (you may find these useful if you're new to contributing to the library):
@@ -56,34 +65,120 @@
 extern crate std;
 
 pub mod address;
+pub mod aest;
+pub mod apei;
+pub mod apmt;
 pub mod bgrt;
+pub mod boot;
+pub mod cdit;
+pub mod cedt;
+pub mod compose;
+pub mod crat;
+pub mod csrt;
+pub mod dbg2;
+pub mod dmar;
+pub mod ecdt;
+pub mod facs;
 pub mod fadt;
+pub mod fpdt;
+pub mod gtdt;
+pub mod hmat;
 pub mod hpet;
+pub mod iort;
+pub mod ivrs;
+pub mod lpit;
 pub mod madt;
 pub mod mcfg;
+pub mod mpst;
+pub mod msct;
+pub mod nfit;
+pub mod pcct;
+pub mod phat;
+pub mod power;
+pub mod pptt;
+pub mod prmt;
+pub mod quirks;
+pub mod ras2;
+pub mod rasf;
+pub mod sdev;
 pub mod sdt;
-
+pub mod slit;
+pub mod spcr;
+pub mod srat;
+pub mod stao;
+pub mod tpm2;
+pub mod viot;
+pub mod wdat;
+pub mod wsmt;
+pub mod xenv;
+
+#[cfg(feature = "fixed_capacity")]
+mod fixed_capacity;
+#[cfg(feature = "fixed_capacity")]
+pub use fixed_capacity::*;
+#[cfg(feature = "allocator_api")]
+mod managed_box;
+#[cfg(feature = "allocator_api")]
+pub use managed_box::*;
 #[cfg(feature = "allocator_api")]
 mod managed_slice;
 #[cfg(feature = "allocator_api")]
 pub use managed_slice::*;
+#[cfg(feature = "allocator_api")]
+mod managed_string;
+#[cfg(feature = "allocator_api")]
+pub use managed_string::*;
+#[cfg(feature = "allocator_api")]
+mod managed_vec;
+#[cfg(feature = "allocator_api")]
+pub use managed_vec::*;
+#[cfg(test)]
+mod test_utils;
 
+#[cfg(all(feature = "allocator_api", feature = "aml"))]
+pub mod cpu_idle;
 #[cfg(feature = "allocator_api")]
 pub mod platform;
 #[cfg(feature = "allocator_api")]
 pub use crate::platform::{interrupt::InterruptModel, PlatformInfo};
 
-#[cfg(feature = "allocator_api")]
+#[cfg(feature = "aml")]
+pub mod ac_adapter;
+#[cfg(feature = "aml")]
+pub mod battery;
+#[cfg(feature = "aml")]
+pub mod cppc;
+#[cfg(feature = "aml")]
+pub mod cpu_perf;
+#[cfg(feature = "aml")]
+pub mod device;
+#[cfg(feature = "aml")]
+pub mod device_power;
+#[cfg(feature = "aml")]
+pub mod ec;
+#[cfg(feature = "aml")]
+pub mod fan;
+#[cfg(feature = "aml")]
+pub mod hotplug;
+#[cfg(feature = "aml")]
+pub mod lid;
+#[cfg(feature = "aml")]
+pub mod thermal;
+
+#[cfg(any(feature = "allocator_api", feature = "aml"))]
 extern crate alloc;
 
+pub use address::RegisterHandler;
 pub use fadt::PowerProfile;
 pub use hpet::HpetInfo;
 pub use madt::MadtError;
 pub use mcfg::PciConfigRegions;
 pub use rsdp::{
     handler::{AcpiHandler, PhysicalMapping},
+    BiosSearchOptions,
     RsdpError,
 };
+pub use sdt::ValidationPolicy;
 
 use crate::sdt::{SdtHeader, Signature};
 use core::mem;
@@ -102,14 +197,37 @@ pub type AcpiResult<T> = core::result::Result<T, AcpiError>;
 /// page-faults, aliasing references, or derefencing uninitialized memory (the latter two being UB).
 /// This isn't forbidden, however, because some tables rely on the impl being larger than a provided SDT in some
 /// versions of ACPI (the [`ExtendedField`](crate::sdt::ExtendedField) type will be useful if you need to do
-/// this. See our [`Fadt`](crate::fadt::Fadt) type for an example of this).
-pub unsafe trait AcpiTable {
+/// this. See our [`Fadt`](crate::fadt::Fadt) type for an example of this). Such tables must set
+/// [`AcpiTable::MIN_LENGTH`] to the length of the earliest version they support.
+pub unsafe trait AcpiTable: Sized {
     const SIGNATURE: Signature;
 
+    /// The shortest length a table of this type can have. Tables that are shorter than this are always rejected,
+    /// whatever the [`ValidationPolicy`] is, because the fields of the impl would be read from outside the table.
+    const MIN_LENGTH: usize = mem::size_of::<Self>();
+
     fn header(&self) -> &sdt::SdtHeader;
 
+    /// Check the parts of the table that are specific to it, once its header and checksum have been checked. This
+    /// should make sure the table can be read safely (e.g. that a variable-length part of it fits inside the
+    /// table), so it's performed whatever the [`ValidationPolicy`] is.
+    fn validate_contents(&self) -> AcpiResult<()> {
+        Ok(())
+    }
+
+    /// Report the problems with the parts of the table that are specific to it, which don't stop it from being
+    /// read safely (`AcpiError::SdtLengthMismatch` and `AcpiError::SdtReservedFieldSet`). Each problem is ignored
+    /// or rejected according to the [`ValidationPolicy`]. This is only called once `validate_contents` has
+    /// succeeded.
+    fn find_content_problems(&self, _report: &mut dyn FnMut(AcpiError)) {}
+
     fn validate(&self) -> AcpiResult<()> {
-        self.header().validate(Self::SIGNATURE)
+        if (self.header().length as usize) < Self::MIN_LENGTH {
+            return Err(AcpiError::SdtInvalidLength(Self::SIGNATURE));
+        }
+        self.header().validate(Self::SIGNATURE)?;
+        self.validate_contents()?;
+        ValidationPolicy::STRICT.check(Self::SIGNATURE, |report| self.find_content_problems(report))
     }
 }
 
@@ -122,12 +240,63 @@ pub enum AcpiError {
     SdtInvalidOemId(Signature),
     SdtInvalidTableId(Signature),
     SdtInvalidChecksum(Signature),
+    /// The table is too short to contain the structures it claims to.
+    SdtInvalidLength(Signature),
+    /// The length of the table disagrees with its contents (e.g. it has bytes left over after its last entry),
+    /// although it's long enough for them to be read safely.
+    SdtLengthMismatch(Signature),
+    /// A reserved field of the table isn't zero.
+    SdtReservedFieldSet(Signature),
 
     TableMissing(Signature),
     InvalidFacsAddress,
     InvalidDsdtAddress,
     InvalidMadt(MadtError),
     InvalidGenericAddress,
+    /// The legacy and extended addresses of a register block (or of the FACS or DSDT) in the FADT disagree, and
+    /// the [`AddressPolicy`](fadt::AddressPolicy) is to report an error.
+    FadtAddressMismatch,
+    /// The register is in an address space that this crate can't access.
+    UnsupportedAddressSpace(address::AddressSpace),
+    Watchdog(wdat::WdatError),
+    Compose(compose::ComposeError),
+    Pcc(pcct::PccError),
+    /// The operation uses fixed hardware (e.g. the PM1 register blocks, or the SMI command port) that doesn't
+    /// exist, because the platform is a hardware-reduced ACPI platform.
+    HardwareReduced,
+    /// The firmware didn't respond to a request to enter or leave ACPI mode.
+    AcpiModeTimeout,
+    /// The platform doesn't support resetting the system through the reset register in the FADT.
+    ResetNotSupported,
+    /// The fixed event is not implemented in the fixed hardware of this platform.
+    #[cfg(feature = "allocator_api")]
+    FixedEventNotSupported(power::event::FixedEvent),
+    /// The GPE number is not in any of the platform's GPE blocks.
+    #[cfg(feature = "allocator_api")]
+    InvalidGpe(u32),
+    #[cfg(feature = "aml")]
+    Sleep(power::sleep::SleepError),
+    #[cfg(feature = "aml")]
+    EmbeddedController(ec::EcError),
+    #[cfg(feature = "aml")]
+    Battery(battery::BatteryError),
+    #[cfg(all(feature = "allocator_api", feature = "aml"))]
+    CpuIdle(cpu_idle::CpuIdleError),
+    #[cfg(feature = "aml")]
+    CpuPerf(cpu_perf::CpuPerfError),
+    #[cfg(feature = "aml")]
+    Cppc(cppc::CppcError),
+    #[cfg(feature = "aml")]
+    DevicePower(device_power::DevicePowerError),
+    #[cfg(feature = "aml")]
+    Fan(fan::FanError),
+    #[cfg(feature = "aml")]
+    Hotplug(hotplug::HotplugError),
+    #[cfg(all(feature = "allocator_api", feature = "aml"))]
+    PciRouting(platform::pci::PciRoutingError),
+    /// An error occurred while evaluating an AML object.
+    #[cfg(feature = "aml")]
+    Aml(aml::AmlError),
 
     AllocError,
 }
@@ -141,11 +310,22 @@ pub enum AcpiError {
 /// API for enumerating ACPI structures once an `AcpiTables` has been constructed.
 #[derive(Debug)]
 pub struct AcpiTables<H: AcpiHandler> {
-    mapping: PhysicalMapping<H, SdtHeader>,
+    tables: TableList<H>,
     revision: u8,
+    policy: ValidationPolicy,
     handler: H,
 }
 
+/// Where the addresses of the tables come from.
+#[derive(Debug)]
+enum TableList<H: AcpiHandler> {
+    /// The RSDT or XSDT, which contains the addresses of the other tables.
+    Root(PhysicalMapping<H, SdtHeader>),
+    /// A list of addresses provided directly, without a root table.
+    #[cfg(feature = "allocator_api")]
+    Direct(alloc::vec::Vec<usize>),
+}
+
 impl<H> AcpiTables<H>
 where
     H: AcpiHandler,
@@ -154,11 +334,32 @@ where
     ///
     /// ### Safety: Caller must ensure the provided address is valid to read as an RSDP.
     pub unsafe fn from_rsdp(handler: H, address: usize) -> AcpiResult<Self> {
+        unsafe { Self::from_rsdp_with_policy(handler, address, ValidationPolicy::STRICT) }
+    }
+
+    /// Create an `AcpiTables` if you have the physical address of the RSDP, validating the RSDP and the tables
+    /// according to `policy`. This can be used on platforms with buggy firmware, whose tables would otherwise be
+    /// rejected.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure the provided address is valid to read as an RSDP.
+    pub unsafe fn from_rsdp_with_policy(handler: H, address: usize, policy: ValidationPolicy) -> AcpiResult<Self> {
         let rsdp_mapping = unsafe { handler.map_physical_region::<Rsdp>(address, mem::size_of::<Rsdp>()) };
-        rsdp_mapping.validate().map_err(AcpiError::Rsdp)?;
+        let mut result = rsdp_mapping.validate();
+        if result == Err(RsdpError::InvalidOemId) && policy.allows(&AcpiError::Rsdp(RsdpError::InvalidOemId)) {
+            log::warn!("Ignoring problem with RSDP: {:?}", RsdpError::InvalidOemId);
+            result = rsdp_mapping.validate_checksum();
+        }
+        if let Err(err) = result {
+            if !policy.allows(&AcpiError::Rsdp(err)) {
+                return Err(AcpiError::Rsdp(err));
+            }
+            log::warn!("Ignoring problem with RSDP: {:?}", err);
+        }
 
         // Safety: `RSDP` has been validated.
-        unsafe { Self::from_validated_rsdp(handler, rsdp_mapping) }
+        unsafe { Self::from_validated_rsdp_with_policy(handler, rsdp_mapping, policy) }
     }
 
     /// Search for the RSDP on a BIOS platform. This accesses BIOS-specific memory locations and will probably not
@@ -170,12 +371,53 @@ where
         unsafe { Self::from_validated_rsdp(handler, rsdp_mapping) }
     }
 
+    /// Search for the RSDP on a BIOS platform, like [`AcpiTables::search_for_rsdp_bios`], but with additional
+    /// areas to search, or without probing the EBDA. See
+    /// [Rsdp::search_for_on_bios_with](rsdp::Rsdp::search_for_on_bios_with) for details.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure each of the additional areas is safe to read.
+    pub unsafe fn search_for_rsdp_bios_with(handler: H, options: &BiosSearchOptions<'_>) -> AcpiResult<Self> {
+        let rsdp_mapping =
+            unsafe { Rsdp::search_for_on_bios_with(handler.clone(), options) }.map_err(AcpiError::Rsdp)?;
+        // Safety: RSDP has been validated from `Rsdp::search_for_on_bios_with`
+        unsafe { Self::from_validated_rsdp(handler, rsdp_mapping) }
+    }
+
+    /// Find the RSDP in the configuration table of the EFI System Table at `system_table`, on a UEFI platform. See
+    /// [Rsdp::from_efi_system_table](rsdp::Rsdp::from_efi_system_table) for details.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure `system_table` is the physical address of a valid EFI System Table.
+    pub unsafe fn from_efi_system_table(handler: H, system_table: usize) -> AcpiResult<Self> {
+        let rsdp_mapping =
+            unsafe { Rsdp::from_efi_system_table(system_table, handler.clone()) }.map_err(AcpiError::Rsdp)?;
+        // Safety: RSDP has been validated from `Rsdp::from_efi_system_table`
+        unsafe { Self::from_validated_rsdp(handler, rsdp_mapping) }
+    }
+
     /// Create an `AcpiTables` if you have a `PhysicalMapping` of the RSDP that you know is correct. This is called
     /// from `from_rsdp` after validation, but can also be used if you've searched for the RSDP manually on a BIOS
     /// system.
     ///
     /// ### Safety: Caller must ensure that the provided mapping is a fully validated RSDP.
     pub unsafe fn from_validated_rsdp(handler: H, rsdp_mapping: PhysicalMapping<H, Rsdp>) -> AcpiResult<Self> {
+        unsafe { Self::from_validated_rsdp_with_policy(handler, rsdp_mapping, ValidationPolicy::STRICT) }
+    }
+
+    /// Create an `AcpiTables` from a `PhysicalMapping` of the RSDP that you know is correct, like
+    /// [`AcpiTables::from_validated_rsdp`], but validating the tables according to `policy`.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure that the provided mapping is an RSDP that has been validated (or accepted by `policy`).
+    pub unsafe fn from_validated_rsdp_with_policy(
+        handler: H,
+        rsdp_mapping: PhysicalMapping<H, Rsdp>,
+        policy: ValidationPolicy,
+    ) -> AcpiResult<Self> {
         macro_rules! read_root_table {
             ($signature_name:ident, $address_getter:ident) => {{
                 #[repr(transparent)]
@@ -189,6 +431,10 @@ where
                     fn header(&self) -> &SdtHeader {
                         &self.header
                     }
+
+                    fn find_content_problems(&self, report: &mut dyn FnMut(AcpiError)) {
+                        find_root_table_problems(&self.header, report)
+                    }
                 }
 
                 // Unmap RSDP as soon as possible
@@ -197,7 +443,8 @@ where
 
                 // Map and validate root table
                 // SAFETY: Addresses from a validated `RSDP` are also guaranteed to be valid.
-                let table_mapping = unsafe { read_table::<_, RootTable>(handler.clone(), table_phys_start) }?;
+                let table_mapping =
+                    unsafe { read_table::<_, RootTable>(handler.clone(), table_phys_start, policy) }?;
 
                 // Convert `table_mapping` to header mapping for storage
                 // Avoid requesting table unmap twice (from both original and converted `table_mapping`s)
@@ -233,7 +480,29 @@ where
             read_root_table!(XSDT, xsdt_address)
         };
 
-        Ok(Self { mapping: root_table_mapping, revision, handler })
+        Ok(Self { tables: TableList::Root(root_table_mapping), revision, policy, handler })
+    }
+
+    /// Create an `AcpiTables` from the physical addresses of the tables, for environments where there is no RSDP
+    /// to find (e.g. when the tables are passed by a bootloader or hypervisor, or through the `acpi` node of a
+    /// device tree). The tables are mapped and validated when they're accessed, in the same way as tables found
+    /// through the XSDT, which is what `revision` reports they came from. See [`AcpiTablesBuilder`] to add the
+    /// tables one at a time.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure that each address is valid to read as an `SdtHeader`.
+    #[cfg(feature = "allocator_api")]
+    pub unsafe fn from_tables<I>(addresses: I, handler: H) -> AcpiTables<H>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        AcpiTables {
+            tables: TableList::Direct(addresses.into_iter().collect()),
+            revision: 2,
+            policy: ValidationPolicy::STRICT,
+            handler,
+        }
     }
 
     /// The ACPI revision of the tables enumerated by this structure.
@@ -242,11 +511,50 @@ where
         self.revision
     }
 
+    pub fn validation_policy(&self) -> ValidationPolicy {
+        self.policy
+    }
+
+    /// Change how strictly tables are validated when they're read from now on. The root table (and RSDP) have
+    /// already been validated, so to relax their validation, use [`AcpiTables::from_rsdp_with_policy`].
+    pub fn set_validation_policy(&mut self, policy: ValidationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Check the header and checksum of each of the tables (including the root table and the DSDT), and list the
+    /// problems found with them, whatever the validation policy is. This can be used to report the problems that a
+    /// relaxed [`ValidationPolicy`] has ignored. The contents of the root table, SLIT, MCFG, HPET and BGRT are
+    /// checked too.
+    pub fn validation_report(&self) -> impl Iterator<Item = ValidationWarning> + '_ {
+        let root_table = match &self.tables {
+            TableList::Root(mapping) => Some(mapping.physical_start()),
+            #[cfg(feature = "allocator_api")]
+            TableList::Direct(_) => None,
+        };
+        let dsdt = self.find_table::<fadt::Fadt>().ok().and_then(|fadt| fadt.dsdt_address().ok());
+
+        root_table
+            .into_iter()
+            .chain(self.tables_phys_ptrs().map(|table_phys_ptr| table_phys_ptr as usize))
+            .chain(dsdt)
+            .flat_map(move |address| {
+                // SAFETY: Table guarantees its contained addresses to be valid.
+                let problems = unsafe { find_table_problems(&self.handler, address) };
+                IntoIterator::into_iter(problems).flatten().map(move |error| ValidationWarning { address, error })
+            })
+    }
+
     /// Constructs a [`TablesPhysPtrsIter`] over this table.
     fn tables_phys_ptrs(&self) -> TablesPhysPtrsIter<'_> {
+        let mapping = match &self.tables {
+            TableList::Root(mapping) => mapping,
+            #[cfg(feature = "allocator_api")]
+            TableList::Direct(addresses) => return TablesPhysPtrsIter::Direct(addresses.iter()),
+        };
+
         // SAFETY: The virtual address of the array of pointers follows the virtual address of the table in memory.
-        let ptrs_virt_start = unsafe { self.mapping.virtual_start().as_ptr().add(1).cast::<u8>() };
-        let ptrs_bytes_len = self.mapping.region_length() - mem::size_of::<SdtHeader>();
+        let ptrs_virt_start = unsafe { mapping.virtual_start().as_ptr().add(1).cast::<u8>() };
+        let ptrs_bytes_len = mapping.region_length() - mem::size_of::<SdtHeader>();
         // SAFETY: `ptrs_virt_start` points to an array of `ptrs_bytes_len` bytes that lives as long as `self`.
         let ptrs_bytes = unsafe { core::slice::from_raw_parts(ptrs_virt_start, ptrs_bytes_len) };
         let ptr_size = if self.revision == 0 {
@@ -255,7 +563,9 @@ where
             8 // XSDT entry size
         };
 
-        ptrs_bytes.chunks(ptr_size).map(|ptr_bytes_src| {
+        // An entry that is cut off by the end of the table (which is only accepted if the policy allows length
+        // mismatches) is ignored
+        TablesPhysPtrsIter::Root(ptrs_bytes.chunks_exact(ptr_size).map(|ptr_bytes_src| {
             // Construct a native pointer using as many bytes as required from `ptr_bytes_src` (note that ACPI is
             // little-endian)
 
@@ -264,7 +574,7 @@ where
             ptr_bytes_dst[..common_ptr_size].copy_from_slice(&ptr_bytes_src[..common_ptr_size]);
 
             usize::from_le_bytes(ptr_bytes_dst) as *const SdtHeader
-        })
+        }))
     }
 
     /// Searches through the ACPI table headers and attempts to locate the table with a matching `T::SIGNATURE`.
@@ -272,7 +582,7 @@ where
         self.tables_phys_ptrs()
             .find_map(|table_phys_ptr| {
                 // SAFETY: Table guarantees its contained addresses to be valid.
-                match unsafe { read_table(self.handler.clone(), table_phys_ptr as usize) } {
+                match unsafe { read_table(self.handler.clone(), table_phys_ptr as usize, self.policy) } {
                     Ok(table_mapping) => Some(table_mapping),
                     Err(AcpiError::SdtInvalidSignature(_)) => None,
                     Err(e) => {
@@ -308,15 +618,25 @@ where
             }
 
             let dsdt_address = fadt.dsdt_address()?;
-            let dsdt = unsafe { read_table::<H, Dsdt>(self.handler.clone(), dsdt_address)? };
+            let dsdt = unsafe { read_table::<H, Dsdt>(self.handler.clone(), dsdt_address, self.policy)? };
 
             Ok(AmlTable::new(dsdt_address, dsdt.header().length))
         })
     }
 
+    /// Finds and maps the FACS, if it exists.
+    pub fn facs(&self) -> AcpiResult<PhysicalMapping<H, facs::Facs>> {
+        let fadt = self.find_table::<fadt::Fadt>()?;
+        unsafe { facs::Facs::map(&self.handler, fadt.facs_address()?) }
+    }
+
     /// Iterates through all of the SSDT tables.
     pub fn ssdts(&self) -> SsdtIterator<H> {
-        SsdtIterator { tables_phys_ptrs: self.tables_phys_ptrs(), handler: self.handler.clone() }
+        SsdtIterator {
+            tables_phys_ptrs: self.tables_phys_ptrs(),
+            policy: self.policy,
+            handler: self.handler.clone(),
+        }
     }
 
     /// Convenience method for contructing a [`PlatformInfo`](crate::platform::PlatformInfo). This is one of the
@@ -331,6 +651,14 @@ where
     }
 }
 
+/// A problem found with a table by [`AcpiTables::validation_report`].
+#[derive(Debug)]
+pub struct ValidationWarning {
+    /// The physical address of the table.
+    pub address: usize,
+    pub error: AcpiError,
+}
+
 #[derive(Debug)]
 pub struct Sdt {
     /// Physical address of the start of the SDT, including the header.
@@ -341,8 +669,61 @@ pub struct Sdt {
     pub validated: bool,
 }
 
+/// Builds an [`AcpiTables`] from the physical addresses of the tables, one at a time. See
+/// [`AcpiTables::from_tables`].
+#[cfg(feature = "allocator_api")]
+#[derive(Debug)]
+pub struct AcpiTablesBuilder<H: AcpiHandler> {
+    addresses: alloc::vec::Vec<usize>,
+    handler: H,
+}
+
+#[cfg(feature = "allocator_api")]
+impl<H> AcpiTablesBuilder<H>
+where
+    H: AcpiHandler,
+{
+    pub fn new(handler: H) -> AcpiTablesBuilder<H> {
+        AcpiTablesBuilder { addresses: alloc::vec::Vec::new(), handler }
+    }
+
+    /// Add the table at the physical address `address`.
+    ///
+    /// ### Safety
+    ///
+    /// Caller must ensure the provided address is valid to read as an `SdtHeader`.
+    pub unsafe fn push_table(&mut self, address: usize) -> &mut Self {
+        self.addresses.push(address);
+        self
+    }
+
+    pub fn build(self) -> AcpiTables<H> {
+        // Safety: Every address was checked by the caller of `push_table`.
+        unsafe { AcpiTables::from_tables(self.addresses, self.handler) }
+    }
+}
+
 /// An iterator over the physical table addresses in an RSDT or XSDT.
-type TablesPhysPtrsIter<'t> = core::iter::Map<core::slice::Chunks<'t, u8>, fn(&[u8]) -> *const SdtHeader>;
+type RootPhysPtrsIter<'t> = core::iter::Map<core::slice::ChunksExact<'t, u8>, fn(&[u8]) -> *const SdtHeader>;
+
+/// An iterator over the physical table addresses in an RSDT or XSDT, or provided directly.
+enum TablesPhysPtrsIter<'t> {
+    Root(RootPhysPtrsIter<'t>),
+    #[cfg(feature = "allocator_api")]
+    Direct(core::slice::Iter<'t, usize>),
+}
+
+impl Iterator for TablesPhysPtrsIter<'_> {
+    type Item = *const SdtHeader;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            TablesPhysPtrsIter::Root(ptrs) => ptrs.next(),
+            #[cfg(feature = "allocator_api")]
+            TablesPhysPtrsIter::Direct(addresses) => addresses.next().map(|&address| address as *const SdtHeader),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct AmlTable {
@@ -366,6 +747,7 @@ impl AmlTable {
 unsafe fn read_table<H: AcpiHandler, T: AcpiTable>(
     handler: H,
     address: usize,
+    policy: ValidationPolicy,
 ) -> AcpiResult<PhysicalMapping<H, T>> {
     // Attempt to peek at the SDT header to correctly enumerate the entire table.
 
@@ -373,7 +755,63 @@ unsafe fn read_table<H: AcpiHandler, T: AcpiTable>(
     // software issue).
     let header_mapping = unsafe { handler.map_physical_region::<SdtHeader>(address, mem::size_of::<SdtHeader>()) };
 
-    SdtHeader::validate_lazy(header_mapping, handler)
+    SdtHeader::validate_lazy(header_mapping, handler, policy)
+}
+
+/// Find the problems with the header and checksum of the table at `address`, which is checked against its own
+/// signature, and the problems with its contents if it's one of the tables that checks them.
+///
+/// ### Safety: Caller must ensure the provided address is valid for being read as an `SdtHeader`.
+unsafe fn find_table_problems<H: AcpiHandler>(handler: &H, address: usize) -> [Option<AcpiError>; 6] {
+    let header_mapping = unsafe { handler.map_physical_region::<SdtHeader>(address, mem::size_of::<SdtHeader>()) };
+    let length = usize::max(header_mapping.length as usize, mem::size_of::<SdtHeader>());
+    let signature = header_mapping.signature;
+    drop(header_mapping);
+
+    // SAFETY: `address` is the physical address of the header and the rest of the table.
+    let table_mapping = unsafe { handler.map_physical_region::<SdtHeader>(address, length) };
+    let mut problems = [None, None, None, None, None, None];
+    let mut next = problems.iter_mut();
+    let mut report = |problem| {
+        if let Some(slot) = next.next() {
+            *slot = Some(problem);
+        }
+    };
+    table_mapping.find_problems(signature, &mut report);
+
+    let header = &*table_mapping;
+    match signature {
+        Signature::RSDT | Signature::XSDT => find_root_table_problems(header, &mut report),
+        Signature::SLIT => find_content_problems::<slit::Slit>(header, &mut report),
+        Signature::MCFG => find_content_problems::<mcfg::Mcfg>(header, &mut report),
+        Signature::HPET => find_content_problems::<hpet::HpetTable>(header, &mut report),
+        Signature::BGRT => find_content_problems::<bgrt::Bgrt>(header, &mut report),
+        _ => (),
+    }
+
+    problems
+}
+
+/// Report the problems with the contents of the table that `header` is the header of, if it can be read as a `T`.
+fn find_content_problems<T: AcpiTable>(header: &SdtHeader, report: &mut dyn FnMut(AcpiError)) {
+    if (header.length as usize) < T::MIN_LENGTH {
+        return;
+    }
+
+    // SAFETY: The whole table is mapped, and is long enough to be read as a `T`, whose fields are unaligned.
+    let table = unsafe { &*(header as *const SdtHeader).cast::<T>() };
+    if table.validate_contents().is_ok() {
+        table.find_content_problems(report);
+    }
+}
+
+/// Report a root table whose entries don't fill it exactly, in which case the last entry is cut off.
+fn find_root_table_problems(header: &SdtHeader, report: &mut dyn FnMut(AcpiError)) {
+    let entry_size = if header.signature == Signature::RSDT { 4 } else { 8 };
+    let entries_length = (header.length as usize).saturating_sub(mem::size_of::<SdtHeader>());
+    if !entries_length.is_multiple_of(entry_size) {
+        report(AcpiError::SdtLengthMismatch(header.signature));
+    }
 }
 
 /// Iterator that steps through all of the tables, and returns only the SSDTs as `AmlTable`s.
@@ -382,6 +820,7 @@ where
     H: AcpiHandler,
 {
     tables_phys_ptrs: TablesPhysPtrsIter<'t>,
+    policy: ValidationPolicy,
     handler: H,
 }
 
@@ -408,11 +847,12 @@ where
 
         // Borrow single field for closure to avoid immutable reference to `self` that inhibits `find_map`
         let handler = &self.handler;
+        let policy = self.policy;
 
         // Consume iterator until next valid SSDT and return the latter
         self.tables_phys_ptrs.find_map(|table_phys_ptr| {
             // SAFETY: Table guarantees its contained addresses to be valid.
-            match unsafe { read_table::<_, Ssdt>(handler.clone(), table_phys_ptr as usize) } {
+            match unsafe { read_table::<_, Ssdt>(handler.clone(), table_phys_ptr as usize, policy) } {
                 Ok(ssdt_mapping) => Some(AmlTable::new(ssdt_mapping.physical_start(), ssdt_mapping.header.length)),
                 Err(AcpiError::SdtInvalidSignature(_)) => None,
                 Err(e) => {
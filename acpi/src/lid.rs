@@ -0,0 +1,65 @@
+use crate::{device, AcpiError, AcpiResult};
+use alloc::vec::Vec;
+use aml::{value::Args, AmlContext, AmlName, AmlValue};
+
+/// What changed about a lid, in response to a `Notify` on it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LidNotification {
+    /// `Notify(lid, 0x80)`: the lid has been opened or closed. Contains whether it is now open.
+    StatusChanged(bool),
+    /// The notification value isn't one defined for lids.
+    Other(u64),
+}
+
+/// A lid switch, described by a `PNP0C0D` device in the namespace.
+#[derive(Clone, Debug)]
+pub struct Lid {
+    path: AmlName,
+    open: bool,
+}
+
+impl Lid {
+    /// Find the lids in the namespace (devices with a `_HID` of `PNP0C0D`), and read their status.
+    pub fn enumerate(context: &mut AmlContext) -> AcpiResult<Vec<Lid>> {
+        device::find_devices_with_hid(context, "PNP0C0D")?
+            .into_iter()
+            .map(|path| {
+                let mut lid = Lid { path, open: true };
+                lid.refresh(context)?;
+                Ok(lid)
+            })
+            .collect()
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    /// Whether the lid was open when its status was last read.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Re-read whether the lid is open, by evaluating `_LID`.
+    pub fn refresh(&mut self, context: &mut AmlContext) -> AcpiResult<bool> {
+        let lid = device::evaluate(context, &self.path, "_LID", Args::EMPTY)?;
+        self.open = lid.as_integer(context).map_err(AcpiError::Aml)? != 0;
+        Ok(self.open)
+    }
+
+    /// Handle a `Notify` on the lid, by re-reading its status if it has changed.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, value: u64) -> AcpiResult<LidNotification> {
+        match value {
+            0x80 => Ok(LidNotification::StatusChanged(self.refresh(context)?)),
+            other => Ok(LidNotification::Other(other)),
+        }
+    }
+
+    /// Enable or disable the lid as a wake source, by evaluating `_PSW` (if the lid has one). This should be done
+    /// before entering a sleep state, so that opening the lid wakes the system.
+    pub fn set_wake_enabled(&self, context: &mut AmlContext, enabled: bool) -> AcpiResult<()> {
+        let args = Args::from_list(alloc::vec![AmlValue::Integer(enabled as u64)]).unwrap();
+        device::evaluate_optional(context, &self.path, "_PSW", args)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+    AcpiResult,
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// Represents the Low Power Idle Table (LPIT), which describes the low-power idle states the platform can enter
+/// while in S0 (often called S0ix), and the counters the OS can read to find out how long the platform has spent
+/// in each of them.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Lpit {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid LPIT.
+unsafe impl AcpiTable for Lpit {
+    const SIGNATURE: Signature = Signature::LPIT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Lpit {
+    pub fn entries(&self) -> LpiStructureIter<'_> {
+        LpiStructureIter {
+            pointer: unsafe { (self as *const Lpit as *const u8).add(mem::size_of::<Lpit>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Lpit>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over the native C-state based LPI structures that are not disabled.
+    pub fn native_c_states(&self) -> impl Iterator<Item = &NativeCStateLpi> {
+        self.entries().filter_map(|entry| match entry {
+            LpiStructure::NativeCState(lpi) if !lpi.is_disabled() => Some(lpi),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct LpiStructureIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum LpiStructure<'a> {
+    NativeCState(&'a NativeCStateLpi),
+    /// An LPI structure of a reserved type.
+    Reserved(&'a LpiStructureHeader),
+}
+
+impl<'a> Iterator for LpiStructureIter<'a> {
+    type Item = LpiStructure<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<LpiStructureHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const LpiStructureHeader) };
+        let length = header.length as usize;
+        if length < mem::size_of::<LpiStructureHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let structure_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        match header.structure_type {
+            0 if length >= mem::size_of::<NativeCStateLpi>() => {
+                Some(LpiStructure::NativeCState(unsafe { &*(structure_pointer as *const NativeCStateLpi) }))
+            }
+            _ => Some(LpiStructure::Reserved(header)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct LpiStructureHeader {
+    pub structure_type: u32,
+    pub length: u32,
+    pub unique_id: u16,
+    _reserved: u16,
+    pub flags: u32,
+}
+
+/// Describes a low-power idle state that is entered by a native C-state (such as `MWAIT`) of the processors.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NativeCStateLpi {
+    pub header: LpiStructureHeader,
+    entry_trigger: RawGenericAddress,
+    /// The minimum time, in microseconds, the platform must stay in this state for entering it to be worthwhile.
+    pub minimum_residency: u32,
+    /// The worst-case time, in microseconds, it takes to leave this state.
+    pub worst_case_wakeup_latency: u32,
+    residency_counter: RawGenericAddress,
+    /// The frequency of the residency counter, in Hz. If this is `0`, the counter counts at the frequency of the
+    /// TSC.
+    residency_counter_frequency: u64,
+}
+
+impl NativeCStateLpi {
+    pub fn unique_id(&self) -> u16 {
+        self.header.unique_id
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        { self.header.flags }.get_bit(0)
+    }
+
+    /// If true, this state doesn't have a residency counter, and the `residency_counter` field should be ignored.
+    pub fn residency_counter_not_available(&self) -> bool {
+        { self.header.flags }.get_bit(1)
+    }
+
+    /// The register that is used to enter this state. This is usually a fixed-function hardware register that
+    /// describes the native C-state (e.g. an `MWAIT` hint).
+    pub fn entry_trigger(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.entry_trigger)
+    }
+
+    /// The counter that records the time the platform has spent in this state. Returns `None` if the state does
+    /// not have a residency counter.
+    pub fn residency_counter(&self) -> Option<AcpiResult<GenericAddress>> {
+        if self.residency_counter_not_available() {
+            return None;
+        }
+
+        Some(GenericAddress::from_raw(self.residency_counter))
+    }
+
+    /// The frequency of the residency counter, in Hz, or `None` if it counts at the frequency of the TSC.
+    pub fn residency_counter_frequency(&self) -> Option<u64> {
+        match self.residency_counter_frequency {
+            0 => None,
+            frequency => Some(frequency),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, zeroed, TableBuffer};
+    use std::vec::Vec;
+
+    fn lpi(unique_id: u16, flags: u32) -> NativeCStateLpi {
+        NativeCStateLpi {
+            header: LpiStructureHeader {
+                structure_type: 0,
+                length: mem::size_of::<NativeCStateLpi>() as u32,
+                unique_id,
+                _reserved: 0,
+                flags,
+            },
+            entry_trigger: RawGenericAddress {
+                address_space: 0x7f,
+                bit_width: 0,
+                bit_offset: 0,
+                access_size: 0,
+                address: 0x60,
+            },
+            minimum_residency: 30_000,
+            worst_case_wakeup_latency: 3000,
+            residency_counter: RawGenericAddress {
+                address_space: 0x7f,
+                bit_width: 64,
+                bit_offset: 0,
+                access_size: 0,
+                address: 0x632,
+            },
+            residency_counter_frequency: 0,
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body = Vec::new();
+        body.extend(bytes_of(&lpi(0, 0)));
+        body.extend(bytes_of(&lpi(1, 0b11)));
+        // A structure of a reserved type, and a native C-state structure too short to be read as one
+        body.extend(bytes_of(&LpiStructureHeader { structure_type: 1, length: 16, ..unsafe { zeroed() } }));
+        body.extend(bytes_of(&LpiStructureHeader { structure_type: 0, length: 16, ..unsafe { zeroed() } }));
+
+        let buffer = TableBuffer::<Lpit>::new(&table(b"LPIT", 1, &body));
+        let lpit = buffer.get();
+        assert!(lpit.validate().is_ok());
+        assert_eq!(lpit.entries().count(), 4);
+        assert_eq!(lpit.entries().filter(|entry| matches!(entry, LpiStructure::Reserved(_))).count(), 2);
+
+        let states: Vec<_> = lpit.native_c_states().collect();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].unique_id(), 0);
+        assert_eq!(states[0].entry_trigger().unwrap().address, 0x60);
+        assert_eq!(states[0].residency_counter().unwrap().unwrap().address, 0x632);
+        assert_eq!(states[0].residency_counter_frequency(), None);
+
+        match lpit.entries().nth(1) {
+            Some(LpiStructure::NativeCState(lpi)) => {
+                assert!(lpi.is_disabled());
+                assert!(lpi.residency_counter().is_none());
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        let lpi = bytes_of(&lpi(0, 0));
+        let buffer = TableBuffer::<Lpit>::new(&table(b"LPIT", 1, &lpi[..lpi.len() - 1]));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
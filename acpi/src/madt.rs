@@ -1,9 +1,16 @@
 use crate::{
     sdt::{ExtendedField, SdtHeader, Signature},
+    AcpiHandler,
     AcpiTable,
 };
 use bit_field::BitField;
-use core::{marker::PhantomData, mem};
+use core::{
+    hint,
+    marker::PhantomData,
+    mem,
+    ptr,
+    sync::atomic::{self, Ordering},
+};
 
 #[cfg(feature = "allocator_api")]
 use crate::{
@@ -21,6 +28,8 @@ pub enum MadtError {
     InvalidLocalNmiLine,
     MpsIntiInvalidPolarity,
     MpsIntiInvalidTriggerMode,
+    /// The MADT describes a GIC, but doesn't contain a GIC distributor entry.
+    NoGicDistributor,
 }
 
 /// Represents the MADT - this contains the MADT header fields. You can then iterate over a `Madt`
@@ -84,7 +93,14 @@ impl Madt {
                 MadtEntry::GicMsiFrame(_) |
                 MadtEntry::GicRedistributor(_) |
                 MadtEntry::GicInterruptTranslationService(_) => {
-                    unimplemented!();
+                    return self.parse_gic_model_in(allocator);
+                }
+
+                MadtEntry::RiscVInterruptController(_) |
+                MadtEntry::Imsic(_) |
+                MadtEntry::Aplic(_) |
+                MadtEntry::Plic(_) => {
+                    return self.parse_riscv_model_in(allocator);
                 }
 
                 MadtEntry::MultiprocessorWakeup(_) => ()
@@ -114,28 +130,30 @@ impl Madt {
                 ProcessorState,
             },
             AcpiError,
+            ManagedVec,
         };
-        use alloc::vec::Vec;
 
         let mut local_apic_address = self.local_apic_address as u64;
 
         let mut boot_processor = None;
-        let mut io_apics = Vec::new_in(allocator.clone());
-        let mut interrupt_source_overrides = Vec::new_in(allocator.clone());
-        let mut nmi_sources = Vec::new_in(allocator.clone());
-        let mut local_apic_nmi_lines = Vec::new_in(allocator.clone());
-        let mut application_processors = Vec::new_in(allocator);
+        let mut io_apics = ManagedVec::new_in(allocator.clone());
+        let mut interrupt_source_overrides = ManagedVec::new_in(allocator.clone());
+        let mut nmi_sources = ManagedVec::new_in(allocator.clone());
+        let mut local_apic_nmi_lines = ManagedVec::new_in(allocator.clone());
+        let mut application_processors = ManagedVec::new_in(allocator);
 
         // Do a pass over the entries so we know how much space we should reserve in the vectors
         for entry in self.entries() {
             match entry {
                 MadtEntry::LocalApicAddressOverride(entry) => local_apic_address = entry.local_apic_address,
 
-                MadtEntry::IoApic(entry) => io_apics.push(IoApic {
-                    id: entry.io_apic_id,
-                    address: entry.io_apic_address,
-                    global_system_interrupt_base: entry.global_system_interrupt_base,
-                }),
+                MadtEntry::IoApic(entry) => io_apics
+                    .try_push(IoApic {
+                        id: entry.io_apic_id,
+                        address: entry.io_apic_address,
+                        global_system_interrupt_base: entry.global_system_interrupt_base,
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
                 MadtEntry::InterruptSourceOverride(entry) => {
                     if entry.bus != 0 {
                         return Err(AcpiError::InvalidMadt(MadtError::InterruptOverrideEntryHasInvalidBus));
@@ -143,55 +161,80 @@ impl Madt {
 
                     let (polarity, trigger_mode) = parse_mps_inti_flags(entry.flags)?;
 
-                    interrupt_source_overrides.push(InterruptSourceOverride {
-                        isa_source: entry.irq,
-                        global_system_interrupt: entry.global_system_interrupt,
-                        polarity,
-                        trigger_mode,
-                    });
+                    interrupt_source_overrides
+                        .try_push(InterruptSourceOverride {
+                            isa_source: entry.irq,
+                            global_system_interrupt: entry.global_system_interrupt,
+                            polarity,
+                            trigger_mode,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
                 }
 
                 MadtEntry::NmiSource(entry) => {
                     let (polarity, trigger_mode) = parse_mps_inti_flags(entry.flags)?;
 
-                    nmi_sources.push(NmiSource {
-                        global_system_interrupt: entry.global_system_interrupt,
-                        polarity,
-                        trigger_mode,
-                    });
+                    nmi_sources
+                        .try_push(NmiSource {
+                            global_system_interrupt: entry.global_system_interrupt,
+                            polarity,
+                            trigger_mode,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
                 }
 
                 MadtEntry::LocalApicNmi(entry) => {
-                    local_apic_nmi_lines.push(NmiLine {
-                        processor: if entry.processor_id == 0xff {
-                            NmiProcessor::All
-                        } else {
-                            NmiProcessor::ProcessorUid(entry.processor_id as u32)
-                        },
-                        line: match entry.nmi_line {
-                            0 => LocalInterruptLine::Lint0,
-                            1 => LocalInterruptLine::Lint1,
-                            _ => return Err(AcpiError::InvalidMadt(MadtError::InvalidLocalNmiLine)),
-                        },
-                    });
+                    let (polarity, trigger_mode) = parse_mps_inti_flags(entry.flags)?;
+
+                    local_apic_nmi_lines
+                        .try_push(NmiLine {
+                            processor: if entry.processor_id == 0xff {
+                                NmiProcessor::All
+                            } else {
+                                NmiProcessor::ProcessorUid(entry.processor_id as u32)
+                            },
+                            line: match entry.nmi_line {
+                                0 => LocalInterruptLine::Lint0,
+                                1 => LocalInterruptLine::Lint1,
+                                _ => return Err(AcpiError::InvalidMadt(MadtError::InvalidLocalNmiLine)),
+                            },
+                            polarity,
+                            trigger_mode,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
                 }
 
                 MadtEntry::X2ApicNmi(entry) => {
-                    local_apic_nmi_lines.push(NmiLine {
-                        processor: if entry.processor_uid == 0xffffffff {
-                            NmiProcessor::All
-                        } else {
-                            NmiProcessor::ProcessorUid(entry.processor_uid)
-                        },
-                        line: match entry.nmi_line {
-                            0 => LocalInterruptLine::Lint0,
-                            1 => LocalInterruptLine::Lint1,
-                            _ => return Err(AcpiError::InvalidMadt(MadtError::InvalidLocalNmiLine)),
-                        },
-                    });
+                    let (polarity, trigger_mode) = parse_mps_inti_flags(entry.flags)?;
+
+                    local_apic_nmi_lines
+                        .try_push(NmiLine {
+                            processor: if entry.processor_uid == 0xffffffff {
+                                NmiProcessor::All
+                            } else {
+                                NmiProcessor::ProcessorUid(entry.processor_uid)
+                            },
+                            line: match entry.nmi_line {
+                                0 => LocalInterruptLine::Lint0,
+                                1 => LocalInterruptLine::Lint1,
+                                _ => return Err(AcpiError::InvalidMadt(MadtError::InvalidLocalNmiLine)),
+                            },
+                            polarity,
+                            trigger_mode,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
                 }
 
                 MadtEntry::LocalApic(entry) => {
+                    /*
+                     * Processors with an APIC ID of `0xff` can't be addressed by the xAPIC, and so must be
+                     * described by a Local X2APIC entry instead. Some firmware describes them with both, so we
+                     * skip the Local APIC entry to avoid reporting the processor twice.
+                     */
+                    if entry.apic_id == 0xff {
+                        continue;
+                    }
+
                     /*
                      * The first processor is the BSP. Subsequent ones are APs. If we haven't found
                      * the BSP yet, this must be it.
@@ -213,7 +256,7 @@ impl Madt {
                     };
 
                     if is_ap {
-                        application_processors.push(processor);
+                        application_processors.try_push(processor).map_err(|_| AcpiError::AllocError)?;
                     } else {
                         boot_processor = Some(processor);
                     }
@@ -237,7 +280,7 @@ impl Madt {
                     };
 
                     if is_ap {
-                        application_processors.push(processor);
+                        application_processors.try_push(processor).map_err(|_| AcpiError::AllocError)?;
                     } else {
                         boot_processor = Some(processor);
                     }
@@ -266,6 +309,233 @@ impl Madt {
         ))
     }
 
+    #[cfg(feature = "allocator_api")]
+    fn parse_gic_model_in<A>(&self, allocator: A) -> AcpiResult<(InterruptModel<A>, Option<ProcessorInfo<A>>)>
+    where
+        A: core::alloc::Allocator + Clone,
+    {
+        use crate::{
+            platform::interrupt::{
+                Gic,
+                GicCpuInterface,
+                GicDistributor,
+                GicInterruptTranslationService,
+                GicMsiFrame,
+                GicRedistributor,
+                GicVersion,
+            },
+            AcpiError,
+            ManagedVec,
+        };
+
+        /*
+         * The GICC entry has grown over time, so we can only read the fields that are actually present in each
+         * entry. These are the lengths of the entry when each group of fields was added.
+         */
+        const GICC_LENGTH_ACPI_5_1: u8 = 76;
+        const GICC_LENGTH_ACPI_6_0: u8 = 80;
+
+        let trigger_mode =
+            |edge_triggered: bool| if edge_triggered { TriggerMode::Edge } else { TriggerMode::Level };
+        let non_zero_u32 = |value: u32| if value == 0 { None } else { Some(value) };
+
+        let mut distributor = None;
+        let mut cpu_interfaces = ManagedVec::new_in(allocator.clone());
+        let mut redistributors = ManagedVec::new_in(allocator.clone());
+        let mut msi_frames = ManagedVec::new_in(allocator.clone());
+        let mut interrupt_translation_services = ManagedVec::new_in(allocator);
+
+        for entry in self.entries() {
+            match entry {
+                MadtEntry::Gicd(entry) => {
+                    distributor = Some(GicDistributor {
+                        id: entry.gic_id,
+                        base_address: entry.physical_base_address,
+                        system_vector_base: entry.system_vector_base,
+                        version: match entry.gic_version {
+                            0x00 => GicVersion::FromHardware,
+                            0x01 => GicVersion::V1,
+                            0x02 => GicVersion::V2,
+                            0x03 => GicVersion::V3,
+                            0x04 => GicVersion::V4,
+                            other => GicVersion::Reserved(other),
+                        },
+                    });
+                }
+
+                MadtEntry::Gicc(entry) => {
+                    let length = entry.header.length;
+                    let flags = entry.flags;
+                    let (gicr_base_address, mpidr) = if length >= GICC_LENGTH_ACPI_5_1 {
+                        (
+                            if entry.gicr_base_address == 0 { None } else { Some(entry.gicr_base_address) },
+                            entry.mpidr,
+                        )
+                    } else {
+                        (None, 0)
+                    };
+                    let processor_power_efficiency_class =
+                        if length >= GICC_LENGTH_ACPI_6_0 { entry.processor_power_efficiency_class } else { 0 };
+                    // The SPE overflow interrupt replaced a reserved field in ACPI 6.3 (MADT revision 5)
+                    let spe_overflow_interrupt = if length >= GICC_LENGTH_ACPI_6_0 && self.header.revision >= 5 {
+                        match entry.spe_overflow_interrupt {
+                            0 => None,
+                            gsiv => Some(gsiv),
+                        }
+                    } else {
+                        None
+                    };
+
+                    cpu_interfaces
+                        .try_push(GicCpuInterface {
+                            processor_uid: entry.processor_uid,
+                            cpu_interface_number: entry.cpu_interface_number,
+                            mpidr,
+                            is_enabled: flags.get_bit(0),
+                            is_online_capable: flags.get_bit(3),
+                            gic_registers_address: entry.gic_registers_address,
+                            gic_virtual_registers_address: entry.gic_virtual_registers_address,
+                            gic_hypervisor_registers_address: entry.gic_hypervisor_registers_address,
+                            gicr_base_address,
+                            performance_interrupt: non_zero_u32(entry.performance_interrupt_gsiv),
+                            performance_interrupt_trigger_mode: trigger_mode(flags.get_bit(1)),
+                            vgic_maintenance_interrupt: non_zero_u32(entry.vgic_maintenance_interrupt),
+                            vgic_maintenance_interrupt_trigger_mode: trigger_mode(flags.get_bit(2)),
+                            parking_protocol_version: entry.parking_protocol_version,
+                            parked_address: entry.parked_address,
+                            spe_overflow_interrupt,
+                            processor_power_efficiency_class,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
+                }
+
+                MadtEntry::GicRedistributor(entry) => redistributors
+                    .try_push(GicRedistributor {
+                        discovery_range_base_address: entry.discovery_range_base_address,
+                        discovery_range_length: entry.discovery_range_length,
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
+
+                MadtEntry::GicMsiFrame(entry) => msi_frames
+                    .try_push(GicMsiFrame {
+                        id: entry.frame_id,
+                        base_address: entry.physical_base_address,
+                        spi_range: if { entry.flags }.get_bit(0) {
+                            Some((entry.spi_base, entry.spi_count))
+                        } else {
+                            None
+                        },
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
+
+                MadtEntry::GicInterruptTranslationService(entry) => interrupt_translation_services
+                    .try_push(GicInterruptTranslationService {
+                        id: entry.id,
+                        base_address: entry.physical_base_address,
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
+
+                _ => {}
+            }
+        }
+
+        let distributor = distributor.ok_or(AcpiError::InvalidMadt(MadtError::NoGicDistributor))?;
+
+        /*
+         * The per-processor information for GIC platforms is held in the CPU interfaces, rather than in a
+         * `ProcessorInfo`, because there is no way to tell which processor is the boot processor from the MADT.
+         */
+        Ok((
+            InterruptModel::Gic(Gic::new(
+                distributor,
+                cpu_interfaces,
+                redistributors,
+                msi_frames,
+                interrupt_translation_services,
+            )),
+            None,
+        ))
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn parse_riscv_model_in<A>(&self, allocator: A) -> AcpiResult<(InterruptModel<A>, Option<ProcessorInfo<A>>)>
+    where
+        A: core::alloc::Allocator + Clone,
+    {
+        use crate::{
+            platform::interrupt::{Aplic, Imsic, Plic, RiscV, RiscVHart},
+            AcpiError,
+            ManagedVec,
+        };
+
+        let mut imsic = None;
+        let mut harts = ManagedVec::new_in(allocator.clone());
+        let mut aplics = ManagedVec::new_in(allocator.clone());
+        let mut plics = ManagedVec::new_in(allocator);
+
+        for entry in self.entries() {
+            match entry {
+                MadtEntry::RiscVInterruptController(entry) => {
+                    let flags = entry.flags;
+                    harts
+                        .try_push(RiscVHart {
+                            hart_id: entry.hart_id,
+                            processor_uid: entry.processor_uid,
+                            is_enabled: flags.get_bit(0),
+                            is_online_capable: flags.get_bit(1),
+                            external_interrupt_controller_id: entry.external_interrupt_controller_id,
+                            imsic_base_address: if entry.imsic_base_address == 0 {
+                                None
+                            } else {
+                                Some(entry.imsic_base_address)
+                            },
+                            imsic_size: entry.imsic_size,
+                        })
+                        .map_err(|_| AcpiError::AllocError)?;
+                }
+
+                MadtEntry::Imsic(entry) => {
+                    imsic = Some(Imsic {
+                        num_supervisor_interrupt_identities: entry.num_supervisor_interrupt_identities,
+                        num_guest_interrupt_identities: entry.num_guest_interrupt_identities,
+                        guest_index_bits: entry.guest_index_bits,
+                        hart_index_bits: entry.hart_index_bits,
+                        group_index_bits: entry.group_index_bits,
+                        group_index_shift: entry.group_index_shift,
+                    })
+                }
+
+                MadtEntry::Aplic(entry) => aplics
+                    .try_push(Aplic {
+                        id: entry.aplic_id,
+                        hardware_id: entry.hardware_id,
+                        num_idcs: entry.num_idcs,
+                        num_interrupt_sources: entry.num_interrupt_sources,
+                        global_system_interrupt_base: entry.global_system_interrupt_base,
+                        base_address: entry.aplic_address,
+                        size: entry.aplic_size,
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
+
+                MadtEntry::Plic(entry) => plics
+                    .try_push(Plic {
+                        id: entry.plic_id,
+                        hardware_id: entry.hardware_id,
+                        num_interrupt_sources: entry.num_interrupt_sources,
+                        max_priority: entry.max_priority,
+                        global_system_interrupt_base: entry.global_system_interrupt_base,
+                        base_address: entry.plic_address,
+                        size: entry.plic_size,
+                    })
+                    .map_err(|_| AcpiError::AllocError)?,
+
+                _ => {}
+            }
+        }
+
+        Ok((InterruptModel::RiscV(RiscV::new(harts, imsic, aplics, plics)), None))
+    }
+
     pub fn entries(&self) -> MadtEntryIter {
         MadtEntryIter {
             pointer: unsafe { (self as *const Madt as *const u8).add(mem::size_of::<Madt>()) },
@@ -277,6 +547,78 @@ impl Madt {
     pub fn supports_8259(&self) -> bool {
         { self.flags }.get_bit(0)
     }
+
+    /// Get the multiprocessor wakeup mailbox, if the platform has one. This is used to start the application
+    /// processors on platforms that can't use INIT-SIPI-SIPI, such as confidential-computing guests.
+    pub fn multiprocessor_wakeup_mailbox(&self) -> Option<MultiprocessorWakeupMailbox> {
+        self.entries().find_map(|entry| match entry {
+            MadtEntry::MultiprocessorWakeup(entry) => Some(MultiprocessorWakeupMailbox {
+                version: entry.mailbox_version,
+                address: entry.mailbox_address,
+            }),
+            _ => None,
+        })
+    }
+}
+
+/// Describes the mailbox used to start application processors with the multiprocessor wakeup protocol. The
+/// firmware holds all of the application processors in a loop, waiting for a command in the mailbox; the OS
+/// starts a processor by writing its APIC ID and a wakeup vector, with [`MultiprocessorWakeupMailbox::wake`].
+#[derive(Clone, Copy, Debug)]
+pub struct MultiprocessorWakeupMailbox {
+    /// The version of the mailbox structure. Only version `0` is currently defined.
+    pub version: u16,
+    /// The physical address of the mailbox. This is 4KiB-aligned.
+    pub address: u64,
+}
+
+impl MultiprocessorWakeupMailbox {
+    const COMMAND_NOOP: u16 = 0;
+    const COMMAND_WAKEUP: u16 = 1;
+
+    /// Wake the processor with the given APIC ID, which will jump to `wakeup_vector` (a physical address) in
+    /// 64-bit mode, with paging identity-mapping the physical memory it needs. This waits until the processor
+    /// has acknowledged the command, after which the mailbox can be used to wake another processor.
+    ///
+    /// ### Safety
+    /// `wakeup_vector` must point to code that is ready to be run by the processor, and the mailbox must not be
+    /// in use by another processor at the same time.
+    pub unsafe fn wake<H>(&self, handler: &H, apic_id: u32, wakeup_vector: u64)
+    where
+        H: AcpiHandler,
+    {
+        let mapping = unsafe {
+            handler.map_physical_region::<MultiprocessorWakeupMailboxRegion>(
+                self.address as usize,
+                mem::size_of::<MultiprocessorWakeupMailboxRegion>(),
+            )
+        };
+        let mailbox = mapping.virtual_start().as_ptr();
+
+        unsafe {
+            ptr::write_volatile(ptr::addr_of_mut!((*mailbox).apic_id), apic_id);
+            ptr::write_volatile(ptr::addr_of_mut!((*mailbox).wakeup_vector), wakeup_vector);
+
+            // The command must be written last, as the processor may start as soon as it sees it
+            atomic::fence(Ordering::Release);
+            ptr::write_volatile(ptr::addr_of_mut!((*mailbox).command), Self::COMMAND_WAKEUP);
+
+            // The firmware clears the command once the processor has read the mailbox
+            while ptr::read_volatile(ptr::addr_of!((*mailbox).command)) != Self::COMMAND_NOOP {
+                hint::spin_loop();
+            }
+        }
+    }
+}
+
+#[repr(C)]
+struct MultiprocessorWakeupMailboxRegion {
+    command: u16,
+    _reserved: u16,
+    apic_id: u32,
+    wakeup_vector: u64,
+    _reserved_for_os: [u8; 2032],
+    _reserved_for_firmware: [u8; 2048],
 }
 
 #[derive(Debug)]
@@ -309,6 +651,10 @@ pub enum MadtEntry<'a> {
     GicRedistributor(&'a GicRedistributorEntry),
     GicInterruptTranslationService(&'a GicInterruptTranslationServiceEntry),
     MultiprocessorWakeup(&'a MultiprocessorWakeupEntry),
+    RiscVInterruptController(&'a RiscVInterruptControllerEntry),
+    Imsic(&'a ImsicEntry),
+    Aplic(&'a AplicEntry),
+    Plic(&'a PlicEntry),
 }
 
 impl<'a> Iterator for MadtEntryIter<'a> {
@@ -340,7 +686,7 @@ impl<'a> Iterator for MadtEntryIter<'a> {
                          * These entry types are reserved by the ACPI standard. We should skip them
                          * if they appear in a real MADT.
                          */
-                        0x11..=0x7f => {}
+                        0x11..=0x17 | 0x1c..=0x7f => {}
 
                         /*
                          * These entry types are reserved for OEM use. Atm, we just skip them too.
@@ -371,7 +717,11 @@ impl<'a> Iterator for MadtEntryIter<'a> {
                 (0xd => MadtEntry::GicMsiFrame as GicMsiFrameEntry),
                 (0xe => MadtEntry::GicRedistributor as GicRedistributorEntry),
                 (0xf => MadtEntry::GicInterruptTranslationService as GicInterruptTranslationServiceEntry),
-                (0x10 => MadtEntry::MultiprocessorWakeup as MultiprocessorWakeupEntry)
+                (0x10 => MadtEntry::MultiprocessorWakeup as MultiprocessorWakeupEntry),
+                (0x18 => MadtEntry::RiscVInterruptController as RiscVInterruptControllerEntry),
+                (0x19 => MadtEntry::Imsic as ImsicEntry),
+                (0x1a => MadtEntry::Aplic as AplicEntry),
+                (0x1b => MadtEntry::Plic as PlicEntry)
             );
         }
 
@@ -386,6 +736,13 @@ pub struct EntryHeader {
     pub length: u8,
 }
 
+impl EntryHeader {
+    /// The header of an entry of type `entry_type`, which is represented by `T`.
+    pub(crate) fn new<T>(entry_type: u8) -> EntryHeader {
+        EntryHeader { entry_type, length: mem::size_of::<T>() as u8 }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct LocalApicEntry {
@@ -395,6 +752,12 @@ pub struct LocalApicEntry {
     pub flags: u32,
 }
 
+impl LocalApicEntry {
+    pub(crate) fn new(processor_id: u8, apic_id: u8, flags: u32) -> LocalApicEntry {
+        LocalApicEntry { header: EntryHeader::new::<LocalApicEntry>(0x0), processor_id, apic_id, flags }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct IoApicEntry {
@@ -405,6 +768,18 @@ pub struct IoApicEntry {
     pub global_system_interrupt_base: u32,
 }
 
+impl IoApicEntry {
+    pub(crate) fn new(io_apic_id: u8, io_apic_address: u32, global_system_interrupt_base: u32) -> IoApicEntry {
+        IoApicEntry {
+            header: EntryHeader::new::<IoApicEntry>(0x1),
+            io_apic_id,
+            _reserved: 0,
+            io_apic_address,
+            global_system_interrupt_base,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct InterruptSourceOverrideEntry {
@@ -415,6 +790,18 @@ pub struct InterruptSourceOverrideEntry {
     pub flags: u16,
 }
 
+impl InterruptSourceOverrideEntry {
+    pub(crate) fn new(irq: u8, global_system_interrupt: u32, flags: u16) -> InterruptSourceOverrideEntry {
+        InterruptSourceOverrideEntry {
+            header: EntryHeader::new::<InterruptSourceOverrideEntry>(0x2),
+            bus: 0,
+            irq,
+            global_system_interrupt,
+            flags,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct NmiSourceEntry {
@@ -423,6 +810,12 @@ pub struct NmiSourceEntry {
     pub global_system_interrupt: u32,
 }
 
+impl NmiSourceEntry {
+    pub(crate) fn new(flags: u16, global_system_interrupt: u32) -> NmiSourceEntry {
+        NmiSourceEntry { header: EntryHeader::new::<NmiSourceEntry>(0x3), flags, global_system_interrupt }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct LocalApicNmiEntry {
@@ -432,6 +825,12 @@ pub struct LocalApicNmiEntry {
     pub nmi_line: u8, // Describes which LINTn is the NMI connected to
 }
 
+impl LocalApicNmiEntry {
+    pub(crate) fn new(processor_id: u8, flags: u16, nmi_line: u8) -> LocalApicNmiEntry {
+        LocalApicNmiEntry { header: EntryHeader::new::<LocalApicNmiEntry>(0x4), processor_id, flags, nmi_line }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct LocalApicAddressOverrideEntry {
@@ -493,6 +892,18 @@ pub struct LocalX2ApicEntry {
     pub processor_uid: u32,
 }
 
+impl LocalX2ApicEntry {
+    pub(crate) fn new(x2apic_id: u32, flags: u32, processor_uid: u32) -> LocalX2ApicEntry {
+        LocalX2ApicEntry {
+            header: EntryHeader::new::<LocalX2ApicEntry>(0x9),
+            _reserved: 0,
+            x2apic_id,
+            flags,
+            processor_uid,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C, packed)]
 pub struct X2ApicNmiEntry {
@@ -503,6 +914,18 @@ pub struct X2ApicNmiEntry {
     _reserved: [u8; 3],
 }
 
+impl X2ApicNmiEntry {
+    pub(crate) fn new(flags: u16, processor_uid: u32, nmi_line: u8) -> X2ApicNmiEntry {
+        X2ApicNmiEntry {
+            header: EntryHeader::new::<X2ApicNmiEntry>(0xa),
+            flags,
+            processor_uid,
+            nmi_line,
+            _reserved: [0; 3],
+        }
+    }
+}
+
 /// This field will appear for ARM processors that support ACPI and use the Generic Interrupt
 /// Controller. In the GICC interrupt model, each logical process has a Processor Device object in
 /// the namespace, and uses this structure to convey its GIC information.
@@ -593,6 +1016,72 @@ pub struct MultiprocessorWakeupEntry {
     pub mailbox_address: u64,
 }
 
+/// Describes the RISC-V Hart Local Interrupt Controller (RINTC) of a hart. In the RISC-V interrupt model,
+/// each hart has a Processor Device object in the namespace, and uses this structure to convey its interrupt
+/// controller information.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RiscVInterruptControllerEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    _reserved: u8,
+    pub flags: u32,
+    pub hart_id: u64,
+    pub processor_uid: u32,
+    pub external_interrupt_controller_id: u32,
+    pub imsic_base_address: u64,
+    pub imsic_size: u32,
+}
+
+/// Describes the properties shared by the Incoming MSI Controllers (IMSICs) of all of the harts. The base
+/// address of each hart's IMSIC is found in its RINTC entry.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ImsicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    _reserved: u8,
+    pub flags: u32,
+    pub num_supervisor_interrupt_identities: u16,
+    pub num_guest_interrupt_identities: u16,
+    pub guest_index_bits: u8,
+    pub hart_index_bits: u8,
+    pub group_index_bits: u8,
+    pub group_index_shift: u8,
+}
+
+/// Describes an Advanced Platform-Level Interrupt Controller (APLIC).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AplicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    pub aplic_id: u8,
+    pub flags: u32,
+    pub hardware_id: [u8; 8],
+    pub num_idcs: u16,
+    pub num_interrupt_sources: u16,
+    pub global_system_interrupt_base: u32,
+    pub aplic_address: u64,
+    pub aplic_size: u32,
+}
+
+/// Describes a Platform-Level Interrupt Controller (PLIC).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PlicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    pub plic_id: u8,
+    pub hardware_id: [u8; 8],
+    pub num_interrupt_sources: u16,
+    pub max_priority: u16,
+    pub flags: u32,
+    pub plic_size: u32,
+    pub plic_address: u64,
+    pub global_system_interrupt_base: u32,
+}
+
 #[cfg(feature = "allocator_api")]
 fn parse_mps_inti_flags(flags: u16) -> crate::AcpiResult<(Polarity, TriggerMode)> {
     let polarity = match flags.get_bits(0..2) {
@@ -0,0 +1,96 @@
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    fmt,
+    mem,
+    ptr::{self, NonNull},
+};
+
+/// A single object in memory from an allocator, like `Box` but for caller-provided allocators, and reporting
+/// allocation failure instead of aborting. The object is dropped and its memory deallocated when the box is
+/// dropped.
+pub struct ManagedBox<T, A>
+where
+    A: Allocator,
+{
+    memory: NonNull<T>,
+    allocator: A,
+}
+
+// Safety: If `T` is `Send`, this type can be as well.
+unsafe impl<T, A> Send for ManagedBox<T, A>
+where
+    T: Send,
+    A: Allocator,
+{
+}
+
+impl<T, A> ManagedBox<T, A>
+where
+    A: Allocator,
+{
+    pub fn try_new_in(value: T, allocator: A) -> Result<Self, AllocError> {
+        let memory: NonNull<T> = allocator.allocate(Layout::new::<T>())?.as_non_null_ptr().cast();
+
+        // Safety: The memory was just allocated with the layout of a `T`, and is uninitialized.
+        unsafe { memory.as_ptr().write(value) };
+        Ok(Self { memory, allocator })
+    }
+
+    /// Move the object out of the box, deallocating its memory.
+    pub fn into_inner(self) -> T {
+        let boxed = mem::ManuallyDrop::new(self);
+
+        // Safety: The object is initialized, and the box is never used or dropped again, so it's only read once.
+        let value = unsafe { boxed.memory.as_ptr().read() };
+        // Safety: As above, the allocator is moved out of a box that's never dropped.
+        let allocator = unsafe { ptr::read(&boxed.allocator) };
+        // Safety: `memory` was allocated by `allocator`, with the layout of a `T`.
+        unsafe { allocator.deallocate(boxed.memory.cast(), Layout::new::<T>()) };
+        value
+    }
+}
+
+impl<T, A> Drop for ManagedBox<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        // Safety: The object is initialized, and `memory` was allocated by `allocator` with the layout of a `T`.
+        unsafe {
+            ptr::drop_in_place(self.memory.as_ptr());
+            self.allocator.deallocate(self.memory.cast(), Layout::new::<T>());
+        }
+    }
+}
+
+impl<T, A> core::ops::Deref for ManagedBox<T, A>
+where
+    A: Allocator,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The object is always initialized.
+        unsafe { self.memory.as_ref() }
+    }
+}
+
+impl<T, A> core::ops::DerefMut for ManagedBox<T, A>
+where
+    A: Allocator,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: The object is always initialized.
+        unsafe { self.memory.as_mut() }
+    }
+}
+
+impl<T, A> fmt::Debug for ManagedBox<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -36,6 +36,67 @@ where
     }
 }
 
+impl<T, A> ManagedSlice<T, A>
+where
+    A: Allocator,
+{
+    /// ### Safety: `memory` must be allocated by `allocator`, with the layout of an array of its length, and hold
+    /// initialized elements.
+    pub(crate) unsafe fn from_raw_parts(memory: NonNull<[T]>, allocator: A) -> Self {
+        Self { memory, allocator }
+    }
+
+    /// Allocate a slice in `allocator` holding clones of `elements`.
+    pub fn from_slice_in(elements: &[T], allocator: A) -> Result<Self, AllocError>
+    where
+        T: Clone,
+    {
+        Ok(Self::new_uninit_in(elements.len(), allocator)?.init_with(|i| elements[i].clone()))
+    }
+
+    /// Clone the slice into `allocator`, which can be a different allocator to the one it's in (e.g. to keep the
+    /// results of parsing a table after the allocator used to parse it has been reset).
+    pub fn try_clone_in<B>(&self, allocator: B) -> Result<ManagedSlice<T, B>, AllocError>
+    where
+        T: Clone,
+        B: Allocator,
+    {
+        ManagedSlice::from_slice_in(self, allocator)
+    }
+}
+
+impl<T, A> ManagedSlice<MaybeUninit<T>, A>
+where
+    A: Allocator,
+{
+    /// Initialize each element with the result of calling `f` with its index, producing a slice of initialized
+    /// elements.
+    pub fn init_with<F>(mut self, mut f: F) -> ManagedSlice<T, A>
+    where
+        F: FnMut(usize) -> T,
+    {
+        for (i, element) in self.iter_mut().enumerate() {
+            element.write(f(i));
+        }
+
+        // Safety: Every element has just been initialized.
+        unsafe { self.assume_init() }
+    }
+
+    /// Convert the slice to one of initialized elements, once it has been filled in some other way than with
+    /// [`ManagedSlice::init_with`] (e.g. by copying a table's entries into it).
+    ///
+    /// ### Safety
+    /// Every element of the slice must have been initialized.
+    pub unsafe fn assume_init(self) -> ManagedSlice<T, A> {
+        let slice = core::mem::ManuallyDrop::new(self);
+        let memory = NonNull::slice_from_raw_parts(slice.memory.as_non_null_ptr().cast(), slice.memory.len());
+        // Safety: The allocator is moved out of the slice, which is never used or dropped again.
+        let allocator = unsafe { core::ptr::read(&slice.allocator) };
+        ManagedSlice { memory, allocator }
+    }
+}
+
 impl<T: Copy, A> ManagedSlice<T, A>
 where
     A: Allocator,
@@ -58,6 +119,9 @@ where
     A: Allocator,
 {
     fn drop(&mut self) {
+        // Safety: Type always initializes memory to a valid T, and the elements are never used again.
+        unsafe { core::ptr::drop_in_place(self.memory.as_ptr()) };
+
         let ptr = self.memory.as_non_null_ptr().cast();
         let layout = Layout::array::<T>(self.len()).unwrap();
 
@@ -0,0 +1,129 @@
+use crate::ManagedVec;
+use core::{
+    alloc::{AllocError, Allocator},
+    fmt,
+};
+
+/// A growable UTF-8 string in memory from an allocator, like `String` but for caller-provided allocators, and
+/// reporting allocation failure instead of aborting.
+pub struct ManagedString<A>
+where
+    A: Allocator,
+{
+    bytes: ManagedVec<u8, A>,
+}
+
+impl<A> ManagedString<A>
+where
+    A: Allocator,
+{
+    /// Create an empty string. Nothing is allocated until something is pushed.
+    pub fn new_in(allocator: A) -> Self {
+        Self { bytes: ManagedVec::new_in(allocator) }
+    }
+
+    pub fn try_from_str_in(string: &str, allocator: A) -> Result<Self, AllocError> {
+        let mut managed = Self::new_in(allocator);
+        managed.try_push_str(string)?;
+        Ok(managed)
+    }
+
+    /// Copy the string held by an AML `String` value (e.g. a battery's model number, or a device's `_MLS`) into
+    /// a string in `allocator`.
+    #[cfg(feature = "aml")]
+    pub fn try_from_aml_in(value: &aml::AmlValue, allocator: A) -> crate::AcpiResult<Self> {
+        match value {
+            aml::AmlValue::String(string) => {
+                Self::try_from_str_in(string, allocator).map_err(|_| crate::AcpiError::AllocError)
+            }
+            other => Err(crate::AcpiError::Aml(aml::AmlError::IncompatibleValueConversion {
+                current: other.type_of(),
+                target: aml::value::AmlType::String,
+            })),
+        }
+    }
+
+    pub fn try_push_str(&mut self, string: &str) -> Result<(), AllocError> {
+        self.bytes.try_extend_from_slice(string.as_bytes())
+    }
+
+    pub fn try_push(&mut self, c: char) -> Result<(), AllocError> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Remove the last character of the string, and return it.
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.as_str().chars().next_back()?;
+        for _ in 0..c.len_utf8() {
+            self.bytes.pop();
+        }
+        Some(c)
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Safety: The bytes are only ever added from `str`s and `char`s, and only whole characters are removed,
+        // so they're always valid UTF-8.
+        unsafe { core::str::from_utf8_unchecked(&self.bytes) }
+    }
+}
+
+impl<A> core::ops::Deref for ManagedString<A>
+where
+    A: Allocator,
+{
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<A> PartialEq<str> for ManagedString<A>
+where
+    A: Allocator,
+{
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<A> PartialEq<&str> for ManagedString<A>
+where
+    A: Allocator,
+{
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Formatting into a `ManagedString` (e.g. with `write!`) fails with `fmt::Error` if it can't be grown.
+impl<A> fmt::Write for ManagedString<A>
+where
+    A: Allocator,
+{
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.try_push_str(string).map_err(|_| fmt::Error)
+    }
+}
+
+impl<A> fmt::Display for ManagedString<A>
+where
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<A> fmt::Debug for ManagedString<A>
+where
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
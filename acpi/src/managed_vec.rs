@@ -0,0 +1,217 @@
+use crate::ManagedSlice;
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    fmt,
+    mem,
+    ptr::{self, NonNull},
+};
+
+/// A growable vector in memory from an allocator, like [`ManagedSlice`] but for when the number of elements isn't
+/// known up front (e.g. when parsing the variable-length entries of a table). Unlike `Vec`, growing it reports
+/// allocation failure instead of aborting.
+pub struct ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    memory: NonNull<T>,
+    capacity: usize,
+    len: usize,
+    allocator: A,
+}
+
+// Safety: If `T` is `Send`, this type can be as well.
+unsafe impl<T, A> Send for ManagedVec<T, A>
+where
+    T: Send,
+    A: Allocator,
+{
+}
+
+impl<T, A> ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    /// Create an empty vector. Nothing is allocated until an element is pushed.
+    pub fn new_in(allocator: A) -> Self {
+        // Zero-sized types never need to allocate, so the vector can hold as many of them as can be counted
+        let capacity = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
+        Self { memory: NonNull::dangling(), capacity, len: 0, allocator }
+    }
+
+    pub fn with_capacity_in(capacity: usize, allocator: A) -> Result<Self, AllocError> {
+        let mut vec = Self::new_in(allocator);
+        vec.try_reserve(capacity)?;
+        Ok(vec)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Make sure there is space for at least `additional` more elements, growing the allocation if there isn't.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let required = self.len.checked_add(additional).ok_or(AllocError)?;
+        if required <= self.capacity {
+            return Ok(());
+        }
+
+        // Grow geometrically, so that pushing elements one at a time doesn't reallocate on every push
+        let capacity = required.max(self.capacity * 2).max(4);
+        let new_layout = Layout::array::<T>(capacity).map_err(|_| AllocError)?;
+        let memory = if self.capacity == 0 {
+            self.allocator.allocate(new_layout)?
+        } else {
+            let old_layout = Layout::array::<T>(self.capacity).unwrap();
+            // Safety: `memory` was allocated by `allocator`, with `old_layout`.
+            unsafe { self.allocator.grow(self.memory.cast(), old_layout, new_layout)? }
+        };
+
+        self.memory = memory.as_non_null_ptr().cast();
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// Add `value` to the end of the vector, growing it if it's full.
+    pub fn try_push(&mut self, value: T) -> Result<(), AllocError> {
+        self.try_reserve(1)?;
+
+        // Safety: There is space for at least one more element, and the element past the end is uninitialized.
+        unsafe { self.memory.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Add clones of `elements` to the end of the vector, growing it if there isn't space for them.
+    pub fn try_extend_from_slice(&mut self, elements: &[T]) -> Result<(), AllocError>
+    where
+        T: Clone,
+    {
+        self.try_reserve(elements.len())?;
+        for element in elements {
+            // Safety: Space for all of the elements has been reserved, and the elements past the end are
+            // uninitialized.
+            unsafe { self.memory.as_ptr().add(self.len).write(element.clone()) };
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        // Safety: The element was initialized, and is no longer part of the vector, so won't be read again.
+        Some(unsafe { self.memory.as_ptr().add(self.len).read() })
+    }
+
+    /// Drop all of the elements, keeping the allocation.
+    pub fn clear(&mut self) {
+        let elements = ptr::slice_from_raw_parts_mut(self.memory.as_ptr(), self.len);
+        self.len = 0;
+        // Safety: The elements were initialized, and are no longer part of the vector.
+        unsafe { ptr::drop_in_place(elements) };
+    }
+
+    /// Convert the vector into a [`ManagedSlice`] of its elements, shrinking the allocation to fit them.
+    pub fn into_managed_slice(self) -> Result<ManagedSlice<T, A>, AllocError> {
+        let mut vec = mem::ManuallyDrop::new(self);
+
+        let memory = match vec.shrink_to_len() {
+            Ok(memory) => memory,
+            Err(err) => {
+                // Safety: The vector hasn't been consumed, so it still needs to be dropped.
+                unsafe { mem::ManuallyDrop::drop(&mut vec) };
+                return Err(err);
+            }
+        };
+
+        // Safety: The allocator is moved out of the vector, which is never used or dropped again.
+        let allocator = unsafe { ptr::read(&vec.allocator) };
+        // Safety: The memory holds `len` initialized elements, and was allocated by `allocator` with the layout of
+        // an array of exactly that many.
+        Ok(unsafe { ManagedSlice::from_raw_parts(memory, allocator) })
+    }
+
+    /// Shrink the allocation so it holds exactly `len` elements, as a `ManagedSlice` expects. The vector is left
+    /// empty and without an allocation, as the returned memory now owns the elements.
+    fn shrink_to_len(&mut self) -> Result<NonNull<[T]>, AllocError> {
+        let layout = Layout::array::<T>(self.len).map_err(|_| AllocError)?;
+
+        let memory = if mem::size_of::<T>() == 0 || self.capacity == 0 {
+            self.allocator.allocate(layout)?.as_non_null_ptr().cast()
+        } else if self.capacity != self.len {
+            let old_layout = Layout::array::<T>(self.capacity).unwrap();
+            // Safety: `memory` was allocated by `allocator`, with `old_layout`.
+            unsafe { self.allocator.shrink(self.memory.cast(), old_layout, layout)? }.as_non_null_ptr().cast()
+        } else {
+            self.memory
+        };
+
+        let len = self.len;
+        self.memory = NonNull::dangling();
+        self.capacity = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
+        self.len = 0;
+        Ok(NonNull::slice_from_raw_parts(memory, len))
+    }
+}
+
+impl<T, A> Drop for ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.clear();
+
+        if mem::size_of::<T>() != 0 && self.capacity != 0 {
+            let layout = Layout::array::<T>(self.capacity).unwrap();
+            // Safety: `memory` was allocated by `allocator`, with `layout`.
+            unsafe { self.allocator.deallocate(self.memory.cast(), layout) };
+        }
+    }
+}
+
+impl<T, A> core::ops::Deref for ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The first `len` elements are always initialized.
+        unsafe { core::slice::from_raw_parts(self.memory.as_ptr(), self.len) }
+    }
+}
+
+impl<T, A> core::ops::DerefMut for ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: The first `len` elements are always initialized.
+        unsafe { core::slice::from_raw_parts_mut(self.memory.as_ptr(), self.len) }
+    }
+}
+
+impl<'a, T, A> IntoIterator for &'a ManagedVec<T, A>
+where
+    A: Allocator,
+{
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, A> fmt::Debug for ManagedVec<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     sdt::{SdtHeader, Signature},
+    AcpiError,
     AcpiTable,
 };
 use core::{mem, slice};
@@ -28,11 +29,9 @@ where
     {
         let mcfg = tables.find_table::<Mcfg>()?;
         let mcfg_entries = mcfg.entries();
-        let mut regions = crate::ManagedSlice::new_in(mcfg_entries.len(), McfgEntry::default(), allocator)
+        let regions = crate::ManagedSlice::from_slice_in(mcfg_entries, allocator)
             .map_err(|_| crate::AcpiError::AllocError)?;
 
-        regions.copy_from_slice(mcfg_entries);
-
         Ok(Self { regions })
     }
 
@@ -104,9 +103,21 @@ unsafe impl AcpiTable for Mcfg {
     fn header(&self) -> &SdtHeader {
         &self.header
     }
+
+    // A length that isn't a whole number of entries isn't reported, as it's common enough in real firmware that
+    // `entries` ignores the rest of the table (see rust-osdev/acpi#58)
+    fn find_content_problems(&self, report: &mut dyn FnMut(AcpiError)) {
+        if { self._reserved } != 0 || self.entries().iter().any(|entry| { entry._reserved } != 0) {
+            report(AcpiError::SdtReservedFieldSet(Self::SIGNATURE));
+        }
+    }
 }
 
 impl Mcfg {
+    pub(crate) fn new(header: SdtHeader) -> Mcfg {
+        Mcfg { header, _reserved: 0 }
+    }
+
     /// Returns a slice containing each of the entries in the MCFG table. Where possible, `PlatformInfo.interrupt_model` should
     /// be enumerated instead.
     pub fn entries(&self) -> &[McfgEntry] {
@@ -138,3 +149,14 @@ pub struct McfgEntry {
     pub bus_number_end: u8,
     _reserved: u32,
 }
+
+impl McfgEntry {
+    pub const fn new(
+        base_address: u64,
+        pci_segment_group: u16,
+        bus_number_start: u8,
+        bus_number_end: u8,
+    ) -> McfgEntry {
+        McfgEntry { base_address, pci_segment_group, bus_number_start, bus_number_end, _reserved: 0 }
+    }
+}
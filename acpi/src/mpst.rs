@@ -0,0 +1,304 @@
+use crate::{
+    pcct::{Pcct, PcctSubspace},
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, ptr, slice};
+
+/// Represents the Memory Power State Table (MPST), which describes the memory power nodes of the platform (ranges
+/// of memory that can be put into lower power states independently), the power states each of them supports,
+/// and the characteristics of those power states. The OS requests power state changes through a PCC subspace,
+/// which can be found with [`Mpst::communication_channel`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Mpst {
+    pub header: SdtHeader,
+    /// The index of the PCC subspace used to communicate with the platform.
+    pub communication_channel_id: u8,
+    _reserved0: [u8; 3],
+    pub memory_power_node_count: u16,
+    _reserved1: u16,
+    // Followed by the memory power nodes, and then the power state characteristics
+}
+
+/// ### Safety: Implementation properly represents a valid MPST.
+unsafe impl AcpiTable for Mpst {
+    const SIGNATURE: Signature = Signature::MPST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Mpst {
+    pub fn nodes(&self) -> MemoryPowerNodeIter<'_> {
+        MemoryPowerNodeIter {
+            pointer: unsafe { (self as *const Mpst as *const u8).add(mem::size_of::<Mpst>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Mpst>()),
+            remaining_nodes: self.memory_power_node_count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The characteristics of the power states supported by the memory power nodes. The
+    /// `power_state_information_index` of a node's [`MemoryPowerState`] is an index into this slice.
+    pub fn power_state_characteristics(&self) -> &[PowerStateCharacteristics] {
+        let mut nodes = self.nodes();
+        for _ in &mut nodes {}
+        if nodes.remaining_nodes != 0 {
+            // The node list is malformed, so we can't find the characteristics that follow it
+            return &[];
+        }
+
+        let characteristics_header = mem::size_of::<u16>() * 2;
+        if nodes.remaining_length < characteristics_header {
+            return &[];
+        }
+        let count = unsafe { ptr::read_unaligned(nodes.pointer as *const u16) } as usize;
+        let available =
+            (nodes.remaining_length - characteristics_header) / mem::size_of::<PowerStateCharacteristics>();
+
+        unsafe {
+            slice::from_raw_parts(
+                nodes.pointer.add(characteristics_header) as *const PowerStateCharacteristics,
+                usize::min(count, available),
+            )
+        }
+    }
+
+    /// Get the PCC subspace used to request power state changes, from the platform's PCCT.
+    pub fn communication_channel<'a>(&self, pcct: &'a Pcct) -> Option<PcctSubspace<'a>> {
+        pcct.subspace(self.communication_channel_id as usize)
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryPowerNodeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_nodes: u16,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for MemoryPowerNodeIter<'a> {
+    type Item = &'a MemoryPowerNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_nodes == 0 || self.remaining_length < mem::size_of::<MemoryPowerNode>() {
+            return None;
+        }
+
+        let node = unsafe { &*(self.pointer as *const MemoryPowerNode) };
+        let length = node.length as usize;
+        if length < node.required_length() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_nodes -= 1;
+        Some(node)
+    }
+}
+
+/// Describes a range of memory that can be moved between power states as a unit.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryPowerNode {
+    pub flags: u8,
+    _reserved: u8,
+    pub node_id: u16,
+    pub length: u32,
+    pub base_address: u64,
+    pub range_length: u64,
+    pub number_of_power_states: u32,
+    pub number_of_physical_components: u32,
+    // Followed by the power states, and then the IDs of the physical components
+}
+
+impl MemoryPowerNode {
+    pub fn is_enabled(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the power state of this node can be managed by the OS.
+    pub fn is_power_managed(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+
+    pub fn is_hot_pluggable(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+
+    pub fn power_states(&self) -> &[MemoryPowerState] {
+        unsafe {
+            let pointer = (self as *const Self as *const u8).add(mem::size_of::<Self>());
+            slice::from_raw_parts(pointer as *const MemoryPowerState, self.number_of_power_states as usize)
+        }
+    }
+
+    /// The IDs of the physical memory components (e.g. DIMMs) that make up this node.
+    pub fn physical_component_ids(&self) -> impl Iterator<Item = u16> + '_ {
+        let base = unsafe {
+            (self as *const Self as *const u8).add(
+                mem::size_of::<Self>() + self.number_of_power_states as usize * mem::size_of::<MemoryPowerState>(),
+            )
+        } as *const u16;
+
+        (0..self.number_of_physical_components as usize).map(move |i| unsafe { ptr::read_unaligned(base.add(i)) })
+    }
+
+    fn required_length(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.number_of_power_states as usize * mem::size_of::<MemoryPowerState>()
+            + self.number_of_physical_components as usize * mem::size_of::<u16>()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryPowerState {
+    /// The value used to request this power state through the communication channel.
+    pub power_state_value: u8,
+    /// The index of the [`PowerStateCharacteristics`] that describes this power state.
+    pub power_state_information_index: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PowerStateCharacteristics {
+    /// The structure ID in bits `0..6`, and the revision of the structure in bits `6..8`.
+    pub structure_id: u8,
+    pub flags: u8,
+    _reserved0: u16,
+    /// The average power consumed by a node in this state, in milliwatts.
+    pub average_power_consumed: u32,
+    /// The power saved by moving a node to this state, relative to the active state, in milliwatts.
+    pub power_saving: u32,
+    /// The latency of moving a node from this state back to the active state, in nanoseconds.
+    pub exit_latency: u64,
+    _reserved1: u64,
+}
+
+impl PowerStateCharacteristics {
+    /// If true, the contents of memory are preserved in this state.
+    pub fn preserves_memory_content(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the platform can move a node into this state by itself.
+    pub fn supports_autonomous_entry(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+
+    /// If true, the platform moves a node out of this state by itself when it is accessed.
+    pub fn supports_autonomous_exit(&self) -> bool {
+        self.flags.get_bit(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn node(node_id: u16, states: &[(u8, u8)], components: &[u16]) -> Vec<u8> {
+        let length = mem::size_of::<MemoryPowerNode>() + states.len() * 2 + components.len() * 2;
+        let mut bytes = bytes_of(&MemoryPowerNode {
+            flags: 0b011,
+            _reserved: 0,
+            node_id,
+            length: length as u32,
+            base_address: node_id as u64 * 0x4000_0000,
+            range_length: 0x4000_0000,
+            number_of_power_states: states.len() as u32,
+            number_of_physical_components: components.len() as u32,
+        });
+        for &(power_state_value, power_state_information_index) in states {
+            bytes.extend([power_state_value, power_state_information_index]);
+        }
+        for component in components {
+            bytes.extend(component.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn mpst(memory_power_node_count: u16, nodes: &[Vec<u8>], characteristics: &[u8]) -> Vec<u8> {
+        let mut body = body_of(&Mpst {
+            header: blank_header(),
+            communication_channel_id: 1,
+            _reserved0: [0; 3],
+            memory_power_node_count,
+            _reserved1: 0,
+        });
+        for node in nodes {
+            body.extend(node);
+        }
+        body.extend(characteristics);
+        table(b"MPST", 1, &body)
+    }
+
+    fn characteristics(count: u16, flags: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(count.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        for &flags in flags {
+            bytes.extend(bytes_of(&PowerStateCharacteristics {
+                structure_id: 1,
+                flags,
+                _reserved0: 0,
+                average_power_consumed: 100,
+                power_saving: 900,
+                exit_latency: 1000,
+                _reserved1: 0,
+            }));
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_nodes() {
+        let nodes = [node(0, &[(0, 0), (1, 1)], &[0x10, 0x11, 0x12]), node(1, &[(2, 0)], &[])];
+        let buffer = TableBuffer::<Mpst>::new(&mpst(2, &nodes, &characteristics(2, &[0b001, 0b110])));
+        let mpst = buffer.get();
+        assert!(mpst.validate().is_ok());
+
+        let nodes: Vec<_> = mpst.nodes().collect();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[0].is_enabled());
+        assert!(nodes[0].is_power_managed());
+        assert!(!nodes[0].is_hot_pluggable());
+        assert_eq!(nodes[0].power_states().len(), 2);
+        assert_eq!(nodes[0].power_states()[1].power_state_information_index, 1);
+        assert!(nodes[0].physical_component_ids().eq([0x10, 0x11, 0x12]));
+        assert_eq!({ nodes[1].base_address }, 0x4000_0000);
+        assert_eq!(nodes[1].physical_component_ids().count(), 0);
+
+        let characteristics = mpst.power_state_characteristics();
+        assert_eq!(characteristics.len(), 2);
+        assert!(characteristics[0].preserves_memory_content());
+        assert!(characteristics[1].supports_autonomous_entry());
+        assert!(characteristics[1].supports_autonomous_exit());
+    }
+
+    #[test]
+    fn test_truncated_nodes() {
+        // A node that is too short for its power states and components ends the list, and hides the
+        // characteristics after it
+        let mut short = node(0, &[(0, 0)], &[0x10]);
+        short[4..8].copy_from_slice(&(mem::size_of::<MemoryPowerNode>() as u32 + 2).to_le_bytes());
+        let buffer = TableBuffer::<Mpst>::new(&mpst(1, &[short], &characteristics(1, &[0])));
+        assert_eq!(buffer.get().nodes().count(), 0);
+        assert!(buffer.get().power_state_characteristics().is_empty());
+
+        // Only the characteristics that fit in the table are read
+        let mut characteristics = characteristics(2, &[0, 0]);
+        characteristics.pop();
+        let buffer = TableBuffer::<Mpst>::new(&mpst(1, &[node(0, &[], &[])], &characteristics));
+        assert_eq!(buffer.get().nodes().count(), 1);
+        assert_eq!(buffer.get().power_state_characteristics().len(), 1);
+    }
+}
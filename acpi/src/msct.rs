@@ -0,0 +1,157 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{marker::PhantomData, mem};
+
+/// Represents the Maximum System Characteristics Table (MSCT), which describes the maximum number of proximity
+/// domains, clock domains, and amount of physical address space the platform can ever have, including any
+/// resources that may be hot-added later.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Msct {
+    pub header: SdtHeader,
+    /// The offset of the first maximum proximity domain information structure from the start of the table.
+    pub proximity_domain_information_offset: u32,
+    max_proximity_domains: u32,
+    max_clock_domains: u32,
+    /// The highest physical address the platform can ever have.
+    pub max_physical_address: u64,
+}
+
+/// ### Safety: Implementation properly represents a valid MSCT.
+unsafe impl AcpiTable for Msct {
+    const SIGNATURE: Signature = Signature::MSCT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Msct {
+    /// The maximum number of proximity domains the platform can ever have. The table stores this minus one, which
+    /// is corrected for here.
+    pub fn max_proximity_domains(&self) -> u64 {
+        self.max_proximity_domains as u64 + 1
+    }
+
+    /// The maximum number of clock domains the platform can ever have. The table stores this minus one, which is
+    /// corrected for here.
+    pub fn max_clock_domains(&self) -> u64 {
+        self.max_clock_domains as u64 + 1
+    }
+
+    pub fn proximity_domains(&self) -> ProximityDomainInfoIter<'_> {
+        let offset = usize::max(self.proximity_domain_information_offset as usize, mem::size_of::<Msct>());
+
+        ProximityDomainInfoIter {
+            pointer: unsafe { (self as *const Msct as *const u8).add(offset) },
+            remaining_length: (self.header.length as usize).saturating_sub(offset),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the information structure that covers `proximity_domain`, if there is one.
+    pub fn proximity_domain_info(&self, proximity_domain: u32) -> Option<&ProximityDomainInfo> {
+        self.proximity_domains().find(|info| info.contains(proximity_domain))
+    }
+}
+
+#[derive(Debug)]
+pub struct ProximityDomainInfoIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for ProximityDomainInfoIter<'a> {
+    type Item = &'a ProximityDomainInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<ProximityDomainInfo>() {
+            return None;
+        }
+
+        let info = unsafe { &*(self.pointer as *const ProximityDomainInfo) };
+        let length = info.length as usize;
+        if length < mem::size_of::<ProximityDomainInfo>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        Some(info)
+    }
+}
+
+/// Describes the maximum capacities of a range of proximity domains, which all have the same characteristics.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ProximityDomainInfo {
+    pub revision: u8,
+    pub length: u8,
+    pub proximity_domain_range_low: u32,
+    pub proximity_domain_range_high: u32,
+    /// The maximum number of processors that can belong to each of the proximity domains.
+    pub max_processor_capacity: u32,
+    /// The maximum amount of memory, in bytes, that can belong to each of the proximity domains.
+    pub max_memory_capacity: u64,
+}
+
+impl ProximityDomainInfo {
+    pub fn contains(&self, proximity_domain: u32) -> bool {
+        (self.proximity_domain_range_low..=self.proximity_domain_range_high).contains(&proximity_domain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, TableBuffer};
+
+    fn info(low: u32, high: u32, max_processor_capacity: u32) -> ProximityDomainInfo {
+        ProximityDomainInfo {
+            revision: 1,
+            length: mem::size_of::<ProximityDomainInfo>() as u8,
+            proximity_domain_range_low: low,
+            proximity_domain_range_high: high,
+            max_processor_capacity,
+            max_memory_capacity: 0x10_0000_0000,
+        }
+    }
+
+    fn msct(infos: &[ProximityDomainInfo]) -> TableBuffer<Msct> {
+        let mut body = body_of(&Msct {
+            header: blank_header(),
+            proximity_domain_information_offset: mem::size_of::<Msct>() as u32,
+            max_proximity_domains: 3,
+            max_clock_domains: 0,
+            max_physical_address: 0xff_ffff_ffff,
+        });
+        for info in infos {
+            body.extend(bytes_of(info));
+        }
+        TableBuffer::new(&table(b"MSCT", 1, &body))
+    }
+
+    #[test]
+    fn test_proximity_domains() {
+        let buffer = msct(&[info(0, 1, 32), info(2, 3, 64)]);
+        let msct = buffer.get();
+        assert!(msct.validate().is_ok());
+        assert_eq!(msct.max_proximity_domains(), 4);
+        assert_eq!(msct.max_clock_domains(), 1);
+        assert_eq!(msct.proximity_domains().count(), 2);
+        assert_eq!({ msct.proximity_domain_info(3).unwrap().max_processor_capacity }, 64);
+        assert!(msct.proximity_domain_info(4).is_none());
+    }
+
+    #[test]
+    fn test_truncated_proximity_domain() {
+        // A structure that claims to be shorter than it is ends the list
+        let mut short = info(2, 3, 64);
+        short.length -= 1;
+        assert_eq!(msct(&[info(0, 1, 32), short]).get().proximity_domains().count(), 1);
+    }
+}
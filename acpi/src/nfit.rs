@@ -0,0 +1,537 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, ptr};
+
+/// Represents the NVDIMM Firmware Interface Table (NFIT), which describes the NVDIMMs in the system and how their
+/// regions are mapped into the system physical address space. You can iterate over the structures in the table
+/// with [`Nfit::entries`], and look up the structures that others refer to by index with [`Nfit::spa_range`],
+/// [`Nfit::interleave`], and [`Nfit::control_region`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Nfit {
+    pub header: SdtHeader,
+    _reserved: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid NFIT.
+unsafe impl AcpiTable for Nfit {
+    const SIGNATURE: Signature = Signature::NFIT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Nfit {
+    pub fn entries(&self) -> NfitEntryIter<'_> {
+        NfitEntryIter {
+            pointer: unsafe { (self as *const Nfit as *const u8).add(mem::size_of::<Nfit>()) },
+            remaining_length: self.header.length - mem::size_of::<Nfit>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Find the SPA Range structure with the given index, as referenced by `spa_range_structure_index` in a
+    /// [`RegionMapping`].
+    pub fn spa_range(&self, index: u16) -> Option<&SpaRange> {
+        self.entries().find_map(|entry| match entry {
+            NfitEntry::SpaRange(spa_range) if spa_range.spa_range_structure_index == index => Some(spa_range),
+            _ => None,
+        })
+    }
+
+    /// Find the Interleave structure with the given index, as referenced by `interleave_structure_index` in a
+    /// [`RegionMapping`].
+    pub fn interleave(&self, index: u16) -> Option<&Interleave> {
+        self.entries().find_map(|entry| match entry {
+            NfitEntry::Interleave(interleave) if interleave.interleave_structure_index == index => {
+                Some(interleave)
+            }
+            _ => None,
+        })
+    }
+
+    /// Find the NVDIMM Control Region structure with the given index, as referenced by
+    /// `control_region_structure_index` in a [`RegionMapping`].
+    pub fn control_region(&self, index: u16) -> Option<&ControlRegion> {
+        self.entries().find_map(|entry| match entry {
+            NfitEntry::ControlRegion(control_region) if control_region.control_region_structure_index == index => {
+                Some(control_region)
+            }
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NfitEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum NfitEntry<'a> {
+    SpaRange(&'a SpaRange),
+    RegionMapping(&'a RegionMapping),
+    Interleave(&'a Interleave),
+    ControlRegion(&'a ControlRegion),
+    FlushHintAddress(&'a FlushHintAddress),
+}
+
+impl<'a> Iterator for NfitEntryIter<'a> {
+    type Item = NfitEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length == 0 || header.length as u32 > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length as u32;
+
+            macro_rules! construct_entry {
+                ($entry_type:expr,
+                 $entry_pointer:expr,
+                 $(($value:expr => $variant:path as $type:ty)),*
+                ) => {
+                    match $entry_type {
+                        $(
+                            $value if header.length as usize >= mem::size_of::<$type>() => {
+                                return Some($variant(unsafe {
+                                    &*($entry_pointer as *const $type)
+                                }))
+                            }
+                         )*
+
+                        /*
+                         * We don't parse the SMBIOS Management Information, Block Data Window Region, or
+                         * Platform Capabilities structures yet, and other types are reserved by the ACPI
+                         * standard. Skip them, along with entries that are too short to be read as their type.
+                         */
+                        _ => {}
+                    }
+                }
+            }
+
+            #[rustfmt::skip]
+            construct_entry!(
+                header.entry_type,
+                entry_pointer,
+                (0x0 => NfitEntry::SpaRange as SpaRange),
+                (0x1 => NfitEntry::RegionMapping as RegionMapping),
+                (0x2 => NfitEntry::Interleave as Interleave),
+                (0x4 => NfitEntry::ControlRegion as ControlRegion),
+                (0x6 => NfitEntry::FlushHintAddress as FlushHintAddress)
+            );
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u16,
+    pub length: u16,
+}
+
+/// The kind of address range described by a [`SpaRange`], decoded from its Address Range Type GUID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressRangeType {
+    PersistentMemory,
+    /// The range contains the control registers of an NVDIMM, described by an NVDIMM Control Region structure.
+    ControlRegion,
+    BlockDataWindow,
+    RamDiskVirtualDisk,
+    RamDiskVirtualCd,
+    RamDiskPersistentVirtualDisk,
+    RamDiskPersistentVirtualCd,
+    /// A range type we don't recognise. Contains the GUID, in the byte order it appears in the table.
+    Unknown([u8; 16]),
+}
+
+impl AddressRangeType {
+    /*
+     * These are the GUIDs in the mixed-endian byte order that they appear in the table, so they can be compared
+     * directly.
+     */
+    const PERSISTENT_MEMORY: [u8; 16] =
+        [0x79, 0xd3, 0xf0, 0x66, 0xf3, 0xb4, 0x74, 0x40, 0xac, 0x43, 0x0d, 0x33, 0x18, 0xb7, 0x8c, 0xdb];
+    const CONTROL_REGION: [u8; 16] =
+        [0xf6, 0x01, 0xf7, 0x92, 0xb4, 0x13, 0x5d, 0x40, 0x91, 0x0b, 0x29, 0x93, 0x67, 0xe8, 0x23, 0x4c];
+    const BLOCK_DATA_WINDOW: [u8; 16] =
+        [0x30, 0x05, 0xaf, 0x91, 0x86, 0x5d, 0x0e, 0x47, 0xa6, 0xb0, 0x0a, 0x2d, 0xb9, 0x40, 0x82, 0x49];
+    const RAM_DISK_VIRTUAL_DISK: [u8; 16] =
+        [0x5a, 0x53, 0xab, 0x77, 0xfc, 0x45, 0x4b, 0x62, 0x55, 0x60, 0xf7, 0xb2, 0x81, 0xd1, 0xf9, 0x6e];
+    const RAM_DISK_VIRTUAL_CD: [u8; 16] =
+        [0x30, 0xbd, 0x5a, 0x3d, 0x75, 0x41, 0xce, 0x87, 0x6d, 0x64, 0xd2, 0xad, 0xe5, 0x23, 0xc4, 0xbb];
+    const RAM_DISK_PERSISTENT_VIRTUAL_DISK: [u8; 16] =
+        [0xc9, 0x02, 0xea, 0x5c, 0x07, 0x4d, 0xd3, 0x69, 0x26, 0x9f, 0x44, 0x96, 0xfb, 0xe0, 0x96, 0xf9];
+    const RAM_DISK_PERSISTENT_VIRTUAL_CD: [u8; 16] =
+        [0x88, 0x81, 0x01, 0x08, 0xcd, 0x42, 0x48, 0xbb, 0x10, 0x0f, 0x53, 0x87, 0xd5, 0x3d, 0xed, 0x3d];
+
+    fn from_guid(guid: [u8; 16]) -> AddressRangeType {
+        match guid {
+            Self::PERSISTENT_MEMORY => AddressRangeType::PersistentMemory,
+            Self::CONTROL_REGION => AddressRangeType::ControlRegion,
+            Self::BLOCK_DATA_WINDOW => AddressRangeType::BlockDataWindow,
+            Self::RAM_DISK_VIRTUAL_DISK => AddressRangeType::RamDiskVirtualDisk,
+            Self::RAM_DISK_VIRTUAL_CD => AddressRangeType::RamDiskVirtualCd,
+            Self::RAM_DISK_PERSISTENT_VIRTUAL_DISK => AddressRangeType::RamDiskPersistentVirtualDisk,
+            Self::RAM_DISK_PERSISTENT_VIRTUAL_CD => AddressRangeType::RamDiskPersistentVirtualCd,
+            other => AddressRangeType::Unknown(other),
+        }
+    }
+}
+
+/// Describes a range of system physical address space (SPA) that NVDIMM regions are mapped into.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SpaRange {
+    pub header: EntryHeader,
+    pub spa_range_structure_index: u16,
+    pub flags: u16,
+    _reserved: u32,
+    /// Only valid if `proximity_domain_valid` is set.
+    pub proximity_domain: u32,
+    address_range_type_guid: [u8; 16],
+    pub base_address: u64,
+    pub length: u64,
+    /// The EFI memory attributes (e.g. `EFI_MEMORY_WB`) that the range can be mapped with.
+    pub memory_mapping_attributes: u64,
+}
+
+impl SpaRange {
+    pub fn address_range_type(&self) -> AddressRangeType {
+        AddressRangeType::from_guid(self.address_range_type_guid)
+    }
+
+    /// If true, the range is only used for managing the NVDIMMs, and shouldn't be used for block or persistent
+    /// memory access.
+    pub fn is_control_region_for_management(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn proximity_domain_valid(&self) -> bool {
+        { self.flags }.get_bit(1)
+    }
+}
+
+/// Describes how a region of an NVDIMM is mapped into a [`SpaRange`]. There is one of these structures for each
+/// NVDIMM that contributes to an interleave set.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RegionMapping {
+    pub header: EntryHeader,
+    /// The handle of the NVDIMM in the namespace, as returned by its `_ADR` object.
+    pub device_handle: u32,
+    pub physical_id: u16,
+    pub region_id: u16,
+    /// The index of the SPA Range structure that the region is mapped into, or `0` if it isn't mapped.
+    pub spa_range_structure_index: u16,
+    pub control_region_structure_index: u16,
+    /// The size, in bytes, of the region within the NVDIMM.
+    pub region_size: u64,
+    /// The offset, in bytes, of the first byte of the region within the SPA range.
+    pub region_offset: u64,
+    pub physical_address_region_base: u64,
+    /// The index of the Interleave structure describing how the region is interleaved, or `0` if it isn't.
+    pub interleave_structure_index: u16,
+    pub interleave_ways: u16,
+    pub state_flags: u16,
+    _reserved: u16,
+}
+
+impl RegionMapping {
+    pub fn save_failed(&self) -> bool {
+        { self.state_flags }.get_bit(0)
+    }
+
+    pub fn restore_failed(&self) -> bool {
+        { self.state_flags }.get_bit(1)
+    }
+
+    pub fn platform_flush_failed(&self) -> bool {
+        { self.state_flags }.get_bit(2)
+    }
+
+    /// If true, the NVDIMM is not able to persist data written to it.
+    pub fn not_armed(&self) -> bool {
+        { self.state_flags }.get_bit(3)
+    }
+
+    pub fn smart_health_events_observed(&self) -> bool {
+        { self.state_flags }.get_bit(4)
+    }
+
+    pub fn smart_health_event_notifications_enabled(&self) -> bool {
+        { self.state_flags }.get_bit(5)
+    }
+
+    /// If true, the firmware didn't map the region into the system physical address space.
+    pub fn not_mapped(&self) -> bool {
+        { self.state_flags }.get_bit(6)
+    }
+}
+
+/// Describes the layout of the lines of an NVDIMM region within an interleave set.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Interleave {
+    pub header: EntryHeader,
+    pub interleave_structure_index: u16,
+    _reserved: u16,
+    pub number_of_lines: u32,
+    /// The size, in bytes, of each line.
+    pub line_size: u32,
+}
+
+impl Interleave {
+    /// The offset of each line within the SPA range, in units of `line_size`.
+    pub fn line_offsets(&self) -> impl Iterator<Item = u32> + '_ {
+        let available =
+            (self.header.length as usize).saturating_sub(mem::size_of::<Interleave>()) / mem::size_of::<u32>();
+        let count = usize::min(self.number_of_lines as usize, available);
+
+        (0..count).map(move |i| unsafe {
+            ptr::read_unaligned(
+                (self as *const Interleave as *const u8)
+                    .add(mem::size_of::<Interleave>() + i * mem::size_of::<u32>()) as *const u32,
+            )
+        })
+    }
+}
+
+/// Describes an NVDIMM's control region: the identity of the NVDIMM, and the interface used to control it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ControlRegion {
+    pub header: EntryHeader,
+    pub control_region_structure_index: u16,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub revision_id: u16,
+    pub subsystem_vendor_id: u16,
+    pub subsystem_device_id: u16,
+    pub subsystem_revision_id: u16,
+    pub valid_fields: u8,
+    /// Only valid if `manufacturing_fields_valid` is set.
+    pub manufacturing_location: u8,
+    /// Only valid if `manufacturing_fields_valid` is set.
+    pub manufacturing_date: u16,
+    _reserved: [u8; 2],
+    pub serial_number: u32,
+    pub region_format_interface_code: u16,
+    pub number_of_block_control_windows: u16,
+}
+
+impl ControlRegion {
+    pub fn manufacturing_fields_valid(&self) -> bool {
+        self.valid_fields.get_bit(0)
+    }
+
+    /// The block control window of the NVDIMM. Returns `None` if the NVDIMM doesn't have any block control
+    /// windows.
+    pub fn block_control_window(&self) -> Option<&BlockControlWindow> {
+        if self.number_of_block_control_windows == 0
+            || (self.header.length as usize)
+                < mem::size_of::<ControlRegion>() + mem::size_of::<BlockControlWindow>()
+        {
+            return None;
+        }
+
+        Some(unsafe {
+            &*((self as *const ControlRegion as *const u8).add(mem::size_of::<ControlRegion>())
+                as *const BlockControlWindow)
+        })
+    }
+}
+
+/// The part of a [`ControlRegion`] that describes its block control windows. This is only present if the NVDIMM
+/// has at least one block control window.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct BlockControlWindow {
+    pub size: u64,
+    pub command_register_offset: u64,
+    pub command_register_size: u64,
+    pub status_register_offset: u64,
+    pub status_register_size: u64,
+    pub flags: u16,
+    _reserved: [u8; 6],
+}
+
+impl BlockControlWindow {
+    /// If true, the NVDIMM buffers block data window accesses, and software must poll the status register to know
+    /// when a command has completed.
+    pub fn is_buffered(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+}
+
+/// Describes the addresses that software writes to in order to flush an NVDIMM's write buffers.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FlushHintAddress {
+    pub header: EntryHeader,
+    pub device_handle: u32,
+    pub number_of_flush_hint_addresses: u16,
+    _reserved: [u8; 6],
+}
+
+impl FlushHintAddress {
+    pub fn addresses(&self) -> impl Iterator<Item = u64> + '_ {
+        let available = (self.header.length as usize).saturating_sub(mem::size_of::<FlushHintAddress>())
+            / mem::size_of::<u64>();
+        let count = usize::min(self.number_of_flush_hint_addresses as usize, available);
+
+        (0..count).map(move |i| unsafe {
+            ptr::read_unaligned(
+                (self as *const FlushHintAddress as *const u8)
+                    .add(mem::size_of::<FlushHintAddress>() + i * mem::size_of::<u64>())
+                    as *const u64,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn header<T>(entry_type: u16, extra: usize) -> EntryHeader {
+        EntryHeader { entry_type, length: (mem::size_of::<T>() + extra) as u16 }
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body = Vec::new();
+        body.extend(0u32.to_le_bytes());
+        body.extend(bytes_of(&SpaRange {
+            header: header::<SpaRange>(0x0, 0),
+            spa_range_structure_index: 1,
+            flags: 0b10,
+            _reserved: 0,
+            proximity_domain: 2,
+            address_range_type_guid: AddressRangeType::PERSISTENT_MEMORY,
+            base_address: 0x1_0000_0000,
+            length: 0x4000_0000,
+            memory_mapping_attributes: 0x8008,
+        }));
+        body.extend(bytes_of(&RegionMapping {
+            header: header::<RegionMapping>(0x1, 0),
+            device_handle: 0x1,
+            physical_id: 0,
+            region_id: 0,
+            spa_range_structure_index: 1,
+            control_region_structure_index: 3,
+            region_size: 0x4000_0000,
+            region_offset: 0,
+            physical_address_region_base: 0,
+            interleave_structure_index: 2,
+            interleave_ways: 1,
+            state_flags: 0b1000,
+            _reserved: 0,
+        }));
+        body.extend(bytes_of(&Interleave {
+            header: header::<Interleave>(0x2, 8),
+            interleave_structure_index: 2,
+            _reserved: 0,
+            number_of_lines: 3,
+            line_size: 256,
+        }));
+        body.extend(0u32.to_le_bytes());
+        body.extend(4u32.to_le_bytes());
+        // An SMBIOS Management Information structure, which isn't parsed
+        body.extend(bytes_of(&EntryHeader { entry_type: 0x3, length: 8 }));
+        body.extend(0u32.to_le_bytes());
+        body.extend(bytes_of(&ControlRegion {
+            header: header::<ControlRegion>(0x4, 0),
+            control_region_structure_index: 3,
+            vendor_id: 0x8086,
+            device_id: 0x1234,
+            revision_id: 1,
+            subsystem_vendor_id: 0,
+            subsystem_device_id: 0,
+            subsystem_revision_id: 0,
+            valid_fields: 0b1,
+            manufacturing_location: 0,
+            manufacturing_date: 0,
+            _reserved: [0; 2],
+            serial_number: 0xcafe,
+            region_format_interface_code: 0x301,
+            number_of_block_control_windows: 0,
+        }));
+        // A Flush Hint Address structure too short to be read as one is skipped
+        body.extend(bytes_of(&EntryHeader { entry_type: 0x6, length: 8 }));
+        body.extend(0u32.to_le_bytes());
+        body.extend(bytes_of(&FlushHintAddress {
+            header: header::<FlushHintAddress>(0x6, 16),
+            device_handle: 0x1,
+            number_of_flush_hint_addresses: 2,
+            _reserved: [0; 6],
+        }));
+        body.extend(0xf000_0000u64.to_le_bytes());
+        body.extend(0xf000_0040u64.to_le_bytes());
+
+        let buffer = TableBuffer::<Nfit>::new(&table(b"NFIT", 1, &body));
+        let nfit = buffer.get();
+        assert!(nfit.validate().is_ok());
+        assert_eq!(nfit.entries().count(), 5);
+
+        let spa_range = nfit.spa_range(1).unwrap();
+        assert_eq!(spa_range.address_range_type(), AddressRangeType::PersistentMemory);
+        assert!(spa_range.proximity_domain_valid());
+        assert!(!spa_range.is_control_region_for_management());
+        assert!(nfit.spa_range(2).is_none());
+
+        let region_mapping = nfit
+            .entries()
+            .find_map(|entry| match entry {
+                NfitEntry::RegionMapping(region_mapping) => Some(region_mapping),
+                _ => None,
+            })
+            .unwrap();
+        assert!(region_mapping.not_armed());
+        assert!(!region_mapping.save_failed());
+
+        let interleave = nfit.interleave(region_mapping.interleave_structure_index).unwrap();
+        // Only the lines that fit in the structure are read
+        assert!(interleave.line_offsets().eq([0, 4]));
+
+        let control_region = nfit.control_region(region_mapping.control_region_structure_index).unwrap();
+        assert_eq!({ control_region.serial_number }, 0xcafe);
+        assert!(control_region.manufacturing_fields_valid());
+        assert!(control_region.block_control_window().is_none());
+
+        match nfit.entries().last() {
+            Some(NfitEntry::FlushHintAddress(flush_hint)) => {
+                assert!(flush_hint.addresses().eq([0xf000_0000, 0xf000_0040]))
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        let mut body = Vec::new();
+        body.extend(0u32.to_le_bytes());
+        body.extend(bytes_of(&EntryHeader { entry_type: 0x0, length: 56 }));
+        body.extend([0; 20]);
+
+        let buffer = TableBuffer::<Nfit>::new(&table(b"NFIT", 1, &body));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
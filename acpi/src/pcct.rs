@@ -0,0 +1,615 @@
+use crate::{
+    address::{AccessSize, AddressSpace, GenericAddress, RawGenericAddress, RegisterHandler},
+    sdt::{SdtHeader, Signature},
+    AcpiError,
+    AcpiResult,
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// The number of times a subspace's status is polled while waiting for the platform to complete a command, before
+/// giving up.
+const PCC_POLL_LIMIT: usize = 1_000_000;
+
+/// The signature of the shared memory region of a subspace, which is ORed with the index of the subspace.
+const PCC_SIGNATURE: u32 = 0x5043_4300;
+
+const PCC_STATUS_COMMAND_COMPLETE: usize = 0;
+const PCC_STATUS_ERROR: usize = 2;
+
+#[derive(Debug)]
+pub enum PccError {
+    NoSuchSubspace(u8),
+    /// Commands can't be sent on the subspace, because it is a slave subspace, or of a type we don't support.
+    UnsupportedSubspace(u8),
+    /// The communication space access is outside the subspace's shared memory region.
+    OutOfBounds,
+    /// The platform didn't complete the command.
+    Timeout,
+    /// The platform completed the command, but reported an error.
+    CommandFailed,
+}
+
+/// Represents the Platform Communications Channel Table (PCCT), which describes the mailbox channels (subspaces)
+/// that the OS can use to communicate with platform entities, such as a management controller. Other parts of
+/// ACPI (e.g. `_CPC` objects) refer to subspaces by their index in this table, which is the position returned by
+/// [`Pcct::subspaces`], or can be looked up with [`Pcct::subspace`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Pcct {
+    pub header: SdtHeader,
+    pub flags: u32,
+    _reserved: u64,
+}
+
+/// ### Safety: Implementation properly represents a valid PCCT.
+unsafe impl AcpiTable for Pcct {
+    const SIGNATURE: Signature = Signature::PCCT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Pcct {
+    /// If true, the platform can signal the completion of commands on generic subspaces with an SCI.
+    pub fn supports_platform_interrupt(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn subspaces(&self) -> PcctSubspaceIter<'_> {
+        PcctSubspaceIter {
+            pointer: unsafe { (self as *const Pcct as *const u8).add(mem::size_of::<Pcct>()) },
+            remaining_length: self.header.length - mem::size_of::<Pcct>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn subspace(&self, index: usize) -> Option<PcctSubspace<'_>> {
+        self.subspaces().nth(index)
+    }
+}
+
+#[derive(Debug)]
+pub struct PcctSubspaceIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum PcctSubspace<'a> {
+    Generic(&'a GenericSubspace),
+    HwReduced(&'a HwReducedSubspace),
+    HwReducedType2(&'a HwReducedType2Subspace),
+    ExtendedMaster(&'a ExtendedSubspace),
+    ExtendedSlave(&'a ExtendedSubspace),
+    /// A subspace of a type we don't parse yet. It is still returned so that the indices of the subspaces after it
+    /// are correct.
+    Other(&'a SubspaceHeader),
+}
+
+impl<'a> Iterator for PcctSubspaceIter<'a> {
+    type Item = PcctSubspace<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if (self.remaining_length as usize) < mem::size_of::<SubspaceHeader>() {
+            return None;
+        }
+
+        let subspace_pointer = self.pointer;
+        let header = unsafe { &*(self.pointer as *const SubspaceHeader) };
+        if header.length == 0 || header.length as u32 > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(header.length as usize) };
+        self.remaining_length -= header.length as u32;
+
+        macro_rules! construct_subspace {
+            ($(($value:expr => $variant:path as $type:ty)),*) => {
+                match header.subspace_type {
+                    $(
+                        $value if header.length as usize >= mem::size_of::<$type>() => {
+                            $variant(unsafe { &*(subspace_pointer as *const $type) })
+                        }
+                     )*
+                    _ => PcctSubspace::Other(header),
+                }
+            }
+        }
+
+        #[rustfmt::skip]
+        let subspace = construct_subspace!(
+            (0x0 => PcctSubspace::Generic as GenericSubspace),
+            (0x1 => PcctSubspace::HwReduced as HwReducedSubspace),
+            (0x2 => PcctSubspace::HwReducedType2 as HwReducedType2Subspace),
+            (0x3 => PcctSubspace::ExtendedMaster as ExtendedSubspace),
+            (0x4 => PcctSubspace::ExtendedSlave as ExtendedSubspace)
+        );
+
+        Some(subspace)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SubspaceHeader {
+    pub subspace_type: u8,
+    pub length: u8,
+}
+
+/// The flags of the platform interrupt of a hardware-reduced or extended subspace.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct PlatformInterruptFlags(u8);
+
+impl PlatformInterruptFlags {
+    pub fn is_active_low(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    pub fn is_edge_triggered(&self) -> bool {
+        self.0.get_bit(1)
+    }
+}
+
+/// A generic communications subspace (type 0). Completion is signalled by the SCI, if the PCCT says that the
+/// platform supports it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericSubspace {
+    pub header: SubspaceHeader,
+    _reserved: [u8; 6],
+    /// The physical address of the shared memory region of the subspace.
+    pub base_address: u64,
+    /// The length, in bytes, of the shared memory region.
+    pub memory_range_length: u64,
+    doorbell_register: RawGenericAddress,
+    /// The bits of the doorbell register that must be preserved when ringing the doorbell.
+    pub doorbell_preserve: u64,
+    /// The bits to set in the doorbell register to ring the doorbell.
+    pub doorbell_write: u64,
+    /// The expected latency, in microseconds, for the platform to process a command.
+    pub nominal_latency: u32,
+    /// The maximum number of commands that can be sent per minute, or `0` if there is no limit.
+    pub maximum_periodic_access_rate: u32,
+    /// The minimum time, in microseconds, that the OS must wait after a command completes before sending another.
+    pub minimum_request_turnaround_time: u16,
+}
+
+impl GenericSubspace {
+    pub fn doorbell_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.doorbell_register)
+    }
+}
+
+/// A communications subspace on a hardware-reduced platform (type 1), which signals completion with a dedicated
+/// platform interrupt, rather than the SCI.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HwReducedSubspace {
+    pub header: SubspaceHeader,
+    /// The GSIV of the interrupt used to signal completion.
+    pub platform_interrupt: u32,
+    pub platform_interrupt_flags: PlatformInterruptFlags,
+    _reserved: u8,
+    pub base_address: u64,
+    pub memory_range_length: u64,
+    doorbell_register: RawGenericAddress,
+    pub doorbell_preserve: u64,
+    pub doorbell_write: u64,
+    pub nominal_latency: u32,
+    pub maximum_periodic_access_rate: u32,
+    pub minimum_request_turnaround_time: u16,
+}
+
+impl HwReducedSubspace {
+    pub fn doorbell_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.doorbell_register)
+    }
+}
+
+/// A hardware-reduced communications subspace (type 2) whose platform interrupt must be acknowledged by writing to
+/// a register.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HwReducedType2Subspace {
+    pub header: SubspaceHeader,
+    pub platform_interrupt: u32,
+    pub platform_interrupt_flags: PlatformInterruptFlags,
+    _reserved: u8,
+    pub base_address: u64,
+    pub memory_range_length: u64,
+    doorbell_register: RawGenericAddress,
+    pub doorbell_preserve: u64,
+    pub doorbell_write: u64,
+    pub nominal_latency: u32,
+    pub maximum_periodic_access_rate: u32,
+    pub minimum_request_turnaround_time: u16,
+    platform_interrupt_ack_register: RawGenericAddress,
+    pub platform_interrupt_ack_preserve: u64,
+    pub platform_interrupt_ack_write: u64,
+}
+
+impl HwReducedType2Subspace {
+    pub fn doorbell_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.doorbell_register)
+    }
+
+    pub fn platform_interrupt_ack_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.platform_interrupt_ack_register)
+    }
+}
+
+/// An extended communications subspace (types 3 and 4). Master subspaces are used by the OS to send commands to
+/// the platform, and slave subspaces are used by the platform to send notifications to the OS.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ExtendedSubspace {
+    pub header: SubspaceHeader,
+    pub platform_interrupt: u32,
+    pub platform_interrupt_flags: PlatformInterruptFlags,
+    _reserved0: u8,
+    pub base_address: u64,
+    pub memory_range_length: u32,
+    doorbell_register: RawGenericAddress,
+    pub doorbell_preserve: u64,
+    pub doorbell_write: u64,
+    pub nominal_latency: u32,
+    pub maximum_periodic_access_rate: u32,
+    pub minimum_request_turnaround_time: u32,
+    platform_interrupt_ack_register: RawGenericAddress,
+    pub platform_interrupt_ack_preserve: u64,
+    pub platform_interrupt_ack_set: u64,
+    _reserved1: [u8; 8],
+    command_complete_check_register: RawGenericAddress,
+    pub command_complete_check_mask: u64,
+    command_complete_update_register: RawGenericAddress,
+    pub command_complete_update_preserve: u64,
+    pub command_complete_update_set: u64,
+    error_status_register: RawGenericAddress,
+    pub error_status_mask: u64,
+}
+
+impl ExtendedSubspace {
+    pub fn doorbell_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.doorbell_register)
+    }
+
+    pub fn platform_interrupt_ack_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.platform_interrupt_ack_register)
+    }
+
+    pub fn command_complete_check_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.command_complete_check_register)
+    }
+
+    pub fn command_complete_update_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.command_complete_update_register)
+    }
+
+    pub fn error_status_register(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.error_status_register)
+    }
+}
+
+/// A mailbox channel to the platform, over a single subspace of the PCCT. Commands are sent by writing to the
+/// subspace's shared memory region and ringing its doorbell, and their completion is detected by polling (the
+/// platform interrupt, if any, is not used).
+///
+/// The layout of the communication space (the part of the shared memory region after its header), and the meaning
+/// of commands, are defined by the protocol using the subspace (e.g. CPPC).
+#[derive(Clone, Copy, Debug)]
+pub struct PccChannel {
+    index: u8,
+    base_address: u64,
+    memory_range_length: u64,
+    doorbell_register: GenericAddress,
+    doorbell_preserve: u64,
+    doorbell_write: u64,
+    /// The expected latency, in microseconds, for the platform to process a command.
+    pub nominal_latency: u32,
+    extended: Option<ExtendedRegisters>,
+}
+
+/// The registers of an extended subspace, which are used instead of the status field of the shared memory region.
+#[derive(Clone, Copy, Debug)]
+struct ExtendedRegisters {
+    command_complete_check_register: GenericAddress,
+    command_complete_check_mask: u64,
+    command_complete_update_register: GenericAddress,
+    command_complete_update_preserve: u64,
+    command_complete_update_set: u64,
+    error_status_register: GenericAddress,
+    error_status_mask: u64,
+}
+
+impl PccChannel {
+    /// Create a channel for the subspace with index `index`. Commands can be sent on generic, hardware-reduced,
+    /// and extended master subspaces.
+    pub fn new(pcct: &Pcct, index: u8) -> AcpiResult<PccChannel> {
+        macro_rules! channel {
+            ($subspace:expr, $memory_range_length:expr, $extended:expr) => {
+                PccChannel {
+                    index,
+                    base_address: $subspace.base_address,
+                    memory_range_length: $memory_range_length,
+                    doorbell_register: $subspace.doorbell_register()?,
+                    doorbell_preserve: $subspace.doorbell_preserve,
+                    doorbell_write: $subspace.doorbell_write,
+                    nominal_latency: $subspace.nominal_latency,
+                    extended: $extended,
+                }
+            };
+        }
+
+        match pcct.subspace(index as usize) {
+            Some(PcctSubspace::Generic(subspace)) => Ok(channel!(subspace, subspace.memory_range_length, None)),
+            Some(PcctSubspace::HwReduced(subspace)) => Ok(channel!(subspace, subspace.memory_range_length, None)),
+            Some(PcctSubspace::HwReducedType2(subspace)) => {
+                Ok(channel!(subspace, subspace.memory_range_length, None))
+            }
+            Some(PcctSubspace::ExtendedMaster(subspace)) => {
+                let extended = ExtendedRegisters {
+                    command_complete_check_register: subspace.command_complete_check_register()?,
+                    command_complete_check_mask: subspace.command_complete_check_mask,
+                    command_complete_update_register: subspace.command_complete_update_register()?,
+                    command_complete_update_preserve: subspace.command_complete_update_preserve,
+                    command_complete_update_set: subspace.command_complete_update_set,
+                    error_status_register: subspace.error_status_register()?,
+                    error_status_mask: subspace.error_status_mask,
+                };
+                Ok(channel!(subspace, subspace.memory_range_length as u64, Some(extended)))
+            }
+            Some(_) => Err(AcpiError::Pcc(PccError::UnsupportedSubspace(index))),
+            None => Err(AcpiError::Pcc(PccError::NoSuchSubspace(index))),
+        }
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Send `command` to the platform, and wait for it to complete. Anything the command needs should already
+    /// have been written to the communication space.
+    pub fn send_command<H>(&self, handler: &H, command: u32) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        /*
+         * Generic shared memory region:            Extended shared memory region:
+         * 0x00     Signature (u32)                 0x00    Signature (u32)
+         * 0x04     Command (u16)                   0x04    Flags (u32)
+         * 0x06     Status (u16)                    0x08    Length (u32)
+         * 0x08     Communication space             0x0c    Command (u32)
+         *                                          0x10    Communication space
+         */
+        self.wait_for_completion(handler)?;
+
+        let signature = PCC_SIGNATURE | self.index as u32;
+        self.shared_memory_register(0x00, 32)?.write_register(handler, signature as u64)?;
+        match self.extended {
+            Some(ref extended) => {
+                self.shared_memory_register(0x04, 32)?.write_register(handler, 0)?;
+                self.shared_memory_register(0x08, 32)?.write_register(handler, self.memory_range_length - 0x0c)?;
+                self.shared_memory_register(0x0c, 32)?.write_register(handler, command as u64)?;
+
+                let update = extended.command_complete_update_register.read_register(handler)?;
+                extended.command_complete_update_register.write_register(
+                    handler,
+                    (update & extended.command_complete_update_preserve) | extended.command_complete_update_set,
+                )?;
+            }
+            None => {
+                self.shared_memory_register(0x04, 16)?.write_register(handler, command as u64)?;
+                self.shared_memory_register(0x06, 16)?.write_register(handler, 0)?;
+            }
+        }
+
+        let doorbell = self.doorbell_register.read_register(handler)?;
+        self.doorbell_register
+            .write_register(handler, (doorbell & self.doorbell_preserve) | self.doorbell_write)?;
+
+        self.wait_for_completion(handler)?;
+        let failed = match self.extended {
+            Some(ref extended) => {
+                extended.error_status_register.read_register(handler)? & extended.error_status_mask != 0
+            }
+            None => self.shared_memory_register(0x06, 16)?.read_register(handler)?.get_bit(PCC_STATUS_ERROR),
+        };
+
+        if failed {
+            Err(AcpiError::Pcc(PccError::CommandFailed))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Read `width` bits (8, 16, 32, or 64) at `offset` bytes into the communication space.
+    pub fn read<H>(&self, handler: &H, offset: u64, width: u8) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        self.shared_memory_register(self.communication_space_offset() + offset, width)?.read_register(handler)
+    }
+
+    /// Write `width` bits (8, 16, 32, or 64) at `offset` bytes into the communication space.
+    pub fn write<H>(&self, handler: &H, offset: u64, width: u8, value: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.shared_memory_register(self.communication_space_offset() + offset, width)?
+            .write_register(handler, value)
+    }
+
+    fn communication_space_offset(&self) -> u64 {
+        if self.extended.is_some() {
+            0x10
+        } else {
+            0x08
+        }
+    }
+
+    fn wait_for_completion<H>(&self, handler: &H) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        for _ in 0..PCC_POLL_LIMIT {
+            let complete = match self.extended {
+                Some(ref extended) => {
+                    extended.command_complete_check_register.read_register(handler)?
+                        & extended.command_complete_check_mask
+                        != 0
+                }
+                None => self
+                    .shared_memory_register(0x06, 16)?
+                    .read_register(handler)?
+                    .get_bit(PCC_STATUS_COMMAND_COMPLETE),
+            };
+
+            if complete {
+                return Ok(());
+            }
+            core::hint::spin_loop();
+        }
+
+        Err(AcpiError::Pcc(PccError::Timeout))
+    }
+
+    /// Get a register for `width` bits at `offset` bytes into the shared memory region.
+    fn shared_memory_register(&self, offset: u64, width: u8) -> AcpiResult<GenericAddress> {
+        let access_size = match width {
+            8 => AccessSize::ByteAccess,
+            16 => AccessSize::WordAccess,
+            32 => AccessSize::DWordAccess,
+            64 => AccessSize::QWordAccess,
+            _ => return Err(AcpiError::InvalidGenericAddress),
+        };
+        if offset + width as u64 / 8 > self.memory_range_length {
+            return Err(AcpiError::Pcc(PccError::OutOfBounds));
+        }
+
+        Ok(GenericAddress {
+            address_space: AddressSpace::SystemMemory,
+            bit_width: width,
+            bit_offset: 0,
+            access_size,
+            address: self.base_address + offset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, HandlerState, TableBuffer, TestHandler};
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::{vec, vec::Vec};
+
+    const DOORBELL_PORT: u16 = 0x500;
+
+    fn generic_subspace(base_address: u64) -> GenericSubspace {
+        GenericSubspace {
+            header: SubspaceHeader { subspace_type: 0x0, length: mem::size_of::<GenericSubspace>() as u8 },
+            _reserved: [0; 6],
+            base_address,
+            memory_range_length: 0x40,
+            doorbell_register: RawGenericAddress {
+                address_space: 1,
+                bit_width: 8,
+                bit_offset: 0,
+                access_size: 1,
+                address: DOORBELL_PORT as u64,
+            },
+            doorbell_preserve: 0,
+            doorbell_write: 1,
+            nominal_latency: 100,
+            maximum_periodic_access_rate: 0,
+            minimum_request_turnaround_time: 0,
+        }
+    }
+
+    fn pcct(subspaces: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = vec![0; 12];
+        body[0] = 0b1;
+        for subspace in subspaces {
+            body.extend(subspace);
+        }
+        table(b"PCCT", 2, &body)
+    }
+
+    #[test]
+    fn test_subspaces() {
+        // A hardware-reduced subspace too short to be read as one, which still takes up an index
+        let mut short = vec![0; 16];
+        short[0] = 0x1;
+        short[1] = 16;
+        let mut unknown = vec![0; 8];
+        unknown[0] = 0x5;
+        unknown[1] = 8;
+        let buffer = TableBuffer::<Pcct>::new(&pcct(&[bytes_of(&generic_subspace(0x1000)), short, unknown]));
+        let pcct = buffer.get();
+        assert!(pcct.validate().is_ok());
+        assert!(pcct.supports_platform_interrupt());
+        assert_eq!(pcct.subspaces().count(), 3);
+
+        match pcct.subspace(0) {
+            Some(PcctSubspace::Generic(subspace)) => {
+                assert_eq!({ subspace.base_address }, 0x1000);
+                assert_eq!(subspace.doorbell_register().unwrap().address_space, AddressSpace::SystemIo);
+            }
+            ref other => panic!("unexpected subspace: {:?}", other),
+        }
+        match pcct.subspace(1) {
+            Some(PcctSubspace::Other(header)) => assert_eq!(header.subspace_type, 0x1),
+            ref other => panic!("unexpected subspace: {:?}", other),
+        }
+        assert!(matches!(PccChannel::new(pcct, 1), Err(AcpiError::Pcc(PccError::UnsupportedSubspace(1)))));
+        assert!(matches!(PccChannel::new(pcct, 3), Err(AcpiError::Pcc(PccError::NoSuchSubspace(3)))));
+    }
+
+    #[test]
+    fn test_truncated_subspace() {
+        let subspace = bytes_of(&generic_subspace(0x1000));
+        let buffer = TableBuffer::<Pcct>::new(&pcct(&[subspace[..subspace.len() - 1].to_vec()]));
+        assert_eq!(buffer.get().subspaces().count(), 0);
+    }
+
+    #[test]
+    fn test_send_command() {
+        static SHARED_MEMORY: AtomicU64 = AtomicU64::new(0);
+
+        // The platform completes each command when the doorbell is rung, and fails command `0xbad`
+        fn ring_doorbell(_state: &mut HandlerState, port: u16, _value: u32) {
+            if port == DOORBELL_PORT {
+                let shared_memory = SHARED_MEMORY.load(Ordering::SeqCst) as *mut u16;
+                unsafe {
+                    let command = shared_memory.add(2).read_volatile();
+                    shared_memory.add(3).write_volatile(if command == 0xbad { 0b101 } else { 0b1 });
+                }
+            }
+        }
+
+        let mut shared_memory = vec![0u64; 8];
+        shared_memory[0] = 1 << 48;
+        let address = shared_memory.as_mut_ptr() as u64;
+        SHARED_MEMORY.store(address, Ordering::SeqCst);
+
+        let buffer = TableBuffer::<Pcct>::new(&pcct(&[bytes_of(&generic_subspace(address))]));
+        let channel = PccChannel::new(buffer.get(), 0).unwrap();
+        let handler = TestHandler::new(HandlerState { on_io_write: Some(ring_doorbell), ..Default::default() });
+
+        channel.write(&handler, 0, 32, 0x1234_5678).unwrap();
+        channel.send_command(&handler, 0x3).unwrap();
+        assert_eq!(shared_memory[0] as u32, PCC_SIGNATURE);
+        assert_eq!(shared_memory[0] >> 32, 0x1_0003);
+        assert_eq!(channel.read(&handler, 0, 32).unwrap(), 0x1234_5678);
+        assert_eq!(handler.state().io_writes, [(DOORBELL_PORT, 1)]);
+
+        assert!(matches!(channel.send_command(&handler, 0xbad), Err(AcpiError::Pcc(PccError::CommandFailed))));
+        assert!(matches!(channel.read(&handler, 0x38, 32), Err(AcpiError::Pcc(PccError::OutOfBounds))));
+    }
+}
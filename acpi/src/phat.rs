@@ -0,0 +1,284 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{char, marker::PhantomData, mem, ptr, slice};
+
+/// Represents the Platform Health Assessment Table (PHAT), which contains records describing the versions of the
+/// firmware components of the platform, and the health of devices (as reported by the firmware).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Phat {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid PHAT.
+unsafe impl AcpiTable for Phat {
+    const SIGNATURE: Signature = Signature::PHAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Phat {
+    pub fn records(&self) -> PhatRecordIter<'_> {
+        PhatRecordIter {
+            pointer: unsafe { (self as *const Phat as *const u8).add(mem::size_of::<Phat>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Phat>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PhatRecordIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum PhatRecord<'a> {
+    FirmwareVersion(&'a FirmwareVersionRecord),
+    FirmwareHealth(&'a FirmwareHealthRecord),
+    /// A record of a reserved type.
+    Reserved(&'a RecordHeader),
+}
+
+impl<'a> Iterator for PhatRecordIter<'a> {
+    type Item = PhatRecord<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<RecordHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const RecordHeader) };
+        let length = header.record_length as usize;
+        if length < mem::size_of::<RecordHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let record_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        match header.record_type {
+            0 if length >= mem::size_of::<FirmwareVersionRecord>() => {
+                Some(PhatRecord::FirmwareVersion(unsafe { &*(record_pointer as *const FirmwareVersionRecord) }))
+            }
+            1 if length >= mem::size_of::<FirmwareHealthRecord>() => {
+                Some(PhatRecord::FirmwareHealth(unsafe { &*(record_pointer as *const FirmwareHealthRecord) }))
+            }
+            _ => Some(PhatRecord::Reserved(header)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RecordHeader {
+    pub record_type: u16,
+    pub record_length: u16,
+    pub revision: u8,
+}
+
+/// Lists the versions of a set of firmware components.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FirmwareVersionRecord {
+    pub header: RecordHeader,
+    _reserved: [u8; 3],
+    pub record_count: u32,
+    // Followed by `record_count` version elements
+}
+
+impl FirmwareVersionRecord {
+    pub fn elements(&self) -> &[FirmwareVersionElement] {
+        let available = (self.header.record_length as usize).saturating_sub(mem::size_of::<Self>())
+            / mem::size_of::<FirmwareVersionElement>();
+
+        unsafe {
+            let pointer = (self as *const Self as *const u8).add(mem::size_of::<Self>());
+            slice::from_raw_parts(
+                pointer as *const FirmwareVersionElement,
+                usize::min(self.record_count as usize, available),
+            )
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FirmwareVersionElement {
+    /// A GUID identifying the component, in the mixed-endian format it is stored in.
+    pub component_id: [u8; 16],
+    pub version_value: u64,
+    /// The vendor ID of the producer of the component, as a PNP ID or ACPI ID.
+    pub producer_id: [u8; 4],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceHealth {
+    /// The firmware found errors with the device.
+    Errors,
+    NoErrors,
+    Unknown,
+    /// The firmware found no errors, but has advisory information about the device.
+    Advisory,
+    Reserved(u8),
+}
+
+/// Describes the health of a device, as assessed by the firmware.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FirmwareHealthRecord {
+    pub header: RecordHeader,
+    _reserved: u16,
+    am_healthy: u8,
+    /// A GUID identifying the format of the device-specific data, in the mixed-endian format it is stored in.
+    pub device_signature: [u8; 16],
+    /// The offset of the device-specific data from the start of the record, or `0` if there isn't any.
+    pub device_specific_data_offset: u32,
+    // Followed by the device path, and then the device-specific data
+}
+
+impl FirmwareHealthRecord {
+    pub fn health(&self) -> DeviceHealth {
+        match self.am_healthy {
+            0 => DeviceHealth::Errors,
+            1 => DeviceHealth::NoErrors,
+            2 => DeviceHealth::Unknown,
+            3 => DeviceHealth::Advisory,
+            other => DeviceHealth::Reserved(other),
+        }
+    }
+
+    /// The UEFI device path of the device, as text. Any invalid UTF-16 is replaced with
+    /// [`char::REPLACEMENT_CHARACTER`].
+    pub fn device_path(&self) -> impl Iterator<Item = char> + '_ {
+        let path_end = match self.device_specific_data_offset as usize {
+            0 => self.header.record_length as usize,
+            offset => usize::min(offset, self.header.record_length as usize),
+        };
+        let length = path_end.saturating_sub(mem::size_of::<Self>()) / mem::size_of::<u16>();
+        let base = unsafe { (self as *const Self as *const u8).add(mem::size_of::<Self>()) } as *const u16;
+
+        let units =
+            (0..length).map(move |i| unsafe { ptr::read_unaligned(base.add(i)) }).take_while(|&unit| unit != 0);
+        char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// The device-specific data of the record, the format of which is identified by `device_signature`. Returns
+    /// `None` if the record doesn't have any.
+    pub fn device_specific_data(&self) -> Option<&[u8]> {
+        let offset = self.device_specific_data_offset as usize;
+        let length = self.header.record_length as usize;
+        if offset < mem::size_of::<Self>() || offset >= length {
+            return None;
+        }
+
+        Some(unsafe { slice::from_raw_parts((self as *const Self as *const u8).add(offset), length - offset) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::{string::String, vec::Vec};
+
+    fn header(record_type: u16, record_length: usize) -> RecordHeader {
+        RecordHeader { record_type, record_length: record_length as u16, revision: 1 }
+    }
+
+    fn health_record(path: &str, data: &[u8]) -> Vec<u8> {
+        let path: Vec<u16> = path.encode_utf16().chain([0]).collect();
+        let data_offset = mem::size_of::<FirmwareHealthRecord>() + path.len() * 2;
+        let mut bytes = bytes_of(&FirmwareHealthRecord {
+            header: header(1, data_offset + data.len()),
+            _reserved: 0,
+            am_healthy: 3,
+            device_signature: [0; 16],
+            device_specific_data_offset: if data.is_empty() { 0 } else { data_offset as u32 },
+        });
+        for unit in path {
+            bytes.extend(unit.to_le_bytes());
+        }
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_records() {
+        let element =
+            FirmwareVersionElement { component_id: [1; 16], version_value: 0x1_0002, producer_id: *b"INTC" };
+        let mut body = bytes_of(&FirmwareVersionRecord {
+            header: header(
+                0,
+                mem::size_of::<FirmwareVersionRecord>() + 2 * mem::size_of::<FirmwareVersionElement>(),
+            ),
+            _reserved: [0; 3],
+            record_count: 2,
+        });
+        body.extend(bytes_of(&element));
+        body.extend(bytes_of(&FirmwareVersionElement { version_value: 3, ..element }));
+        body.extend(health_record("PciRoot(0x0)/Pci(0x1,0x0)", &[0xaa, 0xbb]));
+        body.extend(health_record("VenHw(\u{fffd})", &[]));
+        // A health record that is too short to be read as one
+        body.extend(bytes_of(&header(1, 8)));
+        body.extend([0; 3]);
+
+        let buffer = TableBuffer::<Phat>::new(&table(b"PHAT", 1, &body));
+        let phat = buffer.get();
+        assert!(phat.validate().is_ok());
+
+        let records: Vec<_> = phat.records().collect();
+        assert_eq!(records.len(), 4);
+        match records[0] {
+            PhatRecord::FirmwareVersion(record) => {
+                let versions: Vec<_> = record.elements().iter().map(|element| element.version_value).collect();
+                assert_eq!(versions, [0x1_0002, 3]);
+            }
+            ref other => panic!("unexpected record: {:?}", other),
+        }
+        match records[1] {
+            PhatRecord::FirmwareHealth(record) => {
+                assert_eq!(record.health(), DeviceHealth::Advisory);
+                assert_eq!(record.device_path().collect::<String>(), "PciRoot(0x0)/Pci(0x1,0x0)");
+                assert_eq!(record.device_specific_data(), Some(&[0xaa, 0xbb][..]));
+            }
+            ref other => panic!("unexpected record: {:?}", other),
+        }
+        match records[2] {
+            PhatRecord::FirmwareHealth(record) => {
+                assert_eq!(record.device_path().collect::<String>(), "VenHw(\u{fffd})");
+                assert_eq!(record.device_specific_data(), None);
+            }
+            ref other => panic!("unexpected record: {:?}", other),
+        }
+        assert!(matches!(records[3], PhatRecord::Reserved(header) if header.record_type == 1));
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        // A version record with fewer elements than it claims only returns the ones that fit
+        let mut body = bytes_of(&FirmwareVersionRecord {
+            header: header(0, mem::size_of::<FirmwareVersionRecord>() + mem::size_of::<FirmwareVersionElement>()),
+            _reserved: [0; 3],
+            record_count: 3,
+        });
+        body.extend([0; mem::size_of::<FirmwareVersionElement>()]);
+        let buffer = TableBuffer::<Phat>::new(&table(b"PHAT", 1, &body));
+        match buffer.get().records().next() {
+            Some(PhatRecord::FirmwareVersion(record)) => assert_eq!(record.elements().len(), 1),
+            ref other => panic!("unexpected record: {:?}", other),
+        }
+
+        // A record that runs past the end of the table ends the list
+        let buffer = TableBuffer::<Phat>::new(&table(b"PHAT", 1, &body[..body.len() - 1]));
+        assert_eq!(buffer.get().records().count(), 0);
+    }
+}
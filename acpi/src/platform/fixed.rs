@@ -0,0 +1,294 @@
+//! Versions of the platform structures that hold their lists inline, in [`FixedVec`]s of up to `N` elements,
+//! instead of in memory from an allocator. These are for early-boot environments that need the MADT before they
+//! have an allocator - they can be returned from a function, or stored in a `static`, without borrowing anything.
+
+use super::{
+    interrupt::{
+        Apic,
+        Aplic,
+        Gic,
+        GicCpuInterface,
+        GicDistributor,
+        GicInterruptTranslationService,
+        GicMsiFrame,
+        GicRedistributor,
+        Imsic,
+        InterruptModel,
+        InterruptSourceOverride,
+        IoApic,
+        IrqRouting,
+        NmiLine,
+        NmiProcessor,
+        NmiSource,
+        Plic,
+        RiscV,
+        RiscVHart,
+    },
+    parse_madt_in,
+    PmTimer,
+    Processor,
+    ProcessorInfo,
+};
+use crate::{
+    fadt::{Fadt, PsciConduit},
+    madt::MultiprocessorWakeupMailbox,
+    quirks::{QuirkDatabase, Quirks},
+    AcpiHandler,
+    AcpiResult,
+    AcpiTables,
+    FixedCapacity,
+    FixedVec,
+    PowerProfile,
+};
+use core::alloc::Allocator;
+
+/// Like [`ProcessorInfo`], but holding up to `N` application processors inline.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedProcessorInfo<const N: usize> {
+    pub boot_processor: Processor,
+    /// Application processors should be brought up in the order they're defined in this list.
+    pub application_processors: FixedVec<Processor, N>,
+}
+
+impl<const N: usize> FixedProcessorInfo<N> {
+    /// Copy `processor_info`, failing with `AcpiError::AllocError` if it has more than `N` application processors.
+    pub fn from_processor_info<A>(processor_info: &ProcessorInfo<A>) -> AcpiResult<Self>
+    where
+        A: Allocator,
+    {
+        Ok(FixedProcessorInfo {
+            boot_processor: processor_info.boot_processor,
+            application_processors: FixedVec::from_slice(&processor_info.application_processors)?,
+        })
+    }
+}
+
+/// Like [`Apic`], but holding up to `N` of each kind of entry inline.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedApic<const N: usize> {
+    pub local_apic_address: u64,
+    pub io_apics: FixedVec<IoApic, N>,
+    pub local_apic_nmi_lines: FixedVec<NmiLine, N>,
+    pub interrupt_source_overrides: FixedVec<InterruptSourceOverride, N>,
+    pub nmi_sources: FixedVec<NmiSource, N>,
+
+    /// If this field is set, you must remap and mask all the lines of the legacy PIC, even if
+    /// you choose to use the APIC. It's recommended that you do this even if ACPI does not
+    /// require you to.
+    pub also_has_legacy_pics: bool,
+}
+
+impl<const N: usize> FixedApic<N> {
+    pub fn from_apic<A>(apic: &Apic<A>) -> AcpiResult<Self>
+    where
+        A: Allocator,
+    {
+        Ok(FixedApic {
+            local_apic_address: apic.local_apic_address,
+            io_apics: FixedVec::from_slice(&apic.io_apics)?,
+            local_apic_nmi_lines: FixedVec::from_slice(&apic.local_apic_nmi_lines)?,
+            interrupt_source_overrides: FixedVec::from_slice(&apic.interrupt_source_overrides)?,
+            nmi_sources: FixedVec::from_slice(&apic.nmi_sources)?,
+            also_has_legacy_pics: apic.also_has_legacy_pics,
+        })
+    }
+
+    /// See [`Apic::irq_routing`].
+    pub fn irq_routing(&self) -> IrqRouting {
+        IrqRouting::new(&self.interrupt_source_overrides, &self.nmi_sources)
+    }
+
+    /// See [`Apic::nmi_lines_of`].
+    pub fn nmi_lines_of(&self, processor_uid: u32) -> impl Iterator<Item = &NmiLine> {
+        self.local_apic_nmi_lines.iter().filter(move |nmi_line| match nmi_line.processor {
+            NmiProcessor::All => true,
+            NmiProcessor::ProcessorUid(uid) => uid == processor_uid,
+        })
+    }
+}
+
+/// Like [`Gic`], but holding up to `N` of each kind of entry inline.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedGic<const N: usize> {
+    pub distributor: GicDistributor,
+    /// The CPU interfaces of each processor, in the order they appear in the MADT.
+    pub cpu_interfaces: FixedVec<GicCpuInterface, N>,
+    pub redistributors: FixedVec<GicRedistributor, N>,
+    pub msi_frames: FixedVec<GicMsiFrame, N>,
+    pub interrupt_translation_services: FixedVec<GicInterruptTranslationService, N>,
+}
+
+impl<const N: usize> FixedGic<N> {
+    pub fn from_gic<A>(gic: &Gic<A>) -> AcpiResult<Self>
+    where
+        A: Allocator,
+    {
+        Ok(FixedGic {
+            distributor: gic.distributor,
+            cpu_interfaces: FixedVec::from_slice(&gic.cpu_interfaces)?,
+            redistributors: FixedVec::from_slice(&gic.redistributors)?,
+            msi_frames: FixedVec::from_slice(&gic.msi_frames)?,
+            interrupt_translation_services: FixedVec::from_slice(&gic.interrupt_translation_services)?,
+        })
+    }
+
+    /// See [`Gic::cpu_interface_by_mpidr`].
+    pub fn cpu_interface_by_mpidr(&self, mpidr: u64) -> Option<&GicCpuInterface> {
+        const AFFINITY_MASK: u64 = 0xff_00ff_ffff;
+        self.cpu_interfaces.iter().find(|interface| interface.mpidr & AFFINITY_MASK == mpidr & AFFINITY_MASK)
+    }
+}
+
+/// Like [`RiscV`], but holding up to `N` of each kind of entry inline.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedRiscV<const N: usize> {
+    /// The local interrupt controllers of each hart, in the order they appear in the MADT.
+    pub harts: FixedVec<RiscVHart, N>,
+    /// This will be `None` if the harts don't have IMSICs.
+    pub imsic: Option<Imsic>,
+    pub aplics: FixedVec<Aplic, N>,
+    pub plics: FixedVec<Plic, N>,
+}
+
+impl<const N: usize> FixedRiscV<N> {
+    pub fn from_riscv<A>(riscv: &RiscV<A>) -> AcpiResult<Self>
+    where
+        A: Allocator,
+    {
+        Ok(FixedRiscV {
+            harts: FixedVec::from_slice(&riscv.harts)?,
+            imsic: riscv.imsic,
+            aplics: FixedVec::from_slice(&riscv.aplics)?,
+            plics: FixedVec::from_slice(&riscv.plics)?,
+        })
+    }
+
+    pub fn hart_by_id(&self, hart_id: u64) -> Option<&RiscVHart> {
+        self.harts.iter().find(|hart| hart.hart_id == hart_id)
+    }
+}
+
+/// Like [`InterruptModel`], but holding up to `N` of each kind of entry inline.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum FixedInterruptModel<const N: usize> {
+    /// See [`InterruptModel::Unknown`].
+    Unknown,
+    Apic(FixedApic<N>),
+    Gic(FixedGic<N>),
+    RiscV(FixedRiscV<N>),
+}
+
+impl<const N: usize> FixedInterruptModel<N> {
+    pub fn from_interrupt_model<A>(interrupt_model: &InterruptModel<A>) -> AcpiResult<Self>
+    where
+        A: Allocator,
+    {
+        Ok(match interrupt_model {
+            InterruptModel::Unknown => FixedInterruptModel::Unknown,
+            InterruptModel::Apic(apic) => FixedInterruptModel::Apic(FixedApic::from_apic(apic)?),
+            InterruptModel::Gic(gic) => FixedInterruptModel::Gic(FixedGic::from_gic(gic)?),
+            InterruptModel::RiscV(riscv) => FixedInterruptModel::RiscV(FixedRiscV::from_riscv(riscv)?),
+        })
+    }
+}
+
+/// Like [`PlatformInfo`](super::PlatformInfo), but holding up to `N` of each kind of MADT entry (and up to `N`
+/// application processors) inline, so that it can be constructed without an allocator. It doesn't include the
+/// processor topology, which needs far more space than the MADT - use `PlatformInfo` for that, once an allocator
+/// is available.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedPlatformInfo<const N: usize> {
+    pub power_profile: PowerProfile,
+    /// If this is `true`, the platform is a hardware-reduced ACPI platform, and doesn't have the fixed hardware
+    /// register blocks (see [`Fadt::is_hardware_reduced`]).
+    pub is_hardware_reduced: bool,
+    pub interrupt_model: FixedInterruptModel<N>,
+    pub processor_info: Option<FixedProcessorInfo<N>>,
+    pub pm_timer: Option<PmTimer>,
+    pub psci_conduit: Option<PsciConduit>,
+    pub multiprocessor_wakeup_mailbox: Option<MultiprocessorWakeupMailbox>,
+    /// The firmware workarounds that were applied when this was constructed.
+    pub quirks: Quirks,
+}
+
+impl<const N: usize> FixedPlatformInfo<N> {
+    /// Construct a `FixedPlatformInfo`, applying the workarounds for the platform from the built-in
+    /// [`QuirkDatabase`]. The MADT is parsed into `scratch` before being copied into the result, and `scratch`
+    /// is reset afterwards, so it can be reused (or be a local in the caller's stack frame). Fails with
+    /// `AcpiError::AllocError` if `scratch` is too small, or the platform has more than `N` of any entry.
+    pub fn new<H, const S: usize>(tables: &AcpiTables<H>, scratch: &mut FixedCapacity<S>) -> AcpiResult<Self>
+    where
+        H: AcpiHandler,
+    {
+        FixedPlatformInfo::new_with_quirks(tables, scratch, &QuirkDatabase::new())
+    }
+
+    /// Construct a `FixedPlatformInfo`, applying the workarounds for the platform from `quirk_database`.
+    pub fn new_with_quirks<H, const S: usize>(
+        tables: &AcpiTables<H>,
+        scratch: &mut FixedCapacity<S>,
+        quirk_database: &QuirkDatabase<'_>,
+    ) -> AcpiResult<Self>
+    where
+        H: AcpiHandler,
+    {
+        let fadt = tables.find_table::<Fadt>()?;
+        let quirks = quirk_database.quirks_for(&fadt);
+
+        scratch.reset();
+        let parsed = parse_madt_in(tables, &*scratch, quirks).and_then(
+            |(interrupt_model, processor_info, multiprocessor_wakeup_mailbox)| {
+                Ok((
+                    FixedInterruptModel::from_interrupt_model(&interrupt_model)?,
+                    processor_info.as_ref().map(FixedProcessorInfo::from_processor_info).transpose()?,
+                    multiprocessor_wakeup_mailbox,
+                ))
+            },
+        );
+        scratch.reset();
+        let (interrupt_model, processor_info, multiprocessor_wakeup_mailbox) = parsed?;
+
+        Ok(FixedPlatformInfo {
+            power_profile: fadt.power_profile(),
+            is_hardware_reduced: fadt.is_hardware_reduced(),
+            interrupt_model,
+            processor_info,
+            pm_timer: PmTimer::new_with_quirks(&fadt, quirks)?,
+            psci_conduit: fadt.psci_conduit(),
+            multiprocessor_wakeup_mailbox,
+            quirks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{platform::ProcessorState, ManagedVec};
+
+    #[test]
+    fn test_from_processor_info() {
+        let storage = FixedCapacity::<256>::new();
+        let processor = |uid| Processor {
+            processor_uid: uid,
+            local_apic_id: uid,
+            state: ProcessorState::WaitingForSipi,
+            is_ap: true,
+        };
+
+        let mut application_processors = ManagedVec::new_in(&storage);
+        for uid in 1..4 {
+            application_processors.try_push(processor(uid)).unwrap();
+        }
+        let processor_info = ProcessorInfo::new(
+            Processor { is_ap: false, state: ProcessorState::Running, ..processor(0) },
+            application_processors,
+        );
+
+        let fixed = FixedProcessorInfo::<4>::from_processor_info(&processor_info).unwrap();
+        assert_eq!(fixed.boot_processor, processor_info.boot_processor);
+        assert_eq!(&*fixed.application_processors, &*processor_info.application_processors);
+        assert!(FixedProcessorInfo::<2>::from_processor_info(&processor_info).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use alloc::vec::Vec;
+use crate::ManagedVec;
 use core::alloc::Allocator;
 
 #[derive(Debug, Clone, Copy)]
@@ -14,6 +14,8 @@ pub struct IoApic {
 pub struct NmiLine {
     pub processor: NmiProcessor,
     pub line: LocalInterruptLine,
+    pub polarity: Polarity,
+    pub trigger_mode: TriggerMode,
 }
 
 /// Indicates which local interrupt line will be utilized by an external interrupt. Specifically,
@@ -82,10 +84,10 @@ where
     A: Allocator,
 {
     pub local_apic_address: u64,
-    pub io_apics: Vec<IoApic, A>,
-    pub local_apic_nmi_lines: Vec<NmiLine, A>,
-    pub interrupt_source_overrides: Vec<InterruptSourceOverride, A>,
-    pub nmi_sources: Vec<NmiSource, A>,
+    pub io_apics: ManagedVec<IoApic, A>,
+    pub local_apic_nmi_lines: ManagedVec<NmiLine, A>,
+    pub interrupt_source_overrides: ManagedVec<InterruptSourceOverride, A>,
+    pub nmi_sources: ManagedVec<NmiSource, A>,
 
     /// If this field is set, you must remap and mask all the lines of the legacy PIC, even if
     /// you choose to use the APIC. It's recommended that you do this even if ACPI does not
@@ -99,10 +101,10 @@ where
 {
     pub(crate) fn new(
         local_apic_address: u64,
-        io_apics: Vec<IoApic, A>,
-        local_apic_nmi_lines: Vec<NmiLine, A>,
-        interrupt_source_overrides: Vec<InterruptSourceOverride, A>,
-        nmi_sources: Vec<NmiSource, A>,
+        io_apics: ManagedVec<IoApic, A>,
+        local_apic_nmi_lines: ManagedVec<NmiLine, A>,
+        interrupt_source_overrides: ManagedVec<InterruptSourceOverride, A>,
+        nmi_sources: ManagedVec<NmiSource, A>,
         also_has_legacy_pics: bool,
     ) -> Self {
         Self {
@@ -114,6 +116,331 @@ where
             also_has_legacy_pics,
         }
     }
+
+    /// Resolve how each of the ISA IRQs is routed to the I/O APICs, after applying the interrupt source
+    /// overrides and NMI sources.
+    pub fn irq_routing(&self) -> IrqRouting {
+        IrqRouting::new(&self.interrupt_source_overrides, &self.nmi_sources)
+    }
+
+    /// Get the local APIC lines that are connected to NMI on the processor with the given UID. This includes
+    /// lines that are connected on all processors.
+    pub fn nmi_lines_of(&self, processor_uid: u32) -> impl Iterator<Item = &NmiLine> {
+        self.local_apic_nmi_lines.iter().filter(move |nmi_line| match nmi_line.processor {
+            NmiProcessor::All => true,
+            NmiProcessor::ProcessorUid(uid) => uid == processor_uid,
+        })
+    }
+}
+
+/// Describes where an ISA IRQ is routed. Unlike in an [`InterruptSourceOverride`], the polarity and trigger mode
+/// are always resolved, and so will never be `SameAsBus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsaIrqRoute {
+    pub global_system_interrupt: u32,
+    pub polarity: Polarity,
+    pub trigger_mode: TriggerMode,
+}
+
+/// The resolved routing of the legacy ISA IRQs (`0` to `15`) on an APIC platform. Each IRQ is identity-mapped
+/// to the GSI of the same number, active-high and edge-triggered (as for the ISA bus), unless an interrupt
+/// source override says otherwise.
+#[derive(Debug, Clone, Copy)]
+pub struct IrqRouting {
+    routes: [Option<IsaIrqRoute>; 16],
+}
+
+impl IrqRouting {
+    pub const NUM_ISA_IRQS: u8 = 16;
+
+    pub fn new(interrupt_source_overrides: &[InterruptSourceOverride], nmi_sources: &[NmiSource]) -> IrqRouting {
+        let mut routes: [Option<IsaIrqRoute>; Self::NUM_ISA_IRQS as usize] = core::array::from_fn(|isa_irq| {
+            Some(IsaIrqRoute {
+                global_system_interrupt: isa_irq as u32,
+                polarity: Polarity::ActiveHigh,
+                trigger_mode: TriggerMode::Edge,
+            })
+        });
+
+        for isa_irq in 0..Self::NUM_ISA_IRQS {
+            let gsi = isa_irq as u32;
+
+            /*
+             * An identity-mapped IRQ is not connected if another IRQ has been overridden onto its GSI. For
+             * example, IRQ 2 is usually lost when IRQ 0 (the PIT) is overridden onto GSI 2.
+             */
+            let is_overridden = interrupt_source_overrides.iter().any(|entry| entry.isa_source == isa_irq);
+            let gsi_is_taken = interrupt_source_overrides
+                .iter()
+                .any(|entry| entry.isa_source != isa_irq && entry.global_system_interrupt == gsi);
+            if !is_overridden && gsi_is_taken {
+                routes[isa_irq as usize] = None;
+            }
+        }
+
+        for entry in interrupt_source_overrides.iter().filter(|entry| entry.isa_source < Self::NUM_ISA_IRQS) {
+            routes[entry.isa_source as usize] = Some(IsaIrqRoute {
+                global_system_interrupt: entry.global_system_interrupt,
+                polarity: match entry.polarity {
+                    Polarity::SameAsBus => Polarity::ActiveHigh,
+                    polarity => polarity,
+                },
+                trigger_mode: match entry.trigger_mode {
+                    TriggerMode::SameAsBus => TriggerMode::Edge,
+                    trigger_mode => trigger_mode,
+                },
+            });
+        }
+
+        // GSIs that are NMI sources can't be used by devices
+        for route in routes.iter_mut() {
+            if let Some(IsaIrqRoute { global_system_interrupt, .. }) = *route {
+                if nmi_sources.iter().any(|nmi| nmi.global_system_interrupt == global_system_interrupt) {
+                    *route = None;
+                }
+            }
+        }
+
+        IrqRouting { routes }
+    }
+
+    /// Get the route of the given ISA IRQ. Returns `None` if `isa_irq` is not an ISA IRQ, or if it is not
+    /// connected to a usable GSI.
+    pub fn route(&self, isa_irq: u8) -> Option<IsaIrqRoute> {
+        self.routes.get(isa_irq as usize).copied().flatten()
+    }
+
+    /// Find the ISA IRQ that is routed to the given GSI, if there is one.
+    pub fn isa_irq_of(&self, global_system_interrupt: u32) -> Option<u8> {
+        self.routes
+            .iter()
+            .position(|route| route.is_some_and(|route| route.global_system_interrupt == global_system_interrupt))
+            .map(|isa_irq| isa_irq as u8)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GicVersion {
+    /// The version of the GIC is not described by the MADT, and should be discovered from the hardware.
+    FromHardware,
+    V1,
+    V2,
+    V3,
+    V4,
+    Reserved(u8),
+}
+
+/// Describes the GIC distributor, which routes shared peripheral interrupts (SPIs) to the CPU interfaces.
+#[derive(Debug, Clone, Copy)]
+pub struct GicDistributor {
+    pub id: u32,
+    /// The physical address of the distributor's registers.
+    pub base_address: u64,
+    /// The global system interrupt number where this distributor's interrupt inputs start. This is always `0` on
+    /// current platforms.
+    pub system_vector_base: u32,
+    pub version: GicVersion,
+}
+
+/// Describes the GIC CPU interface of a processor. On GIC platforms, this is where the per-processor
+/// information (the processor's MPIDR and its private interrupts) is described.
+#[derive(Debug, Clone, Copy)]
+pub struct GicCpuInterface {
+    /// Corresponds to the `_UID` object of the processor's `Device` in AML.
+    pub processor_uid: u32,
+    /// The GIC's identifier for this CPU interface. This is used to target SGIs on GICv2.
+    pub cpu_interface_number: u32,
+    /// The affinity fields of the processor's `MPIDR_EL1` register. Will be `0` on platforms that describe an
+    /// ACPI version older than 5.1.
+    pub mpidr: u64,
+    /// If this is `false`, the processor is unusable, and you must not attempt to bring it up (unless
+    /// `is_online_capable` is set, in which case it may be enabled later).
+    pub is_enabled: bool,
+    pub is_online_capable: bool,
+    /// The physical address of the processor's GIC CPU interface registers (used by GICv1 and GICv2).
+    pub gic_registers_address: u64,
+    pub gic_virtual_registers_address: u64,
+    pub gic_hypervisor_registers_address: u64,
+    /// The physical address of the processor's redistributor (on GICv3 and later), if it is not described by a
+    /// [`GicRedistributor`] range instead.
+    pub gicr_base_address: Option<u64>,
+    /// The GSIV of the processor's performance monitoring interrupt, if it has one.
+    pub performance_interrupt: Option<u32>,
+    pub performance_interrupt_trigger_mode: TriggerMode,
+    /// The GSIV of the processor's virtual GIC maintenance interrupt, if it has one.
+    pub vgic_maintenance_interrupt: Option<u32>,
+    pub vgic_maintenance_interrupt_trigger_mode: TriggerMode,
+    /// The version of the ARM parking protocol the processor supports, or `0` if it doesn't support it (in which
+    /// case PSCI should be used to bring it up).
+    pub parking_protocol_version: u32,
+    /// The physical address of the processor's parking protocol mailbox.
+    pub parked_address: u64,
+    /// The GSIV of the processor's Statistical Profiling Extension overflow interrupt, if it has one.
+    pub spe_overflow_interrupt: Option<u16>,
+    pub processor_power_efficiency_class: u8,
+}
+
+/// Describes a range of memory that contains the redistributors (on GICv3 and later) of one or more processors.
+#[derive(Debug, Clone, Copy)]
+pub struct GicRedistributor {
+    pub discovery_range_base_address: u64,
+    pub discovery_range_length: u32,
+}
+
+/// Describes a GICv2m MSI frame, which converts memory writes into SPIs.
+#[derive(Debug, Clone, Copy)]
+pub struct GicMsiFrame {
+    pub id: u32,
+    pub base_address: u64,
+    /// The range of SPIs the frame can generate, as `(spi_base, spi_count)`. If this is `None`, the range should
+    /// be read from the frame's `MSI_TYPER` register instead.
+    pub spi_range: Option<(u16, u16)>,
+}
+
+/// Describes a GIC Interrupt Translation Service (ITS), which converts memory writes into LPIs on GICv3 and
+/// later.
+#[derive(Debug, Clone, Copy)]
+pub struct GicInterruptTranslationService {
+    pub id: u32,
+    pub base_address: u64,
+}
+
+#[derive(Debug)]
+pub struct Gic<A>
+where
+    A: Allocator,
+{
+    pub distributor: GicDistributor,
+    /// The CPU interfaces of each processor, in the order they appear in the MADT. The first processor is not
+    /// necessarily the boot processor on GIC platforms - the boot processor can be found by matching its MPIDR.
+    pub cpu_interfaces: ManagedVec<GicCpuInterface, A>,
+    pub redistributors: ManagedVec<GicRedistributor, A>,
+    pub msi_frames: ManagedVec<GicMsiFrame, A>,
+    pub interrupt_translation_services: ManagedVec<GicInterruptTranslationService, A>,
+}
+
+impl<A> Gic<A>
+where
+    A: Allocator,
+{
+    pub(crate) fn new(
+        distributor: GicDistributor,
+        cpu_interfaces: ManagedVec<GicCpuInterface, A>,
+        redistributors: ManagedVec<GicRedistributor, A>,
+        msi_frames: ManagedVec<GicMsiFrame, A>,
+        interrupt_translation_services: ManagedVec<GicInterruptTranslationService, A>,
+    ) -> Self {
+        Self { distributor, cpu_interfaces, redistributors, msi_frames, interrupt_translation_services }
+    }
+
+    /// Find the CPU interface of the processor with the given MPIDR. Only the affinity fields of `mpidr` are
+    /// compared, so the value of `MPIDR_EL1` can be passed directly.
+    pub fn cpu_interface_by_mpidr(&self, mpidr: u64) -> Option<&GicCpuInterface> {
+        const AFFINITY_MASK: u64 = 0xff_00ff_ffff;
+        self.cpu_interfaces.iter().find(|interface| interface.mpidr & AFFINITY_MASK == mpidr & AFFINITY_MASK)
+    }
+}
+
+/// Describes the local interrupt controller (RINTC) of a RISC-V hart.
+#[derive(Debug, Clone, Copy)]
+pub struct RiscVHart {
+    pub hart_id: u64,
+    /// Corresponds to the `_UID` object of the hart's `Device` in AML.
+    pub processor_uid: u32,
+    /// If this is `false`, the hart is unusable, and you must not attempt to start it (unless
+    /// `is_online_capable` is set, in which case it may be enabled later).
+    pub is_enabled: bool,
+    pub is_online_capable: bool,
+    /// Identifies the APLIC or PLIC that delivers external interrupts directly to this hart, if the harts don't
+    /// use IMSICs. The top 8 bits are the ID of the APLIC or PLIC, and the bottom 16 bits are the index of the
+    /// APLIC's interrupt delivery control (IDC) structure, or the PLIC's context, for this hart.
+    pub external_interrupt_controller_id: u32,
+    /// The physical address of the hart's supervisor-level IMSIC interrupt file, if the hart has an IMSIC.
+    pub imsic_base_address: Option<u64>,
+    pub imsic_size: u32,
+}
+
+impl RiscVHart {
+    /// The ID of the APLIC or PLIC that delivers external interrupts directly to this hart.
+    pub fn external_interrupt_controller(&self) -> u8 {
+        (self.external_interrupt_controller_id >> 24) as u8
+    }
+
+    /// The index of the APLIC's IDC structure, or the PLIC's context, used to deliver interrupts to this hart.
+    pub fn external_interrupt_controller_index(&self) -> u16 {
+        self.external_interrupt_controller_id as u16
+    }
+}
+
+/// Describes the properties shared by the Incoming MSI Controllers (IMSICs) of all of the harts.
+#[derive(Debug, Clone, Copy)]
+pub struct Imsic {
+    pub num_supervisor_interrupt_identities: u16,
+    pub num_guest_interrupt_identities: u16,
+    pub guest_index_bits: u8,
+    pub hart_index_bits: u8,
+    pub group_index_bits: u8,
+    pub group_index_shift: u8,
+}
+
+/// Describes an Advanced Platform-Level Interrupt Controller (APLIC), which delivers wired interrupts either
+/// directly to harts, or as MSIs to their IMSICs.
+#[derive(Debug, Clone, Copy)]
+pub struct Aplic {
+    pub id: u8,
+    pub hardware_id: [u8; 8],
+    /// The number of interrupt delivery control (IDC) structures. This is `0` if the APLIC forwards interrupts as
+    /// MSIs.
+    pub num_idcs: u16,
+    pub num_interrupt_sources: u16,
+    /// The global system interrupt number where this APLIC's interrupt inputs start.
+    pub global_system_interrupt_base: u32,
+    pub base_address: u64,
+    pub size: u32,
+}
+
+/// Describes a Platform-Level Interrupt Controller (PLIC).
+#[derive(Debug, Clone, Copy)]
+pub struct Plic {
+    pub id: u8,
+    pub hardware_id: [u8; 8],
+    pub num_interrupt_sources: u16,
+    pub max_priority: u16,
+    /// The global system interrupt number where this PLIC's interrupt inputs start.
+    pub global_system_interrupt_base: u32,
+    pub base_address: u64,
+    pub size: u32,
+}
+
+#[derive(Debug)]
+pub struct RiscV<A>
+where
+    A: Allocator,
+{
+    /// The local interrupt controllers of each hart, in the order they appear in the MADT.
+    pub harts: ManagedVec<RiscVHart, A>,
+    /// This will be `None` if the harts don't have IMSICs.
+    pub imsic: Option<Imsic>,
+    pub aplics: ManagedVec<Aplic, A>,
+    pub plics: ManagedVec<Plic, A>,
+}
+
+impl<A> RiscV<A>
+where
+    A: Allocator,
+{
+    pub(crate) fn new(
+        harts: ManagedVec<RiscVHart, A>,
+        imsic: Option<Imsic>,
+        aplics: ManagedVec<Aplic, A>,
+        plics: ManagedVec<Plic, A>,
+    ) -> Self {
+        Self { harts, imsic, aplics, plics }
+    }
+
+    pub fn hart_by_id(&self, hart_id: u64) -> Option<&RiscVHart> {
+        self.harts.iter().find(|hart| hart.hart_id == hart_id)
+    }
 }
 
 #[derive(Debug)]
@@ -130,4 +457,14 @@ where
     /// XAPIC, or X2APIC). These are likely to be found on x86 and x86_64 systems and are made up of a Local APIC
     /// for each core and one or more I/O APICs to handle external interrupts.
     Apic(Apic<A>),
+
+    /// Describes an interrupt controller based around the ARM Generic Interrupt Controller (GIC). These are found
+    /// on ARM systems, and are made up of a distributor, a CPU interface for each processor, and (on GICv3 and
+    /// later) redistributors and Interrupt Translation Services.
+    Gic(Gic<A>),
+
+    /// Describes the interrupt controllers of a RISC-V platform. These are made up of a local interrupt
+    /// controller for each hart, and either APLICs (optionally paired with an IMSIC for each hart) or PLICs to
+    /// handle external interrupts.
+    RiscV(RiscV<A>),
 }
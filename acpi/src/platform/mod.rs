@@ -1,9 +1,33 @@
+#[cfg(feature = "fixed_capacity")]
+mod fixed;
 pub mod interrupt;
+pub mod numa;
+#[cfg(feature = "aml")]
+pub mod pci;
+pub mod topology;
 
-use crate::{address::GenericAddress, fadt::Fadt, madt::Madt, AcpiError, AcpiHandler, AcpiTables, PowerProfile};
-use core::alloc::Allocator;
+use crate::{
+    address::{GenericAddress, RegisterHandler},
+    fadt::{Fadt, PsciConduit},
+    madt::{Madt, MultiprocessorWakeupMailbox},
+    pptt::Pptt,
+    quirks::{QuirkDatabase, Quirks},
+    AcpiError,
+    AcpiHandler,
+    AcpiResult,
+    AcpiTables,
+    PowerProfile,
+};
+use core::{alloc::Allocator, hint};
 use interrupt::InterruptModel;
 
+#[cfg(feature = "fixed_capacity")]
+pub use fixed::*;
+pub use numa::NumaInfo;
+#[cfg(feature = "aml")]
+pub use pci::route_pci_interrupt;
+pub use topology::ProcessorTopology;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProcessorState {
     /// A processor in this state is unusable, and you must not attempt to bring it up.
@@ -42,20 +66,21 @@ where
 {
     pub boot_processor: Processor,
     /// Application processors should be brought up in the order they're defined in this list.
-    pub application_processors: alloc::vec::Vec<Processor, A>,
+    pub application_processors: crate::ManagedVec<Processor, A>,
 }
 
 impl<A> ProcessorInfo<A>
 where
     A: Allocator,
 {
-    pub(crate) fn new(boot_processor: Processor, application_processors: alloc::vec::Vec<Processor, A>) -> Self {
+    pub(crate) fn new(boot_processor: Processor, application_processors: crate::ManagedVec<Processor, A>) -> Self {
         Self { boot_processor, application_processors }
     }
 }
 
-/// Information about the ACPI Power Management Timer (ACPI PM Timer).
-#[derive(Debug)]
+/// Information about the ACPI Power Management Timer (ACPI PM Timer). The timer is a free-running counter that
+/// increments at [`PmTimer::FREQUENCY`], and wraps around after 24 or 32 bits.
+#[derive(Clone, Copy, Debug)]
 pub struct PmTimer {
     /// A generic address to the register block of ACPI PM Timer.
     pub base: GenericAddress,
@@ -65,11 +90,131 @@ pub struct PmTimer {
 
 impl PmTimer {
     pub fn new(fadt: &Fadt) -> Result<Option<PmTimer>, AcpiError> {
-        match fadt.pm_timer_block()? {
-            Some(base) => Ok(Some(PmTimer { base, supports_32bit: { fadt.flags }.pm_timer_is_32_bit() })),
+        PmTimer::new_with_quirks(fadt, Quirks::NONE)
+    }
+
+    /// Like [`PmTimer::new`], but applying the workarounds in `quirks` for firmware that describes the timer
+    /// incorrectly.
+    pub fn new_with_quirks(fadt: &Fadt, quirks: Quirks) -> Result<Option<PmTimer>, AcpiError> {
+        match fadt.pm_timer_block_with_policy(quirks.address_policy())? {
+            Some(base) => {
+                let supports_32bit = { fadt.flags }.pm_timer_is_32_bit() && !quirks.pm_timer_is_24_bit;
+                Ok(Some(PmTimer { base, supports_32bit }))
+            }
             None => Ok(None),
         }
     }
+
+    /// The frequency of the PM timer, in Hz.
+    pub const FREQUENCY: u64 = 3_579_545;
+
+    /// The mask of the bits of the counter that are implemented.
+    pub fn counter_mask(&self) -> u32 {
+        if self.supports_32bit {
+            u32::MAX
+        } else {
+            0x00ff_ffff
+        }
+    }
+
+    /// Read the current value of the counter.
+    pub fn read<H>(&self, handler: &H) -> AcpiResult<u32>
+    where
+        H: RegisterHandler,
+    {
+        Ok(self.base.read_register(handler)? as u32 & self.counter_mask())
+    }
+
+    /// The number of ticks from `start` to `end`, accounting for the counter wrapping around (at most once)
+    /// between them.
+    pub fn ticks_between(&self, start: u32, end: u32) -> u32 {
+        end.wrapping_sub(start) & self.counter_mask()
+    }
+
+    /// Convert a number of PM timer ticks to nanoseconds.
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        (ticks as u128 * 1_000_000_000 / PmTimer::FREQUENCY as u128) as u64
+    }
+
+    /// Convert a duration in microseconds to a number of PM timer ticks, rounding up.
+    pub fn micros_to_ticks(micros: u64) -> u64 {
+        (micros as u128 * PmTimer::FREQUENCY as u128).div_ceil(1_000_000) as u64
+    }
+
+    /// Create a [`PmTimerClock`], which counts the ticks since it was created without wrapping around.
+    pub fn clock<H>(&self, handler: &H) -> AcpiResult<PmTimerClock>
+    where
+        H: RegisterHandler,
+    {
+        Ok(PmTimerClock { timer: *self, last: self.read(handler)?, ticks: 0 })
+    }
+
+    /// Busy-wait for the given number of microseconds.
+    pub fn stall<H>(&self, handler: &H, micros: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let mut clock = self.clock(handler)?;
+        let target = PmTimer::micros_to_ticks(micros);
+        while clock.ticks(handler)? < target {
+            hint::spin_loop();
+        }
+
+        Ok(())
+    }
+
+    /// Measure the frequency of another clock (such as the TSC) in Hz, by reading it with `read_clock` before and
+    /// after busy-waiting on the PM timer for `interval_micros` microseconds. Longer intervals give more accurate
+    /// results; the interval can be longer than the PM timer's wrap-around period.
+    pub fn calibrate<H, F>(&self, handler: &H, interval_micros: u64, mut read_clock: F) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+        F: FnMut() -> u64,
+    {
+        let target = PmTimer::micros_to_ticks(interval_micros).max(1);
+
+        let mut clock = self.clock(handler)?;
+        let start = read_clock();
+        let mut elapsed = 0;
+        while elapsed < target {
+            hint::spin_loop();
+            elapsed = clock.ticks(handler)?;
+        }
+        let end = read_clock();
+
+        Ok((end.wrapping_sub(start) as u128 * PmTimer::FREQUENCY as u128 / elapsed as u128) as u64)
+    }
+}
+
+/// Counts the PM timer ticks since it was created, extending the PM timer's counter to 64 bits. To detect every
+/// wrap-around of the counter, [`PmTimerClock::ticks`] must be called at least once per wrap-around period, which
+/// is about 4.7 seconds for 24-bit timers, and about 20 minutes for 32-bit timers.
+#[derive(Clone, Copy, Debug)]
+pub struct PmTimerClock {
+    timer: PmTimer,
+    last: u32,
+    ticks: u64,
+}
+
+impl PmTimerClock {
+    /// The number of ticks since the clock was created. This is monotonic, as long as it is called often enough.
+    pub fn ticks<H>(&mut self, handler: &H) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        let now = self.timer.read(handler)?;
+        self.ticks += self.timer.ticks_between(self.last, now) as u64;
+        self.last = now;
+        Ok(self.ticks)
+    }
+
+    /// The number of nanoseconds since the clock was created.
+    pub fn nanos<H>(&mut self, handler: &H) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        Ok(PmTimer::ticks_to_nanos(self.ticks(handler)?))
+    }
 }
 
 /// `PlatformInfo` allows the collection of some basic information about the platform from some of the fixed-size
@@ -81,11 +226,24 @@ where
     A: Allocator,
 {
     pub power_profile: PowerProfile,
+    /// If this is `true`, the platform is a hardware-reduced ACPI platform, and doesn't have the fixed hardware
+    /// register blocks (see [`Fadt::is_hardware_reduced`]).
+    pub is_hardware_reduced: bool,
     pub interrupt_model: InterruptModel<A>,
     /// On `x86_64` platforms that support the APIC, the processor topology must also be inferred from the
     /// interrupt model. That information is stored here, if present.
     pub processor_info: Option<ProcessorInfo<A>>,
+    /// The package, core, and thread of each processor, and the caches they share, if the platform has a PPTT.
+    pub processor_topology: Option<ProcessorTopology<A>>,
     pub pm_timer: Option<PmTimer>,
+    /// On ARM platforms that implement PSCI, the conduit used to make PSCI calls (e.g. to turn processors on and
+    /// off). This is `None` on platforms that don't implement it.
+    pub psci_conduit: Option<PsciConduit>,
+    /// The mailbox used to start the application processors, on platforms that use the multiprocessor wakeup
+    /// protocol instead of INIT-SIPI-SIPI.
+    pub multiprocessor_wakeup_mailbox: Option<MultiprocessorWakeupMailbox>,
+    /// The firmware workarounds that were applied when this was constructed.
+    pub quirks: Quirks,
     /*
      * TODO: we could provide a nice view of the hardware register blocks in the FADT here.
      */
@@ -95,20 +253,71 @@ impl<A> PlatformInfo<A>
 where
     A: Allocator + Clone,
 {
+    /// Construct a `PlatformInfo`, applying the workarounds for the platform from the built-in
+    /// [`QuirkDatabase`].
     pub fn new_in<H>(tables: &AcpiTables<H>, allocator: A) -> crate::AcpiResult<Self>
+    where
+        H: AcpiHandler,
+    {
+        PlatformInfo::new_in_with_quirks(tables, allocator, &QuirkDatabase::new())
+    }
+
+    /// Construct a `PlatformInfo`, applying the workarounds for the platform from `quirk_database`.
+    pub fn new_in_with_quirks<H>(
+        tables: &AcpiTables<H>,
+        allocator: A,
+        quirk_database: &QuirkDatabase<'_>,
+    ) -> crate::AcpiResult<Self>
     where
         H: AcpiHandler,
     {
         let fadt = tables.find_table::<Fadt>()?;
+        let quirks = quirk_database.quirks_for(&fadt);
         let power_profile = fadt.power_profile();
+        let is_hardware_reduced = fadt.is_hardware_reduced();
 
-        let madt = tables.find_table::<Madt>();
-        let (interrupt_model, processor_info) = match madt {
-            Ok(madt) => madt.parse_interrupt_model_in(allocator)?,
-            Err(_) => (InterruptModel::Unknown, None),
+        let (interrupt_model, processor_info, multiprocessor_wakeup_mailbox) =
+            parse_madt_in(tables, allocator.clone(), quirks)?;
+        let processor_topology = match tables.find_table::<Pptt>() {
+            Ok(pptt) => Some(ProcessorTopology::new_in(&pptt, processor_info.as_ref(), allocator)?),
+            Err(_) => None,
         };
-        let pm_timer = PmTimer::new(&fadt)?;
+        let pm_timer = PmTimer::new_with_quirks(&fadt, quirks)?;
+        let psci_conduit = fadt.psci_conduit();
 
-        Ok(PlatformInfo { power_profile, interrupt_model, processor_info, pm_timer })
+        Ok(PlatformInfo {
+            power_profile,
+            is_hardware_reduced,
+            interrupt_model,
+            processor_info,
+            processor_topology,
+            pm_timer,
+            psci_conduit,
+            multiprocessor_wakeup_mailbox,
+            quirks,
+        })
     }
 }
+
+type ParsedMadt<A> = (InterruptModel<A>, Option<ProcessorInfo<A>>, Option<MultiprocessorWakeupMailbox>);
+
+/// Parse the interrupt model and processors from the MADT, if the platform has one, applying the workarounds in
+/// `quirks`.
+fn parse_madt_in<H, A>(tables: &AcpiTables<H>, allocator: A, quirks: Quirks) -> AcpiResult<ParsedMadt<A>>
+where
+    H: AcpiHandler,
+    A: Allocator + Clone,
+{
+    let (mut interrupt_model, processor_info, multiprocessor_wakeup_mailbox) = match tables.find_table::<Madt>() {
+        Ok(madt) => {
+            let (interrupt_model, processor_info) = madt.parse_interrupt_model_in(allocator)?;
+            (interrupt_model, processor_info, madt.multiprocessor_wakeup_mailbox())
+        }
+        Err(_) => (InterruptModel::Unknown, None, None),
+    };
+    if quirks.force_pic_mode && matches!(interrupt_model, InterruptModel::Apic(_)) {
+        interrupt_model = InterruptModel::Unknown;
+    }
+
+    Ok((interrupt_model, processor_info, multiprocessor_wakeup_mailbox))
+}
@@ -0,0 +1,123 @@
+use crate::{
+    srat::{Srat, SratEntry},
+    AcpiError,
+    AcpiHandler,
+    AcpiResult,
+    AcpiTables,
+    ManagedVec,
+};
+use core::alloc::Allocator;
+
+/// Associates a processor, by its local APIC or X2APIC ID, with a proximity domain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessorAffinity {
+    pub local_apic_id: u32,
+    pub proximity_domain: u32,
+    pub clock_domain: u32,
+}
+
+/// Associates a range of physical memory with a proximity domain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAffinity {
+    pub base_address: u64,
+    pub length: u64,
+    pub proximity_domain: u32,
+    pub hot_pluggable: bool,
+    pub non_volatile: bool,
+}
+
+/// `NumaInfo` describes the NUMA topology of the platform, as reported by the SRAT. Only enabled affinity
+/// structures are included; disabled entries should be ignored by the OS.
+#[derive(Debug)]
+pub struct NumaInfo<A>
+where
+    A: Allocator,
+{
+    pub processor_affinities: ManagedVec<ProcessorAffinity, A>,
+    pub memory_affinities: ManagedVec<MemoryAffinity, A>,
+}
+
+impl<A> NumaInfo<A>
+where
+    A: Allocator + Clone,
+{
+    pub fn new_in<H>(tables: &AcpiTables<H>, allocator: A) -> AcpiResult<Self>
+    where
+        H: AcpiHandler,
+    {
+        let srat = tables.find_table::<Srat>()?;
+
+        let mut processor_affinities = ManagedVec::new_in(allocator.clone());
+        let mut memory_affinities = ManagedVec::new_in(allocator);
+
+        for entry in srat.entries() {
+            match entry {
+                SratEntry::LocalApicAffinity(entry) if entry.is_enabled() => {
+                    processor_affinities.try_push(ProcessorAffinity {
+                        local_apic_id: entry.apic_id as u32,
+                        proximity_domain: entry.proximity_domain(),
+                        clock_domain: entry.clock_domain,
+                    })
+                }
+                SratEntry::LocalX2ApicAffinity(entry) if entry.is_enabled() => {
+                    processor_affinities.try_push(ProcessorAffinity {
+                        local_apic_id: entry.x2apic_id,
+                        proximity_domain: entry.proximity_domain,
+                        clock_domain: entry.clock_domain,
+                    })
+                }
+                SratEntry::MemoryAffinity(entry) if entry.is_enabled() => {
+                    memory_affinities.try_push(MemoryAffinity {
+                        base_address: entry.base_address(),
+                        length: entry.length(),
+                        proximity_domain: entry.proximity_domain,
+                        hot_pluggable: entry.is_hot_pluggable(),
+                        non_volatile: entry.is_non_volatile(),
+                    })
+                }
+                _ => Ok(()),
+            }
+            .map_err(|_| AcpiError::AllocError)?;
+        }
+
+        Ok(NumaInfo { processor_affinities, memory_affinities })
+    }
+}
+
+impl<A> NumaInfo<A>
+where
+    A: Allocator,
+{
+    /// Get the proximity domain of the processor with the given local APIC or X2APIC ID. Returns `None` if the
+    /// SRAT doesn't describe that processor.
+    pub fn proximity_domain_for_apic_id(&self, local_apic_id: u32) -> Option<u32> {
+        self.processor_affinities
+            .iter()
+            .find(|affinity| affinity.local_apic_id == local_apic_id)
+            .map(|affinity| affinity.proximity_domain)
+    }
+
+    /// Get the proximity domain of the memory range containing the given physical address. Returns `None` if the
+    /// address isn't covered by any memory affinity structure.
+    pub fn proximity_domain_for_address(&self, address: u64) -> Option<u32> {
+        self.memory_affinities
+            .iter()
+            .find(|affinity| address >= affinity.base_address && address - affinity.base_address < affinity.length)
+            .map(|affinity| affinity.proximity_domain)
+    }
+
+    /// Iterate over the distinct proximity domains described by the SRAT, in the order they're first mentioned.
+    pub fn proximity_domains(&self) -> impl Iterator<Item = u32> + '_ {
+        let processor_domains = self.processor_affinities.iter().map(|affinity| affinity.proximity_domain);
+        let memory_domains = self.memory_affinities.iter().map(|affinity| affinity.proximity_domain);
+        let all_domains = processor_domains.chain(memory_domains);
+
+        all_domains.clone().enumerate().filter_map(move |(i, domain)| {
+            if all_domains.clone().take(i).any(|earlier| earlier == domain) {
+                None
+            } else {
+                Some(domain)
+            }
+        })
+    }
+}
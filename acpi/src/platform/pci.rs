@@ -0,0 +1,325 @@
+//! Resolution of PCI interrupt pins (`INTA#` to `INTD#`) to the GSIs they are connected to on APIC platforms.
+//!
+//! The interrupts of the devices on a root bus are described by the `_PRT` of the root bridge. Each entry either
+//! connects a pin to a GSI directly, or to a PCI interrupt link device, whose `_CRS` describes the interrupt the
+//! link is currently routed to (and whose `_PRS` and `_SRS` describe and change the interrupts it can be routed
+//! to). Devices behind PCI-to-PCI bridges are routed through the `_PRT` of the bridge if it has one, and otherwise
+//! have their pins rotated ("swizzled") onto the pins of the bridge, as described by the PCI-to-PCI Bridge
+//! Specification.
+//!
+//! The firmware only produces the APIC routing if `\_PIC(1)` has been evaluated, so this should be done before
+//! routing any interrupts.
+
+use crate::{
+    device,
+    platform::interrupt::{InterruptSourceOverride, Polarity, TriggerMode},
+    AcpiError,
+    AcpiResult,
+};
+use alloc::vec::Vec;
+use aml::{
+    pci_routing::{PciRouteType, PciRoutingTable, Pin},
+    resource::{self, InterruptPolarity, InterruptTrigger, IrqDescriptor, Resource},
+    value::Args,
+    AmlContext,
+    AmlName,
+    LevelType,
+};
+use bit_field::BitField;
+
+#[derive(Debug)]
+pub enum PciRoutingError {
+    /// The namespace doesn't contain a PCI root bridge (with a `_HID` of `PNP0A03` or `PNP0A08`) for the
+    /// requested segment and bus.
+    NoRootBridge,
+    /// The root bridge the device is under has no `_PRT`.
+    NoRoutingTable(AmlName),
+    /// The bus isn't reachable through the PCI-to-PCI bridges below the root bridge.
+    BusNotFound(u8),
+    /// The `_CRS` or `_PRS` of a link device doesn't contain the interrupt descriptor selected by the `_PRT`.
+    InvalidLinkResources(AmlName),
+    /// A link device isn't routed to an interrupt, and can't be configured to route to one.
+    LinkNotConfigurable(AmlName),
+}
+
+/// The GSI a PCI interrupt pin is connected to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PciInterruptRoute {
+    pub global_system_interrupt: u32,
+    pub polarity: Polarity,
+    pub trigger_mode: TriggerMode,
+    /// The link device the pin is routed through, if it isn't connected to the GSI directly.
+    pub link_object: Option<AmlName>,
+}
+
+/*
+ * Offsets of the registers in the configuration space of a PCI function that are used to find the PCI-to-PCI
+ * bridges.
+ */
+const PCI_VENDOR_ID: u16 = 0x00;
+const PCI_HEADER_TYPE: u16 = 0x0e;
+const PCI_SECONDARY_BUS: u16 = 0x19;
+const PCI_SUBORDINATE_BUS: u16 = 0x1a;
+
+/// Find the GSI that interrupt pin `pin` of the PCI device at `segment:bus:device` is connected to, after applying
+/// the interrupt source overrides of the MADT. `handler` is used to read the configuration space of the bridges
+/// between the root bus and `bus`. If the pin is routed through a link device that isn't currently connected to an
+/// interrupt, the link is connected to the first interrupt in its `_PRS` by evaluating its `_SRS`.
+pub fn route_pci_interrupt<H>(
+    context: &mut AmlContext,
+    handler: &H,
+    interrupt_source_overrides: &[InterruptSourceOverride],
+    segment: u16,
+    bus: u8,
+    device: u8,
+    pin: Pin,
+) -> AcpiResult<PciInterruptRoute>
+where
+    H: aml::Handler,
+{
+    let (root_bridge, root_bus) = find_root_bridge(context, segment, bus)?;
+    let bridges = find_bridges(handler, segment, root_bus, bus)?;
+
+    /*
+     * Follow the bridges down the namespace for as long as they're described in it, to find the deepest bridge
+     * with a `_PRT`. Below that, the pins of each device are swizzled onto the pins of the bridge it's behind.
+     */
+    let mut prt_scope = root_bridge.clone();
+    let mut prt_depth = 0;
+    let mut scope = root_bridge;
+    for (depth, &(bridge_device, bridge_function)) in bridges.iter().enumerate() {
+        match find_child_with_address(context, &scope, bridge_device, bridge_function)? {
+            Some(child) => scope = child,
+            None => break,
+        }
+        if has_object(context, &scope, "_PRT")? {
+            prt_scope = scope.clone();
+            prt_depth = depth + 1;
+        }
+    }
+
+    let (mut device, mut pin) = (device, pin);
+    for &(bridge_device, _) in bridges[prt_depth..].iter().rev() {
+        pin = swizzle(pin, device);
+        device = bridge_device;
+    }
+
+    if !has_object(context, &prt_scope, "_PRT")? {
+        return Err(AcpiError::PciRouting(PciRoutingError::NoRoutingTable(prt_scope)));
+    }
+    let prt_path = AmlName::from_str("_PRT").unwrap().resolve(&prt_scope).map_err(AcpiError::Aml)?;
+    let table = PciRoutingTable::from_prt_path(&prt_path, context).map_err(AcpiError::Aml)?;
+    let entry = table
+        .entries()
+        .iter()
+        .find(|entry| entry.matches(device as u16, 0, pin))
+        .ok_or(AcpiError::Aml(aml::AmlError::PrtNoEntry))?;
+
+    match entry.route_type {
+        PciRouteType::Gsi(gsi) => Ok(PciInterruptRoute {
+            global_system_interrupt: gsi,
+            polarity: Polarity::ActiveLow,
+            trigger_mode: TriggerMode::Level,
+            link_object: None,
+        }),
+        PciRouteType::LinkObject(ref link) => {
+            let interrupt = link_interrupt(context, link, entry.source_index as usize)?;
+            let irq = interrupt.irqs()[0];
+
+            // Links that are routed to ISA IRQs are subject to the interrupt source overrides
+            let mut route = PciInterruptRoute {
+                global_system_interrupt: irq,
+                polarity: match interrupt.polarity {
+                    InterruptPolarity::ActiveLow => Polarity::ActiveLow,
+                    InterruptPolarity::ActiveHigh | InterruptPolarity::ActiveBoth => Polarity::ActiveHigh,
+                },
+                trigger_mode: match interrupt.trigger {
+                    InterruptTrigger::Edge => TriggerMode::Edge,
+                    InterruptTrigger::Level => TriggerMode::Level,
+                },
+                link_object: Some(link.clone()),
+            };
+            if let Some(entry) =
+                interrupt_source_overrides.iter().find(|entry| irq < 16 && entry.isa_source as u32 == irq)
+            {
+                route.global_system_interrupt = entry.global_system_interrupt;
+                if entry.polarity != Polarity::SameAsBus {
+                    route.polarity = entry.polarity;
+                }
+                if entry.trigger_mode != TriggerMode::SameAsBus {
+                    route.trigger_mode = entry.trigger_mode;
+                }
+            }
+            Ok(route)
+        }
+    }
+}
+
+/// Rotate the interrupt pin of a device behind a PCI-to-PCI bridge onto the pin of the bridge it's connected to.
+fn swizzle(pin: Pin, device: u8) -> Pin {
+    let index = match pin {
+        Pin::IntA => 0,
+        Pin::IntB => 1,
+        Pin::IntC => 2,
+        Pin::IntD => 3,
+    };
+    match (index + device as usize) % 4 {
+        0 => Pin::IntA,
+        1 => Pin::IntB,
+        2 => Pin::IntC,
+        _ => Pin::IntD,
+    }
+}
+
+/// Find the root bridge of `bus` in segment group `segment`: the bridge in the segment group with the highest
+/// base bus number (`_BBN`) that isn't above `bus`. Returns the path of the bridge and its base bus number.
+fn find_root_bridge(context: &mut AmlContext, segment: u16, bus: u8) -> AcpiResult<(AmlName, u8)> {
+    let mut bridges = device::find_devices_with_hid(context, "PNP0A08")?;
+    for bridge in device::find_devices_with_hid(context, "PNP0A03")? {
+        if !bridges.contains(&bridge) {
+            bridges.push(bridge);
+        }
+    }
+
+    let mut root_bridge: Option<(AmlName, u8)> = None;
+    for bridge in bridges {
+        let bridge_segment = evaluate_integer(context, &bridge, "_SEG")?.unwrap_or(0);
+        let bridge_bus = evaluate_integer(context, &bridge, "_BBN")?.unwrap_or(0);
+        if bridge_segment != segment as u64 || bridge_bus > bus as u64 {
+            continue;
+        }
+        if root_bridge.as_ref().is_none_or(|(_, root_bus)| bridge_bus > *root_bus as u64) {
+            root_bridge = Some((bridge, bridge_bus as u8));
+        }
+    }
+
+    root_bridge.ok_or(AcpiError::PciRouting(PciRoutingError::NoRootBridge))
+}
+
+/// Find the PCI-to-PCI bridges between `root_bus` and `bus`, by searching the configuration space below the root
+/// bus. Returns the device and function of each bridge, starting with the bridge on the root bus.
+fn find_bridges<H>(handler: &H, segment: u16, root_bus: u8, bus: u8) -> AcpiResult<Vec<(u8, u8)>>
+where
+    H: aml::Handler,
+{
+    let mut bridges = Vec::new();
+    let mut current_bus = root_bus;
+
+    'search: while current_bus != bus {
+        for device in 0..32 {
+            for function in 0..8 {
+                if handler.read_pci_u16(segment, current_bus, device, function, PCI_VENDOR_ID) == 0xffff {
+                    if function == 0 {
+                        break;
+                    }
+                    continue;
+                }
+
+                let header_type = handler.read_pci_u8(segment, current_bus, device, function, PCI_HEADER_TYPE);
+                if header_type.get_bits(0..7) == 0x01 {
+                    let secondary = handler.read_pci_u8(segment, current_bus, device, function, PCI_SECONDARY_BUS);
+                    let subordinate =
+                        handler.read_pci_u8(segment, current_bus, device, function, PCI_SUBORDINATE_BUS);
+
+                    // A bridge can only lead to buses numbered above its own bus
+                    if secondary > current_bus && (secondary..=subordinate).contains(&bus) {
+                        bridges.push((device, function));
+                        current_bus = secondary;
+                        continue 'search;
+                    }
+                }
+
+                // Functions other than 0 only exist on multi-function devices
+                if function == 0 && !header_type.get_bit(7) {
+                    break;
+                }
+            }
+        }
+
+        return Err(AcpiError::PciRouting(PciRoutingError::BusNotFound(bus)));
+    }
+
+    Ok(bridges)
+}
+
+/// Find the device directly under `scope` whose `_ADR` is that of the given PCI device and function.
+fn find_child_with_address(
+    context: &mut AmlContext,
+    scope: &AmlName,
+    device: u8,
+    function: u8,
+) -> AcpiResult<Option<AmlName>> {
+    let mut children = Vec::new();
+    context
+        .namespace
+        .traverse(|path, level| match level.typ {
+            LevelType::Device if path.parent().as_ref() == Ok(scope) => {
+                children.push(path.clone());
+                Ok(false)
+            }
+            LevelType::Scope | LevelType::Device => Ok(true),
+            _ => Ok(false),
+        })
+        .map_err(AcpiError::Aml)?;
+
+    for child in children {
+        if let Some(address) = evaluate_integer(context, &child, "_ADR")? {
+            let child_function = address.get_bits(0..16);
+            if address.get_bits(16..32) == device as u64
+                && (child_function == function as u64 || child_function == 0xffff)
+            {
+                return Ok(Some(child));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn has_object(context: &AmlContext, scope: &AmlName, name: &str) -> AcpiResult<bool> {
+    let path = AmlName::from_str(name).map_err(AcpiError::Aml)?.resolve(scope).map_err(AcpiError::Aml)?;
+    Ok(context.namespace.get_by_path(&path).is_ok())
+}
+
+fn evaluate_integer(context: &mut AmlContext, device: &AmlName, name: &str) -> AcpiResult<Option<u64>> {
+    match device::evaluate_optional(context, device, name, Args::EMPTY)? {
+        Some(value) => Ok(Some(value.as_integer(context).map_err(AcpiError::Aml)?)),
+        None => Ok(None),
+    }
+}
+
+/// Find the interrupt that the link device `link` is routed to, from the interrupt descriptor at index `index` of
+/// its `_CRS`. If the link isn't routed to an interrupt, it's routed to the first interrupt in the descriptor at
+/// the same index of its `_PRS`. The returned descriptor only lists the chosen interrupt.
+fn link_interrupt(context: &mut AmlContext, link: &AmlName, index: usize) -> AcpiResult<IrqDescriptor> {
+    let invalid_resources = || AcpiError::PciRouting(PciRoutingError::InvalidLinkResources(link.clone()));
+
+    let is_enabled = evaluate_integer(context, link, "_STA")?.is_none_or(|status| status.get_bit(1));
+    let current = match resource::current_resources(context, link).map_err(AcpiError::Aml)?.get(index) {
+        Some(Resource::Irq(descriptor)) => descriptor.clone(),
+        _ => return Err(invalid_resources()),
+    };
+    if let Some(&irq) = current.irqs().first() {
+        if is_enabled && irq != 0 {
+            return Ok(current.with_irq(irq));
+        }
+    }
+
+    let not_configurable = || AcpiError::PciRouting(PciRoutingError::LinkNotConfigurable(link.clone()));
+    if !has_object(context, link, "_SRS")? || !has_object(context, link, "_PRS")? {
+        return Err(not_configurable());
+    }
+    let possible = match resource::possible_resources(context, link).map_err(AcpiError::Aml)?.first() {
+        Some(setting) => match setting.get(index) {
+            Some(Resource::Irq(descriptor)) => descriptor.clone(),
+            _ => return Err(invalid_resources()),
+        },
+        None => return Err(invalid_resources()),
+    };
+    let irq = possible.irqs().into_iter().find(|&irq| irq != 0).ok_or_else(not_configurable)?;
+
+    // Link devices only have one resource, so the new setting is just the chosen interrupt
+    let setting = possible.with_irq(irq);
+    resource::set_resources(context, link, &[Resource::Irq(setting.clone())]).map_err(AcpiError::Aml)?;
+    Ok(setting)
+}
@@ -0,0 +1,231 @@
+use crate::{
+    platform::ProcessorInfo,
+    pptt::{CacheKind, CacheWritePolicy, Pptt, PpttEntry, ProcessorHierarchyNode},
+    AcpiError,
+    AcpiResult,
+    ManagedVec,
+};
+use core::alloc::Allocator;
+
+/*
+ * The PPTT is a tree, so we should never need to walk further than the number of nodes in it. This limit stops us
+ * looping forever on a malformed table that contains a cycle.
+ */
+const MAX_DEPTH: usize = 64;
+
+/// Describes where a processor sits in the processor hierarchy. The IDs are opaque (they are the offsets of the
+/// corresponding nodes in the PPTT), but are the same for all processors that share that level of the hierarchy:
+/// two processors with the same `core_id` are threads of the same core, and two processors with the same
+/// `package_id` are in the same physical package.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessorLocation {
+    /// The ACPI Processor UID of the processor, which matches `Processor::processor_uid`.
+    pub processor_uid: u32,
+    /// The local APIC or X2APIC ID of the processor, if the MADT describes it.
+    pub local_apic_id: Option<u32>,
+    pub package_id: u32,
+    pub core_id: u32,
+    /// The ID of the thread, if the processor is one of several threads of a core.
+    pub thread_id: Option<u32>,
+}
+
+/// Describes a cache in the processor hierarchy. Properties that the firmware didn't report are `None`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cache {
+    /// An opaque ID for the cache (its offset in the PPTT).
+    pub id: u32,
+    /// The level of the cache, where `1` is the level closest to the processor.
+    pub level: u8,
+    pub kind: Option<CacheKind>,
+    pub size: Option<u32>,
+    pub number_of_sets: Option<u32>,
+    pub associativity: Option<u8>,
+    pub line_size: Option<u16>,
+    pub write_policy: Option<CacheWritePolicy>,
+}
+
+/// `ProcessorTopology` merges the processor hierarchy from the PPTT with the processors described by the MADT, so
+/// that the package, core, and thread of each processor, and the caches it shares with other processors, can be
+/// found in one place.
+#[derive(Debug)]
+pub struct ProcessorTopology<A>
+where
+    A: Allocator,
+{
+    pub processors: ManagedVec<ProcessorLocation, A>,
+    pub caches: ManagedVec<Cache, A>,
+    /// Pairs of `(processor_uid, index into caches)`, meaning that the processor can use the cache.
+    cache_assignments: ManagedVec<(u32, usize), A>,
+}
+
+impl<A> ProcessorTopology<A>
+where
+    A: Allocator + Clone,
+{
+    pub(crate) fn new_in<B>(
+        pptt: &Pptt,
+        processor_info: Option<&ProcessorInfo<B>>,
+        allocator: A,
+    ) -> AcpiResult<Self>
+    where
+        B: Allocator,
+    {
+        let mut topology = ProcessorTopology {
+            processors: ManagedVec::new_in(allocator.clone()),
+            caches: ManagedVec::new_in(allocator.clone()),
+            cache_assignments: ManagedVec::new_in(allocator),
+        };
+
+        /*
+         * Revision 1 of the PPTT didn't have the leaf flag, so if none of the nodes have it set, we have to find
+         * the leaves by looking for nodes that aren't the parent of any other node.
+         */
+        let has_leaf_flags = hierarchy_nodes(pptt).any(|(_, node)| node.is_leaf());
+        let leaves = hierarchy_nodes(pptt).filter(|&(offset, node)| {
+            if has_leaf_flags {
+                node.is_leaf()
+            } else {
+                !hierarchy_nodes(pptt).any(|(_, other)| other.parent == offset)
+            }
+        });
+
+        for (offset, leaf) in leaves {
+            let processor_uid = leaf.acpi_processor_id;
+            let local_apic_id = processor_info.and_then(|info| {
+                core::iter::once(&info.boot_processor)
+                    .chain(info.application_processors.iter())
+                    .find(|processor| processor.processor_uid == processor_uid)
+                    .map(|processor| processor.local_apic_id)
+            });
+
+            let (core_id, thread_id) =
+                if leaf.is_thread() && leaf.parent != 0 { (leaf.parent, Some(offset)) } else { (offset, None) };
+
+            let mut package_id = offset;
+            let mut base_level = 0;
+            let mut node = Some((offset, leaf));
+            for _ in 0..MAX_DEPTH {
+                let (node_offset, current) = match node {
+                    Some(node) => node,
+                    None => break,
+                };
+
+                package_id = node_offset;
+                base_level = topology.add_caches(pptt, processor_uid, current, base_level)?;
+
+                if current.is_physical_package() {
+                    break;
+                }
+                node =
+                    pptt.processor_hierarchy_node_at_offset(current.parent).map(|parent| (current.parent, parent));
+            }
+
+            /*
+             * Keep walking up the tree from the package, to find any caches that are shared between packages.
+             */
+            if let Some(package) = pptt.processor_hierarchy_node_at_offset(package_id) {
+                let mut parent = package.parent;
+                for _ in 0..MAX_DEPTH {
+                    let node = match pptt.processor_hierarchy_node_at_offset(parent) {
+                        Some(node) => node,
+                        None => break,
+                    };
+                    base_level = topology.add_caches(pptt, processor_uid, node, base_level)?;
+                    parent = node.parent;
+                }
+            }
+
+            topology
+                .processors
+                .try_push(ProcessorLocation { processor_uid, local_apic_id, package_id, core_id, thread_id })
+                .map_err(|_| AcpiError::AllocError)?;
+        }
+
+        Ok(topology)
+    }
+
+    /// Add the caches that are private to `node` (and the further levels of cache they point to) to the
+    /// topology, and record that processor `processor_uid` can use them. `base_level` is the highest level of
+    /// cache found below this node. Returns the highest level of cache found at or below this node.
+    fn add_caches(
+        &mut self,
+        pptt: &Pptt,
+        processor_uid: u32,
+        node: &ProcessorHierarchyNode,
+        base_level: u8,
+    ) -> AcpiResult<u8> {
+        let mut max_level = base_level;
+
+        for resource in node.private_resources() {
+            let mut cache_offset = resource;
+
+            for depth in 1..=(MAX_DEPTH as u8) {
+                let level = base_level.saturating_add(depth);
+                let cache = match pptt.cache_at_offset(cache_offset) {
+                    Some(cache) => cache,
+                    None => break,
+                };
+
+                let index = match self.caches.iter().position(|existing| existing.id == cache_offset) {
+                    Some(index) => index,
+                    None => {
+                        self.caches
+                            .try_push(Cache {
+                                id: cache_offset,
+                                level,
+                                kind: cache.kind(),
+                                size: cache.size(),
+                                number_of_sets: cache.number_of_sets(),
+                                associativity: cache.associativity(),
+                                line_size: cache.line_size(),
+                                write_policy: cache.write_policy(),
+                            })
+                            .map_err(|_| AcpiError::AllocError)?;
+                        self.caches.len() - 1
+                    }
+                };
+                if !self.cache_assignments.contains(&(processor_uid, index)) {
+                    self.cache_assignments.try_push((processor_uid, index)).map_err(|_| AcpiError::AllocError)?;
+                }
+
+                max_level = u8::max(max_level, level);
+                cache_offset = cache.next_level_of_cache;
+            }
+        }
+
+        Ok(max_level)
+    }
+}
+
+impl<A> ProcessorTopology<A>
+where
+    A: Allocator,
+{
+    pub fn processor(&self, processor_uid: u32) -> Option<&ProcessorLocation> {
+        self.processors.iter().find(|processor| processor.processor_uid == processor_uid)
+    }
+
+    /// The caches that the processor with the given UID can use, at all levels.
+    pub fn caches_of(&self, processor_uid: u32) -> impl Iterator<Item = &Cache> + '_ {
+        self.cache_assignments
+            .iter()
+            .filter(move |&&(uid, _)| uid == processor_uid)
+            .map(move |&(_, index)| &self.caches[index])
+    }
+
+    /// The processors that share the cache with the given ID.
+    pub fn processors_sharing(&self, cache_id: u32) -> impl Iterator<Item = &ProcessorLocation> + '_ {
+        let index = self.caches.iter().position(|cache| cache.id == cache_id);
+        self.cache_assignments
+            .iter()
+            .filter(move |&&(_, cache_index)| Some(cache_index) == index)
+            .filter_map(move |&(uid, _)| self.processor(uid))
+    }
+}
+
+fn hierarchy_nodes(pptt: &Pptt) -> impl Iterator<Item = (u32, &ProcessorHierarchyNode)> {
+    pptt.entries().filter_map(|(offset, entry)| match entry {
+        PpttEntry::ProcessorHierarchyNode(node) => Some((offset, node)),
+        _ => None,
+    })
+}
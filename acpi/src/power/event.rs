@@ -0,0 +1,218 @@
+use super::{pm1_enable_register, pm1_status_register};
+use crate::{
+    address::{GenericAddress, RegisterHandler},
+    fadt::Fadt,
+    quirks::Quirks,
+    AcpiError,
+    AcpiResult,
+};
+use alloc::boxed::Box;
+use bit_field::BitField;
+
+/// The fixed events, which are signalled through the status bits of the PM1 event registers, and raise the SCI
+/// when they are enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FixedEvent {
+    /// The PM timer's most significant bit has changed (i.e. its counter has overflowed past half its range).
+    PmTimerOverflow,
+    /// The firmware has released the Global Lock, after the OS set the pending bit while trying to acquire it.
+    GlobalLockRelease,
+    PowerButton,
+    SleepButton,
+    /// The RTC alarm has fired.
+    Rtc,
+}
+
+impl FixedEvent {
+    pub const ALL: [FixedEvent; 5] = [
+        FixedEvent::PmTimerOverflow,
+        FixedEvent::GlobalLockRelease,
+        FixedEvent::PowerButton,
+        FixedEvent::SleepButton,
+        FixedEvent::Rtc,
+    ];
+
+    /// The bit of the event's status and enable fields in the PM1 status and enable registers.
+    fn bit(self) -> usize {
+        match self {
+            FixedEvent::PmTimerOverflow => 0,
+            FixedEvent::GlobalLockRelease => 5,
+            FixedEvent::PowerButton => 8,
+            FixedEvent::SleepButton => 9,
+            FixedEvent::Rtc => 10,
+        }
+    }
+}
+
+type FixedEventHandler = Box<dyn FnMut(FixedEvent) + Send>;
+
+/// Manages the fixed events of a platform. The OS should call [`FixedEvents::handle_sci`] whenever the SCI
+/// fires, which acknowledges the fixed events that have occurred, and calls the handlers registered for them.
+pub struct FixedEvents {
+    pm1a_event: GenericAddress,
+    pm1b_event: Option<GenericAddress>,
+    supported: [bool; 5],
+    handlers: [Option<FixedEventHandler>; 5],
+}
+
+impl FixedEvents {
+    /// Returns [`AcpiError::HardwareReduced`] on hardware-reduced platforms, which don't have any fixed events.
+    pub fn new(fadt: &Fadt) -> AcpiResult<FixedEvents> {
+        FixedEvents::new_with_quirks(fadt, Quirks::NONE)
+    }
+
+    /// Like [`FixedEvents::new`], but applying the workarounds in `quirks` for firmware that describes the PM1
+    /// event blocks incorrectly.
+    pub fn new_with_quirks(fadt: &Fadt, quirks: Quirks) -> AcpiResult<FixedEvents> {
+        let flags = { fadt.flags };
+        let policy = quirks.address_policy();
+
+        Ok(FixedEvents {
+            pm1a_event: fadt.pm1a_event_block_with_policy(policy)?,
+            pm1b_event: fadt.pm1b_event_block_with_policy(policy)?,
+            supported: FixedEvent::ALL.map(|event| match event {
+                FixedEvent::PmTimerOverflow => {
+                    fadt.pm_timer_block_with_policy(policy).is_ok_and(|block| block.is_some())
+                }
+                FixedEvent::GlobalLockRelease => true,
+                FixedEvent::PowerButton => !flags.power_button_is_control_method(),
+                FixedEvent::SleepButton => !flags.sleep_button_is_control_method(),
+                FixedEvent::Rtc => !flags.no_rtc_wake_in_fixed_register_space(),
+            }),
+            handlers: [None, None, None, None, None],
+        })
+    }
+
+    /// If false, the platform doesn't implement the event in the fixed hardware. Unsupported buttons are instead
+    /// implemented as control method devices in the namespace (`PNP0C0C` and `PNP0C0E`), which raise `Notify`s.
+    pub fn is_supported(&self, event: FixedEvent) -> bool {
+        self.supported[event as usize]
+    }
+
+    /// Register a handler that will be called from [`FixedEvents::handle_sci`] when the event occurs. This
+    /// replaces the event's existing handler, if it has one. The event must be enabled separately, with
+    /// [`FixedEvents::enable`].
+    pub fn set_handler<F>(&mut self, event: FixedEvent, handler: F)
+    where
+        F: FnMut(FixedEvent) + Send + 'static,
+    {
+        self.handlers[event as usize] = Some(Box::new(handler));
+    }
+
+    pub fn remove_handler(&mut self, event: FixedEvent) {
+        self.handlers[event as usize] = None;
+    }
+
+    /// Enable the event, so it raises the SCI. Any occurrence of the event from before it was enabled is
+    /// acknowledged first.
+    pub fn enable<H>(&self, handler: &H, event: FixedEvent) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        if !self.is_supported(event) {
+            return Err(AcpiError::FixedEventNotSupported(event));
+        }
+
+        self.acknowledge(handler, 1 << event.bit())?;
+        self.set_enabled(handler, event, true)
+    }
+
+    pub fn disable<H>(&self, handler: &H, event: FixedEvent) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        if !self.is_supported(event) {
+            return Err(AcpiError::FixedEventNotSupported(event));
+        }
+
+        self.set_enabled(handler, event, false)
+    }
+
+    pub fn is_enabled<H>(&self, handler: &H, event: FixedEvent) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        Ok(pm1_enable_register(&self.pm1a_event)?.read_register(handler)?.get_bit(event.bit()))
+    }
+
+    /// Handle the fixed events that have occurred. This should be called by the OS's SCI interrupt handler. Each
+    /// enabled event that has occurred is acknowledged, and then its handler (if it has one) is called. Returns
+    /// `true` if any fixed events were handled; as the SCI is shared with the GPEs, `false` does not mean the
+    /// interrupt was spurious.
+    pub fn handle_sci<H>(&mut self, handler: &H) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let mut status = self.read_combined(handler, |block| Ok(pm1_status_register(block)))?;
+        status &= self.read_combined(handler, pm1_enable_register)?;
+
+        let mut handled = false;
+        for event in FixedEvent::ALL {
+            if !status.get_bit(event.bit()) {
+                continue;
+            }
+
+            self.acknowledge(handler, 1 << event.bit())?;
+            if let Some(event_handler) = &mut self.handlers[event as usize] {
+                event_handler(event);
+            }
+            handled = true;
+        }
+
+        Ok(handled)
+    }
+
+    /// Read the PM1a and PM1b status or enable registers, which are combined by ORing their values.
+    fn read_combined<H>(
+        &self,
+        handler: &H,
+        register: fn(&GenericAddress) -> AcpiResult<GenericAddress>,
+    ) -> AcpiResult<u64>
+    where
+        H: RegisterHandler,
+    {
+        let mut value = register(&self.pm1a_event)?.read_register(handler)?;
+        if let Some(pm1b_event) = &self.pm1b_event {
+            value |= register(pm1b_event)?.read_register(handler)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Clear the given bits of the PM1 status registers, which are cleared by writing `1` to them.
+    fn acknowledge<H>(&self, handler: &H, bits: u64) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        pm1_status_register(&self.pm1a_event).write_register(handler, bits)?;
+        if let Some(pm1b_event) = &self.pm1b_event {
+            pm1_status_register(pm1b_event).write_register(handler, bits)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_enabled<H>(&self, handler: &H, event: FixedEvent, enabled: bool) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        for event_block in core::iter::once(&self.pm1a_event).chain(self.pm1b_event.as_ref()) {
+            let register = pm1_enable_register(event_block)?;
+            let mut value = register.read_register(handler)?;
+            value.set_bit(event.bit(), enabled);
+            register.write_register(handler, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for FixedEvents {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FixedEvents")
+            .field("pm1a_event", &self.pm1a_event)
+            .field("pm1b_event", &self.pm1b_event)
+            .field("supported", &self.supported)
+            .finish_non_exhaustive()
+    }
+}
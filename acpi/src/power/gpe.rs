@@ -0,0 +1,516 @@
+use crate::{
+    address::{AccessSize, AddressSpace, GenericAddress, RegisterHandler},
+    fadt::Fadt,
+    quirks::Quirks,
+    AcpiError,
+    AcpiResult,
+};
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use bit_field::BitField;
+
+#[cfg(feature = "aml")]
+use alloc::format;
+#[cfg(feature = "aml")]
+use aml::{resource::Resource, value::AmlType, AmlContext, AmlError, AmlName, AmlValue, LevelType};
+
+/// How a GPE is signalled, which determines when its status bit is cleared.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GpeTrigger {
+    /// The status bit is cleared before the GPE is handled, so another occurrence while it is being handled isn't
+    /// lost.
+    Edge,
+    /// The status bit is cleared after the GPE is handled, as the source of the event will keep it set until then.
+    Level,
+}
+
+/// A block of GPE registers. The block is split into two halves: the first contains the status registers, and the
+/// second contains the enable registers, with one bit per GPE in each. Each register is accessed a byte at a time.
+#[derive(Clone, Debug)]
+pub struct GpeBlock {
+    address: GenericAddress,
+    /// The length of the block in bytes, including both the status and enable registers.
+    length: u8,
+    base: u32,
+    /// The scope that contains the `_Lxx` and `_Exx` methods of the block's GPEs.
+    #[cfg(feature = "aml")]
+    scope: AmlName,
+    /// The `_Lxx` and `_Exx` methods of GPE block devices are numbered from the start of the block, while those of
+    /// the FADT blocks use the GPE number.
+    #[cfg(feature = "aml")]
+    is_block_device: bool,
+}
+
+impl GpeBlock {
+    /// The number of the first GPE in the block.
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The number of GPEs in the block.
+    pub fn count(&self) -> u32 {
+        (self.length as u32 / 2) * 8
+    }
+
+    pub fn contains(&self, gpe: u32) -> bool {
+        gpe >= self.base && gpe < self.base + self.count()
+    }
+
+    pub fn address(&self) -> GenericAddress {
+        self.address
+    }
+
+    fn status_register(&self, index: u8) -> GenericAddress {
+        self.register(index)
+    }
+
+    fn enable_register(&self, index: u8) -> GenericAddress {
+        self.register(self.length / 2 + index)
+    }
+
+    fn register(&self, offset: u8) -> GenericAddress {
+        let address = match self.address.address_space {
+            AddressSpace::SystemMemory | AddressSpace::SystemIo => self.address.address + offset as u64,
+            _ => self.address.address,
+        };
+
+        GenericAddress {
+            bit_width: 8,
+            bit_offset: 0,
+            access_size: AccessSize::ByteAccess,
+            address,
+            ..self.address
+        }
+    }
+}
+
+type GpeHandler = Box<dyn FnMut(u32) + Send>;
+
+/// Manages the General Purpose Events (GPEs) of a platform. GPEs are numbered across all of the platform's GPE
+/// blocks: those described by the FADT, and (with the `aml` feature) any GPE block devices in the namespace. The
+/// OS should call [`Gpes::handle_sci`] (or, with the `aml` feature, [`Gpes::handle_sci_with_context`]) whenever
+/// the SCI fires.
+pub struct Gpes {
+    blocks: Vec<GpeBlock>,
+    handlers: BTreeMap<u32, (GpeTrigger, GpeHandler)>,
+    #[cfg(feature = "aml")]
+    methods: BTreeMap<u32, (AmlName, GpeTrigger)>,
+}
+
+impl Gpes {
+    /// Find the GPE blocks described by the FADT. `GPE0` starts at GPE `0`, and `GPE1` at `fadt.gpe1_base`.
+    /// Hardware-reduced platforms don't have any GPE blocks in the FADT.
+    pub fn new(fadt: &Fadt) -> AcpiResult<Gpes> {
+        Gpes::new_with_quirks(fadt, Quirks::NONE)
+    }
+
+    /// Like [`Gpes::new`], but applying the workarounds in `quirks` for firmware that describes the GPE blocks
+    /// incorrectly.
+    pub fn new_with_quirks(fadt: &Fadt, quirks: Quirks) -> AcpiResult<Gpes> {
+        let mut blocks = Vec::new();
+        let policy = quirks.address_policy();
+
+        if !fadt.is_hardware_reduced() {
+            let fadt_blocks = [
+                (fadt.gpe0_block_with_policy(policy)?, fadt.gpe0_block_length, 0),
+                (fadt.gpe1_block_with_policy(policy)?, fadt.gpe1_block_length, fadt.gpe1_base as u32),
+            ];
+            for &(address, length, base) in fadt_blocks.iter() {
+                if let Some(address) = address {
+                    blocks.push(GpeBlock {
+                        address,
+                        length,
+                        base,
+                        #[cfg(feature = "aml")]
+                        scope: AmlName::from_str("\\_GPE").unwrap(),
+                        #[cfg(feature = "aml")]
+                        is_block_device: false,
+                    });
+                }
+            }
+        }
+
+        Ok(Gpes {
+            blocks,
+            handlers: BTreeMap::new(),
+            #[cfg(feature = "aml")]
+            methods: BTreeMap::new(),
+        })
+    }
+
+    pub fn blocks(&self) -> &[GpeBlock] {
+        &self.blocks
+    }
+
+    /// Register a handler that will be called with the GPE's number when it occurs. This replaces the GPE's
+    /// existing handler, if it has one, and takes priority over its `_Lxx` or `_Exx` method. The GPE must be
+    /// enabled separately, with [`Gpes::enable`].
+    pub fn set_handler<F>(&mut self, gpe: u32, trigger: GpeTrigger, handler: F) -> AcpiResult<()>
+    where
+        F: FnMut(u32) + Send + 'static,
+    {
+        self.block_of(gpe)?;
+        self.handlers.insert(gpe, (trigger, Box::new(handler)));
+        Ok(())
+    }
+
+    pub fn remove_handler(&mut self, gpe: u32) {
+        self.handlers.remove(&gpe);
+    }
+
+    /// Enable the GPE, so it raises the SCI. Any occurrence of the GPE from before it was enabled is cleared
+    /// first.
+    pub fn enable<H>(&self, handler: &H, gpe: u32) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.clear(handler, gpe)?;
+        self.set_enabled(handler, gpe, true)
+    }
+
+    pub fn disable<H>(&self, handler: &H, gpe: u32) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.set_enabled(handler, gpe, false)
+    }
+
+    pub fn is_enabled<H>(&self, handler: &H, gpe: u32) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let (block, index, bit) = self.locate(gpe)?;
+        Ok(block.enable_register(index).read_register(handler)?.get_bit(bit))
+    }
+
+    /// Returns `true` if the GPE has occurred, and hasn't been cleared yet.
+    pub fn status<H>(&self, handler: &H, gpe: u32) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let (block, index, bit) = self.locate(gpe)?;
+        Ok(block.status_register(index).read_register(handler)?.get_bit(bit))
+    }
+
+    /// Clear the GPE's status bit, which is cleared by writing `1` to it.
+    pub fn clear<H>(&self, handler: &H, gpe: u32) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let (block, index, bit) = self.locate(gpe)?;
+        block.status_register(index).write_register(handler, 1 << bit)
+    }
+
+    /// Handle the GPEs that have occurred, by calling their handlers. This should be called by the OS's SCI
+    /// interrupt handler. Enabled GPEs without a handler are disabled, so they don't keep raising the SCI. Returns
+    /// `true` if any GPEs were handled; as the SCI is shared with the fixed events, `false` does not mean the
+    /// interrupt was spurious.
+    pub fn handle_sci<H>(&mut self, handler: &H) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let pending = self.pending(handler)?;
+
+        for &gpe in pending.iter() {
+            if !self.dispatch_to_handler(handler, gpe)? {
+                self.disable(handler, gpe)?;
+                self.clear(handler, gpe)?;
+            }
+        }
+
+        Ok(!pending.is_empty())
+    }
+
+    /// Get the GPEs that are enabled and have occurred.
+    fn pending<H>(&self, handler: &H) -> AcpiResult<Vec<u32>>
+    where
+        H: RegisterHandler,
+    {
+        let mut pending = Vec::new();
+
+        for block in self.blocks.iter() {
+            for index in 0..(block.length / 2) {
+                let status = block.status_register(index).read_register(handler)?;
+                let enabled = block.enable_register(index).read_register(handler)?;
+                let active = status & enabled;
+
+                for bit in 0..8 {
+                    if active.get_bit(bit) {
+                        pending.push(block.base + index as u32 * 8 + bit as u32);
+                    }
+                }
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Call the GPE's handler, if it has one, clearing its status bit at the right time for its trigger mode.
+    /// Returns `false` if it doesn't have a handler.
+    fn dispatch_to_handler<H>(&mut self, handler: &H, gpe: u32) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let trigger = match self.handlers.get(&gpe) {
+            Some((trigger, _)) => *trigger,
+            None => return Ok(false),
+        };
+
+        if trigger == GpeTrigger::Edge {
+            self.clear(handler, gpe)?;
+        }
+        if let Some((_, gpe_handler)) = self.handlers.get_mut(&gpe) {
+            gpe_handler(gpe);
+        }
+        if trigger == GpeTrigger::Level {
+            self.clear(handler, gpe)?;
+        }
+
+        Ok(true)
+    }
+
+    fn block_of(&self, gpe: u32) -> AcpiResult<&GpeBlock> {
+        self.blocks.iter().find(|block| block.contains(gpe)).ok_or(AcpiError::InvalidGpe(gpe))
+    }
+
+    /// Find the block containing the GPE, and the index of its status and enable registers within the block, and
+    /// its bit within those registers.
+    fn locate(&self, gpe: u32) -> AcpiResult<(&GpeBlock, u8, usize)> {
+        let block = self.block_of(gpe)?;
+        let offset = gpe - block.base;
+        Ok((block, (offset / 8) as u8, (offset % 8) as usize))
+    }
+
+    fn set_enabled<H>(&self, handler: &H, gpe: u32, enabled: bool) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let (block, index, bit) = self.locate(gpe)?;
+        let register = block.enable_register(index);
+        let mut value = register.read_register(handler)?;
+        value.set_bit(bit, enabled);
+        register.write_register(handler, value)
+    }
+}
+
+#[cfg(feature = "aml")]
+impl Gpes {
+    /// Find the GPE block devices (devices with a `_HID` of `ACPI0006`) in the namespace, and add their blocks.
+    /// Their GPEs are numbered after those of the FADT blocks, in the order the devices are found. This should be
+    /// called before [`Gpes::discover_methods`].
+    pub fn discover_block_devices(&mut self, context: &mut AmlContext) -> AcpiResult<()> {
+        let mut next_base = self.blocks.iter().map(|block| block.base + block.count()).max().unwrap_or(0);
+        let mut blocks = Vec::new();
+
+        // The namespace is cloned so that `_HID` and `_CRS` can be evaluated during the traversal
+        context
+            .namespace
+            .clone()
+            .traverse(|path, level| match level.typ {
+                LevelType::Device => {
+                    let hid = AmlName::from_str("_HID").unwrap().resolve(path)?;
+                    let is_block_device = match context.invoke_method(&hid, aml::value::Args::EMPTY) {
+                        Ok(AmlValue::String(hid)) => hid == "ACPI0006",
+                        Ok(_) | Err(AmlError::ValueDoesNotExist(_)) => false,
+                        Err(err) => return Err(err),
+                    };
+
+                    if is_block_device {
+                        let crs = AmlName::from_str("_CRS").unwrap().resolve(path)?;
+                        let resources = aml::resource::resource_descriptor_list(
+                            &context.invoke_method(&crs, aml::value::Args::EMPTY)?,
+                        )?;
+
+                        if let Some((address_space, address, length)) = resources.iter().find_map(block_resource) {
+                            let block = GpeBlock {
+                                address: GenericAddress {
+                                    address_space,
+                                    bit_width: length.saturating_mul(8),
+                                    bit_offset: 0,
+                                    access_size: AccessSize::ByteAccess,
+                                    address,
+                                },
+                                length,
+                                base: next_base,
+                                scope: path.clone(),
+                                is_block_device: true,
+                            };
+                            next_base += block.count();
+                            blocks.push(block);
+                        }
+                    }
+
+                    Ok(true)
+                }
+
+                LevelType::Scope => Ok(true),
+                _ => Ok(false),
+            })
+            .map_err(AcpiError::Aml)?;
+
+        self.blocks.extend(blocks);
+        Ok(())
+    }
+
+    /// Find the `_Lxx` and `_Exx` methods that handle the GPEs, in `\_GPE` for the FADT blocks, and in the device
+    /// for GPE block devices. GPEs with a method are handled by [`Gpes::handle_sci_with_context`] if they don't
+    /// have a Rust handler, but must still be enabled with [`Gpes::enable`]. Methods of GPEs that occur without
+    /// having been discovered (e.g. that were added by a table loaded later) are looked up when they're handled.
+    pub fn discover_methods(&mut self, context: &AmlContext) -> AcpiResult<()> {
+        self.methods.clear();
+
+        for block in self.blocks.iter() {
+            for offset in 0..block.count() {
+                if let Some(method) = find_method(context, block, offset)? {
+                    self.methods.insert(block.base + offset, method);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve a reference to a GPE, as found in a device's `_GPE` object, or the first element of its `_PRW`. It
+    /// is either the number of a GPE in the FADT blocks, or a package of a GPE block device (as a name, which is
+    /// resolved relative to `scope`) and the index of the GPE within that device's block.
+    pub fn resolve_reference(&self, context: &AmlContext, scope: &AmlName, value: &AmlValue) -> AcpiResult<u32> {
+        let (device, index) = match value {
+            AmlValue::Package(elements) => match elements.as_slice() {
+                [AmlValue::String(device), index] => (device, index.as_integer(context).map_err(AcpiError::Aml)?),
+                _ => {
+                    return Err(AcpiError::Aml(AmlError::IncompatibleValueConversion {
+                        current: AmlType::Package,
+                        target: AmlType::Integer,
+                    }))
+                }
+            },
+            value => {
+                let gpe = value.as_integer(context).map_err(AcpiError::Aml)? as u32;
+                return match self.block_of(gpe)? {
+                    block if !block.is_block_device => Ok(gpe),
+                    _ => Err(AcpiError::InvalidGpe(gpe)),
+                };
+            }
+        };
+
+        let device = AmlName::from_str(device).map_err(AcpiError::Aml)?;
+        let device = match context.namespace.search_for_level(&device, scope) {
+            Ok(path) => path,
+            Err(_) => device.resolve(scope).map_err(AcpiError::Aml)?,
+        };
+        let block = self
+            .blocks
+            .iter()
+            .find(|block| block.is_block_device && block.scope == device)
+            .ok_or(AcpiError::Aml(AmlError::LevelDoesNotExist(device)))?;
+
+        if index >= block.count() as u64 {
+            return Err(AcpiError::InvalidGpe(block.base.saturating_add(index as u32)));
+        }
+        Ok(block.base + index as u32)
+    }
+
+    /// Find the GPE that `device` raises its events on, from its `_GPE` object (e.g. the GPE of the embedded
+    /// controller). Returns `None` if it doesn't have a `_GPE`.
+    pub fn device_gpe(&self, context: &mut AmlContext, device: &AmlName) -> AcpiResult<Option<u32>> {
+        match crate::device::evaluate_optional(context, device, "_GPE", aml::value::Args::EMPTY)? {
+            Some(value) => self.resolve_reference(context, device, &value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The path and trigger mode of the method that handles the GPE, if it has one.
+    pub fn method(&self, gpe: u32) -> Option<(&AmlName, GpeTrigger)> {
+        self.methods.get(&gpe).map(|(path, trigger)| (path, *trigger))
+    }
+
+    /// Handle the GPEs that have occurred, like [`Gpes::handle_sci`], but also invoke the `_Lxx` or `_Exx` method
+    /// of GPEs that don't have a Rust handler. The GPE is disabled while its method runs, and is then re-armed:
+    /// the status bit of an edge-triggered GPE is cleared before the method is invoked, and that of a
+    /// level-triggered GPE after, as the method is what makes its source stop asserting it.
+    pub fn handle_sci_with_context<H>(&mut self, handler: &H, context: &mut AmlContext) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        let pending = self.pending(handler)?;
+
+        for &gpe in pending.iter() {
+            if self.dispatch_to_handler(handler, gpe)? {
+                continue;
+            }
+
+            if !self.methods.contains_key(&gpe) {
+                let block = self.block_of(gpe)?;
+                if let Some(method) = find_method(context, block, gpe - block.base)? {
+                    self.methods.insert(gpe, method);
+                }
+            }
+
+            match self.methods.get(&gpe) {
+                Some((path, trigger)) => {
+                    self.disable(handler, gpe)?;
+                    if *trigger == GpeTrigger::Edge {
+                        self.clear(handler, gpe)?;
+                    }
+                    let result = context.invoke_method(path, aml::value::Args::EMPTY);
+                    if *trigger == GpeTrigger::Level {
+                        self.clear(handler, gpe)?;
+                    }
+                    self.set_enabled(handler, gpe, true)?;
+                    result.map_err(AcpiError::Aml)?;
+                }
+
+                None => {
+                    self.disable(handler, gpe)?;
+                    self.clear(handler, gpe)?;
+                }
+            }
+        }
+
+        Ok(!pending.is_empty())
+    }
+}
+
+/// Find the `_Lxx` or `_Exx` method of the GPE at `offset` into `block`. Methods can only be numbered up to
+/// `0xff`, so GPEs past that can't have one.
+#[cfg(feature = "aml")]
+fn find_method(context: &AmlContext, block: &GpeBlock, offset: u32) -> AcpiResult<Option<(AmlName, GpeTrigger)>> {
+    let number = if block.is_block_device { offset } else { block.base + offset };
+    if number > 0xff {
+        return Ok(None);
+    }
+
+    for &(prefix, trigger) in [('L', GpeTrigger::Level), ('E', GpeTrigger::Edge)].iter() {
+        let path =
+            AmlName::from_str(&format!("_{}{:02X}", prefix, number)).unwrap().resolve(&block.scope).unwrap();
+        match context.namespace.get_by_path(&path) {
+            Ok(_) => return Ok(Some((path, trigger))),
+            Err(AmlError::ValueDoesNotExist(_)) | Err(AmlError::LevelDoesNotExist(_)) => (),
+            Err(err) => return Err(AcpiError::Aml(err)),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Get the address space, address, and length of the register block described by a resource of a GPE block
+/// device's `_CRS`.
+#[cfg(feature = "aml")]
+fn block_resource(resource: &Resource) -> Option<(AddressSpace, u64, u8)> {
+    match resource {
+        Resource::IOPort(descriptor) => {
+            Some((AddressSpace::SystemIo, descriptor.memory_range.0 as u64, descriptor.range_length))
+        }
+        Resource::MemoryRange(aml::resource::MemoryRangeDescriptor::FixedLocation {
+            base_address,
+            range_length,
+            ..
+        }) => Some((AddressSpace::SystemMemory, *base_address as u64, (*range_length).min(u8::MAX as u32) as u8)),
+        _ => None,
+    }
+}
+
+impl core::fmt::Debug for Gpes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Gpes").field("blocks", &self.blocks).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,47 @@
+//! Support for the ACPI power management features: the fixed hardware registers described by the FADT (including
+//! the fixed events and GPEs), and (with the `aml` feature) the sleep states described by the namespace.
+
+#[cfg(feature = "allocator_api")]
+pub mod event;
+#[cfg(feature = "allocator_api")]
+pub mod gpe;
+#[cfg(feature = "aml")]
+pub mod sleep;
+#[cfg(feature = "aml")]
+pub mod suspend;
+
+#[cfg(feature = "allocator_api")]
+pub use event::{FixedEvent, FixedEvents};
+#[cfg(feature = "allocator_api")]
+pub use gpe::{GpeBlock, GpeTrigger, Gpes};
+#[cfg(feature = "aml")]
+pub use sleep::{SleepState, SleepStates, SleepType};
+#[cfg(feature = "aml")]
+pub use suspend::{SavedPmState, WakingVector};
+
+use crate::{
+    address::{AddressSpace, GenericAddress},
+    AcpiError,
+    AcpiResult,
+};
+
+/// The `WAK_STS` bit of the PM1 status register.
+#[cfg(feature = "aml")]
+pub(crate) const PM1_STATUS_WAKE: u64 = 1 << 15;
+
+/// Get the PM1 status register, which is the first half of a PM1 event register block.
+pub(crate) fn pm1_status_register(event_block: &GenericAddress) -> GenericAddress {
+    GenericAddress { bit_width: event_block.bit_width / 2, ..*event_block }
+}
+
+/// Get the PM1 enable register, which is the second half of a PM1 event register block. This can only be found
+/// if the block is in an address space where the second half of the block is at an offset from its address.
+pub(crate) fn pm1_enable_register(event_block: &GenericAddress) -> AcpiResult<GenericAddress> {
+    let half_width = event_block.bit_width / 2;
+    let address = match event_block.address_space {
+        AddressSpace::SystemMemory | AddressSpace::SystemIo => event_block.address + (half_width / 8) as u64,
+        space => return Err(AcpiError::UnsupportedAddressSpace(space)),
+    };
+
+    Ok(GenericAddress { bit_width: half_width, address, ..*event_block })
+}
@@ -0,0 +1,256 @@
+use super::{pm1_status_register, PM1_STATUS_WAKE};
+use crate::{
+    address::{GenericAddress, RegisterHandler},
+    fadt::{Fadt, SleepRegisters},
+    quirks::Quirks,
+    AcpiError,
+    AcpiHandler,
+    AcpiResult,
+    AcpiTables,
+};
+use alloc::vec;
+use aml::{value::Args, AmlContext, AmlError, AmlName, AmlValue};
+use bit_field::BitField;
+use core::hint;
+
+#[derive(Debug)]
+pub enum SleepError {
+    /// The platform doesn't support the sleep state, because the namespace doesn't contain its `\_Sx` object.
+    StateNotSupported(SleepState),
+    /// The `\_Sx` object of the sleep state is not a package of the sleep type values.
+    InvalidSleepObject(SleepState),
+    /// A 64-bit waking vector was requested, but the firmware doesn't support waking in 64-bit mode.
+    LongModeWakeNotSupported,
+    /// A protected-mode or 64-bit waking vector was requested, but the FACS is too old to have the 64-bit
+    /// `X_Firmware_Waking_Vector` field, so only a real-mode vector can be used.
+    ExtendedWakingVectorNotSupported,
+}
+
+/// The system sleep states. `S0` is the working state, and each of the states `S1` to `S5` consumes less
+/// power than the previous one, but takes longer to wake from:
+///    - In `S1` and `S2`, the processors stop executing, but the system context is maintained by the hardware.
+///    - In `S3` ("suspend to RAM"), only memory retains its contents, and the firmware restarts the processors at
+///      the waking vector in the FACS.
+///    - `S4` ("hibernation") behaves like `S5` to the hardware, and the OS must have saved its context to disk.
+///    - In `S5` ("soft off"), the system is powered off.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum SleepState {
+    S0,
+    S1,
+    S2,
+    S3,
+    S4,
+    S5,
+}
+
+impl SleepState {
+    pub const ALL: [SleepState; 6] =
+        [SleepState::S0, SleepState::S1, SleepState::S2, SleepState::S3, SleepState::S4, SleepState::S5];
+
+    /// The number of the sleep state, as passed to `_PTS`, `_WAK`, etc.
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+
+    fn object_path(self) -> &'static str {
+        match self {
+            SleepState::S0 => "\\_S0",
+            SleepState::S1 => "\\_S1",
+            SleepState::S2 => "\\_S2",
+            SleepState::S3 => "\\_S3",
+            SleepState::S4 => "\\_S4",
+            SleepState::S5 => "\\_S5",
+        }
+    }
+}
+
+/// The values that are written to the `SLP_TYP` fields of the PM1a and PM1b control registers to enter a sleep
+/// state. On hardware-reduced platforms, `pm1a` is written to the `SLP_TYPx` field of the sleep control register,
+/// and `pm1b` is unused.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct SleepType {
+    pub pm1a: u8,
+    pub pm1b: u8,
+}
+
+/// Describes the sleep states that the platform supports, and how to enter them.
+#[derive(Clone, Copy, Debug)]
+pub struct SleepStates {
+    registers: SleepRegisters,
+    types: [Option<SleepType>; 6],
+}
+
+impl SleepStates {
+    /// Find the sleep states the platform supports, by evaluating the `\_S0` to `\_S5` objects. The DSDT and
+    /// SSDTs must have been parsed by `context`.
+    pub fn discover<H>(tables: &AcpiTables<H>, context: &mut AmlContext) -> AcpiResult<SleepStates>
+    where
+        H: AcpiHandler,
+    {
+        SleepStates::discover_with_quirks(tables, context, Quirks::NONE)
+    }
+
+    /// Like [`SleepStates::discover`], but applying the workarounds in `quirks` for firmware that describes the
+    /// sleep registers incorrectly.
+    pub fn discover_with_quirks<H>(
+        tables: &AcpiTables<H>,
+        context: &mut AmlContext,
+        quirks: Quirks,
+    ) -> AcpiResult<SleepStates>
+    where
+        H: AcpiHandler,
+    {
+        let registers = tables.find_table::<Fadt>()?.sleep_registers_with_policy(quirks.address_policy())?;
+        let mut types = [None; 6];
+
+        for state in SleepState::ALL {
+            let path = AmlName::from_str(state.object_path()).unwrap();
+            types[state as usize] = match context.invoke_method(&path, Args::EMPTY) {
+                Ok(object) => Some(parse_sleep_object(context, state, &object)?),
+                Err(AmlError::ValueDoesNotExist(_)) => None,
+                Err(err) => return Err(AcpiError::Aml(err)),
+            };
+        }
+
+        Ok(SleepStates { registers, types })
+    }
+
+    pub fn registers(&self) -> &SleepRegisters {
+        &self.registers
+    }
+
+    pub fn sleep_type(&self, state: SleepState) -> Option<SleepType> {
+        self.types[state as usize]
+    }
+
+    pub fn is_supported(&self, state: SleepState) -> bool {
+        self.sleep_type(state).is_some()
+    }
+
+    pub fn supported_states(&self) -> impl Iterator<Item = SleepState> + '_ {
+        SleepState::ALL.iter().copied().filter(move |&state| self.is_supported(state))
+    }
+
+    /// Tell the firmware that the system is about to enter a sleep state, by invoking `\_PTS` (if it exists).
+    /// This should be done before the OS starts saving its context, as it may put devices in a state where the
+    /// OS can't use them.
+    pub fn prepare_to_sleep(&self, context: &mut AmlContext, state: SleepState) -> AcpiResult<()> {
+        invoke_optional_method(context, "\\_PTS", state)
+    }
+
+    /// Tell the firmware that the system has woken from a sleep state, by invoking `\_WAK` (if it exists). This
+    /// should be done once the OS has restored its context, before it starts using devices again.
+    pub fn wake(&self, context: &mut AmlContext, state: SleepState) -> AcpiResult<()> {
+        invoke_optional_method(context, "\\_WAK", state)
+    }
+
+    /// Enter a sleep state: this invokes `\_PTS`, then writes the sleep type of the state to the sleep registers,
+    /// and sets `SLP_EN`. The OS must have already put the devices and processors in a state suitable for the
+    /// sleep state (including flushing the caches, and stopping the application processors for `S3` and
+    /// deeper).
+    ///
+    /// For `S1` and `S2`, this returns once the system has woken, and the OS should then call
+    /// [`SleepStates::wake`]. For `S3`, the processor loses its context, and the firmware will instead jump to
+    /// the waking vector in the FACS (see [`SleepStates::enter_s3`]). For `S4` and `S5`, the system is powered
+    /// off, so this doesn't return unless entering the state failed.
+    pub fn enter_sleep_state<H>(&self, handler: &H, context: &mut AmlContext, state: SleepState) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let sleep_type = self.sleep_type(state).ok_or(AcpiError::Sleep(SleepError::StateNotSupported(state)))?;
+        self.prepare_to_sleep(context, state)?;
+        self.write_sleep_type(handler, sleep_type)
+    }
+
+    /// Write `sleep_type` to the sleep registers and set `SLP_EN`, then wait for the platform to set `WAK_STS`.
+    pub(crate) fn write_sleep_type<H>(&self, handler: &H, sleep_type: SleepType) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        match self.registers {
+            SleepRegisters::Pm1 { pm1a_control, pm1b_control, pm1a_event, pm1b_event } => {
+                let pm1a_status = pm1_status_register(&pm1a_event);
+                let pm1b_status = pm1b_event.map(|event| pm1_status_register(&event));
+
+                // `WAK_STS` is cleared by writing `1` to it
+                pm1a_status.write_register(handler, PM1_STATUS_WAKE)?;
+                if let Some(pm1b_status) = pm1b_status {
+                    pm1b_status.write_register(handler, PM1_STATUS_WAKE)?;
+                }
+
+                /*
+                 * The sleep type is written before `SLP_EN` is set, as some hardware doesn't expect both to be
+                 * written at once.
+                 */
+                write_pm1_sleep_type(handler, &pm1a_control, sleep_type.pm1a, false)?;
+                if let Some(pm1b_control) = pm1b_control {
+                    write_pm1_sleep_type(handler, &pm1b_control, sleep_type.pm1b, false)?;
+                }
+                write_pm1_sleep_type(handler, &pm1a_control, sleep_type.pm1a, true)?;
+                if let Some(pm1b_control) = pm1b_control {
+                    write_pm1_sleep_type(handler, &pm1b_control, sleep_type.pm1b, true)?;
+                }
+
+                while pm1a_status.read_register(handler)? & PM1_STATUS_WAKE == 0 {
+                    hint::spin_loop();
+                }
+            }
+
+            SleepRegisters::SleepControl { control, status } => {
+                const WAK_STS: u64 = 1 << 7;
+                const SLP_EN: u64 = 1 << 5;
+
+                status.write_register(handler, WAK_STS)?;
+                control.write_register(handler, ((sleep_type.pm1a as u64 & 0b111) << 2) | SLP_EN)?;
+
+                while status.read_register(handler)? & WAK_STS == 0 {
+                    hint::spin_loop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read-modify-write the `SLP_TYP` and `SLP_EN` fields of a PM1 control register.
+fn write_pm1_sleep_type<H>(handler: &H, register: &GenericAddress, sleep_type: u8, enable: bool) -> AcpiResult<()>
+where
+    H: RegisterHandler,
+{
+    let mut value = register.read_register(handler)?;
+    value.set_bits(10..13, sleep_type as u64 & 0b111);
+    value.set_bit(13, enable);
+    register.write_register(handler, value)
+}
+
+fn parse_sleep_object(context: &AmlContext, state: SleepState, object: &AmlValue) -> AcpiResult<SleepType> {
+    let as_integer = |value: &AmlValue| value.as_integer(context).map_err(AcpiError::Aml);
+
+    match object {
+        AmlValue::Package(elements) => match elements.as_slice() {
+            [pm1a, pm1b, ..] => Ok(SleepType { pm1a: as_integer(pm1a)? as u8, pm1b: as_integer(pm1b)? as u8 }),
+
+            // Some firmware packs both values into a single integer
+            [packed] => {
+                let packed = as_integer(packed)?;
+                Ok(SleepType { pm1a: packed.get_bits(0..8) as u8, pm1b: packed.get_bits(8..16) as u8 })
+            }
+
+            [] => Err(AcpiError::Sleep(SleepError::InvalidSleepObject(state))),
+        },
+
+        _ => Err(AcpiError::Sleep(SleepError::InvalidSleepObject(state))),
+    }
+}
+
+/// Invoke a method that takes the sleep state as its only argument, if it exists.
+pub(crate) fn invoke_optional_method(context: &mut AmlContext, path: &str, state: SleepState) -> AcpiResult<()> {
+    let path = AmlName::from_str(path).unwrap();
+    let args = Args::from_list(vec![AmlValue::Integer(state.number() as u64)]).unwrap();
+
+    match context.invoke_method(&path, args) {
+        Ok(_) | Err(AmlError::ValueDoesNotExist(_)) => Ok(()),
+        Err(err) => Err(AcpiError::Aml(err)),
+    }
+}
@@ -0,0 +1,142 @@
+use super::{
+    pm1_enable_register,
+    pm1_status_register,
+    sleep::{invoke_optional_method, SleepError},
+    SleepState,
+    SleepStates,
+    PM1_STATUS_WAKE,
+};
+use crate::{address::RegisterHandler, facs::Facs, fadt::SleepRegisters, AcpiError, AcpiResult};
+use aml::AmlContext;
+use bit_field::BitField;
+
+/// The address the firmware jumps to when the system resumes from `S3`, and the mode the processor is in when
+/// it does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WakingVector {
+    /// The firmware jumps to the vector in real mode, with `CS` set to `vector >> 4` and `IP` set to
+    /// `vector & 0xf`.
+    RealMode(u32),
+    /// The firmware jumps to the vector in 32-bit protected mode, with paging disabled.
+    ProtectedMode(u64),
+    /// The firmware jumps to the vector in 64-bit mode, with identity-mapped paging. This is only supported if
+    /// [`Facs::supports_64bit_wake`].
+    LongMode(u64),
+}
+
+/// The state of the fixed hardware registers, which is lost when the system enters `S3`. This must be kept in
+/// memory that is preserved across the sleep, so that it can be restored by [`SleepStates::resume_from_s3`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SavedPmState {
+    pm1a_enable: u64,
+    pm1b_enable: Option<u64>,
+    pm1a_control: u64,
+    pm1b_control: Option<u64>,
+}
+
+impl SleepStates {
+    /// Enter `S3`, after setting the waking vector in the FACS. The firmware will jump to `waking_vector` when the
+    /// system resumes, at which point the OS should restore its context and then call
+    /// [`SleepStates::resume_from_s3`] with `saved_state`. This invokes `\_PTS` and `\_GTS`, so
+    /// [`SleepStates::prepare_to_sleep`] doesn't need to be called.
+    ///
+    /// As for [`SleepStates::enter_sleep_state`], the OS must have already put the devices and processors in a
+    /// state suitable for `S3`, and saved its own context. If this returns, the system didn't enter `S3`.
+    pub fn enter_s3<H>(
+        &self,
+        handler: &H,
+        context: &mut AmlContext,
+        facs: &Facs,
+        waking_vector: WakingVector,
+        saved_state: &mut SavedPmState,
+    ) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let sleep_type = self
+            .sleep_type(SleepState::S3)
+            .ok_or(AcpiError::Sleep(SleepError::StateNotSupported(SleepState::S3)))?;
+
+        match waking_vector {
+            WakingVector::RealMode(vector) => {
+                facs.set_firmware_waking_vector(vector);
+                facs.set_x_firmware_waking_vector(0, false);
+            }
+            WakingVector::ProtectedMode(vector) => {
+                if facs.version < 1 {
+                    return Err(AcpiError::Sleep(SleepError::ExtendedWakingVectorNotSupported));
+                }
+                facs.set_x_firmware_waking_vector(vector, false);
+            }
+            WakingVector::LongMode(vector) => {
+                if facs.version < 1 {
+                    return Err(AcpiError::Sleep(SleepError::ExtendedWakingVectorNotSupported));
+                }
+                if !facs.supports_64bit_wake() {
+                    return Err(AcpiError::Sleep(SleepError::LongModeWakeNotSupported));
+                }
+                facs.set_x_firmware_waking_vector(vector, true);
+            }
+        }
+
+        self.prepare_to_sleep(context, SleepState::S3)?;
+        invoke_optional_method(context, "\\_GTS", SleepState::S3)?;
+
+        *saved_state = self.save_pm_state(handler)?;
+        self.write_sleep_type(handler, sleep_type)
+    }
+
+    /// Restore the state of the fixed hardware registers after resuming from `S3`, then invoke `\_WAK`.
+    pub fn resume_from_s3<H>(
+        &self,
+        handler: &H,
+        context: &mut AmlContext,
+        saved_state: &SavedPmState,
+    ) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        if let SleepRegisters::Pm1 { pm1a_control, pm1b_control, pm1a_event, pm1b_event } = *self.registers() {
+            let s0_type = self.sleep_type(SleepState::S0).unwrap_or_default();
+
+            // Restore the control registers, but in the working state
+            let restore_control = |mut value: u64, sleep_type: u8| {
+                value.set_bits(10..13, sleep_type as u64 & 0b111);
+                value.set_bit(13, false);
+                value
+            };
+            pm1a_control.write_register(handler, restore_control(saved_state.pm1a_control, s0_type.pm1a))?;
+            if let (Some(pm1b_control), Some(value)) = (pm1b_control, saved_state.pm1b_control) {
+                pm1b_control.write_register(handler, restore_control(value, s0_type.pm1b))?;
+            }
+
+            pm1_status_register(&pm1a_event).write_register(handler, PM1_STATUS_WAKE)?;
+            pm1_enable_register(&pm1a_event)?.write_register(handler, saved_state.pm1a_enable)?;
+            if let (Some(pm1b_event), Some(value)) = (pm1b_event, saved_state.pm1b_enable) {
+                pm1_status_register(&pm1b_event).write_register(handler, PM1_STATUS_WAKE)?;
+                pm1_enable_register(&pm1b_event)?.write_register(handler, value)?;
+            }
+        }
+
+        self.wake(context, SleepState::S3)
+    }
+
+    fn save_pm_state<H>(&self, handler: &H) -> AcpiResult<SavedPmState>
+    where
+        H: RegisterHandler,
+    {
+        match *self.registers() {
+            SleepRegisters::Pm1 { pm1a_control, pm1b_control, pm1a_event, pm1b_event } => Ok(SavedPmState {
+                pm1a_enable: pm1_enable_register(&pm1a_event)?.read_register(handler)?,
+                pm1b_enable: pm1b_event
+                    .map(|event| pm1_enable_register(&event)?.read_register(handler))
+                    .transpose()?,
+                pm1a_control: pm1a_control.read_register(handler)?,
+                pm1b_control: pm1b_control.map(|control| control.read_register(handler)).transpose()?,
+            }),
+
+            // Hardware-reduced platforms don't have any fixed hardware state to save
+            SleepRegisters::SleepControl { .. } => Ok(SavedPmState::default()),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, ptr};
+
+/// Represents the Processor Properties Topology Table (PPTT), which describes the topology of the processors in
+/// the system as a tree of processor hierarchy nodes (packages, clusters, cores, and threads), and the caches
+/// attached to each level of the tree. You can iterate over the structures in the table with [`Pptt::entries`],
+/// and follow the references between them with [`Pptt::entry_at_offset`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Pptt {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid PPTT.
+unsafe impl AcpiTable for Pptt {
+    const SIGNATURE: Signature = Signature::PPTT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Pptt {
+    pub fn entries(&self) -> PpttEntryIter<'_> {
+        PpttEntryIter { pptt: self, offset: mem::size_of::<Pptt>() as u32 }
+    }
+
+    /// Get the structure at `offset` bytes from the start of the table. This is how structures reference each
+    /// other, for example in the `parent` field of a [`ProcessorHierarchyNode`]. Returns `None` if the offset
+    /// doesn't point to a valid structure, if the structure is of a type we don't parse, or if it's too short to
+    /// be read as its type.
+    pub fn entry_at_offset(&self, offset: u32) -> Option<PpttEntry<'_>> {
+        let (header, pointer) = self.header_at_offset(offset)?;
+        let fits = |size| header.length as usize >= size;
+        match header.entry_type {
+            0 if fits(mem::size_of::<ProcessorHierarchyNode>()) => {
+                Some(PpttEntry::ProcessorHierarchyNode(unsafe { &*(pointer as *const ProcessorHierarchyNode) }))
+            }
+            1 if fits(mem::size_of::<CacheType>()) => {
+                Some(PpttEntry::Cache(unsafe { &*(pointer as *const CacheType) }))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn processor_hierarchy_node_at_offset(&self, offset: u32) -> Option<&ProcessorHierarchyNode> {
+        match self.entry_at_offset(offset)? {
+            PpttEntry::ProcessorHierarchyNode(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn cache_at_offset(&self, offset: u32) -> Option<&CacheType> {
+        match self.entry_at_offset(offset)? {
+            PpttEntry::Cache(cache) => Some(cache),
+            _ => None,
+        }
+    }
+
+    fn header_at_offset(&self, offset: u32) -> Option<(EntryHeader, *const u8)> {
+        let table_length = self.header.length as usize;
+        let offset = offset as usize;
+        if offset < mem::size_of::<Pptt>() || offset + mem::size_of::<EntryHeader>() > table_length {
+            return None;
+        }
+
+        let pointer = unsafe { (self as *const Pptt as *const u8).add(offset) };
+        let header = unsafe { *(pointer as *const EntryHeader) };
+        if (header.length as usize) < mem::size_of::<EntryHeader>()
+            || offset + header.length as usize > table_length
+        {
+            return None;
+        }
+
+        Some((header, pointer))
+    }
+}
+
+/// Iterates over the structures in the PPTT. Each structure is returned along with its offset from the start of
+/// the table, which is what other structures use to refer to it.
+#[derive(Debug)]
+pub struct PpttEntryIter<'a> {
+    pptt: &'a Pptt,
+    offset: u32,
+}
+
+#[derive(Debug)]
+pub enum PpttEntry<'a> {
+    ProcessorHierarchyNode(&'a ProcessorHierarchyNode),
+    Cache(&'a CacheType),
+}
+
+impl<'a> Iterator for PpttEntryIter<'a> {
+    type Item = (u32, PpttEntry<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = self.offset;
+            let (header, _) = self.pptt.header_at_offset(offset)?;
+            self.offset += header.length as u32;
+
+            /*
+             * The ID structure (type 2) was deprecated in ACPI 6.3, and other types are reserved by the ACPI
+             * standard. Skip them, along with structures that are too short to be read as their type.
+             */
+            if let Some(entry) = self.pptt.entry_at_offset(offset) {
+                return Some((offset, entry));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u8,
+    pub length: u8,
+    _reserved: u16,
+}
+
+/// Describes a single node in the processor hierarchy: a physical package, a group of cores (e.g. a cluster), a
+/// core, or a thread.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ProcessorHierarchyNode {
+    pub header: EntryHeader,
+    pub flags: u32,
+    /// The offset of the parent node from the start of the PPTT, or `0` if this node has no parent.
+    pub parent: u32,
+    /// For leaf nodes, this matches the ACPI Processor UID of the processor in the MADT. For other nodes, it is a
+    /// unique identifier for the node, if `acpi_processor_id_valid` is set.
+    pub acpi_processor_id: u32,
+    pub number_of_private_resources: u32,
+}
+
+impl ProcessorHierarchyNode {
+    /// If true, this node represents the boundary of a physical package.
+    pub fn is_physical_package(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn acpi_processor_id_valid(&self) -> bool {
+        { self.flags }.get_bit(1)
+    }
+
+    /// If true, this node is a thread of a core that supports simultaneous multithreading.
+    pub fn is_thread(&self) -> bool {
+        { self.flags }.get_bit(2)
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        { self.flags }.get_bit(3)
+    }
+
+    /// If true, all the children of this node have an identical implementation.
+    pub fn has_identical_implementation(&self) -> bool {
+        { self.flags }.get_bit(4)
+    }
+
+    /// The offsets, from the start of the PPTT, of the resources (usually caches) that are private to this node.
+    pub fn private_resources(&self) -> impl Iterator<Item = u32> + '_ {
+        let available = (self.header.length as usize).saturating_sub(mem::size_of::<ProcessorHierarchyNode>())
+            / mem::size_of::<u32>();
+        let count = usize::min(self.number_of_private_resources as usize, available);
+
+        (0..count).map(move |i| unsafe {
+            ptr::read_unaligned(
+                (self as *const ProcessorHierarchyNode as *const u8)
+                    .add(mem::size_of::<ProcessorHierarchyNode>() + i * mem::size_of::<u32>())
+                    as *const u32,
+            )
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheKind {
+    Data,
+    Instruction,
+    Unified,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheAllocationType {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheWritePolicy {
+    WriteBack,
+    WriteThrough,
+}
+
+/// Describes a cache. Each of the properties of the cache is only reported if the firmware marked it as valid.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct CacheType {
+    pub header: EntryHeader,
+    pub flags: u32,
+    /// The offset of the next level of cache from the start of the PPTT, or `0` if this is the last level.
+    pub next_level_of_cache: u32,
+    size: u32,
+    number_of_sets: u32,
+    associativity: u8,
+    attributes: u8,
+    line_size: u16,
+}
+
+impl CacheType {
+    /// The size of the cache, in bytes.
+    pub fn size(&self) -> Option<u32> {
+        if { self.flags }.get_bit(0) {
+            Some(self.size)
+        } else {
+            None
+        }
+    }
+
+    pub fn number_of_sets(&self) -> Option<u32> {
+        if { self.flags }.get_bit(1) {
+            Some(self.number_of_sets)
+        } else {
+            None
+        }
+    }
+
+    pub fn associativity(&self) -> Option<u8> {
+        if { self.flags }.get_bit(2) {
+            Some(self.associativity)
+        } else {
+            None
+        }
+    }
+
+    pub fn allocation_type(&self) -> Option<CacheAllocationType> {
+        if !{ self.flags }.get_bit(3) {
+            return None;
+        }
+
+        Some(match self.attributes.get_bits(0..2) {
+            0 => CacheAllocationType::Read,
+            1 => CacheAllocationType::Write,
+            _ => CacheAllocationType::ReadWrite,
+        })
+    }
+
+    pub fn kind(&self) -> Option<CacheKind> {
+        if !{ self.flags }.get_bit(4) {
+            return None;
+        }
+
+        Some(match self.attributes.get_bits(2..4) {
+            0 => CacheKind::Data,
+            1 => CacheKind::Instruction,
+            _ => CacheKind::Unified,
+        })
+    }
+
+    pub fn write_policy(&self) -> Option<CacheWritePolicy> {
+        if !{ self.flags }.get_bit(5) {
+            return None;
+        }
+
+        Some(if self.attributes.get_bit(4) { CacheWritePolicy::WriteThrough } else { CacheWritePolicy::WriteBack })
+    }
+
+    /// The size of a cache line, in bytes.
+    pub fn line_size(&self) -> Option<u16> {
+        if { self.flags }.get_bit(6) {
+            Some(self.line_size)
+        } else {
+            None
+        }
+    }
+
+    /// A unique, non-zero identifier for the cache. Only present in revision 3 and later of the PPTT, so
+    /// `pptt_revision` should be the revision of the table this structure came from.
+    pub fn cache_id(&self, pptt_revision: u8) -> Option<u32> {
+        const CACHE_ID_END: usize = mem::size_of::<CacheType>() + mem::size_of::<u32>();
+
+        if pptt_revision < 3 || !{ self.flags }.get_bit(7) || (self.header.length as usize) < CACHE_ID_END {
+            return None;
+        }
+
+        Some(unsafe {
+            ptr::read_unaligned(
+                (self as *const CacheType as *const u8).add(mem::size_of::<CacheType>()) as *const u32
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn entry_header<T>(entry_type: u8, extra: usize) -> EntryHeader {
+        EntryHeader { entry_type, length: (mem::size_of::<T>() + extra) as u8, _reserved: 0 }
+    }
+
+    fn node(flags: u32, parent: u32, acpi_processor_id: u32, private_resources: &[u32]) -> Vec<u8> {
+        let mut bytes = bytes_of(&ProcessorHierarchyNode {
+            header: entry_header::<ProcessorHierarchyNode>(0, private_resources.len() * 4),
+            flags,
+            parent,
+            acpi_processor_id,
+            number_of_private_resources: private_resources.len() as u32,
+        });
+        for resource in private_resources {
+            bytes.extend(resource.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_entries() {
+        const L2_OFFSET: u32 = 36;
+        const PACKAGE_OFFSET: u32 = 36 + 28;
+
+        let mut body = Vec::new();
+        let mut l2 = bytes_of(&CacheType {
+            header: entry_header::<CacheType>(1, 4),
+            flags: 0xff,
+            next_level_of_cache: 0,
+            size: 0x10_0000,
+            number_of_sets: 1024,
+            associativity: 16,
+            attributes: 0b01010,
+            line_size: 64,
+        });
+        l2.extend(7u32.to_le_bytes());
+        body.extend(l2);
+        body.extend(node(0b00001, 0, 0, &[]));
+        // A deprecated ID structure, which is skipped
+        body.extend(bytes_of(&entry_header::<EntryHeader>(2, 4)));
+        body.extend([0; 4]);
+        // A node too short to be read as one is also skipped
+        body.extend(bytes_of(&entry_header::<EntryHeader>(0, 4)));
+        body.extend([0; 4]);
+        let core_offset = (36 + body.len()) as u32;
+        body.extend(node(0b01010, PACKAGE_OFFSET, 5, &[L2_OFFSET]));
+
+        let buffer = TableBuffer::<Pptt>::new(&table(b"PPTT", 3, &body));
+        let pptt = buffer.get();
+        assert!(pptt.validate().is_ok());
+
+        let offsets: Vec<_> = pptt.entries().map(|(offset, _)| offset).collect();
+        assert_eq!(offsets, [L2_OFFSET, PACKAGE_OFFSET, core_offset]);
+
+        let core = pptt.processor_hierarchy_node_at_offset(core_offset).unwrap();
+        assert!(core.is_leaf());
+        assert!(core.acpi_processor_id_valid());
+        assert_eq!({ core.acpi_processor_id }, 5);
+        assert!(pptt.processor_hierarchy_node_at_offset(core.parent).unwrap().is_physical_package());
+
+        let cache = pptt.cache_at_offset(core.private_resources().next().unwrap()).unwrap();
+        assert_eq!(cache.size(), Some(0x10_0000));
+        assert_eq!(cache.associativity(), Some(16));
+        assert_eq!(cache.allocation_type(), Some(CacheAllocationType::ReadWrite));
+        assert_eq!(cache.kind(), Some(CacheKind::Unified));
+        assert_eq!(cache.write_policy(), Some(CacheWritePolicy::WriteBack));
+        assert_eq!(cache.line_size(), Some(64));
+        assert_eq!(cache.cache_id(3), Some(7));
+        assert_eq!(cache.cache_id(2), None);
+
+        assert!(pptt.entry_at_offset(0).is_none());
+        assert!(pptt.cache_at_offset(PACKAGE_OFFSET).is_none());
+        assert!(pptt.entry_at_offset(PACKAGE_OFFSET + 1).is_none());
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        let node = node(0, 0, 0, &[]);
+        let buffer = TableBuffer::<Pptt>::new(&table(b"PPTT", 3, &node[..node.len() - 1]));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
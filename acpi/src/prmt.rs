@@ -0,0 +1,231 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{marker::PhantomData, mem};
+
+/// Represents the Platform Runtime Mechanism Table (PRMT), which describes the PRM modules the firmware provides.
+/// Each module contains a set of handlers: native code the OS can call at runtime (instead of using SMM), which
+/// are identified by GUID and invoked with a pointer to a static data buffer and an ACPI parameter buffer.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Prmt {
+    pub header: SdtHeader,
+    /// A GUID identifying the platform, in the mixed-endian format it is stored in.
+    pub platform_guid: [u8; 16],
+    /// The offset of the first module information structure from the start of the table.
+    pub module_info_offset: u32,
+    pub module_info_count: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid PRMT.
+unsafe impl AcpiTable for Prmt {
+    const SIGNATURE: Signature = Signature::PRMT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Prmt {
+    pub fn modules(&self) -> PrmModuleIter<'_> {
+        let offset = usize::max(self.module_info_offset as usize, mem::size_of::<Prmt>());
+
+        PrmModuleIter {
+            pointer: unsafe { (self as *const Prmt as *const u8).add(offset) },
+            remaining_length: (self.header.length as usize).saturating_sub(offset),
+            remaining_structures: self.module_info_count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Find the handler identified by `guid` (in the mixed-endian format it is stored in), in any of the modules.
+    pub fn find_handler(&self, guid: &[u8; 16]) -> Option<&PrmHandlerInfo> {
+        self.modules().flat_map(|module| module.handlers()).find(|handler| &handler.guid == guid)
+    }
+}
+
+#[derive(Debug)]
+pub struct PrmModuleIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_structures: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for PrmModuleIter<'a> {
+    type Item = &'a PrmModuleInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_structures == 0 || self.remaining_length < mem::size_of::<PrmModuleInfo>() {
+            return None;
+        }
+
+        let module = unsafe { &*(self.pointer as *const PrmModuleInfo) };
+        let length = module.length as usize;
+        if length < mem::size_of::<PrmModuleInfo>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_structures -= 1;
+        Some(module)
+    }
+}
+
+/// Describes a PRM module, and contains the handlers it provides.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PrmModuleInfo {
+    pub revision: u16,
+    pub length: u16,
+    /// A GUID identifying the module, in the mixed-endian format it is stored in.
+    pub guid: [u8; 16],
+    pub major_revision: u16,
+    pub minor_revision: u16,
+    pub handler_info_count: u16,
+    /// The offset of the first handler information structure from the start of this structure.
+    pub handler_info_offset: u32,
+    /// The physical address of the list of MMIO ranges the module's handlers need to access, or `0` if they don't
+    /// need to access any. The OS must map these ranges before calling the handlers.
+    pub runtime_mmio_ranges: u64,
+}
+
+impl PrmModuleInfo {
+    pub fn handlers(&self) -> PrmHandlerIter<'_> {
+        let offset = usize::max(self.handler_info_offset as usize, mem::size_of::<PrmModuleInfo>());
+
+        PrmHandlerIter {
+            pointer: unsafe { (self as *const PrmModuleInfo as *const u8).add(offset) },
+            remaining_length: (self.length as usize).saturating_sub(offset),
+            remaining_structures: self.handler_info_count,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PrmHandlerIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_structures: u16,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for PrmHandlerIter<'a> {
+    type Item = &'a PrmHandlerInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_structures == 0 || self.remaining_length < mem::size_of::<PrmHandlerInfo>() {
+            return None;
+        }
+
+        let handler = unsafe { &*(self.pointer as *const PrmHandlerInfo) };
+        let length = handler.length as usize;
+        if length < mem::size_of::<PrmHandlerInfo>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_structures -= 1;
+        Some(handler)
+    }
+}
+
+/// Describes a PRM handler. To invoke a handler, the OS calls the function at `handler_address` with the
+/// `acpi_parameter_buffer_address` and `static_data_buffer_address` as arguments (using the calling convention of
+/// the firmware).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PrmHandlerInfo {
+    pub revision: u16,
+    pub length: u16,
+    /// A GUID identifying the handler, in the mixed-endian format it is stored in.
+    pub guid: [u8; 16],
+    /// The physical address of the handler's entry point.
+    pub handler_address: u64,
+    /// The physical address of the handler's static data buffer, or `0` if it doesn't have one.
+    pub static_data_buffer_address: u64,
+    /// The physical address of the buffer used to pass parameters to the handler when it is called through the
+    /// ACPI `PlatformRtMechanism` operation region, or `0` if it isn't.
+    pub acpi_parameter_buffer_address: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn handler(guid: u8) -> PrmHandlerInfo {
+        PrmHandlerInfo {
+            revision: 1,
+            length: mem::size_of::<PrmHandlerInfo>() as u16,
+            guid: [guid; 16],
+            handler_address: 0x7e00_0000 + guid as u64 * 0x100,
+            static_data_buffer_address: 0,
+            acpi_parameter_buffer_address: 0,
+        }
+    }
+
+    fn module(guid: u8, handler_info_count: u16, handlers: &[PrmHandlerInfo]) -> Vec<u8> {
+        let length = mem::size_of::<PrmModuleInfo>() + mem::size_of_val(handlers);
+        let mut bytes = bytes_of(&PrmModuleInfo {
+            revision: 1,
+            length: length as u16,
+            guid: [guid; 16],
+            major_revision: 1,
+            minor_revision: 0,
+            handler_info_count,
+            handler_info_offset: mem::size_of::<PrmModuleInfo>() as u32,
+            runtime_mmio_ranges: 0,
+        });
+        for handler in handlers {
+            bytes.extend(bytes_of(handler));
+        }
+        bytes
+    }
+
+    fn prmt(module_info_count: u32, modules: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = body_of(&Prmt {
+            header: blank_header(),
+            platform_guid: [0; 16],
+            module_info_offset: mem::size_of::<Prmt>() as u32,
+            module_info_count,
+        });
+        for module in modules {
+            body.extend(module);
+        }
+        table(b"PRMT", 0, &body)
+    }
+
+    #[test]
+    fn test_modules() {
+        let modules = [module(0xa0, 2, &[handler(1), handler(2)]), module(0xb0, 1, &[handler(3)])];
+        let buffer = TableBuffer::<Prmt>::new(&prmt(2, &modules));
+        let prmt = buffer.get();
+        assert!(prmt.validate().is_ok());
+        assert_eq!(prmt.modules().count(), 2);
+        assert_eq!(prmt.modules().map(|module| module.handlers().count()).sum::<usize>(), 3);
+        assert_eq!({ prmt.find_handler(&[3; 16]).unwrap().handler_address }, 0x7e00_0300);
+        assert!(prmt.find_handler(&[4; 16]).is_none());
+    }
+
+    #[test]
+    fn test_truncated_structures() {
+        // The module claims more handlers than it contains, and the table claims more modules than it contains
+        let buffer = TableBuffer::<Prmt>::new(&prmt(3, &[module(0xa0, 3, &[handler(1), handler(2)])]));
+        assert_eq!(buffer.get().modules().count(), 1);
+        assert_eq!(buffer.get().modules().next().unwrap().handlers().count(), 2);
+
+        // A handler that claims to be shorter than the structure ends the list of handlers
+        let mut short = handler(2);
+        short.length = 8;
+        let buffer = TableBuffer::<Prmt>::new(&prmt(1, &[module(0xa0, 2, &[handler(1), short])]));
+        assert_eq!(buffer.get().modules().next().unwrap().handlers().count(), 1);
+    }
+}
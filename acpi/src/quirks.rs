@@ -0,0 +1,156 @@
+//! Workarounds for firmware that describes the platform incorrectly. Quirks are matched against the OEM ID, OEM
+//! table ID, and OEM revision of the FADT, which identify the firmware that produced the tables.
+//!
+//! The workarounds that apply to a platform are found with [`QuirkDatabase::quirks_for`], and are applied by
+//! [`PlatformInfo`](crate::platform::PlatformInfo) (and [`PmTimer::new_with_quirks`](crate::platform::PmTimer))
+//! when it's constructed. The workarounds it applied are kept in its `quirks` field, so that they can be passed on
+//! to the `_with_quirks` constructors of the `power` module. Consumers that know of other broken firmware can
+//! register their own entries with [`QuirkDatabase::with_entries`].
+
+use crate::{
+    fadt::{AddressPolicy, Fadt},
+    sdt::SdtHeader,
+    AcpiTable,
+};
+use core::ops::RangeInclusive;
+
+/// A set of workarounds to apply to a platform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// Prefer the legacy 32-bit addresses of the FADT's register blocks to the 64-bit `X_*` addresses (see
+    /// [`AddressPolicy::PreferLegacy`]). Some firmware fills the extended fields with garbage. This applies to
+    /// the PM timer, and to the PM1 and GPE blocks used by the `power` module when the quirks are passed to
+    /// `FixedEvents::new_with_quirks`, `Gpes::new_with_quirks`, and `SleepStates::discover_with_quirks`.
+    pub ignore_extended_addresses: bool,
+    /// Use the legacy i8259 PIC, even if the MADT describes APICs. This is for platforms whose APIC description
+    /// (or interrupt routing through the APICs) is too broken to be used. The interrupt model of the
+    /// [`PlatformInfo`](crate::platform::PlatformInfo) is then [`InterruptModel::Unknown`], which is what it is on
+    /// platforms that only have the PIC, but its processor info still lists the processors from the MADT.
+    ///
+    /// [`InterruptModel::Unknown`]: crate::platform::interrupt::InterruptModel::Unknown
+    pub force_pic_mode: bool,
+    /// Treat the PM timer as 24 bits wide, even if the FADT claims that it is 32 bits wide.
+    pub pm_timer_is_24_bit: bool,
+}
+
+impl Quirks {
+    pub const NONE: Quirks =
+        Quirks { ignore_extended_addresses: false, force_pic_mode: false, pm_timer_is_24_bit: false };
+
+    /// The workarounds that are in either `self` or `other`.
+    pub const fn union(self, other: Quirks) -> Quirks {
+        Quirks {
+            ignore_extended_addresses: self.ignore_extended_addresses || other.ignore_extended_addresses,
+            force_pic_mode: self.force_pic_mode || other.force_pic_mode,
+            pm_timer_is_24_bit: self.pm_timer_is_24_bit || other.pm_timer_is_24_bit,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Quirks::NONE
+    }
+
+    /// The policy to use to choose between the legacy and extended addresses in the FADT.
+    pub fn address_policy(&self) -> AddressPolicy {
+        if self.ignore_extended_addresses {
+            AddressPolicy::PreferLegacy
+        } else {
+            AddressPolicy::PreferExtended
+        }
+    }
+}
+
+/// Describes the firmware that a set of [`Quirks`] should be applied to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuirkEntry {
+    /// The OEM ID of the FADT, padded with spaces (as it appears in the table).
+    pub oem_id: [u8; 6],
+    /// The OEM table ID of the FADT, padded with spaces. If this is `None`, any table ID matches.
+    pub oem_table_id: Option<[u8; 8]>,
+    /// The range of OEM revisions of the FADT that are affected.
+    pub oem_revisions: RangeInclusive<u32>,
+    pub quirks: Quirks,
+}
+
+impl QuirkEntry {
+    /// Create an entry matching every table ID and revision from the OEM `oem_id`.
+    pub const fn new(oem_id: [u8; 6], quirks: Quirks) -> QuirkEntry {
+        QuirkEntry { oem_id, oem_table_id: None, oem_revisions: 0..=u32::MAX, quirks }
+    }
+
+    pub fn matches(&self, header: &SdtHeader) -> bool {
+        let oem_id = header.oem_id;
+        let oem_table_id = header.oem_table_id;
+        let oem_revision = header.oem_revision;
+
+        oem_id == self.oem_id
+            && self.oem_table_id.is_none_or(|table_id| table_id == oem_table_id)
+            && self.oem_revisions.contains(&oem_revision)
+    }
+}
+
+/// The entries built into the library. There are none yet: entries should only be added here for firmware that
+/// is known to be broken, along with a description of the platform it's found on.
+pub const BUILTIN_QUIRKS: &[QuirkEntry] = &[];
+
+/// The entries that [`Quirks`] are looked up in: the [`BUILTIN_QUIRKS`], and any entries registered by the
+/// consumer of the library.
+#[derive(Clone, Copy, Debug)]
+pub struct QuirkDatabase<'a> {
+    entries: &'a [QuirkEntry],
+    use_builtin: bool,
+}
+
+impl QuirkDatabase<'static> {
+    /// A database containing only the built-in entries.
+    pub const fn new() -> QuirkDatabase<'static> {
+        QuirkDatabase { entries: &[], use_builtin: true }
+    }
+
+    /// A database that matches nothing, so that no workarounds are applied.
+    pub const fn empty() -> QuirkDatabase<'static> {
+        QuirkDatabase { entries: &[], use_builtin: false }
+    }
+}
+
+impl<'a> QuirkDatabase<'a> {
+    /// Register additional entries, which are matched as well as the built-in ones (if they are used). This
+    /// replaces any entries that were registered previously.
+    pub const fn with_entries<'b>(self, entries: &'b [QuirkEntry]) -> QuirkDatabase<'b> {
+        QuirkDatabase { entries, use_builtin: self.use_builtin }
+    }
+
+    /// Don't match the built-in entries, only the ones registered with [`QuirkDatabase::with_entries`].
+    pub const fn without_builtin(self) -> QuirkDatabase<'a> {
+        QuirkDatabase { entries: self.entries, use_builtin: false }
+    }
+
+    /// The entries that match the firmware that produced the table with the given `header`.
+    pub fn matching<'h>(&self, header: &'h SdtHeader) -> impl Iterator<Item = &'a QuirkEntry> + 'h
+    where
+        'a: 'h,
+    {
+        let builtin = if self.use_builtin { BUILTIN_QUIRKS } else { &[] };
+        builtin.iter().chain(self.entries.iter()).filter(move |entry| entry.matches(header))
+    }
+
+    /// The workarounds to apply to the platform described by `fadt`.
+    pub fn quirks_for(&self, fadt: &Fadt) -> Quirks {
+        self.matching(fadt.header()).fold(Quirks::NONE, |quirks, entry| {
+            log::info!(
+                "Applying firmware quirks for OEM {:?} (table ID {:?}, revision {:#x}): {:?}",
+                fadt.header().oem_id(),
+                fadt.header().oem_table_id(),
+                { fadt.header().oem_revision },
+                entry.quirks
+            );
+            quirks.union(entry.quirks)
+        })
+    }
+}
+
+impl Default for QuirkDatabase<'static> {
+    fn default() -> Self {
+        QuirkDatabase::new()
+    }
+}
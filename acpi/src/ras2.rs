@@ -0,0 +1,156 @@
+use crate::{
+    pcct::{Pcct, PcctSubspace},
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, slice};
+
+/// Represents the ACPI RAS2 Feature Table (RAS2), which supersedes the RASF. Rather than one PCC subspace for the
+/// whole platform, it lists a PCC subspace for each instance of a RAS feature (e.g. one per memory proximity
+/// domain). The shared memory region of each subspace has the layout of a [`Ras2CommunicationRegion`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Ras2 {
+    pub header: SdtHeader,
+    _reserved: u16,
+    pub number_of_pcc_descriptors: u16,
+}
+
+/// ### Safety: Implementation properly represents a valid RAS2.
+unsafe impl AcpiTable for Ras2 {
+    const SIGNATURE: Signature = Signature::RAS2;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Ras2 {
+    pub fn pcc_descriptors(&self) -> &[PccDescriptor] {
+        let available =
+            (self.header.length as usize).saturating_sub(mem::size_of::<Ras2>()) / mem::size_of::<PccDescriptor>();
+
+        unsafe {
+            let pointer = (self as *const Ras2 as *const u8).add(mem::size_of::<Ras2>());
+            slice::from_raw_parts(
+                pointer as *const PccDescriptor,
+                usize::min(self.number_of_pcc_descriptors as usize, available),
+            )
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ras2FeatureType {
+    Memory,
+    Reserved(u8),
+}
+
+/// Associates an instance of a RAS feature with the PCC subspace used to control it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PccDescriptor {
+    /// The index of the PCC subspace in the PCCT.
+    pub pcc_id: u8,
+    _reserved: u16,
+    feature_type: u8,
+    /// Identifies the instance of the feature. For memory features, this is the proximity domain of the memory.
+    pub instance: u32,
+}
+
+impl PccDescriptor {
+    pub fn feature_type(&self) -> Ras2FeatureType {
+        match self.feature_type {
+            0 => Ras2FeatureType::Memory,
+            other => Ras2FeatureType::Reserved(other),
+        }
+    }
+
+    /// Get the PCC subspace described by this descriptor, from the platform's PCCT.
+    pub fn communication_channel<'a>(&self, pcct: &'a Pcct) -> Option<PcctSubspace<'a>> {
+        pcct.subspace(self.pcc_id as usize)
+    }
+}
+
+/// A bitmap of RAS2 features, used to report which features a PCC subspace supports, and by the OS to select which
+/// features it wants to use.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct Ras2Features(pub [u8; 16]);
+
+impl Ras2Features {
+    pub fn get(&self, bit: usize) -> bool {
+        bit < 128 && self.0[bit / 8].get_bit(bit % 8)
+    }
+
+    pub fn patrol_scrub(&self) -> bool {
+        self.get(0)
+    }
+
+    /// If true, the platform can translate logical addresses (e.g. addresses reported in error records) to
+    /// physical addresses.
+    pub fn la2pa_translation(&self) -> bool {
+        self.get(1)
+    }
+}
+
+/// The layout of the shared memory region of a RAS2 PCC subspace. It is followed by `number_of_parameter_blocks`
+/// feature-specific parameter blocks.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ras2CommunicationRegion {
+    pub signature: u32,
+    pub command: u16,
+    pub status: u16,
+    pub version: u16,
+    pub features: Ras2Features,
+    pub set_capabilities: Ras2Features,
+    pub number_of_parameter_blocks: u16,
+    pub set_capabilities_status: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, finish_table, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn ras2(number_of_pcc_descriptors: u16, descriptors: &[PccDescriptor]) -> Vec<u8> {
+        let mut body = body_of(&Ras2 { header: blank_header(), _reserved: 0, number_of_pcc_descriptors });
+        for descriptor in descriptors {
+            body.extend(bytes_of(descriptor));
+        }
+        table(b"RAS2", 1, &body)
+    }
+
+    fn descriptor(pcc_id: u8, feature_type: u8, instance: u32) -> PccDescriptor {
+        PccDescriptor { pcc_id, _reserved: 0, feature_type, instance }
+    }
+
+    #[test]
+    fn test_pcc_descriptors() {
+        let buffer = TableBuffer::<Ras2>::new(&ras2(2, &[descriptor(0, 0, 0), descriptor(1, 1, 1)]));
+        let ras2 = buffer.get();
+        assert!(ras2.validate().is_ok());
+
+        let descriptors = ras2.pcc_descriptors();
+        assert_eq!(descriptors.len(), 2);
+        assert_eq!(descriptors[0].feature_type(), Ras2FeatureType::Memory);
+        assert_eq!(descriptors[1].feature_type(), Ras2FeatureType::Reserved(1));
+        assert_eq!({ descriptors[1].instance }, 1);
+
+        let pcct = TableBuffer::<Pcct>::new(&table(b"PCCT", 2, &[0; 12]));
+        assert!(descriptors[0].communication_channel(pcct.get()).is_none());
+    }
+
+    #[test]
+    fn test_truncated_pcc_descriptors() {
+        // Only the descriptors that fit in the table are read
+        let mut bytes = ras2(3, &[descriptor(0, 0, 0), descriptor(1, 0, 1)]);
+        bytes.pop();
+        finish_table(&mut bytes);
+        let buffer = TableBuffer::<Ras2>::new(&bytes);
+        assert_eq!(buffer.get().pcc_descriptors().len(), 1);
+    }
+}
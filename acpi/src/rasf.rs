@@ -0,0 +1,220 @@
+use crate::{
+    pcct::{Pcct, PcctSubspace},
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+
+/// Represents the ACPI RAS Feature Table (RASF), which identifies the PCC subspace the OS can use to control the
+/// platform's RAS features, such as patrol scrubbing. The shared memory region of the subspace has the layout of
+/// a [`RasfCommunicationRegion`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Rasf {
+    pub header: SdtHeader,
+    /// Identifies the PCC subspace used to communicate with the platform. The first byte is the index of the
+    /// subspace in the PCCT.
+    pub pcc_identifier: [u8; 12],
+}
+
+/// ### Safety: Implementation properly represents a valid RASF.
+unsafe impl AcpiTable for Rasf {
+    const SIGNATURE: Signature = Signature::RASF;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Rasf {
+    pub fn pcc_subspace_id(&self) -> u8 {
+        self.pcc_identifier[0]
+    }
+
+    /// Get the PCC subspace used to communicate with the platform, from the platform's PCCT.
+    pub fn communication_channel<'a>(&self, pcct: &'a Pcct) -> Option<PcctSubspace<'a>> {
+        pcct.subspace(self.pcc_subspace_id() as usize)
+    }
+}
+
+/// A bitmap of RAS features, used to report which features the platform supports, and by the OS to select which
+/// features it wants to use.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct RasfFeatures(pub [u8; 16]);
+
+impl RasfFeatures {
+    pub fn get(&self, bit: usize) -> bool {
+        bit < 128 && self.0[bit / 8].get_bit(bit % 8)
+    }
+
+    pub fn hardware_patrol_scrub(&self) -> bool {
+        self.get(0)
+    }
+
+    /// If true, the hardware patrol scrubber can be controlled by the OS through the
+    /// [`PatrolScrubParameterBlock`].
+    pub fn hardware_patrol_scrub_exposed_to_software(&self) -> bool {
+        self.get(1)
+    }
+}
+
+/// The layout of the shared memory region of the RASF PCC subspace. It is followed by
+/// `number_of_parameter_blocks` parameter blocks, the first of which is usually a [`PatrolScrubParameterBlock`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RasfCommunicationRegion {
+    /// Should be `0x52415346` ("RASF").
+    pub signature: u32,
+    pub command: u16,
+    pub status: u16,
+    pub version: u16,
+    pub ras_capabilities: RasfFeatures,
+    pub set_ras_capabilities: RasfFeatures,
+    pub number_of_parameter_blocks: u16,
+    pub set_ras_capabilities_status: u32,
+}
+
+impl RasfCommunicationRegion {
+    pub const SIGNATURE: u32 = 0x52415346;
+    /// The value written to `command` to ask the platform to execute the command described by the parameter
+    /// blocks.
+    pub const EXECUTE_COMMAND: u16 = 0x01;
+
+    pub fn is_command_complete(&self) -> bool {
+        { self.status }.get_bit(0)
+    }
+
+    pub fn is_error(&self) -> bool {
+        { self.status }.get_bit(2)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PatrolScrubCommand {
+    /// Get the current patrol scrub parameters (and the range they apply to).
+    GetParameters,
+    Start,
+    Stop,
+    Reserved(u16),
+}
+
+impl From<PatrolScrubCommand> for u16 {
+    fn from(command: PatrolScrubCommand) -> u16 {
+        match command {
+            PatrolScrubCommand::GetParameters => 0x01,
+            PatrolScrubCommand::Start => 0x02,
+            PatrolScrubCommand::Stop => 0x03,
+            PatrolScrubCommand::Reserved(other) => other,
+        }
+    }
+}
+
+/// The parameter block used to control the hardware patrol scrubber.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PatrolScrubParameterBlock {
+    /// Should be `0` for this parameter block.
+    pub block_type: u16,
+    pub version: u16,
+    pub length: u16,
+    pub patrol_scrub_command: u16,
+    /// The base address and length of the range the OS wants to scrub.
+    pub requested_address_range: [u64; 2],
+    /// The base address and length of the range the platform is actually scrubbing.
+    pub actual_address_range: [u64; 2],
+    pub flags: u16,
+    pub requested_speed: u8,
+}
+
+impl PatrolScrubParameterBlock {
+    pub fn command(&self) -> PatrolScrubCommand {
+        match self.patrol_scrub_command {
+            0x01 => PatrolScrubCommand::GetParameters,
+            0x02 => PatrolScrubCommand::Start,
+            0x03 => PatrolScrubCommand::Stop,
+            other => PatrolScrubCommand::Reserved(other),
+        }
+    }
+
+    /// If true, the patrol scrubber is currently running (after a [`PatrolScrubCommand::GetParameters`]).
+    pub fn is_running(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    /// The speed the scrubber is running at, from bits `1..4` of the flags: `0` is slow, `4` is medium, and `7` is
+    /// fast.
+    pub fn current_speed(&self) -> u8 {
+        { self.flags }.get_bits(1..4) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use core::mem;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_communication_channel() {
+        let mut pcc_identifier = [0; 12];
+        pcc_identifier[0] = 1;
+        let buffer = TableBuffer::<Rasf>::new(&table(b"RASF", 1, &pcc_identifier));
+        let rasf = buffer.get();
+        assert!(rasf.validate().is_ok());
+        assert_eq!(rasf.pcc_subspace_id(), 1);
+
+        let mut pcct = Vec::new();
+        pcct.extend([0; 12]);
+        pcct.extend([0x5, 8, 0, 0, 0, 0, 0, 0]);
+        pcct.extend([0x6, 8, 0, 0, 0, 0, 0, 0]);
+        let pcct = TableBuffer::<Pcct>::new(&table(b"PCCT", 2, &pcct));
+        assert!(matches!(
+            rasf.communication_channel(pcct.get()),
+            Some(PcctSubspace::Other(header)) if header.subspace_type == 0x6
+        ));
+
+        // The PCCT doesn't have the subspace at all
+        let pcct = TableBuffer::<Pcct>::new(&table(b"PCCT", 2, &[0; 12]));
+        assert!(rasf.communication_channel(pcct.get()).is_none());
+    }
+
+    #[test]
+    fn test_communication_region() {
+        let mut capabilities = [0; 16];
+        capabilities[0] = 0b11;
+        let region = RasfCommunicationRegion {
+            signature: RasfCommunicationRegion::SIGNATURE,
+            command: RasfCommunicationRegion::EXECUTE_COMMAND,
+            status: 0b101,
+            version: 1,
+            ras_capabilities: RasfFeatures(capabilities),
+            set_ras_capabilities: RasfFeatures([0; 16]),
+            number_of_parameter_blocks: 1,
+            set_ras_capabilities_status: 0,
+        };
+        assert!(region.is_command_complete());
+        assert!(region.is_error());
+        assert!(region.ras_capabilities.hardware_patrol_scrub());
+        assert!(region.ras_capabilities.hardware_patrol_scrub_exposed_to_software());
+        assert!(!region.set_ras_capabilities.hardware_patrol_scrub());
+        assert!(!region.ras_capabilities.get(128));
+
+        let bytes = bytes_of(&PatrolScrubParameterBlock {
+            block_type: 0,
+            version: 1,
+            length: mem::size_of::<PatrolScrubParameterBlock>() as u16,
+            patrol_scrub_command: u16::from(PatrolScrubCommand::GetParameters),
+            requested_address_range: [0, 0x1_0000_0000],
+            actual_address_range: [0, 0x8000_0000],
+            flags: 0b1001,
+            requested_speed: 0,
+        });
+        let buffer = TableBuffer::<PatrolScrubParameterBlock>::new(&bytes);
+        let block = buffer.get();
+        assert_eq!(block.command(), PatrolScrubCommand::GetParameters);
+        assert!(block.is_running());
+        assert_eq!(block.current_speed(), 4);
+    }
+}
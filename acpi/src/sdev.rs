@@ -0,0 +1,250 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem, slice, str};
+
+/// Represents the Secure Devices Table (SDEV), which lists the devices that the firmware has assigned to the
+/// secure world (e.g. a secure OS or hypervisor running alongside the OS). The OS should not try to use these
+/// devices, unless the firmware says that they may be handed off to it.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Sdev {
+    pub header: SdtHeader,
+}
+
+/// ### Safety: Implementation properly represents a valid SDEV.
+unsafe impl AcpiTable for Sdev {
+    const SIGNATURE: Signature = Signature::SDEV;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Sdev {
+    pub fn entries(&self) -> SecureDeviceIter<'_> {
+        SecureDeviceIter {
+            pointer: unsafe { (self as *const Sdev as *const u8).add(mem::size_of::<Sdev>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Sdev>()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SecureDeviceIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum SecureDevice<'a> {
+    AcpiNamespaceDevice(&'a AcpiNamespaceDevice),
+    PcieEndpoint(&'a PcieEndpointDevice),
+    /// A structure of a reserved type.
+    Reserved(&'a SecureDeviceHeader),
+}
+
+impl<'a> Iterator for SecureDeviceIter<'a> {
+    type Item = SecureDevice<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<SecureDeviceHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const SecureDeviceHeader) };
+        let length = header.length as usize;
+        if length < mem::size_of::<SecureDeviceHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let entry_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        match header.device_type {
+            0 if length >= mem::size_of::<AcpiNamespaceDevice>() => {
+                Some(SecureDevice::AcpiNamespaceDevice(unsafe { &*(entry_pointer as *const AcpiNamespaceDevice) }))
+            }
+            1 if length >= mem::size_of::<PcieEndpointDevice>() => {
+                Some(SecureDevice::PcieEndpoint(unsafe { &*(entry_pointer as *const PcieEndpointDevice) }))
+            }
+            _ => Some(SecureDevice::Reserved(header)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SecureDeviceHeader {
+    pub device_type: u8,
+    pub flags: u8,
+    pub length: u16,
+}
+
+impl SecureDeviceHeader {
+    /// If true, the secure world may hand the device off to the OS, so the OS can use it if it is not being used
+    /// by the secure world.
+    pub fn allow_handoff(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the structure is followed by secure access components, which describe the parts of the device
+    /// the secure world controls.
+    pub fn has_secure_access_components(&self) -> bool {
+        self.flags.get_bit(1)
+    }
+}
+
+/// Get `length` bytes at `offset` from the start of a secure device structure, if they are within it.
+fn bytes_at<T>(structure: &T, structure_length: u16, offset: u16, length: u16) -> Option<&[u8]> {
+    let (offset, length) = (offset as usize, length as usize);
+    if offset < mem::size_of::<T>() || offset + length > structure_length as usize {
+        return None;
+    }
+
+    Some(unsafe { slice::from_raw_parts((structure as *const T as *const u8).add(offset), length) })
+}
+
+/// A device that is identified by its path in the ACPI namespace.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AcpiNamespaceDevice {
+    pub header: SecureDeviceHeader,
+    pub device_identifier_offset: u16,
+    pub device_identifier_length: u16,
+    pub vendor_specific_data_offset: u16,
+    pub vendor_specific_data_length: u16,
+}
+
+impl AcpiNamespaceDevice {
+    /// The path of the device in the namespace (e.g. `\_SB.TPM`).
+    pub fn device_identifier(&self) -> Option<&str> {
+        let bytes =
+            bytes_at(self, self.header.length, self.device_identifier_offset, self.device_identifier_length)?;
+        let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+        str::from_utf8(&bytes[..end]).ok()
+    }
+
+    pub fn vendor_specific_data(&self) -> Option<&[u8]> {
+        bytes_at(self, self.header.length, self.vendor_specific_data_offset, self.vendor_specific_data_length)
+    }
+}
+
+/// A PCIe endpoint, identified by its segment, start bus, and the path of devices and functions from the start bus
+/// to the endpoint.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieEndpointDevice {
+    pub header: SecureDeviceHeader,
+    pub pci_segment_number: u16,
+    pub start_bus_number: u16,
+    pub pci_path_offset: u16,
+    pub pci_path_length: u16,
+    pub vendor_specific_data_offset: u16,
+    pub vendor_specific_data_length: u16,
+}
+
+impl PcieEndpointDevice {
+    /// The path to the endpoint, as `(device, function)` pairs.
+    pub fn pci_path(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        let path = bytes_at(self, self.header.length, self.pci_path_offset, self.pci_path_length).unwrap_or(&[]);
+        path.chunks_exact(2).map(|entry| (entry[0], entry[1]))
+    }
+
+    pub fn vendor_specific_data(&self) -> Option<&[u8]> {
+        bytes_at(self, self.header.length, self.vendor_specific_data_offset, self.vendor_specific_data_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn namespace_device(name: &[u8], vendor_data: &[u8]) -> Vec<u8> {
+        let name_offset = mem::size_of::<AcpiNamespaceDevice>();
+        let vendor_data_offset = name_offset + name.len();
+        let mut bytes = bytes_of(&AcpiNamespaceDevice {
+            header: SecureDeviceHeader {
+                device_type: 0,
+                flags: 0b1,
+                length: (vendor_data_offset + vendor_data.len()) as u16,
+            },
+            device_identifier_offset: name_offset as u16,
+            device_identifier_length: name.len() as u16,
+            vendor_specific_data_offset: vendor_data_offset as u16,
+            vendor_specific_data_length: vendor_data.len() as u16,
+        });
+        bytes.extend(name);
+        bytes.extend(vendor_data);
+        bytes
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut body = namespace_device(b"\\_SB.TPM\0", &[1, 2, 3]);
+        let path_offset = mem::size_of::<PcieEndpointDevice>();
+        body.extend(bytes_of(&PcieEndpointDevice {
+            header: SecureDeviceHeader { device_type: 1, flags: 0b10, length: (path_offset + 4) as u16 },
+            pci_segment_number: 0,
+            start_bus_number: 0,
+            pci_path_offset: path_offset as u16,
+            pci_path_length: 4,
+            vendor_specific_data_offset: 0,
+            vendor_specific_data_length: 0,
+        }));
+        body.extend([0x1c, 0x0, 0x0, 0x1]);
+        // A PCIe endpoint structure too short to be read as one
+        body.extend(bytes_of(&SecureDeviceHeader { device_type: 1, flags: 0, length: 8 }));
+        body.extend([0; 4]);
+
+        let buffer = TableBuffer::<Sdev>::new(&table(b"SDEV", 1, &body));
+        let sdev = buffer.get();
+        assert!(sdev.validate().is_ok());
+
+        let entries: Vec<_> = sdev.entries().collect();
+        assert_eq!(entries.len(), 3);
+        match entries[0] {
+            SecureDevice::AcpiNamespaceDevice(device) => {
+                assert!(device.header.allow_handoff());
+                assert_eq!(device.device_identifier(), Some("\\_SB.TPM"));
+                assert_eq!(device.vendor_specific_data(), Some(&[1, 2, 3][..]));
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            SecureDevice::PcieEndpoint(device) => {
+                assert!(device.header.has_secure_access_components());
+                assert!(device.pci_path().eq([(0x1c, 0x0), (0x0, 0x1)]));
+                assert_eq!(device.vendor_specific_data(), None);
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        assert!(matches!(entries[2], SecureDevice::Reserved(header) if header.device_type == 1));
+    }
+
+    #[test]
+    fn test_truncated_entry() {
+        // The name runs past the end of the structure, so it isn't read
+        let mut device = namespace_device(b"\\_SB.TPM\0", &[]);
+        device[2..4].copy_from_slice(&(mem::size_of::<AcpiNamespaceDevice>() as u16 + 4).to_le_bytes());
+        device.truncate(mem::size_of::<AcpiNamespaceDevice>() + 4);
+        let buffer = TableBuffer::<Sdev>::new(&table(b"SDEV", 1, &device));
+        match buffer.get().entries().next() {
+            Some(SecureDevice::AcpiNamespaceDevice(device)) => assert_eq!(device.device_identifier(), None),
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+
+        // A structure that runs past the end of the table ends the list
+        let device = namespace_device(b"\\_SB.TPM\0", &[]);
+        let buffer = TableBuffer::<Sdev>::new(&table(b"SDEV", 1, &device[..device.len() - 1]));
+        assert_eq!(buffer.get().entries().count(), 0);
+    }
+}
@@ -1,5 +1,10 @@
-use crate::{AcpiError, AcpiHandler, AcpiResult, AcpiTable, PhysicalMapping};
-use core::{fmt, mem::MaybeUninit, str};
+use crate::{AcpiError, AcpiHandler, AcpiResult, AcpiTable, PhysicalMapping, RsdpError};
+use core::{
+    fmt,
+    mem::{self, MaybeUninit},
+    ptr,
+    str,
+};
 
 /// Represents a field which may or may not be present within an ACPI structure, depending on the version of ACPI
 /// that a system supports. If the field is not present, it is not safe to treat the data as initialised.
@@ -8,6 +13,10 @@ use core::{fmt, mem::MaybeUninit, str};
 pub struct ExtendedField<T: Copy, const MIN_REVISION: u8>(MaybeUninit<T>);
 
 impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
+    pub(crate) const fn new(value: T) -> Self {
+        ExtendedField(MaybeUninit::new(value))
+    }
+
     /// Access the field if it's present for the given revision of the table.
     ///
     /// ### Safety
@@ -39,6 +48,7 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * FPDT - Firmware Performance Data Table
 /// * GTDT - Generic Timer Description Table
 /// * HEST - Hardware Error Source Table
+/// * HMAT - Heterogeneous Memory Attribute Table
 /// * MSCT - Maximum System Characteristics Table
 /// * MPST - Memory Power StateTable
 /// * NFIT - NVDIMM Firmware Interface Table
@@ -46,8 +56,10 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * PCCT - Platform Communications Channel Table
 /// * PHAT - Platform Health Assessment Table
 /// * PMTT - Platform Memory Topology Table
+/// * PPTT - Processor Properties Topology Table
 /// * PSDT - Persistent System Description Table
 /// * RASF - ACPI RAS Feature Table
+/// * RAS2 - ACPI RAS2 Feature Table
 /// * RSDT - Root System Description Table
 /// * SBST - Smart Battery Specification Table
 /// * SDEV - Secure DEVices Table
@@ -59,7 +71,9 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// Acpi reserves the following signatures and the specifications for them can be found [here](https://uefi.org/acpi):
 ///
 /// * AEST - ARM Error Source Table
+/// * APMT - ARM Performance Monitoring Unit Table
 /// * BDAT - BIOS Data ACPI Table
+/// * BOOT - Simple Boot Flag Table
 /// * CDIT - Component Distance Information Table
 /// * CEDT - CXL Early Discovery Table
 /// * CRAT - Component Resource Attribute Table
@@ -89,6 +103,7 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * TCPA - Trusted Computing Platform Alliance Capabilities Table
 /// * TPM2 - Trusted Platform Module 2 Table
 /// * UEFI - Unified Extensible Firmware Interface Specification table
+/// * VIOT - Virtual I/O Translation Table
 /// * WAET - Windows ACPI Emulated Devices Table
 /// * WDAT - Watch Dog Action Table
 /// * WDRT - Watchdog Resource Table
@@ -110,68 +125,102 @@ pub struct SdtHeader {
 }
 
 impl SdtHeader {
-    /// Whether values of header fields are permitted.
-    fn validate_header_fields(&self, signature: Signature) -> AcpiResult<()> {
+    /// Read the extended field at `field`, which must point into the table this is the header of, if the table's
+    /// revision says that it's present and the table is long enough to contain it. Firmware doesn't always update
+    /// the length of a table along with its revision, and the field isn't read at all if it's missing, as it may
+    /// not be mapped.
+    pub(crate) fn extended_field<T: Copy, const MIN_REVISION: u8>(
+        &self,
+        field: *const ExtendedField<T, MIN_REVISION>,
+    ) -> Option<T> {
+        let end = field as usize - (self as *const SdtHeader as usize) + mem::size_of::<T>();
+        if self.revision < MIN_REVISION || (self.length as usize) < end {
+            return None;
+        }
+
+        // SAFETY: The field is inside the table, which is mapped in its entirety, and is present in its revision.
+        unsafe { ptr::read_unaligned(field).access(self.revision) }
+    }
+
+    /// Check the header and checksum of the table, calling `report` with each problem that is found, in the order
+    /// they're checked in. If the signature is wrong, nothing else is checked.
+    ///
+    /// This assumes that the whole SDT is mapped.
+    pub(crate) fn find_problems(&self, signature: Signature, mut report: impl FnMut(AcpiError)) {
         // Check the signature
         if self.signature != signature || str::from_utf8(&self.signature.0).is_err() {
-            return Err(AcpiError::SdtInvalidSignature(signature));
+            report(AcpiError::SdtInvalidSignature(signature));
+            return;
         }
 
         // Check the OEM id
         if str::from_utf8(&self.oem_id).is_err() {
-            return Err(AcpiError::SdtInvalidOemId(signature));
+            report(AcpiError::SdtInvalidOemId(signature));
         }
 
         // Check the OEM table id
         if str::from_utf8(&self.oem_table_id).is_err() {
-            return Err(AcpiError::SdtInvalidTableId(signature));
+            report(AcpiError::SdtInvalidTableId(signature));
         }
 
-        Ok(())
-    }
+        // Check the table is at least long enough to contain its header
+        if (self.length as usize) < mem::size_of::<SdtHeader>() {
+            report(AcpiError::SdtInvalidLength(signature));
+        }
 
-    /// Whether table is valid according to checksum.
-    fn validate_checksum(&self, signature: Signature) -> AcpiResult<()> {
+        // Check the checksum
         // SAFETY: Entire table is mapped.
         let table_bytes =
             unsafe { core::slice::from_raw_parts((self as *const SdtHeader).cast::<u8>(), self.length as usize) };
         let sum = table_bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
-
-        if sum == 0 {
-            Ok(())
-        } else {
-            Err(AcpiError::SdtInvalidChecksum(signature))
+        if sum != 0 {
+            report(AcpiError::SdtInvalidChecksum(signature));
         }
     }
 
+    /// Check the header and checksum of the table, ignoring any problems that `policy` allows (which are logged
+    /// instead). Returns the first problem that isn't allowed.
+    ///
+    /// This assumes that the whole SDT is mapped.
+    pub(crate) fn validate_with_policy(&self, signature: Signature, policy: ValidationPolicy) -> AcpiResult<()> {
+        policy.check(signature, |report| self.find_problems(signature, report))
+    }
+
     /// Checks that:
     ///
     /// 1. The signature matches the one given.
     /// 2. The values of various fields in the header are allowed.
-    /// 3. The checksum of the SDT is valid.
+    /// 3. The length of the SDT is long enough to contain the header.
+    /// 4. The checksum of the SDT is valid.
     ///
     /// This assumes that the whole SDT is mapped.
     pub fn validate(&self, signature: Signature) -> AcpiResult<()> {
-        self.validate_header_fields(signature)?;
-        self.validate_checksum(signature)?;
-
-        Ok(())
+        self.validate_with_policy(signature, ValidationPolicy::STRICT)
     }
 
     /// Validates header, proceeding with checking entire table and returning a [`PhysicalMapping`] to it if
     /// successful.
     ///
-    /// The same checks are performed as [`SdtHeader::validate`], but `header_mapping` does not have to map the
-    /// entire table when calling. This is useful to avoid completely mapping a table that will be immediately
+    /// The same checks are performed as [`SdtHeader::validate`] (as well as checking that the table is at least
+    /// [`AcpiTable::MIN_LENGTH`] bytes long), but `header_mapping` does not have to map the entire table when
+    /// calling. This is useful to avoid completely mapping a table that will be immediately
     /// unmapped if it does not have a particular signature or has an invalid header.
     pub(crate) fn validate_lazy<H: AcpiHandler, T: AcpiTable>(
         header_mapping: PhysicalMapping<H, Self>,
         handler: H,
+        policy: ValidationPolicy,
     ) -> AcpiResult<PhysicalMapping<H, T>> {
-        header_mapping.validate_header_fields(T::SIGNATURE)?;
+        if header_mapping.signature != T::SIGNATURE || str::from_utf8(&header_mapping.signature.0).is_err() {
+            return Err(AcpiError::SdtInvalidSignature(T::SIGNATURE));
+        }
 
-        // Reuse `header_mapping` to access the rest of the table if the latter is already mapped entirely
+        // A table that is shorter than `T` can't be read safely, so this is checked whatever the policy is.
         let table_length = header_mapping.length as usize;
+        if table_length < T::MIN_LENGTH {
+            return Err(AcpiError::SdtInvalidLength(T::SIGNATURE));
+        }
+
+        // Reuse `header_mapping` to access the rest of the table if the latter is already mapped entirely.
         let table_mapping = if header_mapping.mapped_length() >= table_length {
             // Avoid requesting table unmap twice (from both `header_mapping` and `table_mapping`)
             let header_mapping = core::mem::ManuallyDrop::new(header_mapping);
@@ -195,21 +244,100 @@ impl SdtHeader {
             unsafe { handler.map_physical_region(table_phys_start, table_length) }
         };
 
-        // This is usually redundant compared to simply calling `validate_checksum` but respects custom
-        // `AcpiTable::validate` implementations.
-        table_mapping.validate()?;
+        // Checks the header and checksum according to the policy, followed by any checks specific to the table
+        table_mapping.header().validate_with_policy(T::SIGNATURE, policy)?;
+        table_mapping.validate_contents()?;
+        policy.check(T::SIGNATURE, |report| table_mapping.find_content_problems(report))?;
 
         Ok(table_mapping)
     }
 
+    /// The OEM ID. If the ID isn't valid UTF-8 (which is only accepted by a relaxed [`ValidationPolicy`]), this
+    /// is the part of it before the first invalid byte.
     pub fn oem_id(&self) -> &str {
-        // Safe to unwrap because checked in `validate`
-        str::from_utf8(&self.oem_id).unwrap()
+        valid_prefix(&self.oem_id)
     }
 
+    /// The OEM table ID. If the ID isn't valid UTF-8 (which is only accepted by a relaxed [`ValidationPolicy`]),
+    /// this is the part of it before the first invalid byte.
     pub fn oem_table_id(&self) -> &str {
-        // Safe to unwrap because checked in `validate`
-        str::from_utf8(&self.oem_table_id).unwrap()
+        valid_prefix(&self.oem_table_id)
+    }
+}
+
+/// The longest prefix of `bytes` that is valid UTF-8.
+fn valid_prefix(bytes: &[u8]) -> &str {
+    str::from_utf8(bytes).unwrap_or_else(|err| str::from_utf8(&bytes[..err.valid_up_to()]).unwrap())
+}
+
+/// How strictly tables are validated when they're read. By default, tables with any problem with their header,
+/// checksum, length or reserved fields are rejected, but plenty of real firmware ships tables with (for example)
+/// bad checksums, so some of these problems can be ignored instead. Ignored problems are logged, and can be listed
+/// with [`AcpiTables::validation_report`](crate::AcpiTables::validation_report).
+///
+/// Checks that make sure a table can be read safely (such as that the table is at least [`AcpiTable::MIN_LENGTH`]
+/// bytes long, or that the SLIT's distance matrix fits inside it) are always enforced. The checks of lengths that
+/// are longer than a table needs, and of reserved fields, are made by the tables that implement
+/// [`AcpiTable::find_content_problems`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ValidationPolicy {
+    /// Accept tables, and the RSDP, with invalid checksums.
+    pub allow_invalid_checksums: bool,
+    /// Accept tables, and the RSDP, with OEM IDs or OEM table IDs that aren't valid UTF-8.
+    pub allow_invalid_header_fields: bool,
+    /// Accept tables whose length disagrees with their contents, as long as they're long enough to be read (e.g.
+    /// an XSDT whose length isn't a whole number of entries, or a SLIT that is longer than its distance matrix).
+    pub allow_length_mismatches: bool,
+    /// Accept tables with reserved fields that aren't zero.
+    pub allow_reserved_fields: bool,
+}
+
+impl ValidationPolicy {
+    pub const STRICT: ValidationPolicy = ValidationPolicy {
+        allow_invalid_checksums: false,
+        allow_invalid_header_fields: false,
+        allow_length_mismatches: false,
+        allow_reserved_fields: false,
+    };
+    pub const RELAXED: ValidationPolicy = ValidationPolicy {
+        allow_invalid_checksums: true,
+        allow_invalid_header_fields: true,
+        allow_length_mismatches: true,
+        allow_reserved_fields: true,
+    };
+
+    /// Whether a table (or the RSDP) with `problem` should be accepted.
+    pub(crate) fn allows(&self, problem: &AcpiError) -> bool {
+        match problem {
+            AcpiError::SdtInvalidChecksum(_) | AcpiError::Rsdp(RsdpError::InvalidChecksum) => {
+                self.allow_invalid_checksums
+            }
+            AcpiError::SdtInvalidOemId(_)
+            | AcpiError::SdtInvalidTableId(_)
+            | AcpiError::Rsdp(RsdpError::InvalidOemId) => self.allow_invalid_header_fields,
+            AcpiError::SdtLengthMismatch(_) => self.allow_length_mismatches,
+            AcpiError::SdtReservedFieldSet(_) => self.allow_reserved_fields,
+            _ => false,
+        }
+    }
+
+    /// Call `find_problems` to report the problems with the table with `signature`, logging those that are
+    /// allowed. Returns the first problem that isn't allowed.
+    pub(crate) fn check(
+        &self,
+        signature: Signature,
+        find_problems: impl FnOnce(&mut dyn FnMut(AcpiError)),
+    ) -> AcpiResult<()> {
+        let mut result = Ok(());
+        find_problems(&mut |problem| {
+            if self.allows(&problem) {
+                log::warn!("Ignoring problem with {} table: {:?}", signature, problem);
+            } else if result.is_ok() {
+                result = Err(problem);
+            }
+        });
+
+        result
     }
 }
 
@@ -236,20 +364,25 @@ impl Signature {
     pub const FPDT: Signature = Signature(*b"FPDT");
     pub const GTDT: Signature = Signature(*b"GTDT");
     pub const HEST: Signature = Signature(*b"HEST");
+    pub const HMAT: Signature = Signature(*b"HMAT");
     pub const MSCT: Signature = Signature(*b"MSCT");
     pub const MPST: Signature = Signature(*b"MPST");
     pub const NFIT: Signature = Signature(*b"NFIT");
     pub const PCCT: Signature = Signature(*b"PCCT");
     pub const PHAT: Signature = Signature(*b"PHAT");
     pub const PMTT: Signature = Signature(*b"PMTT");
+    pub const PPTT: Signature = Signature(*b"PPTT");
     pub const PSDT: Signature = Signature(*b"PSDT");
     pub const RASF: Signature = Signature(*b"RASF");
+    pub const RAS2: Signature = Signature(*b"RAS2");
     pub const SBST: Signature = Signature(*b"SBST");
     pub const SDEV: Signature = Signature(*b"SDEV");
     pub const SLIT: Signature = Signature(*b"SLIT");
     pub const SRAT: Signature = Signature(*b"SRAT");
     pub const AEST: Signature = Signature(*b"AEST");
+    pub const APMT: Signature = Signature(*b"APMT");
     pub const BDAT: Signature = Signature(*b"BDAT");
+    pub const BOOT: Signature = Signature(*b"BOOT");
     pub const CDIT: Signature = Signature(*b"CDIT");
     pub const CEDT: Signature = Signature(*b"CEDT");
     pub const CRAT: Signature = Signature(*b"CRAT");
@@ -277,6 +410,7 @@ impl Signature {
     pub const TCPA: Signature = Signature(*b"TCPA");
     pub const TPM2: Signature = Signature(*b"TPM2");
     pub const UEFI: Signature = Signature(*b"UEFI");
+    pub const VIOT: Signature = Signature(*b"VIOT");
     pub const WAET: Signature = Signature(*b"WAET");
     pub const WDAT: Signature = Signature(*b"WDAT");
     pub const WDRT: Signature = Signature(*b"WDRT");
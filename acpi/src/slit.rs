@@ -0,0 +1,136 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiError,
+    AcpiResult,
+    AcpiTable,
+};
+use core::{convert::TryFrom, mem, slice};
+
+/// Represents the System Locality Distance Information Table (SLIT). This contains a matrix of the relative
+/// distances between each pair of localities (proximity domains, as described by the SRAT) in the system.
+///
+/// Distances are relative to the distance from a locality to itself, which is always `10`. A distance of `0xff`
+/// means that the second locality is unreachable from the first.
+#[repr(C, packed)]
+pub struct Slit {
+    header: SdtHeader,
+    number_of_localities: u64,
+    // Followed by a `number_of_localities * number_of_localities` matrix of `u8` distances
+}
+
+/// ### Safety: Implementation properly represents a valid SLIT.
+unsafe impl AcpiTable for Slit {
+    const SIGNATURE: Signature = Signature::SLIT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+
+    fn validate_contents(&self) -> AcpiResult<()> {
+        // Make sure the distance matrix fits inside the table, so we never read past the end of it
+        let matrix_size = usize::try_from(self.number_of_localities)
+            .ok()
+            .and_then(|localities| localities.checked_mul(localities))
+            .ok_or(AcpiError::SdtInvalidLength(Self::SIGNATURE))?;
+        if mem::size_of::<Slit>() + matrix_size > self.header.length as usize {
+            return Err(AcpiError::SdtInvalidLength(Self::SIGNATURE));
+        }
+
+        Ok(())
+    }
+
+    fn find_content_problems(&self, report: &mut dyn FnMut(AcpiError)) {
+        // `validate_contents` has checked that the size of the matrix doesn't overflow, and fits inside the table
+        let localities = self.number_of_localities as usize;
+        if mem::size_of::<Slit>() + localities * localities != self.header.length as usize {
+            report(AcpiError::SdtLengthMismatch(Self::SIGNATURE));
+        }
+    }
+}
+
+impl Slit {
+    pub fn number_of_localities(&self) -> u64 {
+        self.number_of_localities
+    }
+
+    /// Get the relative distance from locality `from` to locality `to`. Returns `None` if either locality is not
+    /// described by the table.
+    pub fn distance(&self, from: u32, to: u32) -> Option<u8> {
+        let (from, to) = (from as u64, to as u64);
+        if from >= self.number_of_localities || to >= self.number_of_localities {
+            return None;
+        }
+
+        self.matrix().get((from * self.number_of_localities + to) as usize).copied()
+    }
+
+    /// Returns an iterator over each row of the distance matrix. The `n`th item is a slice of the distances from
+    /// locality `n` to every locality in the system.
+    pub fn localities(&self) -> slice::ChunksExact<'_, u8> {
+        // `chunks_exact` panics on a zero chunk size, but the matrix is empty in that case anyway
+        self.matrix().chunks_exact(usize::max(self.number_of_localities as usize, 1))
+    }
+
+    fn matrix(&self) -> &[u8] {
+        let localities = self.number_of_localities as usize;
+
+        unsafe {
+            let pointer = (self as *const Slit as *const u8).add(mem::size_of::<Slit>());
+            slice::from_raw_parts(pointer, localities * localities)
+        }
+    }
+}
+
+impl core::fmt::Debug for Slit {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("Slit")
+            .field("header", &self.header)
+            .field("number_of_localities", &{ self.number_of_localities })
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{finish_table, table, TableBuffer};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_distances() {
+        let mut body = 3u64.to_le_bytes().to_vec();
+        body.extend([10, 20, 30, 20, 10, 0xff, 30, 0xff, 10]);
+        let buffer = TableBuffer::<Slit>::new(&table(b"SLIT", 1, &body));
+        let slit = buffer.get();
+        assert!(slit.validate().is_ok());
+
+        assert_eq!(slit.number_of_localities(), 3);
+        assert_eq!(slit.distance(0, 2), Some(30));
+        assert_eq!(slit.distance(1, 2), Some(0xff));
+        assert_eq!(slit.distance(2, 2), Some(10));
+        assert_eq!(slit.distance(3, 0), None);
+        let rows: Vec<&[u8]> = slit.localities().collect();
+        assert_eq!(rows, [&[10, 20, 30][..], &[20, 10, 0xff], &[30, 0xff, 10]]);
+    }
+
+    #[test]
+    fn test_truncated_matrix() {
+        let mut bytes = table(b"SLIT", 1, &[3, 0, 0, 0, 0, 0, 0, 0, 10, 20, 30, 20, 10, 0xff, 30, 0xff, 10]);
+        bytes.pop();
+        finish_table(&mut bytes);
+        let buffer = TableBuffer::<Slit>::new(&bytes);
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::SLIT))));
+
+        let buffer = TableBuffer::<Slit>::new(&table(b"SLIT", 1, &u64::MAX.to_le_bytes()));
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::SLIT))));
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let buffer = TableBuffer::<Slit>::new(&table(b"SLIT", 1, &[1, 0, 0, 0, 0, 0, 0, 0, 10, 0]));
+        let slit = buffer.get();
+        assert!(matches!(slit.validate(), Err(AcpiError::SdtLengthMismatch(Signature::SLIT))));
+        assert_eq!(slit.distance(0, 0), Some(10));
+    }
+}
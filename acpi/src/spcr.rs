@@ -0,0 +1,365 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress},
+    sdt::{ExtendedField, SdtHeader, Signature},
+    AcpiResult,
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, ptr, slice, str};
+
+/// Represents the Serial Port Console Redirection Table (SPCR). This describes the serial port that the firmware
+/// used to redirect its console, so the OS can continue to use it for its own console.
+///
+/// The layout of the table is the same in all revisions, but later revisions add some fields onto the end, and
+/// the meaning of `interface_type` changed in revision 2. The accessors on this type take care of both.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Spcr {
+    pub header: SdtHeader,
+    interface_type: u8,
+    _reserved: [u8; 3],
+    base_address: RawGenericAddress,
+    pub interrupt_type: SpcrInterruptType,
+    irq: u8,
+    global_system_interrupt: u32,
+    configured_baud_rate: u8,
+    /// The parity of the port. `0` means no parity; all other values are reserved.
+    pub parity: u8,
+    /// The number of stop bits of the port. `1` means one stop bit; all other values are reserved.
+    pub stop_bits: u8,
+    pub flow_control: SpcrFlowControl,
+    terminal_type: u8,
+    /// The language used by the firmware on the console. `0` is US Western English.
+    pub language: u8,
+    /// The PCI device ID of the port, or `0xffff` if it isn't a PCI device.
+    pub pci_device_id: u16,
+    /// The PCI vendor ID of the port, or `0xffff` if it isn't a PCI device.
+    pub pci_vendor_id: u16,
+    pub pci_bus: u8,
+    pub pci_device: u8,
+    pub pci_function: u8,
+    pub pci_flags: u32,
+    pub pci_segment: u8,
+    uart_clock_frequency: ExtendedField<u32, 3>,
+    precise_baud_rate: ExtendedField<u32, 4>,
+    namespace_string_length: ExtendedField<u16, 4>,
+    namespace_string_offset: ExtendedField<u16, 4>,
+}
+
+/// ### Safety: Implementation properly represents a valid SPCR.
+unsafe impl AcpiTable for Spcr {
+    const SIGNATURE: Signature = Signature::SPCR;
+    const MIN_LENGTH: usize = mem::offset_of!(Spcr, uart_clock_frequency);
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Spcr {
+    pub fn interface_type(&self) -> SerialInterfaceType {
+        /*
+         * Revision 1 of the SPCR only defined two interface types, the second of which conflicts with the
+         * numbering used from revision 2 onwards (which shares the serial port subtypes of the DBG2).
+         */
+        if self.header.revision < 2 && self.interface_type == 1 {
+            SerialInterfaceType::Full16450
+        } else {
+            SerialInterfaceType::from(self.interface_type as u16)
+        }
+    }
+
+    /// The location of the port's registers.
+    pub fn base_address(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.base_address)
+    }
+
+    /// The PC-AT-compatible IRQ used by the port. Returns `None` if the port isn't connected to a dual-8259 PIC.
+    pub fn irq(&self) -> Option<u8> {
+        if self.interrupt_type.pc_at_compatible() {
+            Some(self.irq)
+        } else {
+            None
+        }
+    }
+
+    /// The Global System Interrupt used by the port. Returns `None` if the port isn't connected to an I/O APIC,
+    /// I/O SAPIC, GIC, or PLIC/APLIC.
+    pub fn global_system_interrupt(&self) -> Option<u32> {
+        let interrupt_type = self.interrupt_type;
+        if interrupt_type.io_apic()
+            || interrupt_type.io_sapic()
+            || interrupt_type.arm_gic()
+            || interrupt_type.risc_v_plic()
+        {
+            Some(self.global_system_interrupt)
+        } else {
+            None
+        }
+    }
+
+    /// The baud rate that the firmware configured the port with. Returns `None` if the OS should keep whatever
+    /// configuration the port already has.
+    pub fn baud_rate(&self) -> Option<u32> {
+        if let Some(precise_baud_rate) = self.header.extended_field(ptr::addr_of!(self.precise_baud_rate)) {
+            if precise_baud_rate != 0 {
+                return Some(precise_baud_rate);
+            }
+        }
+
+        match self.configured_baud_rate {
+            3 => Some(9600),
+            4 => Some(19200),
+            6 => Some(57600),
+            7 => Some(115200),
+            _ => None,
+        }
+    }
+
+    pub fn terminal_type(&self) -> SpcrTerminalType {
+        match self.terminal_type {
+            0 => SpcrTerminalType::Vt100,
+            1 => SpcrTerminalType::ExtendedVt100,
+            2 => SpcrTerminalType::VtUtf8,
+            3 => SpcrTerminalType::Ansi,
+            other => SpcrTerminalType::Reserved(other),
+        }
+    }
+
+    /// Whether the port is a PCI device. If it is, the `pci_*` fields describe where to find it.
+    pub fn is_pci_device(&self) -> bool {
+        self.pci_device_id != 0xffff || self.pci_vendor_id != 0xffff
+    }
+
+    /// The frequency of the UART's input clock, in Hz. Returns `None` if the table is older than revision 3, or if
+    /// the frequency isn't known.
+    pub fn uart_clock_frequency(&self) -> Option<u32> {
+        self.header.extended_field(ptr::addr_of!(self.uart_clock_frequency)).filter(|&frequency| frequency != 0)
+    }
+
+    /// The fully-qualified path of the port's device in the ACPI namespace. Returns `None` if the table is older
+    /// than revision 4, or if the name isn't valid ASCII.
+    pub fn namespace_string(&self) -> Option<&str> {
+        let length = self.header.extended_field(ptr::addr_of!(self.namespace_string_length))? as usize;
+        let offset = self.header.extended_field(ptr::addr_of!(self.namespace_string_offset))? as usize;
+        if length == 0 || offset + length > self.header.length as usize {
+            return None;
+        }
+
+        let bytes = unsafe { slice::from_raw_parts((self as *const Spcr as *const u8).add(offset), length) };
+        let bytes = match bytes.iter().position(|&byte| byte == 0) {
+            Some(nul) => &bytes[..nul],
+            None => bytes,
+        };
+
+        str::from_utf8(bytes).ok()
+    }
+}
+
+/// The type of a serial port, as described by the SPCR and DBG2 tables.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SerialInterfaceType {
+    Full16550,
+    /// Only used by revision 1 of the SPCR.
+    Full16450,
+    /// A 16550-compatible port that only supports the subset of the interface described by revision 1 of the DBGP.
+    Dbgp16550Subset,
+    Max311xESpiUart,
+    ArmPl011,
+    Msm8x60,
+    Nvidia16550,
+    TiOmap,
+    Apm88xxxx,
+    Msm8974,
+    Sam5250,
+    IntelUsif,
+    Imx6,
+    /// An ARM SBSA (version 2.x only) generic UART, which only supports 32-bit accesses. This type is deprecated.
+    ArmSbsa32BitUart,
+    ArmSbsaGenericUart,
+    ArmDcc,
+    Bcm2835,
+    Sdm845At1_8432MHz,
+    /// A 16550-compatible port, whose register width and access size are described by its Generic Address
+    /// Structure.
+    Generic16550,
+    Sdm845At7_372MHz,
+    IntelLpss,
+    RiscVSbiConsole,
+    Reserved(u16),
+}
+
+impl From<u16> for SerialInterfaceType {
+    /// Decode a serial port subtype, as numbered by the DBG2 (and revision 2+ of the SPCR).
+    fn from(subtype: u16) -> Self {
+        match subtype {
+            0x00 => SerialInterfaceType::Full16550,
+            0x01 => SerialInterfaceType::Dbgp16550Subset,
+            0x02 => SerialInterfaceType::Max311xESpiUart,
+            0x03 => SerialInterfaceType::ArmPl011,
+            0x04 => SerialInterfaceType::Msm8x60,
+            0x05 => SerialInterfaceType::Nvidia16550,
+            0x06 => SerialInterfaceType::TiOmap,
+            0x08 => SerialInterfaceType::Apm88xxxx,
+            0x09 => SerialInterfaceType::Msm8974,
+            0x0a => SerialInterfaceType::Sam5250,
+            0x0b => SerialInterfaceType::IntelUsif,
+            0x0c => SerialInterfaceType::Imx6,
+            0x0d => SerialInterfaceType::ArmSbsa32BitUart,
+            0x0e => SerialInterfaceType::ArmSbsaGenericUart,
+            0x0f => SerialInterfaceType::ArmDcc,
+            0x10 => SerialInterfaceType::Bcm2835,
+            0x11 => SerialInterfaceType::Sdm845At1_8432MHz,
+            0x12 => SerialInterfaceType::Generic16550,
+            0x13 => SerialInterfaceType::Sdm845At7_372MHz,
+            0x14 => SerialInterfaceType::IntelLpss,
+            0x15 => SerialInterfaceType::RiscVSbiConsole,
+            other => SerialInterfaceType::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpcrTerminalType {
+    Vt100,
+    ExtendedVt100,
+    VtUtf8,
+    Ansi,
+    Reserved(u8),
+}
+
+/// Describes which interrupt controllers the port's interrupt is connected to. More than one may be set.
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct SpcrInterruptType(u8);
+
+impl SpcrInterruptType {
+    /// The interrupt is connected to a dual-8259 PIC, and is described by the `irq` field.
+    pub fn pc_at_compatible(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    pub fn io_apic(&self) -> bool {
+        self.0.get_bit(1)
+    }
+
+    pub fn io_sapic(&self) -> bool {
+        self.0.get_bit(2)
+    }
+
+    pub fn arm_gic(&self) -> bool {
+        self.0.get_bit(3)
+    }
+
+    pub fn risc_v_plic(&self) -> bool {
+        self.0.get_bit(4)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct SpcrFlowControl(u8);
+
+impl SpcrFlowControl {
+    /// If true, DCD is required for transmitting.
+    pub fn dcd(&self) -> bool {
+        self.0.get_bit(0)
+    }
+
+    pub fn rts_cts(&self) -> bool {
+        self.0.get_bit(1)
+    }
+
+    pub fn xon_xoff(&self) -> bool {
+        self.0.get_bit(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        address::AddressSpace,
+        test_utils::{blank_header, body_of, table, TableBuffer},
+    };
+
+    fn spcr(interface_type: u8) -> Spcr {
+        Spcr {
+            header: blank_header(),
+            interface_type,
+            _reserved: [0; 3],
+            base_address: RawGenericAddress {
+                address_space: 0,
+                bit_width: 32,
+                bit_offset: 0,
+                access_size: 3,
+                address: 0x900_0000,
+            },
+            interrupt_type: SpcrInterruptType(0b1000),
+            irq: 0,
+            global_system_interrupt: 33,
+            configured_baud_rate: 7,
+            parity: 0,
+            stop_bits: 1,
+            flow_control: SpcrFlowControl(0b010),
+            terminal_type: 2,
+            language: 0,
+            pci_device_id: 0xffff,
+            pci_vendor_id: 0xffff,
+            pci_bus: 0,
+            pci_device: 0,
+            pci_function: 0,
+            pci_flags: 0,
+            pci_segment: 0,
+            uart_clock_frequency: ExtendedField::new(24_000_000),
+            precise_baud_rate: ExtendedField::new(1_500_000),
+            namespace_string_length: ExtendedField::new(10),
+            namespace_string_offset: ExtendedField::new(mem::size_of::<Spcr>() as u16),
+        }
+    }
+
+    #[test]
+    fn test_revision_4() {
+        let mut body = body_of(&spcr(0x03));
+        body.extend(b"\\_SB.COM0\0");
+        let buffer = TableBuffer::<Spcr>::new(&table(b"SPCR", 4, &body));
+        let spcr = buffer.get();
+        assert!(spcr.validate().is_ok());
+
+        assert_eq!(spcr.interface_type(), SerialInterfaceType::ArmPl011);
+        let base_address = spcr.base_address().unwrap();
+        assert_eq!(base_address.address_space, AddressSpace::SystemMemory);
+        assert_eq!(base_address.address, 0x900_0000);
+        assert_eq!(spcr.irq(), None);
+        assert_eq!(spcr.global_system_interrupt(), Some(33));
+        assert_eq!(spcr.baud_rate(), Some(1_500_000));
+        assert_eq!(spcr.uart_clock_frequency(), Some(24_000_000));
+        assert_eq!(spcr.terminal_type(), SpcrTerminalType::VtUtf8);
+        assert!({ spcr.flow_control }.rts_cts());
+        assert!(!spcr.is_pci_device());
+        assert_eq!(spcr.namespace_string(), Some("\\_SB.COM0"));
+    }
+
+    #[test]
+    fn test_older_revisions() {
+        // The extended fields aren't read from tables that are too old, or too short, to contain them
+        let body = body_of(&spcr(0x01));
+        let buffer = TableBuffer::<Spcr>::new(&table(b"SPCR", 1, &body[..Spcr::MIN_LENGTH - 36]));
+        let spcr = buffer.get();
+        assert!(spcr.validate().is_ok());
+        assert_eq!(spcr.interface_type(), SerialInterfaceType::Full16450);
+        assert_eq!(spcr.baud_rate(), Some(115200));
+        assert_eq!(spcr.uart_clock_frequency(), None);
+
+        let buffer = TableBuffer::<Spcr>::new(&table(b"SPCR", 4, &body[..Spcr::MIN_LENGTH - 36 + 4]));
+        let spcr = buffer.get();
+        assert_eq!(spcr.interface_type(), SerialInterfaceType::Dbgp16550Subset);
+        assert_eq!(spcr.uart_clock_frequency(), Some(24_000_000));
+        assert_eq!(spcr.baud_rate(), Some(115200));
+        assert_eq!(spcr.namespace_string(), None);
+
+        // The namespace string is past the end of the table
+        let buffer = TableBuffer::<Spcr>::new(&table(b"SPCR", 4, &body));
+        assert_eq!(buffer.get().namespace_string(), None);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{marker::PhantomData, mem};
+
+/// Represents the System Resource Affinity Table (SRAT). This table associates processors, memory ranges, and
+/// other initiators with proximity domains, which is the information needed to build a NUMA topology. You can
+/// iterate over the affinity structures in the table with [`Srat::entries`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Srat {
+    pub header: SdtHeader,
+    _reserved1: u32,
+    _reserved2: u64,
+}
+
+/// ### Safety: Implementation properly represents a valid SRAT.
+unsafe impl AcpiTable for Srat {
+    const SIGNATURE: Signature = Signature::SRAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Srat {
+    pub fn entries(&self) -> SratEntryIter<'_> {
+        SratEntryIter {
+            pointer: unsafe { (self as *const Srat as *const u8).add(mem::size_of::<Srat>()) },
+            remaining_length: self.header.length - mem::size_of::<Srat>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SratEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum SratEntry<'a> {
+    LocalApicAffinity(&'a LocalApicAffinityEntry),
+    MemoryAffinity(&'a MemoryAffinityEntry),
+    LocalX2ApicAffinity(&'a LocalX2ApicAffinityEntry),
+    GiccAffinity(&'a GiccAffinityEntry),
+    GicItsAffinity(&'a GicItsAffinityEntry),
+    GenericInitiatorAffinity(&'a GenericInitiatorAffinityEntry),
+    GenericPortAffinity(&'a GenericInitiatorAffinityEntry),
+}
+
+impl<'a> Iterator for SratEntryIter<'a> {
+    type Item = SratEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            /*
+             * A zero-length entry would cause us to loop forever, and an entry that runs off the end of the table
+             * can't be read safely. Either way, the table is malformed, so stop iterating.
+             */
+            if header.length == 0 || header.length as u32 > self.remaining_length {
+                self.remaining_length = 0;
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length as u32;
+
+            macro_rules! construct_entry {
+                ($entry_type:expr,
+                 $entry_pointer:expr,
+                 $(($value:expr => $variant:path as $type:ty)),*
+                ) => {
+                    match $entry_type {
+                        $(
+                            $value if header.length as usize >= mem::size_of::<$type>() => {
+                                return Some($variant(unsafe {
+                                    &*($entry_pointer as *const $type)
+                                }))
+                            }
+                         )*
+
+                        /*
+                         * Other entry types are reserved by the ACPI standard. We should skip them if they
+                         * appear in a real SRAT, along with entries that are too short to be read as their type.
+                         */
+                        _ => {}
+                    }
+                }
+            }
+
+            #[rustfmt::skip]
+            construct_entry!(
+                header.entry_type,
+                entry_pointer,
+                (0x0 => SratEntry::LocalApicAffinity as LocalApicAffinityEntry),
+                (0x1 => SratEntry::MemoryAffinity as MemoryAffinityEntry),
+                (0x2 => SratEntry::LocalX2ApicAffinity as LocalX2ApicAffinityEntry),
+                (0x3 => SratEntry::GiccAffinity as GiccAffinityEntry),
+                (0x4 => SratEntry::GicItsAffinity as GicItsAffinityEntry),
+                (0x5 => SratEntry::GenericInitiatorAffinity as GenericInitiatorAffinityEntry),
+                (0x6 => SratEntry::GenericPortAffinity as GenericInitiatorAffinityEntry)
+            );
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub entry_type: u8,
+    pub length: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct LocalApicAffinityEntry {
+    pub header: EntryHeader,
+    /// Bits `0..8` of the proximity domain. The remaining bits are in `proximity_domain_high`.
+    pub proximity_domain_low: u8,
+    pub apic_id: u8,
+    pub flags: u32,
+    pub local_sapic_eid: u8,
+    /// Bits `8..32` of the proximity domain.
+    pub proximity_domain_high: [u8; 3],
+    pub clock_domain: u32,
+}
+
+impl LocalApicAffinityEntry {
+    pub fn proximity_domain(&self) -> u32 {
+        let [high0, high1, high2] = self.proximity_domain_high;
+        u32::from_le_bytes([self.proximity_domain_low, high0, high1, high2])
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryAffinityEntry {
+    pub header: EntryHeader,
+    pub proximity_domain: u32,
+    _reserved1: u16,
+    pub base_address_low: u32,
+    pub base_address_high: u32,
+    pub length_low: u32,
+    pub length_high: u32,
+    _reserved2: u32,
+    pub flags: u32,
+    _reserved3: u64,
+}
+
+impl MemoryAffinityEntry {
+    pub fn base_address(&self) -> u64 {
+        ((self.base_address_high as u64) << 32) | self.base_address_low as u64
+    }
+
+    pub fn length(&self) -> u64 {
+        ((self.length_high as u64) << 32) | self.length_low as u64
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn is_hot_pluggable(&self) -> bool {
+        { self.flags }.get_bit(1)
+    }
+
+    pub fn is_non_volatile(&self) -> bool {
+        { self.flags }.get_bit(2)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct LocalX2ApicAffinityEntry {
+    pub header: EntryHeader,
+    _reserved1: u16,
+    pub proximity_domain: u32,
+    pub x2apic_id: u32,
+    pub flags: u32,
+    pub clock_domain: u32,
+    _reserved2: u32,
+}
+
+impl LocalX2ApicAffinityEntry {
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+}
+
+/// Associates a processor, identified by the `ProcessorUid` field of its GICC entry in the MADT, with a
+/// proximity domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GiccAffinityEntry {
+    pub header: EntryHeader,
+    pub proximity_domain: u32,
+    pub processor_uid: u32,
+    pub flags: u32,
+    pub clock_domain: u32,
+}
+
+impl GiccAffinityEntry {
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GicItsAffinityEntry {
+    pub header: EntryHeader,
+    pub proximity_domain: u32,
+    _reserved: u16,
+    pub its_id: u32,
+}
+
+/// Used for both Generic Initiator Affinity (type `0x5`) and Generic Port Affinity (type `0x6`) entries, which
+/// share the same layout.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericInitiatorAffinityEntry {
+    pub header: EntryHeader,
+    _reserved1: u8,
+    /// `0` if `device_handle` is an ACPI device handle (`_HID` + `_UID`), `1` if it is a PCI device handle
+    /// (segment + BDF).
+    pub device_handle_type: u8,
+    pub proximity_domain: u32,
+    pub device_handle: [u8; 16],
+    pub flags: u32,
+    _reserved2: u32,
+}
+
+impl GenericInitiatorAffinityEntry {
+    pub fn is_enabled(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn is_architectural_transaction(&self) -> bool {
+        { self.flags }.get_bit(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_entries() {
+        let mut body = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&LocalApicAffinityEntry {
+            header: EntryHeader { entry_type: 0x0, length: 16 },
+            proximity_domain_low: 0x01,
+            apic_id: 2,
+            flags: 1,
+            local_sapic_eid: 0,
+            proximity_domain_high: [0x02, 0, 0],
+            clock_domain: 0,
+        }));
+        // A GICC Affinity entry that is too short to be read, and a reserved entry, which are both skipped
+        body.extend([0x3, 6, 0, 0, 0, 0]);
+        body.extend([0x20, 4, 0, 0]);
+        body.extend(bytes_of(&MemoryAffinityEntry {
+            header: EntryHeader { entry_type: 0x1, length: 40 },
+            proximity_domain: 1,
+            _reserved1: 0,
+            base_address_low: 0x4000_0000,
+            base_address_high: 0x1,
+            length_low: 0x8000_0000,
+            length_high: 0,
+            _reserved2: 0,
+            flags: 0b011,
+            _reserved3: 0,
+        }));
+        // An entry that runs off the end of the table ends the iteration
+        body.extend([0x2, 24, 0, 0]);
+
+        let buffer = TableBuffer::<Srat>::new(&table(b"SRAT", 3, &body));
+        let srat = buffer.get();
+        assert!(srat.validate().is_ok());
+
+        let entries: Vec<_> = srat.entries().collect();
+        assert_eq!(entries.len(), 2);
+        match entries[0] {
+            SratEntry::LocalApicAffinity(entry) => {
+                assert_eq!(entry.proximity_domain(), 0x0201);
+                assert_eq!(entry.apic_id, 2);
+                assert!(entry.is_enabled());
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+        match entries[1] {
+            SratEntry::MemoryAffinity(entry) => {
+                assert_eq!(entry.base_address(), 0x1_4000_0000);
+                assert_eq!(entry.length(), 0x8000_0000);
+                assert!(entry.is_enabled() && entry.is_hot_pluggable() && !entry.is_non_volatile());
+            }
+            ref other => panic!("unexpected entry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_truncated_entry_header() {
+        // A single byte is left after the entry, which isn't enough for an entry header
+        let mut body = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0].to_vec();
+        body.extend(bytes_of(&GicItsAffinityEntry {
+            header: EntryHeader { entry_type: 0x4, length: 12 },
+            proximity_domain: 3,
+            _reserved: 0,
+            its_id: 7,
+        }));
+        body.push(0x0);
+
+        let buffer = TableBuffer::<Srat>::new(&table(b"SRAT", 3, &body));
+        let entries: Vec<_> = buffer.get().entries().collect();
+        assert!(matches!(entries[..], [SratEntry::GicItsAffinity(entry)] if entry.its_id == 7));
+    }
+}
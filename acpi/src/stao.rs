@@ -0,0 +1,74 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{mem, slice, str};
+
+/// Represents the `_STA` Override Table (STAO), which is provided by hypervisors (e.g. Xen) to tell the OS to
+/// ignore devices that are described in the namespace, but which the OS can't use because they are owned by the
+/// hypervisor. The OS should treat these devices as if their `_STA` objects returned `0`.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Stao {
+    pub header: SdtHeader,
+    ignore_uart: u8,
+    // Followed by a list of null-terminated namespace paths
+}
+
+/// ### Safety: Implementation properly represents a valid STAO.
+unsafe impl AcpiTable for Stao {
+    const SIGNATURE: Signature = Signature::STAO;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Stao {
+    /// If true, the OS should ignore the UART described by the SPCR, as it is in use by the hypervisor.
+    pub fn ignore_uart(&self) -> bool {
+        self.ignore_uart != 0
+    }
+
+    /// The paths of the devices the OS should ignore (e.g. `\_SB.DEV0`). Paths that aren't valid UTF-8 are
+    /// skipped.
+    pub fn ignored_paths(&self) -> impl Iterator<Item = &str> {
+        let length = (self.header.length as usize).saturating_sub(mem::size_of::<Stao>());
+        let bytes = unsafe {
+            slice::from_raw_parts((self as *const Stao as *const u8).add(mem::size_of::<Stao>()), length)
+        };
+
+        bytes.split(|&byte| byte == 0).filter(|path| !path.is_empty()).filter_map(|path| str::from_utf8(path).ok())
+    }
+
+    /// Check if the device at `path` should be ignored.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.ignored_paths().any(|ignored| ignored == path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{table, TableBuffer};
+    use std::{vec, vec::Vec};
+
+    #[test]
+    fn test_ignored_paths() {
+        let mut body = vec![1];
+        body.extend(b"\\_SB.UAR0\0\\_SB.\xff\0\0\\_SB.PCI0.GPU0");
+        let buffer = TableBuffer::<Stao>::new(&table(b"STAO", 1, &body));
+        let stao = buffer.get();
+        assert!(stao.validate().is_ok());
+        assert!(stao.ignore_uart());
+
+        // The path that isn't valid UTF-8 is skipped, and the last path doesn't need to be null-terminated
+        assert_eq!(stao.ignored_paths().collect::<Vec<_>>(), ["\\_SB.UAR0", "\\_SB.PCI0.GPU0"]);
+        assert!(stao.is_ignored("\\_SB.PCI0.GPU0"));
+        assert!(!stao.is_ignored("\\_SB.PCI0"));
+
+        let buffer = TableBuffer::<Stao>::new(&table(b"STAO", 1, &[0]));
+        assert!(!buffer.get().ignore_uart());
+        assert_eq!(buffer.get().ignored_paths().count(), 0);
+    }
+}
@@ -0,0 +1,177 @@
+//! Fixtures for the tests of the table parsers, and a handler whose I/O ports are simulated in memory.
+
+use crate::{address::RegisterHandler, sdt::SdtHeader, AcpiHandler, PhysicalMapping};
+use core::{marker::PhantomData, mem, ptr::NonNull};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    vec,
+    vec::Vec,
+};
+
+/// Build a table with the given signature and revision, whose contents after the header are `body`. The length and
+/// checksum are filled in.
+pub(crate) fn table(signature: &[u8; 4], revision: u8, body: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0; mem::size_of::<SdtHeader>()];
+    bytes[0..4].copy_from_slice(signature);
+    bytes[8] = revision;
+    bytes[10..16].copy_from_slice(b"RUSTOS");
+    bytes[16..24].copy_from_slice(b"TESTTBL ");
+    bytes.extend_from_slice(body);
+    finish_table(&mut bytes);
+    bytes
+}
+
+/// Set the length of the table in `bytes` to the length of `bytes`, and recalculate its checksum.
+pub(crate) fn finish_table(bytes: &mut [u8]) {
+    let length = bytes.len() as u32;
+    bytes[4..8].copy_from_slice(&length.to_le_bytes());
+    bytes[9] = 0;
+    bytes[9] = bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_sub(byte));
+}
+
+/// The bytes of a structure, such as a table entry, to be included in the body of a fixture.
+pub(crate) fn bytes_of<T>(value: &T) -> Vec<u8> {
+    // Safety: The structures of the tables are plain old data, which can be read as bytes.
+    unsafe { core::slice::from_raw_parts((value as *const T).cast::<u8>(), mem::size_of::<T>()).to_vec() }
+}
+
+/// A blank header, for building a table structure in a test. Only the rest of the structure is used, by passing
+/// it to [`body_of`].
+pub(crate) fn blank_header() -> SdtHeader {
+    // Safety: The header is plain old data, which is valid when zeroed.
+    unsafe { mem::zeroed() }
+}
+
+/// A zeroed structure, for fixtures that only need to set a few of its fields.
+///
+/// ### Safety: `T` must be valid when zeroed, which is true of the plain old data structures of the tables.
+pub(crate) unsafe fn zeroed<T>() -> T {
+    unsafe { mem::zeroed() }
+}
+
+/// The bytes of a table structure after its header, to be used as the start of the body of a fixture.
+pub(crate) fn body_of<T>(table: &T) -> Vec<u8> {
+    bytes_of(table)[mem::size_of::<SdtHeader>()..].to_vec()
+}
+
+/// A copy of a table that can be viewed as a `T`. It's padded with `0xff` to at least the size of `T`, so that
+/// references to it stay inside the allocation, but reads of fields past the end of the table see the padding.
+pub(crate) struct TableBuffer<T> {
+    storage: Vec<u64>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> TableBuffer<T> {
+    pub(crate) fn new(bytes: &[u8]) -> TableBuffer<T> {
+        let length = usize::max(bytes.len(), mem::size_of::<T>());
+        let mut storage = vec![u64::MAX; length.div_ceil(8)];
+        // Safety: `storage` is at least `bytes.len()` bytes long.
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), storage.as_mut_ptr().cast::<u8>(), bytes.len()) };
+        TableBuffer { storage, _phantom: PhantomData }
+    }
+
+    pub(crate) fn get(&self) -> &T {
+        // Safety: The storage is aligned to 8 bytes, and is at least as large as `T`.
+        unsafe { &*self.storage.as_ptr().cast::<T>() }
+    }
+}
+
+/// The simulated state of a [`TestHandler`].
+#[derive(Default)]
+pub(crate) struct HandlerState {
+    /// The values of the I/O ports. Ports that haven't been written read as `0`.
+    pub(crate) io: HashMap<u16, u32>,
+    /// Each write to an I/O port, in order.
+    pub(crate) io_writes: Vec<(u16, u32)>,
+    /// The port of a simulated PM timer, and the number of ticks it advances by each time it's read.
+    pub(crate) pm_timer: Option<(u16, u32)>,
+    /// Called after each write to an I/O port, to simulate the hardware or firmware responding to it.
+    pub(crate) on_io_write: Option<fn(&mut HandlerState, u16, u32)>,
+}
+
+/// Maps physical memory at the same virtual address, and simulates the I/O ports.
+#[derive(Clone, Default)]
+pub(crate) struct TestHandler(pub(crate) Arc<Mutex<HandlerState>>);
+
+impl TestHandler {
+    pub(crate) fn new(state: HandlerState) -> TestHandler {
+        TestHandler(Arc::new(Mutex::new(state)))
+    }
+
+    pub(crate) fn state(&self) -> std::sync::MutexGuard<'_, HandlerState> {
+        self.0.lock().unwrap()
+    }
+
+    fn read_io(&self, port: u16) -> u32 {
+        let mut state = self.state();
+        let value = state.io.get(&port).copied().unwrap_or(0);
+        if let Some((timer_port, ticks_per_read)) = state.pm_timer {
+            if timer_port == port {
+                state.io.insert(port, value.wrapping_add(ticks_per_read));
+            }
+        }
+        value
+    }
+
+    fn write_io(&self, port: u16, value: u32) {
+        let mut state = self.state();
+        state.io.insert(port, value);
+        state.io_writes.push((port, value));
+        if let Some(on_io_write) = state.on_io_write {
+            on_io_write(&mut state, port, value);
+        }
+    }
+}
+
+impl AcpiHandler for TestHandler {
+    unsafe fn map_physical_region<T>(&self, address: usize, size: usize) -> PhysicalMapping<Self, T> {
+        unsafe {
+            PhysicalMapping::new(address, NonNull::new(address as *mut T).unwrap(), size, size, self.clone())
+        }
+    }
+
+    fn unmap_physical_region<T>(_region: &PhysicalMapping<Self, T>) {}
+}
+
+impl RegisterHandler for TestHandler {
+    fn read_io_u8(&self, port: u16) -> u8 {
+        self.read_io(port) as u8
+    }
+    fn read_io_u16(&self, port: u16) -> u16 {
+        self.read_io(port) as u16
+    }
+    fn read_io_u32(&self, port: u16) -> u32 {
+        self.read_io(port)
+    }
+
+    fn write_io_u8(&self, port: u16, value: u8) {
+        self.write_io(port, value.into())
+    }
+    fn write_io_u16(&self, port: u16, value: u16) {
+        self.write_io(port, value.into())
+    }
+    fn write_io_u32(&self, port: u16, value: u32) {
+        self.write_io(port, value)
+    }
+
+    fn read_pci_u8(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u8 {
+        unimplemented!()
+    }
+    fn read_pci_u16(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u16 {
+        unimplemented!()
+    }
+    fn read_pci_u32(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16) -> u32 {
+        unimplemented!()
+    }
+
+    fn write_pci_u8(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u8) {
+        unimplemented!()
+    }
+    fn write_pci_u16(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u16) {
+        unimplemented!()
+    }
+    fn write_pci_u32(&self, _segment: u16, _bus: u8, _device: u8, _function: u8, _offset: u16, _value: u32) {
+        unimplemented!()
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{device, device_power::PowerResources, fan::Fan, AcpiError, AcpiResult};
+use alloc::{format, vec::Vec};
+use aml::{value::Args, AmlContext, AmlName, AmlValue, LevelType};
+
+/// The maximum number of active cooling levels (`_AC0` to `_AC9`) a thermal zone can have.
+pub const MAX_ACTIVE_COOLING_LEVELS: usize = 10;
+
+/// A temperature in tenths of a Kelvin, which is the unit used by the thermal zone objects.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct DeciKelvin(pub u32);
+
+impl DeciKelvin {
+    pub fn from_millicelsius(millicelsius: i64) -> DeciKelvin {
+        DeciKelvin(((millicelsius + 273_150) / 100).max(0) as u32)
+    }
+
+    pub fn from_celsius(celsius: i32) -> DeciKelvin {
+        DeciKelvin::from_millicelsius(celsius as i64 * 1000)
+    }
+
+    pub fn to_millicelsius(self) -> i64 {
+        self.0 as i64 * 100 - 273_150
+    }
+
+    /// The temperature in whole degrees Celsius, rounded towards zero.
+    pub fn to_celsius(self) -> i32 {
+        (self.to_millicelsius() / 1000) as i32
+    }
+}
+
+/// The temperatures at which the OS should take action to cool a thermal zone.
+#[derive(Clone, Debug, Default)]
+pub struct TripPoints {
+    /// From `_CRT`: the OS should shut the system down immediately.
+    pub critical: Option<DeciKelvin>,
+    /// From `_HOT`: the OS should put the system into `S4`, if it supports it.
+    pub hot: Option<DeciKelvin>,
+    /// From `_PSV`: the OS should start passive cooling (i.e. throttling the processors).
+    pub passive: Option<DeciKelvin>,
+    /// From `_ACx` and `_ALx`: the OS should turn on the active cooling devices of each level. Level `0` has the
+    /// highest temperature, and the most cooling.
+    pub active: Vec<ActiveTripPoint>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActiveTripPoint {
+    pub temperature: DeciKelvin,
+    /// The paths of the cooling devices (e.g. fans) to turn on at this level.
+    pub devices: Vec<AmlName>,
+}
+
+/// What changed in a thermal zone, in response to a `Notify` on it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThermalNotification {
+    /// `Notify(zone, 0x80)`: the temperature of the zone has changed, and has been re-read.
+    TemperatureChanged(DeciKelvin),
+    /// `Notify(zone, 0x81)`: the zone's trip points have changed, and have been re-read.
+    TripPointsChanged,
+    /// `Notify(zone, 0x82)`: the zone's cooling device lists have changed, and have been re-read.
+    DeviceListsChanged,
+    /// The notification value isn't one defined for thermal zones.
+    Other(u64),
+}
+
+/// A `ThermalZone` object in the namespace.
+#[derive(Clone, Debug)]
+pub struct ThermalZone {
+    path: AmlName,
+    temperature: Option<DeciKelvin>,
+    trip_points: TripPoints,
+}
+
+impl ThermalZone {
+    /// Find the thermal zones in the namespace, and read their trip points.
+    pub fn enumerate(context: &mut AmlContext) -> AcpiResult<Vec<ThermalZone>> {
+        let mut paths = Vec::new();
+        context
+            .namespace
+            .traverse(|path, level| match level.typ {
+                LevelType::ThermalZone => {
+                    paths.push(path.clone());
+                    Ok(false)
+                }
+                LevelType::Scope | LevelType::Device => Ok(true),
+                _ => Ok(false),
+            })
+            .map_err(AcpiError::Aml)?;
+
+        paths.into_iter().map(|path| ThermalZone::new(context, path)).collect()
+    }
+
+    /// Create a thermal zone for the `ThermalZone` object at `path`, and read its trip points.
+    pub fn new(context: &mut AmlContext, path: AmlName) -> AcpiResult<ThermalZone> {
+        let mut zone = ThermalZone { path, temperature: None, trip_points: TripPoints::default() };
+        zone.refresh_trip_points(context)?;
+        Ok(zone)
+    }
+
+    pub fn path(&self) -> &AmlName {
+        &self.path
+    }
+
+    pub fn trip_points(&self) -> &TripPoints {
+        &self.trip_points
+    }
+
+    /// The temperature of the zone when it was last read, if it has been read.
+    pub fn last_temperature(&self) -> Option<DeciKelvin> {
+        self.temperature
+    }
+
+    /// Read the current temperature of the zone, by evaluating `_TMP`.
+    pub fn temperature(&mut self, context: &mut AmlContext) -> AcpiResult<DeciKelvin> {
+        let temperature = device::evaluate(context, &self.path, "_TMP", Args::EMPTY)?;
+        let temperature = DeciKelvin(temperature.as_integer(context).map_err(AcpiError::Aml)? as u32);
+
+        self.temperature = Some(temperature);
+        Ok(temperature)
+    }
+
+    /// Re-read the zone's trip points, and cooling device lists.
+    pub fn refresh_trip_points(&mut self, context: &mut AmlContext) -> AcpiResult<()> {
+        let mut active = Vec::new();
+        for level in 0..MAX_ACTIVE_COOLING_LEVELS {
+            let temperature = match self.evaluate_temperature(context, &format!("_AC{}", level))? {
+                Some(temperature) => temperature,
+                None => break,
+            };
+            let devices = self.evaluate_device_list(context, &format!("_AL{}", level))?;
+            active.push(ActiveTripPoint { temperature, devices });
+        }
+
+        self.trip_points = TripPoints {
+            critical: self.evaluate_temperature(context, "_CRT")?,
+            hot: self.evaluate_temperature(context, "_HOT")?,
+            passive: self.evaluate_temperature(context, "_PSV")?,
+            active,
+        };
+        Ok(())
+    }
+
+    /// Handle a `Notify` on the thermal zone, by re-reading whatever the notification says has changed.
+    pub fn handle_notify(&mut self, context: &mut AmlContext, value: u64) -> AcpiResult<ThermalNotification> {
+        match value {
+            0x80 => Ok(ThermalNotification::TemperatureChanged(self.temperature(context)?)),
+            0x81 => {
+                self.refresh_trip_points(context)?;
+                Ok(ThermalNotification::TripPointsChanged)
+            }
+            0x82 => {
+                self.refresh_trip_points(context)?;
+                Ok(ThermalNotification::DeviceListsChanged)
+            }
+            other => Ok(ThermalNotification::Other(other)),
+        }
+    }
+
+    /// Turn the fans in the zone's active cooling lists on or off, for the zone being at `temperature`. A fan is
+    /// turned on if it is in the list of a level whose trip point has been reached, and off otherwise. If the fan
+    /// has a performance state for the highest level reached, it is also put into that state. Fans that aren't in
+    /// any of the zone's lists are left alone.
+    pub fn update_active_cooling(
+        &self,
+        context: &mut AmlContext,
+        resources: &mut PowerResources,
+        fans: &mut [Fan],
+        temperature: DeciKelvin,
+    ) -> AcpiResult<()> {
+        for fan in fans.iter_mut() {
+            let mut levels = self
+                .trip_points
+                .active
+                .iter()
+                .enumerate()
+                .filter(|(_, trip_point)| trip_point.devices.contains(fan.path()))
+                .peekable();
+            if levels.peek().is_none() {
+                continue;
+            }
+
+            // Level 0 has the highest trip point, so the first level reached is the highest
+            match levels.find(|(_, trip_point)| temperature >= trip_point.temperature) {
+                Some((level, _)) => {
+                    fan.set_on(context, resources, true)?;
+
+                    let state =
+                        fan.performance_states().iter().find(|state| state.trip_point == Some(level as u64));
+                    if let Some(control) = state.map(|state| state.control) {
+                        fan.set_level(context, control)?;
+                    }
+                }
+                None => fan.set_on(context, resources, false)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate an object of the zone that returns a temperature, if it exists.
+    fn evaluate_temperature(&self, context: &mut AmlContext, name: &str) -> AcpiResult<Option<DeciKelvin>> {
+        match self.evaluate(context, name)? {
+            Some(value) => Ok(Some(DeciKelvin(value.as_integer(context).map_err(AcpiError::Aml)? as u32))),
+            None => Ok(None),
+        }
+    }
+
+    /// Evaluate an object of the zone that returns a package of references to devices, if it exists.
+    fn evaluate_device_list(&self, context: &mut AmlContext, name: &str) -> AcpiResult<Vec<AmlName>> {
+        Ok(device::evaluate_references(context, &self.path, name)?.unwrap_or_default())
+    }
+
+    fn evaluate(&self, context: &mut AmlContext, name: &str) -> AcpiResult<Option<AmlValue>> {
+        device::evaluate_optional(context, &self.path, name, Args::EMPTY)
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, ptr, slice};
+
+/// Represents the TPM 2.0 table (TPM2), which describes how to communicate with a TPM 2.0 device: which
+/// interface (the "start method") it uses, where its control area is, and any parameters specific to the start
+/// method. The layout of this table is defined by the TCG ACPI Specification, rather than the ACPI specification
+/// itself.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Tpm2 {
+    pub header: SdtHeader,
+    platform_class: u16,
+    _reserved: u16,
+    address_of_control_area: u64,
+    start_method: u32,
+    // Followed by the start-method-specific parameters, and then (optionally) the log area fields.
+}
+
+/// ### Safety: Implementation properly represents a valid TPM2.
+unsafe impl AcpiTable for Tpm2 {
+    const SIGNATURE: Signature = Signature::TPM2;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+/*
+ * The log area fields take up the last 12 bytes of the table if they're present. The parameters are usually 12
+ * bytes long, so we can only be sure they're present if the table is long enough for both.
+ */
+const LOG_AREA_FIELDS_LENGTH: usize = mem::size_of::<u32>() + mem::size_of::<u64>();
+const MIN_LENGTH_WITH_LOG_AREA: usize = mem::size_of::<Tpm2>() + 12 + LOG_AREA_FIELDS_LENGTH;
+
+impl Tpm2 {
+    pub fn platform_class(&self) -> Tpm2PlatformClass {
+        match self.platform_class {
+            0 => Tpm2PlatformClass::Client,
+            1 => Tpm2PlatformClass::Server,
+            other => Tpm2PlatformClass::Reserved(other),
+        }
+    }
+
+    /// The physical address of the control area of the TPM. For CRB-based start methods, this is the address of
+    /// the CRB control area. Returns `None` if the address is not provided (which is usually the case for
+    /// TIS-based TPMs, which live at a fixed address).
+    pub fn control_area_address(&self) -> Option<u64> {
+        match self.address_of_control_area {
+            0 => None,
+            address => Some(address),
+        }
+    }
+
+    pub fn start_method(&self) -> StartMethod {
+        match self.start_method {
+            2 => StartMethod::Acpi,
+            6 => StartMethod::MemoryMappedIo,
+            7 => StartMethod::Crb,
+            8 => StartMethod::CrbWithAcpi,
+            11 => StartMethod::CrbWithArmSmc,
+            13 => StartMethod::CrbWithPluton,
+            15 => StartMethod::CrbWithArmFfa,
+            16 => StartMethod::CrbWithPcc,
+            other => StartMethod::Reserved(other),
+        }
+    }
+
+    /// The raw start-method-specific parameters. Use [`Tpm2::start_method_parameters`] to decode them.
+    pub fn raw_start_method_parameters(&self) -> &[u8] {
+        let start = mem::size_of::<Tpm2>();
+        let end = if self.has_log_area() {
+            self.header.length as usize - LOG_AREA_FIELDS_LENGTH
+        } else {
+            self.header.length as usize
+        };
+
+        unsafe { slice::from_raw_parts((self as *const Tpm2 as *const u8).add(start), end.saturating_sub(start)) }
+    }
+
+    /// Decode the start-method-specific parameters. Returns `None` if the start method doesn't have parameters we
+    /// know how to decode, or if the parameters are too short.
+    pub fn start_method_parameters(&self) -> Option<StartMethodParameters> {
+        let parameters = self.raw_start_method_parameters();
+
+        macro_rules! read_parameters {
+            ($type:ty) => {{
+                if parameters.len() < mem::size_of::<$type>() {
+                    return None;
+                }
+                unsafe { ptr::read_unaligned(parameters.as_ptr() as *const $type) }
+            }};
+        }
+
+        Some(match self.start_method() {
+            StartMethod::CrbWithArmSmc => StartMethodParameters::ArmSmc(read_parameters!(ArmSmcParameters)),
+            StartMethod::CrbWithPluton => StartMethodParameters::Pluton(read_parameters!(PlutonParameters)),
+            StartMethod::CrbWithArmFfa => StartMethodParameters::ArmFfa(read_parameters!(ArmFfaParameters)),
+            StartMethod::CrbWithPcc => StartMethodParameters::Pcc(read_parameters!(PccParameters)),
+            _ => return None,
+        })
+    }
+
+    /// The minimum length, in bytes, of the event log area. Returns `None` if the table doesn't describe the log.
+    pub fn log_area_minimum_length(&self) -> Option<u32> {
+        if !self.has_log_area() {
+            return None;
+        }
+
+        Some(unsafe { self.read_at::<u32>(self.header.length as usize - LOG_AREA_FIELDS_LENGTH) })
+    }
+
+    /// The physical address of the event log area. Returns `None` if the table doesn't describe the log.
+    pub fn log_area_start_address(&self) -> Option<u64> {
+        if !self.has_log_area() {
+            return None;
+        }
+
+        Some(unsafe { self.read_at::<u64>(self.header.length as usize - mem::size_of::<u64>()) })
+    }
+
+    fn has_log_area(&self) -> bool {
+        self.header.revision >= 4 && self.header.length as usize >= MIN_LENGTH_WITH_LOG_AREA
+    }
+
+    /// Read a `T` from `offset` bytes after the start of the table.
+    ///
+    /// ### Safety: `offset + size_of::<T>()` must be within the length of the table.
+    unsafe fn read_at<T: Copy>(&self, offset: usize) -> T {
+        unsafe { ptr::read_unaligned((self as *const Tpm2 as *const u8).add(offset) as *const T) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tpm2PlatformClass {
+    Client,
+    Server,
+    Reserved(u16),
+}
+
+/// The interface used to send commands to the TPM.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StartMethod {
+    /// Commands are started by evaluating the `_DSM` method of the TPM's device.
+    Acpi,
+    /// The TPM uses the memory-mapped FIFO (TIS) interface.
+    MemoryMappedIo,
+    /// The TPM uses the Command Response Buffer interface.
+    Crb,
+    /// The TPM uses the Command Response Buffer interface, but commands are started with the `_DSM` method.
+    CrbWithAcpi,
+    /// The TPM uses the Command Response Buffer interface, and commands are started by an ARM SMC call.
+    CrbWithArmSmc,
+    CrbWithPluton,
+    /// The TPM uses the Command Response Buffer interface, and commands are started by an ARM FF-A message to a
+    /// secure partition.
+    CrbWithArmFfa,
+    /// The TPM uses the Command Response Buffer interface, and commands are started by ringing the doorbell of a
+    /// Platform Communications Channel (see [`PccParameters`]).
+    CrbWithPcc,
+    /// A start method we don't recognise. Its parameters can still be read with
+    /// [`Tpm2::raw_start_method_parameters`].
+    Reserved(u32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum StartMethodParameters {
+    ArmSmc(ArmSmcParameters),
+    Pluton(PlutonParameters),
+    ArmFfa(ArmFfaParameters),
+    Pcc(PccParameters),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ArmSmcParameters {
+    /// The GSIV of the interrupt used to signal completion. Only valid if `interrupt_supported` is set.
+    pub interrupt: u32,
+    pub flags: u8,
+    pub operation_flags: u8,
+    _reserved: [u8; 2],
+    /// The SMC function ID used to start commands.
+    pub smc_function_id: u32,
+}
+
+impl ArmSmcParameters {
+    pub fn interrupt_supported(&self) -> bool {
+        self.flags.get_bit(0)
+    }
+
+    /// If true, the TPM can be moved into and out of its idle state with the SMC call.
+    pub fn supports_idle(&self) -> bool {
+        self.operation_flags.get_bit(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PlutonParameters {
+    /// The physical address of the start register.
+    pub start_address: u64,
+    /// The physical address of the reply register.
+    pub reply_address: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ArmFfaParameters {
+    pub flags: u8,
+    pub attributes: u8,
+    /// The ID of the secure partition that implements the TPM.
+    pub partition_id: u16,
+    _reserved: [u8; 8],
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PccParameters {
+    /// The ID of the PCC subspace (see [`Pcct`](crate::pcct::Pcct)) used to start commands.
+    pub client_channel_id: u8,
+    _reserved: [u8; 3],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    fn tpm2_body(start_method: u32, parameters: &[u8]) -> Vec<u8> {
+        let mut body = body_of(&Tpm2 {
+            header: blank_header(),
+            platform_class: 1,
+            _reserved: 0,
+            address_of_control_area: 0xfed4_0040,
+            start_method,
+        });
+        body.extend(parameters);
+        body
+    }
+
+    #[test]
+    fn test_start_method_parameters() {
+        let parameters = bytes_of(&ArmSmcParameters {
+            interrupt: 0,
+            flags: 0,
+            operation_flags: 0b1,
+            _reserved: [0; 2],
+            smc_function_id: 0xc400_0050,
+        });
+        let mut body = tpm2_body(11, &parameters);
+        body.extend(0x1_0000u32.to_le_bytes());
+        body.extend(0x8000_0000u64.to_le_bytes());
+
+        let buffer = TableBuffer::<Tpm2>::new(&table(b"TPM2", 4, &body));
+        let tpm2 = buffer.get();
+        assert!(tpm2.validate().is_ok());
+        assert_eq!(tpm2.platform_class(), Tpm2PlatformClass::Server);
+        assert_eq!(tpm2.control_area_address(), Some(0xfed4_0040));
+        assert_eq!(tpm2.start_method(), StartMethod::CrbWithArmSmc);
+        assert_eq!(tpm2.raw_start_method_parameters(), &parameters[..]);
+        match tpm2.start_method_parameters() {
+            Some(StartMethodParameters::ArmSmc(parameters)) => {
+                assert!(parameters.supports_idle());
+                assert!(!parameters.interrupt_supported());
+                assert_eq!({ parameters.smc_function_id }, 0xc400_0050);
+            }
+            ref other => panic!("unexpected parameters: {:?}", other),
+        }
+        assert_eq!(tpm2.log_area_minimum_length(), Some(0x1_0000));
+        assert_eq!(tpm2.log_area_start_address(), Some(0x8000_0000));
+
+        // The log area fields are only in revision 4 and later
+        let buffer = TableBuffer::<Tpm2>::new(&table(b"TPM2", 3, &body));
+        assert_eq!(buffer.get().raw_start_method_parameters().len(), body.len() - 16);
+        assert_eq!(buffer.get().log_area_start_address(), None);
+    }
+
+    #[test]
+    fn test_truncated_parameters() {
+        // The FF-A parameters are 12 bytes long, so a table with only 4 bytes of parameters can't be decoded
+        let buffer = TableBuffer::<Tpm2>::new(&table(b"TPM2", 4, &tpm2_body(15, &[0; 4])));
+        let tpm2 = buffer.get();
+        assert_eq!(tpm2.start_method(), StartMethod::CrbWithArmFfa);
+        assert_eq!(tpm2.raw_start_method_parameters(), &[0; 4]);
+        assert!(tpm2.start_method_parameters().is_none());
+        assert_eq!(tpm2.log_area_minimum_length(), None);
+    }
+}
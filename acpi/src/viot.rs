@@ -0,0 +1,311 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use core::{marker::PhantomData, mem};
+
+/// Represents the Virtual I/O Translation Table (VIOT), which describes the topology of paravirtualized IOMMUs
+/// (such as virtio-iommu) in a virtual machine: which endpoints (PCI devices or MMIO devices) are translated by
+/// which IOMMU, and which endpoint IDs they use to identify themselves to it.
+///
+/// Endpoint nodes refer to the IOMMU that translates them by its offset in the table, which can be looked up with
+/// [`Viot::node_at_offset`], or resolved directly with [`Viot::iommu_of`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Viot {
+    pub header: SdtHeader,
+    pub node_count: u16,
+    /// The offset of the first node from the start of the table.
+    pub node_offset: u16,
+    _reserved: [u8; 8],
+}
+
+/// ### Safety: Implementation properly represents a valid VIOT.
+unsafe impl AcpiTable for Viot {
+    const SIGNATURE: Signature = Signature::VIOT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Viot {
+    pub fn nodes(&self) -> ViotNodeIter<'_> {
+        let offset = usize::max(self.node_offset as usize, mem::size_of::<Viot>());
+
+        ViotNodeIter {
+            pointer: unsafe { (self as *const Viot as *const u8).add(offset) },
+            remaining_length: (self.header.length as usize).saturating_sub(offset),
+            remaining_nodes: self.node_count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the node at `offset` bytes from the start of the table. Returns `None` if there isn't a valid node at
+    /// that offset.
+    pub fn node_at_offset(&self, offset: u16) -> Option<ViotNode<'_>> {
+        let offset = offset as usize;
+        if offset < mem::size_of::<Viot>() {
+            return None;
+        }
+
+        ViotNodeIter {
+            pointer: unsafe { (self as *const Viot as *const u8).add(offset) },
+            remaining_length: (self.header.length as usize).saturating_sub(offset),
+            remaining_nodes: 1,
+            _phantom: PhantomData,
+        }
+        .next()
+    }
+
+    /// Get the IOMMU node that translates an endpoint node (a [`ViotNode::PciRange`] or
+    /// [`ViotNode::MmioEndpoint`]). Returns `None` for other nodes, or if the endpoint refers to a node that is
+    /// not an IOMMU.
+    pub fn iommu_of(&self, endpoint: &ViotNode<'_>) -> Option<ViotNode<'_>> {
+        let output_node = match endpoint {
+            ViotNode::PciRange(range) => range.output_node,
+            ViotNode::MmioEndpoint(mmio) => mmio.output_node,
+            _ => return None,
+        };
+
+        match self.node_at_offset(output_node)? {
+            node @ (ViotNode::VirtioPciIommu(_) | ViotNode::VirtioMmioIommu(_)) => Some(node),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ViotNodeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    remaining_nodes: u16,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum ViotNode<'a> {
+    PciRange(&'a PciRangeNode),
+    MmioEndpoint(&'a MmioEndpointNode),
+    VirtioPciIommu(&'a VirtioPciIommuNode),
+    VirtioMmioIommu(&'a VirtioMmioIommuNode),
+    /// A node of a reserved type.
+    Reserved(&'a NodeHeader),
+}
+
+impl<'a> Iterator for ViotNodeIter<'a> {
+    type Item = ViotNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_nodes == 0 || self.remaining_length < mem::size_of::<NodeHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const NodeHeader) };
+        let length = header.length as usize;
+        if length < mem::size_of::<NodeHeader>() || length > self.remaining_length {
+            self.remaining_length = 0;
+            return None;
+        }
+
+        let node_pointer = self.pointer;
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+        self.remaining_nodes -= 1;
+
+        macro_rules! construct_node {
+            ($(($value:expr => $variant:path as $type:ty)),*) => {
+                match header.node_type {
+                    $(
+                        $value if length >= mem::size_of::<$type>() => {
+                            $variant(unsafe { &*(node_pointer as *const $type) })
+                        }
+                     )*
+                    _ => ViotNode::Reserved(header),
+                }
+            }
+        }
+
+        #[rustfmt::skip]
+        let node = construct_node!(
+            (1 => ViotNode::PciRange as PciRangeNode),
+            (2 => ViotNode::MmioEndpoint as MmioEndpointNode),
+            (3 => ViotNode::VirtioPciIommu as VirtioPciIommuNode),
+            (4 => ViotNode::VirtioMmioIommu as VirtioMmioIommuNode)
+        );
+
+        Some(node)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeHeader {
+    pub node_type: u8,
+    _reserved: u8,
+    pub length: u16,
+}
+
+/// Describes a range of PCI endpoints that are translated by the same IOMMU. The endpoint ID of a device in the
+/// range is `endpoint_start + (bdf - bdf_start)`, where `bdf` is the device's bus, device, and function number.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PciRangeNode {
+    pub header: NodeHeader,
+    pub endpoint_start: u32,
+    pub segment_start: u16,
+    pub segment_end: u16,
+    pub bdf_start: u16,
+    pub bdf_end: u16,
+    /// The offset of the IOMMU node that translates these endpoints from the start of the table.
+    pub output_node: u16,
+    _reserved: [u8; 6],
+}
+
+impl PciRangeNode {
+    /// Get the endpoint ID of a PCI device, if it is in this range. Each segment of the range is given a block
+    /// of `0x10000` endpoint IDs, so the ID is `endpoint_start + ((segment - segment_start) << 16) +
+    /// (bdf - bdf_start)`. Returns `None` if the ID doesn't fit in 32 bits.
+    pub fn endpoint_id(&self, segment: u16, bdf: u16) -> Option<u32> {
+        if !(self.segment_start..=self.segment_end).contains(&segment)
+            || !(self.bdf_start..=self.bdf_end).contains(&bdf)
+        {
+            return None;
+        }
+
+        // A `u16` shifted into the upper half of a `u32` can't overflow, but the sums can
+        let segment_offset = ((segment - self.segment_start) as u32) << 16;
+        self.endpoint_start.checked_add(segment_offset)?.checked_add((bdf - self.bdf_start) as u32)
+    }
+}
+
+/// Describes a single MMIO device that is translated by an IOMMU.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MmioEndpointNode {
+    pub header: NodeHeader,
+    pub endpoint_id: u32,
+    /// The base address of the device's MMIO region.
+    pub base_address: u64,
+    /// The offset of the IOMMU node that translates this endpoint from the start of the table.
+    pub output_node: u16,
+    _reserved: [u8; 6],
+}
+
+/// Describes a virtio-iommu that is implemented as a virtio-pci device.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct VirtioPciIommuNode {
+    pub header: NodeHeader,
+    pub segment: u16,
+    pub bdf: u16,
+    _reserved: [u8; 8],
+}
+
+/// Describes a virtio-iommu that is implemented as a virtio-mmio device.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct VirtioMmioIommuNode {
+    pub header: NodeHeader,
+    _reserved: u32,
+    pub base_address: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{blank_header, body_of, bytes_of, table, TableBuffer};
+    use std::vec::Vec;
+
+    const IOMMU_OFFSET: u16 = 48;
+
+    fn header<T>(node_type: u8) -> NodeHeader {
+        NodeHeader { node_type, _reserved: 0, length: mem::size_of::<T>() as u16 }
+    }
+
+    fn viot(node_count: u16, nodes: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = body_of(&Viot {
+            header: blank_header(),
+            node_count,
+            node_offset: mem::size_of::<Viot>() as u16,
+            _reserved: [0; 8],
+        });
+        for node in nodes {
+            body.extend(node);
+        }
+        table(b"VIOT", 0, &body)
+    }
+
+    #[test]
+    fn test_nodes() {
+        let iommu = bytes_of(&VirtioPciIommuNode {
+            header: header::<VirtioPciIommuNode>(3),
+            segment: 0,
+            bdf: 0x8,
+            _reserved: [0; 8],
+        });
+        let pci_range = bytes_of(&PciRangeNode {
+            header: header::<PciRangeNode>(1),
+            endpoint_start: 0x100,
+            segment_start: 0,
+            segment_end: 1,
+            bdf_start: 0x10,
+            bdf_end: 0xff,
+            output_node: IOMMU_OFFSET,
+            _reserved: [0; 6],
+        });
+        let mmio = bytes_of(&MmioEndpointNode {
+            header: header::<MmioEndpointNode>(2),
+            endpoint_id: 7,
+            base_address: 0x1000_0000,
+            // Refers to the PCI range, which isn't an IOMMU
+            output_node: IOMMU_OFFSET + 16,
+            _reserved: [0; 6],
+        });
+        // An MMIO IOMMU node too short to be read as one
+        let short = bytes_of(&NodeHeader { node_type: 4, _reserved: 0, length: 4 });
+
+        let buffer = TableBuffer::<Viot>::new(&viot(4, &[iommu, pci_range, mmio, short]));
+        let viot = buffer.get();
+        assert!(viot.validate().is_ok());
+
+        let nodes: Vec<_> = viot.nodes().collect();
+        assert_eq!(nodes.len(), 4);
+        assert!(matches!(nodes[3], ViotNode::Reserved(header) if header.node_type == 4));
+
+        match viot.iommu_of(&nodes[1]) {
+            Some(ViotNode::VirtioPciIommu(iommu)) => assert_eq!({ iommu.bdf }, 0x8),
+            ref other => panic!("unexpected node: {:?}", other),
+        }
+        assert!(viot.iommu_of(&nodes[2]).is_none());
+        assert!(viot.iommu_of(&nodes[0]).is_none());
+
+        match nodes[1] {
+            ViotNode::PciRange(range) => {
+                assert_eq!(range.endpoint_id(0, 0x18), Some(0x108));
+                assert_eq!(range.endpoint_id(1, 0x10), Some(0x1_0100));
+                assert_eq!(range.endpoint_id(0, 0x8), None);
+                assert_eq!(range.endpoint_id(2, 0x10), None);
+            }
+            ref other => panic!("unexpected node: {:?}", other),
+        }
+
+        assert!(viot.node_at_offset(0).is_none());
+        assert!(viot.node_at_offset(IOMMU_OFFSET + 1).is_none());
+    }
+
+    #[test]
+    fn test_truncated_node() {
+        let iommu = bytes_of(&VirtioMmioIommuNode {
+            header: header::<VirtioMmioIommuNode>(4),
+            _reserved: 0,
+            base_address: 0x1000_0000,
+        });
+        let buffer = TableBuffer::<Viot>::new(&viot(1, &[iommu[..iommu.len() - 1].to_vec()]));
+        assert_eq!(buffer.get().nodes().count(), 0);
+
+        // Only `node_count` nodes are read
+        let buffer = TableBuffer::<Viot>::new(&viot(1, &[iommu.clone(), iommu]));
+        assert_eq!(buffer.get().nodes().count(), 1);
+    }
+}
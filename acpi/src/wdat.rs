@@ -0,0 +1,420 @@
+use crate::{
+    address::{GenericAddress, RawGenericAddress, RegisterHandler},
+    sdt::{SdtHeader, Signature},
+    AcpiError,
+    AcpiResult,
+    AcpiTable,
+};
+use bit_field::BitField;
+use core::{mem, slice};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WdatError {
+    /// The WDAT doesn't have any instructions for the requested action.
+    ActionNotSupported(WatchdogAction),
+    /// The requested timeout can't be represented within the watchdog's minimum and maximum counts.
+    TimeoutOutOfRange,
+    /// One of the instruction entries has an instruction type we don't recognise.
+    InvalidInstruction(u8),
+}
+
+/// Represents the Watchdog Action Table (WDAT), which describes a hardware watchdog timer as a list of
+/// instructions that perform each watchdog action (e.g. setting the countdown period, or pinging the watchdog).
+/// The instructions can be executed with [`Wdat::execute`], or with the helpers built on top of it, such as
+/// [`Wdat::set_timeout`] and [`Wdat::ping`].
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Wdat {
+    pub header: SdtHeader,
+    /// The length of the watchdog header, which is the part of the table between the `SdtHeader` and the
+    /// instruction entries.
+    pub watchdog_header_length: u32,
+    /// The PCI segment of the watchdog device, or `0xff` if it isn't a PCI device.
+    pub pci_segment: u16,
+    pub pci_bus: u8,
+    pub pci_device: u8,
+    pub pci_function: u8,
+    _reserved0: [u8; 3],
+    /// The period of one count of the watchdog, in milliseconds.
+    pub timer_period: u32,
+    pub maximum_count: u32,
+    pub minimum_count: u32,
+    pub watchdog_flags: u8,
+    _reserved1: [u8; 3],
+    pub number_of_entries: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid WDAT.
+unsafe impl AcpiTable for Wdat {
+    const SIGNATURE: Signature = Signature::WDAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Wdat {
+    pub fn is_enabled(&self) -> bool {
+        self.watchdog_flags.get_bit(0)
+    }
+
+    /// If true, the watchdog is stopped when the system is in a sleep state.
+    pub fn stopped_in_sleep_state(&self) -> bool {
+        self.watchdog_flags.get_bit(7)
+    }
+
+    pub fn entries(&self) -> &[InstructionEntry] {
+        let available = (self.header.length as usize).saturating_sub(mem::size_of::<Wdat>())
+            / mem::size_of::<InstructionEntry>();
+        let count = usize::min(self.number_of_entries as usize, available);
+        if count == 0 {
+            return &[];
+        }
+
+        unsafe {
+            let pointer = (self as *const Wdat as *const u8).add(mem::size_of::<Wdat>());
+            slice::from_raw_parts(pointer as *const InstructionEntry, count)
+        }
+    }
+
+    pub fn supports_action(&self, action: WatchdogAction) -> bool {
+        self.entries().iter().any(|entry| entry.action() == action)
+    }
+
+    /// Execute all of the instructions for `action`, in the order they appear in the table. `parameter` is the
+    /// value written by `WriteCountdown` instructions (and is ignored by actions that don't take a parameter).
+    ///
+    /// Returns the result of the last read instruction: the value read for `ReadCountdown`, or `1` or `0` for
+    /// `ReadValue`, depending on whether the register matched the value in the entry. Returns `0` if the action
+    /// doesn't have any read instructions.
+    pub fn execute<H>(&self, handler: &H, action: WatchdogAction, parameter: u32) -> AcpiResult<u32>
+    where
+        H: RegisterHandler,
+    {
+        let mut found = false;
+        let mut result = 0;
+
+        for entry in self.entries().iter().filter(|entry| entry.action() == action) {
+            found = true;
+            if let Some(value) = entry.execute(handler, parameter)? {
+                result = value;
+            }
+        }
+
+        if found {
+            Ok(result)
+        } else {
+            Err(AcpiError::Watchdog(WdatError::ActionNotSupported(action)))
+        }
+    }
+
+    /// Ping the watchdog, resetting its countdown to the countdown period.
+    pub fn ping<H>(&self, handler: &H) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.execute(handler, WatchdogAction::Reset, 0).map(|_| ())
+    }
+
+    /// Set the countdown period of the watchdog to `timeout_ms` milliseconds, rounded down to a whole number of
+    /// counts.
+    pub fn set_timeout<H>(&self, handler: &H, timeout_ms: u32) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        let count = timeout_ms.checked_div(self.timer_period).unwrap_or(0);
+        if count < self.minimum_count || count > self.maximum_count {
+            return Err(AcpiError::Watchdog(WdatError::TimeoutOutOfRange));
+        }
+
+        self.execute(handler, WatchdogAction::SetCountdownPeriod, count).map(|_| ())
+    }
+
+    /// The countdown period of the watchdog, in milliseconds.
+    pub fn timeout<H>(&self, handler: &H) -> AcpiResult<u32>
+    where
+        H: RegisterHandler,
+    {
+        let count = self.execute(handler, WatchdogAction::QueryCountdownPeriod, 0)?;
+        Ok(count.saturating_mul(self.timer_period))
+    }
+
+    /// The time left before the watchdog expires, in milliseconds.
+    pub fn time_left<H>(&self, handler: &H) -> AcpiResult<u32>
+    where
+        H: RegisterHandler,
+    {
+        let count = self.execute(handler, WatchdogAction::QueryCurrentCountdownPeriod, 0)?;
+        Ok(count.saturating_mul(self.timer_period))
+    }
+
+    pub fn is_running<H>(&self, handler: &H) -> AcpiResult<bool>
+    where
+        H: RegisterHandler,
+    {
+        Ok(self.execute(handler, WatchdogAction::QueryRunningState, 0)? != 0)
+    }
+
+    pub fn start<H>(&self, handler: &H) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.execute(handler, WatchdogAction::SetRunningState, 0).map(|_| ())
+    }
+
+    pub fn stop<H>(&self, handler: &H) -> AcpiResult<()>
+    where
+        H: RegisterHandler,
+    {
+        self.execute(handler, WatchdogAction::SetStoppedState, 0).map(|_| ())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WatchdogAction {
+    /// Reset the countdown of the watchdog (also known as pinging, or kicking, the watchdog).
+    Reset,
+    QueryCurrentCountdownPeriod,
+    QueryCountdownPeriod,
+    SetCountdownPeriod,
+    QueryRunningState,
+    SetRunningState,
+    QueryStoppedState,
+    SetStoppedState,
+    /// Query whether the watchdog reboots the system when it expires.
+    QueryReboot,
+    /// Make the watchdog reboot the system when it expires.
+    SetReboot,
+    /// Query whether the watchdog shuts down the system when it expires.
+    QueryShutdown,
+    /// Make the watchdog shut down the system when it expires.
+    SetShutdown,
+    /// Query whether the last reboot was caused by the watchdog expiring.
+    QueryWatchdogStatus,
+    /// Clear the status that records that the watchdog expired.
+    SetWatchdogStatus,
+    Reserved(u8),
+}
+
+impl From<u8> for WatchdogAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x01 => WatchdogAction::Reset,
+            0x04 => WatchdogAction::QueryCurrentCountdownPeriod,
+            0x05 => WatchdogAction::QueryCountdownPeriod,
+            0x06 => WatchdogAction::SetCountdownPeriod,
+            0x08 => WatchdogAction::QueryRunningState,
+            0x09 => WatchdogAction::SetRunningState,
+            0x0a => WatchdogAction::QueryStoppedState,
+            0x0b => WatchdogAction::SetStoppedState,
+            0x0e => WatchdogAction::QueryReboot,
+            0x0f => WatchdogAction::SetReboot,
+            0x10 => WatchdogAction::QueryShutdown,
+            0x11 => WatchdogAction::SetShutdown,
+            0x20 => WatchdogAction::QueryWatchdogStatus,
+            0x21 => WatchdogAction::SetWatchdogStatus,
+            other => WatchdogAction::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    /// Read the register, and compare the masked value against `value`.
+    ReadValue,
+    /// Read the register, and return the masked value.
+    ReadCountdown,
+    /// Write `value` to the register.
+    WriteValue,
+    /// Write the action's parameter (e.g. the new countdown period) to the register.
+    WriteCountdown,
+}
+
+/// A single instruction of a watchdog action.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct InstructionEntry {
+    action: u8,
+    pub instruction_flags: u8,
+    _reserved: u16,
+    register_region: RawGenericAddress,
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl InstructionEntry {
+    pub fn action(&self) -> WatchdogAction {
+        WatchdogAction::from(self.action)
+    }
+
+    pub fn instruction(&self) -> AcpiResult<Instruction> {
+        match self.instruction_flags.get_bits(0..7) {
+            0 => Ok(Instruction::ReadValue),
+            1 => Ok(Instruction::ReadCountdown),
+            2 => Ok(Instruction::WriteValue),
+            3 => Ok(Instruction::WriteCountdown),
+            other => Err(AcpiError::Watchdog(WdatError::InvalidInstruction(other))),
+        }
+    }
+
+    /// If true, write instructions must preserve the bits of the register outside of `mask`.
+    pub fn preserve_register(&self) -> bool {
+        self.instruction_flags.get_bit(7)
+    }
+
+    pub fn register_region(&self) -> AcpiResult<GenericAddress> {
+        GenericAddress::from_raw(self.register_region)
+    }
+
+    /// Execute this instruction. Returns the result of read instructions, and `None` for write instructions.
+    pub fn execute<H>(&self, handler: &H, parameter: u32) -> AcpiResult<Option<u32>>
+    where
+        H: RegisterHandler,
+    {
+        let register = self.register_region()?;
+        let bit_offset = register.bit_offset as u32;
+        let mask = self.mask as u64;
+
+        match self.instruction()? {
+            Instruction::ReadValue => {
+                let value = (register.read_register(handler)? >> bit_offset) & mask;
+                Ok(Some((value == self.value as u64) as u32))
+            }
+            Instruction::ReadCountdown => {
+                let value = (register.read_register(handler)? >> bit_offset) & mask;
+                Ok(Some(value as u32))
+            }
+            Instruction::WriteValue | Instruction::WriteCountdown => {
+                let value = if self.instruction()? == Instruction::WriteValue { self.value } else { parameter };
+                let mut to_write = (value as u64 & mask) << bit_offset;
+                if self.preserve_register() {
+                    to_write |= register.read_register(handler)? & !(mask << bit_offset);
+                }
+
+                register.write_register(handler, to_write)?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        blank_header,
+        body_of,
+        bytes_of,
+        finish_table,
+        table,
+        HandlerState,
+        TableBuffer,
+        TestHandler,
+    };
+    use std::vec::Vec;
+
+    const CONTROL_PORT: u16 = 0x600;
+    const COUNT_PORT: u16 = 0x604;
+
+    fn entry(action: u8, instruction_flags: u8, port: u16, value: u32, mask: u32) -> InstructionEntry {
+        InstructionEntry {
+            action,
+            instruction_flags,
+            _reserved: 0,
+            register_region: RawGenericAddress {
+                address_space: 1,
+                bit_width: 32,
+                bit_offset: 0,
+                access_size: 3,
+                address: port as u64,
+            },
+            value,
+            mask,
+        }
+    }
+
+    fn wdat(entries: &[InstructionEntry], number_of_entries: u32) -> Vec<u8> {
+        let mut body = body_of(&Wdat {
+            header: blank_header(),
+            watchdog_header_length: 32,
+            pci_segment: 0xff,
+            pci_bus: 0xff,
+            pci_device: 0xff,
+            pci_function: 0xff,
+            _reserved0: [0; 3],
+            timer_period: 1000,
+            maximum_count: 600,
+            minimum_count: 2,
+            watchdog_flags: 0b1000_0001,
+            _reserved1: [0; 3],
+            number_of_entries,
+        });
+        for entry in entries {
+            body.extend(bytes_of(entry));
+        }
+        table(b"WDAT", 1, &body)
+    }
+
+    #[test]
+    fn test_actions() {
+        let entries = [
+            // Ping by setting bit 1 of the control register, preserving the rest of it
+            entry(0x01, 0x82, CONTROL_PORT, 1 << 1, 1 << 1),
+            entry(0x04, 0x01, COUNT_PORT, 0, 0xffff),
+            entry(0x06, 0x03, COUNT_PORT, 0, 0xffff),
+            entry(0x08, 0x00, CONTROL_PORT, 1, 1),
+            entry(0x09, 0x82, CONTROL_PORT, 1, 1),
+            // A reserved instruction type
+            entry(0x0b, 0x05, CONTROL_PORT, 0, 1),
+        ];
+        let buffer = TableBuffer::<Wdat>::new(&wdat(&entries, entries.len() as u32));
+        let wdat = buffer.get();
+        assert!(wdat.validate().is_ok());
+        assert!(wdat.is_enabled());
+        assert!(wdat.stopped_in_sleep_state());
+        assert_eq!(wdat.entries().len(), 6);
+        assert!(wdat.supports_action(WatchdogAction::Reset));
+        assert!(!wdat.supports_action(WatchdogAction::SetReboot));
+
+        let handler = TestHandler::default();
+        handler.state().io.insert(CONTROL_PORT, 0b100);
+
+        wdat.ping(&handler).unwrap();
+        assert_eq!(handler.state().io[&CONTROL_PORT], 0b110);
+        assert!(!wdat.is_running(&handler).unwrap());
+        wdat.start(&handler).unwrap();
+        assert!(wdat.is_running(&handler).unwrap());
+
+        wdat.set_timeout(&handler, 30_500).unwrap();
+        assert_eq!(handler.state().io[&COUNT_PORT], 30);
+        handler.state().io.insert(COUNT_PORT, 0x1_0000 | 12);
+        assert_eq!(wdat.time_left(&handler).unwrap(), 12_000);
+
+        assert!(matches!(
+            wdat.set_timeout(&handler, 1000),
+            Err(AcpiError::Watchdog(WdatError::TimeoutOutOfRange))
+        ));
+        assert!(matches!(
+            wdat.timeout(&handler),
+            Err(AcpiError::Watchdog(WdatError::ActionNotSupported(WatchdogAction::QueryCountdownPeriod)))
+        ));
+        assert!(matches!(wdat.stop(&handler), Err(AcpiError::Watchdog(WdatError::InvalidInstruction(5)))));
+    }
+
+    #[test]
+    fn test_truncated_entries() {
+        // Only the entries that fit in the table are read, regardless of the number of entries
+        let entries = [entry(0x01, 0x02, CONTROL_PORT, 1, 1), entry(0x01, 0x02, COUNT_PORT, 1, 1)];
+        let mut bytes = wdat(&entries, 3);
+        bytes.pop();
+        finish_table(&mut bytes);
+
+        let buffer = TableBuffer::<Wdat>::new(&bytes);
+        let wdat = buffer.get();
+        assert_eq!(wdat.entries().len(), 1);
+
+        let handler = TestHandler::new(HandlerState::default());
+        wdat.ping(&handler).unwrap();
+        assert_eq!(handler.state().io_writes, [(CONTROL_PORT, 1)]);
+    }
+}
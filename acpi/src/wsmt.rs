@@ -0,0 +1,65 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+
+/// Represents the Windows SMM Security Mitigations Table (WSMT), which reports the security mitigations the
+/// firmware's SMM code implements.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Wsmt {
+    pub header: SdtHeader,
+    pub protection_flags: u32,
+}
+
+/// ### Safety: Implementation properly represents a valid WSMT.
+unsafe impl AcpiTable for Wsmt {
+    const SIGNATURE: Signature = Signature::WSMT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Wsmt {
+    /// If true, the firmware's SMI handlers only use communication buffers in fixed, firmware-allocated memory
+    /// regions.
+    pub fn fixed_comm_buffers(&self) -> bool {
+        { self.protection_flags }.get_bit(0)
+    }
+
+    /// If true, the firmware's SMI handlers check that pointers in communication buffers point inside fixed
+    /// communication buffers. This is only valid if [`Wsmt::fixed_comm_buffers`] is also set.
+    pub fn comm_buffer_nested_pointer_protection(&self) -> bool {
+        { self.protection_flags }.get_bit(1)
+    }
+
+    /// If true, the firmware doesn't allow the OS to reconfigure critical system resources (e.g. IOMMU
+    /// configuration) from SMM.
+    pub fn system_resource_protection(&self) -> bool {
+        { self.protection_flags }.get_bit(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{table, TableBuffer},
+        AcpiError,
+    };
+
+    #[test]
+    fn test_protection_flags() {
+        let buffer = TableBuffer::<Wsmt>::new(&table(b"WSMT", 1, &0b101u32.to_le_bytes()));
+        let wsmt = buffer.get();
+        assert!(wsmt.validate().is_ok());
+        assert!(wsmt.fixed_comm_buffers());
+        assert!(!wsmt.comm_buffer_nested_pointer_protection());
+        assert!(wsmt.system_resource_protection());
+
+        let buffer = TableBuffer::<Wsmt>::new(&table(b"WSMT", 1, &[0b111, 0, 0]));
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::WSMT))));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    sdt::{SdtHeader, Signature},
+    AcpiTable,
+};
+use bit_field::BitField;
+
+/// Represents the Xen Environment Table (XENV), which is provided to guests running under Xen, and describes the
+/// grant table and the interrupt used to deliver event channel upcalls.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy)]
+pub struct Xenv {
+    pub header: SdtHeader,
+    /// The guest physical address of the grant table.
+    pub grant_table_start: u64,
+    /// The size, in bytes, of the grant table.
+    pub grant_table_size: u64,
+    /// The GSIV of the interrupt used to deliver event channel upcalls.
+    pub event_channel_interrupt: u32,
+    pub event_channel_flags: u8,
+}
+
+/// ### Safety: Implementation properly represents a valid XENV.
+unsafe impl AcpiTable for Xenv {
+    const SIGNATURE: Signature = Signature::XENV;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Xenv {
+    pub fn event_channel_interrupt_is_edge_triggered(&self) -> bool {
+        self.event_channel_flags.get_bit(0)
+    }
+
+    pub fn event_channel_interrupt_is_active_low(&self) -> bool {
+        self.event_channel_flags.get_bit(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{blank_header, body_of, table, TableBuffer},
+        AcpiError,
+    };
+
+    #[test]
+    fn test_xenv() {
+        let body = body_of(&Xenv {
+            header: blank_header(),
+            grant_table_start: 0x3800_0000,
+            grant_table_size: 0x100_0000,
+            event_channel_interrupt: 31,
+            event_channel_flags: 0b01,
+        });
+        let buffer = TableBuffer::<Xenv>::new(&table(b"XENV", 1, &body));
+        let xenv = buffer.get();
+        assert!(xenv.validate().is_ok());
+        assert_eq!({ xenv.grant_table_start }, 0x3800_0000);
+        assert!(xenv.event_channel_interrupt_is_edge_triggered());
+        assert!(!xenv.event_channel_interrupt_is_active_low());
+
+        // A table too short to contain the event channel flags
+        let buffer = TableBuffer::<Xenv>::new(&table(b"XENV", 1, &body[..body.len() - 1]));
+        assert!(matches!(buffer.get().validate(), Err(AcpiError::SdtInvalidLength(Signature::XENV))));
+    }
+}
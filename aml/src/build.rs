@@ -0,0 +1,1020 @@
+//! Building AML bytecode from Rust. This can be used to synthesize tables (e.g. a hypervisor generating a DSDT
+//! for its virtual hardware), or to write tests without needing tables compiled by `iasl`.
+//!
+//! Each type corresponds to an ASL construct, and objects are built up by nesting them:
+//! ```
+//! use aml::build::*;
+//!
+//! let dsdt = DefinitionBlock::new("DSDT", "RUST  ", "EXAMPLE ", 1).with(
+//!     Scope::new("\\_SB").with(
+//!         Device::new("COM1")
+//!             .with(Name::new("_HID", EisaId::new("PNP0501")))
+//!             .with(Method::new("_STA", 0).with(Return::new(0x0fu8))),
+//!     ),
+//! );
+//! let bytes = dsdt.to_bytes().unwrap();
+//! ```
+//!
+//! Names are given as strings, in the same form as [`AmlName::from_str`] takes, and are checked when the AML is
+//! encoded.
+
+use crate::{
+    device::eisa_id,
+    opcode,
+    resource::{
+        self,
+        AddressSpaceDecodeType,
+        AddressSpaceDescriptor,
+        AddressSpaceResourceType,
+        AddressSpaceWidth,
+        IOPortDescriptor,
+        InterruptPolarity,
+        InterruptTrigger,
+        IrqDescriptor,
+        IrqFormat,
+        MemoryRangeDescriptor,
+        Resource,
+    },
+    value::{FieldAccessType, FieldUpdateRule, RegionSpace},
+    AmlError,
+    AmlName,
+    NameComponent,
+};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::convert::TryInto;
+
+/// An object that can be encoded as AML.
+pub trait Aml {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError>;
+}
+
+impl<T: Aml + ?Sized> Aml for Box<T> {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        (**self).to_aml_bytes(bytes)
+    }
+}
+
+/// Integers are encoded in the smallest form that can hold them.
+impl Aml for u64 {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        match *self {
+            0 => bytes.push(opcode::ZERO_OP),
+            1 => bytes.push(opcode::ONE_OP),
+            u64::MAX => bytes.push(opcode::ONES_OP),
+            value if value <= u8::MAX as u64 => bytes.extend_from_slice(&[opcode::BYTE_CONST, value as u8]),
+            value if value <= u16::MAX as u64 => {
+                bytes.push(opcode::WORD_CONST);
+                bytes.extend_from_slice(&(value as u16).to_le_bytes());
+            }
+            value if value <= u32::MAX as u64 => {
+                bytes.push(opcode::DWORD_CONST);
+                bytes.extend_from_slice(&(value as u32).to_le_bytes());
+            }
+            value => {
+                bytes.push(opcode::QWORD_CONST);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_aml_for_integer {
+    ($($type:ty),*) => {
+        $(
+            impl Aml for $type {
+                fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+                    (*self as u64).to_aml_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_aml_for_integer!(u8, u16, u32, usize);
+
+/// Strings are encoded as string constants. Use [`Path`] for references to other objects.
+impl Aml for &str {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if !self.bytes().all(|byte| (0x01..=0x7f).contains(&byte)) {
+            return Err(AmlError::InvalidStringConstant);
+        }
+        bytes.push(opcode::STRING_PREFIX);
+        bytes.extend_from_slice(self.as_bytes());
+        bytes.push(0x00);
+        Ok(())
+    }
+}
+
+impl Aml for String {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        self.as_str().to_aml_bytes(bytes)
+    }
+}
+
+fn encode_name_string(name: &str, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+    let name = AmlName::from_str(name)?;
+    let mut segments = Vec::new();
+    for component in name.components() {
+        match component {
+            NameComponent::Root => bytes.push(opcode::ROOT_CHAR),
+            NameComponent::Prefix => bytes.push(opcode::PREFIX_CHAR),
+            NameComponent::Segment(seg) => segments.push(seg),
+        }
+    }
+
+    match segments.len() {
+        0 => bytes.push(opcode::NULL_NAME),
+        1 => (),
+        2 => bytes.push(opcode::DUAL_NAME_PREFIX),
+        count => bytes.extend_from_slice(&[opcode::MULTI_NAME_PREFIX, count as u8]),
+    }
+    for seg in segments {
+        bytes.extend_from_slice(seg.as_str().as_bytes());
+    }
+    Ok(())
+}
+
+/// Encode an object whose contents are preceded by a `PkgLength`.
+fn encode_with_pkg_length(lead: &[u8], contents: &[u8], bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+    bytes.extend_from_slice(lead);
+
+    // The length includes the bytes of the `PkgLength` itself, which can be up to four bytes long
+    let length = contents.len() + 1;
+    if length < (1 << 6) {
+        bytes.push(length as u8);
+    } else {
+        let extra_bytes = (1..=3).find(|&n| length + n < (1 << (4 + 8 * n))).ok_or(AmlError::InvalidPkgLength)?;
+        let length = length + extra_bytes;
+        bytes.push(((extra_bytes as u8) << 6) | (length as u8 & 0x0f));
+        for i in 0..extra_bytes {
+            bytes.push((length >> (4 + 8 * i)) as u8);
+        }
+    }
+
+    bytes.extend_from_slice(contents);
+    Ok(())
+}
+
+fn encode_objects(objects: &[Box<dyn Aml>], bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+    for object in objects {
+        object.to_aml_bytes(bytes)?;
+    }
+    Ok(())
+}
+
+/// A reference to another object, by name.
+pub struct Path(String);
+
+impl Path {
+    pub fn new(name: &str) -> Path {
+        Path(String::from(name))
+    }
+}
+
+impl Aml for Path {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        encode_name_string(&self.0, bytes)
+    }
+}
+
+/// One of the local variables of a method, `Local0` to `Local7`.
+pub struct Local(pub u8);
+
+impl Aml for Local {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if self.0 > 7 {
+            return Err(AmlError::InvalidLocalAccess(self.0));
+        }
+        bytes.push(opcode::LOCAL0_OP + self.0);
+        Ok(())
+    }
+}
+
+/// One of the arguments of a method, `Arg0` to `Arg6`.
+pub struct Arg(pub u8);
+
+impl Aml for Arg {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if self.0 > 6 {
+            return Err(AmlError::InvalidArgAccess(self.0));
+        }
+        bytes.push(opcode::ARG0_OP + self.0);
+        Ok(())
+    }
+}
+
+/// A PNP ID compressed into the 32-bit EISA ID encoding, like the ASL `EisaId` macro.
+pub struct EisaId(String);
+
+impl EisaId {
+    pub fn new(id: &str) -> EisaId {
+        EisaId(String::from(id))
+    }
+}
+
+impl Aml for EisaId {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        let id = eisa_id(&self.0).ok_or(AmlError::InvalidEisaId)?;
+        bytes.push(opcode::DWORD_CONST);
+        bytes.extend_from_slice(&id.to_le_bytes());
+        Ok(())
+    }
+}
+
+pub struct Buffer(Vec<u8>);
+
+impl Buffer {
+    pub fn new(data: Vec<u8>) -> Buffer {
+        Buffer(data)
+    }
+}
+
+impl Aml for Buffer {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        let mut contents = Vec::new();
+        self.0.len().to_aml_bytes(&mut contents)?;
+        contents.extend_from_slice(&self.0);
+        encode_with_pkg_length(&[opcode::DEF_BUFFER_OP], &contents, bytes)
+    }
+}
+
+pub struct Package(Vec<Box<dyn Aml>>);
+
+impl Package {
+    pub fn new() -> Package {
+        Package(Vec::new())
+    }
+
+    pub fn with(mut self, element: impl Aml + 'static) -> Package {
+        self.0.push(Box::new(element));
+        self
+    }
+}
+
+impl Default for Package {
+    fn default() -> Package {
+        Package::new()
+    }
+}
+
+impl Aml for Package {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        let count: u8 = self.0.len().try_into().map_err(|_| AmlError::TooManyPackageElements)?;
+        let mut contents = alloc::vec![count];
+        encode_objects(&self.0, &mut contents)?;
+        encode_with_pkg_length(&[opcode::DEF_PACKAGE_OP], &contents, bytes)
+    }
+}
+
+/// A buffer containing a resource template, like the ASL `ResourceTemplate` macro. Resources can be added with
+/// [`ResourceTemplate::with`], or with the methods named after the ASL macros for the common descriptors.
+pub struct ResourceTemplate {
+    resources: Vec<Resource>,
+    /// Set if one of the resources couldn't be described (e.g. because its range overflows). This is reported when
+    /// the template is encoded.
+    error: Option<AmlError>,
+}
+
+impl ResourceTemplate {
+    pub fn new() -> ResourceTemplate {
+        ResourceTemplate { resources: Vec::new(), error: None }
+    }
+
+    pub fn with(mut self, resource: Resource) -> ResourceTemplate {
+        self.resources.push(resource);
+        self
+    }
+
+    pub fn memory_32_fixed(self, is_writable: bool, base_address: u32, range_length: u32) -> ResourceTemplate {
+        self.with(Resource::MemoryRange(MemoryRangeDescriptor::FixedLocation {
+            is_writable,
+            base_address,
+            range_length,
+        }))
+    }
+
+    /// An I/O port descriptor that decodes the full 16-bit address, like the ASL `IO (Decode16, ...)` macro.
+    pub fn io(self, minimum: u16, maximum: u16, alignment: u8, length: u8) -> ResourceTemplate {
+        self.with(Resource::IOPort(IOPortDescriptor {
+            decodes_full_address: true,
+            memory_range: (minimum, maximum),
+            base_alignment: alignment,
+            range_length: length,
+        }))
+    }
+
+    /// An extended interrupt descriptor consumed by the device, like the ASL `Interrupt` macro.
+    pub fn interrupt(
+        self,
+        trigger: InterruptTrigger,
+        polarity: InterruptPolarity,
+        is_shared: bool,
+        irq: u32,
+    ) -> ResourceTemplate {
+        self.with(Resource::Irq(IrqDescriptor {
+            is_consumer: true,
+            trigger,
+            polarity,
+            is_shared,
+            is_wake_capable: false,
+            irq,
+            format: IrqFormat::Extended { additional_irqs: Vec::new() },
+        }))
+    }
+
+    /// A memory range that the device decodes, like the ASL `QWordMemory` macro, with a fixed base address.
+    pub fn qword_memory(self, base_address: u64, length: u64) -> ResourceTemplate {
+        self.address_space(AddressSpaceWidth::QWord, AddressSpaceResourceType::MemoryRange, base_address, length)
+    }
+
+    /// A range of bus numbers that a bridge decodes, like the ASL `WordBusNumber` macro.
+    pub fn bus_number(self, start: u16, count: u16) -> ResourceTemplate {
+        self.address_space(
+            AddressSpaceWidth::Word,
+            AddressSpaceResourceType::BusNumberRange,
+            start as u64,
+            count as u64,
+        )
+    }
+
+    fn address_space(
+        mut self,
+        width: AddressSpaceWidth,
+        resource_type: AddressSpaceResourceType,
+        base: u64,
+        length: u64,
+    ) -> ResourceTemplate {
+        // The maximum is the last address in the range, so a range of length `0` is just the base
+        let maximum = match length.checked_sub(1).map_or(Some(base), |extent| base.checked_add(extent)) {
+            Some(maximum) => maximum,
+            None => {
+                self.error = Some(AmlError::InvalidResourceDescriptor);
+                return self;
+            }
+        };
+
+        self.with(Resource::AddressSpace(AddressSpaceDescriptor {
+            width,
+            resource_type,
+            is_consumer: false,
+            is_maximum_address_fixed: true,
+            is_minimum_address_fixed: true,
+            decode_type: AddressSpaceDecodeType::Additive,
+            type_specific_flags: 0,
+            granularity: 0,
+            address_range: (base, maximum),
+            translation_offset: 0,
+            length,
+            resource_source: String::new(),
+            resource_source_index: 0,
+        }))
+    }
+}
+
+impl Default for ResourceTemplate {
+    fn default() -> ResourceTemplate {
+        ResourceTemplate::new()
+    }
+}
+
+impl Aml for ResourceTemplate {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
+        }
+        Buffer::new(resource::encode_resource_template(&self.resources)?).to_aml_bytes(bytes)
+    }
+}
+
+/// Declares a named object, like the ASL `Name` operator.
+pub struct Name {
+    name: String,
+    value: Box<dyn Aml>,
+}
+
+impl Name {
+    pub fn new(name: &str, value: impl Aml + 'static) -> Name {
+        Name { name: String::from(name), value: Box::new(value) }
+    }
+}
+
+impl Aml for Name {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        bytes.push(opcode::DEF_NAME_OP);
+        encode_name_string(&self.name, bytes)?;
+        self.value.to_aml_bytes(bytes)
+    }
+}
+
+macro_rules! scoped_object {
+    ($(#[$meta:meta])* $name:ident, $($opcode:expr),+) => {
+        $(#[$meta])*
+        pub struct $name {
+            name: String,
+            objects: Vec<Box<dyn Aml>>,
+        }
+
+        impl $name {
+            pub fn new(name: &str) -> $name {
+                $name { name: String::from(name), objects: Vec::new() }
+            }
+
+            pub fn with(mut self, object: impl Aml + 'static) -> $name {
+                self.objects.push(Box::new(object));
+                self
+            }
+        }
+
+        impl Aml for $name {
+            fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+                let mut contents = Vec::new();
+                encode_name_string(&self.name, &mut contents)?;
+                encode_objects(&self.objects, &mut contents)?;
+                encode_with_pkg_length(&[$($opcode),+], &contents, bytes)
+            }
+        }
+    };
+}
+
+scoped_object!(
+    /// Opens an existing scope of the namespace, like the ASL `Scope` operator.
+    Scope,
+    opcode::DEF_SCOPE_OP
+);
+scoped_object!(Device, opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_DEVICE_OP);
+scoped_object!(ThermalZone, opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_THERMAL_ZONE_OP);
+
+pub struct Method {
+    name: String,
+    arg_count: u8,
+    serialized: bool,
+    sync_level: u8,
+    objects: Vec<Box<dyn Aml>>,
+}
+
+impl Method {
+    pub fn new(name: &str, arg_count: u8) -> Method {
+        Method { name: String::from(name), arg_count, serialized: false, sync_level: 0, objects: Vec::new() }
+    }
+
+    /// Make the method serialized, with the given sync level.
+    pub fn serialized(mut self, sync_level: u8) -> Method {
+        self.serialized = true;
+        self.sync_level = sync_level;
+        self
+    }
+
+    pub fn with(mut self, object: impl Aml + 'static) -> Method {
+        self.objects.push(Box::new(object));
+        self
+    }
+}
+
+impl Aml for Method {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if self.arg_count > 7 || self.sync_level > 15 {
+            return Err(AmlError::InvalidMethodFlags);
+        }
+
+        let mut contents = Vec::new();
+        encode_name_string(&self.name, &mut contents)?;
+        contents.push(self.arg_count | ((self.serialized as u8) << 3) | (self.sync_level << 4));
+        encode_objects(&self.objects, &mut contents)?;
+        encode_with_pkg_length(&[opcode::DEF_METHOD_OP], &contents, bytes)
+    }
+}
+
+pub struct OperationRegion {
+    name: String,
+    space: RegionSpace,
+    offset: Box<dyn Aml>,
+    length: Box<dyn Aml>,
+}
+
+impl OperationRegion {
+    pub fn new(name: &str, space: RegionSpace, offset: impl Aml + 'static, length: impl Aml + 'static) -> Self {
+        OperationRegion { name: String::from(name), space, offset: Box::new(offset), length: Box::new(length) }
+    }
+}
+
+impl Aml for OperationRegion {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        bytes.extend_from_slice(&[opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_OP_REGION_OP]);
+        encode_name_string(&self.name, bytes)?;
+        bytes.push(self.space.id());
+        self.offset.to_aml_bytes(bytes)?;
+        self.length.to_aml_bytes(bytes)
+    }
+}
+
+/// Declares the field units of an operation region, like the ASL `Field` operator.
+pub struct Field {
+    region: String,
+    flags: u8,
+    /// The encoded field list.
+    elements: Vec<u8>,
+    error: Option<AmlError>,
+}
+
+impl Field {
+    pub fn new(region: &str, access_type: FieldAccessType, lock: bool, update_rule: FieldUpdateRule) -> Field {
+        let access_type = match access_type {
+            FieldAccessType::Any => 0,
+            FieldAccessType::Byte => 1,
+            FieldAccessType::Word => 2,
+            FieldAccessType::DWord => 3,
+            FieldAccessType::QWord => 4,
+            FieldAccessType::Buffer => 5,
+        };
+        let update_rule = match update_rule {
+            FieldUpdateRule::Preserve => 0,
+            FieldUpdateRule::WriteAsOnes => 1,
+            FieldUpdateRule::WriteAsZeros => 2,
+        };
+        Field {
+            region: String::from(region),
+            flags: access_type | ((lock as u8) << 4) | (update_rule << 5),
+            elements: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Add a field unit called `name`, which is `length` bits long.
+    pub fn named(mut self, name: &str, length: u32) -> Field {
+        let mut seg = Vec::new();
+        match encode_name_string(name, &mut seg) {
+            Ok(()) if seg.len() == 4 => {
+                self.elements.extend_from_slice(&seg);
+                self.encode_field_length(length);
+            }
+            Ok(()) => self.error = Some(AmlError::InvalidNameSeg),
+            Err(err) => self.error = Some(err),
+        }
+        self
+    }
+
+    /// Skip `length` bits of the region, like an unnamed entry or `Offset` in ASL.
+    pub fn reserved(mut self, length: u32) -> Field {
+        self.elements.push(opcode::RESERVED_FIELD);
+        self.encode_field_length(length);
+        self
+    }
+
+    fn encode_field_length(&mut self, length: u32) {
+        // Field lengths are encoded as `PkgLength`s, but don't include the length of the encoding
+        if length < (1 << 6) {
+            self.elements.push(length as u8);
+        } else if let Some(extra_bytes) = (1..=3).find(|&n| length < (1 << (4 + 8 * n))) {
+            self.elements.push(((extra_bytes as u8) << 6) | (length as u8 & 0x0f));
+            for i in 0..extra_bytes {
+                self.elements.push((length >> (4 + 8 * i)) as u8);
+            }
+        } else {
+            self.error = Some(AmlError::InvalidPkgLength);
+        }
+    }
+}
+
+impl Aml for Field {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        if let Some(ref err) = self.error {
+            return Err(err.clone());
+        }
+
+        let mut contents = Vec::new();
+        encode_name_string(&self.region, &mut contents)?;
+        contents.push(self.flags);
+        contents.extend_from_slice(&self.elements);
+        encode_with_pkg_length(&[opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_FIELD_OP], &contents, bytes)
+    }
+}
+
+pub struct Mutex {
+    name: String,
+    sync_level: u8,
+}
+
+impl Mutex {
+    pub fn new(name: &str, sync_level: u8) -> Mutex {
+        Mutex { name: String::from(name), sync_level }
+    }
+}
+
+impl Aml for Mutex {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        bytes.extend_from_slice(&[opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_MUTEX_OP]);
+        encode_name_string(&self.name, bytes)?;
+        bytes.push(self.sync_level);
+        Ok(())
+    }
+}
+
+/// An invocation of a control method.
+pub struct MethodCall {
+    name: String,
+    args: Vec<Box<dyn Aml>>,
+}
+
+impl MethodCall {
+    pub fn new(name: &str) -> MethodCall {
+        MethodCall { name: String::from(name), args: Vec::new() }
+    }
+
+    pub fn arg(mut self, arg: impl Aml + 'static) -> MethodCall {
+        self.args.push(Box::new(arg));
+        self
+    }
+}
+
+impl Aml for MethodCall {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        encode_name_string(&self.name, bytes)?;
+        encode_objects(&self.args, bytes)
+    }
+}
+
+macro_rules! block_statement {
+    ($(#[$meta:meta])* $name:ident, $opcode:expr) => {
+        $(#[$meta])*
+        pub struct $name {
+            predicate: Box<dyn Aml>,
+            objects: Vec<Box<dyn Aml>>,
+        }
+
+        impl $name {
+            pub fn new(predicate: impl Aml + 'static) -> $name {
+                $name { predicate: Box::new(predicate), objects: Vec::new() }
+            }
+
+            pub fn with(mut self, object: impl Aml + 'static) -> $name {
+                self.objects.push(Box::new(object));
+                self
+            }
+        }
+
+        impl Aml for $name {
+            fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+                let mut contents = Vec::new();
+                self.predicate.to_aml_bytes(&mut contents)?;
+                encode_objects(&self.objects, &mut contents)?;
+                encode_with_pkg_length(&[$opcode], &contents, bytes)
+            }
+        }
+    };
+}
+
+block_statement!(
+    /// An `If` statement. An `Else` can follow it as the next object.
+    If,
+    opcode::DEF_IF_ELSE_OP
+);
+block_statement!(While, opcode::DEF_WHILE_OP);
+
+pub struct Else(Vec<Box<dyn Aml>>);
+
+impl Else {
+    pub fn new() -> Else {
+        Else(Vec::new())
+    }
+
+    pub fn with(mut self, object: impl Aml + 'static) -> Else {
+        self.0.push(Box::new(object));
+        self
+    }
+}
+
+impl Default for Else {
+    fn default() -> Else {
+        Else::new()
+    }
+}
+
+impl Aml for Else {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        let mut contents = Vec::new();
+        encode_objects(&self.0, &mut contents)?;
+        encode_with_pkg_length(&[opcode::DEF_ELSE_OP], &contents, bytes)
+    }
+}
+
+macro_rules! operator {
+    ($(#[$meta:meta])* $name:ident, $opcode:expr, $($operand:ident),+) => {
+        $(#[$meta])*
+        pub struct $name {
+            $($operand: Box<dyn Aml>),+
+        }
+
+        impl $name {
+            pub fn new($($operand: impl Aml + 'static),+) -> $name {
+                $name { $($operand: Box::new($operand)),+ }
+            }
+        }
+
+        impl Aml for $name {
+            fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+                bytes.extend_from_slice(&$opcode);
+                $(self.$operand.to_aml_bytes(bytes)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+operator!(Return, [opcode::DEF_RETURN_OP], value);
+operator!(
+    /// Stores `value` into `target`, which can be a name, local, or argument.
+    Store,
+    [opcode::DEF_STORE_OP],
+    value,
+    target
+);
+operator!(Notify, [opcode::DEF_NOTIFY_OP], object, value);
+operator!(Increment, [opcode::DEF_INCREMENT_OP], target);
+operator!(Decrement, [opcode::DEF_DECREMENT_OP], target);
+operator!(Release, [opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_RELEASE_OP], mutex);
+operator!(LAnd, [opcode::DEF_L_AND_OP], left, right);
+operator!(LOr, [opcode::DEF_L_OR_OP], left, right);
+operator!(LNot, [opcode::DEF_L_NOT_OP], operand);
+operator!(LEqual, [opcode::DEF_L_EQUAL_OP], left, right);
+operator!(LGreater, [opcode::DEF_L_GREATER_OP], left, right);
+operator!(LLess, [opcode::DEF_L_LESS_OP], left, right);
+
+/// Acquires `mutex`, like the ASL `Acquire` operator, waiting for up to `timeout` milliseconds (or forever, if
+/// `timeout` is `0xffff`). Evaluates to whether the acquire timed out.
+pub struct Acquire {
+    mutex: Box<dyn Aml>,
+    timeout: u16,
+}
+
+impl Acquire {
+    pub fn new(mutex: impl Aml + 'static, timeout: u16) -> Acquire {
+        Acquire { mutex: Box::new(mutex), timeout }
+    }
+}
+
+impl Aml for Acquire {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        bytes.extend_from_slice(&[opcode::EXT_OPCODE_PREFIX, opcode::EXT_DEF_ACQUIRE_OP]);
+        self.mutex.to_aml_bytes(bytes)?;
+        // The timeout is a raw `WordData`, rather than an integer object
+        bytes.extend_from_slice(&self.timeout.to_le_bytes());
+        Ok(())
+    }
+}
+
+/// A `Target` that isn't stored to.
+struct NullTarget;
+
+impl Aml for NullTarget {
+    fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+        bytes.push(opcode::NULL_NAME);
+        Ok(())
+    }
+}
+
+macro_rules! binary_operator {
+    ($($name:ident => $opcode:expr),* $(,)?) => {
+        $(
+            pub struct $name {
+                left: Box<dyn Aml>,
+                right: Box<dyn Aml>,
+                target: Box<dyn Aml>,
+            }
+
+            impl $name {
+                pub fn new(left: impl Aml + 'static, right: impl Aml + 'static) -> $name {
+                    $name { left: Box::new(left), right: Box::new(right), target: Box::new(NullTarget) }
+                }
+
+                /// Store the result into `target`, as well as producing it.
+                pub fn target(mut self, target: impl Aml + 'static) -> $name {
+                    self.target = Box::new(target);
+                    self
+                }
+            }
+
+            impl Aml for $name {
+                fn to_aml_bytes(&self, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+                    bytes.push($opcode);
+                    self.left.to_aml_bytes(bytes)?;
+                    self.right.to_aml_bytes(bytes)?;
+                    self.target.to_aml_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+binary_operator!(
+    Add => opcode::DEF_ADD_OP,
+    Subtract => opcode::DEF_SUBTRACT_OP,
+    Multiply => opcode::DEF_MULTIPLY_OP,
+    And => opcode::DEF_AND_OP,
+    Or => opcode::DEF_OR_OP,
+    XOr => opcode::DEF_XOR_OP,
+    ShiftLeft => opcode::DEF_SHIFT_LEFT,
+    ShiftRight => opcode::DEF_SHIFT_RIGHT,
+    Mod => opcode::DEF_MOD_OP,
+    Concatenate => opcode::DEF_CONCAT_OP,
+);
+
+/// A complete table of AML (e.g. a DSDT or SSDT), like the ASL `DefinitionBlock`.
+pub struct DefinitionBlock {
+    signature: [u8; 4],
+    oem_id: [u8; 6],
+    oem_table_id: [u8; 8],
+    oem_revision: u32,
+    objects: Vec<Box<dyn Aml>>,
+}
+
+impl DefinitionBlock {
+    const HEADER_LENGTH: usize = 36;
+
+    /// Create a definition block. The signature, OEM ID, and OEM table ID are padded with spaces, or truncated, to
+    /// the lengths of their fields in the table header.
+    pub fn new(signature: &str, oem_id: &str, oem_table_id: &str, oem_revision: u32) -> DefinitionBlock {
+        fn pad<const N: usize>(string: &str) -> [u8; N] {
+            let mut field = [b' '; N];
+            for (byte, &c) in field.iter_mut().zip(string.as_bytes()) {
+                *byte = c;
+            }
+            field
+        }
+
+        DefinitionBlock {
+            signature: pad(signature),
+            oem_id: pad(oem_id),
+            oem_table_id: pad(oem_table_id),
+            oem_revision,
+            objects: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, object: impl Aml + 'static) -> DefinitionBlock {
+        self.objects.push(Box::new(object));
+        self
+    }
+
+    /// Encode the table, including its header.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AmlError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.signature);
+        bytes.extend_from_slice(&[0; 4]);
+        // Revision 2 tables have 64-bit integers
+        bytes.push(2);
+        bytes.push(0);
+        bytes.extend_from_slice(&self.oem_id);
+        bytes.extend_from_slice(&self.oem_table_id);
+        bytes.extend_from_slice(&self.oem_revision.to_le_bytes());
+        bytes.extend_from_slice(b"RUST");
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        debug_assert_eq!(bytes.len(), Self::HEADER_LENGTH);
+
+        encode_objects(&self.objects, &mut bytes)?;
+
+        let length: u32 = bytes.len().try_into().map_err(|_| AmlError::InvalidDefinitionBlock)?;
+        bytes[4..8].copy_from_slice(&length.to_le_bytes());
+        let checksum = bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        bytes[9] = 0u8.wrapping_sub(checksum);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, value::Args, AmlValue};
+
+    fn encode(object: impl Aml) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        object.to_aml_bytes(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(0u64), [0x00]);
+        assert_eq!(encode(0x12u8), [0x0a, 0x12]);
+        assert_eq!(encode(0x1234_5678u64), [0x0c, 0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(encode(u64::MAX), [0xff]);
+        assert_eq!(encode("Hi"), [0x0d, b'H', b'i', 0x00]);
+        assert_eq!(encode(Path::new("\\_SB.PCI0")), *b"\\\x2e_SB_PCI0");
+        assert_eq!(encode(Path::new("^^FOO")), *b"^^FOO_");
+        assert_eq!(encode(Path::new("A.B.C")), *b"\x2f\x03A___B___C___");
+        assert_eq!(
+            encode(Name::new("_HID", EisaId::new("PNP0A08"))),
+            [0x08, b'_', b'H', b'I', b'D', 0x0c, 0x41, 0xd0, 0x0a, 0x08]
+        );
+        assert_eq!(
+            encode(Method::new("_STA", 0).with(Return::new(0x0fu8))),
+            [0x14, 0x09, b'_', b'S', b'T', b'A', 0x00, 0xa4, 0x0a, 0x0f]
+        );
+        assert_eq!(encode(Add::new(Local(0), 1u8).target(Arg(1))), [0x72, 0x60, 0x01, 0x69]);
+        assert_eq!(encode(Buffer::new(alloc::vec![0; 70]))[0..4], [0x11, 0x4a, 0x04, 0x0a]);
+
+        let mut bytes = Vec::new();
+        assert_eq!(Path::new("FOOBAR").to_aml_bytes(&mut bytes), Err(AmlError::InvalidNameSeg));
+        assert_eq!(EisaId::new("PNP").to_aml_bytes(&mut bytes), Err(AmlError::InvalidEisaId));
+    }
+
+    #[test]
+    fn test_definition_block() {
+        let table = DefinitionBlock::new("SSDT", "RUST", "TEST", 1)
+            .with(
+                Scope::new("\\_SB").with(
+                    Device::new("PCI0")
+                        .with(Name::new("_HID", EisaId::new("PNP0A08")))
+                        .with(Name::new(
+                            "_CRS",
+                            ResourceTemplate::new().bus_number(0, 0x100).io(0xcf8, 0xcf8, 1, 8),
+                        ))
+                        .with(OperationRegion::new("GPIO", RegionSpace::SystemIo, 0x80u8, 2u8))
+                        .with(
+                            Field::new("GPIO", FieldAccessType::Byte, false, FieldUpdateRule::Preserve)
+                                .named("GP0", 8)
+                                .reserved(4)
+                                .named("GP1", 4),
+                        )
+                        .with(
+                            Method::new("FOO", 1)
+                                .serialized(0)
+                                .with(
+                                    If::new(LEqual::new(Arg(0), 1u8))
+                                        .with(Return::new(Package::new().with(1u8).with("One"))),
+                                )
+                                .with(Else::new().with(Store::new(ShiftLeft::new(Arg(0), 4u8), Local(0))))
+                                .with(Return::new(Local(0))),
+                        ),
+                ),
+            )
+            .to_bytes()
+            .unwrap();
+
+        assert_eq!(&table[0..4], b"SSDT");
+        assert_eq!(u32::from_le_bytes([table[4], table[5], table[6], table[7]]) as usize, table.len());
+        assert_eq!(table.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)), 0);
+
+        let mut context = make_test_context();
+        context.parse_table(&table[36..]).unwrap();
+        let foo = AmlName::from_str("\\_SB.PCI0.FOO").unwrap();
+        assert!(matches!(
+            context.invoke_method(&foo, Args::from_list(alloc::vec![AmlValue::Integer(2)]).unwrap()),
+            Ok(AmlValue::Integer(0x20))
+        ));
+        assert!(matches!(
+            context.invoke_method(&foo, Args::from_list(alloc::vec![AmlValue::Integer(1)]).unwrap()),
+            Ok(AmlValue::Package(ref elements)) if elements.len() == 2
+        ));
+
+        let crs = context.namespace.get_by_path(&AmlName::from_str("\\_SB.PCI0._CRS").unwrap()).unwrap();
+        let resources = resource::resource_descriptor_list(&crs).unwrap();
+        assert_eq!(resources.len(), 2);
+        assert!(matches!(
+            resources[0],
+            Resource::AddressSpace(AddressSpaceDescriptor {
+                width: AddressSpaceWidth::Word,
+                resource_type: AddressSpaceResourceType::BusNumberRange,
+                address_range: (0, 0xff),
+                length: 0x100,
+                ..
+            })
+        ));
+        assert!(matches!(resources[1], Resource::IOPort(IOPortDescriptor { memory_range: (0xcf8, 0xcf8), .. })));
+    }
+
+    #[test]
+    fn test_acquire() {
+        assert_eq!(
+            encode(Acquire::new(Path::new("MTX0"), 0xffff)),
+            [0x5b, 0x23, b'M', b'T', b'X', b'0', 0xff, 0xff]
+        );
+
+        let table = DefinitionBlock::new("SSDT", "RUST", "TEST", 1)
+            .with(Mutex::new("MTX0", 0))
+            .with(
+                Method::new("ACQ", 0)
+                    .with(Store::new(Acquire::new(Path::new("MTX0"), 0x0100), Local(0)))
+                    .with(Release::new(Path::new("MTX0")))
+                    .with(Return::new(Local(0))),
+            )
+            .to_bytes()
+            .unwrap();
+
+        let mut context = make_test_context();
+        context.parse_table(&table[36..]).unwrap();
+        assert!(matches!(
+            context.invoke_method(&AmlName::from_str("\\ACQ").unwrap(), Args::EMPTY),
+            Ok(AmlValue::Boolean(false))
+        ));
+    }
+
+    #[test]
+    fn test_address_space_overflow() {
+        let mut bytes = Vec::new();
+        assert_eq!(
+            ResourceTemplate::new().qword_memory(u64::MAX, 2).to_aml_bytes(&mut bytes),
+            Err(AmlError::InvalidResourceDescriptor)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Hooks for debugging the AML executed by the interpreter, enabled by the `debug` feature. A [`Debugger`]
+//! installed with [`AmlContext::install_debugger`] is called before an opcode is executed if:
+//!    - the opcode is a `BreakPoint`
+//!    - a breakpoint has been set at the opcode with [`AmlContext::add_breakpoint`]
+//!    - the debugger asked to single-step when it was last called
+//!    - opcode tracing has been turned on with [`AmlContext::set_opcode_trace`]
+//!
+//! The interpreter is paused while the debugger is called, so it can inspect the state of the method (through
+//! [`DebugState`]) and the namespace, and then choose how execution should continue.
+
+use crate::{opcode, AmlContext, AmlError, AmlName, AmlValue};
+use alloc::{boxed::Box, collections::BTreeSet, vec::Vec};
+
+/// Why the interpreter called the debugger.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopReason {
+    /// The opcode is a `BreakPoint`.
+    BreakPointOp,
+    /// A breakpoint was set at the opcode with [`AmlContext::add_breakpoint`].
+    Breakpoint,
+    /// The debugger asked to single-step when it was last called.
+    Step,
+    /// Opcode tracing is turned on.
+    Trace,
+}
+
+/// What the interpreter should do after calling the debugger.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DebugAction {
+    /// Execute the opcode, and continue until the next breakpoint (or the next opcode, if tracing is turned on).
+    Continue,
+    /// Execute the opcode, and call the debugger again before the next one.
+    Step,
+    /// Stop executing, producing an `AmlError::DebuggerAbort`.
+    Abort,
+}
+
+/// The state of the interpreter when it calls the debugger.
+#[derive(Debug)]
+pub struct DebugState<'a> {
+    pub reason: StopReason,
+    /// The control method being executed, or `None` if the opcode is part of a table being loaded.
+    pub method: Option<&'a AmlName>,
+    /// The offset of the opcode from the start of the method's code, or from the start of the table.
+    pub pc: usize,
+    /// The opcode that is about to be executed. Extended opcodes have the `0x5b` prefix in their high byte.
+    pub opcode: u16,
+    /// The values of `Local0` to `Local7`. This is empty if a table is being loaded.
+    pub locals: &'a [Option<AmlValue>],
+    /// The arguments the method was invoked with. This is empty if a table is being loaded.
+    pub args: &'a [Option<AmlValue>],
+}
+
+pub trait Debugger: Send + Sync {
+    fn on_stop(&mut self, state: &DebugState) -> DebugAction;
+}
+
+/// A stream of AML being executed - either the code of a control method, or a table being loaded.
+struct Frame {
+    method: Option<AmlName>,
+    /// The address of the first byte of the stream, which is used to work out the offsets of opcodes in it.
+    base: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct DebuggerState {
+    debugger: Option<Box<dyn Debugger>>,
+    breakpoints: BTreeSet<(AmlName, usize)>,
+    trace: bool,
+    stepping: bool,
+    frames: Vec<Frame>,
+}
+
+impl AmlContext {
+    /// Install the debugger called by the interpreter, returning the previous one.
+    pub fn install_debugger(&mut self, debugger: Box<dyn Debugger>) -> Option<Box<dyn Debugger>> {
+        self.debugger.debugger.replace(debugger)
+    }
+
+    pub fn remove_debugger(&mut self) -> Option<Box<dyn Debugger>> {
+        self.debugger.debugger.take()
+    }
+
+    /// Set a breakpoint at the opcode at offset `pc` into the code of the control method at `method`.
+    pub fn add_breakpoint(&mut self, method: AmlName, pc: usize) {
+        self.debugger.breakpoints.insert((method, pc));
+    }
+
+    /// Remove a breakpoint set with [`AmlContext::add_breakpoint`]. Returns `false` if it wasn't set.
+    pub fn remove_breakpoint(&mut self, method: AmlName, pc: usize) -> bool {
+        self.debugger.breakpoints.remove(&(method, pc))
+    }
+
+    /// Set whether the debugger is called before every opcode.
+    pub fn set_opcode_trace(&mut self, trace: bool) {
+        self.debugger.trace = trace;
+    }
+
+    /// Mark the start of executing a stream of AML, either the code of `method`, or a table if it's `None`.
+    pub(crate) fn debug_enter_stream(&mut self, method: Option<AmlName>, stream: &[u8]) {
+        self.debugger.frames.push(Frame { method, base: stream.as_ptr() as usize });
+    }
+
+    pub(crate) fn debug_exit_stream(&mut self) {
+        self.debugger.frames.pop();
+    }
+
+    /// Called by the interpreter before it executes the opcode at the start of `input`.
+    pub(crate) fn debug_opcode(&mut self, input: &[u8]) -> Result<(), AmlError> {
+        let DebuggerState { debugger, breakpoints, trace, stepping, frames } = &mut self.debugger;
+        let (debugger, frame) = match (debugger, frames.last()) {
+            (Some(debugger), Some(frame)) => (debugger, frame),
+            _ => return Ok(()),
+        };
+
+        let pc = input.as_ptr() as usize - frame.base;
+        let opcode = match input {
+            [opcode::EXT_OPCODE_PREFIX, ext_opcode, ..] => {
+                u16::from_be_bytes([opcode::EXT_OPCODE_PREFIX, *ext_opcode])
+            }
+            [opcode, ..] => *opcode as u16,
+            [] => return Ok(()),
+        };
+
+        let reason = if opcode == opcode::DEF_BREAKPOINT_OP as u16 {
+            StopReason::BreakPointOp
+        } else if frame.method.as_ref().is_some_and(|method| breakpoints.contains(&(method.clone(), pc))) {
+            StopReason::Breakpoint
+        } else if *stepping {
+            StopReason::Step
+        } else if *trace {
+            StopReason::Trace
+        } else {
+            return Ok(());
+        };
+
+        let (locals, args): (&[Option<AmlValue>], &[Option<AmlValue>]) =
+            match (&frame.method, &self.method_context) {
+                (Some(_), Some(method_context)) => (&method_context.locals, &method_context.args.0),
+                _ => (&[], &[]),
+            };
+        let state = DebugState { reason, method: frame.method.as_ref(), pc, opcode, locals, args };
+
+        match debugger.on_stop(&state) {
+            DebugAction::Continue => {
+                *stepping = false;
+                Ok(())
+            }
+            DebugAction::Step => {
+                *stepping = true;
+                Ok(())
+            }
+            DebugAction::Abort => {
+                *stepping = false;
+                Err(AmlError::DebuggerAbort)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::*,
+        value::{AmlCode, Args, MethodCode, MethodFlags},
+    };
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use spinning_top::Spinlock;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Stop {
+        reason: StopReason,
+        pc: usize,
+        opcode: u16,
+        local0: Option<u64>,
+    }
+
+    struct TestDebugger {
+        stops: Arc<Spinlock<Vec<Stop>>>,
+        actions: Vec<DebugAction>,
+    }
+
+    impl Debugger for TestDebugger {
+        fn on_stop(&mut self, state: &DebugState) -> DebugAction {
+            let local0 = match state.locals[0] {
+                Some(AmlValue::Integer(value)) => Some(value),
+                _ => None,
+            };
+            self.stops.lock().push(Stop { reason: state.reason, pc: state.pc, opcode: state.opcode, local0 });
+            if self.actions.is_empty() {
+                DebugAction::Continue
+            } else {
+                self.actions.remove(0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_debugger() {
+        let mut context = make_test_context();
+        let method = AmlName::from_str("\\TEST").unwrap();
+
+        /*
+         * Method (TEST) {
+         *     Local0 = 5
+         *     BreakPoint
+         *     Local0 = 6
+         *     Return (Local0)
+         * }
+         */
+        let code = vec![0x70, 0x0a, 0x05, 0x60, 0xcc, 0x70, 0x0a, 0x06, 0x60, 0xa4, 0x60];
+        context
+            .namespace
+            .add_value(
+                method.clone(),
+                AmlValue::Method {
+                    flags: MethodFlags::new(0, false, 0),
+                    code: MethodCode::Aml(AmlCode::new(&code)),
+                },
+            )
+            .unwrap();
+
+        let stops = Arc::new(Spinlock::new(Vec::new()));
+        context.install_debugger(Box::new(TestDebugger {
+            stops: stops.clone(),
+            actions: vec![DebugAction::Step, DebugAction::Continue],
+        }));
+        context.add_breakpoint(method.clone(), 0);
+        assert!(matches!(context.invoke_method(&method, Args::EMPTY), Ok(AmlValue::Integer(6))));
+        assert_eq!(
+            *stops.lock(),
+            vec![
+                Stop { reason: StopReason::Breakpoint, pc: 0, opcode: 0x70, local0: None },
+                Stop { reason: StopReason::BreakPointOp, pc: 4, opcode: 0xcc, local0: Some(5) },
+            ]
+        );
+
+        stops.lock().clear();
+        assert!(context.remove_breakpoint(method.clone(), 0));
+        context.set_opcode_trace(true);
+        context.install_debugger(Box::new(TestDebugger {
+            stops: stops.clone(),
+            actions: vec![DebugAction::Continue, DebugAction::Abort],
+        }));
+        assert_eq!(context.invoke_method(&method, Args::EMPTY).err(), Some(AmlError::DebuggerAbort));
+        assert_eq!(
+            stops.lock().iter().map(|stop| (stop.reason, stop.pc)).collect::<Vec<_>>(),
+            vec![(StopReason::Trace, 0), (StopReason::BreakPointOp, 4)]
+        );
+    }
+}
@@ -0,0 +1,564 @@
+//! Finding devices in the namespace by their hardware IDs (`_HID`), compatible IDs (`_CID`), or addresses
+//! (`_ADR`), which is how drivers find the devices they bind to.
+//!
+//! Device IDs are either ACPI or PNP ID strings (e.g. `ACPI0003` or `PNP0C09`), or PNP IDs compressed into the
+//! 32-bit EISA ID encoding (which is what the ASL `EisaId` macro produces). Matchers that take a string match IDs
+//! in either encoding.
+//!
+//! Once a device has been found, the objects that describe it - `_UID`, `_SUB`, `_STR`, `_PLD`, and `_CLS` - can
+//! be evaluated and decoded with the `device_*` methods of [`AmlContext`].
+
+use crate::{value::Args, AmlContext, AmlError, AmlName, AmlValue, LevelType};
+use alloc::{string::String, vec::Vec};
+use bit_field::BitField;
+use core::convert::TryInto;
+
+/// Compress a PNP ID (e.g. `PNP0C09`) into the 32-bit EISA ID encoding. Returns `None` if `id` isn't a valid PNP
+/// ID.
+pub fn eisa_id(id: &str) -> Option<u32> {
+    let bytes = id.as_bytes();
+    if bytes.len() != 7 || !bytes[0..3].iter().all(u8::is_ascii_uppercase) {
+        return None;
+    }
+
+    let vendor = bytes[0..3].iter().fold(0u16, |vendor, &c| (vendor << 5) | (c - b'@') as u16);
+    let product = u16::from_str_radix(id.get(3..7)?, 16).ok()?;
+
+    // The vendor and product are stored big-endian
+    Some(u32::from_le_bytes([(vendor >> 8) as u8, vendor as u8, (product >> 8) as u8, product as u8]))
+}
+
+/// Decompress an ID in the 32-bit EISA ID encoding into its PNP ID string.
+pub fn eisa_id_to_string(id: u32) -> String {
+    let [vendor_high, vendor_low, product_high, product_low] = id.to_le_bytes();
+    let vendor = u16::from_be_bytes([vendor_high, vendor_low]);
+    let product = u16::from_be_bytes([product_high, product_low]);
+
+    let mut string = String::new();
+    for shift in [10, 5, 0] {
+        string.push((b'@' + ((vendor >> shift) & 0x1f) as u8) as char);
+    }
+    string.push_str(&alloc::format!("{:04X}", product));
+    string
+}
+
+/// Check if the value of a `_HID`, or of an entry of a `_CID`, matches `id`.
+pub fn id_matches(value: &AmlValue, id: &str) -> bool {
+    match value {
+        AmlValue::String(value) => value == id,
+        AmlValue::Integer(value) => eisa_id(id).map(u64::from) == Some(*value),
+        _ => false,
+    }
+}
+
+/// Describes the devices to find with [`AmlContext::find_devices`].
+#[derive(Clone, Copy, Debug)]
+pub enum DeviceMatcher<'a> {
+    /// Devices with a `_HID` of the given ACPI or PNP ID.
+    Hid(&'a str),
+    /// Devices with a `_HID` of the given ID, in the compressed EISA ID encoding.
+    EisaId(u32),
+    /// Devices with the given ID in their `_CID`, which can be a single ID or a package of them.
+    Cid(&'a str),
+    /// Devices with either a `_HID` of the given ID, or the given ID in their `_CID`.
+    HidOrCid(&'a str),
+    /// The device directly under `bridge` (e.g. a PCI root bridge) with an `_ADR` of `address`.
+    Address { bridge: &'a AmlName, address: u64 },
+}
+
+/// The unique ID (`_UID`) of a device, which distinguishes it from other devices with the same `_HID`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DeviceUid {
+    Integer(u64),
+    String(String),
+}
+
+/// A device found by [`AmlContext::find_devices`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeviceMatch {
+    pub path: AmlName,
+    /// The device's `_UID`, if it has one.
+    pub uid: Option<DeviceUid>,
+}
+
+/// The panel of the system's housing that a device is on, from its `_PLD`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PldPanel {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Front,
+    Back,
+    Unknown,
+}
+
+/// The vertical position of a device on its panel, from its `_PLD`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PldVerticalPosition {
+    Upper,
+    Center,
+    Lower,
+}
+
+/// The horizontal position of a device on its panel, from its `_PLD`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PldHorizontalPosition {
+    Left,
+    Center,
+    Right,
+}
+
+/// The shape of a device's connector, or of the device itself, from its `_PLD`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PldShape {
+    Round,
+    Oval,
+    Square,
+    VerticalRectangle,
+    HorizontalRectangle,
+    VerticalTrapezoid,
+    HorizontalTrapezoid,
+    Unknown,
+    Chamfered,
+    Reserved(u8),
+}
+
+/// The physical location of a device, decoded from the buffer returned by its `_PLD` (§6.1.8).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PhysicalLocation {
+    pub revision: u8,
+    /// The color of the device as `(red, green, blue)`, or `None` if the color should be ignored.
+    pub color: Option<(u8, u8, u8)>,
+    /// The width of the device, in millimeters.
+    pub width: u16,
+    /// The height of the device, in millimeters.
+    pub height: u16,
+    /// Whether the device can be seen by the user.
+    pub user_visible: bool,
+    /// Whether the device is on a docking station or port replicator.
+    pub dock: bool,
+    /// Whether the device is on the lid of a laptop.
+    pub lid: bool,
+    pub panel: PldPanel,
+    pub vertical_position: PldVerticalPosition,
+    pub horizontal_position: PldHorizontalPosition,
+    pub shape: PldShape,
+    /// Whether the devices of the group are ordered vertically (`true`) or horizontally (`false`).
+    pub group_orientation: bool,
+    /// Identifies the group the device is in. Devices in the same group have the same token.
+    pub group_token: u8,
+    /// The position of the device in its group.
+    pub group_position: u8,
+    /// Whether the device is in a bay.
+    pub bay: bool,
+    pub ejectable: bool,
+    /// Whether OSPM has to be involved in ejecting the device.
+    pub ospm_eject_required: bool,
+    pub cabinet_number: u8,
+    pub card_cage_number: u8,
+    /// Whether the device is a reference shape, which other devices are located relative to.
+    pub reference: bool,
+    /// The rotation of the device clockwise, in increments of 45 degrees.
+    pub rotation: u8,
+    /// The order in which the device is ejected, relative to other devices.
+    pub order: u8,
+    /// The vertical offset of the device from the origin of its panel, in millimeters. Only present in revision
+    /// `2` and later, and `None` if it isn't supplied.
+    pub vertical_offset: Option<u16>,
+    /// The horizontal offset of the device from the origin of its panel, in millimeters. Only present in revision
+    /// `2` and later, and `None` if it isn't supplied.
+    pub horizontal_offset: Option<u16>,
+}
+
+impl PhysicalLocation {
+    /// Decode the first buffer of a `_PLD`. Returns `None` if it's shorter than the 16 bytes of revision `1`, or
+    /// the 20 bytes of later revisions.
+    pub fn from_bytes(bytes: &[u8]) -> Option<PhysicalLocation> {
+        if bytes.len() < 16 {
+            return None;
+        }
+        let dword = |i: usize| u32::from_le_bytes(bytes[(i * 4)..(i * 4 + 4)].try_into().unwrap());
+        let [first, second, third, fourth] = [dword(0), dword(1), dword(2), dword(3)];
+
+        let revision = first.get_bits(0..7) as u8;
+        let (vertical_offset, horizontal_offset) = if revision >= 2 {
+            if bytes.len() < 20 {
+                return None;
+            }
+            let fifth = dword(4);
+            let offset = |offset: u16| if offset == 0xffff { None } else { Some(offset) };
+            (offset(fifth.get_bits(0..16) as u16), offset(fifth.get_bits(16..32) as u16))
+        } else {
+            (None, None)
+        };
+
+        Some(PhysicalLocation {
+            revision,
+            color: if first.get_bit(7) {
+                None
+            } else {
+                Some((first.get_bits(8..16) as u8, first.get_bits(16..24) as u8, first.get_bits(24..32) as u8))
+            },
+            width: second.get_bits(0..16) as u16,
+            height: second.get_bits(16..32) as u16,
+            user_visible: third.get_bit(0),
+            dock: third.get_bit(1),
+            lid: third.get_bit(2),
+            panel: match third.get_bits(3..6) {
+                0 => PldPanel::Top,
+                1 => PldPanel::Bottom,
+                2 => PldPanel::Left,
+                3 => PldPanel::Right,
+                4 => PldPanel::Front,
+                5 => PldPanel::Back,
+                _ => PldPanel::Unknown,
+            },
+            vertical_position: match third.get_bits(6..8) {
+                0 => PldVerticalPosition::Upper,
+                1 => PldVerticalPosition::Center,
+                _ => PldVerticalPosition::Lower,
+            },
+            horizontal_position: match third.get_bits(8..10) {
+                0 => PldHorizontalPosition::Left,
+                1 => PldHorizontalPosition::Center,
+                _ => PldHorizontalPosition::Right,
+            },
+            shape: match third.get_bits(10..14) as u8 {
+                0 => PldShape::Round,
+                1 => PldShape::Oval,
+                2 => PldShape::Square,
+                3 => PldShape::VerticalRectangle,
+                4 => PldShape::HorizontalRectangle,
+                5 => PldShape::VerticalTrapezoid,
+                6 => PldShape::HorizontalTrapezoid,
+                7 => PldShape::Unknown,
+                8 => PldShape::Chamfered,
+                shape => PldShape::Reserved(shape),
+            },
+            group_orientation: third.get_bit(14),
+            group_token: third.get_bits(15..23) as u8,
+            group_position: third.get_bits(23..31) as u8,
+            bay: third.get_bit(31),
+            ejectable: fourth.get_bit(0),
+            ospm_eject_required: fourth.get_bit(1),
+            cabinet_number: fourth.get_bits(2..10) as u8,
+            card_cage_number: fourth.get_bits(10..18) as u8,
+            reference: fourth.get_bit(18),
+            rotation: fourth.get_bits(19..23) as u8,
+            order: fourth.get_bits(23..28) as u8,
+            vertical_offset,
+            horizontal_offset,
+        })
+    }
+}
+
+/// The class of a device that's attached to a bus without a standard enumeration mechanism (e.g. a SATA
+/// controller that isn't on PCI), from its `_CLS`. The codes are the same as the PCI class codes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DeviceClass {
+    pub base_class: u8,
+    pub sub_class: u8,
+    pub programming_interface: u8,
+}
+
+impl AmlContext {
+    /// Find the devices in the namespace that match `matcher`, along with their `_UID`s. This is implemented on
+    /// the context, rather than the namespace, because `_HID`, `_CID`, `_ADR`, and `_UID` can be methods.
+    pub fn find_devices(&mut self, matcher: &DeviceMatcher) -> Result<Vec<DeviceMatch>, AmlError> {
+        let mut devices = Vec::new();
+        self.namespace.traverse(|path, level| match level.typ {
+            LevelType::Device => {
+                devices.push(path.clone());
+                Ok(true)
+            }
+            LevelType::Scope => Ok(true),
+            _ => Ok(false),
+        })?;
+
+        let mut matches = Vec::new();
+        for device in devices {
+            let is_match = match *matcher {
+                DeviceMatcher::Hid(id) => {
+                    self.evaluate_device_object(&device, "_HID")?.is_some_and(|hid| id_matches(&hid, id))
+                }
+                DeviceMatcher::EisaId(id) => match self.evaluate_device_object(&device, "_HID")? {
+                    Some(AmlValue::Integer(hid)) => hid == id as u64,
+                    Some(AmlValue::String(hid)) => hid == eisa_id_to_string(id),
+                    _ => false,
+                },
+                DeviceMatcher::Cid(id) => self.cid_matches(&device, id)?,
+                DeviceMatcher::HidOrCid(id) => {
+                    self.evaluate_device_object(&device, "_HID")?.is_some_and(|hid| id_matches(&hid, id))
+                        || self.cid_matches(&device, id)?
+                }
+                DeviceMatcher::Address { bridge, address } => {
+                    device.parent().as_ref() == Ok(bridge)
+                        && match self.evaluate_device_object(&device, "_ADR")? {
+                            Some(adr) => adr.as_integer(self)? == address,
+                            None => false,
+                        }
+                }
+            };
+
+            if is_match {
+                let uid = self.device_uid(&device)?;
+                matches.push(DeviceMatch { path: device, uid });
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Evaluate the `_UID` of `device`. Returns `None` if it doesn't have one.
+    pub fn device_uid(&mut self, device: &AmlName) -> Result<Option<DeviceUid>, AmlError> {
+        Ok(match self.evaluate_device_object(device, "_UID")? {
+            Some(AmlValue::String(uid)) => Some(DeviceUid::String(uid)),
+            Some(uid) => Some(DeviceUid::Integer(uid.as_integer(self)?)),
+            None => None,
+        })
+    }
+
+    /// Evaluate the `_SUB` of `device`, which is its subsystem ID as a string of the form `VVVVSSSS` (a vendor ID
+    /// and a subsystem ID). Returns `None` if it doesn't have one.
+    pub fn device_sub(&mut self, device: &AmlName) -> Result<Option<String>, AmlError> {
+        match self.evaluate_device_object(device, "_SUB")? {
+            Some(AmlValue::String(sub)) => Ok(Some(sub)),
+            Some(_) => Err(invalid_device_object(device, "_SUB")),
+            None => Ok(None),
+        }
+    }
+
+    /// Evaluate the `_STR` of `device`, which is a description of it for the user, and decode it from the
+    /// null-terminated UTF-16 buffer it's returned in. Returns `None` if it doesn't have one.
+    pub fn device_str(&mut self, device: &AmlName) -> Result<Option<String>, AmlError> {
+        let bytes = match self.evaluate_device_object(device, "_STR")? {
+            Some(AmlValue::Buffer(bytes)) => bytes,
+            Some(_) => return Err(invalid_device_object(device, "_STR")),
+            None => return Ok(None),
+        };
+
+        let bytes = bytes.lock();
+        let units =
+            bytes.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).take_while(|&unit| unit != 0);
+        Ok(Some(char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()))
+    }
+
+    /// Evaluate the `_PLD` of `device`, and decode the physical location it describes. Returns `None` if it
+    /// doesn't have one.
+    pub fn device_pld(&mut self, device: &AmlName) -> Result<Option<PhysicalLocation>, AmlError> {
+        match self.evaluate_device_object(device, "_PLD")? {
+            Some(AmlValue::Package(elements)) => match elements.first() {
+                Some(AmlValue::Buffer(bytes)) => PhysicalLocation::from_bytes(&bytes.lock())
+                    .map(Some)
+                    .ok_or_else(|| invalid_device_object(device, "_PLD")),
+                _ => Err(invalid_device_object(device, "_PLD")),
+            },
+            Some(_) => Err(invalid_device_object(device, "_PLD")),
+            None => Ok(None),
+        }
+    }
+
+    /// Evaluate the `_CLS` of `device`, which is a package of its base class, sub-class, and programming
+    /// interface. Returns `None` if it doesn't have one.
+    pub fn device_cls(&mut self, device: &AmlName) -> Result<Option<DeviceClass>, AmlError> {
+        let elements = match self.evaluate_device_object(device, "_CLS")? {
+            Some(AmlValue::Package(elements)) if elements.len() == 3 => elements,
+            Some(_) => return Err(invalid_device_object(device, "_CLS")),
+            None => return Ok(None),
+        };
+
+        let mut codes = [0u8; 3];
+        for (code, element) in codes.iter_mut().zip(elements.iter()) {
+            *code = element.as_integer(self)?.try_into().map_err(|_| invalid_device_object(device, "_CLS"))?;
+        }
+        Ok(Some(DeviceClass { base_class: codes[0], sub_class: codes[1], programming_interface: codes[2] }))
+    }
+
+    fn cid_matches(&mut self, device: &AmlName, id: &str) -> Result<bool, AmlError> {
+        Ok(match self.evaluate_device_object(device, "_CID")? {
+            Some(AmlValue::Package(cids)) => cids.iter().any(|cid| id_matches(cid, id)),
+            Some(cid) => id_matches(&cid, id),
+            None => false,
+        })
+    }
+
+    /// Evaluate the object `name` of `device`. Returns `None` if the object doesn't exist.
+    fn evaluate_device_object(&mut self, device: &AmlName, name: &str) -> Result<Option<AmlValue>, AmlError> {
+        match self.invoke_method(&AmlName::from_str(name).unwrap().resolve(device)?, Args::EMPTY) {
+            Ok(value) => Ok(Some(value)),
+            Err(AmlError::ValueDoesNotExist(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn invalid_device_object(device: &AmlName, name: &str) -> AmlError {
+    match AmlName::from_str(name).unwrap().resolve(device) {
+        Ok(path) => AmlError::InvalidDeviceObject(path),
+        Err(err) => err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_eisa_id() {
+        assert_eq!(eisa_id("PNP0C09"), Some(0x090cd041));
+        assert_eq!(eisa_id("PNP0c09"), Some(0x090cd041));
+        assert_eq!(eisa_id("ACPI0003"), None);
+        assert_eq!(eisa_id_to_string(0x090cd041), "PNP0C09");
+    }
+
+    #[test]
+    fn test_find_devices() {
+        let mut context = make_test_context();
+        let name = |name| AmlName::from_str(name).unwrap();
+
+        /*
+         * Device (\_SB.PCI0) {
+         *     Name (_HID, EisaId ("PNP0A08"))
+         *     Name (_CID, Package () { EisaId ("PNP0A03"), "PCI_ROOT" })
+         *     Device (GFX0) { Name (_ADR, 0x00020000) }
+         *     Device (ADP1) { Name (_HID, "ACPI0003") Name (_UID, "AC") }
+         * }
+         * Device (\_SB.PCI1) { Name (_HID, "PNP0A03") Name (_UID, 1) }
+         */
+        for device in ["\\_SB.PCI0", "\\_SB.PCI0.GFX0", "\\_SB.PCI0.ADP1", "\\_SB.PCI1"] {
+            context.namespace.add_level(name(device), LevelType::Device).unwrap();
+        }
+        let values = [
+            ("\\_SB.PCI0._HID", AmlValue::Integer(eisa_id("PNP0A08").unwrap() as u64)),
+            (
+                "\\_SB.PCI0._CID",
+                AmlValue::package(vec![
+                    AmlValue::Integer(eisa_id("PNP0A03").unwrap() as u64),
+                    AmlValue::String("PCI_ROOT".into()),
+                ]),
+            ),
+            ("\\_SB.PCI0.GFX0._ADR", AmlValue::Integer(0x00020000)),
+            ("\\_SB.PCI0.ADP1._HID", AmlValue::String("ACPI0003".into())),
+            ("\\_SB.PCI0.ADP1._UID", AmlValue::String("AC".into())),
+            ("\\_SB.PCI1._HID", AmlValue::String("PNP0A03".into())),
+            ("\\_SB.PCI1._UID", AmlValue::Integer(1)),
+        ];
+        for (path, value) in values {
+            context.namespace.add_value(name(path), value).unwrap();
+        }
+
+        let paths = |matches: Vec<DeviceMatch>| matches.into_iter().map(|device| device.path).collect::<Vec<_>>();
+        assert_eq!(paths(context.find_devices(&DeviceMatcher::Hid("PNP0A08")).unwrap()), vec![name("\\_SB.PCI0")]);
+        assert_eq!(
+            paths(context.find_devices(&DeviceMatcher::EisaId(eisa_id("PNP0A03").unwrap())).unwrap()),
+            vec![name("\\_SB.PCI1")]
+        );
+        assert_eq!(
+            paths(context.find_devices(&DeviceMatcher::Cid("PCI_ROOT")).unwrap()),
+            vec![name("\\_SB.PCI0")]
+        );
+        assert_eq!(
+            paths(context.find_devices(&DeviceMatcher::HidOrCid("PNP0A03")).unwrap()),
+            vec![name("\\_SB.PCI0"), name("\\_SB.PCI1")]
+        );
+        assert_eq!(
+            paths(
+                context
+                    .find_devices(&DeviceMatcher::Address { bridge: &name("\\_SB.PCI0"), address: 0x00020000 })
+                    .unwrap()
+            ),
+            vec![name("\\_SB.PCI0.GFX0")]
+        );
+
+        assert_eq!(
+            context.find_devices(&DeviceMatcher::Hid("ACPI0003")).unwrap(),
+            vec![DeviceMatch { path: name("\\_SB.PCI0.ADP1"), uid: Some(DeviceUid::String("AC".into())) }]
+        );
+        assert_eq!(
+            context.find_devices(&DeviceMatcher::Hid("PNP0A03")).unwrap(),
+            vec![DeviceMatch { path: name("\\_SB.PCI1"), uid: Some(DeviceUid::Integer(1)) }]
+        );
+    }
+
+    #[test]
+    fn test_device_objects() {
+        let mut context = make_test_context();
+        let name = |name| AmlName::from_str(name).unwrap();
+        let device = name("\\_SB.USB0");
+        context.namespace.add_level(device.clone(), LevelType::Device).unwrap();
+
+        let third: u32 =
+            1 | (1 << 2) | (4 << 3) | (1 << 6) | (2 << 8) | (1 << 10) | (5 << 15) | (3 << 23) | (1 << 31);
+        let fourth: u32 = 1 | (7 << 2) | (2 << 19) | (1 << 23);
+        let pld = [0x33221102u32, 0x0014000a, third, fourth, 0xffff0030]
+            .iter()
+            .flat_map(|dword| dword.to_le_bytes())
+            .collect::<Vec<_>>();
+        let str_buffer = "USB ¶".encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+        let values = [
+            ("\\_SB.USB0._UID", AmlValue::Integer(3)),
+            ("\\_SB.USB0._SUB", AmlValue::String("8086A0ED".into())),
+            ("\\_SB.USB0._STR", AmlValue::buffer(str_buffer)),
+            ("\\_SB.USB0._PLD", AmlValue::package(vec![AmlValue::buffer(pld)])),
+            (
+                "\\_SB.USB0._CLS",
+                AmlValue::package(vec![AmlValue::Integer(0x0c), AmlValue::Integer(0x03), AmlValue::Integer(0x30)]),
+            ),
+        ];
+        for (path, value) in values {
+            context.namespace.add_value(name(path), value).unwrap();
+        }
+
+        assert_eq!(context.device_uid(&device), Ok(Some(DeviceUid::Integer(3))));
+        assert_eq!(context.device_sub(&device), Ok(Some("8086A0ED".into())));
+        assert_eq!(context.device_str(&device), Ok(Some("USB ¶".into())));
+        assert_eq!(
+            context.device_cls(&device),
+            Ok(Some(DeviceClass { base_class: 0x0c, sub_class: 0x03, programming_interface: 0x30 }))
+        );
+        assert_eq!(
+            context.device_pld(&device),
+            Ok(Some(PhysicalLocation {
+                revision: 2,
+                color: Some((0x11, 0x22, 0x33)),
+                width: 10,
+                height: 20,
+                user_visible: true,
+                dock: false,
+                lid: true,
+                panel: PldPanel::Front,
+                vertical_position: PldVerticalPosition::Center,
+                horizontal_position: PldHorizontalPosition::Right,
+                shape: PldShape::Oval,
+                group_orientation: false,
+                group_token: 5,
+                group_position: 3,
+                bay: true,
+                ejectable: true,
+                ospm_eject_required: false,
+                cabinet_number: 7,
+                card_cage_number: 0,
+                reference: false,
+                rotation: 2,
+                order: 1,
+                vertical_offset: Some(0x30),
+                horizontal_offset: None,
+            }))
+        );
+
+        let other = name("\\_SB.USB1");
+        context.namespace.add_level(other.clone(), LevelType::Device).unwrap();
+        context.namespace.add_value(name("\\_SB.USB1._CLS"), AmlValue::Integer(0x0c)).unwrap();
+        context
+            .namespace
+            .add_value(name("\\_SB.USB1._PLD"), AmlValue::package(vec![AmlValue::buffer(vec![0; 8])]))
+            .unwrap();
+        assert_eq!(context.device_uid(&other), Ok(None));
+        assert_eq!(context.device_str(&other), Ok(None));
+        assert_eq!(context.device_cls(&other), Err(AmlError::InvalidDeviceObject(name("\\_SB.USB1._CLS"))));
+        assert_eq!(context.device_pld(&other), Err(AmlError::InvalidDeviceObject(name("\\_SB.USB1._PLD"))));
+    }
+}
@@ -0,0 +1,908 @@
+//! A disassembler that renders a stream of AML as ASL-like pseudocode. This doesn't need an `AmlContext`, and
+//! doesn't evaluate anything, so it can be used to look at AML that the interpreter fails to parse or execute.
+//!
+//! The names of objects are resolved to absolute paths where possible: declarations are resolved against the scope
+//! they appear in, and names that follow the namespace search rules are resolved to the object they find, if the
+//! object is declared in the stream. This also means the disassembler knows how many arguments methods declared in
+//! the stream (or with `External`) take, which is needed to know where each method invocation ends.
+//!
+//! Parts of the stream that can't be disassembled are marked with a comment, and skipped up to the end of the
+//! enclosing block.
+
+use crate::{
+    device::eisa_id_to_string,
+    name_object::NameSeg,
+    opcode,
+    pkg_length::PkgLength,
+    AmlError,
+    AmlName,
+    NameComponent,
+};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
+use bit_field::BitField;
+use core::fmt::Write;
+
+/// Disassemble a stream of AML (e.g. the contents of a DSDT or SSDT, without the table header).
+pub fn disassemble(stream: &[u8]) -> String {
+    let mut disassembler = Disassembler {
+        stream,
+        offset: 0,
+        output: String::new(),
+        indent: 0,
+        scope: AmlName::root(),
+        declared: BTreeSet::new(),
+        methods: BTreeMap::new(),
+    };
+
+    /*
+     * Methods can be invoked before they're declared, so first walk the stream once to find the declarations, and
+     * then again to produce the output.
+     */
+    disassembler.term_list(stream.len());
+    disassembler.offset = 0;
+    disassembler.output.clear();
+    disassembler.term_list(stream.len());
+    disassembler.output
+}
+
+/// The kinds of operands an opcode can take.
+#[derive(Clone, Copy)]
+enum Operand {
+    TermArg,
+    SuperName,
+    /// A `SuperName`, or a `NullName` if there is no target.
+    Target,
+    NameString,
+    Byte,
+    Word,
+    DWord,
+    /// One of the comparisons of a `Match`.
+    MatchOp,
+}
+
+struct Disassembler<'a> {
+    stream: &'a [u8],
+    offset: usize,
+    output: String,
+    indent: usize,
+    scope: AmlName,
+    /// The resolved paths of the objects declared in the stream.
+    declared: BTreeSet<AmlName>,
+    /// The argument counts of the methods declared in the stream, by their resolved paths.
+    methods: BTreeMap<AmlName, u8>,
+}
+
+impl<'a> Disassembler<'a> {
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn peek(&self, end: usize) -> Result<u8, AmlError> {
+        if self.offset < end {
+            Ok(self.stream[self.offset])
+        } else {
+            Err(AmlError::UnexpectedEndOfStream)
+        }
+    }
+
+    fn take(&mut self, end: usize) -> Result<u8, AmlError> {
+        let byte = self.peek(end)?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn take_n(&mut self, n: usize, end: usize) -> Result<&'a [u8], AmlError> {
+        if self.offset + n > end {
+            return Err(AmlError::UnexpectedEndOfStream);
+        }
+        let bytes = &self.stream[self.offset..(self.offset + n)];
+        self.offset += n;
+        Ok(bytes)
+    }
+
+    fn take_u16(&mut self, end: usize) -> Result<u16, AmlError> {
+        let bytes = self.take_n(2, end)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn take_u32(&mut self, end: usize) -> Result<u32, AmlError> {
+        let bytes = self.take_n(4, end)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn take_u64(&mut self, end: usize) -> Result<u64, AmlError> {
+        let bytes = self.take_n(8, end)?;
+        let mut value = [0; 8];
+        value.copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    /// Parse a `PkgLength`, returning the offset of the end of the structure it's the length of.
+    fn pkg_length(&mut self, end: usize) -> Result<usize, AmlError> {
+        let start = self.offset;
+        let lead_byte = self.take(end)?;
+        let byte_count = lead_byte.get_bits(6..8);
+
+        let length = if byte_count == 0 {
+            u32::from(lead_byte.get_bits(0..6))
+        } else {
+            let mut length = u32::from(lead_byte.get_bits(0..4));
+            for i in 0..byte_count {
+                length |= u32::from(self.take(end)?) << (4 + i * 8);
+            }
+            length
+        };
+
+        /*
+         * The length of the structure includes the bytes of the `PkgLength`, and has to fit in the enclosing one.
+         */
+        let pkg_length = PkgLength::from_raw_length(&self.stream[start..end], length)?;
+        Ok(end - pkg_length.end_offset as usize)
+    }
+
+    fn name_seg(&mut self, end: usize) -> Result<NameSeg, AmlError> {
+        let bytes = self.take_n(4, end)?;
+        let is_name_char = |byte: u8| byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'_';
+        if (bytes[0].is_ascii_digit()) || !bytes.iter().all(|&byte| is_name_char(byte)) {
+            return Err(AmlError::UnexpectedByte(bytes[0]));
+        }
+        Ok(NameSeg([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Parse a `NameString`. Returns `None` for a `NullName`.
+    fn name_string(&mut self, end: usize) -> Result<Option<AmlName>, AmlError> {
+        let mut components = Vec::new();
+        match self.peek(end)? {
+            opcode::ROOT_CHAR => {
+                self.offset += 1;
+                components.push(NameComponent::Root);
+            }
+            opcode::PREFIX_CHAR => {
+                while self.peek(end)? == opcode::PREFIX_CHAR {
+                    self.offset += 1;
+                    components.push(NameComponent::Prefix);
+                }
+            }
+            _ => (),
+        }
+
+        let segment_count = match self.peek(end)? {
+            opcode::NULL_NAME => {
+                self.offset += 1;
+                0
+            }
+            opcode::DUAL_NAME_PREFIX => {
+                self.offset += 1;
+                2
+            }
+            opcode::MULTI_NAME_PREFIX => {
+                self.offset += 1;
+                self.take(end)?
+            }
+            _ => 1,
+        };
+        for _ in 0..segment_count {
+            components.push(NameComponent::Segment(self.name_seg(end)?));
+        }
+
+        Ok(if components.is_empty() { None } else { Some(AmlName::from_components(components)) })
+    }
+
+    /// Parse the name of an object being declared, returning its resolved path.
+    fn declaration(&mut self, end: usize) -> Result<AmlName, AmlError> {
+        let name = self.name_string(end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+        let path = name.resolve(&self.scope)?;
+        self.declared.insert(path.clone());
+        Ok(path)
+    }
+
+    /// Resolve a reference to an object, following the search rules if they apply. Returns `None` if the object
+    /// isn't declared in the stream.
+    fn search(&self, name: &AmlName) -> Option<AmlName> {
+        if !name.search_rules_apply() {
+            return name.resolve(&self.scope).ok().filter(|path| self.declared.contains(path));
+        }
+
+        let mut scope = self.scope.clone();
+        loop {
+            let path = name.resolve(&scope).ok()?;
+            if self.declared.contains(&path) {
+                return Some(path);
+            }
+            scope = scope.parent().ok()?;
+        }
+    }
+
+    fn reference(&self, name: &AmlName) -> String {
+        match self.search(name) {
+            Some(path) => path.as_string(),
+            None => name.resolve(&self.scope).map(|path| path.as_string()).unwrap_or_else(|_| name.as_string()),
+        }
+    }
+
+    fn term_list(&mut self, end: usize) {
+        while self.offset < end {
+            let start = self.offset;
+            if let Err(err) = self.term(end) {
+                self.line(&format!(
+                    "// Failed to disassemble: {:?}. Skipped {} byte(s) at {:#x}.",
+                    err,
+                    end - start,
+                    start
+                ));
+                self.offset = end;
+            }
+        }
+    }
+
+    /// Parse a term of a term list, writing it to the output as a statement.
+    fn term(&mut self, end: usize) -> Result<(), AmlError> {
+        match self.peek(end)? {
+            opcode::DEF_SCOPE_OP => {
+                self.offset += 1;
+                self.scoped_block("Scope", end, |_, _| Ok(Vec::new()))
+            }
+            opcode::DEF_METHOD_OP => {
+                self.offset += 1;
+                let block_end = self.pkg_length(end)?;
+                let path = self.declaration(block_end)?;
+                let flags = self.take(block_end)?;
+                let arg_count = flags.get_bits(0..3);
+                self.methods.insert(path.clone(), arg_count);
+
+                let serialized = if flags.get_bit(3) { "Serialized" } else { "NotSerialized" };
+                let header = match flags.get_bits(4..8) {
+                    0 => format!("Method ({}, {}, {})", path, arg_count, serialized),
+                    sync_level => format!("Method ({}, {}, {}, {})", path, arg_count, serialized, sync_level),
+                };
+                self.block(&header, path, block_end);
+                Ok(())
+            }
+            opcode::DEF_IF_ELSE_OP | opcode::DEF_WHILE_OP => {
+                let name = if self.take(end)? == opcode::DEF_IF_ELSE_OP { "If" } else { "While" };
+                let block_end = self.pkg_length(end)?;
+                let predicate = self.term_arg(block_end)?;
+                let scope = self.scope.clone();
+                self.block(&format!("{} ({})", name, predicate), scope, block_end);
+                Ok(())
+            }
+            opcode::DEF_ELSE_OP => {
+                self.offset += 1;
+                let block_end = self.pkg_length(end)?;
+                let scope = self.scope.clone();
+                self.block("Else", scope, block_end);
+                Ok(())
+            }
+            opcode::EXT_OPCODE_PREFIX => {
+                let ext_opcode = *self.stream.get(self.offset + 1).ok_or(AmlError::UnexpectedEndOfStream)?;
+                match ext_opcode {
+                    opcode::EXT_DEF_DEVICE_OP => {
+                        self.offset += 2;
+                        self.scoped_block("Device", end, |_, _| Ok(Vec::new()))
+                    }
+                    opcode::EXT_DEF_THERMAL_ZONE_OP => {
+                        self.offset += 2;
+                        self.scoped_block("ThermalZone", end, |_, _| Ok(Vec::new()))
+                    }
+                    opcode::EXT_DEF_PROCESSOR_OP => {
+                        self.offset += 2;
+                        self.scoped_block("Processor", end, |disassembler, block_end| {
+                            Ok(alloc::vec![
+                                format!("0x{:02X}", disassembler.take(block_end)?),
+                                format!("0x{:08X}", disassembler.take_u32(block_end)?),
+                                format!("0x{:02X}", disassembler.take(block_end)?),
+                            ])
+                        })
+                    }
+                    opcode::EXT_DEF_POWER_RES_OP => {
+                        self.offset += 2;
+                        self.scoped_block("PowerResource", end, |disassembler, block_end| {
+                            Ok(alloc::vec![
+                                format!("0x{:02X}", disassembler.take(block_end)?),
+                                format!("0x{:04X}", disassembler.take_u16(block_end)?),
+                            ])
+                        })
+                    }
+                    opcode::EXT_DEF_FIELD_OP | opcode::EXT_DEF_INDEX_FIELD_OP | opcode::EXT_DEF_BANK_FIELD_OP => {
+                        self.offset += 2;
+                        self.field(ext_opcode, end)
+                    }
+                    _ => self.statement(end),
+                }
+            }
+            _ => self.statement(end),
+        }
+    }
+
+    fn statement(&mut self, end: usize) -> Result<(), AmlError> {
+        let statement = self.term_arg(end)?;
+        self.line(&statement);
+        Ok(())
+    }
+
+    /// Write a block, disassembling the term list up to `block_end` within it, in `scope`.
+    fn block(&mut self, header: &str, scope: AmlName, block_end: usize) {
+        let previous_scope = core::mem::replace(&mut self.scope, scope);
+        self.line(header);
+        self.line("{");
+        self.indent += 1;
+        self.term_list(block_end);
+        self.indent -= 1;
+        self.line("}");
+        self.scope = previous_scope;
+    }
+
+    /// Parse an object that opens a new scope, such as a `Scope` or `Device`. `extra_operands` parses any operands
+    /// between the object's name and its term list.
+    fn scoped_block<F>(&mut self, name: &str, end: usize, extra_operands: F) -> Result<(), AmlError>
+    where
+        F: FnOnce(&mut Self, usize) -> Result<Vec<String>, AmlError>,
+    {
+        let block_end = self.pkg_length(end)?;
+        let path = self.declaration(block_end)?;
+        let mut operands = alloc::vec![path.as_string()];
+        operands.extend(extra_operands(self, block_end)?);
+        self.block(&format!("{} ({})", name, operands.join(", ")), path, block_end);
+        Ok(())
+    }
+
+    fn field(&mut self, ext_opcode: u8, end: usize) -> Result<(), AmlError> {
+        let field_end = self.pkg_length(end)?;
+        let header = match ext_opcode {
+            opcode::EXT_DEF_FIELD_OP => {
+                let region = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                format!("Field ({}", self.reference(&region))
+            }
+            opcode::EXT_DEF_INDEX_FIELD_OP => {
+                let index = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                let data = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                format!("IndexField ({}, {}", self.reference(&index), self.reference(&data))
+            }
+            _ => {
+                let region = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                let bank = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                let bank_value = self.term_arg(field_end)?;
+                format!("BankField ({}, {}, {}", self.reference(&region), self.reference(&bank), bank_value)
+            }
+        };
+
+        let flags = self.take(field_end)?;
+        let update_rule = match flags.get_bits(5..7) {
+            0 => "Preserve",
+            1 => "WriteAsOnes",
+            _ => "WriteAsZeros",
+        };
+        let lock_rule = if flags.get_bit(4) { "Lock" } else { "NoLock" };
+        self.line(&format!("{}, {}, {}, {})", header, access_type(flags.get_bits(0..4)), lock_rule, update_rule));
+        self.line("{");
+        self.indent += 1;
+
+        while self.offset < field_end {
+            let element = match self.peek(field_end)? {
+                opcode::RESERVED_FIELD => {
+                    self.offset += 1;
+                    format!("Offset (+{})", self.field_length(field_end)?)
+                }
+                opcode::ACCESS_FIELD => {
+                    self.offset += 1;
+                    let access = self.take(field_end)?;
+                    let attrib = self.take(field_end)?;
+                    format!("AccessAs ({}, 0x{:02X})", access_type(access.get_bits(0..4)), attrib)
+                }
+                opcode::CONNECT_FIELD => {
+                    self.offset += 1;
+                    if self.peek(field_end)? == opcode::DEF_BUFFER_OP {
+                        format!("Connection ({})", self.term_arg(field_end)?)
+                    } else {
+                        let name = self.name_string(field_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                        format!("Connection ({})", self.reference(&name))
+                    }
+                }
+                opcode::EXTENDED_ACCESS_FIELD => {
+                    self.offset += 1;
+                    let access = self.take(field_end)?;
+                    let attrib = self.take(field_end)?;
+                    let length = self.take(field_end)?;
+                    format!(
+                        "AccessAs ({}, 0x{:02X}, 0x{:02X})",
+                        access_type(access.get_bits(0..4)),
+                        attrib,
+                        length
+                    )
+                }
+                _ => {
+                    let seg = self.name_seg(field_end)?;
+                    let path = AmlName::from_name_seg(seg).resolve(&self.scope)?;
+                    self.declared.insert(path.clone());
+                    format!("{}, {}", path, self.field_length(field_end)?)
+                }
+            };
+            self.line(&format!("{},", element));
+        }
+
+        self.indent -= 1;
+        self.line("}");
+        Ok(())
+    }
+
+    /// The length of a field element is encoded as a `PkgLength`, but isn't the length of anything in the stream.
+    fn field_length(&mut self, end: usize) -> Result<u32, AmlError> {
+        let lead_byte = self.take(end)?;
+        let byte_count = lead_byte.get_bits(6..8);
+        if byte_count == 0 {
+            return Ok(u32::from(lead_byte.get_bits(0..6)));
+        }
+
+        let mut length = u32::from(lead_byte.get_bits(0..4));
+        for i in 0..byte_count {
+            length |= u32::from(self.take(end)?) << (4 + i * 8);
+        }
+        Ok(length)
+    }
+
+    fn operands(&mut self, operands: &[Operand], end: usize) -> Result<String, AmlError> {
+        let mut rendered = Vec::new();
+        for operand in operands {
+            rendered.push(match operand {
+                Operand::TermArg => self.term_arg(end)?,
+                Operand::SuperName => self.super_name(end)?,
+                Operand::Target => {
+                    if self.peek(end)? == opcode::NULL_NAME {
+                        self.offset += 1;
+                        String::new()
+                    } else {
+                        self.super_name(end)?
+                    }
+                }
+                Operand::NameString => {
+                    let name = self.name_string(end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                    self.reference(&name)
+                }
+                Operand::Byte => format!("0x{:02X}", self.take(end)?),
+                Operand::Word => format!("0x{:04X}", self.take_u16(end)?),
+                Operand::DWord => format!("0x{:08X}", self.take_u32(end)?),
+                Operand::MatchOp => String::from(match self.take(end)? {
+                    0 => "MTR",
+                    1 => "MEQ",
+                    2 => "MLE",
+                    3 => "MLT",
+                    4 => "MGE",
+                    5 => "MGT",
+                    other => return Err(AmlError::UnexpectedByte(other)),
+                }),
+            });
+        }
+
+        // Targets that aren't stored to are left out, unless there's a target after them
+        while rendered.last().is_some_and(String::is_empty) {
+            rendered.pop();
+        }
+        Ok(rendered.join(", "))
+    }
+
+    fn operator(&mut self, name: &str, operands: &[Operand], end: usize) -> Result<String, AmlError> {
+        Ok(format!("{} ({})", name, self.operands(operands, end)?))
+    }
+
+    /// Parse a `SuperName` - which can be a name, a local or argument, `Debug`, or an expression that produces a
+    /// reference.
+    fn super_name(&mut self, end: usize) -> Result<String, AmlError> {
+        match self.peek(end)? {
+            byte if is_name_start(byte) => {
+                let name = self.name_string(end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                Ok(self.reference(&name))
+            }
+            _ => self.term_arg(end),
+        }
+    }
+
+    /// Parse a `TermArg`, or any other opcode that isn't a block, and render it as an expression.
+    fn term_arg(&mut self, end: usize) -> Result<String, AmlError> {
+        use Operand::*;
+
+        let opcode = self.peek(end)?;
+        if is_name_start(opcode) {
+            return self.name_or_invocation(end);
+        }
+        self.offset += 1;
+
+        match opcode {
+            opcode::ZERO_OP => Ok(String::from("Zero")),
+            opcode::ONE_OP => Ok(String::from("One")),
+            opcode::ONES_OP => Ok(String::from("Ones")),
+            opcode::BYTE_CONST => Ok(format!("0x{:02X}", self.take(end)?)),
+            opcode::WORD_CONST => Ok(format!("0x{:04X}", self.take_u16(end)?)),
+            opcode::DWORD_CONST => Ok(format!("0x{:08X}", self.take_u32(end)?)),
+            opcode::QWORD_CONST => Ok(format!("0x{:016X}", self.take_u64(end)?)),
+            opcode::STRING_PREFIX => {
+                let length = self.stream[self.offset..end].iter().position(|&byte| byte == 0x00);
+                let length = length.ok_or(AmlError::UnexpectedEndOfStream)?;
+                let string = self.take_n(length, end)?;
+                self.offset += 1;
+                Ok(quote_string(string))
+            }
+            opcode::LOCAL0_OP..=opcode::LOCAL7_OP => Ok(format!("Local{}", opcode - opcode::LOCAL0_OP)),
+            opcode::ARG0_OP..=opcode::ARG6_OP => Ok(format!("Arg{}", opcode - opcode::ARG0_OP)),
+
+            opcode::DEF_ALIAS_OP => {
+                let source = self.name_string(end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                let source = self.reference(&source);
+                let alias = self.declaration(end)?;
+                Ok(format!("Alias ({}, {})", source, alias))
+            }
+            opcode::DEF_NAME_OP => {
+                let path = self.declaration(end)?;
+                let is_id = path.as_string().ends_with("._HID") || path.as_string().ends_with("._CID");
+                let value = if is_id && self.peek(end)? == opcode::DWORD_CONST {
+                    self.offset += 1;
+                    let id = self.take_u32(end)?;
+                    let string = eisa_id_to_string(id);
+                    if crate::device::eisa_id(&string) == Some(id) {
+                        format!("EisaId (\"{}\")", string)
+                    } else {
+                        format!("0x{:08X}", id)
+                    }
+                } else {
+                    self.term_arg(end)?
+                };
+                Ok(format!("Name ({}, {})", path, value))
+            }
+            opcode::DEF_BUFFER_OP => {
+                let buffer_end = self.pkg_length(end)?;
+                let size = self.term_arg(buffer_end)?;
+                let bytes = self.take_n(buffer_end - self.offset, buffer_end)?;
+                let bytes = bytes.iter().map(|byte| format!("0x{:02X}", byte)).collect::<Vec<_>>();
+                Ok(format!("Buffer ({}) {{ {} }}", size, bytes.join(", ")))
+            }
+            opcode::DEF_PACKAGE_OP | opcode::DEF_VAR_PACKAGE_OP => {
+                let package_end = self.pkg_length(end)?;
+                let (name, size) = if opcode == opcode::DEF_PACKAGE_OP {
+                    ("Package", format!("0x{:02X}", self.take(package_end)?))
+                } else {
+                    ("VarPackage", self.term_arg(package_end)?)
+                };
+
+                let mut elements = Vec::new();
+                while self.offset < package_end {
+                    elements.push(if is_name_start(self.peek(package_end)?) {
+                        let name = self.name_string(package_end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+                        self.reference(&name)
+                    } else {
+                        self.term_arg(package_end)?
+                    });
+                }
+                Ok(format!("{} ({}) {{ {} }}", name, size, elements.join(", ")))
+            }
+            opcode::DEF_EXTERNAL_OP => {
+                let path = self.declaration(end)?;
+                let object_type = self.take(end)?;
+                let arg_count = self.take(end)?;
+                if object_type == 8 {
+                    self.methods.insert(path.clone(), arg_count);
+                    Ok(format!("External ({}, MethodObj, {})", path, arg_count))
+                } else {
+                    Ok(format!("External ({}, {})", path, object_type_name(object_type)))
+                }
+            }
+            opcode::DEF_CREATE_BIT_FIELD_OP
+            | opcode::DEF_CREATE_BYTE_FIELD_OP
+            | opcode::DEF_CREATE_WORD_FIELD_OP
+            | opcode::DEF_CREATE_DWORD_FIELD_OP
+            | opcode::DEF_CREATE_QWORD_FIELD_OP => {
+                let name = match opcode {
+                    opcode::DEF_CREATE_BIT_FIELD_OP => "CreateBitField",
+                    opcode::DEF_CREATE_BYTE_FIELD_OP => "CreateByteField",
+                    opcode::DEF_CREATE_WORD_FIELD_OP => "CreateWordField",
+                    opcode::DEF_CREATE_DWORD_FIELD_OP => "CreateDWordField",
+                    _ => "CreateQWordField",
+                };
+                let operands = self.operands(&[TermArg, TermArg], end)?;
+                let path = self.declaration(end)?;
+                Ok(format!("{} ({}, {})", name, operands, path))
+            }
+
+            opcode::DEF_CONTINUE_OP => Ok(String::from("Continue")),
+            opcode::DEF_NOOP_OP => Ok(String::from("Noop")),
+            opcode::DEF_BREAK_OP => Ok(String::from("Break")),
+            opcode::DEF_BREAKPOINT_OP => Ok(String::from("BreakPoint")),
+            opcode::DEF_RETURN_OP => self.operator("Return", &[TermArg], end),
+            opcode::DEF_NOTIFY_OP => self.operator("Notify", &[SuperName, TermArg], end),
+
+            opcode::DEF_STORE_OP => self.operator("Store", &[TermArg, SuperName], end),
+            opcode::DEF_REF_OF_OP => self.operator("RefOf", &[SuperName], end),
+            opcode::DEF_ADD_OP => self.operator("Add", &[TermArg, TermArg, Target], end),
+            opcode::DEF_CONCAT_OP => self.operator("Concatenate", &[TermArg, TermArg, Target], end),
+            opcode::DEF_SUBTRACT_OP => self.operator("Subtract", &[TermArg, TermArg, Target], end),
+            opcode::DEF_INCREMENT_OP => self.operator("Increment", &[SuperName], end),
+            opcode::DEF_DECREMENT_OP => self.operator("Decrement", &[SuperName], end),
+            opcode::DEF_MULTIPLY_OP => self.operator("Multiply", &[TermArg, TermArg, Target], end),
+            opcode::DEF_DIVIDE_OP => self.operator("Divide", &[TermArg, TermArg, Target, Target], end),
+            opcode::DEF_SHIFT_LEFT => self.operator("ShiftLeft", &[TermArg, TermArg, Target], end),
+            opcode::DEF_SHIFT_RIGHT => self.operator("ShiftRight", &[TermArg, TermArg, Target], end),
+            opcode::DEF_AND_OP => self.operator("And", &[TermArg, TermArg, Target], end),
+            opcode::DEF_NAND_OP => self.operator("NAnd", &[TermArg, TermArg, Target], end),
+            opcode::DEF_OR_OP => self.operator("Or", &[TermArg, TermArg, Target], end),
+            opcode::DEF_NOR_OP => self.operator("NOr", &[TermArg, TermArg, Target], end),
+            opcode::DEF_XOR_OP => self.operator("XOr", &[TermArg, TermArg, Target], end),
+            opcode::DEF_NOT_OP => self.operator("Not", &[TermArg, Target], end),
+            opcode::DEF_FIND_SET_LEFT_BIT_OP => self.operator("FindSetLeftBit", &[TermArg, Target], end),
+            opcode::DEF_FIND_SET_RIGHT_BIT_OP => self.operator("FindSetRightBit", &[TermArg, Target], end),
+            opcode::DEF_DEREF_OF_OP => self.operator("DerefOf", &[TermArg], end),
+            opcode::DEF_CONCAT_RES_OP => self.operator("ConcatenateResTemplate", &[TermArg, TermArg, Target], end),
+            opcode::DEF_MOD_OP => self.operator("Mod", &[TermArg, TermArg, Target], end),
+            opcode::DEF_SIZE_OF_OP => self.operator("SizeOf", &[SuperName], end),
+            opcode::DEF_INDEX_OP => self.operator("Index", &[TermArg, TermArg, Target], end),
+            opcode::DEF_MATCH_OP => {
+                self.operator("Match", &[TermArg, MatchOp, TermArg, MatchOp, TermArg, TermArg], end)
+            }
+            opcode::DEF_OBJECT_TYPE_OP => self.operator("ObjectType", &[SuperName], end),
+            opcode::DEF_L_AND_OP => self.operator("LAnd", &[TermArg, TermArg], end),
+            opcode::DEF_L_OR_OP => self.operator("LOr", &[TermArg, TermArg], end),
+            opcode::DEF_L_NOT_OP => match self.peek(end) {
+                Ok(opcode::DEF_L_EQUAL_OP) => {
+                    self.offset += 1;
+                    self.operator("LNotEqual", &[TermArg, TermArg], end)
+                }
+                Ok(opcode::DEF_L_GREATER_OP) => {
+                    self.offset += 1;
+                    self.operator("LLessEqual", &[TermArg, TermArg], end)
+                }
+                Ok(opcode::DEF_L_LESS_OP) => {
+                    self.offset += 1;
+                    self.operator("LGreaterEqual", &[TermArg, TermArg], end)
+                }
+                _ => self.operator("LNot", &[TermArg], end),
+            },
+            opcode::DEF_L_EQUAL_OP => self.operator("LEqual", &[TermArg, TermArg], end),
+            opcode::DEF_L_GREATER_OP => self.operator("LGreater", &[TermArg, TermArg], end),
+            opcode::DEF_L_LESS_OP => self.operator("LLess", &[TermArg, TermArg], end),
+            opcode::DEF_TO_BUFFER_OP => self.operator("ToBuffer", &[TermArg, Target], end),
+            opcode::DEF_TO_DECIMAL_STRING_OP => self.operator("ToDecimalString", &[TermArg, Target], end),
+            opcode::DEF_TO_HEX_STRING_OP => self.operator("ToHexString", &[TermArg, Target], end),
+            opcode::DEF_TO_INTEGER_OP => self.operator("ToInteger", &[TermArg, Target], end),
+            opcode::DEF_TO_STRING_OP => self.operator("ToString", &[TermArg, TermArg, Target], end),
+            opcode::DEF_COPY_OBJECT_OP => self.operator("CopyObject", &[TermArg, SuperName], end),
+            opcode::DEF_MID_OP => self.operator("Mid", &[TermArg, TermArg, TermArg, Target], end),
+
+            opcode::EXT_OPCODE_PREFIX => self.ext_term_arg(end),
+            other => Err(AmlError::UnexpectedByte(other)),
+        }
+    }
+
+    fn ext_term_arg(&mut self, end: usize) -> Result<String, AmlError> {
+        use Operand::*;
+
+        match self.take(end)? {
+            opcode::EXT_DEF_MUTEX_OP => {
+                let path = self.declaration(end)?;
+                Ok(format!("Mutex ({}, {})", path, self.take(end)?))
+            }
+            opcode::EXT_DEF_EVENT_OP => Ok(format!("Event ({})", self.declaration(end)?)),
+            opcode::EXT_DEF_COND_REF_OF_OP => self.operator("CondRefOf", &[SuperName, Target], end),
+            opcode::EXT_DEF_CREATE_FIELD_OP => {
+                let operands = self.operands(&[TermArg, TermArg, TermArg], end)?;
+                let path = self.declaration(end)?;
+                Ok(format!("CreateField ({}, {})", operands, path))
+            }
+            opcode::EXT_DEF_LOAD_TABLE_OP => {
+                self.operator("LoadTable", &[TermArg, TermArg, TermArg, TermArg, TermArg, TermArg], end)
+            }
+            opcode::EXT_DEF_LOAD_OP => self.operator("Load", &[NameString, Target], end),
+            opcode::EXT_DEF_STALL_OP => self.operator("Stall", &[TermArg], end),
+            opcode::EXT_DEF_SLEEP_OP => self.operator("Sleep", &[TermArg], end),
+            opcode::EXT_DEF_ACQUIRE_OP => self.operator("Acquire", &[SuperName, Word], end),
+            opcode::EXT_DEF_SIGNAL_OP => self.operator("Signal", &[SuperName], end),
+            opcode::EXT_DEF_WAIT_OP => self.operator("Wait", &[SuperName, TermArg], end),
+            opcode::EXT_DEF_RESET_OP => self.operator("Reset", &[SuperName], end),
+            opcode::EXT_DEF_RELEASE_OP => self.operator("Release", &[SuperName], end),
+            opcode::EXT_DEF_FROM_BCD_OP => self.operator("FromBCD", &[TermArg, Target], end),
+            opcode::EXT_DEF_TO_BCD_OP => self.operator("ToBCD", &[TermArg, Target], end),
+            opcode::EXT_DEF_UNLOAD_OP => self.operator("Unload", &[SuperName], end),
+            opcode::EXT_REVISION_OP => Ok(String::from("Revision")),
+            opcode::EXT_DEBUG_OP => Ok(String::from("Debug")),
+            opcode::EXT_DEF_FATAL_OP => self.operator("Fatal", &[Byte, DWord, TermArg], end),
+            opcode::EXT_DEF_TIMER_OP => Ok(String::from("Timer")),
+            opcode::EXT_DEF_OP_REGION_OP => {
+                let path = self.declaration(end)?;
+                let space = region_space_name(self.take(end)?);
+                let operands = self.operands(&[TermArg, TermArg], end)?;
+                Ok(format!("OperationRegion ({}, {}, {})", path, space, operands))
+            }
+            opcode::EXT_DEF_DATA_REGION_OP => {
+                let path = self.declaration(end)?;
+                let operands = self.operands(&[TermArg, TermArg, TermArg], end)?;
+                Ok(format!("DataTableRegion ({}, {})", path, operands))
+            }
+            other => Err(AmlError::UnexpectedByte(other)),
+        }
+    }
+
+    /// Parse a name in a `TermArg`, which is an invocation if it refers to a method.
+    fn name_or_invocation(&mut self, end: usize) -> Result<String, AmlError> {
+        let name = self.name_string(end)?.ok_or(AmlError::EmptyNamesAreInvalid)?;
+        let path = self.search(&name);
+        match path.as_ref().and_then(|path| self.methods.get(path)) {
+            Some(&arg_count) => {
+                let mut args = Vec::new();
+                for _ in 0..arg_count {
+                    args.push(self.term_arg(end)?);
+                }
+                Ok(format!("{} ({})", path.unwrap(), args.join(", ")))
+            }
+            None => Ok(self.reference(&name)),
+        }
+    }
+}
+
+fn is_name_start(byte: u8) -> bool {
+    byte.is_ascii_uppercase()
+        || byte == b'_'
+        || byte == opcode::ROOT_CHAR
+        || byte == opcode::PREFIX_CHAR
+        || byte == opcode::DUAL_NAME_PREFIX
+        || byte == opcode::MULTI_NAME_PREFIX
+}
+
+fn quote_string(string: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for &byte in string {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            0x20..=0x7e => quoted.push(byte as char),
+            _ => write!(quoted, "\\x{:02X}", byte).unwrap(),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn access_type(access_type: u8) -> String {
+    match access_type {
+        0 => String::from("AnyAcc"),
+        1 => String::from("ByteAcc"),
+        2 => String::from("WordAcc"),
+        3 => String::from("DWordAcc"),
+        4 => String::from("QWordAcc"),
+        5 => String::from("BufferAcc"),
+        other => format!("0x{:02X}", other),
+    }
+}
+
+fn region_space_name(space: u8) -> String {
+    match space {
+        0x00 => String::from("SystemMemory"),
+        0x01 => String::from("SystemIO"),
+        0x02 => String::from("PCI_Config"),
+        0x03 => String::from("EmbeddedControl"),
+        0x04 => String::from("SMBus"),
+        0x05 => String::from("SystemCMOS"),
+        0x06 => String::from("PciBarTarget"),
+        0x07 => String::from("IPMI"),
+        0x08 => String::from("GeneralPurposeIO"),
+        0x09 => String::from("GenericSerialBus"),
+        0x0a => String::from("PCC"),
+        other => format!("0x{:02X}", other),
+    }
+}
+
+fn object_type_name(object_type: u8) -> String {
+    let name = match object_type {
+        0 => "UnknownObj",
+        1 => "IntObj",
+        2 => "StrObj",
+        3 => "BuffObj",
+        4 => "PkgObj",
+        5 => "FieldUnitObj",
+        6 => "DeviceObj",
+        7 => "EventObj",
+        8 => "MethodObj",
+        9 => "MutexObj",
+        10 => "OpRegionObj",
+        11 => "PowerResObj",
+        12 => "ProcessorObj",
+        13 => "ThermalZoneObj",
+        14 => "BuffFieldObj",
+        15 => "DDBHandleObj",
+        other => return format!("0x{:02X}", other),
+    };
+    String::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        /*
+         * DefinitionBlock ("", "SSDT", 2, "", "", 1) {
+         *     Scope (\_SB) {
+         *         Device (PCI0) {
+         *             Name (_HID, EisaId ("PNP0A08"))
+         *             OperationRegion (GPIO, SystemIO, 0x80, 2)
+         *             Field (GPIO, ByteAcc, NoLock, Preserve) { GP0, 8, , 4, GP1, 4 }
+         *             Method (FOO, 1, Serialized) {
+         *                 If (LEqual (Arg0, One)) { Return (GP0) } Else { Add (Local0, 0x10, Local1) }
+         *                 Return (BAR ())
+         *             }
+         *         }
+         *     }
+         *     Method (BAR, 0) { \_SB.PCI0.FOO (Zero) Return ("Hi") }
+         *     Name (PKG, Package () { \_SB.PCI0.FOO, Zero, PKG })
+         * }
+         */
+        let stream = [
+            0x10, 0x45, 0x05, 0x5c, 0x5f, 0x53, 0x42, 0x5f, 0x5b, 0x82, 0x4c, 0x04, 0x50, 0x43, 0x49, 0x30, 0x08,
+            0x5f, 0x48, 0x49, 0x44, 0x0c, 0x41, 0xd0, 0x0a, 0x08, 0x5b, 0x80, 0x47, 0x50, 0x49, 0x4f, 0x01, 0x0a,
+            0x80, 0x0a, 0x02, 0x5b, 0x81, 0x12, 0x47, 0x50, 0x49, 0x4f, 0x01, 0x47, 0x50, 0x30, 0x5f, 0x08, 0x00,
+            0x04, 0x47, 0x50, 0x31, 0x5f, 0x04, 0x14, 0x1c, 0x46, 0x4f, 0x4f, 0x5f, 0x09, 0xa0, 0x09, 0x93, 0x68,
+            0x01, 0xa4, 0x47, 0x50, 0x30, 0x5f, 0xa1, 0x06, 0x72, 0x60, 0x0a, 0x10, 0x61, 0xa4, 0x42, 0x41, 0x52,
+            0x5f, 0x14, 0x1b, 0x42, 0x41, 0x52, 0x5f, 0x00, 0x5c, 0x2f, 0x03, 0x5f, 0x53, 0x42, 0x5f, 0x50, 0x43,
+            0x49, 0x30, 0x46, 0x4f, 0x4f, 0x5f, 0x00, 0xa4, 0x0d, 0x48, 0x69, 0x00, 0x08, 0x50, 0x4b, 0x47, 0x5f,
+            0x12, 0x16, 0x03, 0x5c, 0x2f, 0x03, 0x5f, 0x53, 0x42, 0x5f, 0x50, 0x43, 0x49, 0x30, 0x46, 0x4f, 0x4f,
+            0x5f, 0x00, 0x50, 0x4b, 0x47, 0x5f,
+        ];
+
+        assert_eq!(
+            disassemble(&stream),
+            "Scope (\\_SB_)
+{
+    Device (\\_SB_.PCI0)
+    {
+        Name (\\_SB_.PCI0._HID, EisaId (\"PNP0A08\"))
+        OperationRegion (\\_SB_.PCI0.GPIO, SystemIO, 0x80, 0x02)
+        Field (\\_SB_.PCI0.GPIO, ByteAcc, NoLock, Preserve)
+        {
+            \\_SB_.PCI0.GP0_, 8,
+            Offset (+4),
+            \\_SB_.PCI0.GP1_, 4,
+        }
+        Method (\\_SB_.PCI0.FOO_, 1, Serialized)
+        {
+            If (LEqual (Arg0, One))
+            {
+                Return (\\_SB_.PCI0.GP0_)
+            }
+            Else
+            {
+                Add (Local0, 0x10, Local1)
+            }
+            Return (\\BAR_ ())
+        }
+    }
+}
+Method (\\BAR_, 0, NotSerialized)
+{
+    \\_SB_.PCI0.FOO_ (Zero)
+    Return (\"Hi\")
+}
+Name (\\PKG_, Package (0x03) { \\_SB_.PCI0.FOO_, Zero, \\PKG_ })
+"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_invalid() {
+        // A `Scope` containing an unknown opcode, followed by a valid `Name`
+        let stream = [0x10, 0x07, 0x5c, 0x5f, 0x53, 0x42, 0x5f, 0xfe, 0x08, 0x41, 0x42, 0x43, 0x44, 0x01];
+        assert_eq!(
+            disassemble(&stream),
+            "Scope (\\_SB_)
+{
+    // Failed to disassemble: UnexpectedByte(254). Skipped 1 byte(s) at 0x7.
+}
+Name (\\ABCD, One)
+"
+        );
+    }
+}
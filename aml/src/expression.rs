@@ -1,20 +1,28 @@
 use crate::{
     misc::debug_obj,
-    name_object::{name_string, simple_name, super_name, target},
+    name_object::{name_string, simple_name, super_name, target, Target},
     opcode::{self, opcode},
-    parser::{choice, comment_scope, n_of, take, take_to_end_of_pkglength, try_with_context, Parser, Propagate},
+    parser::{
+        choice,
+        comment_scope,
+        id,
+        n_of,
+        take,
+        take_to_end_of_pkglength,
+        take_u16,
+        try_with_context,
+        Parser,
+        Propagate,
+    },
     pkg_length::pkg_length,
     term_object::{data_ref_object, term_arg, def_cond_ref_of},
-    value::{AmlType, AmlValue, Args},
+    value::{convert, AmlType, AmlValue, Args},
+    AmlContext,
     AmlError,
+    AmlName,
     DebugVerbosity,
 };
-use alloc::{
-    string::{String, ToString},
-    sync::Arc,
-    vec,
-    vec::Vec,
-};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
 use core::{cmp::Ordering, convert::TryInto, mem, ops::Deref};
 
 pub fn expression_opcode<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
@@ -36,13 +44,16 @@ where
         DebugVerbosity::AllScopes,
         "ExpressionOpcode",
         choice!(
+            def_acquire(),
             def_add(),
             def_and(),
             def_buffer(),
             def_concat(),
             def_concat_res(),
+            def_copy_object(),
             def_increment(),
             def_decrement(),
+            def_from_bcd(),
             def_l_equal(),
             def_l_greater(),
             def_l_greater_equal(),
@@ -51,19 +62,47 @@ where
             def_l_not_equal(),
             def_l_and(),
             def_l_or(),
+            def_load_table(),
+            def_match(),
             def_mid(),
             def_object_type(),
             def_package(),
             def_shift_left(),
             def_shift_right(),
             def_store(),
+            def_timer(),
+            def_to_bcd(),
             def_to_integer(),
+            def_wait(),
             def_cond_ref_of(),
             method_invocation() // XXX: this must always appear last. See how we have to parse it to see why.
         ),
     )
 }
 
+fn def_acquire<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefAcquire := ExtOpPrefix 0x23 MutexObject Timeout
+     * MutexObject := SuperName
+     * Timeout := WordData
+     *
+     * Evaluates to `True` if the acquire timed out.
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_ACQUIRE_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefAcquire",
+            super_name().then(take_u16()).map_with_context(|(mutex, timeout), context| {
+                let timed_out = try_with_context!(context, context.acquire_mutex(&mutex, timeout));
+                (Ok(AmlValue::Boolean(timed_out)), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
 pub fn def_add<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
@@ -263,6 +302,26 @@ where
         .map(|((), result)| Ok(result))
 }
 
+fn def_copy_object<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefCopyObject := 0x9d TermArg SimpleName
+     *
+     * Unlike `DefStore`, no implicit conversion is applied, and the destination takes on the type of the source.
+     */
+    opcode(opcode::DEF_COPY_OBJECT_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefCopyObject",
+            term_arg().then(simple_name()).map_with_context(|(value, target), context| {
+                (Ok(try_with_context!(context, context.copy_object(target, value))), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
 fn def_increment<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
@@ -307,6 +366,29 @@ where
         .map(|((), result)| Ok(result))
 }
 
+fn def_from_bcd<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefFromBCD := ExtOpPrefix 0x28 BCDValue Target
+     * BCDValue := TermArg => Integer
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_FROM_BCD_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefFromBCD",
+            term_arg().then(target()).map_with_context(|(value, target), context| {
+                let value = try_with_context!(context, value.as_integer(context));
+                let result = AmlValue::Integer(try_with_context!(context, convert::from_bcd(value)));
+
+                try_with_context!(context, context.store(target, result.clone()));
+                (Ok(result), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
 fn def_l_and<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
@@ -467,6 +549,177 @@ where
         .map(|(((), ()), result)| Ok(result))
 }
 
+fn def_load_table<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefLoadTable := ExtOpPrefix 0x1f SignatureString OemIdString OemTableIdString RootPathString
+     *                 ParameterPathString ParameterData
+     * SignatureString, OemIdString, OemTableIdString, RootPathString, ParameterPathString := TermArg => String
+     * ParameterData := TermArg => DataRefObject
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_LOAD_TABLE_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefLoadTable",
+            n_of(term_arg(), 6).map_with_context(|args, context| {
+                let mut strings = [String::new(), String::new(), String::new(), String::new(), String::new()];
+                for (string, arg) in strings.iter_mut().zip(args.iter()) {
+                    *string = try_with_context!(context, arg.as_string(context));
+                }
+                let [signature, oem_id, oem_table_id, root_path, parameter_path] = strings;
+
+                // If the table can't be found, `LoadTable` evaluates to `0` rather than to a handle
+                let table = match context.handler.find_table(&signature, &oem_id, &oem_table_id) {
+                    Some(table) => table,
+                    None => return (Ok(AmlValue::zero()), context),
+                };
+
+                let root = if root_path.is_empty() {
+                    AmlName::root()
+                } else {
+                    try_with_context!(
+                        context,
+                        AmlName::from_str(&root_path).and_then(|name| name.resolve(&context.current_scope))
+                    )
+                };
+                let handle = try_with_context!(context, context.load_table_at(&table, root.clone()));
+
+                if !parameter_path.is_empty() {
+                    let parameter = try_with_context!(
+                        context,
+                        AmlName::from_str(&parameter_path).and_then(|name| name.resolve(&root))
+                    );
+                    try_with_context!(context, context.store(Target::Name(parameter), args[5].clone()));
+                }
+
+                (Ok(AmlValue::DdbHandle(handle)), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
+fn def_wait<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefWait := ExtOpPrefix 0x25 EventObject Operand
+     * EventObject := SuperName
+     * Operand := TermArg => Integer
+     *
+     * Evaluates to `True` if the wait timed out. The timeout is in milliseconds, and values of `0xffff` or more
+     * wait forever.
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_WAIT_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefWait",
+            super_name().then(term_arg()).map_with_context(|(event, timeout), context| {
+                let timeout = try_with_context!(context, timeout.as_integer(context));
+                let timeout = if timeout >= crate::sync::WAIT_FOREVER as u64 {
+                    crate::sync::WAIT_FOREVER
+                } else {
+                    timeout as u16
+                };
+                let timed_out = try_with_context!(context, context.wait_event(&event, timeout));
+                (Ok(AmlValue::Boolean(timed_out)), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
+fn def_match<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefMatch := 0x89 SearchPkg MatchOpcode Operand MatchOpcode Operand StartIndex
+     * SearchPkg := TermArg => Package
+     * MatchOpcode := ByteData
+     * Operand := TermArg => ComputationalData
+     * StartIndex := TermArg => Integer
+     *
+     * Evaluates to the index of the first element of the package, starting at `StartIndex`, that satisfies both
+     * comparisons, or `Ones` if no element does.
+     */
+    opcode(opcode::DEF_MATCH_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefMatch",
+            term_arg()
+                .then(take())
+                .then(term_arg())
+                .then(take())
+                .then(term_arg())
+                .then(term_arg())
+                .map_with_context(|(((((package, op1), operand1), op2), operand2), start_index), context| {
+                    let start_index = try_with_context!(context, start_index.as_integer(context));
+                    let result = try_with_context!(
+                        context,
+                        match_package(&package, [(op1, operand1), (op2, operand2)], start_index, context)
+                    );
+                    (Ok(result), context)
+                }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
+fn match_package(
+    package: &AmlValue,
+    comparisons: [(u8, AmlValue); 2],
+    start_index: u64,
+    context: &mut AmlContext,
+) -> Result<AmlValue, AmlError> {
+    /*
+     * The match opcodes are:
+     *    0 = MTR (always true)
+     *    1 = MEQ (element == operand)
+     *    2 = MLE (element <= operand)
+     *    3 = MLT (element < operand)
+     *    4 = MGE (element >= operand)
+     *    5 = MGT (element > operand)
+     */
+    if let Some(&(op, _)) = comparisons.iter().find(|(op, _)| *op > 5) {
+        return Err(AmlError::InvalidMatchOpcode(op));
+    }
+
+    let elements = package.as_package()?;
+    if start_index >= elements.len() as u64 {
+        return Err(AmlError::MatchStartIndexOutOfBounds);
+    }
+
+    for (index, element) in elements.iter().enumerate().skip(start_index as usize) {
+        let is_match = comparisons.iter().all(|(op, operand)| {
+            if *op == 0 {
+                return true;
+            }
+
+            /*
+             * The operand is converted to the type of the element. Elements that can't be compared (e.g. nested
+             * packages) never match.
+             */
+            match element.cmp(operand.clone(), context) {
+                Ok(ordering) => match op {
+                    1 => ordering == Ordering::Equal,
+                    2 => ordering != Ordering::Greater,
+                    3 => ordering == Ordering::Less,
+                    4 => ordering != Ordering::Less,
+                    _ => ordering == Ordering::Greater,
+                },
+                Err(_) => false,
+            }
+        });
+
+        if is_match {
+            return Ok(AmlValue::Integer(index as u64));
+        }
+    }
+
+    Ok(AmlValue::ones())
+}
+
 fn def_mid<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
@@ -491,7 +744,7 @@ where
                                 let foo = bytes.lock();
                                 if index >= foo.len() {
                                     Ok(AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(vec![]))))
-                                } else if (index + length) >= foo.len() {
+                                } else if index.saturating_add(length) >= foo.len() {
                                     Ok(AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(
                                         foo[index..].to_vec(),
                                     ))))
@@ -506,13 +759,15 @@ where
                              * this bytewise, to hopefully match other implementations.
                              */
                             AmlValue::String(string) => {
-                                if index >= string.len() {
-                                    Ok(AmlValue::String(String::new()))
-                                } else if (index + length) >= string.len() {
-                                    Ok(AmlValue::String(string[index..].to_string()))
+                                let bytes = string.as_bytes();
+                                let bytes = if index >= bytes.len() {
+                                    &[]
+                                } else if index.saturating_add(length) >= bytes.len() {
+                                    &bytes[index..]
                                 } else {
-                                    Ok(AmlValue::String(string[index..(index + length)].to_string()))
-                                }
+                                    &bytes[index..(index + length)]
+                                };
+                                Ok(AmlValue::String(String::from_utf8_lossy(bytes).into_owned()))
                             }
                             _ => Err(AmlError::TypeCannotBeSliced(source.type_of())),
                         }
@@ -552,19 +807,26 @@ where
      *    12 = Processor
      *    13 = Thermal Zone
      *    14 = Buffer Field
-     *    15 = Reserved
+     *    15 = DDB Handle
      *    16 = Debug Object
      *    >16 = *Reserved*
      */
-    // TODO: this doesn't correctly handle taking the type of a namespace node (e.g. `\_SB`), but I'm not sure any
-    // other implementations do either?
     opcode(opcode::DEF_OBJECT_TYPE_OP)
         .then(comment_scope(
             DebugVerbosity::AllScopes,
             "DefObjectType",
             choice!(
                 simple_name().map_with_context(|target, context| {
-                    let value = try_with_context!(context, context.read_target(&target));
+                    let value = match (context.read_target(&target), &target) {
+                        (Ok(value), _) => value,
+                        // Levels of the namespace without a value (e.g. `\_SB`) are uninitialized objects
+                        (Err(AmlError::ValueDoesNotExist(_)), Target::Name(name))
+                            if context.namespace.search_for_level(name, &context.current_scope).is_ok() =>
+                        {
+                            return (Ok(AmlValue::Integer(0)), context);
+                        }
+                        (Err(err), _) => return (Err(Propagate::Err(err)), context),
+                    };
                     let typ = match value.type_of() {
                         AmlType::Uninitialized => 0,
                         AmlType::Integer => 1,
@@ -582,10 +844,9 @@ where
                         AmlType::Processor => 12,
                         AmlType::ThermalZone => 13,
                         AmlType::BufferField => 14,
+                        AmlType::DdbHandle => 15,
                         AmlType::DebugObject => 16,
 
-                        // TODO: what to do with this?
-                        AmlType::DdbHandle => 0,
                         AmlType::ObjReference => todo!(),
                     };
 
@@ -627,7 +888,7 @@ where
                         return Err((input, context, Propagate::Err(AmlError::MalformedPackage)));
                     }
 
-                    Ok((input, context, AmlValue::Package(package_contents)))
+                    Ok((input, context, AmlValue::package(package_contents)))
                 }
             }),
         ))
@@ -715,6 +976,49 @@ where
         })
 }
 
+fn def_timer<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefTimer := ExtOpPrefix 0x33
+     *
+     * Evaluates to a monotonically increasing count of 100 nanosecond ticks.
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_TIMER_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefTimer",
+            id().map_with_context(|(), context| {
+                (Ok(AmlValue::Integer(context.handler.nanos_since_boot() / 100)), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
+fn def_to_bcd<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
+where
+    'c: 'a,
+{
+    /*
+     * DefToBCD := ExtOpPrefix 0x29 Operand Target
+     * Operand := TermArg => Integer
+     */
+    opcode::ext_opcode(opcode::EXT_DEF_TO_BCD_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefToBCD",
+            term_arg().then(target()).map_with_context(|(value, target), context| {
+                let value = try_with_context!(context, value.as_integer(context));
+                let result = AmlValue::Integer(try_with_context!(context, convert::to_bcd(value)));
+
+                try_with_context!(context, context.store(target, result.clone()));
+                (Ok(result), context)
+            }),
+        ))
+        .map(|((), result)| Ok(result))
+}
+
 fn def_to_integer<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
@@ -805,3 +1109,128 @@ where
             }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_rare_opcodes() {
+        let mut context = make_test_context();
+
+        /*
+         * Name (TOLS, Package () { 0x10, 0x20, 0x30, 0x40 })
+         * Name (NAMS, Package () { "AAA", "STR" })
+         * Method (FIND, 1) { Return (Match (TOLS, MGE, Arg0, MTR, Zero, Zero)) }
+         * Method (FNDS) { Return (Match (NAMS, MEQ, "STR", MTR, Zero, Zero)) }
+         * Method (OOB) { Return (Match (TOLS, MTR, Zero, MTR, Zero, 4)) }
+         * Method (CRES) {
+         *     Return (ConcatenateResTemplate (
+         *         ResourceTemplate () { IO (Decode16, 0x60, 0x60, 0x01, 0x01) },
+         *         ResourceTemplate () { IRQNoFlags () { 1 } }))
+         * }
+         * Name (OBJ, 5)
+         * Method (COPY) { CopyObject ("Hello", OBJ) Return (ObjectType (OBJ)) }
+         * Method (TYSB) { Return (ObjectType (\_SB)) }
+         * Method (MID) { Return (Mid ("HelloWorld", 5, Ones)) }
+         */
+        context
+            .parse_table(&[
+                0x08, 0x54, 0x4f, 0x4c, 0x53, 0x12, 0x0a, 0x04, 0x0a, 0x10, 0x0a, 0x20, 0x0a, 0x30, 0x0a, 0x40,
+                0x08, 0x4e, 0x41, 0x4d, 0x53, 0x12, 0x0c, 0x02, 0x0d, 0x41, 0x41, 0x41, 0x00, 0x0d, 0x53, 0x54,
+                0x52, 0x00, 0x14, 0x12, 0x46, 0x49, 0x4e, 0x44, 0x01, 0xa4, 0x89, 0x54, 0x4f, 0x4c, 0x53, 0x04,
+                0x68, 0x00, 0x00, 0x00, 0x00, 0x14, 0x15, 0x46, 0x4e, 0x44, 0x53, 0x00, 0xa4, 0x89, 0x4e, 0x41,
+                0x4d, 0x53, 0x01, 0x0d, 0x53, 0x54, 0x52, 0x00, 0x00, 0x00, 0x00, 0x14, 0x12, 0x4f, 0x4f, 0x42,
+                0x5f, 0x00, 0xa4, 0x89, 0x54, 0x4f, 0x4c, 0x53, 0x00, 0x00, 0x00, 0x00, 0x0a, 0x04, 0x14, 0x20,
+                0x43, 0x52, 0x45, 0x53, 0x00, 0xa4, 0x84, 0x11, 0x0d, 0x0a, 0x0a, 0x47, 0x01, 0x60, 0x00, 0x60,
+                0x00, 0x01, 0x01, 0x79, 0x00, 0x11, 0x08, 0x0a, 0x05, 0x22, 0x02, 0x00, 0x79, 0x00, 0x00, 0x08,
+                0x4f, 0x42, 0x4a, 0x5f, 0x0a, 0x05, 0x14, 0x18, 0x43, 0x4f, 0x50, 0x59, 0x00, 0x9d, 0x0d, 0x48,
+                0x65, 0x6c, 0x6c, 0x6f, 0x00, 0x4f, 0x42, 0x4a, 0x5f, 0xa4, 0x8e, 0x4f, 0x42, 0x4a, 0x5f, 0x14,
+                0x0d, 0x54, 0x59, 0x53, 0x42, 0x00, 0xa4, 0x8e, 0x5c, 0x5f, 0x53, 0x42, 0x5f, 0x14, 0x18, 0x4d,
+                0x49, 0x44, 0x5f, 0x00, 0xa4, 0x9e, 0x0d, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x57, 0x6f, 0x72, 0x6c,
+                0x64, 0x00, 0x0a, 0x05, 0xff, 0x00,
+            ])
+            .unwrap();
+
+        let invoke = |context: &mut AmlContext, name: &str, args: Vec<AmlValue>| {
+            context.invoke_method(&AmlName::from_str(name).unwrap(), Args::from_list(args).unwrap())
+        };
+        assert!(matches!(invoke(&mut context, "\\FIND", vec![AmlValue::Integer(0x18)]), Ok(AmlValue::Integer(1))));
+        assert!(matches!(
+            invoke(&mut context, "\\FIND", vec![AmlValue::Integer(0x41)]),
+            Ok(AmlValue::Integer(u64::MAX))
+        ));
+        assert!(matches!(invoke(&mut context, "\\FNDS", vec![]), Ok(AmlValue::Integer(1))));
+        assert_eq!(invoke(&mut context, "\\OOB", vec![]).err(), Some(AmlError::MatchStartIndexOutOfBounds));
+
+        let resources = invoke(&mut context, "\\CRES", vec![]).unwrap().as_buffer(&context).unwrap();
+        assert_eq!(
+            *resources.lock(),
+            vec![0x47, 0x01, 0x60, 0x00, 0x60, 0x00, 0x01, 0x01, 0x22, 0x02, 0x00, 0x79, 0x59]
+        );
+
+        assert!(matches!(invoke(&mut context, "\\COPY", vec![]), Ok(AmlValue::Integer(2))));
+        assert!(matches!(
+            context.namespace.get_by_path(&AmlName::from_str("\\OBJ").unwrap()),
+            Ok(AmlValue::String(ref string)) if string == "Hello"
+        ));
+        assert!(matches!(invoke(&mut context, "\\TYSB", vec![]), Ok(AmlValue::Integer(0))));
+        assert!(
+            matches!(invoke(&mut context, "\\MID", vec![]), Ok(AmlValue::String(ref string)) if string == "World")
+        );
+    }
+
+    #[test]
+    fn test_timing_opcodes() {
+        let mut context = make_test_context();
+
+        /*
+         * Method (TIME) {
+         *     Sleep (5)
+         *     Stall (50)
+         *     Return (Timer)
+         * }
+         * Method (STL2) { Stall (0x100) }
+         */
+        context
+            .parse_table(&[
+                0x14, 0x11, b'T', b'I', b'M', b'E', 0x00, 0x5b, 0x22, 0x0a, 0x05, 0x5b, 0x21, 0x0a, 0x32, 0xa4,
+                0x5b, 0x33, 0x14, 0x0b, b'S', b'T', b'L', b'2', 0x00, 0x5b, 0x21, 0x0b, 0x00, 0x01,
+            ])
+            .unwrap();
+
+        // The test handler's clock starts at `0`, and only moves when the AML sleeps or stalls
+        let invoke = |context: &mut AmlContext, name: &str| {
+            context.invoke_method(&AmlName::from_str(name).unwrap(), Args::EMPTY)
+        };
+        assert!(matches!(invoke(&mut context, "\\TIME"), Ok(AmlValue::Integer(50500))));
+        assert_eq!(invoke(&mut context, "\\STL2").err(), Some(AmlError::InvalidStallTime(0x100)));
+    }
+
+    #[test]
+    fn test_bcd_opcodes() {
+        let mut context = make_test_context();
+
+        /*
+         * Method (TBCD, 1) { Return (ToBCD (Arg0)) }
+         * Method (FBCD, 1) { Return (FromBCD (Arg0)) }
+         */
+        context
+            .parse_table(&[
+                0x14, 0x0b, b'T', b'B', b'C', b'D', 0x01, 0xa4, 0x5b, 0x29, 0x68, 0x00, 0x14, 0x0b, b'F', b'B',
+                b'C', b'D', 0x01, 0xa4, 0x5b, 0x28, 0x68, 0x00,
+            ])
+            .unwrap();
+
+        let mut invoke = |name: &str, arg: u64| {
+            context.invoke_method(
+                &AmlName::from_str(name).unwrap(),
+                Args::from_list(vec![AmlValue::Integer(arg)]).unwrap(),
+            )
+        };
+        assert!(matches!(invoke("\\TBCD", 1999), Ok(AmlValue::Integer(0x1999))));
+        assert!(matches!(invoke("\\FBCD", 0x1999), Ok(AmlValue::Integer(1999))));
+        assert_eq!(invoke("\\FBCD", 0x19a9).err(), Some(AmlError::InvalidBcdValue(0x19a9)));
+    }
+}
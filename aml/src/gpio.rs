@@ -0,0 +1,112 @@
+//! Accesses to fields of `GeneralPurposeIo` operation regions, which read and write the pins of a GPIO
+//! controller. Each field is connected to a `GpioIo` (or `GpioInt`) resource by a `Connection` in its field
+//! list, and its bits are the pins in that resource's pin list, starting at the pin whose index is the field's
+//! offset from the connection. The transfers themselves are performed by a [`GpioHandler`], installed with
+//! [`AmlContext::install_gpio_handler`].
+
+use crate::{value::FieldConnection, AmlContext, AmlError, RegionSpace};
+use alloc::{boxed::Box, sync::Arc};
+
+/// Handles reading and writing the pins of GPIO controllers, for AML that accesses `GeneralPurposeIo` regions.
+pub trait GpioHandler: Send + Sync {
+    /// Read `length` pins of the GPIO connection described by `connection` (a resource template containing a
+    /// `GpioIo` or `GpioInt` descriptor), starting at index `pin` of its pin list. The first pin is returned in
+    /// bit 0.
+    fn read(&self, connection: &[u8], pin: u64, length: u64) -> Result<u64, AmlError>;
+    /// Write the low `length` bits of `value` to the pins of `connection`, starting at index `pin` of its pin
+    /// list.
+    fn write(&self, connection: &[u8], pin: u64, length: u64, value: u64) -> Result<(), AmlError>;
+}
+
+impl AmlContext {
+    /// Install the handler used to access the pins of `GeneralPurposeIo` fields, returning the previous one.
+    pub fn install_gpio_handler(&mut self, handler: Box<dyn GpioHandler>) -> Option<Arc<dyn GpioHandler>> {
+        let previous = self.shared.gpio_handler.lock().replace(Arc::from(handler));
+        self.update_region_space(RegionSpace::GeneralPurposeIo);
+        previous
+    }
+
+    pub fn remove_gpio_handler(&mut self) -> Option<Arc<dyn GpioHandler>> {
+        let previous = self.shared.gpio_handler.lock().take();
+        self.update_region_space(RegionSpace::GeneralPurposeIo);
+        previous
+    }
+
+    pub(crate) fn read_gpio_field(
+        &self,
+        connection: Option<&FieldConnection>,
+        offset: u64,
+        length: u64,
+    ) -> Result<u64, AmlError> {
+        let connection = connection.ok_or(AmlError::FieldNotConnected)?;
+        let handler = self.gpio_handler()?;
+        handler.read(&connection.resource, offset - connection.offset, length)
+    }
+
+    pub(crate) fn write_gpio_field(
+        &self,
+        connection: Option<&FieldConnection>,
+        offset: u64,
+        length: u64,
+        value: u64,
+    ) -> Result<(), AmlError> {
+        let connection = connection.ok_or(AmlError::FieldNotConnected)?;
+        let handler = self.gpio_handler()?;
+        handler.write(&connection.resource, offset - connection.offset, length, value)
+    }
+
+    fn gpio_handler(&self) -> Result<Arc<dyn GpioHandler>, AmlError> {
+        self.shared.gpio_handler.lock().clone().ok_or(AmlError::NoRegionHandler(RegionSpace::GeneralPurposeIo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, AmlName, AmlValue};
+    use alloc::vec::Vec;
+    use spinning_top::Spinlock;
+
+    struct TestGpioHandler(Spinlock<Vec<(u64, u64, u64)>>);
+
+    impl GpioHandler for TestGpioHandler {
+        fn read(&self, connection: &[u8], pin: u64, length: u64) -> Result<u64, AmlError> {
+            assert_eq!(connection, &[0x01, 0x02]);
+            Ok(pin * 0x10 + length)
+        }
+
+        fn write(&self, _connection: &[u8], pin: u64, length: u64, value: u64) -> Result<(), AmlError> {
+            self.0.lock().push((pin, length, value));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_gpio_field() {
+        let mut context = make_test_context();
+        context.install_gpio_handler(Box::new(TestGpioHandler(Spinlock::new(Vec::new()))));
+
+        /*
+         * Name (GPC0, Buffer () { 0x01, 0x02 })
+         * OperationRegion (GPO0, GeneralPurposeIo, 0, 1)
+         * Field (GPO0, ByteAcc, NoLock, Preserve) {
+         *     Connection (GPC0),
+         *     PIN0, 1,
+         *     PIN1, 2
+         * }
+         */
+        context
+            .parse_table(&[
+                0x08, b'G', b'P', b'C', b'0', 0x11, 0x05, 0x0a, 0x02, 0x01, 0x02, 0x5b, 0x80, b'G', b'P', b'O',
+                b'0', 0x08, 0x00, 0x01, 0x5b, 0x81, 0x15, b'G', b'P', b'O', b'0', 0x01, 0x02, b'G', b'P', b'C',
+                b'0', b'P', b'I', b'N', b'0', 0x01, b'P', b'I', b'N', b'1', 0x02,
+            ])
+            .unwrap();
+
+        let pin1 = context.namespace.get_by_path(&AmlName::from_str("\\PIN1").unwrap()).unwrap().clone();
+        assert!(matches!(pin1.read_field(&context), Ok(AmlValue::Integer(0x12))));
+
+        let mut pin0 = context.namespace.get_by_path(&AmlName::from_str("\\PIN0").unwrap()).unwrap().clone();
+        pin0.write_field(AmlValue::Integer(1), &mut context).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+//! Accesses to fields of `IPMI` operation regions, which send requests to the system's Baseboard Management
+//! Controller (BMC). The offset of a field (in bytes) selects the request: its high byte is the network function,
+//! and its low byte is the command.
+//!
+//! Like serial bus fields, IPMI fields are accessed with buffers. Writing a buffer of the form
+//! `{ Status, Length, Data... }` to a field sends a request containing `Data`, and evaluates to a buffer of the
+//! same form containing the response (which starts with the completion code). Reading a field sends a request with
+//! no data. These buffers are always 66 bytes long, with up to 64 bytes of data. The requests themselves are sent
+//! by an [`IpmiHandler`], installed with [`AmlContext::install_ipmi_handler`].
+
+use crate::{AmlContext, AmlError, AmlValue, RegionSpace};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use bit_field::BitField;
+
+/// The length of the buffers used to access IPMI fields.
+pub const IPMI_BUFFER_LENGTH: usize = 66;
+/// The maximum length of the data in a request or response.
+pub const IPMI_MAX_DATA_LENGTH: usize = IPMI_BUFFER_LENGTH - 2;
+
+/// Handles transactions with the BMC, for AML that accesses `IPMI` regions.
+pub trait IpmiHandler: Send + Sync {
+    /// Send a request with network function `network_function` and command `command`, containing `data`, to the
+    /// BMC. Returns the data of the response, starting with its completion code. If the request couldn't be sent,
+    /// this returns a non-zero status, which is passed to the AML.
+    fn transact(&self, network_function: u8, command: u8, data: &[u8]) -> Result<Vec<u8>, u8>;
+}
+
+impl AmlContext {
+    /// Install the handler used to send requests for `IPMI` fields, returning the previous one.
+    pub fn install_ipmi_handler(&mut self, handler: Box<dyn IpmiHandler>) -> Option<Arc<dyn IpmiHandler>> {
+        let previous = self.shared.ipmi_handler.lock().replace(Arc::from(handler));
+        self.update_region_space(RegionSpace::IPMI);
+        previous
+    }
+
+    pub fn remove_ipmi_handler(&mut self) -> Option<Arc<dyn IpmiHandler>> {
+        let previous = self.shared.ipmi_handler.lock().take();
+        self.update_region_space(RegionSpace::IPMI);
+        previous
+    }
+
+    /// Send the request in `buffer` (which is of the form `{ Status, Length, Data... }`) to the IPMI field at
+    /// `offset`, and return the response.
+    pub(crate) fn ipmi_transaction(&self, offset: u64, buffer: &[u8]) -> Result<AmlValue, AmlError> {
+        let handler =
+            self.shared.ipmi_handler.lock().clone().ok_or(AmlError::NoRegionHandler(RegionSpace::IPMI))?;
+
+        let offset = offset / 8;
+        let length = usize::min(buffer.get(1).copied().unwrap_or(0) as usize, IPMI_MAX_DATA_LENGTH);
+        let data = buffer.get(2..).unwrap_or(&[]);
+        let data = &data[..usize::min(length, data.len())];
+
+        let mut response = vec![0; IPMI_BUFFER_LENGTH];
+        match handler.transact(offset.get_bits(8..16) as u8, offset.get_bits(0..8) as u8, data) {
+            Ok(data) => {
+                let length = usize::min(data.len(), IPMI_MAX_DATA_LENGTH);
+                response[1] = length as u8;
+                response[2..(2 + length)].copy_from_slice(&data[..length]);
+            }
+            Err(status) => response[0] = status,
+        }
+        Ok(AmlValue::buffer(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{name_object::Target, test_utils::*, AmlName};
+
+    struct TestIpmiHandler;
+
+    impl IpmiHandler for TestIpmiHandler {
+        fn transact(&self, network_function: u8, command: u8, data: &[u8]) -> Result<Vec<u8>, u8> {
+            // Get Device ID
+            if network_function == 0x06 && command == 0x01 {
+                assert_eq!(data, &[0xaa]);
+                Ok(vec![0x00, 0x20])
+            } else {
+                Err(0x01)
+            }
+        }
+    }
+
+    #[test]
+    fn test_ipmi_field() {
+        let mut context = make_test_context();
+        context.install_ipmi_handler(Box::new(TestIpmiHandler));
+
+        /*
+         * OperationRegion (IPM0, IPMI, 0, 0x10000)
+         * Field (IPM0, BufferAcc, NoLock, Preserve) {
+         *     Offset (0x0601),
+         *     GDID, 528,
+         *     GDI2, 528
+         * }
+         */
+        context
+            .parse_table(&[
+                0x5b, 0x80, b'I', b'P', b'M', b'0', 0x07, 0x00, 0x0c, 0x00, 0x00, 0x01, 0x00, 0x5b, 0x81, 0x16,
+                b'I', b'P', b'M', b'0', 0x05, 0x00, 0x88, 0x00, 0x03, b'G', b'D', b'I', b'D', 0x40, 0x21, b'G',
+                b'D', b'I', b'2', 0x40, 0x21,
+            ])
+            .unwrap();
+
+        let mut request = vec![0; IPMI_BUFFER_LENGTH];
+        request[1] = 1;
+        request[2] = 0xaa;
+        let response = context
+            .store(Target::Name(AmlName::from_str("\\GDID").unwrap()), AmlValue::buffer(request.clone()))
+            .unwrap();
+        let response = response.as_buffer(&context).unwrap().lock().clone();
+        assert_eq!(response.len(), IPMI_BUFFER_LENGTH);
+        assert_eq!(&response[0..4], &[0x00, 0x02, 0x00, 0x20]);
+
+        let response =
+            context.store(Target::Name(AmlName::from_str("\\GDI2").unwrap()), AmlValue::buffer(request)).unwrap();
+        assert_eq!(response.as_buffer(&context).unwrap().lock()[0..2], [0x01, 0x00]);
+    }
+}
@@ -45,30 +45,59 @@ extern crate std;
 #[cfg(test)]
 mod test_utils;
 
+pub mod build;
+#[cfg(feature = "debug")]
+pub mod debugger;
+pub mod device;
+pub mod disassemble;
 pub(crate) mod expression;
+pub mod gpio;
+pub mod ipmi;
 pub(crate) mod misc;
 pub(crate) mod name_object;
 pub(crate) mod namespace;
+pub mod notify;
 pub(crate) mod opcode;
+pub mod osi;
 pub(crate) mod parser;
+pub(crate) mod pci_config;
 pub mod pci_routing;
 pub(crate) mod pkg_length;
 pub mod resource;
+pub mod serial_bus;
+pub mod snapshot;
 pub(crate) mod statement;
+pub mod sync;
 pub(crate) mod term_object;
 pub mod value;
 
 pub use crate::{namespace::*, value::AmlValue};
 
-use alloc::{boxed::Box, string::ToString};
-use core::mem;
-use log::{error, warn};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::ToString,
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    mem,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+use gpio::GpioHandler;
+use ipmi::IpmiHandler;
+use log::{error, info, warn};
 use misc::{ArgNum, LocalNum};
 use name_object::Target;
+use notify::NotifyState;
+use osi::OsiConfig;
 use parser::{Parser, Propagate};
 use pkg_length::PkgLength;
+use serial_bus::SerialBusHandler;
+use spinning_top::Spinlock;
+use sync::{HeldMutex, SpinSyncHandler, SyncHandler, WAIT_FOREVER};
 use term_object::term_list;
-use value::{AmlType, Args};
+use value::{AmlStream, AmlType, ArgValue, Args, DdbHandle, RegionSpace};
 
 /// AML has a `RevisionOp` operator that returns the "AML interpreter revision". It's not clear
 /// what this is actually used for, but this is ours.
@@ -88,6 +117,27 @@ pub enum DebugVerbosity {
     All,
 }
 
+/// Limits on the execution of AML, which stop buggy or malicious AML from hanging the host (e.g. with a `While`
+/// loop that never ends). Exceeding any of them fails the evaluation with `AmlError::FuelExhausted`. A limit of
+/// `None` means no limit. Set with [`AmlContext::set_execution_limits`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExecutionLimits {
+    /// The number of iterations a single `While` loop can perform.
+    pub max_loop_iterations: Option<u64>,
+    /// The depth of nested control method invocations. As the interpreter is recursive, this also bounds the
+    /// stack it uses.
+    pub max_call_depth: Option<usize>,
+    /// The number of terms that can be executed by an invocation of a control method from outside AML, including
+    /// those of the methods it invokes.
+    pub max_ops: Option<u64>,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> ExecutionLimits {
+        ExecutionLimits { max_loop_iterations: Some(0x10_0000), max_call_depth: Some(32), max_ops: None }
+    }
+}
+
 #[derive(Debug)]
 struct MethodContext {
     /// AML local variables. These are used when we invoke a control method. A `None` value represents a null AML
@@ -109,64 +159,323 @@ impl MethodContext {
 }
 
 pub struct AmlContext {
-    /// The `Handler` passed from the library user. This is stored as a boxed trait object simply to avoid having
-    /// to add a lifetime and type parameter to `AmlContext`, as they would massively complicate the parser types.
-    handler: Box<dyn Handler>,
+    /// The `Handler` passed from the library user. This is stored as a trait object simply to avoid having to add
+    /// a lifetime and type parameter to `AmlContext`, as they would massively complicate the parser types.
+    handler: Arc<dyn Handler>,
 
-    pub namespace: Namespace,
+    /// The namespace, which is shared with any contexts forked from this one.
+    pub namespace: Arc<Namespace>,
+    shared: Arc<SharedState>,
+
+    /*
+     * The rest of the state is owned by the evaluation the context is being used for, and isn't shared with forked
+     * contexts.
+     */
     method_context: Option<MethodContext>,
+    /// The mutexes held by the executing AML, in the order they were acquired.
+    held_mutexes: Vec<HeldMutex>,
+    /// The current sync level, which is the sync level of the last mutex acquired.
+    sync_level: u8,
+    /// The handle of `\_GL`, the mutex that represents the firmware's Global Lock.
+    global_lock: Option<AmlHandle>,
+    /// Whether the Global Lock has been acquired to access a field with the `Lock` rule.
+    field_holds_global_lock: AtomicBool,
+    notify: NotifyState,
+    limits: ExecutionLimits,
+    /// The depth of nested control method invocations.
+    call_depth: usize,
+    /// The number of terms executed by the current invocation of a control method from outside AML.
+    ops_executed: u64,
+    #[cfg(feature = "debug")]
+    debugger: debugger::DebuggerState,
 
     /*
      * These track the state of the context while it's parsing an AML table.
      */
     current_scope: AmlName,
+    /// The stream being parsed (a table, or the table that defined the executing method), which the bodies of the
+    /// methods it defines refer into.
+    current_stream: Option<AmlStream>,
     scope_indent: usize,
     debug_verbosity: DebugVerbosity,
 }
 
+/// The state of a context that's shared with the contexts forked from it with [`AmlContext::fork`]. Handlers are
+/// stored in `Arc`s, so they can be called without holding the locks.
+struct SharedState {
+    /// Handlers for the address spaces of operation regions that the interpreter doesn't access itself (e.g.
+    /// `EmbeddedControl`), or for which the library user wants to override the interpreter's accesses.
+    region_handlers: Spinlock<BTreeMap<RegionSpace, Arc<dyn RegionHandler>>>,
+    gpio_handler: Spinlock<Option<Arc<dyn GpioHandler>>>,
+    serial_bus_handler: Spinlock<Option<Arc<dyn SerialBusHandler>>>,
+    ipmi_handler: Spinlock<Option<Arc<dyn IpmiHandler>>>,
+    sync_handler: Spinlock<Arc<dyn SyncHandler>>,
+    notify_handlers: Spinlock<Vec<(notify::NotifyTarget, notify::NotifyHandler)>>,
+    /// The objects created by each definition block loaded with `Load` or `LoadTable`, so they can be removed if
+    /// it is unloaded.
+    loaded_tables: Spinlock<BTreeMap<DdbHandle, Vec<CreatedObject>>>,
+    /// Handles start at `1`, so they can be distinguished from the `0` that `LoadTable` evaluates to when it
+    /// fails.
+    next_ddb_handle: AtomicU64,
+    /// The number of invocations of each control method in progress. The level holding a method's local objects is
+    /// removed when the last of them returns.
+    method_invocations: Spinlock<BTreeMap<AmlName, usize>>,
+    /// The PCI addresses of `PciConfig` regions that have been resolved, by the handles of the regions.
+    pci_addresses: Spinlock<BTreeMap<AmlHandle, pci_config::PciAddress>>,
+    /// Whether `initialize_objects` has been run. Until then, the `_REG` methods of operation regions aren't run.
+    objects_initialized: AtomicBool,
+    /// The address spaces whose regions have been told they can be accessed, by running their `_REG` methods.
+    connected_spaces: Spinlock<BTreeSet<RegionSpace>>,
+}
+
 impl AmlContext {
     /// Creates a new `AmlContext` - the central type in managing the AML tables. Only one of these should be
-    /// created, and it should be passed the DSDT and all SSDTs defined by the hardware.
+    /// created (contexts for evaluating AML on other threads are made with [`AmlContext::fork`]), and it should be
+    /// passed the DSDT and all SSDTs defined by the hardware.
     pub fn new(handler: Box<dyn Handler>, debug_verbosity: DebugVerbosity) -> AmlContext {
-        let mut context = AmlContext {
+        let shared = SharedState {
+            region_handlers: Spinlock::new(BTreeMap::new()),
+            gpio_handler: Spinlock::new(None),
+            serial_bus_handler: Spinlock::new(None),
+            ipmi_handler: Spinlock::new(None),
+            sync_handler: Spinlock::new(Arc::new(SpinSyncHandler::new())),
+            notify_handlers: Spinlock::new(Vec::new()),
+            loaded_tables: Spinlock::new(BTreeMap::new()),
+            next_ddb_handle: AtomicU64::new(1),
+            method_invocations: Spinlock::new(BTreeMap::new()),
+            pci_addresses: Spinlock::new(BTreeMap::new()),
+            objects_initialized: AtomicBool::new(false),
+            connected_spaces: Spinlock::new(BTreeSet::new()),
+        };
+        let mut context = AmlContext::with_shared(
+            Arc::from(handler),
+            Arc::new(Namespace::new()),
+            Arc::new(shared),
+            None,
+            ExecutionLimits::default(),
+            debug_verbosity,
+        );
+
+        context.add_predefined_objects();
+        context
+    }
+
+    /// Create a context that shares the namespace, handlers, and loaded tables of this one, but has its own state
+    /// for evaluating AML (and a copy of its execution limits). Forked contexts can be moved to other threads, so
+    /// that methods can be invoked concurrently (e.g. from the handler of a GPE, while a driver invokes methods of
+    /// its device). The mutexes acquired by AML, and the serialized methods it invokes, are synchronized between
+    /// contexts by the [`SyncHandler`], so hosts that evaluate AML concurrently should install one that blocks.
+    pub fn fork(&self) -> AmlContext {
+        AmlContext::with_shared(
+            self.handler.clone(),
+            self.namespace.clone(),
+            self.shared.clone(),
+            self.global_lock,
+            self.limits,
+            self.debug_verbosity,
+        )
+    }
+
+    fn with_shared(
+        handler: Arc<dyn Handler>,
+        namespace: Arc<Namespace>,
+        shared: Arc<SharedState>,
+        global_lock: Option<AmlHandle>,
+        limits: ExecutionLimits,
+        debug_verbosity: DebugVerbosity,
+    ) -> AmlContext {
+        AmlContext {
             handler,
-            namespace: Namespace::new(),
+            namespace,
+            shared,
             method_context: None,
+            held_mutexes: Vec::new(),
+            sync_level: 0,
+            global_lock,
+            field_holds_global_lock: AtomicBool::new(false),
+            notify: NotifyState::default(),
+            limits,
+            call_depth: 0,
+            ops_executed: 0,
+            #[cfg(feature = "debug")]
+            debugger: debugger::DebuggerState::default(),
 
             current_scope: AmlName::root(),
+            current_stream: None,
             scope_indent: 0,
             debug_verbosity,
-        };
-
-        context.add_predefined_objects();
-        context
+        }
     }
 
     pub fn parse_table(&mut self, stream: &[u8]) -> Result<(), AmlError> {
-        if stream.len() == 0 {
+        self.parse_stream(AmlStream::Shared(stream.into()))
+    }
+
+    /// Parse a table that will live as long as the context, such as the DSDT mapped into the kernel's address
+    /// space. Unlike [`AmlContext::parse_table`], this doesn't need to copy the table, as the bodies of the
+    /// control methods it defines can refer to it directly.
+    pub fn parse_static_table(&mut self, stream: &'static [u8]) -> Result<(), AmlError> {
+        self.parse_stream(AmlStream::Static(stream))
+    }
+
+    fn parse_stream(&mut self, stream: AmlStream) -> Result<(), AmlError> {
+        let bytes = stream.as_bytes();
+        if bytes.is_empty() {
             return Err(AmlError::UnexpectedEndOfStream);
         }
 
-        let table_length = PkgLength::from_raw_length(stream, stream.len() as u32).unwrap();
-        match term_object::term_list(table_length).parse(stream, self) {
-            Ok(_) => Ok(()),
-            Err((_, _, Propagate::Err(err))) => {
+        let table_length = PkgLength::from_raw_length(bytes, bytes.len() as u32).unwrap();
+        self.begin_evaluation();
+        let old_stream = self.current_stream.replace(stream.clone());
+        #[cfg(feature = "debug")]
+        self.debug_enter_stream(None, bytes);
+        let result =
+            term_object::term_list(table_length).parse(bytes, self).map(|_| ()).map_err(|(_, _, err)| err);
+        #[cfg(feature = "debug")]
+        self.debug_exit_stream();
+        self.current_stream = old_stream;
+        self.end_evaluation();
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(Propagate::Err(err)) => {
                 error!("Failed to parse AML stream. Err = {:?}", err);
                 Err(err)
             }
-            Err((_, _, other)) => {
+            Err(other) => {
                 error!("AML table evaluated to unexpected result: {:?}", other);
                 Err(AmlError::MalformedStream)
             }
         }
     }
 
+    /// Load a definition block (an SSDT, with its header), in the same way as the AML `Load` operator. Unlike
+    /// tables passed to [`AmlContext::parse_table`], the objects created by the table are tracked, and can be
+    /// removed from the namespace again by passing the returned handle to [`AmlContext::unload_table`].
+    pub fn load_table(&mut self, table: &[u8]) -> Result<DdbHandle, AmlError> {
+        self.load_table_at(table, AmlName::root())
+    }
+
+    /// Load a definition block, resolving the names it creates relative to `root`.
+    pub(crate) fn load_table_at(&mut self, table: &[u8], root: AmlName) -> Result<DdbHandle, AmlError> {
+        const HEADER_LENGTH: usize = 36;
+
+        if table.len() < HEADER_LENGTH {
+            return Err(AmlError::InvalidDefinitionBlock);
+        }
+        let length = u32::from_le_bytes([table[4], table[5], table[6], table[7]]) as usize;
+        if length < HEADER_LENGTH || length > table.len() {
+            return Err(AmlError::InvalidDefinitionBlock);
+        }
+        if table[..length].iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+            return Err(AmlError::InvalidDefinitionBlock);
+        }
+
+        /*
+         * The table is loaded as if it were parsed at the top level, even if it's loaded from inside a control
+         * method, so we set up the state for that, and restore the current state afterwards.
+         */
+        let old_context = self.method_context.take();
+        let old_scope = mem::replace(&mut self.current_scope, root);
+        let previous_recording = self.namespace.start_recording();
+
+        let result = if length > HEADER_LENGTH { self.parse_table(&table[HEADER_LENGTH..length]) } else { Ok(()) };
+
+        let created_objects = self.namespace.finish_recording(previous_recording);
+        self.method_context = old_context;
+        self.current_scope = old_scope;
+
+        match result {
+            Ok(()) => {
+                let handle = DdbHandle(self.shared.next_ddb_handle.fetch_add(1, Ordering::Relaxed));
+                self.shared.loaded_tables.lock().insert(handle, created_objects);
+                Ok(handle)
+            }
+            Err(err) => {
+                // Roll back the objects the table managed to create before it failed
+                self.namespace.remove_created_objects(created_objects);
+                Err(err)
+            }
+        }
+    }
+
+    /// Unload a definition block loaded with [`AmlContext::load_table`] (or by AML with `Load` or `LoadTable`),
+    /// removing the objects it created from the namespace.
+    pub fn unload_table(&mut self, handle: DdbHandle) -> Result<(), AmlError> {
+        let created_objects =
+            self.shared.loaded_tables.lock().remove(&handle).ok_or(AmlError::InvalidDdbHandle)?;
+        self.namespace.remove_created_objects(created_objects);
+        Ok(())
+    }
+
+    /// Invoke the method at `path`, converting each of `args` into an `AmlValue`. This is a more convenient form
+    /// of [`AmlContext::invoke_method`], e.g. `context.invoke(&path, &[1u64.into(), "string".into()])`.
+    pub fn invoke(&mut self, path: &AmlName, args: &[ArgValue]) -> Result<AmlValue, AmlError> {
+        let args = Args::from_list(args.iter().map(ArgValue::to_aml_value).collect())?;
+        self.invoke_method(path, args)
+    }
+
     // TODO: docs
     pub fn invoke_method(&mut self, path: &AmlName, args: Args) -> Result<AmlValue, AmlError> {
-        use value::MethodCode;
+        if self.limits.max_call_depth.is_some_and(|max| self.call_depth >= max) {
+            warn!("Invoking {} exceeded the maximum call depth", path);
+            return Err(AmlError::FuelExhausted);
+        }
+        if self.call_depth == 0 {
+            self.ops_executed = 0;
+        }
+
+        self.call_depth += 1;
+        self.begin_evaluation();
+        let result = self.invoke_method_inner(path, args);
+        self.call_depth -= 1;
+        self.end_evaluation();
+        result
+    }
+
+    /// Set the limits on the execution of AML by this context. See [`ExecutionLimits`] for the defaults.
+    pub fn set_execution_limits(&mut self, limits: ExecutionLimits) {
+        self.limits = limits;
+    }
+
+    /// Count a term executed by a control method against the limit on the number executed by each invocation.
+    pub(crate) fn consume_op(&mut self) -> Result<(), AmlError> {
+        if self.method_context.is_none() {
+            return Ok(());
+        }
+
+        self.ops_executed += 1;
+        if self.limits.max_ops.is_some_and(|max| self.ops_executed > max) {
+            warn!("AML exceeded the maximum number of ops for an invocation");
+            return Err(AmlError::FuelExhausted);
+        }
+        Ok(())
+    }
+
+    /// Check the number of iterations a `While` loop has performed against its limit.
+    pub(crate) fn check_loop_iterations(&self, iterations: u64) -> Result<(), AmlError> {
+        if self.limits.max_loop_iterations.is_some_and(|max| iterations >= max) {
+            warn!("AML While loop exceeded the maximum number of iterations");
+            return Err(AmlError::FuelExhausted);
+        }
+        Ok(())
+    }
 
-        match self.namespace.get_by_path(path)?.clone() {
+    fn invoke_method_inner(&mut self, path: &AmlName, args: Args) -> Result<AmlValue, AmlError> {
+        match self.namespace.get_by_path(path)? {
             AmlValue::Method { flags, code } => {
+                /*
+                 * Serialized methods can only be executed by one evaluation at a time, which is enforced by
+                 * acquiring an implicit mutex at the method's sync level. Like the mutexes acquired by AML, the
+                 * evaluation that holds it can invoke the method again.
+                 */
+                let method_handle = self.namespace.get_handle(path)?;
+                if flags.serialize()
+                    && !self.acquire_mutex_handle(method_handle, flags.sync_level(), WAIT_FOREVER)?
+                {
+                    return Err(AmlError::SerializedMethodNotAcquired(path.clone()));
+                }
+
                 /*
                  * First, set up the state we expect to enter the method with, but clearing local
                  * variables to "null" and setting the arguments. Save the current method state and scope, so if we're
@@ -174,45 +483,39 @@ impl AmlContext {
                  */
                 let old_context = mem::replace(&mut self.method_context, Some(MethodContext::new(args)));
                 let old_scope = mem::replace(&mut self.current_scope, path.clone());
+                let held_mutexes = self.held_mutexes.len();
 
                 /*
-                 * Create a namespace level to store local objects created by the invocation.
+                 * Create a namespace level to store local objects created by the invocation. This is shared by the
+                 * invocations of the method in progress, so it's only created by the first one. From here on,
+                 * errors are only returned once the implicit mutex has been released and the old state restored.
                  */
-                self.namespace.add_level(path.clone(), LevelType::MethodLocals)?;
-
-                let return_value = match code {
-                    MethodCode::Aml(ref code) => {
-                        match term_list(PkgLength::from_raw_length(code, code.len() as u32).unwrap())
-                            .parse(code, self)
-                        {
-                            // If the method doesn't return a value, we implicitly return `0`
-                            Ok(_) => Ok(AmlValue::Integer(0)),
-                            Err((_, _, Propagate::Return(result))) => Ok(result),
-                            Err((_, _, Propagate::Break)) => Err(AmlError::BreakInInvalidPosition),
-                            Err((_, _, Propagate::Continue)) => Err(AmlError::ContinueInInvalidPosition),
-                            Err((_, _, Propagate::Err(err))) => {
-                                error!("Failed to execute control method: {:?}", err);
-                                Err(err)
-                            }
-                        }
-                    }
-
-                    MethodCode::Native(ref method) => match (method)(self) {
-                        Ok(result) => Ok(result),
-                        Err(err) => {
-                            error!("Failed to execute control method: {:?}", err);
-                            Err(err)
-                        }
-                    },
-                };
+                let return_value = self.enter_method_locals(path).and_then(|()| {
+                    let result = self.execute_method_code(path, &code);
+
+                    /*
+                     * Locally-created objects should be destroyed on method exit (see §5.5.2.3 of the ACPI spec).
+                     * We do this by simply removing the method's local object layer.
+                     */
+                    // TODO: this should also remove objects created by the method outside the method's scope, if
+                    // they weren't statically created. This is harder.
+                    let exited = self.exit_method_locals(path);
+                    result.and_then(|result| exited.map(|()| result))
+                });
 
                 /*
-                 * Locally-created objects should be destroyed on method exit (see §5.5.2.3 of the ACPI spec). We do
-                 * this by simply removing the method's local object layer.
+                 * If the method failed, it won't get a chance to release the mutexes it acquired, so we do it for
+                 * it.
                  */
-                // TODO: this should also remove objects created by the method outside the method's scope, if they
-                // weren't statically created. This is harder.
-                self.namespace.remove_level(path.clone())?;
+                if return_value.is_err() {
+                    let acquired = self.held_mutexes.split_off(held_mutexes);
+                    self.release_all_mutexes(acquired);
+                }
+                let released = if flags.serialize() {
+                    self.release_mutex_handle(method_handle, flags.sync_level())
+                } else {
+                    Ok(())
+                };
 
                 /*
                  * Restore the old state.
@@ -220,7 +523,7 @@ impl AmlContext {
                 self.method_context = old_context;
                 self.current_scope = old_scope;
 
-                return_value
+                return_value.and_then(|result| released.map(|()| result))
             }
 
             /*
@@ -232,11 +535,88 @@ impl AmlContext {
         }
     }
 
-    // TODO: docs
+    /// Run the code of a method, which must be invoked with its state set up by [`AmlContext::invoke_method_inner`].
+    fn execute_method_code(&mut self, path: &AmlName, code: &value::MethodCode) -> Result<AmlValue, AmlError> {
+        use value::MethodCode;
+
+        match code {
+            MethodCode::Aml(ref code) => {
+                let old_stream = self.current_stream.replace(code.stream().clone());
+                let code = code.as_bytes();
+                #[cfg(feature = "debug")]
+                self.debug_enter_stream(Some(path.clone()), code);
+                let result = term_list(PkgLength::from_raw_length(code, code.len() as u32).unwrap())
+                    .parse(code, self)
+                    .map(|_| ())
+                    .map_err(|(_, _, propagate)| propagate);
+                #[cfg(feature = "debug")]
+                self.debug_exit_stream();
+                self.current_stream = old_stream;
+
+                match result {
+                    // If the method doesn't return a value, we implicitly return `0`
+                    Ok(()) => Ok(AmlValue::Integer(0)),
+                    Err(Propagate::Return(result)) => Ok(result),
+                    Err(Propagate::Break) => Err(AmlError::BreakInInvalidPosition),
+                    Err(Propagate::Continue) => Err(AmlError::ContinueInInvalidPosition),
+                    Err(Propagate::Err(err)) => {
+                        error!("Failed to execute control method {}: {:?}", path, err);
+                        Err(err)
+                    }
+                }
+            }
+
+            MethodCode::Native(ref method) => match (method)(self) {
+                Ok(result) => Ok(result),
+                Err(err) => {
+                    error!("Failed to execute control method {}: {:?}", path, err);
+                    Err(err)
+                }
+            },
+        }
+    }
+
+    fn enter_method_locals(&self, path: &AmlName) -> Result<(), AmlError> {
+        let mut invocations = self.shared.method_invocations.lock();
+        let count = invocations.entry(path.clone()).or_insert(0);
+        if *count == 0 {
+            self.namespace.add_level(path.clone(), LevelType::MethodLocals)?;
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    fn exit_method_locals(&self, path: &AmlName) -> Result<(), AmlError> {
+        let mut invocations = self.shared.method_invocations.lock();
+        let count = invocations.get_mut(path).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            invocations.remove(path);
+            self.namespace.remove_level(path.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Initialize the objects in the namespace, in the order described by the ACPI spec. This should be called
+    /// once all of the tables describing the system (the DSDT and SSDTs) have been parsed. It:
+    ///    - Runs the `_REG` methods of regions in the address spaces that can be accessed (the spaces the
+    ///      interpreter accesses itself, and those that handlers have been installed for). `_REG` methods for
+    ///      spaces whose handlers are installed later are run when they're installed.
+    ///    - Invokes `\_SB._INI`, if it exists.
+    ///    - Evaluates the `_STA` of each device, and invokes its `_INI` if it's present. The children of a device
+    ///      are only initialized if it's present or functional.
     pub fn initialize_objects(&mut self) -> Result<(), AmlError> {
         use name_object::NameSeg;
         use value::StatusObject;
 
+        self.shared.objects_initialized.store(true, Ordering::Release);
+        let mut spaces = alloc::vec![RegionSpace::SystemMemory, RegionSpace::SystemIo, RegionSpace::PciConfig];
+        spaces.extend(self.shared.region_handlers.lock().keys().copied());
+        spaces.extend([RegionSpace::GeneralPurposeIo, RegionSpace::GenericSerialBus, RegionSpace::IPMI]);
+        for space in spaces {
+            self.update_region_space(space);
+        }
+
         /*
          * If `\_SB._INI` exists, we unconditionally execute it at the beginning of device initialization.
          */
@@ -247,16 +627,16 @@ impl AmlContext {
         }
 
         /*
-         * Next, we traverse the namespace, looking for devices.
-         *
-         * XXX: we clone the namespace here, which obviously drives up heap burden quite a bit (not as much as you
-         * might first expect though - we're only duplicating the level data structure, not all the objects). The
-         * issue here is that we need to access the namespace during traversal (e.g. to invoke a method), which the
-         * borrow checker really doesn't like. A better solution could be a iterator-like traversal system that
-         * keeps track of the namespace without keeping it borrowed. This works for now.
+         * Next, we traverse the namespace, looking for devices. The traversal is of a copy of the levels, so the
+         * methods it finds can be invoked.
          */
-        self.namespace.clone().traverse(|path, level: &NamespaceLevel| match level.typ {
-            LevelType::Device => {
+        let namespace = self.namespace.clone();
+        namespace.traverse(|path, level: &NamespaceLevel| match level.typ {
+            /*
+             * Processors and thermal zones can have `_STA` and `_INI` objects too, and are initialized in the same
+             * way as devices.
+             */
+            LevelType::Device | LevelType::Processor | LevelType::ThermalZone => {
                 let status = if level.values.contains_key(&NameSeg::from_str("_STA").unwrap()) {
                     self.invoke_method(&AmlName::from_str("_STA").unwrap().resolve(&path)?, Args::default())?
                         .as_status()?
@@ -280,17 +660,20 @@ impl AmlContext {
 
             LevelType::Scope => Ok(true),
 
-            // TODO: can any of these contain devices?
-            LevelType::Processor => Ok(false),
             LevelType::PowerResource => Ok(false),
-            LevelType::ThermalZone => Ok(false),
             LevelType::MethodLocals => Ok(false),
         })?;
 
+        /*
+         * Now that the devices have been initialized, the objects that the PCI addresses of `PciConfig` regions
+         * are found from can be evaluated.
+         */
+        self.resolve_pci_addresses()?;
+
         Ok(())
     }
 
-    pub(crate) fn read_target(&self, target: &Target) -> Result<&AmlValue, AmlError> {
+    pub(crate) fn read_target(&self, target: &Target) -> Result<AmlValue, AmlError> {
         match target {
             Target::Null => todo!(),
             Target::Name(name) => {
@@ -298,8 +681,8 @@ impl AmlContext {
                 self.namespace.get(handle)
             }
             Target::Debug => todo!(),
-            Target::Arg(arg) => self.current_arg(*arg),
-            Target::Local(local) => self.local(*local),
+            Target::Arg(arg) => self.current_arg(*arg).cloned(),
+            Target::Local(local) => self.local(*local).cloned(),
         }
     }
 
@@ -331,27 +714,47 @@ impl AmlContext {
             Target::Name(ref path) => {
                 let (_, handle) = self.namespace.search(path, &self.current_scope)?;
 
-                match self.namespace.get(handle).unwrap().type_of() {
+                match self.namespace.get(handle)?.type_of() {
                     AmlType::FieldUnit => {
-                        let mut field = self.namespace.get(handle).unwrap().clone();
-                        field.write_field(value, self)?;
-                        field.read_field(self)
+                        let mut field = self.namespace.get(handle)?;
+                        field.store_field(value, self)
                     }
                     AmlType::BufferField => {
-                        let mut buffer_field = self.namespace.get(handle).unwrap().clone();
+                        let mut buffer_field = self.namespace.get(handle)?;
                         buffer_field.write_buffer_field(value.clone(), self)?;
                         Ok(value)
                     }
+                    AmlType::Buffer => {
+                        /*
+                         * Stores into a buffer copy the new contents into the existing buffer, so that any
+                         * `BufferField`s created from it see them.
+                         */
+                        let target = self.namespace.get(handle)?.as_buffer(self)?;
+                        let source = value.as_buffer(self)?;
+                        if !Arc::ptr_eq(&target, &source) {
+                            value::convert::store_to_buffer(&mut target.lock(), &source.lock());
+                        }
+                        self.namespace.get(handle)
+                    }
                     typ => {
-                        *self.namespace.get_mut(handle)? = value.as_type(typ, self)?;
-                        Ok(self.namespace.get(handle)?.clone())
+                        self.namespace.set(handle, value.as_type(typ, self)?.copy_object())?;
+                        self.namespace.get(handle)
                     }
                 }
             }
 
             Target::Debug => {
-                // TODO
-                unimplemented!()
+                /*
+                 * Firmware stores to the `Debug` object to print diagnostics. Field units are read, so that their
+                 * values are shown.
+                 */
+                let value = match value.type_of() {
+                    AmlType::FieldUnit => value.read_field(self)?,
+                    AmlType::BufferField => value.read_buffer_field(self)?,
+                    _ => value,
+                };
+                self.handler.handle_debug(&alloc::format!("{}", value));
+                Ok(value)
             }
 
             Target::Arg(arg_num) => {
@@ -365,6 +768,7 @@ impl AmlContext {
                  * copied to the target of the Object Reference, instead of overwriting the `Arg.`
                  */
                 // TODO: implement behaviour for object references
+                let value = value.copy_object();
                 self.method_context.as_mut().unwrap().args.store_arg(arg_num, value.clone())?;
                 Ok(value)
             }
@@ -378,6 +782,7 @@ impl AmlContext {
                  * Stores into `Local` objects are always simply copied into the destination with no conversion
                  * applied, even if it contains an Object Reference.
                  */
+                let value = value.copy_object();
                 self.method_context.as_mut().unwrap().locals[local_num as usize] = Some(value.clone());
                 Ok(value)
             }
@@ -386,23 +791,146 @@ impl AmlContext {
         }
     }
 
+    /// Perform a `CopyObject` into a `Target`. Unlike a store, no implicit conversion is applied, and a named
+    /// object is replaced by the value, even if it's of a different type. Like ACPICA, we still write the value
+    /// into field units and buffer fields, as they must keep their type.
+    pub(crate) fn copy_object(&mut self, target: Target, value: AmlValue) -> Result<AmlValue, AmlError> {
+        match target {
+            Target::Name(ref path) => {
+                let (_, handle) = self.namespace.search(path, &self.current_scope)?;
+
+                match self.namespace.get(handle)?.type_of() {
+                    AmlType::FieldUnit | AmlType::BufferField => self.store(target, value),
+                    _ => {
+                        self.namespace.set(handle, value.copy_object())?;
+                        self.namespace.get(handle)
+                    }
+                }
+            }
+            _ => self.store(target, value),
+        }
+    }
+
+    /// Install a handler for accesses to operation regions in the given address space, replacing the
+    /// existing handler for that space (which is returned), if there is one. This takes priority over the
+    /// interpreter's own accesses to the `SystemMemory`, `SystemIo`, and `PciConfig` spaces. If the objects in the
+    /// namespace have been initialized, the `_REG` methods of the space's regions are run to tell the AML that the
+    /// space can now be accessed.
+    pub fn install_region_handler(
+        &mut self,
+        space: RegionSpace,
+        handler: Box<dyn RegionHandler>,
+    ) -> Option<Arc<dyn RegionHandler>> {
+        let previous = self.shared.region_handlers.lock().insert(space, Arc::from(handler));
+        self.update_region_space(space);
+        previous
+    }
+
+    /// Remove the handler for an address space. If the interpreter can't access the space itself, the `_REG`
+    /// methods of the space's regions are run to tell the AML that it can no longer be accessed.
+    pub fn remove_region_handler(&mut self, space: RegionSpace) -> Option<Arc<dyn RegionHandler>> {
+        let previous = self.shared.region_handlers.lock().remove(&space);
+        self.update_region_space(space);
+        previous
+    }
+
+    /// Whether the interpreter can currently access operation regions in `space`, either itself or through an
+    /// installed handler.
+    fn region_space_available(&self, space: RegionSpace) -> bool {
+        match space {
+            RegionSpace::SystemMemory | RegionSpace::SystemIo | RegionSpace::PciConfig => true,
+            RegionSpace::GeneralPurposeIo => self.shared.gpio_handler.lock().is_some(),
+            RegionSpace::GenericSerialBus => self.shared.serial_bus_handler.lock().is_some(),
+            RegionSpace::IPMI => self.shared.ipmi_handler.lock().is_some(),
+            space => self.shared.region_handlers.lock().contains_key(&space),
+        }
+    }
+
+    /// Called when the availability of an address space may have changed (e.g. when a handler is installed for
+    /// it). If it has, the `_REG` methods of the space's regions are run, with `Arg0` set to the space's ID, and
+    /// `Arg1` set to `1` if the space has become available, and `0` if it's no longer available.
+    pub(crate) fn update_region_space(&mut self, space: RegionSpace) {
+        if !self.shared.objects_initialized.load(Ordering::Acquire) {
+            return;
+        }
+        let available = self.region_space_available(space);
+        let changed = {
+            let mut connected_spaces = self.shared.connected_spaces.lock();
+            if available {
+                connected_spaces.insert(space)
+            } else {
+                connected_spaces.remove(&space)
+            }
+        };
+        if !changed {
+            return;
+        }
+
+        /*
+         * `_REG` is run once for each scope that declares a region in the space, rather than once for each region.
+         */
+        let reg_seg = name_object::NameSeg::from_str("_REG").unwrap();
+        let mut scopes = Vec::new();
+        let namespace = &self.namespace;
+        let result = namespace.traverse(|path, level: &NamespaceLevel| {
+            let has_region = level.values.values().any(|&handle| {
+                matches!(namespace.get(handle), Ok(AmlValue::OpRegion { region, .. }) if region == space)
+            });
+            if has_region && level.values.contains_key(&reg_seg) {
+                scopes.push(path.clone());
+            }
+            Ok(!matches!(level.typ, LevelType::MethodLocals))
+        });
+        if let Err(err) = result {
+            warn!("Failed to find the _REG methods for {:?} regions: {:?}", space, err);
+            return;
+        }
+
+        for scope in scopes {
+            let path = AmlName::from_name_seg(reg_seg).resolve(&scope).unwrap();
+            let args = Args::from_list(alloc::vec![
+                AmlValue::Integer(space.id() as u64),
+                AmlValue::Integer(available as u64)
+            ])
+            .unwrap();
+
+            // Failing `_REG` methods are common, and shouldn't stop the rest from running
+            if let Err(err) = self.invoke_method(&path, args) {
+                warn!("Failed to run {}: {:?}", path, err);
+            }
+        }
+    }
+
+    /// Read the definition block that the AML `Load` operator loads from the object at `name`. This can be an
+    /// operation region, a field, or a buffer.
+    pub(crate) fn read_definition_block(&self, name: &AmlName) -> Result<Vec<u8>, AmlError> {
+        let (_, handle) = self.namespace.search(name, &self.current_scope)?;
+        match self.namespace.get(handle)? {
+            AmlValue::OpRegion { length, .. } => {
+                (0..length).map(|i| Ok(self.read_region(handle, i, 8)? as u8)).collect()
+            }
+            value => Ok(value.as_buffer(self)?.lock().clone()),
+        }
+    }
+
     /// Read from an operation-region, performing only standard-sized reads (supported powers-of-2 only. If a field
     /// is not one of these sizes, it may need to be masked, or multiple reads may need to be performed).
     pub(crate) fn read_region(&self, region_handle: AmlHandle, offset: u64, length: u64) -> Result<u64, AmlError> {
-        use bit_field::BitField;
         use core::convert::TryInto;
-        use value::RegionSpace;
 
-        let (region_space, region_base, region_length, parent_device) = {
-            if let AmlValue::OpRegion { region, offset, length, parent_device } =
-                self.namespace.get(region_handle)?
-            {
-                (region, offset, length, parent_device)
+        let (region_space, region_base, region_length) = {
+            if let AmlValue::OpRegion { region, offset, length, .. } = self.namespace.get(region_handle)? {
+                (region, offset, length)
             } else {
                 return Err(AmlError::FieldRegionIsNotOpRegion);
             }
         };
 
+        let handler = self.shared.region_handlers.lock().get(&region_space).cloned();
+        if let Some(handler) = handler {
+            return handler.read(region_base + offset, length);
+        }
+
         match region_space {
             RegionSpace::SystemMemory => {
                 let address = (region_base + offset).try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
@@ -425,76 +953,34 @@ impl AmlContext {
                 }
             }
 
-            RegionSpace::PciConfig => {
-                /*
-                 * First, we need to get some extra information out of objects in the parent object. Both
-                 * `_SEG` and `_BBN` seem optional, with defaults that line up with legacy PCI implementations
-                 * (e.g. systems with a single segment group and a single root, respectively).
-                 */
-                let parent_device = parent_device.as_ref().unwrap();
-                let seg = match self.namespace.search(&AmlName::from_str("_SEG").unwrap(), parent_device) {
-                    Ok((_, handle)) => self
-                        .namespace
-                        .get(handle)?
-                        .as_integer(self)?
-                        .try_into()
-                        .map_err(|_| AmlError::FieldInvalidAddress)?,
-                    Err(AmlError::ValueDoesNotExist(_)) => 0,
-                    Err(err) => return Err(err),
-                };
-                let bbn = match self.namespace.search(&AmlName::from_str("_BBN").unwrap(), parent_device) {
-                    Ok((_, handle)) => self
-                        .namespace
-                        .get(handle)?
-                        .as_integer(self)?
-                        .try_into()
-                        .map_err(|_| AmlError::FieldInvalidAddress)?,
-                    Err(AmlError::ValueDoesNotExist(_)) => 0,
-                    Err(err) => return Err(err),
-                };
-                let adr = {
-                    let (_, handle) = self.namespace.search(&AmlName::from_str("_ADR").unwrap(), parent_device)?;
-                    self.namespace.get(handle)?.as_integer(self)?
-                };
+            RegionSpace::PciConfig => self.read_pci_config(region_handle, region_base + offset, length),
 
-                let device = adr.get_bits(16..24) as u8;
-                let function = adr.get_bits(0..8) as u8;
-                let offset = (region_base + offset).try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
-
-                match length {
-                    8 => Ok(self.handler.read_pci_u8(seg, bbn, device, function, offset) as u64),
-                    16 => Ok(self.handler.read_pci_u16(seg, bbn, device, function, offset) as u64),
-                    32 => Ok(self.handler.read_pci_u32(seg, bbn, device, function, offset) as u64),
-                    _ => Err(AmlError::FieldInvalidAccessSize),
-                }
-            }
-
-            // TODO
-            _ => unimplemented!(),
+            space => Err(AmlError::NoRegionHandler(space)),
         }
     }
 
     pub(crate) fn write_region(
-        &mut self,
+        &self,
         region_handle: AmlHandle,
         offset: u64,
         length: u64,
         value: u64,
     ) -> Result<(), AmlError> {
-        use bit_field::BitField;
         use core::convert::TryInto;
-        use value::RegionSpace;
 
-        let (region_space, region_base, region_length, parent_device) = {
-            if let AmlValue::OpRegion { region, offset, length, parent_device } =
-                self.namespace.get(region_handle)?
-            {
-                (region, offset, length, parent_device)
+        let (region_space, region_base, region_length) = {
+            if let AmlValue::OpRegion { region, offset, length, .. } = self.namespace.get(region_handle)? {
+                (region, offset, length)
             } else {
                 return Err(AmlError::FieldRegionIsNotOpRegion);
             }
         };
 
+        let handler = self.shared.region_handlers.lock().get(&region_space).cloned();
+        if let Some(handler) = handler {
+            return handler.write(region_base + offset, length, value);
+        }
+
         match region_space {
             RegionSpace::SystemMemory => {
                 let address = (region_base + offset).try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
@@ -517,52 +1003,9 @@ impl AmlContext {
                 }
             }
 
-            RegionSpace::PciConfig => {
-                /*
-                 * First, we need to get some extra information out of objects in the parent object. Both
-                 * `_SEG` and `_BBN` seem optional, with defaults that line up with legacy PCI implementations
-                 * (e.g. systems with a single segment group and a single root, respectively).
-                 */
-                let parent_device = parent_device.as_ref().unwrap();
-                let seg = match self.namespace.search(&AmlName::from_str("_SEG").unwrap(), parent_device) {
-                    Ok((_, handle)) => self
-                        .namespace
-                        .get(handle)?
-                        .as_integer(self)?
-                        .try_into()
-                        .map_err(|_| AmlError::FieldInvalidAddress)?,
-                    Err(AmlError::ValueDoesNotExist(_)) => 0,
-                    Err(err) => return Err(err),
-                };
-                let bbn = match self.namespace.search(&AmlName::from_str("_BBN").unwrap(), parent_device) {
-                    Ok((_, handle)) => self
-                        .namespace
-                        .get(handle)?
-                        .as_integer(self)?
-                        .try_into()
-                        .map_err(|_| AmlError::FieldInvalidAddress)?,
-                    Err(AmlError::ValueDoesNotExist(_)) => 0,
-                    Err(err) => return Err(err),
-                };
-                let adr = {
-                    let (_, handle) = self.namespace.search(&AmlName::from_str("_ADR").unwrap(), parent_device)?;
-                    self.namespace.get(handle)?.as_integer(self)?
-                };
-
-                let device = adr.get_bits(16..24) as u8;
-                let function = adr.get_bits(0..8) as u8;
-                let offset = (region_base + offset).try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
+            RegionSpace::PciConfig => self.write_pci_config(region_handle, region_base + offset, length, value),
 
-                match length {
-                    8 => Ok(self.handler.write_pci_u8(seg, bbn, device, function, offset, value as u8)),
-                    16 => Ok(self.handler.write_pci_u16(seg, bbn, device, function, offset, value as u16)),
-                    32 => Ok(self.handler.write_pci_u32(seg, bbn, device, function, offset, value as u32)),
-                    _ => Err(AmlError::FieldInvalidAccessSize),
-                }
-            }
-
-            // TODO
-            _ => unimplemented!(),
+            space => Err(AmlError::NoRegionHandler(space)),
         }
     }
 
@@ -590,65 +1033,11 @@ impl AmlContext {
             .unwrap();
 
         /*
-         * `\_OSI` was introduced by ACPI 3.0 to improve the situation created by `\_OS`. Unfortunately, exactly
-         * the same problem was immediately repeated by introducing capabilities reflecting that an ACPI
-         * implementation is exactly the same as a particular version of Windows' (e.g. firmwares will call
-         * `\_OSI("Windows 2001")`).
-         *
-         * We basically follow suit with whatever Linux does, as this will hopefully minimise breakage:
-         *    - We always claim `Windows *` compatability
-         *    - We answer 'yes' to `_OSI("Darwin")
-         *    - We answer 'no' to `_OSI("Linux")`, and report that the tables are doing the wrong thing
+         * `\_OSI` is used by firmware to query which versions of Windows (and which features) the OS is compatible
+         * with. See the `osi` module for what we claim by default.
          */
         self.namespace
-            .add_value(
-                AmlName::from_str("\\_OSI").unwrap(),
-                AmlValue::native_method(1, false, 0, |context| {
-                    Ok(match context.current_arg(0)?.as_string(context)?.as_str() {
-                        "Windows 2000" => true,       // 2000
-                        "Windows 2001" => true,       // XP
-                        "Windows 2001 SP1" => true,   // XP SP1
-                        "Windows 2001 SP2" => true,   // XP SP2
-                        "Windows 2001.1" => true,     // Server 2003
-                        "Windows 2001.1 SP1" => true, // Server 2003 SP1
-                        "Windows 2006" => true,       // Vista
-                        "Windows 2006 SP1" => true,   // Vista SP1
-                        "Windows 2006 SP2" => true,   // Vista SP2
-                        "Windows 2006.1" => true,     // Server 2008
-                        "Windows 2009" => true,       // 7 and Server 2008 R2
-                        "Windows 2012" => true,       // 8 and Server 2012
-                        "Windows 2013" => true,       // 8.1 and Server 2012 R2
-                        "Windows 2015" => true,       // 10
-                        "Windows 2016" => true,       // 10 version 1607
-                        "Windows 2017" => true,       // 10 version 1703
-                        "Windows 2017.2" => true,     // 10 version 1709
-                        "Windows 2018" => true,       // 10 version 1803
-                        "Windows 2018.2" => true,     // 10 version 1809
-                        "Windows 2019" => true,       // 10 version 1903
-
-                        "Darwin" => true,
-
-                        "Linux" => {
-                            // TODO: should we allow users to specify that this should be true? Linux has a
-                            // command line option for this.
-                            warn!("ACPI evaluated `_OSI(\"Linux\")`. This is a bug. Reporting no support.");
-                            false
-                        }
-
-                        "Extended Address Space Descriptor" => true,
-                        // TODO: support module devices
-                        "Module Device" => false,
-                        "3.0 Thermal Model" => true,
-                        "3.0 _SCP Extensions" => true,
-                        // TODO: support processor aggregator devices
-                        "Processor Aggregator Device" => false,
-
-                        _ => false,
-                    }
-                    .then_some(AmlValue::ones())
-                    .unwrap_or(AmlValue::zero()))
-                }),
-            )
+            .add_value(AmlName::from_str("\\_OSI").unwrap(), OsiConfig::default().into_method())
             .unwrap();
 
         /*
@@ -658,6 +1047,16 @@ impl AmlContext {
          * useless and deprecated (this is mirrored in newer specs, which claim `2` means "ACPI 2 or greater").
          */
         self.namespace.add_value(AmlName::from_str("\\_REV").unwrap(), AmlValue::Integer(2)).unwrap();
+
+        /*
+         * `\_GL` is the mutex that AML acquires to synchronize with the firmware through the Global Lock. See the
+         * `sync` module for how it's handled.
+         */
+        self.global_lock = Some(
+            self.namespace
+                .add_value(AmlName::from_str("\\_GL").unwrap(), AmlValue::Mutex { sync_level: 0 })
+                .unwrap(),
+        );
     }
 }
 
@@ -668,10 +1067,10 @@ pub trait Handler: Send + Sync {
     fn read_u32(&self, address: usize) -> u32;
     fn read_u64(&self, address: usize) -> u64;
 
-    fn write_u8(&mut self, address: usize, value: u8);
-    fn write_u16(&mut self, address: usize, value: u16);
-    fn write_u32(&mut self, address: usize, value: u32);
-    fn write_u64(&mut self, address: usize, value: u64);
+    fn write_u8(&self, address: usize, value: u8);
+    fn write_u16(&self, address: usize, value: u16);
+    fn write_u32(&self, address: usize, value: u32);
+    fn write_u64(&self, address: usize, value: u64);
 
     fn read_io_u8(&self, port: u16) -> u8;
     fn read_io_u16(&self, port: u16) -> u16;
@@ -689,9 +1088,46 @@ pub trait Handler: Send + Sync {
     fn write_pci_u16(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16, value: u16);
     fn write_pci_u32(&self, segment: u16, bus: u8, device: u8, function: u8, offset: u16, value: u32);
 
+    /// Returns a monotonically-increasing value of nanoseconds, for the AML `Timer` operator. It doesn't need to
+    /// count from when the system booted, but must not go backwards.
+    fn nanos_since_boot(&self) -> u64;
+
+    /// Busy-wait for at least `microseconds`, for the AML `Stall` operator. This is used by firmware for short
+    /// delays (of up to 255 microseconds), e.g. while waiting for a hardware status bit to change.
+    fn stall(&self, microseconds: u64);
+
+    /// Sleep for at least `milliseconds`, for the AML `Sleep` operator. Unlike `stall`, the host may run something
+    /// else while the AML sleeps.
+    fn sleep(&self, milliseconds: u64);
+
+    /// Called when AML stores a value to the `Debug` object, with a rendering of the value. Firmware uses this to
+    /// print diagnostics, so hosts may want to show them somewhere. By default, they're logged with the `log`
+    /// crate.
+    fn handle_debug(&self, message: &str) {
+        info!("AML debug: {}", message);
+    }
+
     fn handle_fatal_error(&self, fatal_type: u8, fatal_code: u32, fatal_arg: u64) {
         panic!("Fatal error while executing AML (encountered DefFatal op). fatal_type = {:?}, fatal_code = {:?}, fatal_arg = {:?}", fatal_type, fatal_code, fatal_arg);
     }
+
+    /// Find a table in the RSDT/XSDT with the given signature, OEM ID, and OEM table ID, for the AML `LoadTable`
+    /// operator. The table should be returned in its entirety, including its header. The IDs are empty if AML
+    /// doesn't specify them, in which case they match any table. By default, no tables are found.
+    fn find_table(&self, _signature: &str, _oem_id: &str, _oem_table_id: &str) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Handles accesses to the operation regions of an address space, for address spaces that are accessed through
+/// another device or protocol (e.g. the embedded controller). Installed with
+/// [`AmlContext::install_region_handler`].
+pub trait RegionHandler: Send + Sync {
+    /// Read `length` bits from `address`, which is the offset of the access in the address space (i.e. the offset
+    /// of the region plus the offset of the access within the region).
+    fn read(&self, address: u64, length: u64) -> Result<u64, AmlError>;
+    /// Write the low `length` bits of `value` to `address`.
+    fn write(&self, address: u64, length: u64, value: u64) -> Result<(), AmlError>;
 }
 
 /// Used when an [`AmlContext`] encounters an error.
@@ -720,11 +1156,17 @@ pub enum AmlError {
     WrongParser,
     /// Returned when a `DefFatal` op is encountered. This is separately reported using [`Handler::handle_fatal_error`].
     FatalError,
+    /// A `Stall` was for longer than the 255 microseconds we allow.
+    InvalidStallTime(u64),
 
     /*
      * Errors produced manipulating AML names.
      */
     EmptyNamesAreInvalid,
+    /// Produced when a pattern passed to `Namespace::glob` isn't an absolute path, or has an invalid segment.
+    InvalidGlobPattern,
+    /// Produced when a debugger installed with `AmlContext::install_debugger` stops execution.
+    DebuggerAbort,
     /// Produced when trying to normalize a path that does not point to a valid level of the
     /// namespace. E.g. `\_SB.^^PCI0` goes above the root of the namespace. The contained value is the name that
     /// normalization was attempted upon.
@@ -738,6 +1180,9 @@ pub enum AmlError {
     /// `AmlName` is the name of the entire sub-level/value.
     LevelDoesNotExist(AmlName),
     ValueDoesNotExist(AmlName),
+    /// Produced when a handle doesn't refer to an object in the namespace (e.g. because the object has been
+    /// removed).
+    HandleDoesNotExist(AmlHandle),
     /// Produced when two values with the same name are added to the namespace.
     NameCollision(AmlName),
     TriedToRemoveRootNamespace,
@@ -759,10 +1204,39 @@ pub enum AmlError {
     BreakInInvalidPosition,
     /// A `DefContinue` operation was performed outside of a `DefWhile`.
     ContinueInInvalidPosition,
+    /// Produced when a mutex is acquired while a mutex with a higher sync level is held, or released while a mutex
+    /// with a higher sync level is still held.
+    MutexSyncLevelTooLow {
+        mutex_level: u8,
+        current_level: u8,
+    },
+    /// Produced when a mutex is released that the executing AML doesn't hold.
+    MutexNotAcquired,
+    /// The Global Lock couldn't be acquired to access a field with the `Lock` rule.
+    GlobalLockNotAcquired,
+    /// The [`SyncHandler`] failed to acquire a serialized method that's being executed by another evaluation.
+    SerializedMethodNotAcquired(AmlName),
+    /// Produced when AML exceeds one of the context's [`ExecutionLimits`] (e.g. a `While` loop doesn't end).
+    FuelExhausted,
+
+    /*
+     * Errors produced reading a snapshot of the namespace.
+     */
+    /// The snapshot passed to [`Snapshot::from_bytes`](snapshot::Snapshot::from_bytes) is truncated, or is from an
+    /// unsupported version.
+    InvalidSnapshot,
+    /// Produced when a table loaded with `Load` or `LoadTable` has an invalid header or checksum.
+    InvalidDefinitionBlock,
+    /// Produced when `Unload` is performed on a handle that doesn't refer to a loaded definition block.
+    InvalidDdbHandle,
+    /// Produced when `Notify` is performed on an object that isn't a device, processor, or thermal zone.
+    InvalidNotifyObject(AmlName),
 
     /*
      * Errors produced parsing the PCI routing tables (_PRT objects).
      */
+    /// Produced when an entry of a PRT is not a package of four elements.
+    PrtInvalidEntry,
     PrtInvalidAddress,
     PrtInvalidPin,
     PrtInvalidSource,
@@ -776,6 +1250,8 @@ pub enum AmlError {
     ReservedResourceType,
     ResourceDescriptorTooShort,
     ResourceDescriptorTooLong,
+    /// Produced when a field of a resource descriptor has a reserved or invalid value.
+    InvalidResourceDescriptor,
     UnexpectedResourceType,
 
     /*
@@ -786,17 +1262,54 @@ pub enum AmlError {
         target: AmlType,
     },
     InvalidStatusObject,
+    /// Produced when one of the objects that describes a device (`_SUB`, `_STR`, `_PLD`, or `_CLS`) has the wrong
+    /// type, or a value that can't be decoded.
+    InvalidDeviceObject(AmlName),
     InvalidShiftLeft,
     InvalidShiftRight,
     FieldRegionIsNotOpRegion,
     FieldInvalidAddress,
     FieldInvalidAccessSize,
+    /// Produced when a field in an operation region is accessed, but the interpreter can't access the region's
+    /// address space itself, and no [`RegionHandler`] has been installed for it.
+    NoRegionHandler(RegionSpace),
+    /// Produced by a [`RegionHandler`] when the device backing the region failed to complete the access.
+    RegionAccessFailed(RegionSpace),
+    /// Produced when a `PciConfig` region is read before its PCI address has been resolved, and it can't be
+    /// resolved without evaluating methods (see [`AmlContext::initialize_objects`]).
+    PciAddressNotResolved,
+    /// Produced when a field of a `GeneralPurposeIo` or `GenericSerialBus` region is accessed, but no
+    /// `Connection` precedes it in its field list.
+    FieldNotConnected,
+    /// Produced when a field of a `GenericSerialBus` region is accessed, but no `AccessAs` in its field list sets
+    /// the protocol to access it with.
+    FieldNoAccessAttrib,
     TypeCannotBeCompared(AmlType),
     /// Produced when the `Mid` operator is applied to a value of a type other than `Buffer` or `String`.
     TypeCannotBeSliced(AmlType),
+    /// Produced when converting a buffer to a string would produce a string longer than
+    /// [`value::convert::MAX_STRING_CONVERSION`].
+    StringConversionTooLong,
+    /// Produced when `ToBCD` is applied to an integer with more than 16 decimal digits, or `FromBCD` to a value
+    /// with a nibble that isn't a decimal digit.
+    InvalidBcdValue(u64),
+    /// Produced when the `Match` operator is given a match opcode greater than `5` (`MGT`).
+    InvalidMatchOpcode(u8),
+    /// Produced when the start index of a `Match` is past the end of the package being searched.
+    MatchStartIndexOutOfBounds,
     TypeCannotBeWrittenToBufferField(AmlType),
     BufferFieldIndexesOutOfBounds,
 
+    /*
+     * Errors produced building AML with the `build` module.
+     */
+    /// Produced when the string passed to `build::EisaId` isn't a valid PNP ID.
+    InvalidEisaId,
+    /// Produced when a `build::Method` has more than 7 arguments, or a sync level greater than 15.
+    InvalidMethodFlags,
+    /// Produced when a `build::Package` has more than 255 elements.
+    TooManyPackageElements,
+
     /// Unimplemented functionality - return error rather than abort
     Unimplemented,
 }
@@ -811,4 +1324,248 @@ mod tests {
         fn test_send_sync<T: Send + Sync>() {}
         test_send_sync::<AmlContext>();
     }
+
+    #[test]
+    fn test_static_table_methods() {
+        // Method (TEST) { Return (5) }
+        static TABLE: [u8; 10] = [0x14, 0x09, b'T', b'E', b'S', b'T', 0x00, 0xa4, 0x0a, 0x05];
+
+        let mut context = crate::test_utils::make_test_context();
+        context.parse_static_table(&TABLE).unwrap();
+
+        // The method's body should refer to the table, rather than a copy of it
+        let path = AmlName::from_str("\\TEST").unwrap();
+        match context.namespace.get_by_path(&path).unwrap() {
+            AmlValue::Method { code: value::MethodCode::Aml(code), .. } => {
+                assert_eq!(code.as_bytes().as_ptr(), TABLE[7..].as_ptr());
+                assert_eq!(code.as_bytes(), &TABLE[7..]);
+            }
+            other => panic!("Expected a method, got {:?}", other),
+        }
+        assert!(matches!(context.invoke_method(&path, Args::EMPTY), Ok(AmlValue::Integer(5))));
+    }
+
+    #[test]
+    fn test_execution_limits() {
+        let mut context = crate::test_utils::make_test_context();
+
+        /*
+         * Method (LOOP) { While (One) {} }
+         * Method (RECR) { RECR () }
+         * Method (NOPS) { Noop Noop Noop Noop }
+         */
+        context
+            .parse_table(&[
+                0x14, 0x09, b'L', b'O', b'O', b'P', 0x00, 0xa2, 0x02, 0x01, 0x14, 0x0a, b'R', b'E', b'C', b'R',
+                0x00, b'R', b'E', b'C', b'R', 0x14, 0x0a, b'N', b'O', b'P', b'S', 0x00, 0xa3, 0xa3, 0xa3, 0xa3,
+            ])
+            .unwrap();
+        let invoke =
+            |context: &mut AmlContext, name| context.invoke_method(&AmlName::from_str(name).unwrap(), Args::EMPTY);
+
+        context.set_execution_limits(ExecutionLimits {
+            max_loop_iterations: Some(100),
+            max_call_depth: Some(8),
+            max_ops: Some(4),
+        });
+        assert_eq!(invoke(&mut context, "\\LOOP").err(), Some(AmlError::FuelExhausted));
+        assert_eq!(invoke(&mut context, "\\RECR").err(), Some(AmlError::FuelExhausted));
+        assert!(matches!(invoke(&mut context, "\\NOPS"), Ok(AmlValue::Integer(0))));
+
+        context.set_execution_limits(ExecutionLimits { max_ops: Some(3), ..ExecutionLimits::default() });
+        assert_eq!(invoke(&mut context, "\\NOPS").err(), Some(AmlError::FuelExhausted));
+
+        // The default limits stop unbounded recursion before it overflows the stack
+        context.set_execution_limits(ExecutionLimits::default());
+        assert_eq!(invoke(&mut context, "\\RECR").err(), Some(AmlError::FuelExhausted));
+    }
+
+    #[test]
+    fn test_failed_method_restores_state() {
+        let mut context = crate::test_utils::make_test_context();
+        let fail = AmlName::from_str("\\FAIL").unwrap();
+        context
+            .namespace
+            .add_value(fail.clone(), AmlValue::native_method(0, true, 0, |_| Err(AmlError::InvalidArgAccess(0))))
+            .unwrap();
+
+        assert_eq!(context.invoke_method(&fail, Args::EMPTY).err(), Some(AmlError::InvalidArgAccess(0)));
+        assert!(context.held_mutexes.is_empty());
+        assert!(context.method_context.is_none());
+        assert_eq!(context.current_scope, AmlName::root());
+    }
+
+    #[test]
+    fn test_region_handler() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+
+        struct TestRegionHandler(AtomicU64);
+
+        impl RegionHandler for TestRegionHandler {
+            fn read(&self, address: u64, _length: u64) -> Result<u64, AmlError> {
+                Ok(address)
+            }
+
+            fn write(&self, address: u64, _length: u64, value: u64) -> Result<(), AmlError> {
+                self.0.store(address + value, Ordering::Relaxed);
+                Ok(())
+            }
+        }
+
+        let mut context = crate::test_utils::make_test_context();
+        let region = context
+            .namespace
+            .add_value(
+                AmlName::from_str("\\REG0").unwrap(),
+                AmlValue::OpRegion {
+                    region: RegionSpace::EmbeddedControl,
+                    offset: 0x10,
+                    length: 0x10,
+                    parent_device: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            context.read_region(region, 0x4, 8),
+            Err(AmlError::NoRegionHandler(RegionSpace::EmbeddedControl))
+        );
+
+        context
+            .install_region_handler(RegionSpace::EmbeddedControl, Box::new(TestRegionHandler(AtomicU64::new(0))));
+        assert_eq!(context.read_region(region, 0x4, 8), Ok(0x14));
+        assert_eq!(context.write_region(region, 0x4, 8, 0x100), Ok(()));
+        assert!(context.remove_region_handler(RegionSpace::EmbeddedControl).is_some());
+    }
+
+    #[test]
+    fn test_initialize_objects() {
+        struct NullRegionHandler;
+
+        impl RegionHandler for NullRegionHandler {
+            fn read(&self, _address: u64, _length: u64) -> Result<u64, AmlError> {
+                Ok(0)
+            }
+
+            fn write(&self, _address: u64, _length: u64, _value: u64) -> Result<(), AmlError> {
+                Ok(())
+            }
+        }
+
+        let mut context = crate::test_utils::make_test_context();
+
+        /*
+         * Name (INIC, 0)
+         * Device (DEV0) {
+         *     Method (_INI) { Increment (INIC) }
+         *     Device (DEV1) {
+         *         Name (_STA, 0)
+         *         Device (DEV2) {
+         *             Method (_INI) { Store (0xff, INIC) }
+         *         }
+         *     }
+         * }
+         *
+         * Device (EC0_) {
+         *     OperationRegion (ECOR, EmbeddedControl, 0, 0xff)
+         *     Name (REGC, 5)
+         *     Method (_REG, 2) { Store (Arg1, REGC) }
+         * }
+         * Device (SIO_) {
+         *     OperationRegion (SIOR, SystemIO, 0x2e, 2)
+         *     Name (REGC, 5)
+         *     Method (_REG, 2) { Store (Arg1, REGC) }
+         * }
+         */
+        context
+            .parse_table(&[
+                0x08, b'I', b'N', b'I', b'C', 0x00, 0x5b, 0x82, 0x33, b'D', b'E', b'V', b'0', 0x14, 0x0b, b'_',
+                b'I', b'N', b'I', 0x00, 0x75, b'I', b'N', b'I', b'C', 0x5b, 0x82, 0x20, b'D', b'E', b'V', b'1',
+                0x08, b'_', b'S', b'T', b'A', 0x00, 0x5b, 0x82, 0x13, b'D', b'E', b'V', b'2', 0x14, 0x0d, b'_',
+                b'I', b'N', b'I', 0x00, 0x70, 0x0a, 0xff, b'I', b'N', b'I', b'C', 0x5b, 0x82, 0x23, b'E', b'C',
+                b'0', b'_', 0x5b, 0x80, b'E', b'C', b'O', b'R', 0x03, 0x00, 0x0a, 0xff, 0x08, b'R', b'E', b'G',
+                b'C', 0x0a, 0x05, 0x14, 0x0c, b'_', b'R', b'E', b'G', 0x02, 0x70, 0x69, b'R', b'E', b'G', b'C',
+                0x5b, 0x82, 0x24, b'S', b'I', b'O', b'_', 0x5b, 0x80, b'S', b'I', b'O', b'R', 0x01, 0x0a, 0x2e,
+                0x0a, 0x02, 0x08, b'R', b'E', b'G', b'C', 0x0a, 0x05, 0x14, 0x0c, b'_', b'R', b'E', b'G', 0x02,
+                0x70, 0x69, b'R', b'E', b'G', b'C',
+            ])
+            .unwrap();
+        let integer = |context: &AmlContext, name: &str| {
+            context.namespace.get_by_path(&AmlName::from_str(name).unwrap()).unwrap().as_integer(context).unwrap()
+        };
+
+        // `_REG` methods aren't run until the objects are initialized
+        context.install_region_handler(RegionSpace::SMBus, Box::new(NullRegionHandler));
+        assert_eq!(integer(&context, "\\SIO_.REGC"), 5);
+
+        context.initialize_objects().unwrap();
+        assert_eq!(integer(&context, "\\INIC"), 1);
+        assert_eq!(integer(&context, "\\SIO_.REGC"), 1);
+        assert_eq!(integer(&context, "\\EC0_.REGC"), 5);
+
+        context.install_region_handler(RegionSpace::EmbeddedControl, Box::new(NullRegionHandler));
+        assert_eq!(integer(&context, "\\EC0_.REGC"), 1);
+        context.remove_region_handler(RegionSpace::EmbeddedControl);
+        assert_eq!(integer(&context, "\\EC0_.REGC"), 0);
+    }
+
+    fn make_ssdt(aml: &[u8]) -> Vec<u8> {
+        let mut table = alloc::vec![0; 36];
+        table[0..4].copy_from_slice(b"SSDT");
+        table[4..8].copy_from_slice(&((36 + aml.len()) as u32).to_le_bytes());
+        table.extend_from_slice(aml);
+        table[9] = 0u8.wrapping_sub(table.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)));
+        table
+    }
+
+    #[test]
+    fn test_load_table() {
+        let mut context = crate::test_utils::make_test_context();
+
+        /*
+         * Scope (\_SB) { Device (DEV0) { Name (_ADR, 0) } }
+         * Name (FOO, 5)
+         */
+        let ssdt = make_ssdt(&[
+            0x10, 0x13, b'\\', b'_', b'S', b'B', b'_', 0x5b, 0x82, 0x0b, b'D', b'E', b'V', b'0', 0x08, b'_', b'A',
+            b'D', b'R', 0x00, 0x08, b'F', b'O', b'O', b'_', 0x0a, 0x05,
+        ]);
+        context.namespace.add_value(AmlName::from_str("\\TBL_").unwrap(), AmlValue::buffer(ssdt.clone())).unwrap();
+        context.namespace.add_value(AmlName::from_str("\\HNDL").unwrap(), AmlValue::zero()).unwrap();
+
+        // Load (TBL, HNDL)
+        context.parse_table(&[0x5b, 0x20, b'T', b'B', b'L', b'_', b'H', b'N', b'D', b'L']).unwrap();
+        assert!(matches!(
+            context.namespace.get_by_path(&AmlName::from_str("\\FOO_").unwrap()),
+            Ok(AmlValue::Integer(5))
+        ));
+        assert!(context.namespace.get_by_path(&AmlName::from_str("\\_SB.DEV0._ADR").unwrap()).is_ok());
+        assert!(matches!(
+            context.namespace.get_by_path(&AmlName::from_str("\\HNDL").unwrap()),
+            Ok(AmlValue::Integer(1))
+        ));
+
+        // Unload (HNDL)
+        context.parse_table(&[0x5b, 0x2a, b'H', b'N', b'D', b'L']).unwrap();
+        assert!(context.namespace.get_by_path(&AmlName::from_str("\\FOO_").unwrap()).is_err());
+        assert!(context.namespace.get_by_path(&AmlName::from_str("\\_SB.DEV0").unwrap()).is_err());
+        let mut levels = Vec::new();
+        context
+            .namespace
+            .traverse(|name, _| {
+                levels.push(name.clone());
+                Ok(true)
+            })
+            .unwrap();
+        assert!(levels.contains(&AmlName::from_str("\\_SB").unwrap()));
+        assert!(!levels.contains(&AmlName::from_str("\\_SB.DEV0").unwrap()));
+
+        // The same definitions can be loaded again, and a table with a bad checksum is rejected
+        let handle = context.load_table(&ssdt).unwrap();
+        let mut corrupted = ssdt.clone();
+        corrupted[9] = corrupted[9].wrapping_add(1);
+        assert_eq!(context.load_table(&corrupted), Err(AmlError::InvalidDefinitionBlock));
+        assert_eq!(context.unload_table(handle), Ok(()));
+        assert_eq!(context.unload_table(handle), Err(AmlError::InvalidDdbHandle));
+    }
 }
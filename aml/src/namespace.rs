@@ -2,9 +2,11 @@ use crate::{name_object::NameSeg, value::AmlValue, AmlError};
 use alloc::{
     collections::BTreeMap,
     string::{String, ToString},
+    vec,
     vec::Vec,
 };
-use core::fmt;
+use core::{fmt, mem};
+use spinning_top::Spinlock;
 
 /// A handle is used to refer to an AML value without actually borrowing it until you need to
 /// access it (this makes borrowing situation much easier as you only have to consider who's
@@ -52,8 +54,17 @@ impl NamespaceLevel {
     }
 }
 
-#[derive(Clone)]
+/// The namespace of objects defined by the AML tables, and created as AML is executed. The namespace is shared
+/// between the contexts that evaluate AML concurrently (see [`AmlContext::fork`](crate::AmlContext::fork)), so it
+/// synchronizes access to itself: each method only locks it for its own duration, and so values are returned as
+/// copies, rather than borrowed from it. Copies are cheap, as the contents of buffers, packages, and methods are
+/// shared between them.
 pub struct Namespace {
+    inner: Spinlock<NamespaceInner>,
+}
+
+#[derive(Clone)]
+struct NamespaceInner {
     /// This is a running count of ids, which are never reused. This is incremented every time we
     /// add a new object to the namespace. We can then remove objects, freeing their memory, without
     /// risking using the same id for two objects.
@@ -68,26 +79,30 @@ pub struct Namespace {
     /// recursively inside this structure. It holds handles to references, which need to be indexed into
     /// `object_map` to acctually access the object.
     root: NamespaceLevel,
+
+    /// While a definition block is being loaded, this records the levels and values that are created, so they can
+    /// be removed if the table is unloaded.
+    created_objects: Option<Vec<CreatedObject>>,
 }
 
-impl Namespace {
-    pub fn new() -> Namespace {
-        Namespace {
+/// A level or value created in the namespace while its creation was being recorded.
+#[derive(Clone, Debug)]
+pub(crate) enum CreatedObject {
+    Level(AmlName),
+    Value(AmlName),
+}
+
+impl NamespaceInner {
+    fn new() -> NamespaceInner {
+        NamespaceInner {
             next_handle: AmlHandle(0),
             object_map: BTreeMap::new(),
             root: NamespaceLevel::new(LevelType::Scope),
+            created_objects: None,
         }
     }
 
-    /// Add a new level to the namespace. A "level" is named by a single `NameSeg`, and can contain values, and
-    /// also other further sub-levels. Once a level has been created, AML values can be added to it with
-    /// `add_value`.
-    ///
-    /// ### Note
-    /// At first glance, you might expect `DefDevice` to add a value of type `Device`. However, because all
-    /// `Devices` do is hold other values, we model them as namespace levels, and so they must be created
-    /// accordingly.
-    pub fn add_level(&mut self, path: AmlName, typ: LevelType) -> Result<(), AmlError> {
+    fn add_level(&mut self, path: AmlName, typ: LevelType) -> Result<(), AmlError> {
         assert!(path.is_absolute());
         let path = path.normalize()?;
 
@@ -105,13 +120,14 @@ impl Namespace {
              */
             if !level.children.contains_key(&last_seg) {
                 level.children.insert(last_seg, NamespaceLevel::new(typ));
+                self.record(CreatedObject::Level(path));
             }
         }
 
         Ok(())
     }
 
-    pub fn remove_level(&mut self, path: AmlName) -> Result<(), AmlError> {
+    fn remove_level(&mut self, path: AmlName) -> Result<(), AmlError> {
         assert!(path.is_absolute());
         let path = path.normalize()?;
 
@@ -127,10 +143,7 @@ impl Namespace {
         }
     }
 
-    /// Add a value to the namespace at the given path, which must be a normalized, absolute AML
-    /// name. If you want to add at a path relative to a given scope, use `add_at_resolved_path`
-    /// instead.
-    pub fn add_value(&mut self, path: AmlName, value: AmlValue) -> Result<AmlHandle, AmlError> {
+    fn add_value(&mut self, path: AmlName, value: AmlValue) -> Result<AmlHandle, AmlError> {
         assert!(path.is_absolute());
         let path = path.normalize()?;
 
@@ -140,15 +153,29 @@ impl Namespace {
 
         let (level, last_seg) = self.get_level_for_path_mut(&path)?;
         match level.values.insert(last_seg, handle) {
-            None => Ok(handle),
+            None => {
+                self.record(CreatedObject::Value(path));
+                Ok(handle)
+            }
             Some(_) => Err(AmlError::NameCollision(path)),
         }
     }
 
-    /// Helper method for adding a value to the namespace at a path that is relative to the given
-    /// scope. This operation involves a lot of error handling in parts of the parser, so is
-    /// encapsulated here.
-    pub fn add_value_at_resolved_path(
+    fn remove_value(&mut self, path: AmlName) -> Result<(), AmlError> {
+        assert!(path.is_absolute());
+        let path = path.normalize()?;
+
+        let (level, last_seg) = self.get_level_for_path_mut(&path)?;
+        let handle = level.values.remove(&last_seg).ok_or(AmlError::ValueDoesNotExist(path))?;
+
+        // Aliases share the handle of the value they refer to, so the value is only freed once nothing uses it
+        if !self.handle_is_referenced(&self.root, handle) {
+            self.object_map.remove(&handle);
+        }
+        Ok(())
+    }
+
+    fn add_value_at_resolved_path(
         &mut self,
         path: AmlName,
         scope: &AmlName,
@@ -157,9 +184,7 @@ impl Namespace {
         self.add_value(path.resolve(scope)?, value)
     }
 
-    /// Add an alias for an existing name. The alias will refer to the same value as the original,
-    /// and the fact that the alias exists is forgotten.
-    pub fn add_alias_at_resolved_path(
+    fn add_alias_at_resolved_path(
         &mut self,
         path: AmlName,
         scope: &AmlName,
@@ -172,38 +197,65 @@ impl Namespace {
         
         let (level, last_seg) = self.get_level_for_path_mut(&path)?;
         match level.values.insert(last_seg, handle) {
-            None => Ok(handle),
+            None => {
+                self.record(CreatedObject::Value(path));
+                Ok(handle)
+            }
             Some(_) => Err(AmlError::NameCollision(path)),
         }
     }
 
-    pub fn get(&self, handle: AmlHandle) -> Result<&AmlValue, AmlError> {
-        Ok(self.object_map.get(&handle).unwrap())
+    fn start_recording(&mut self) -> Option<Vec<CreatedObject>> {
+        self.created_objects.replace(Vec::new())
     }
 
-    pub fn get_mut(&mut self, handle: AmlHandle) -> Result<&mut AmlValue, AmlError> {
-        Ok(self.object_map.get_mut(&handle).unwrap())
+    fn finish_recording(&mut self, previous: Option<Vec<CreatedObject>>) -> Vec<CreatedObject> {
+        let created = mem::replace(&mut self.created_objects, previous).unwrap_or_default();
+        if let Some(ref mut previous) = self.created_objects {
+            previous.extend(created.iter().cloned());
+        }
+        created
     }
 
-    pub fn get_handle(&self, path: &AmlName) -> Result<AmlHandle, AmlError> {
-        let (level, last_seg) = self.get_level_for_path(path)?;
-        Ok(*level.values.get(&last_seg).ok_or(AmlError::ValueDoesNotExist(path.clone()))?)
+    fn remove_created_objects(&mut self, objects: Vec<CreatedObject>) {
+        for object in objects.into_iter().rev() {
+            let _ = match object {
+                CreatedObject::Level(path) => self.remove_level(path),
+                CreatedObject::Value(path) => self.remove_value(path),
+            };
+        }
     }
 
-    pub fn get_by_path(&self, path: &AmlName) -> Result<&AmlValue, AmlError> {
-        let handle = self.get_handle(path)?;
-        Ok(self.get(handle).unwrap())
+    fn record(&mut self, object: CreatedObject) {
+        if let Some(ref mut created_objects) = self.created_objects {
+            created_objects.push(object);
+        }
+    }
+
+    fn handle_is_referenced(&self, level: &NamespaceLevel, handle: AmlHandle) -> bool {
+        level.values.values().any(|&value| value == handle)
+            || level.children.values().any(|child| self.handle_is_referenced(child, handle))
     }
 
-    pub fn get_by_path_mut(&mut self, path: &AmlName) -> Result<&mut AmlValue, AmlError> {
+    fn get(&self, handle: AmlHandle) -> Result<&AmlValue, AmlError> {
+        self.object_map.get(&handle).ok_or(AmlError::HandleDoesNotExist(handle))
+    }
+
+    fn get_mut(&mut self, handle: AmlHandle) -> Result<&mut AmlValue, AmlError> {
+        self.object_map.get_mut(&handle).ok_or(AmlError::HandleDoesNotExist(handle))
+    }
+
+    fn get_handle(&self, path: &AmlName) -> Result<AmlHandle, AmlError> {
+        let (level, last_seg) = self.get_level_for_path(path)?;
+        Ok(*level.values.get(&last_seg).ok_or(AmlError::ValueDoesNotExist(path.clone()))?)
+    }
+
+    fn get_by_path(&self, path: &AmlName) -> Result<&AmlValue, AmlError> {
         let handle = self.get_handle(path)?;
-        Ok(self.get_mut(handle).unwrap())
+        self.get(handle)
     }
 
-    /// Search for an object at the given path of the namespace, applying the search rules described in §5.3 of the
-    /// ACPI specification, if they are applicable. Returns the resolved name, and the handle of the first valid
-    /// object, if found.
-    pub fn search(&self, path: &AmlName, starting_scope: &AmlName) -> Result<(AmlName, AmlHandle), AmlError> {
+    fn search(&self, path: &AmlName, starting_scope: &AmlName) -> Result<(AmlName, AmlHandle), AmlError> {
         if path.search_rules_apply() {
             /*
              * If search rules apply, we need to recursively look through the namespace. If the
@@ -260,7 +312,7 @@ impl Namespace {
         }
     }
 
-    pub fn search_for_level(&self, level_name: &AmlName, starting_scope: &AmlName) -> Result<AmlName, AmlError> {
+    fn search_for_level(&self, level_name: &AmlName, starting_scope: &AmlName) -> Result<AmlName, AmlError> {
         if level_name.search_rules_apply() {
             let mut scope = starting_scope.clone().normalize()?;
             assert!(scope.is_absolute());
@@ -331,10 +383,133 @@ impl Namespace {
         Ok((current_level, last_seg))
     }
 
+    /// Get the level at `path`, which must be a normalized, absolute AML name (and can be `\\`).
+    fn level(&self, path: &AmlName) -> Result<&NamespaceLevel, AmlError> {
+        if *path == AmlName::root() {
+            return Ok(&self.root);
+        }
+
+        let (level, last_seg) = self.get_level_for_path(path)?;
+        level.children.get(&last_seg).ok_or_else(|| AmlError::LevelDoesNotExist(path.clone()))
+    }
+}
+
+impl Clone for Namespace {
+    fn clone(&self) -> Namespace {
+        Namespace { inner: Spinlock::new(self.inner.lock().clone()) }
+    }
+}
+
+impl Namespace {
+    pub fn new() -> Namespace {
+        Namespace { inner: Spinlock::new(NamespaceInner::new()) }
+    }
+
+    /// Add a new level to the namespace. A "level" is named by a single `NameSeg`, and can contain values, and
+    /// also other further sub-levels. Once a level has been created, AML values can be added to it with
+    /// `add_value`.
+    ///
+    /// ### Note
+    /// At first glance, you might expect `DefDevice` to add a value of type `Device`. However, because all
+    /// `Devices` do is hold other values, we model them as namespace levels, and so they must be created
+    /// accordingly.
+    pub fn add_level(&self, path: AmlName, typ: LevelType) -> Result<(), AmlError> {
+        self.inner.lock().add_level(path, typ)
+    }
+
+    pub fn remove_level(&self, path: AmlName) -> Result<(), AmlError> {
+        self.inner.lock().remove_level(path)
+    }
+
+    /// Add a value to the namespace at the given path, which must be a normalized, absolute AML
+    /// name. If you want to add at a path relative to a given scope, use `add_at_resolved_path`
+    /// instead.
+    pub fn add_value(&self, path: AmlName, value: AmlValue) -> Result<AmlHandle, AmlError> {
+        self.inner.lock().add_value(path, value)
+    }
+
+    /// Remove the value at the given path, which must be a normalized, absolute AML name.
+    pub fn remove_value(&self, path: AmlName) -> Result<(), AmlError> {
+        self.inner.lock().remove_value(path)
+    }
+
+    /// Helper method for adding a value to the namespace at a path that is relative to the given
+    /// scope. This operation involves a lot of error handling in parts of the parser, so is
+    /// encapsulated here.
+    pub fn add_value_at_resolved_path(
+        &self,
+        path: AmlName,
+        scope: &AmlName,
+        value: AmlValue,
+    ) -> Result<AmlHandle, AmlError> {
+        self.inner.lock().add_value_at_resolved_path(path, scope, value)
+    }
+
+    /// Add an alias for an existing name. The alias will refer to the same value as the original,
+    /// and the fact that the alias exists is forgotten.
+    pub fn add_alias_at_resolved_path(
+        &self,
+        path: AmlName,
+        scope: &AmlName,
+        target: AmlName,
+    ) -> Result<AmlHandle, AmlError> {
+        self.inner.lock().add_alias_at_resolved_path(path, scope, target)
+    }
+
+    /// Start recording the levels and values created in the namespace. Returns the objects recorded by any
+    /// recording that was already in progress, which should be passed back to `finish_recording`.
+    pub(crate) fn start_recording(&self) -> Option<Vec<CreatedObject>> {
+        self.inner.lock().start_recording()
+    }
+
+    /// Finish a recording started with `start_recording`, returning the objects that were created during it.
+    pub(crate) fn finish_recording(&self, previous: Option<Vec<CreatedObject>>) -> Vec<CreatedObject> {
+        self.inner.lock().finish_recording(previous)
+    }
+
+    /// Remove recorded objects from the namespace, in the reverse of the order they were created in. Objects that
+    /// have already been removed (e.g. the locals of a method, or the values in a removed level) are skipped.
+    pub(crate) fn remove_created_objects(&self, objects: Vec<CreatedObject>) {
+        self.inner.lock().remove_created_objects(objects)
+    }
+
+    /// Get a copy of the value with the given handle.
+    pub fn get(&self, handle: AmlHandle) -> Result<AmlValue, AmlError> {
+        self.inner.lock().get(handle).cloned()
+    }
+
+    /// Replace the value with the given handle.
+    pub fn set(&self, handle: AmlHandle, value: AmlValue) -> Result<(), AmlError> {
+        *self.inner.lock().get_mut(handle)? = value;
+        Ok(())
+    }
+
+    pub fn get_handle(&self, path: &AmlName) -> Result<AmlHandle, AmlError> {
+        self.inner.lock().get_handle(path)
+    }
+
+    pub fn get_by_path(&self, path: &AmlName) -> Result<AmlValue, AmlError> {
+        self.inner.lock().get_by_path(path).cloned()
+    }
+
+    /// Search for an object at the given path of the namespace, applying the search rules described in §5.3 of the
+    /// ACPI specification, if they are applicable. Returns the resolved name, and the handle of the first valid
+    /// object, if found.
+    pub fn search(&self, path: &AmlName, starting_scope: &AmlName) -> Result<(AmlName, AmlHandle), AmlError> {
+        self.inner.lock().search(path, starting_scope)
+    }
+
+    pub fn search_for_level(&self, level_name: &AmlName, starting_scope: &AmlName) -> Result<AmlName, AmlError> {
+        self.inner.lock().search_for_level(level_name, starting_scope)
+    }
+
     /// Traverse the namespace, calling `f` on each namespace level. `f` returns a `Result<bool, AmlError>` -
     /// errors terminate the traversal and are propagated, and the `bool` on the successful path marks whether the
-    /// children of the level should also be traversed.
-    pub fn traverse<F>(&mut self, mut f: F) -> Result<(), AmlError>
+    /// children of the level should also be traversed. [`Namespace::iter_under`] is often easier to use.
+    ///
+    /// The traversal is of a copy of the levels of the namespace (but not the values in them), so `f` can use the
+    /// namespace, e.g. to evaluate the objects it finds.
+    pub fn traverse<F>(&self, mut f: F) -> Result<(), AmlError>
     where
         F: FnMut(&AmlName, &NamespaceLevel) -> Result<bool, AmlError>,
     {
@@ -353,12 +528,218 @@ impl Namespace {
             Ok(())
         }
 
-        if f(&AmlName::root(), &self.root)? {
-            traverse_level(&self.root, &AmlName::root(), &mut f)?;
+        let root = self.inner.lock().root.clone();
+        if f(&AmlName::root(), &root)? {
+            traverse_level(&root, &AmlName::root(), &mut f)?;
         }
 
         Ok(())
     }
+
+    /// Iterate over the levels and values under the level at `path`, depth-first. The values in each level come
+    /// before its sub-levels, and each sub-level comes before the objects within it. The level at `path` itself
+    /// isn't included. Unlike [`Namespace::traverse`], the walk can be stopped at any point. The namespace isn't
+    /// locked between steps, so objects added or removed during the walk may or may not be produced.
+    pub fn iter_under(&self, path: &AmlName) -> Result<NamespaceIter<'_>, AmlError> {
+        let path = path.resolve(&AmlName::root())?;
+        let frame = IterFrame::new(self.inner.lock().level(&path)?, path, 1);
+        Ok(NamespaceIter { namespace: self, stack: alloc::vec![frame], max_depth: usize::MAX })
+    }
+
+    /// Write a listing of every object in the namespace to `f`, with its type, and the value of data objects
+    /// (integers, strings, buffers, and packages). Sub-objects are indented beneath the object they're declared
+    /// in, and objects are listed in the order of their names, so a namespace always produces the same listing.
+    /// The levels holding the locals of methods being executed are left out.
+    pub fn dump(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        const INDENT_PER_LEVEL: usize = 4;
+
+        fn dump_level(
+            namespace: &NamespaceInner,
+            f: &mut impl fmt::Write,
+            level: &NamespaceLevel,
+            indent: usize,
+        ) -> fmt::Result {
+            /*
+             * Devices and the like have both a value, and a level holding their sub-objects, so the names of both
+             * are merged, to list each object once.
+             */
+            let mut names: Vec<NameSeg> = level.values.keys().chain(level.children.keys()).copied().collect();
+            names.sort();
+            names.dedup();
+
+            for name in names {
+                let child = level.children.get(&name).filter(|child| child.typ != LevelType::MethodLocals);
+                if child.is_none() && !level.values.contains_key(&name) {
+                    continue;
+                }
+
+                write!(f, "{:indent$}{}", "", name.as_str(), indent = indent)?;
+                match level.values.get(&name).map(|handle| &namespace.object_map[handle]) {
+                    Some(
+                        value @ (AmlValue::Boolean(_)
+                        | AmlValue::Integer(_)
+                        | AmlValue::String(_)
+                        | AmlValue::Buffer(_)
+                        | AmlValue::Package(_)),
+                    ) => writeln!(f, " {:?} = {}", value.type_of(), value)?,
+                    Some(value) => writeln!(f, " {:?}", value.type_of())?,
+                    None => writeln!(f, " {:?}", child.unwrap().typ)?,
+                }
+
+                if let Some(child) = child {
+                    dump_level(namespace, f, child, indent + INDENT_PER_LEVEL)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        let namespace = self.inner.lock();
+        writeln!(f, "\\")?;
+        dump_level(&namespace, f, &namespace.root, INDENT_PER_LEVEL)
+    }
+
+    /// Find the levels and values whose paths match the glob-style pattern `pattern`. The pattern is an absolute
+    /// path (e.g. `\_SB.PCI?.*._PRT`), in which each segment can contain `?` to match any single character and
+    /// `*` to match any number of characters, and a segment of `**` matches any number of segments. Segments
+    /// without wildcards are padded with `_`, as in AML names.
+    pub fn glob(&self, pattern: &str) -> Result<Vec<(AmlName, NamespaceItem)>, AmlError> {
+        let segments = match pattern.strip_prefix('\\') {
+            Some("") => return Ok(Vec::new()),
+            Some(path) => path
+                .split('.')
+                .map(|segment| {
+                    if segment.is_empty() || segment.contains(|c: char| !c.is_ascii()) {
+                        Err(AmlError::InvalidGlobPattern)
+                    } else if segment.contains(['*', '?']) {
+                        Ok(segment.to_string())
+                    } else if segment.len() <= 4 {
+                        Ok(alloc::format!("{:_<4}", segment))
+                    } else {
+                        Err(AmlError::InvalidGlobPattern)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => return Err(AmlError::InvalidGlobPattern),
+        };
+
+        // Without a `**`, nothing deeper than the pattern can match
+        let max_depth = if segments.iter().any(|segment| segment == "**") { usize::MAX } else { segments.len() };
+        Ok(self
+            .iter_under(&AmlName::root())?
+            .with_max_depth(max_depth)
+            .filter(|(name, _)| {
+                let name_segments = name.0[1..]
+                    .iter()
+                    .filter_map(|component| match component {
+                        NameComponent::Segment(seg) => Some(seg.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                glob_matches(&segments, &name_segments)
+            })
+            .collect())
+    }
+}
+/// An object in the namespace, produced by [`NamespaceIter`].
+#[derive(Clone, Debug)]
+pub enum NamespaceItem {
+    Level(LevelType),
+    Value(AmlHandle, AmlValue),
+}
+
+/// An iterator over the objects under a level of the namespace. Created by [`Namespace::iter_under`].
+pub struct NamespaceIter<'a> {
+    namespace: &'a Namespace,
+    stack: Vec<IterFrame>,
+    max_depth: usize,
+}
+
+/// The objects in a level that are yet to be produced, which are copied from the level when the iterator reaches
+/// it.
+struct IterFrame {
+    path: AmlName,
+    /// The depth of the objects in this level, relative to the level the iteration started at.
+    depth: usize,
+    values: vec::IntoIter<(NameSeg, AmlHandle)>,
+    children: vec::IntoIter<(NameSeg, LevelType)>,
+}
+
+impl IterFrame {
+    fn new(level: &NamespaceLevel, path: AmlName, depth: usize) -> IterFrame {
+        let values = level.values.iter().map(|(seg, handle)| (*seg, *handle)).collect::<Vec<_>>();
+        let children = level.children.iter().map(|(seg, child)| (*seg, child.typ)).collect::<Vec<_>>();
+        IterFrame { path, depth, values: values.into_iter(), children: children.into_iter() }
+    }
+}
+
+impl<'a> NamespaceIter<'a> {
+    /// Only produce objects up to `depth` levels below the level the iteration started at. A depth of `1` produces
+    /// only the values and levels directly within it.
+    pub fn with_max_depth(mut self, depth: usize) -> NamespaceIter<'a> {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl<'a> Iterator for NamespaceIter<'a> {
+    type Item = (AmlName, NamespaceItem);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            if frame.depth > self.max_depth {
+                self.stack.pop();
+                continue;
+            }
+
+            if let Some((seg, handle)) = frame.values.next() {
+                // Skip values that have been removed since the level was copied
+                if let Ok(value) = self.namespace.get(handle) {
+                    let name = AmlName::from_name_seg(seg).resolve(&frame.path).unwrap();
+                    return Some((name, NamespaceItem::Value(handle, value)));
+                }
+                continue;
+            }
+
+            if let Some((seg, typ)) = frame.children.next() {
+                let name = AmlName::from_name_seg(seg).resolve(&frame.path).unwrap();
+                let depth = frame.depth + 1;
+                if let Ok(level) = self.namespace.inner.lock().level(&name) {
+                    let frame = IterFrame::new(level, name.clone(), depth);
+                    self.stack.push(frame);
+                }
+                return Some((name, NamespaceItem::Level(typ)));
+            }
+
+            self.stack.pop();
+        }
+    }
+}
+
+/// Match the segments of a name against the segments of a glob pattern.
+fn glob_matches(pattern: &[String], name: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=name.len()).any(|skip| glob_matches(rest, &name[skip..])),
+        Some((first, rest)) => match name.split_first() {
+            Some((segment, name_rest)) => {
+                segment_matches(first.as_bytes(), segment.as_bytes()) && glob_matches(rest, name_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn segment_matches(pattern: &[u8], segment: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => segment.is_empty(),
+        Some((b'*', rest)) => (0..=segment.len()).any(|skip| segment_matches(rest, &segment[skip..])),
+        Some((&c, rest)) => match segment.split_first() {
+            Some((&s, segment_rest)) => (c == b'?' || c == s) && segment_matches(rest, segment_rest),
+            None => false,
+        },
+    }
 }
 
 impl fmt::Debug for Namespace {
@@ -366,7 +747,7 @@ impl fmt::Debug for Namespace {
         const INDENT_PER_LEVEL: usize = 4;
 
         fn print_level(
-            namespace: &Namespace,
+            namespace: &NamespaceInner,
             f: &mut fmt::Formatter<'_>,
             level_name: &str,
             level: &NamespaceLevel,
@@ -392,7 +773,8 @@ impl fmt::Debug for Namespace {
             Ok(())
         }
 
-        print_level(self, f, "\\", &self.root, 0)
+        let namespace = self.inner.lock();
+        print_level(&namespace, f, "\\", &namespace.root, 0)
     }
 }
 
@@ -413,6 +795,10 @@ impl AmlName {
         AmlName(components)
     }
 
+    pub fn components(&self) -> &[NameComponent] {
+        &self.0
+    }
+
     /// Convert a string representation of an AML name into an `AmlName`.
     pub fn from_str(mut string: &str) -> Result<AmlName, AmlError> {
         if string.len() == 0 {
@@ -568,6 +954,7 @@ impl NameComponent {
 mod tests {
     use super::*;
     use crate::test_utils::crudely_cmp_values;
+    use alloc::vec;
 
     #[test]
     fn test_aml_name_from_str() {
@@ -662,7 +1049,7 @@ mod tests {
 
     #[test]
     fn test_namespace() {
-        let mut namespace = Namespace::new();
+        let namespace = Namespace::new();
 
         /*
          * This should succeed but do nothing.
@@ -703,19 +1090,19 @@ mod tests {
          * Get objects using their absolute paths.
          */
         assert!(crudely_cmp_values(
-            namespace.get_by_path(&AmlName::from_str("\\MOO").unwrap()).unwrap(),
+            &namespace.get_by_path(&AmlName::from_str("\\MOO").unwrap()).unwrap(),
             &AmlValue::Boolean(true)
         ));
         assert!(crudely_cmp_values(
-            namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.A").unwrap()).unwrap(),
+            &namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.A").unwrap()).unwrap(),
             &AmlValue::Integer(12345)
         ));
         assert!(crudely_cmp_values(
-            namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.B").unwrap()).unwrap(),
+            &namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.B").unwrap()).unwrap(),
             &AmlValue::Integer(6)
         ));
         assert!(crudely_cmp_values(
-            namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.C").unwrap()).unwrap(),
+            &namespace.get_by_path(&AmlName::from_str("\\FOO.BAR.C").unwrap()).unwrap(),
             &AmlValue::String(String::from("hello, world!"))
         ));
 
@@ -744,7 +1131,7 @@ mod tests {
 
     #[test]
     fn test_alias() {
-        let mut namespace = Namespace::new();
+        let namespace = Namespace::new();
 
         assert_eq!(namespace.add_level((AmlName::from_str("\\FOO")).unwrap(), LevelType::Scope), Ok(()));
 
@@ -771,7 +1158,7 @@ mod tests {
 
     #[test]
     fn test_get_level_for_path() {
-        let mut namespace = Namespace::new();
+        let namespace = Namespace::new();
 
         // Add some scopes
         assert_eq!(namespace.add_level(AmlName::from_str("\\FOO").unwrap(), LevelType::Scope), Ok(()));
@@ -781,13 +1168,93 @@ mod tests {
         assert_eq!(namespace.add_level(AmlName::from_str("\\FOO.BAR.BAZ.QUX").unwrap(), LevelType::Scope), Ok(()));
 
         {
+            let namespace = namespace.inner.lock();
             let (_, last_seg) =
                 namespace.get_level_for_path(&AmlName::from_str("\\FOO.BAR.BAZ").unwrap()).unwrap();
             assert_eq!(last_seg, NameSeg::from_str("BAZ").unwrap());
         }
         {
+            let namespace = namespace.inner.lock();
             let (_, last_seg) = namespace.get_level_for_path(&AmlName::from_str("\\FOO").unwrap()).unwrap();
             assert_eq!(last_seg, NameSeg::from_str("FOO").unwrap());
         }
     }
+
+    #[test]
+    fn test_dump() {
+        let namespace = Namespace::new();
+        let name = |name| AmlName::from_str(name).unwrap();
+        namespace.add_level(name("\\_SB"), LevelType::Scope).unwrap();
+        namespace.add_level(name("\\_SB.PCI0"), LevelType::Device).unwrap();
+        namespace.add_value(name("\\_SB.PCI0"), AmlValue::Device).unwrap();
+        namespace.add_value(name("\\_SB.PCI0._ADR"), AmlValue::Integer(0x1f0003)).unwrap();
+        namespace.add_value(name("\\_SB.PCI0._STR"), AmlValue::String("PCI".into())).unwrap();
+        let prw = AmlValue::package(vec![AmlValue::Integer(0xd), AmlValue::Integer(3)]);
+        namespace.add_value(name("\\_SB.PCI0._PRW"), prw).unwrap();
+        let sta = AmlValue::native_method(0, false, 0, |_| Ok(AmlValue::Integer(0xf)));
+        namespace.add_value(name("\\_SB.PCI0._STA"), sta).unwrap();
+        namespace.add_value(name("\\_GL"), AmlValue::Mutex { sync_level: 0 }).unwrap();
+        namespace.add_level(name("\\_SB.PCI0._STA"), LevelType::MethodLocals).unwrap();
+
+        let mut dump = String::new();
+        namespace.dump(&mut dump).unwrap();
+        assert_eq!(
+            dump.lines().collect::<Vec<_>>(),
+            vec![
+                "\\",
+                "    _GL_ Mutex",
+                "    _SB_ Scope",
+                "        PCI0 Device",
+                "            _ADR Integer = 0x1f0003",
+                "            _PRW Package = Package(0x2) { 0xd, 0x3 }",
+                "            _STA Method",
+                "            _STR String = \"PCI\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_under_and_glob() {
+        let namespace = Namespace::new();
+        let name = |name| AmlName::from_str(name).unwrap();
+
+        for level in ["\\_SB", "\\_SB.PCI0", "\\_SB.PCI0.GFX0", "\\_SB.PCI1", "\\_GPE"] {
+            assert_eq!(namespace.add_level(name(level), LevelType::Scope), Ok(()));
+        }
+        for value in ["\\_SB.PCI0._HID", "\\_SB.PCI0._PRT", "\\_SB.PCI0.GFX0._ADR", "\\_SB.PCI1._PRT"] {
+            assert!(namespace.add_value(name(value), AmlValue::Integer(0)).is_ok());
+        }
+
+        let paths = |items: Vec<(AmlName, NamespaceItem)>| {
+            items.into_iter().map(|(path, _)| path.as_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(namespace.iter_under(&name("\\_SB_")).unwrap().collect()),
+            vec![
+                "\\_SB_.PCI0",
+                "\\_SB_.PCI0._HID",
+                "\\_SB_.PCI0._PRT",
+                "\\_SB_.PCI0.GFX0",
+                "\\_SB_.PCI0.GFX0._ADR",
+                "\\_SB_.PCI1",
+                "\\_SB_.PCI1._PRT",
+            ]
+        );
+        assert_eq!(
+            paths(namespace.iter_under(&AmlName::root()).unwrap().with_max_depth(2).collect()),
+            vec!["\\_GPE", "\\_SB_", "\\_SB_.PCI0", "\\_SB_.PCI1"]
+        );
+        assert!(matches!(
+            namespace.iter_under(&AmlName::root()).unwrap().find(|(path, _)| *path == name("\\_SB_.PCI0._PRT")),
+            Some((_, NamespaceItem::Value(_, AmlValue::Integer(0))))
+        ));
+        assert_eq!(namespace.iter_under(&name("\\FOO")).err(), Some(AmlError::LevelDoesNotExist(name("\\FOO"))));
+
+        assert_eq!(paths(namespace.glob("\\_SB_.*._PRT").unwrap()), vec!["\\_SB_.PCI0._PRT", "\\_SB_.PCI1._PRT"]);
+        assert_eq!(paths(namespace.glob("\\_SB_.PCI?").unwrap()), vec!["\\_SB_.PCI0", "\\_SB_.PCI1"]);
+        assert_eq!(paths(namespace.glob("\\**._ADR").unwrap()), vec!["\\_SB_.PCI0.GFX0._ADR"]);
+        assert_eq!(paths(namespace.glob("\\_SB").unwrap()), vec!["\\_SB_"]);
+        assert_eq!(namespace.glob("_SB.*").err(), Some(AmlError::InvalidGlobPattern));
+        assert_eq!(namespace.glob("\\_SB_..PCI0").err(), Some(AmlError::InvalidGlobPattern));
+    }
 }
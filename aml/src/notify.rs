@@ -0,0 +1,176 @@
+//! Support for AML's `Notify` operator, which AML uses to tell the host about events concerning a device (e.g. a
+//! device being inserted or ejected, a battery's status changing, or a lid being opened). Hosts register handlers
+//! for the objects they're interested in with [`AmlContext::register_notify_handler`].
+//!
+//! Notifications are usually produced in the middle of evaluating AML (e.g. by the method that handles a GPE),
+//! when the host can't use the context to respond to them. Instead, they're queued, and dispatched to the handlers
+//! once the outermost evaluation (a method invoked by the host, or a table being parsed) has completed. Handlers
+//! are passed the context, so they can evaluate objects (e.g. `_STA` or `_BST`) in response.
+
+use crate::{name_object::Target, AmlContext, AmlError, AmlName, AmlValue};
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use log::trace;
+
+/// The objects a notify handler is registered for.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum NotifyTarget {
+    /// Receive the notifications for all objects.
+    All,
+    /// Receive the notifications for the object at this (absolute) path.
+    Object(AmlName),
+}
+
+/// A handler for notifications, which is called with the path of the object the notification is for, and the
+/// notification value.
+pub type NotifyHandler = Arc<dyn Fn(&mut AmlContext, &AmlName, u8) + Send + Sync>;
+
+/// The notifications queued by an evaluation of AML, which are dispatched to the registered handlers when it ends.
+#[derive(Default)]
+pub(crate) struct NotifyState {
+    pending: VecDeque<(AmlName, u8)>,
+    /// The number of evaluations of AML in progress. Notifications are only dispatched when this is zero.
+    evaluation_depth: usize,
+    dispatching: bool,
+}
+
+impl AmlContext {
+    /// Register `handler` to be called for notifications for `target`, replacing any handler already registered
+    /// for it. When a notification is dispatched, the handler registered for its object is called, followed by the
+    /// handler registered for [`NotifyTarget::All`].
+    pub fn register_notify_handler<F>(&mut self, target: NotifyTarget, handler: F)
+    where
+        F: Fn(&mut AmlContext, &AmlName, u8) + Send + Sync + 'static,
+    {
+        self.unregister_notify_handler(&target);
+        self.shared.notify_handlers.lock().push((target, Arc::new(handler)));
+    }
+
+    /// Remove the handler registered for `target`. Returns whether there was one.
+    pub fn unregister_notify_handler(&mut self, target: &NotifyTarget) -> bool {
+        let mut handlers = self.shared.notify_handlers.lock();
+        let count = handlers.len();
+        handlers.retain(|(handler_target, _)| handler_target != target);
+        handlers.len() != count
+    }
+
+    /// Queue a notification produced by `Notify`, for the object referred to by `target`. It's dispatched once the
+    /// current evaluation has completed.
+    pub(crate) fn queue_notification(&mut self, target: &Target, value: u64) -> Result<(), AmlError> {
+        let (path, handle) = match target {
+            Target::Name(name) => self.namespace.search(name, &self.current_scope)?,
+            _ => return Err(AmlError::Unimplemented),
+        };
+
+        match self.namespace.get(handle)? {
+            AmlValue::Device | AmlValue::Processor { .. } | AmlValue::ThermalZone => (),
+            _ => return Err(AmlError::InvalidNotifyObject(path)),
+        }
+
+        // Notification values are defined to be bytes, so only the low byte is used
+        self.notify.pending.push_back((path, value as u8));
+        Ok(())
+    }
+
+    /// Mark that an evaluation of AML has started. Notifications aren't dispatched until it has ended.
+    pub(crate) fn begin_evaluation(&mut self) {
+        self.notify.evaluation_depth += 1;
+    }
+
+    /// Mark that an evaluation of AML has ended, and dispatch any queued notifications if it was the outermost
+    /// one.
+    pub(crate) fn end_evaluation(&mut self) {
+        self.notify.evaluation_depth -= 1;
+        if self.notify.evaluation_depth == 0 {
+            self.dispatch_notifications();
+        }
+    }
+
+    fn dispatch_notifications(&mut self) {
+        /*
+         * Handlers can evaluate AML, which can queue more notifications. These are dispatched by the outermost
+         * call, after the notifications already queued, rather than re-entering the handlers.
+         */
+        if self.notify.dispatching {
+            return;
+        }
+        self.notify.dispatching = true;
+
+        while let Some((object, value)) = self.notify.pending.pop_front() {
+            let handlers: Vec<NotifyHandler> = {
+                let registered = self.shared.notify_handlers.lock();
+                [NotifyTarget::Object(object.clone()), NotifyTarget::All]
+                    .iter()
+                    .filter_map(|target| registered.iter().find(|(handler_target, _)| handler_target == target))
+                    .map(|(_, handler)| handler.clone())
+                    .collect()
+            };
+
+            if handlers.is_empty() {
+                trace!("No handler for notification {:#x} for {}", value, object);
+            }
+            for handler in handlers {
+                handler(self, &object, value);
+            }
+        }
+
+        self.notify.dispatching = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, value::Args};
+    use spinning_top::Spinlock;
+
+    #[test]
+    fn test_notify() {
+        let mut context = make_test_context();
+        let notifications = Arc::new(Spinlock::new(Vec::new()));
+
+        /*
+         * Device (DEV0) {}
+         * Device (DEV1) {}
+         * Method (NTFY) {
+         *     Notify (DEV0, 0x80)
+         *     Notify (DEV1, 0x01)
+         * }
+         */
+        context
+            .parse_table(&[
+                0x5b, 0x82, 0x05, b'D', b'E', b'V', b'0', 0x5b, 0x82, 0x05, b'D', b'E', b'V', b'1', 0x14, 0x13,
+                b'N', b'T', b'F', b'Y', 0x00, 0x86, b'D', b'E', b'V', b'0', 0x0a, 0x80, 0x86, b'D', b'E', b'V',
+                b'1', 0x01,
+            ])
+            .unwrap();
+
+        let dev0_notifications = notifications.clone();
+        context.register_notify_handler(
+            NotifyTarget::Object(AmlName::from_str("\\DEV0").unwrap()),
+            move |context, object, value| {
+                // Handlers can evaluate AML, but the notifications it produces are dispatched afterwards
+                if dev0_notifications.lock().is_empty() {
+                    context.invoke_method(&AmlName::from_str("\\NTFY").unwrap(), Args::EMPTY).unwrap();
+                }
+                dev0_notifications.lock().push((object.as_string(), value));
+            },
+        );
+        let all_notifications = notifications.clone();
+        context.register_notify_handler(NotifyTarget::All, move |_, object, value| {
+            all_notifications.lock().push((object.as_string() + " (all)", value));
+        });
+
+        context.invoke_method(&AmlName::from_str("\\NTFY").unwrap(), Args::EMPTY).unwrap();
+        assert_eq!(
+            *notifications.lock(),
+            [
+                ("\\DEV0".into(), 0x80),
+                ("\\DEV0 (all)".into(), 0x80),
+                ("\\DEV1 (all)".into(), 0x01),
+                ("\\DEV0".into(), 0x80),
+                ("\\DEV0 (all)".into(), 0x80),
+                ("\\DEV1 (all)".into(), 0x01),
+            ]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Configuration of the strings the interpreter claims to support when AML evaluates `\_OSI`.
+//!
+//! `\_OSI` was introduced by ACPI 3.0 to improve the situation created by `\_OS`. Unfortunately, exactly the same
+//! problem was immediately repeated by introducing capabilities reflecting that an ACPI implementation is exactly
+//! the same as a particular version of Windows' (e.g. firmwares will call `\_OSI("Windows 2001")`). Many firmwares
+//! take completely different code paths depending on which versions of Windows are claimed, so this is
+//! configurable with an [`OsiConfig`], which is installed with [`AmlContext::set_osi_config`].
+//!
+//! By default, we basically follow suit with whatever Linux does, as this will hopefully minimise breakage:
+//!    - We claim `Windows *` compatability, up to Windows 10 version 1903 (`"Windows 2019"`)
+//!    - We answer 'yes' to `_OSI("Darwin")`
+//!    - We answer 'no' to `_OSI("Linux")`, and report that the tables are doing the wrong thing
+
+use crate::{AmlContext, AmlError, AmlName, AmlValue};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+};
+use core::fmt;
+use log::warn;
+
+/// The versions of Windows that firmware can query with `_OSI`, in order of release.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum WindowsVersion {
+    /// Windows 2000
+    Windows2000,
+    /// Windows XP
+    Windows2001,
+    /// Windows XP SP1
+    Windows2001Sp1,
+    /// Windows XP SP2
+    Windows2001Sp2,
+    /// Windows Server 2003
+    Windows2001_1,
+    /// Windows Server 2003 SP1
+    Windows2001_1Sp1,
+    /// Windows Vista
+    Windows2006,
+    /// Windows Vista SP1
+    Windows2006Sp1,
+    /// Windows Vista SP2
+    Windows2006Sp2,
+    /// Windows Server 2008
+    Windows2006_1,
+    /// Windows 7 and Windows Server 2008 R2
+    Windows2009,
+    /// Windows 8 and Windows Server 2012
+    Windows2012,
+    /// Windows 8.1 and Windows Server 2012 R2
+    Windows2013,
+    /// Windows 10
+    Windows2015,
+    /// Windows 10 version 1607
+    Windows2016,
+    /// Windows 10 version 1703
+    Windows2017,
+    /// Windows 10 version 1709
+    Windows2017_2,
+    /// Windows 10 version 1803
+    Windows2018,
+    /// Windows 10 version 1809
+    Windows2018_2,
+    /// Windows 10 version 1903
+    Windows2019,
+    /// Windows 10 version 2004
+    Windows2020,
+    /// Windows 11
+    Windows2021,
+    /// Windows 11 version 22H2
+    Windows2022,
+}
+
+impl WindowsVersion {
+    pub const ALL: [WindowsVersion; 23] = [
+        WindowsVersion::Windows2000,
+        WindowsVersion::Windows2001,
+        WindowsVersion::Windows2001Sp1,
+        WindowsVersion::Windows2001Sp2,
+        WindowsVersion::Windows2001_1,
+        WindowsVersion::Windows2001_1Sp1,
+        WindowsVersion::Windows2006,
+        WindowsVersion::Windows2006Sp1,
+        WindowsVersion::Windows2006Sp2,
+        WindowsVersion::Windows2006_1,
+        WindowsVersion::Windows2009,
+        WindowsVersion::Windows2012,
+        WindowsVersion::Windows2013,
+        WindowsVersion::Windows2015,
+        WindowsVersion::Windows2016,
+        WindowsVersion::Windows2017,
+        WindowsVersion::Windows2017_2,
+        WindowsVersion::Windows2018,
+        WindowsVersion::Windows2018_2,
+        WindowsVersion::Windows2019,
+        WindowsVersion::Windows2020,
+        WindowsVersion::Windows2021,
+        WindowsVersion::Windows2022,
+    ];
+
+    /// The string firmware passes to `_OSI` to query this version.
+    pub fn osi_string(self) -> &'static str {
+        match self {
+            WindowsVersion::Windows2000 => "Windows 2000",
+            WindowsVersion::Windows2001 => "Windows 2001",
+            WindowsVersion::Windows2001Sp1 => "Windows 2001 SP1",
+            WindowsVersion::Windows2001Sp2 => "Windows 2001 SP2",
+            WindowsVersion::Windows2001_1 => "Windows 2001.1",
+            WindowsVersion::Windows2001_1Sp1 => "Windows 2001.1 SP1",
+            WindowsVersion::Windows2006 => "Windows 2006",
+            WindowsVersion::Windows2006Sp1 => "Windows 2006 SP1",
+            WindowsVersion::Windows2006Sp2 => "Windows 2006 SP2",
+            WindowsVersion::Windows2006_1 => "Windows 2006.1",
+            WindowsVersion::Windows2009 => "Windows 2009",
+            WindowsVersion::Windows2012 => "Windows 2012",
+            WindowsVersion::Windows2013 => "Windows 2013",
+            WindowsVersion::Windows2015 => "Windows 2015",
+            WindowsVersion::Windows2016 => "Windows 2016",
+            WindowsVersion::Windows2017 => "Windows 2017",
+            WindowsVersion::Windows2017_2 => "Windows 2017.2",
+            WindowsVersion::Windows2018 => "Windows 2018",
+            WindowsVersion::Windows2018_2 => "Windows 2018.2",
+            WindowsVersion::Windows2019 => "Windows 2019",
+            WindowsVersion::Windows2020 => "Windows 2020",
+            WindowsVersion::Windows2021 => "Windows 2021",
+            WindowsVersion::Windows2022 => "Windows 2022",
+        }
+    }
+
+    pub fn from_osi_string(string: &str) -> Option<WindowsVersion> {
+        WindowsVersion::ALL.iter().copied().find(|version| version.osi_string() == string)
+    }
+}
+
+/// A callback that decides whether to claim support for an `_OSI` string. It is called before the rest of the
+/// configuration is consulted, and returns `None` to defer to it.
+pub type OsiCallback = dyn Fn(&str) -> Option<bool> + Send + Sync;
+
+/// Configures the strings the interpreter claims to support when AML evaluates `\_OSI`. This is built with
+/// `OsiConfig::default()` (which matches the default behaviour of the interpreter) or `OsiConfig::none()`, and
+/// then the builder methods.
+#[derive(Clone)]
+pub struct OsiConfig {
+    windows: Option<WindowsVersion>,
+    darwin: bool,
+    linux: bool,
+    /// Strings that have been explicitly claimed or disclaimed. These override everything except the callback.
+    strings: BTreeMap<String, bool>,
+    callback: Option<Arc<OsiCallback>>,
+}
+
+impl OsiConfig {
+    /// A configuration that claims support for nothing, including the feature strings.
+    pub fn none() -> OsiConfig {
+        OsiConfig { windows: None, darwin: false, linux: false, strings: BTreeMap::new(), callback: None }
+    }
+
+    /// Claim compatibility with all versions of Windows up to, and including, `version`.
+    pub fn windows_up_to(mut self, version: WindowsVersion) -> OsiConfig {
+        self.windows = Some(version);
+        self
+    }
+
+    /// Don't claim compatibility with any version of Windows.
+    pub fn no_windows(mut self) -> OsiConfig {
+        self.windows = None;
+        self
+    }
+
+    pub fn darwin(mut self, supported: bool) -> OsiConfig {
+        self.darwin = supported;
+        self
+    }
+
+    /// Whether to claim `"Linux"`. Firmware that checks this is almost always doing the wrong thing, so this
+    /// should only be enabled to work around specific firmware.
+    pub fn linux(mut self, supported: bool) -> OsiConfig {
+        self.linux = supported;
+        self
+    }
+
+    /// Claim support for `string` (e.g. a custom OS string, or a feature string).
+    pub fn support(mut self, string: &str) -> OsiConfig {
+        self.strings.insert(string.to_string(), true);
+        self
+    }
+
+    /// Don't claim support for `string`, even if it would otherwise be claimed.
+    pub fn unsupport(mut self, string: &str) -> OsiConfig {
+        self.strings.insert(string.to_string(), false);
+        self
+    }
+
+    /// Decide dynamically whether to claim support for strings. The callback is consulted first, and the rest of
+    /// the configuration is used if it returns `None`.
+    pub fn callback<F>(mut self, callback: F) -> OsiConfig
+    where
+        F: Fn(&str) -> Option<bool> + Send + Sync + 'static,
+    {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Check whether the configuration claims support for `string`.
+    pub fn supports(&self, string: &str) -> bool {
+        if let Some(supported) = self.callback.as_ref().and_then(|callback| callback(string)) {
+            return supported;
+        }
+        if let Some(&supported) = self.strings.get(string) {
+            return supported;
+        }
+        if let Some(version) = WindowsVersion::from_osi_string(string) {
+            return self.windows.map(|max| version <= max).unwrap_or(false);
+        }
+
+        match string {
+            "Darwin" => self.darwin,
+            "Linux" => {
+                if !self.linux {
+                    warn!("ACPI evaluated `_OSI(\"Linux\")`. This is a bug. Reporting no support.");
+                }
+                self.linux
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn into_method(self) -> AmlValue {
+        AmlValue::native_method(1, false, 0, move |context| {
+            let supported = self.supports(context.current_arg(0)?.as_string(context)?.as_str());
+            Ok(if supported { AmlValue::ones() } else { AmlValue::zero() })
+        })
+    }
+}
+
+impl Default for OsiConfig {
+    fn default() -> OsiConfig {
+        OsiConfig::none()
+            .windows_up_to(WindowsVersion::Windows2019)
+            .darwin(true)
+            .support("Extended Address Space Descriptor")
+            // TODO: support module devices
+            .unsupport("Module Device")
+            .support("3.0 Thermal Model")
+            .support("3.0 _SCP Extensions")
+            // TODO: support processor aggregator devices
+            .unsupport("Processor Aggregator Device")
+    }
+}
+
+impl fmt::Debug for OsiConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OsiConfig")
+            .field("windows", &self.windows)
+            .field("darwin", &self.darwin)
+            .field("linux", &self.linux)
+            .field("strings", &self.strings)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl AmlContext {
+    /// Replace the `\_OSI` method with one that claims support for the strings configured by `config`. This
+    /// should be done before any tables are loaded, as firmware often evaluates `_OSI` during initialization.
+    pub fn set_osi_config(&mut self, config: OsiConfig) -> Result<(), AmlError> {
+        let osi = self.namespace.get_handle(&AmlName::from_str("\\_OSI").unwrap())?;
+        self.namespace.set(osi, config.into_method())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use alloc::vec;
+
+    fn osi(context: &mut AmlContext, string: &str) -> bool {
+        let args = crate::value::Args::from_list(vec![AmlValue::String(string.to_string())]).unwrap();
+        context.invoke_method(&AmlName::from_str("\\_OSI").unwrap(), args).unwrap().as_bool().unwrap()
+    }
+
+    #[test]
+    fn test_osi_config() {
+        let mut context = make_test_context();
+        assert!(osi(&mut context, "Windows 2019"));
+        assert!(!osi(&mut context, "Windows 2020"));
+        assert!(osi(&mut context, "Darwin"));
+
+        context
+            .set_osi_config(
+                OsiConfig::default()
+                    .windows_up_to(WindowsVersion::Windows2009)
+                    .darwin(false)
+                    .support("Custom OS")
+                    .callback(|string| if string == "Windows 2000" { Some(false) } else { None }),
+            )
+            .unwrap();
+        assert!(osi(&mut context, "Windows 2009"));
+        assert!(!osi(&mut context, "Windows 2012"));
+        assert!(!osi(&mut context, "Windows 2000"));
+        assert!(!osi(&mut context, "Darwin"));
+        assert!(osi(&mut context, "Custom OS"));
+        assert!(osi(&mut context, "3.0 Thermal Model"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Accesses to `PciConfig` operation regions, which are routed to the PCI configuration space of the device the
+//! region is declared in, through the `Handler::read_pci_*` and `Handler::write_pci_*` methods.
+//!
+//! The PCI address of a region is found from the `_ADR` of its parent device, and the `_SEG` and `_BBN` of the
+//! host bridge it's under (which default to `0`, for systems with a single segment group and a single root bus).
+//! These objects are often methods, which can't be evaluated by the interpreter while it's accessing a region, so
+//! addresses are resolved (and cached) when the interpreter can run methods: by
+//! [`AmlContext::initialize_objects`], and when a field of a region is written to. Before that, a region's address
+//! can be resolved only if all of the objects are plain values.
+
+use crate::{value::Args, AmlContext, AmlError, AmlHandle, AmlName, AmlValue, LevelType, RegionSpace};
+use alloc::vec::Vec;
+use bit_field::BitField;
+use core::convert::TryInto;
+use log::warn;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct PciAddress {
+    pub segment: u16,
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl AmlContext {
+    /// Find the PCI address of the `PciConfig` region `region`, evaluating methods if necessary, and cache it for
+    /// later accesses to the region.
+    pub(crate) fn resolve_pci_address(&mut self, region: AmlHandle) -> Result<PciAddress, AmlError> {
+        if let Some(address) = self.shared.pci_addresses.lock().get(&region) {
+            return Ok(*address);
+        }
+
+        let parent_device = pci_region_parent(self, region)?;
+        let address = pci_address(&parent_device, |name| match self.namespace.search(name, &parent_device) {
+            Ok((path, _)) => self.invoke_method(&path, Args::EMPTY)?.as_integer(self).map(Some),
+            Err(AmlError::ValueDoesNotExist(_)) => Ok(None),
+            Err(err) => Err(err),
+        })?;
+
+        self.shared.pci_addresses.lock().insert(region, address);
+        Ok(address)
+    }
+
+    /// Resolve the PCI addresses of all of the `PciConfig` regions in the namespace.
+    pub(crate) fn resolve_pci_addresses(&mut self) -> Result<(), AmlError> {
+        let mut handles = Vec::new();
+        self.namespace.traverse(|_, level| {
+            handles.extend(level.values.values().copied());
+            Ok(!matches!(level.typ, LevelType::MethodLocals))
+        })?;
+
+        for handle in handles {
+            if let Ok(AmlValue::OpRegion { region: RegionSpace::PciConfig, .. }) = self.namespace.get(handle) {
+                /*
+                 * A region can fail to resolve if its device is missing objects, but it's only a problem if the
+                 * region is actually accessed, so we don't fail the rest of the regions.
+                 */
+                if let Err(err) = self.resolve_pci_address(handle) {
+                    warn!("Failed to resolve the PCI address of a PciConfig region: {:?}", err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the PCI address of the `PciConfig` region `region` without evaluating methods, using the cached
+    /// address if it has already been resolved.
+    fn cached_pci_address(&self, region: AmlHandle) -> Result<PciAddress, AmlError> {
+        if let Some(address) = self.shared.pci_addresses.lock().get(&region) {
+            return Ok(*address);
+        }
+
+        let parent_device = pci_region_parent(self, region)?;
+        pci_address(&parent_device, |name| match self.namespace.search(name, &parent_device) {
+            Ok((_, handle)) => match self.namespace.get(handle)? {
+                AmlValue::Method { .. } => Err(AmlError::PciAddressNotResolved),
+                value => value.as_integer(self).map(Some),
+            },
+            Err(AmlError::ValueDoesNotExist(_)) => Ok(None),
+            Err(err) => Err(err),
+        })
+    }
+
+    /// Read `length` bits from offset `offset` of the configuration space of the device of `region`.
+    pub(crate) fn read_pci_config(&self, region: AmlHandle, offset: u64, length: u64) -> Result<u64, AmlError> {
+        let address = self.cached_pci_address(region)?;
+        let offset: u16 = offset.try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
+        let PciAddress { segment, bus, device, function } = address;
+
+        match length {
+            8 => Ok(self.handler.read_pci_u8(segment, bus, device, function, offset) as u64),
+            16 => Ok(self.handler.read_pci_u16(segment, bus, device, function, offset) as u64),
+            32 => Ok(self.handler.read_pci_u32(segment, bus, device, function, offset) as u64),
+            // Configuration space can't be accessed 64 bits at a time, so these are split into two accesses
+            64 => {
+                let low = self.read_pci_config(region, offset as u64, 32)?;
+                let high = self.read_pci_config(region, offset as u64 + 4, 32)?;
+                Ok(low | (high << 32))
+            }
+            _ => Err(AmlError::FieldInvalidAccessSize),
+        }
+    }
+
+    /// Write the low `length` bits of `value` to offset `offset` of the configuration space of the device of
+    /// `region`.
+    pub(crate) fn write_pci_config(
+        &self,
+        region: AmlHandle,
+        offset: u64,
+        length: u64,
+        value: u64,
+    ) -> Result<(), AmlError> {
+        let address = self.cached_pci_address(region)?;
+        let offset: u16 = offset.try_into().map_err(|_| AmlError::FieldInvalidAddress)?;
+        let PciAddress { segment, bus, device, function } = address;
+
+        match length {
+            8 => self.handler.write_pci_u8(segment, bus, device, function, offset, value as u8),
+            16 => self.handler.write_pci_u16(segment, bus, device, function, offset, value as u16),
+            32 => self.handler.write_pci_u32(segment, bus, device, function, offset, value as u32),
+            64 => {
+                self.write_pci_config(region, offset as u64, 32, value.get_bits(0..32))?;
+                self.write_pci_config(region, offset as u64 + 4, 32, value.get_bits(32..64))?;
+            }
+            _ => return Err(AmlError::FieldInvalidAccessSize),
+        }
+        Ok(())
+    }
+}
+
+fn pci_region_parent(context: &AmlContext, region: AmlHandle) -> Result<AmlName, AmlError> {
+    match context.namespace.get(region)? {
+        AmlValue::OpRegion { region: RegionSpace::PciConfig, parent_device: Some(parent_device), .. } => {
+            Ok(parent_device.clone())
+        }
+        _ => Err(AmlError::FieldRegionIsNotOpRegion),
+    }
+}
+
+/// Find the PCI address of a device, given a way of evaluating the integer objects it's found from (which returns
+/// `None` if an object doesn't exist). `_SEG` and `_BBN` are searched for from the device upwards, so they're
+/// found on the host bridge the device is under.
+fn pci_address<F>(device: &AmlName, mut evaluate: F) -> Result<PciAddress, AmlError>
+where
+    F: FnMut(&AmlName) -> Result<Option<u64>, AmlError>,
+{
+    let segment = evaluate(&AmlName::from_str("_SEG").unwrap())?.unwrap_or(0);
+    let bus = evaluate(&AmlName::from_str("_BBN").unwrap())?.unwrap_or(0);
+    let adr = evaluate(&AmlName::from_str("_ADR").unwrap())?
+        .ok_or_else(|| AmlError::ValueDoesNotExist(AmlName::from_str("_ADR").unwrap().resolve(device).unwrap()))?;
+
+    Ok(PciAddress {
+        segment: segment.try_into().map_err(|_| AmlError::FieldInvalidAddress)?,
+        bus: bus.try_into().map_err(|_| AmlError::FieldInvalidAddress)?,
+        device: adr.get_bits(16..24) as u8,
+        function: adr.get_bits(0..8) as u8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_pci_address() {
+        let mut context = make_test_context();
+        let name = |name: &str| AmlName::from_str(name).unwrap();
+
+        context.namespace.add_level(name("\\_SB.PCI0"), LevelType::Device).unwrap();
+        context.namespace.add_value(name("\\_SB.PCI0._BBN"), AmlValue::Integer(2)).unwrap();
+        context.namespace.add_level(name("\\_SB.PCI0.DEV0"), LevelType::Device).unwrap();
+        context.namespace.add_value(name("\\_SB.PCI0.DEV0._ADR"), AmlValue::Integer(0x001f0003)).unwrap();
+        let region = context
+            .namespace
+            .add_value(
+                name("\\_SB.PCI0.DEV0.REG0"),
+                AmlValue::OpRegion {
+                    region: RegionSpace::PciConfig,
+                    offset: 0,
+                    length: 0x100,
+                    parent_device: Some(name("\\_SB.PCI0.DEV0")),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            context.cached_pci_address(region),
+            Ok(PciAddress { segment: 0, bus: 2, device: 0x1f, function: 3 })
+        );
+
+        // `_SEG` can only be evaluated once the interpreter can run methods
+        context
+            .namespace
+            .add_value(name("\\_SB.PCI0._SEG"), AmlValue::native_method(0, false, 0, |_| Ok(AmlValue::Integer(1))))
+            .unwrap();
+        assert_eq!(context.cached_pci_address(region), Err(AmlError::PciAddressNotResolved));
+        context.resolve_pci_addresses().unwrap();
+        assert_eq!(
+            context.cached_pci_address(region),
+            Ok(PciAddress { segment: 1, bus: 2, device: 0x1f, function: 3 })
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     namespace::AmlName,
-    resource::{self, InterruptPolarity, InterruptTrigger, Resource},
+    resource::{self, InterruptPolarity, InterruptTrigger, IrqFormat, Resource},
     value::Args,
     AmlContext,
     AmlError,
@@ -21,7 +21,7 @@ pub enum Pin {
     IntD,
 }
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum PciRouteType {
     /// The interrupt is hard-coded to a specific GSI
     Gsi(u32),
@@ -36,12 +36,51 @@ pub enum PciRouteType {
     LinkObject(AmlName),
 }
 
-#[derive(Debug)]
+/// A single entry of a `_PRT`, describing where one interrupt pin of a PCI device is routed.
+#[derive(Clone, Debug)]
 pub struct PciRoute {
-    device: u16,
-    function: u16,
-    pin: Pin,
-    route_type: PciRouteType,
+    pub device: u16,
+    /// The function this entry applies to. This is `0xffff` if the entry applies to all functions of the device.
+    pub function: u16,
+    pub pin: Pin,
+    pub route_type: PciRouteType,
+    /// For link objects, the index of the resource descriptor in the link object's resource template that the
+    /// interrupt is allocated from. For hard-coded routes, this is the GSI.
+    pub source_index: u32,
+}
+
+impl PciRoute {
+    /// Whether this entry describes the routing of `pin` of the given device and function.
+    pub fn matches(&self, device: u16, function: u16, pin: Pin) -> bool {
+        self.device == device && (self.function == 0xffff || self.function == function) && self.pin == pin
+    }
+
+    /// Get the interrupt input that this entry's pin is currently wired to. For link objects, this evaluates the
+    /// link object's `_CRS`, and returns `AmlError::UnexpectedResourceType` if the descriptor selected by the
+    /// Source Index is not an interrupt descriptor.
+    pub fn current_irq(&self, context: &mut AmlContext) -> Result<IrqDescriptor, AmlError> {
+        match self.route_type {
+            PciRouteType::Gsi(gsi) => Ok(IrqDescriptor {
+                is_consumer: true,
+                trigger: InterruptTrigger::Level,
+                polarity: InterruptPolarity::ActiveLow,
+                is_shared: true,
+                is_wake_capable: false,
+                irq: gsi,
+                format: IrqFormat::Extended { additional_irqs: Vec::new() },
+            }),
+            PciRouteType::LinkObject(ref name) => {
+                let path = AmlName::from_str("_CRS").unwrap().resolve(name)?;
+                let link_crs = context.invoke_method(&path, Args::EMPTY)?;
+
+                let resources = resource::resource_descriptor_list(&link_crs)?;
+                match resources.get(self.source_index as usize) {
+                    Some(Resource::Irq(descriptor)) => Ok(descriptor.clone()),
+                    _ => Err(AmlError::UnexpectedResourceType),
+                }
+            }
+        }
+    }
 }
 
 /// A `PciRoutingTable` is used to interpret the data in a `_PRT` object, which provides a mapping
@@ -63,8 +102,12 @@ impl PciRoutingTable {
 
         let prt = context.invoke_method(&prt_path, Args::default())?;
         if let AmlValue::Package(ref inner_values) = prt {
-            for value in inner_values {
+            for value in inner_values.iter() {
                 if let AmlValue::Package(ref pin_package) = value {
+                    if pin_package.len() != 4 {
+                        return Err(AmlError::PrtInvalidEntry);
+                    }
+
                     /*
                      * Each inner package has the following structure:
                      *   | Field      | Type      | Description                                               |
@@ -105,26 +148,31 @@ impl PciRoutingTable {
                              * The Source Index field contains the GSI number that this interrupt is attached
                              * to.
                              */
+                            let gsi = pin_package[3]
+                                .as_integer(context)?
+                                .try_into()
+                                .map_err(|_| AmlError::PrtInvalidGsi)?;
                             entries.push(PciRoute {
                                 device,
                                 function,
                                 pin,
-                                route_type: PciRouteType::Gsi(
-                                    pin_package[3]
-                                        .as_integer(context)?
-                                        .try_into()
-                                        .map_err(|_| AmlError::PrtInvalidGsi)?,
-                                ),
+                                route_type: PciRouteType::Gsi(gsi),
+                                source_index: gsi,
                             });
                         }
                         AmlValue::String(ref name) => {
                             let link_object_name =
                                 context.namespace.search_for_level(&AmlName::from_str(name)?, &prt_path)?;
+                            let source_index = pin_package[3]
+                                .as_integer(context)?
+                                .try_into()
+                                .map_err(|_| AmlError::PrtInvalidSource)?;
                             entries.push(PciRoute {
                                 device,
                                 function,
                                 pin,
                                 route_type: PciRouteType::LinkObject(link_object_name),
+                                source_index,
                             });
                         }
                         _ => return Err(AmlError::PrtInvalidSource),
@@ -143,6 +191,11 @@ impl PciRoutingTable {
         }
     }
 
+    /// The entries of the `_PRT`, in the order they appear in it.
+    pub fn entries(&self) -> &[PciRoute] {
+        &self.entries
+    }
+
     /// Get the interrupt input that a given PCI interrupt pin is wired to. Returns `AmlError::PrtNoEntry` if the
     /// PRT doesn't contain an entry for the given address + pin.
     pub fn route(
@@ -152,35 +205,99 @@ impl PciRoutingTable {
         pin: Pin,
         context: &mut AmlContext,
     ) -> Result<IrqDescriptor, AmlError> {
-        let entry = self
-            .entries
-            .iter()
-            .find(|entry| {
-                entry.device == device
-                    && (entry.function == 0xffff || entry.function == function)
-                    && entry.pin == pin
-            })
-            .ok_or(AmlError::PrtNoEntry)?;
-
-        match entry.route_type {
-            PciRouteType::Gsi(gsi) => Ok(IrqDescriptor {
+        let entry =
+            self.entries.iter().find(|entry| entry.matches(device, function, pin)).ok_or(AmlError::PrtNoEntry)?;
+        entry.current_irq(context)
+    }
+
+    /// Resolve every entry of the table to the interrupt input it is currently wired to, evaluating the `_CRS` of
+    /// any link objects.
+    pub fn resolve(&self, context: &mut AmlContext) -> Result<Vec<(&PciRoute, IrqDescriptor)>, AmlError> {
+        self.entries.iter().map(|entry| Ok((entry, entry.current_irq(context)?))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{namespace::LevelType, test_utils::*};
+    use alloc::vec;
+
+    #[test]
+    fn test_prt() {
+        let mut context = make_test_context();
+
+        /*
+         * Scope (\_SB) {
+         *     Device (LNKA) {
+         *         Name (_CRS, ResourceTemplate () {
+         *             Interrupt (ResourceConsumer, Level, ActiveLow, Shared) { 11 }
+         *         })
+         *     }
+         *     Device (PCI0) {
+         *         Name (_PRT, Package () {
+         *             Package () { 0x0001ffff, 0, 0, 16 },
+         *             Package () { 0x00020000, 1, LNKA, 0 },
+         *         })
+         *     }
+         * }
+         */
+        let link = AmlName::from_str("\\_SB.LNKA").unwrap();
+        let pci = AmlName::from_str("\\_SB.PCI0").unwrap();
+        context.namespace.add_level(link.clone(), LevelType::Device).unwrap();
+        context.namespace.add_level(pci.clone(), LevelType::Device).unwrap();
+        context
+            .namespace
+            .add_value(
+                AmlName::from_str("_CRS").unwrap().resolve(&link).unwrap(),
+                AmlValue::buffer(vec![0x89, 0x06, 0x00, 0x0d, 0x01, 0x0b, 0x00, 0x00, 0x00, 0x79, 0x00]),
+            )
+            .unwrap();
+        let prt_path = AmlName::from_str("_PRT").unwrap().resolve(&pci).unwrap();
+        context
+            .namespace
+            .add_value(
+                prt_path.clone(),
+                AmlValue::package(vec![
+                    AmlValue::package(vec![
+                        AmlValue::Integer(0x0001ffff),
+                        AmlValue::Integer(0),
+                        AmlValue::Integer(0),
+                        AmlValue::Integer(16),
+                    ]),
+                    AmlValue::package(vec![
+                        AmlValue::Integer(0x00020000),
+                        AmlValue::Integer(1),
+                        AmlValue::String("LNKA".into()),
+                        AmlValue::Integer(0),
+                    ]),
+                ]),
+            )
+            .unwrap();
+
+        let table = PciRoutingTable::from_prt_path(&prt_path, &mut context).unwrap();
+        assert_eq!(table.entries().len(), 2);
+        assert_eq!(table.entries()[1].route_type, PciRouteType::LinkObject(link));
+
+        assert_eq!(table.route(1, 3, Pin::IntA, &mut context).unwrap().irq, 16);
+        assert!(matches!(table.route(1, 3, Pin::IntB, &mut context), Err(AmlError::PrtNoEntry)));
+        assert!(matches!(table.route(2, 1, Pin::IntB, &mut context), Err(AmlError::PrtNoEntry)));
+
+        let link_irq = table.route(2, 0, Pin::IntB, &mut context).unwrap();
+        assert_eq!(
+            link_irq,
+            IrqDescriptor {
                 is_consumer: true,
                 trigger: InterruptTrigger::Level,
                 polarity: InterruptPolarity::ActiveLow,
                 is_shared: true,
                 is_wake_capable: false,
-                irq: gsi,
-            }),
-            PciRouteType::LinkObject(ref name) => {
-                let path = AmlName::from_str("_CRS").unwrap().resolve(name)?;
-                let link_crs = context.invoke_method(&path, Args::EMPTY)?;
-
-                let resources = resource::resource_descriptor_list(&link_crs)?;
-                match resources.as_slice() {
-                    [Resource::Irq(descriptor)] => Ok(descriptor.clone()),
-                    _ => Err(AmlError::UnexpectedResourceType),
-                }
+                irq: 11,
+                format: IrqFormat::Extended { additional_irqs: Vec::new() },
             }
-        }
+        );
+
+        let resolved = table.resolve(&mut context).unwrap();
+        assert_eq!(resolved.iter().map(|(_, irq)| irq.irq).collect::<Vec<_>>(), vec![16, 11]);
     }
 }
@@ -1,20 +1,34 @@
-use core::mem;
+use core::{
+    convert::{TryFrom, TryInto},
+    mem,
+};
 
 use crate::{
-    value::{AmlType, AmlValue},
+    value::{AmlType, AmlValue, Args},
+    AmlContext,
     AmlError,
+    AmlName,
 };
-use alloc::vec::Vec;
+use alloc::{string::String, vec, vec::Vec};
 use bit_field::BitField;
 use byteorder::{ByteOrder, LittleEndian};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Resource {
     Irq(IrqDescriptor),
     AddressSpace(AddressSpaceDescriptor),
     MemoryRange(MemoryRangeDescriptor),
     IOPort(IOPortDescriptor),
     Dma(DMADescriptor),
+    Gpio(GpioDescriptor),
+    SerialBus(SerialBusDescriptor),
+    /// Starts a set of descriptors that is one of the alternative configurations of a device, in a `_PRS`. The
+    /// set extends up to the next `StartDependentFunctions` or `EndDependentFunctions`.
+    StartDependentFunctions {
+        compatibility: DependentFunctionPriority,
+        performance: DependentFunctionPriority,
+    },
+    EndDependentFunctions,
 }
 
 /// Parse a `ResourceDescriptor` into a list of resources. Returns `AmlError::IncompatibleValueConversion` if the passed value is not a
@@ -91,9 +105,9 @@ fn resource_descriptor(bytes: &[u8]) -> Result<(Option<Resource>, &[u8]), AmlErr
             0x09 => extended_interrupt_descriptor(descriptor_bytes),
             0x0a => address_space_descriptor::<u64>(descriptor_bytes),
             0x0b => unimplemented!("Extended Address Space Descriptor"),
-            0x0c => unimplemented!("GPIO Connection Descriptor"),
+            0x0c => gpio_connection_descriptor(descriptor_bytes),
             0x0d => unimplemented!("Pin Function Descriptor"),
-            0x0e => unimplemented!("GenericSerialBus Connection Descriptor"),
+            0x0e => serial_bus_connection_descriptor(descriptor_bytes),
             0x0f => unimplemented!("Pin Configuration Descriptor"),
             0x10 => unimplemented!("Pin Group Descriptor"),
             0x11 => unimplemented!("Pin Group Function Descriptor"),
@@ -134,8 +148,8 @@ fn resource_descriptor(bytes: &[u8]) -> Result<(Option<Resource>, &[u8]), AmlErr
             0x00..=0x03 => Err(AmlError::ReservedResourceType),
             0x04 => irq_format_descriptor(descriptor_bytes),
             0x05 => dma_format_descriptor(descriptor_bytes),
-            0x06 => unimplemented!("Start Dependent Functions Descriptor"),
-            0x07 => unimplemented!("End Dependent Functions Descriptor"),
+            0x06 => start_dependent_functions_descriptor(descriptor_bytes),
+            0x07 => Ok(Resource::EndDependentFunctions),
             0x08 => io_port_descriptor(descriptor_bytes),
             0x09 => unimplemented!("Fixed Location IO Port Descriptor"),
             0x0A => unimplemented!("Fixed DMA Descriptor"),
@@ -159,6 +173,8 @@ pub enum InterruptTrigger {
 pub enum InterruptPolarity {
     ActiveHigh,
     ActiveLow,
+    /// The interrupt is triggered on both edges of the signal. This is only used by GPIO interrupts.
+    ActiveBoth,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -174,20 +190,47 @@ pub enum AddressSpaceDecodeType {
     Subtractive,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct AddressSpaceDescriptor {
-    resource_type: AddressSpaceResourceType,
-    is_maximum_address_fixed: bool,
-    is_minimum_address_fixed: bool,
-    decode_type: AddressSpaceDecodeType,
+/// The width of the address fields of an address space descriptor, which determines the form of the descriptor.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum AddressSpaceWidth {
+    Word,
+    DWord,
+    QWord,
+}
+
+impl AddressSpaceWidth {
+    fn size(self) -> usize {
+        match self {
+            AddressSpaceWidth::Word => 2,
+            AddressSpaceWidth::DWord => 4,
+            AddressSpaceWidth::QWord => 8,
+        }
+    }
+}
 
-    granularity: u64,
-    address_range: (u64, u64),
-    translation_offset: u64,
-    length: u64,
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddressSpaceDescriptor {
+    pub width: AddressSpaceWidth,
+    pub resource_type: AddressSpaceResourceType,
+    /// If true, the device consumes this range; otherwise it produces it (e.g. a bridge that decodes it).
+    pub is_consumer: bool,
+    pub is_maximum_address_fixed: bool,
+    pub is_minimum_address_fixed: bool,
+    pub decode_type: AddressSpaceDecodeType,
+    /// The flags specific to the resource type (e.g. the cacheability of a memory range). These aren't decoded.
+    pub type_specific_flags: u8,
+
+    pub granularity: u64,
+    pub address_range: (u64, u64),
+    pub translation_offset: u64,
+    pub length: u64,
+
+    /// The device that this resource is consumed from, or an empty string if it's consumed from the parent.
+    pub resource_source: String,
+    pub resource_source_index: u8,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MemoryRangeDescriptor {
     FixedLocation { is_writable: bool, base_address: u32, range_length: u32 },
 }
@@ -287,7 +330,14 @@ fn address_space_descriptor<T>(bytes: &[u8]) -> Result<Resource, AmlError> {
         192..=255 => unimplemented!(),
     };
 
+    let width = match size {
+        2 => AddressSpaceWidth::Word,
+        4 => AddressSpaceWidth::DWord,
+        _ => AddressSpaceWidth::QWord,
+    };
+
     let general_flags = bytes[4];
+    let is_consumer = general_flags.get_bit(0);
     let is_maximum_address_fixed = general_flags.get_bit(3);
     let is_minimum_address_fixed = general_flags.get_bit(2);
     let decode_type = if general_flags.get_bit(1) {
@@ -305,15 +355,25 @@ fn address_space_descriptor<T>(bytes: &[u8]) -> Result<Resource, AmlError> {
     let translation_offset = LittleEndian::read_uint(address_fields.next().unwrap(), size);
     let length = LittleEndian::read_uint(address_fields.next().unwrap(), size);
 
+    let (resource_source_index, resource_source) = match bytes.get(6 + size * 5..) {
+        Some([index, name @ ..]) => (*index, resource_source_name(name)?),
+        _ => (0, String::new()),
+    };
+
     Ok(Resource::AddressSpace(AddressSpaceDescriptor {
+        width,
         resource_type,
+        is_consumer,
         is_maximum_address_fixed,
         is_minimum_address_fixed,
         decode_type,
+        type_specific_flags: bytes[5],
         granularity,
         address_range: (address_range_min, address_range_max),
         translation_offset,
         length,
+        resource_source,
+        resource_source_index,
     }))
 }
 
@@ -324,11 +384,43 @@ pub struct IrqDescriptor {
     pub polarity: InterruptPolarity,
     pub is_shared: bool,
     pub is_wake_capable: bool,
-    /*
-     * NOTE: We currently only support the cases where a descriptor only contains a single interrupt
-     * number.
-     */
+    /// For IRQ descriptors, this is a mask of the IRQs, where bit `n` represents IRQ `n`. For Extended Interrupt
+    /// descriptors, this is the first interrupt number. Use [`IrqDescriptor::irqs`] to get the interrupt numbers
+    /// of either.
     pub irq: u32,
+    pub format: IrqFormat,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IrqFormat {
+    /// An IRQ descriptor, which describes interrupts connected to a PIC-compatible interrupt controller.
+    Legacy,
+    /// An Extended Interrupt descriptor. In a `_PRS`, these can list more than one interrupt: the first is in
+    /// `irq`, and the rest are in `additional_irqs`.
+    Extended { additional_irqs: Vec<u32> },
+}
+
+impl IrqDescriptor {
+    /// The interrupt numbers the descriptor lists.
+    pub fn irqs(&self) -> Vec<u32> {
+        match self.format {
+            IrqFormat::Legacy => (0..16).filter(|&irq| self.irq.get_bit(irq)).map(|irq| irq as u32).collect(),
+            IrqFormat::Extended { ref additional_irqs } => {
+                core::iter::once(self.irq).chain(additional_irqs.iter().copied()).collect()
+            }
+        }
+    }
+
+    /// Make a descriptor of the same format that only lists `irq`, e.g. to choose one of the interrupts of a
+    /// `_PRS` descriptor to pass to `_SRS`.
+    pub fn with_irq(&self, irq: u32) -> IrqDescriptor {
+        match self.format {
+            IrqFormat::Legacy => IrqDescriptor { irq: 1 << irq, ..self.clone() },
+            IrqFormat::Extended { .. } => {
+                IrqDescriptor { irq, format: IrqFormat::Extended { additional_irqs: Vec::new() }, ..self.clone() }
+            }
+        }
+    }
 }
 
 fn irq_format_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
@@ -374,6 +466,7 @@ fn irq_format_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
                 is_shared: false,
                 polarity: InterruptPolarity::ActiveHigh,
                 trigger: InterruptTrigger::Edge,
+                format: IrqFormat::Legacy,
 
                 is_consumer: false, // assumed to be producer
             }))
@@ -400,6 +493,7 @@ fn irq_format_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
                 is_shared,
                 polarity,
                 trigger,
+                format: IrqFormat::Legacy,
 
                 is_consumer: false, // assumed to be producer
             }))
@@ -408,7 +502,7 @@ fn irq_format_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum DMASupportedSpeed {
     CompatibilityMode,
     TypeA, // as described by the EISA
@@ -416,14 +510,14 @@ pub enum DMASupportedSpeed {
     TypeF,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum DMATransferTypePreference {
     _8BitOnly,
     _8And16Bit,
     _16Bit,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct DMADescriptor {
     channel_mask: u8,
     supported_speeds: DMASupportedSpeed,
@@ -483,12 +577,15 @@ pub fn dma_format_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
     Ok(Resource::Dma(DMADescriptor { channel_mask, supported_speeds, is_bus_master, transfer_type_preference }))
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct IOPortDescriptor {
-    decodes_full_address: bool,
-    memory_range: (u16, u16),
-    base_alignment: u8,
-    range_length: u8,
+    /// If `false`, the device only decodes bits `0..10` of the port address.
+    pub decodes_full_address: bool,
+    /// The minimum and maximum base addresses the ports can be configured at.
+    pub memory_range: (u16, u16),
+    pub base_alignment: u8,
+    /// The number of contiguous ports.
+    pub range_length: u8,
 }
 
 fn io_port_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
@@ -544,15 +641,22 @@ fn extended_interrupt_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
      * From Byte 5 onwards, there are `n` interrupt numbers, each of which is encoded as a
      * 4-byte little-endian number.
      *
-     * NOTE: We only support the case where there is a single interrupt number.
+     * The interrupt numbers may be followed by a resource source index and name, which aren't decoded.
      */
     if bytes.len() < 9 {
         return Err(AmlError::ResourceDescriptorTooShort);
     }
 
     let number_of_interrupts = bytes[4] as usize;
-    assert_eq!(number_of_interrupts, 1);
-    let irq = LittleEndian::read_u32(&[bytes[5], bytes[6], bytes[7], bytes[8]]);
+    if number_of_interrupts == 0 {
+        return Err(AmlError::InvalidResourceDescriptor);
+    }
+    let mut irqs = bytes
+        .get(5..(5 + number_of_interrupts * 4))
+        .ok_or(AmlError::ResourceDescriptorTooShort)?
+        .chunks_exact(4)
+        .map(LittleEndian::read_u32);
+    let irq = irqs.next().unwrap();
 
     Ok(Resource::Irq(IrqDescriptor {
         is_consumer: bytes[3].get_bit(0),
@@ -561,9 +665,775 @@ fn extended_interrupt_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
         is_shared: bytes[3].get_bit(3),
         is_wake_capable: bytes[3].get_bit(4),
         irq,
+        format: IrqFormat::Extended { additional_irqs: irqs.collect() },
+    }))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GpioPinConfig {
+    Default,
+    PullUp,
+    PullDown,
+    NoPull,
+    /// A vendor-defined configuration, between `0x80` and `0xff`.
+    Vendor(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GpioIoRestriction {
+    None,
+    InputOnly,
+    OutputOnly,
+    /// The pins can be used for both input and output, but their configuration must be preserved while the
+    /// controller isn't using them.
+    Preserve,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GpioConnectionType {
+    /// A `GpioInt` descriptor: the pins are used as interrupt sources.
+    Interrupt { trigger: InterruptTrigger, polarity: InterruptPolarity, is_shared: bool, is_wake_capable: bool },
+    /// A `GpioIo` descriptor: the pins are used for input and output.
+    Io { restriction: GpioIoRestriction, is_shared: bool },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GpioDescriptor {
+    pub is_consumer: bool,
+    pub connection_type: GpioConnectionType,
+    pub pin_config: GpioPinConfig,
+    /// The output drive strength of the pins, in hundredths of milliamperes.
+    pub output_drive_strength: u16,
+    /// The debounce timeout of the pins, in hundredths of milliseconds.
+    pub debounce_timeout: u16,
+    /// The pins of the GPIO controller that are used, which are numbered by the controller.
+    pub pins: Vec<u16>,
+    /// The path of the GPIO controller device.
+    pub resource_source: String,
+    pub vendor_data: Vec<u8>,
+}
+
+fn gpio_connection_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
+    /*
+     * GPIO Connection Descriptor Definition
+     * Offset       Field Name
+     * Byte 0       Value = 0x8C (10001100B) – Type = 1, Large item name = 0x0C
+     * Byte 1-2     Length (minus the header)
+     * Byte 3       Revision ID
+     * Byte 4       GPIO Connection Type
+     *                0x00 = Interrupt Connection
+     *                0x01 = I/O Connection
+     * Byte 5-6     General Flags
+     *                Bit [0]  Consumer/Producer: 1 = this device consumes the resource
+     * Byte 7-8     Interrupt and I/O Flags
+     *                For interrupt connections:
+     *                  Bit [4]    Wake Capability
+     *                  Bit [3]    Interrupt Sharing
+     *                  Bits [2:1] Interrupt Polarity: 00 = Active-High, 01 = Active-Low, 10 = Active-Both
+     *                  Bit [0]    Interrupt Mode: 0 = Level-Triggered, 1 = Edge-Triggered
+     *                For I/O connections:
+     *                  Bit [3]    Sharing
+     *                  Bits [1:0] I/O Restriction: 00 = None, 01 = Input Only, 10 = Output Only, 11 = Preserve
+     * Byte 9       Pin Configuration: 0x00 = Default, 0x01 = Pull-Up, 0x02 = Pull-Down, 0x03 = No Pull,
+     *              0x80-0xff = Vendor-defined
+     * Byte 10-11   Output Drive Strength, in hundredths of milliamperes
+     * Byte 12-13   Debounce Timeout, in hundredths of milliseconds
+     * Byte 14-15   Pin Table Offset, from the start of the descriptor
+     * Byte 16      Resource Source Index (reserved, must be 0)
+     * Byte 17-18   Resource Source Name Offset, from the start of the descriptor
+     * Byte 19-20   Vendor Data Offset, from the start of the descriptor
+     * Byte 21-22   Vendor Data Length
+     *
+     * The pin table is a list of 16-bit pin numbers, which extends up to the resource source name. The resource
+     * source name is a null-terminated string, which extends up to the vendor data.
+     */
+    if bytes.len() < 23 {
+        return Err(AmlError::ResourceDescriptorTooShort);
+    }
+
+    let is_consumer = LittleEndian::read_u16(&bytes[5..=6]).get_bit(0);
+    let flags = LittleEndian::read_u16(&bytes[7..=8]);
+    let connection_type = match bytes[4] {
+        0x00 => GpioConnectionType::Interrupt {
+            trigger: if flags.get_bit(0) { InterruptTrigger::Edge } else { InterruptTrigger::Level },
+            polarity: match flags.get_bits(1..=2) {
+                0 => InterruptPolarity::ActiveHigh,
+                1 => InterruptPolarity::ActiveLow,
+                2 => InterruptPolarity::ActiveBoth,
+                _ => return Err(AmlError::InvalidResourceDescriptor),
+            },
+            is_shared: flags.get_bit(3),
+            is_wake_capable: flags.get_bit(4),
+        },
+        0x01 => GpioConnectionType::Io {
+            restriction: match flags.get_bits(0..=1) {
+                0 => GpioIoRestriction::None,
+                1 => GpioIoRestriction::InputOnly,
+                2 => GpioIoRestriction::OutputOnly,
+                _ => GpioIoRestriction::Preserve,
+            },
+            is_shared: flags.get_bit(3),
+        },
+        _ => return Err(AmlError::InvalidResourceDescriptor),
+    };
+    let pin_config = match bytes[9] {
+        0x00 => GpioPinConfig::Default,
+        0x01 => GpioPinConfig::PullUp,
+        0x02 => GpioPinConfig::PullDown,
+        0x03 => GpioPinConfig::NoPull,
+        vendor @ 0x80..=0xff => GpioPinConfig::Vendor(vendor),
+        _ => return Err(AmlError::InvalidResourceDescriptor),
+    };
+
+    let pin_table_offset = LittleEndian::read_u16(&bytes[14..=15]) as usize;
+    let resource_source_offset = LittleEndian::read_u16(&bytes[17..=18]) as usize;
+    let vendor_data_offset = LittleEndian::read_u16(&bytes[19..=20]) as usize;
+    let vendor_data_length = LittleEndian::read_u16(&bytes[21..=22]) as usize;
+
+    let pins = bytes
+        .get(pin_table_offset..resource_source_offset)
+        .ok_or(AmlError::ResourceDescriptorTooShort)?
+        .chunks_exact(2)
+        .map(LittleEndian::read_u16)
+        .collect();
+    let resource_source = resource_source_name(
+        bytes.get(resource_source_offset..vendor_data_offset).ok_or(AmlError::ResourceDescriptorTooShort)?,
+    )?;
+    let vendor_data = bytes
+        .get(vendor_data_offset..(vendor_data_offset + vendor_data_length))
+        .ok_or(AmlError::ResourceDescriptorTooShort)?
+        .to_vec();
+
+    Ok(Resource::Gpio(GpioDescriptor {
+        is_consumer,
+        connection_type,
+        pin_config,
+        output_drive_strength: LittleEndian::read_u16(&bytes[10..=11]),
+        debounce_timeout: LittleEndian::read_u16(&bytes[12..=13]),
+        pins,
+        resource_source,
+        vendor_data,
+    }))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct I2cSerialBus {
+    /// The maximum speed of the connection, in hertz.
+    pub connection_speed: u32,
+    pub slave_address: u16,
+    /// If `true`, the device uses 10-bit addressing. If `false`, it uses 7-bit addressing.
+    pub is_10_bit_addressing: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SpiClockPhase {
+    /// Data is sampled on the first edge of the clock.
+    First,
+    /// Data is sampled on the second edge of the clock.
+    Second,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SpiClockPolarity {
+    StartLow,
+    StartHigh,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SpiSerialBus {
+    /// The maximum speed of the connection, in hertz.
+    pub connection_speed: u32,
+    /// The size of a data word, in bits.
+    pub data_bit_length: u8,
+    pub clock_phase: SpiClockPhase,
+    pub clock_polarity: SpiClockPolarity,
+    /// The chip select line (or other device-selection value) of the device.
+    pub device_selection: u16,
+    /// If `true`, the bus is in 3-wire mode. If `false`, it's in 4-wire mode.
+    pub is_3_wire: bool,
+    /// If `true`, the device selection line is active-high. If `false`, it's active-low.
+    pub is_device_selection_active_high: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum UartFlowControl {
+    None,
+    Hardware,
+    XonXoff,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum UartStopBits {
+    None,
+    One,
+    OnePointFive,
+    Two,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum UartParity {
+    None,
+    Even,
+    Odd,
+    Mark,
+    Space,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct UartSerialBus {
+    pub baud_rate: u32,
+    pub rx_fifo_size: u16,
+    pub tx_fifo_size: u16,
+    pub parity: UartParity,
+    /// The serial lines that are enabled: bit 7 is RTS, bit 6 CTS, bit 5 DTR, bit 4 DSR, bit 3 RI, and bit 2 DTD.
+    pub lines_enabled: u8,
+    pub flow_control: UartFlowControl,
+    pub stop_bits: UartStopBits,
+    /// The number of bits in each character (from 5 to 9).
+    pub data_bits: u8,
+    pub is_big_endian: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SerialBusType {
+    I2c(I2cSerialBus),
+    Spi(SpiSerialBus),
+    Uart(UartSerialBus),
+    /// A bus type that isn't decoded (e.g. CSI-2). Contains the raw type, type-specific flags, and type-specific
+    /// data of the descriptor.
+    Other {
+        bus_type: u8,
+        flags: u16,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SerialBusDescriptor {
+    pub is_consumer: bool,
+    /// If `true`, the device initiates transfers on the bus (it's in slave mode). If `false`, the controller does.
+    pub is_device_initiated: bool,
+    pub is_shared: bool,
+    pub bus: SerialBusType,
+    /// The path of the serial bus controller device.
+    pub resource_source: String,
+    pub resource_source_index: u8,
+    pub vendor_data: Vec<u8>,
+}
+
+fn serial_bus_connection_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
+    /*
+     * GenericSerialBus Connection Descriptor Definition
+     * Offset       Field Name
+     * Byte 0       Value = 0x8E (10001110B) – Type = 1, Large item name = 0x0E
+     * Byte 1-2     Length (minus the header)
+     * Byte 3       Revision ID
+     * Byte 4       Resource Source Index
+     * Byte 5       Serial Bus Type: 1 = I2C, 2 = SPI, 3 = UART, 4 = CSI-2
+     * Byte 6       General Flags
+     *                Bit [2]  Connection Sharing
+     *                Bit [1]  Consumer/Producer: 1 = this device consumes the resource
+     *                Bit [0]  Slave Mode: 1 = the device initiates transfers
+     * Byte 7-8     Type Specific Flags
+     * Byte 9       Type Specific Revision ID
+     * Byte 10-11   Type Data Length
+     * Byte 12-n    Type Specific Data, followed by vendor data
+     * Byte n+1-..  Resource Source, a null-terminated string
+     *
+     * I2C:  Flags bit [0] is set for 10-bit addressing. The data is the connection speed (4 bytes) and the slave
+     *       address (2 bytes).
+     * SPI:  Flags bit [1] is set if the device selection is active-high, and bit [0] is set for 3-wire mode. The
+     *       data is the connection speed (4 bytes), the data bit length (1 byte), the clock phase (1 byte), the
+     *       clock polarity (1 byte), and the device selection (2 bytes).
+     * UART: Flags bit [7] is set for big-endian, bits [6:4] are the data bits (0 = 5 bits to 4 = 9 bits), bits
+     *       [3:2] are the stop bits (0 = none, 1 = 1, 2 = 1.5, 3 = 2), and bits [1:0] are the flow control (0 =
+     *       none, 1 = hardware, 2 = XON/XOFF). The data is the baud rate (4 bytes), the RX FIFO size (2 bytes),
+     *       the TX FIFO size (2 bytes), the parity (1 byte: 0 = none, 1 = even, 2 = odd, 3 = mark, 4 = space), and
+     *       the serial lines enabled (1 byte).
+     */
+    if bytes.len() < 12 {
+        return Err(AmlError::ResourceDescriptorTooShort);
+    }
+
+    let general_flags = bytes[6];
+    let flags = LittleEndian::read_u16(&bytes[7..=8]);
+    let type_data_length = LittleEndian::read_u16(&bytes[10..=11]) as usize;
+    let type_data = bytes.get(12..(12 + type_data_length)).ok_or(AmlError::ResourceDescriptorTooShort)?;
+    let resource_source = resource_source_name(&bytes[(12 + type_data_length)..])?;
+
+    let fixed_data_length = match bytes[5] {
+        1 => 6,
+        2 => 9,
+        3 => 10,
+        _ => 0,
+    };
+    if type_data.len() < fixed_data_length {
+        return Err(AmlError::ResourceDescriptorTooShort);
+    }
+
+    let bus = match bytes[5] {
+        1 => SerialBusType::I2c(I2cSerialBus {
+            connection_speed: LittleEndian::read_u32(&type_data[0..4]),
+            slave_address: LittleEndian::read_u16(&type_data[4..6]),
+            is_10_bit_addressing: flags.get_bit(0),
+        }),
+        2 => SerialBusType::Spi(SpiSerialBus {
+            connection_speed: LittleEndian::read_u32(&type_data[0..4]),
+            data_bit_length: type_data[4],
+            clock_phase: if type_data[5] == 0 { SpiClockPhase::First } else { SpiClockPhase::Second },
+            clock_polarity: if type_data[6] == 0 {
+                SpiClockPolarity::StartLow
+            } else {
+                SpiClockPolarity::StartHigh
+            },
+            device_selection: LittleEndian::read_u16(&type_data[7..9]),
+            is_3_wire: flags.get_bit(0),
+            is_device_selection_active_high: flags.get_bit(1),
+        }),
+        3 => SerialBusType::Uart(UartSerialBus {
+            baud_rate: LittleEndian::read_u32(&type_data[0..4]),
+            rx_fifo_size: LittleEndian::read_u16(&type_data[4..6]),
+            tx_fifo_size: LittleEndian::read_u16(&type_data[6..8]),
+            parity: match type_data[8] {
+                0 => UartParity::None,
+                1 => UartParity::Even,
+                2 => UartParity::Odd,
+                3 => UartParity::Mark,
+                4 => UartParity::Space,
+                _ => return Err(AmlError::InvalidResourceDescriptor),
+            },
+            lines_enabled: type_data[9],
+            flow_control: match flags.get_bits(0..=1) {
+                0 => UartFlowControl::None,
+                1 => UartFlowControl::Hardware,
+                2 => UartFlowControl::XonXoff,
+                _ => return Err(AmlError::InvalidResourceDescriptor),
+            },
+            stop_bits: match flags.get_bits(2..=3) {
+                0 => UartStopBits::None,
+                1 => UartStopBits::One,
+                2 => UartStopBits::OnePointFive,
+                _ => UartStopBits::Two,
+            },
+            data_bits: match flags.get_bits(4..=6) {
+                data_bits @ 0..=4 => data_bits as u8 + 5,
+                _ => return Err(AmlError::InvalidResourceDescriptor),
+            },
+            is_big_endian: flags.get_bit(7),
+        }),
+        bus_type => SerialBusType::Other { bus_type, flags, data: type_data.to_vec() },
+    };
+
+    Ok(Resource::SerialBus(SerialBusDescriptor {
+        is_consumer: general_flags.get_bit(1),
+        is_device_initiated: general_flags.get_bit(0),
+        is_shared: general_flags.get_bit(2),
+        bus,
+        resource_source,
+        resource_source_index: bytes[4],
+        vendor_data: type_data[fixed_data_length..].to_vec(),
     }))
 }
 
+/// Decode the null-terminated resource source name of a connection descriptor.
+fn resource_source_name(bytes: &[u8]) -> Result<String, AmlError> {
+    let length = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    core::str::from_utf8(&bytes[..length]).map(String::from).map_err(|_| AmlError::InvalidResourceDescriptor)
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DependentFunctionPriority {
+    Good,
+    Acceptable,
+    SubOptimal,
+}
+
+fn start_dependent_functions_descriptor(bytes: &[u8]) -> Result<Resource, AmlError> {
+    /*
+     * Start Dependent Functions Descriptor Definition
+     * Offset   Field Name
+     * Byte 0   Value = 0x30 or 0x31 (0011000nB) – Type = 0, Small item name = 0x6, Length = 0 or 1
+     * Byte 1   Priority (Optional—if not included then assume acceptable for both)
+     *            Bits [7:4]  Reserved (must be 0)
+     *            Bits [3:2]  Performance/robustness: 0 = Good, 1 = Acceptable, 2 = Sub-optimal
+     *            Bits [1:0]  Compatibility or performance: 0 = Good, 1 = Acceptable, 2 = Sub-optimal
+     */
+    let priority = |value: u8| match value {
+        0 => Ok(DependentFunctionPriority::Good),
+        1 => Ok(DependentFunctionPriority::Acceptable),
+        2 => Ok(DependentFunctionPriority::SubOptimal),
+        _ => Err(AmlError::InvalidResourceDescriptor),
+    };
+
+    match bytes.len() {
+        1 => Ok(Resource::StartDependentFunctions {
+            compatibility: DependentFunctionPriority::Acceptable,
+            performance: DependentFunctionPriority::Acceptable,
+        }),
+        2 => Ok(Resource::StartDependentFunctions {
+            compatibility: priority(bytes[1].get_bits(0..2))?,
+            performance: priority(bytes[1].get_bits(2..4))?,
+        }),
+        _ => Err(AmlError::ResourceDescriptorTooLong),
+    }
+}
+
+/// Parse a `_PRS` resource template into the alternative sets of resources the device can be configured to use.
+/// Descriptors that aren't between a `StartDependentFunctions` and an `EndDependentFunctions` are common to all
+/// sets. If the template has no dependent functions, there is a single set.
+pub fn possible_resource_settings(descriptor: &AmlValue) -> Result<Vec<Vec<Resource>>, AmlError> {
+    let mut common = Vec::new();
+    let mut sets: Vec<Vec<Resource>> = Vec::new();
+    let mut in_dependent_functions = false;
+
+    for resource in resource_descriptor_list(descriptor)? {
+        match resource {
+            Resource::StartDependentFunctions { .. } => {
+                sets.push(Vec::new());
+                in_dependent_functions = true;
+            }
+            Resource::EndDependentFunctions => in_dependent_functions = false,
+            resource if in_dependent_functions => sets.last_mut().unwrap().push(resource),
+            resource => common.push(resource),
+        }
+    }
+
+    if sets.is_empty() {
+        return Ok(vec![common]);
+    }
+    // The common descriptors can appear both before and after the dependent functions
+    Ok(sets.into_iter().map(|set| common.iter().cloned().chain(set).collect()).collect())
+}
+
+/// Build a resource template from a list of resources: the inverse of [`resource_descriptor_list`]. This is
+/// terminated with an End Tag, and can be passed to `_SRS`. Address space descriptors are encoded in the form
+/// given by their [`AddressSpaceWidth`], and fail to encode if their fields don't fit in it.
+pub fn encode_resource_descriptor_list(resources: &[Resource]) -> Result<AmlValue, AmlError> {
+    Ok(AmlValue::buffer(encode_resource_template(resources)?))
+}
+
+/// Encode a list of resources into the bytes of a resource template, including the End Tag.
+pub(crate) fn encode_resource_template(resources: &[Resource]) -> Result<Vec<u8>, AmlError> {
+    let mut bytes = Vec::new();
+    for resource in resources {
+        encode_resource_descriptor(resource, &mut bytes)?;
+    }
+
+    // An End Tag, with a checksum of zero (which means that the template doesn't have to be checksummed)
+    bytes.extend_from_slice(&[0x79, 0x00]);
+    Ok(bytes)
+}
+
+fn encode_resource_descriptor(resource: &Resource, bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+    match resource {
+        Resource::Irq(descriptor) => match descriptor.format {
+            IrqFormat::Legacy => {
+                let mask: u16 = descriptor.irq.try_into().map_err(|_| AmlError::InvalidResourceDescriptor)?;
+                let information = *0u8
+                    .set_bit(0, descriptor.trigger == InterruptTrigger::Edge)
+                    .set_bit(3, descriptor.polarity == InterruptPolarity::ActiveLow)
+                    .set_bit(4, descriptor.is_shared)
+                    .set_bit(5, descriptor.is_wake_capable);
+
+                // Edge-triggered, active-high, exclusive interrupts can use the short form, without information
+                if information == 0x01 {
+                    bytes.push(0x22);
+                    bytes.extend_from_slice(&mask.to_le_bytes());
+                } else {
+                    bytes.push(0x23);
+                    bytes.extend_from_slice(&mask.to_le_bytes());
+                    bytes.push(information);
+                }
+            }
+            IrqFormat::Extended { ref additional_irqs } => {
+                let flags = *0u8
+                    .set_bit(0, descriptor.is_consumer)
+                    .set_bit(1, descriptor.trigger == InterruptTrigger::Edge)
+                    .set_bit(2, descriptor.polarity == InterruptPolarity::ActiveLow)
+                    .set_bit(3, descriptor.is_shared)
+                    .set_bit(4, descriptor.is_wake_capable);
+                let count: u8 =
+                    (1 + additional_irqs.len()).try_into().map_err(|_| AmlError::InvalidResourceDescriptor)?;
+
+                let mut data = vec![flags, count];
+                for irq in core::iter::once(&descriptor.irq).chain(additional_irqs.iter()) {
+                    data.extend_from_slice(&irq.to_le_bytes());
+                }
+                large_descriptor(0x89, &data, bytes)?;
+            }
+        },
+        Resource::AddressSpace(descriptor) => {
+            let mut data = vec![
+                match descriptor.resource_type {
+                    AddressSpaceResourceType::MemoryRange => 0,
+                    AddressSpaceResourceType::IORange => 1,
+                    AddressSpaceResourceType::BusNumberRange => 2,
+                },
+                *0u8.set_bit(3, descriptor.is_maximum_address_fixed)
+                    .set_bit(2, descriptor.is_minimum_address_fixed)
+                    .set_bit(1, descriptor.decode_type == AddressSpaceDecodeType::Subtractive)
+                    .set_bit(0, descriptor.is_consumer),
+                descriptor.type_specific_flags,
+            ];
+
+            let size = descriptor.width.size();
+            for field in [
+                descriptor.granularity,
+                descriptor.address_range.0,
+                descriptor.address_range.1,
+                descriptor.translation_offset,
+                descriptor.length,
+            ] {
+                if size < 8 && field >> (size * 8) != 0 {
+                    return Err(AmlError::InvalidResourceDescriptor);
+                }
+                data.extend_from_slice(&field.to_le_bytes()[..size]);
+            }
+
+            if !descriptor.resource_source.is_empty() {
+                data.push(descriptor.resource_source_index);
+                data.extend_from_slice(descriptor.resource_source.as_bytes());
+                data.push(0x00);
+            }
+
+            let tag = match descriptor.width {
+                AddressSpaceWidth::Word => 0x88,
+                AddressSpaceWidth::DWord => 0x87,
+                AddressSpaceWidth::QWord => 0x8a,
+            };
+            large_descriptor(tag, &data, bytes)?;
+        }
+        Resource::MemoryRange(MemoryRangeDescriptor::FixedLocation {
+            is_writable,
+            base_address,
+            range_length,
+        }) => {
+            let mut data = vec![*is_writable as u8];
+            data.extend_from_slice(&base_address.to_le_bytes());
+            data.extend_from_slice(&range_length.to_le_bytes());
+            large_descriptor(0x86, &data, bytes)?;
+        }
+        Resource::IOPort(descriptor) => {
+            bytes.extend_from_slice(&[0x47, descriptor.decodes_full_address as u8]);
+            bytes.extend_from_slice(&descriptor.memory_range.0.to_le_bytes());
+            bytes.extend_from_slice(&descriptor.memory_range.1.to_le_bytes());
+            bytes.extend_from_slice(&[descriptor.base_alignment, descriptor.range_length]);
+        }
+        Resource::Dma(descriptor) => {
+            let speed = match descriptor.supported_speeds {
+                DMASupportedSpeed::CompatibilityMode => 0,
+                DMASupportedSpeed::TypeA => 1,
+                DMASupportedSpeed::TypeB => 2,
+                DMASupportedSpeed::TypeF => 3,
+            };
+            let transfer_type = match descriptor.transfer_type_preference {
+                DMATransferTypePreference::_8BitOnly => 0,
+                DMATransferTypePreference::_8And16Bit => 1,
+                DMATransferTypePreference::_16Bit => 2,
+            };
+            let options =
+                *0u8.set_bits(5..=6, speed).set_bit(2, descriptor.is_bus_master).set_bits(0..=1, transfer_type);
+            bytes.extend_from_slice(&[0x2a, descriptor.channel_mask, options]);
+        }
+        Resource::Gpio(descriptor) => {
+            let (connection_type, flags) = match descriptor.connection_type {
+                GpioConnectionType::Interrupt { trigger, polarity, is_shared, is_wake_capable } => (
+                    0x00,
+                    *0u16
+                        .set_bit(0, trigger == InterruptTrigger::Edge)
+                        .set_bits(
+                            1..=2,
+                            match polarity {
+                                InterruptPolarity::ActiveHigh => 0,
+                                InterruptPolarity::ActiveLow => 1,
+                                InterruptPolarity::ActiveBoth => 2,
+                            },
+                        )
+                        .set_bit(3, is_shared)
+                        .set_bit(4, is_wake_capable),
+                ),
+                GpioConnectionType::Io { restriction, is_shared } => (
+                    0x01,
+                    *0u16
+                        .set_bits(
+                            0..=1,
+                            match restriction {
+                                GpioIoRestriction::None => 0,
+                                GpioIoRestriction::InputOnly => 1,
+                                GpioIoRestriction::OutputOnly => 2,
+                                GpioIoRestriction::Preserve => 3,
+                            },
+                        )
+                        .set_bit(3, is_shared),
+                ),
+            };
+            let pin_config = match descriptor.pin_config {
+                GpioPinConfig::Default => 0x00,
+                GpioPinConfig::PullUp => 0x01,
+                GpioPinConfig::PullDown => 0x02,
+                GpioPinConfig::NoPull => 0x03,
+                GpioPinConfig::Vendor(vendor) => vendor,
+            };
+
+            // The offsets of the variable-length parts are from the start of the descriptor, including its header
+            let pin_table_offset = 23;
+            let resource_source_offset = pin_table_offset + descriptor.pins.len() * 2;
+            let vendor_data_offset = resource_source_offset + descriptor.resource_source.len() + 1;
+            let offset = |offset: usize| u16::try_from(offset).map_err(|_| AmlError::InvalidResourceDescriptor);
+
+            let mut data = vec![0x01, connection_type];
+            data.extend_from_slice(&(descriptor.is_consumer as u16).to_le_bytes());
+            data.extend_from_slice(&flags.to_le_bytes());
+            data.push(pin_config);
+            data.extend_from_slice(&descriptor.output_drive_strength.to_le_bytes());
+            data.extend_from_slice(&descriptor.debounce_timeout.to_le_bytes());
+            data.extend_from_slice(&offset(pin_table_offset)?.to_le_bytes());
+            data.push(0x00);
+            data.extend_from_slice(&offset(resource_source_offset)?.to_le_bytes());
+            data.extend_from_slice(&offset(vendor_data_offset)?.to_le_bytes());
+            data.extend_from_slice(&offset(descriptor.vendor_data.len())?.to_le_bytes());
+            for pin in descriptor.pins.iter() {
+                data.extend_from_slice(&pin.to_le_bytes());
+            }
+            data.extend_from_slice(descriptor.resource_source.as_bytes());
+            data.push(0x00);
+            data.extend_from_slice(&descriptor.vendor_data);
+            large_descriptor(0x8c, &data, bytes)?;
+        }
+        Resource::SerialBus(descriptor) => {
+            let (bus_type, flags, mut type_data) = match descriptor.bus {
+                SerialBusType::I2c(ref bus) => {
+                    let mut data = bus.connection_speed.to_le_bytes().to_vec();
+                    data.extend_from_slice(&bus.slave_address.to_le_bytes());
+                    (1, *0u16.set_bit(0, bus.is_10_bit_addressing), data)
+                }
+                SerialBusType::Spi(ref bus) => {
+                    let mut data = bus.connection_speed.to_le_bytes().to_vec();
+                    data.extend_from_slice(&[
+                        bus.data_bit_length,
+                        (bus.clock_phase == SpiClockPhase::Second) as u8,
+                        (bus.clock_polarity == SpiClockPolarity::StartHigh) as u8,
+                    ]);
+                    data.extend_from_slice(&bus.device_selection.to_le_bytes());
+                    (2, *0u16.set_bit(0, bus.is_3_wire).set_bit(1, bus.is_device_selection_active_high), data)
+                }
+                SerialBusType::Uart(ref bus) => {
+                    let mut data = bus.baud_rate.to_le_bytes().to_vec();
+                    data.extend_from_slice(&bus.rx_fifo_size.to_le_bytes());
+                    data.extend_from_slice(&bus.tx_fifo_size.to_le_bytes());
+                    data.push(match bus.parity {
+                        UartParity::None => 0,
+                        UartParity::Even => 1,
+                        UartParity::Odd => 2,
+                        UartParity::Mark => 3,
+                        UartParity::Space => 4,
+                    });
+                    data.push(bus.lines_enabled);
+                    if !(5..=9).contains(&bus.data_bits) {
+                        return Err(AmlError::InvalidResourceDescriptor);
+                    }
+                    let flags = *0u16
+                        .set_bits(
+                            0..=1,
+                            match bus.flow_control {
+                                UartFlowControl::None => 0,
+                                UartFlowControl::Hardware => 1,
+                                UartFlowControl::XonXoff => 2,
+                            },
+                        )
+                        .set_bits(
+                            2..=3,
+                            match bus.stop_bits {
+                                UartStopBits::None => 0,
+                                UartStopBits::One => 1,
+                                UartStopBits::OnePointFive => 2,
+                                UartStopBits::Two => 3,
+                            },
+                        )
+                        .set_bits(4..=6, bus.data_bits as u16 - 5)
+                        .set_bit(7, bus.is_big_endian);
+                    (3, flags, data)
+                }
+                SerialBusType::Other { bus_type, flags, ref data } => (bus_type, flags, data.clone()),
+            };
+            if !matches!(descriptor.bus, SerialBusType::Other { .. }) {
+                type_data.extend_from_slice(&descriptor.vendor_data);
+            }
+
+            let general_flags = *0u8
+                .set_bit(0, descriptor.is_device_initiated)
+                .set_bit(1, descriptor.is_consumer)
+                .set_bit(2, descriptor.is_shared);
+            let type_data_length =
+                u16::try_from(type_data.len()).map_err(|_| AmlError::InvalidResourceDescriptor)?;
+
+            let mut data = vec![0x02, descriptor.resource_source_index, bus_type, general_flags];
+            data.extend_from_slice(&flags.to_le_bytes());
+            data.push(0x01);
+            data.extend_from_slice(&type_data_length.to_le_bytes());
+            data.extend_from_slice(&type_data);
+            data.extend_from_slice(descriptor.resource_source.as_bytes());
+            data.push(0x00);
+            large_descriptor(0x8e, &data, bytes)?;
+        }
+        Resource::StartDependentFunctions { compatibility, performance } => {
+            let priority = |priority: &DependentFunctionPriority| match priority {
+                DependentFunctionPriority::Good => 0,
+                DependentFunctionPriority::Acceptable => 1,
+                DependentFunctionPriority::SubOptimal => 2,
+            };
+
+            // Without the priority byte, both priorities are acceptable
+            if (*compatibility, *performance)
+                == (DependentFunctionPriority::Acceptable, DependentFunctionPriority::Acceptable)
+            {
+                bytes.push(0x30);
+            } else {
+                bytes.extend_from_slice(&[
+                    0x31,
+                    *0u8.set_bits(0..2, priority(compatibility)).set_bits(2..4, priority(performance)),
+                ]);
+            }
+        }
+        Resource::EndDependentFunctions => bytes.push(0x38),
+    }
+
+    Ok(())
+}
+
+/// Append a large descriptor with the given tag and data (which follows the length) to `bytes`.
+fn large_descriptor(tag: u8, data: &[u8], bytes: &mut Vec<u8>) -> Result<(), AmlError> {
+    let length = u16::try_from(data.len()).map_err(|_| AmlError::InvalidResourceDescriptor)?;
+    bytes.push(tag);
+    bytes.extend_from_slice(&length.to_le_bytes());
+    bytes.extend_from_slice(data);
+    Ok(())
+}
+
+/// Evaluate the `_CRS` of `device`, and parse the resources it's currently configured to use.
+pub fn current_resources(context: &mut AmlContext, device: &AmlName) -> Result<Vec<Resource>, AmlError> {
+    let crs = context.invoke_method(&AmlName::from_str("_CRS").unwrap().resolve(device)?, Args::EMPTY)?;
+    resource_descriptor_list(&crs)
+}
+
+/// Evaluate the `_PRS` of `device`, and parse the alternative sets of resources it can be configured to use. See
+/// [`possible_resource_settings`].
+pub fn possible_resources(context: &mut AmlContext, device: &AmlName) -> Result<Vec<Vec<Resource>>, AmlError> {
+    let prs = context.invoke_method(&AmlName::from_str("_PRS").unwrap().resolve(device)?, Args::EMPTY)?;
+    possible_resource_settings(&prs)
+}
+
+/// Configure `device` to use `resources`, by passing them to its `_SRS`. The resources should be in the same order
+/// as the descriptors of its `_CRS`.
+pub fn set_resources(context: &mut AmlContext, device: &AmlName, resources: &[Resource]) -> Result<(), AmlError> {
+    let template = encode_resource_descriptor_list(resources)?;
+    context.invoke_method(
+        &AmlName::from_str("_SRS").unwrap().resolve(device)?,
+        Args::from_list(vec![template]).unwrap(),
+    )?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -625,7 +1495,8 @@ mod tests {
                     polarity: InterruptPolarity::ActiveHigh,
                     is_shared: false,
                     is_wake_capable: false,
-                    irq: (1 << 1)
+                    irq: (1 << 1),
+                    format: IrqFormat::Legacy,
                 })
             ])
         );
@@ -711,21 +1582,29 @@ mod tests {
         ]
         .to_vec();
 
-        let value: AmlValue = AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(bytes)));
+        let value: AmlValue = AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(bytes.clone())));
         let resources = resource_descriptor_list(&value).unwrap();
 
+        // Encoding the resources again should give back the same template
+        assert_eq!(encode_resource_template(&resources).unwrap(), bytes);
+
         assert_eq!(
             resources,
             Vec::from([
                 Resource::AddressSpace(AddressSpaceDescriptor {
+                    width: AddressSpaceWidth::Word,
                     resource_type: AddressSpaceResourceType::BusNumberRange,
+                    is_consumer: false,
                     is_maximum_address_fixed: true,
                     is_minimum_address_fixed: true,
                     decode_type: AddressSpaceDecodeType::Additive,
+                    type_specific_flags: 0x00,
                     granularity: 0,
                     address_range: (0x00, 0xFF),
                     translation_offset: 0,
-                    length: 0x100
+                    length: 0x100,
+                    resource_source: String::new(),
+                    resource_source_index: 0,
                 }),
                 Resource::IOPort(IOPortDescriptor {
                     decodes_full_address: true,
@@ -734,44 +1613,64 @@ mod tests {
                     range_length: 8
                 }),
                 Resource::AddressSpace(AddressSpaceDescriptor {
+                    width: AddressSpaceWidth::Word,
                     resource_type: AddressSpaceResourceType::IORange,
+                    is_consumer: false,
                     is_maximum_address_fixed: true,
                     is_minimum_address_fixed: true,
                     decode_type: AddressSpaceDecodeType::Additive,
+                    type_specific_flags: 0x03,
                     granularity: 0,
                     address_range: (0x0000, 0x0CF7),
                     translation_offset: 0,
-                    length: 0xCF8
+                    length: 0xCF8,
+                    resource_source: String::new(),
+                    resource_source_index: 0,
                 }),
                 Resource::AddressSpace(AddressSpaceDescriptor {
+                    width: AddressSpaceWidth::Word,
                     resource_type: AddressSpaceResourceType::IORange,
+                    is_consumer: false,
                     is_maximum_address_fixed: true,
                     is_minimum_address_fixed: true,
                     decode_type: AddressSpaceDecodeType::Additive,
+                    type_specific_flags: 0x03,
                     granularity: 0,
                     address_range: (0x0D00, 0xFFFF),
                     translation_offset: 0,
-                    length: 0xF300
+                    length: 0xF300,
+                    resource_source: String::new(),
+                    resource_source_index: 0,
                 }),
                 Resource::AddressSpace(AddressSpaceDescriptor {
+                    width: AddressSpaceWidth::DWord,
                     resource_type: AddressSpaceResourceType::MemoryRange,
+                    is_consumer: false,
                     is_maximum_address_fixed: true,
                     is_minimum_address_fixed: true,
                     decode_type: AddressSpaceDecodeType::Additive,
+                    type_specific_flags: 0x03,
                     granularity: 0,
                     address_range: (0xA0000, 0xBFFFF),
                     translation_offset: 0,
-                    length: 0x20000
+                    length: 0x20000,
+                    resource_source: String::new(),
+                    resource_source_index: 0,
                 }),
                 Resource::AddressSpace(AddressSpaceDescriptor {
+                    width: AddressSpaceWidth::DWord,
                     resource_type: AddressSpaceResourceType::MemoryRange,
+                    is_consumer: false,
                     is_maximum_address_fixed: true,
                     is_minimum_address_fixed: true,
                     decode_type: AddressSpaceDecodeType::Additive,
+                    type_specific_flags: 0x01,
                     granularity: 0,
                     address_range: (0xE0000000, 0xFEBFFFFF),
                     translation_offset: 0,
-                    length: 0x1EC00000
+                    length: 0x1EC00000,
+                    resource_source: String::new(),
+                    resource_source_index: 0,
                 }),
             ])
         );
@@ -836,7 +1735,8 @@ mod tests {
                     polarity: InterruptPolarity::ActiveHigh,
                     is_shared: false,
                     is_wake_capable: false,
-                    irq: (1 << 6)
+                    irq: (1 << 6),
+                    format: IrqFormat::Legacy,
                 }),
                 Resource::Dma(DMADescriptor {
                     channel_mask: 1 << 2,
@@ -847,4 +1747,144 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_gpio_and_serial_bus_crs() {
+        let bytes: Vec<u8> = [
+            // GpioInt (Edge, ActiveLow, ExclusiveAndWake, PullUp, 0x0000, "\\_SB.GPI0", 0, ResourceConsumer) { 0x12 }
+            0x8c, 0x20, 0x00, 0x01, 0x00, 0x01, 0x00, 0x13, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x17, 0x00, 0x00,
+            0x19, 0x00, 0x23, 0x00, 0x00, 0x00, 0x12, 0x00, b'\\', b'_', b'S', b'B', b'.', b'G', b'P', b'I', b'0',
+            0x00,
+            // I2cSerialBusV2 (0x50, ControllerInitiated, 400000, AddressingMode7Bit, "\\_SB.I2C0", 0, ResourceConsumer)
+            0x8e, 0x19, 0x00, 0x02, 0x00, 0x01, 0x02, 0x00, 0x00, 0x01, 0x06, 0x00, 0x80, 0x1a, 0x06, 0x00, 0x50,
+            0x00, b'\\', b'_', b'S', b'B', b'.', b'I', b'2', b'C', b'0', 0x00,
+            // UartSerialBusV2 (115200, DataBitsEight, StopBitsOne, 0xc0, LittleEndian, ParityTypeNone,
+            //     FlowControlHardware, 0x40, 0x40, "\\_SB.URT0", 0, ResourceConsumer)
+            0x8e, 0x1d, 0x00, 0x02, 0x00, 0x03, 0x02, 0x35, 0x00, 0x01, 0x0a, 0x00, 0x00, 0xc2, 0x01, 0x00, 0x40,
+            0x00, 0x40, 0x00, 0x00, 0xc0, b'\\', b'_', b'S', b'B', b'.', b'U', b'R', b'T', b'0', 0x00, 0x79, 0x00,
+        ]
+        .to_vec();
+
+        let value: AmlValue = AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(bytes.clone())));
+        let resources = resource_descriptor_list(&value).unwrap();
+        assert_buffer_eq(&encode_resource_descriptor_list(&resources).unwrap(), &bytes);
+
+        assert_eq!(
+            resources,
+            Vec::from([
+                Resource::Gpio(GpioDescriptor {
+                    is_consumer: true,
+                    connection_type: GpioConnectionType::Interrupt {
+                        trigger: InterruptTrigger::Edge,
+                        polarity: InterruptPolarity::ActiveLow,
+                        is_shared: false,
+                        is_wake_capable: true,
+                    },
+                    pin_config: GpioPinConfig::PullUp,
+                    output_drive_strength: 0,
+                    debounce_timeout: 0,
+                    pins: Vec::from([0x12]),
+                    resource_source: String::from("\\_SB.GPI0"),
+                    vendor_data: Vec::new(),
+                }),
+                Resource::SerialBus(SerialBusDescriptor {
+                    is_consumer: true,
+                    is_device_initiated: false,
+                    is_shared: false,
+                    bus: SerialBusType::I2c(I2cSerialBus {
+                        connection_speed: 400000,
+                        slave_address: 0x50,
+                        is_10_bit_addressing: false,
+                    }),
+                    resource_source: String::from("\\_SB.I2C0"),
+                    resource_source_index: 0,
+                    vendor_data: Vec::new(),
+                }),
+                Resource::SerialBus(SerialBusDescriptor {
+                    is_consumer: true,
+                    is_device_initiated: false,
+                    is_shared: false,
+                    bus: SerialBusType::Uart(UartSerialBus {
+                        baud_rate: 115200,
+                        rx_fifo_size: 0x40,
+                        tx_fifo_size: 0x40,
+                        parity: UartParity::None,
+                        lines_enabled: 0xc0,
+                        flow_control: UartFlowControl::Hardware,
+                        stop_bits: UartStopBits::One,
+                        data_bits: 8,
+                        is_big_endian: false,
+                    }),
+                    resource_source: String::from("\\_SB.URT0"),
+                    resource_source_index: 0,
+                    vendor_data: Vec::new(),
+                }),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_dependent_functions() {
+        let bytes: Vec<u8> = [
+            // StartDependentFn (0, 0) { IO (Decode16, 0x3f8, 0x3f8, 0x01, 0x08) IRQNoFlags () { 4 } }
+            0x31, 0x00, 0x47, 0x01, 0xf8, 0x03, 0xf8, 0x03, 0x01, 0x08, 0x22, 0x10, 0x00,
+            // StartDependentFnNoPri () { IO (Decode16, 0x2f8, 0x2f8, 0x01, 0x08) IRQNoFlags () { 3 } }
+            0x30, 0x47, 0x01, 0xf8, 0x02, 0xf8, 0x02, 0x01, 0x08, 0x22, 0x08, 0x00, // EndDependentFn ()
+            0x38, // DMA (Compatibility, NotBusMaster, Transfer8) { 2 }
+            0x2a, 0x04, 0x00, 0x79, 0x00,
+        ]
+        .to_vec();
+
+        let value: AmlValue = AmlValue::Buffer(Arc::new(spinning_top::Spinlock::new(bytes.clone())));
+        assert_buffer_eq(
+            &encode_resource_descriptor_list(&resource_descriptor_list(&value).unwrap()).unwrap(),
+            &bytes,
+        );
+
+        let settings = possible_resource_settings(&value).unwrap();
+        assert_eq!(settings.len(), 2);
+        assert!(settings.iter().all(|setting| setting.len() == 3 && matches!(setting[0], Resource::Dma(_))));
+        assert!(matches!(settings[0][1], Resource::IOPort(IOPortDescriptor { memory_range: (0x3f8, 0x3f8), .. })));
+
+        let irq = match settings[1][2] {
+            Resource::Irq(ref irq) => irq,
+            _ => panic!(),
+        };
+        assert_eq!(irq.irqs(), vec![3]);
+        assert_eq!(irq.with_irq(5).irqs(), vec![5]);
+    }
+
+    #[test]
+    fn test_extended_interrupts() {
+        let descriptor = IrqDescriptor {
+            is_consumer: true,
+            trigger: InterruptTrigger::Level,
+            polarity: InterruptPolarity::ActiveLow,
+            is_shared: true,
+            is_wake_capable: false,
+            irq: 16,
+            format: IrqFormat::Extended { additional_irqs: vec![17, 18] },
+        };
+        let template = encode_resource_descriptor_list(&[Resource::Irq(descriptor.clone())]).unwrap();
+        assert_buffer_eq(
+            &template,
+            &[
+                0x89, 0x0e, 0x00, 0x0d, 0x03, 0x10, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, 0x12, 0x00, 0x00,
+                0x00, 0x79, 0x00,
+            ],
+        );
+        assert_eq!(resource_descriptor_list(&template).unwrap(), vec![Resource::Irq(descriptor.clone())]);
+        assert_eq!(descriptor.irqs(), vec![16, 17, 18]);
+        assert_eq!(
+            descriptor.with_irq(18),
+            IrqDescriptor { irq: 18, format: IrqFormat::Extended { additional_irqs: vec![] }, ..descriptor }
+        );
+    }
+
+    fn assert_buffer_eq(value: &AmlValue, bytes: &[u8]) {
+        match value {
+            AmlValue::Buffer(buffer) => assert_eq!(buffer.lock().as_slice(), bytes),
+            _ => panic!("Not a buffer: {:?}", value),
+        }
+    }
 }
@@ -0,0 +1,188 @@
+//! Accesses to fields of `GenericSerialBus` operation regions, which perform transfers to devices on a serial bus
+//! (e.g. I2C or SMBus devices). Each field is connected to a serial bus connection resource by a `Connection` in
+//! its field list, and its offset (in bytes) is the command of the transfer. The protocol of the transfer is set
+//! by an `AccessAs` in the field list.
+//!
+//! Fields are accessed with buffers: reading a field, or writing a buffer to it, evaluates to a buffer of the form
+//! `{ Status, Length, Data... }`, and the data of a write is taken from the same position of the buffer written.
+//! The transfers themselves are performed by a [`SerialBusHandler`], installed with
+//! [`AmlContext::install_serial_bus_handler`].
+
+use crate::{
+    value::{FieldAccessAttrib, FieldConnection},
+    AmlContext,
+    AmlError,
+    AmlValue,
+    RegionSpace,
+};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+
+/// Handles transfers to serial bus devices, for AML that accesses `GenericSerialBus` regions. The status returned
+/// in the `Err` case of a transfer is passed to the AML, and must be non-zero.
+pub trait SerialBusHandler: Send + Sync {
+    /// Read from the device described by `connection` (a resource template containing a serial bus connection
+    /// descriptor), using `protocol` and `command`. Returns the data read.
+    fn read(&self, connection: &[u8], protocol: FieldAccessAttrib, command: u64) -> Result<Vec<u8>, u8>;
+    /// Write `data` to the device described by `connection`, using `protocol` and `command`. For process calls,
+    /// this returns the data the device responds with; otherwise, it should return no data.
+    fn write(
+        &self,
+        connection: &[u8],
+        protocol: FieldAccessAttrib,
+        command: u64,
+        data: &[u8],
+    ) -> Result<Vec<u8>, u8>;
+}
+
+impl AmlContext {
+    /// Install the handler used to perform transfers for `GenericSerialBus` fields, returning the previous one.
+    pub fn install_serial_bus_handler(
+        &mut self,
+        handler: Box<dyn SerialBusHandler>,
+    ) -> Option<Arc<dyn SerialBusHandler>> {
+        let previous = self.shared.serial_bus_handler.lock().replace(Arc::from(handler));
+        self.update_region_space(RegionSpace::GenericSerialBus);
+        previous
+    }
+
+    pub fn remove_serial_bus_handler(&mut self) -> Option<Arc<dyn SerialBusHandler>> {
+        let previous = self.shared.serial_bus_handler.lock().take();
+        self.update_region_space(RegionSpace::GenericSerialBus);
+        previous
+    }
+
+    pub(crate) fn read_serial_bus_field(
+        &self,
+        connection: Option<&FieldConnection>,
+        protocol: Option<FieldAccessAttrib>,
+        offset: u64,
+    ) -> Result<AmlValue, AmlError> {
+        let (connection, protocol, handler) = self.serial_bus_transfer(connection, protocol)?;
+        Ok(transfer_result(handler.read(&connection.resource, protocol, offset / 8)))
+    }
+
+    /// Write the data in `buffer` (which is of the form `{ Status, Length, Data... }`) to a serial bus field, and
+    /// return the result of the transfer.
+    pub(crate) fn write_serial_bus_field(
+        &self,
+        connection: Option<&FieldConnection>,
+        protocol: Option<FieldAccessAttrib>,
+        offset: u64,
+        buffer: &[u8],
+    ) -> Result<AmlValue, AmlError> {
+        let (connection, protocol, handler) = self.serial_bus_transfer(connection, protocol)?;
+
+        let length = match protocol {
+            FieldAccessAttrib::Quick => 0,
+            FieldAccessAttrib::SendReceive | FieldAccessAttrib::Byte => 1,
+            FieldAccessAttrib::Word | FieldAccessAttrib::ProcessCall => 2,
+            FieldAccessAttrib::Block | FieldAccessAttrib::BlockProcessCall => {
+                buffer.get(1).copied().unwrap_or(0) as usize
+            }
+            FieldAccessAttrib::Bytes(length)
+            | FieldAccessAttrib::RawBytes(length)
+            | FieldAccessAttrib::RawProcessBytes(length) => length as usize,
+        };
+        let data = buffer.get(2..).unwrap_or(&[]);
+        let data = &data[..usize::min(length, data.len())];
+
+        Ok(transfer_result(handler.write(&connection.resource, protocol, offset / 8, data)))
+    }
+
+    fn serial_bus_transfer<'a>(
+        &self,
+        connection: Option<&'a FieldConnection>,
+        protocol: Option<FieldAccessAttrib>,
+    ) -> Result<(&'a FieldConnection, FieldAccessAttrib, Arc<dyn SerialBusHandler>), AmlError> {
+        let connection = connection.ok_or(AmlError::FieldNotConnected)?;
+        let protocol = protocol.ok_or(AmlError::FieldNoAccessAttrib)?;
+        let handler = self
+            .shared
+            .serial_bus_handler
+            .lock()
+            .clone()
+            .ok_or(AmlError::NoRegionHandler(RegionSpace::GenericSerialBus))?;
+        Ok((connection, protocol, handler))
+    }
+}
+
+/// Build the `{ Status, Length, Data... }` buffer that a serial bus field access evaluates to.
+fn transfer_result(result: Result<Vec<u8>, u8>) -> AmlValue {
+    match result {
+        Ok(data) => {
+            let mut buffer = vec![0, data.len() as u8];
+            buffer.extend_from_slice(&data);
+            AmlValue::buffer(buffer)
+        }
+        Err(status) => AmlValue::buffer(vec![status, 0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{name_object::Target, test_utils::*, AmlName};
+    use spinning_top::Spinlock;
+
+    struct TestSerialBusHandler(Spinlock<Vec<u8>>);
+
+    impl SerialBusHandler for TestSerialBusHandler {
+        fn read(&self, connection: &[u8], protocol: FieldAccessAttrib, command: u64) -> Result<Vec<u8>, u8> {
+            assert_eq!(connection, &[0xaa, 0xbb, 0xcc]);
+            assert_eq!(protocol, FieldAccessAttrib::Byte);
+            assert_eq!(command, 2);
+            Ok(vec![0x42])
+        }
+
+        fn write(
+            &self,
+            _connection: &[u8],
+            _protocol: FieldAccessAttrib,
+            _command: u64,
+            data: &[u8],
+        ) -> Result<Vec<u8>, u8> {
+            *self.0.lock() = data.to_vec();
+            Err(0x1a)
+        }
+    }
+
+    #[test]
+    fn test_serial_bus_field() {
+        let mut context = make_test_context();
+
+        /*
+         * OperationRegion (GSB0, GenericSerialBus, 0, 0x100)
+         * Field (GSB0, BufferAcc, NoLock, Preserve) {
+         *     Connection (Buffer () { 0xaa, 0xbb, 0xcc }),
+         *     AccessAs (BufferAcc, AttribByte),
+         *     Offset (2),
+         *     CMD0, 8
+         * }
+         */
+        context
+            .parse_table(&[
+                0x5b, 0x80, b'G', b'S', b'B', b'0', 0x09, 0x00, 0x0b, 0x00, 0x01, 0x5b, 0x81, 0x18, b'G', b'S',
+                b'B', b'0', 0x05, 0x02, 0x11, 0x06, 0x0a, 0x03, 0xaa, 0xbb, 0xcc, 0x01, 0x05, 0x06, 0x00, 0x10,
+                b'C', b'M', b'D', b'0', 0x08,
+            ])
+            .unwrap();
+        let field = context.namespace.get_by_path(&AmlName::from_str("\\CMD0").unwrap()).unwrap().clone();
+
+        assert!(matches!(
+            field.read_field(&context),
+            Err(AmlError::NoRegionHandler(RegionSpace::GenericSerialBus))
+        ));
+        context.install_serial_bus_handler(Box::new(TestSerialBusHandler(Spinlock::new(Vec::new()))));
+
+        let result = field.read_field(&context).unwrap();
+        assert_eq!(*result.as_buffer(&context).unwrap().lock(), vec![0x00, 0x01, 0x42]);
+
+        let result = context
+            .store(
+                Target::Name(AmlName::from_str("\\CMD0").unwrap()),
+                AmlValue::buffer(vec![0x00, 0x01, 0x55, 0x66]),
+            )
+            .unwrap();
+        assert_eq!(*result.as_buffer(&context).unwrap().lock(), vec![0x1a, 0x00]);
+    }
+}
@@ -0,0 +1,351 @@
+//! A compact binary snapshot of the namespace, which can be saved (e.g. each boot) and compared with another
+//! snapshot, to find the objects the firmware defines differently. [`Namespace::snapshot`] produces a snapshot,
+//! and [`Snapshot::from_bytes`] reads one back.
+//!
+//! A snapshot starts with the magic `AMLS` and a version byte, followed by an entry for each object. The entries
+//! for the objects directly within a level come first, in the order of their names, followed by the entries for
+//! the objects within each of its sub-levels, so a namespace always produces the same snapshot. Each entry is made
+//! of:
+//!    - the number of segments in the object's path, as a byte, followed by the segments
+//!    - a tag byte for the object's type. Levels that don't have an object of their own (e.g. scopes) are tagged
+//!      by their level type
+//!    - for data objects, the value: integers as 8 little-endian bytes, strings and buffers as a 4-byte
+//!      little-endian length followed by their bytes, and packages as a 4-byte element count followed by the tag
+//!      and value of each element
+//!
+//! The values of other objects, like fields and methods, aren't recorded, as reading them could have side effects.
+
+use crate::{
+    name_object::NameSeg,
+    value::AmlType,
+    AmlError,
+    AmlName,
+    AmlValue,
+    LevelType,
+    NameComponent,
+    Namespace,
+    NamespaceLevel,
+};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{convert::TryInto, str};
+
+const MAGIC: &[u8; 4] = b"AMLS";
+const VERSION: u8 = 1;
+
+/// The object types, in the order of their tags.
+const OBJECT_TYPES: [AmlType; 19] = [
+    AmlType::Uninitialized,
+    AmlType::Buffer,
+    AmlType::BufferField,
+    AmlType::DdbHandle,
+    AmlType::DebugObject,
+    AmlType::Event,
+    AmlType::FieldUnit,
+    AmlType::Device,
+    AmlType::Integer,
+    AmlType::Method,
+    AmlType::Mutex,
+    AmlType::ObjReference,
+    AmlType::OpRegion,
+    AmlType::Package,
+    AmlType::PowerResource,
+    AmlType::Processor,
+    AmlType::RawDataBuffer,
+    AmlType::String,
+    AmlType::ThermalZone,
+];
+
+/// The level types, in the order of their tags, which start at `LEVEL_TAG_BASE`.
+const LEVEL_TYPES: [LevelType; 6] = [
+    LevelType::Scope,
+    LevelType::Device,
+    LevelType::Processor,
+    LevelType::PowerResource,
+    LevelType::ThermalZone,
+    LevelType::MethodLocals,
+];
+const LEVEL_TAG_BASE: u8 = 0x80;
+
+/// An object recorded in a [`Snapshot`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SnapshotObject {
+    /// A level that doesn't have an object of its own, such as a `Scope`.
+    Level(LevelType),
+    /// An object whose value isn't recorded, such as a method or a field.
+    Object(AmlType),
+    Integer(u64),
+    String(String),
+    Buffer(Vec<u8>),
+    Package(Vec<SnapshotObject>),
+}
+
+/// A difference between two snapshots, produced by [`Snapshot::diff`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SnapshotDiff<'a> {
+    /// An object that's only in the new snapshot.
+    Added(&'a str, &'a SnapshotObject),
+    /// An object that's only in the old snapshot.
+    Removed(&'a str, &'a SnapshotObject),
+    /// An object whose type or value is different in the new snapshot.
+    Changed { path: &'a str, old: &'a SnapshotObject, new: &'a SnapshotObject },
+}
+
+/// A snapshot read back with [`Snapshot::from_bytes`], with the objects it records by their paths.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Snapshot {
+    objects: BTreeMap<String, SnapshotObject>,
+}
+
+impl Snapshot {
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Snapshot, AmlError> {
+        if take(&mut bytes, 4)? != MAGIC || take(&mut bytes, 1)?[0] != VERSION {
+            return Err(AmlError::InvalidSnapshot);
+        }
+
+        let mut objects = BTreeMap::new();
+        while !bytes.is_empty() {
+            let segments = take(&mut bytes, 1)?[0] as usize;
+            let mut path = String::from("\\");
+            for i in 0..segments {
+                let seg = str::from_utf8(take(&mut bytes, 4)?)
+                    .ok()
+                    .and_then(|seg| NameSeg::from_str(seg).ok())
+                    .ok_or(AmlError::InvalidSnapshot)?;
+                if i != 0 {
+                    path.push('.');
+                }
+                path.push_str(seg.as_str());
+            }
+
+            let object = read_object(&mut bytes)?;
+            objects.insert(path, object);
+        }
+
+        Ok(Snapshot { objects })
+    }
+
+    /// The objects in the snapshot, by their absolute paths, with each segment padded to four characters (e.g.
+    /// `\_SB_.PCI0`).
+    pub fn objects(&self) -> &BTreeMap<String, SnapshotObject> {
+        &self.objects
+    }
+
+    /// Find the objects that were added, removed, or changed between this snapshot and `new`, in the order of
+    /// their paths.
+    pub fn diff<'a>(&'a self, new: &'a Snapshot) -> Vec<SnapshotDiff<'a>> {
+        let mut paths: Vec<&String> = self.objects.keys().chain(new.objects.keys()).collect();
+        paths.sort();
+        paths.dedup();
+
+        paths
+            .into_iter()
+            .filter_map(|path| match (self.objects.get(path), new.objects.get(path)) {
+                (Some(old), Some(new)) if old == new => None,
+                (Some(old), Some(new)) => Some(SnapshotDiff::Changed { path, old, new }),
+                (Some(old), None) => Some(SnapshotDiff::Removed(path, old)),
+                (None, Some(new)) => Some(SnapshotDiff::Added(path, new)),
+                (None, None) => unreachable!(),
+            })
+            .collect()
+    }
+}
+
+impl Namespace {
+    /// Produce a snapshot of the objects in the namespace, in the format described in the [`snapshot`](self)
+    /// module. The levels holding the locals of methods being executed are left out.
+    pub fn snapshot(&self) -> Result<Vec<u8>, AmlError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+
+        self.traverse(|path, level: &NamespaceLevel| {
+            if level.typ == LevelType::MethodLocals {
+                return Ok(false);
+            }
+
+            let mut names: Vec<NameSeg> = level.values.keys().chain(level.children.keys()).copied().collect();
+            names.sort();
+            names.dedup();
+
+            for name in names {
+                let child = level.children.get(&name).filter(|child| child.typ != LevelType::MethodLocals);
+                if child.is_none() && !level.values.contains_key(&name) {
+                    continue;
+                }
+
+                let path = AmlName::from_name_seg(name).resolve(path)?;
+                write_path(&mut bytes, &path);
+                match level.values.get(&name) {
+                    Some(&handle) => write_value(&mut bytes, &self.get(handle)?),
+                    None => bytes.push(level_tag(child.unwrap().typ)),
+                }
+            }
+
+            Ok(true)
+        })?;
+
+        Ok(bytes)
+    }
+}
+
+fn write_path(bytes: &mut Vec<u8>, path: &AmlName) {
+    let segments: Vec<&NameSeg> = path
+        .components()
+        .iter()
+        .filter_map(|component| match component {
+            NameComponent::Segment(seg) => Some(seg),
+            _ => None,
+        })
+        .collect();
+    bytes.push(segments.len() as u8);
+    for seg in segments {
+        bytes.extend_from_slice(&seg.0);
+    }
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &AmlValue) {
+    bytes.push(object_tag(value.type_of()));
+    match value {
+        AmlValue::Boolean(value) => bytes.extend_from_slice(&(*value as u64).to_le_bytes()),
+        AmlValue::Integer(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+        AmlValue::String(string) => write_bytes(bytes, string.as_bytes()),
+        AmlValue::Buffer(buffer) => write_bytes(bytes, &buffer.lock()),
+        AmlValue::Package(elements) => {
+            bytes.extend_from_slice(&(elements.len() as u32).to_le_bytes());
+            for element in elements.iter() {
+                write_value(bytes, element);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+}
+
+fn object_tag(typ: AmlType) -> u8 {
+    OBJECT_TYPES.iter().position(|&object_type| object_type == typ).unwrap() as u8
+}
+
+fn level_tag(typ: LevelType) -> u8 {
+    LEVEL_TAG_BASE + LEVEL_TYPES.iter().position(|&level_type| level_type == typ).unwrap() as u8
+}
+
+fn read_object(bytes: &mut &[u8]) -> Result<SnapshotObject, AmlError> {
+    let tag = take(bytes, 1)?[0];
+    if tag >= LEVEL_TAG_BASE {
+        let typ = LEVEL_TYPES.get((tag - LEVEL_TAG_BASE) as usize).ok_or(AmlError::InvalidSnapshot)?;
+        return Ok(SnapshotObject::Level(*typ));
+    }
+
+    match OBJECT_TYPES.get(tag as usize).ok_or(AmlError::InvalidSnapshot)? {
+        AmlType::Integer => Ok(SnapshotObject::Integer(u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap()))),
+        AmlType::String => {
+            let string = str::from_utf8(read_bytes(bytes)?).map_err(|_| AmlError::InvalidSnapshot)?;
+            Ok(SnapshotObject::String(string.to_string()))
+        }
+        AmlType::Buffer => Ok(SnapshotObject::Buffer(read_bytes(bytes)?.to_vec())),
+        AmlType::Package => {
+            let count = read_u32(bytes)?;
+            (0..count).map(|_| read_object(bytes)).collect::<Result<_, _>>().map(SnapshotObject::Package)
+        }
+        typ => Ok(SnapshotObject::Object(*typ)),
+    }
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], AmlError> {
+    let length = read_u32(bytes)?;
+    take(bytes, length as usize)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, AmlError> {
+    Ok(u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap()))
+}
+
+fn take<'a>(bytes: &mut &'a [u8], length: usize) -> Result<&'a [u8], AmlError> {
+    if bytes.len() < length {
+        return Err(AmlError::InvalidSnapshot);
+    }
+    let (taken, rest) = bytes.split_at(length);
+    *bytes = rest;
+    Ok(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, AmlContext};
+    use alloc::vec;
+
+    fn snapshot(context: &AmlContext) -> Snapshot {
+        Snapshot::from_bytes(&context.namespace.snapshot().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut context = make_test_context();
+        let add = |context: &mut AmlContext, name, value| {
+            context.namespace.add_value(AmlName::from_str(name).unwrap(), value).unwrap();
+        };
+        context.namespace.add_level(AmlName::from_str("\\_SB.PCI0").unwrap(), LevelType::Device).unwrap();
+        add(&mut context, "\\_SB.PCI0", AmlValue::Device);
+        add(&mut context, "\\_SB.PCI0._ADR", AmlValue::Integer(0x1f0003));
+        add(
+            &mut context,
+            "\\_SB.PCI0._CRS",
+            AmlValue::Buffer(alloc::sync::Arc::new(spinning_top::Spinlock::new(vec![0x79, 0x00]))),
+        );
+        add(
+            &mut context,
+            "\\_SB.PCI0._PRW",
+            AmlValue::package(vec![AmlValue::Integer(0x0d), AmlValue::String("S3".into())]),
+        );
+        add(&mut context, "\\_SB.PCI0._STA", AmlValue::native_method(0, false, 0, |_| Ok(AmlValue::Integer(0xf))));
+
+        let old = snapshot(&context);
+        assert_eq!(old.objects()["\\_SB_"], SnapshotObject::Level(LevelType::Scope));
+        assert_eq!(old.objects()["\\_SB_.PCI0"], SnapshotObject::Object(AmlType::Device));
+        assert_eq!(old.objects()["\\_SB_.PCI0._ADR"], SnapshotObject::Integer(0x1f0003));
+        assert_eq!(old.objects()["\\_SB_.PCI0._CRS"], SnapshotObject::Buffer(vec![0x79, 0x00]));
+        assert_eq!(
+            old.objects()["\\_SB_.PCI0._PRW"],
+            SnapshotObject::Package(vec![SnapshotObject::Integer(0x0d), SnapshotObject::String("S3".into())])
+        );
+        assert_eq!(old.objects()["\\_SB_.PCI0._STA"], SnapshotObject::Object(AmlType::Method));
+        assert_eq!(context.namespace.snapshot().unwrap(), context.namespace.snapshot().unwrap());
+
+        // The levels holding the locals of methods aren't recorded
+        let sta = AmlName::from_str("\\_SB_.PCI0._STA").unwrap();
+        context.namespace.add_level(sta.clone(), LevelType::MethodLocals).unwrap();
+        assert_eq!(snapshot(&context), old);
+        context.namespace.remove_level(sta.clone()).unwrap();
+
+        let adr = context.namespace.get_handle(&AmlName::from_str("\\_SB_.PCI0._ADR").unwrap()).unwrap();
+        context.namespace.set(adr, AmlValue::Integer(0x1f0004)).unwrap();
+        context.namespace.remove_value(sta).unwrap();
+        add(&mut context, "\\_SB_.PCI0._UID", AmlValue::Integer(1));
+        let new = snapshot(&context);
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                SnapshotDiff::Changed {
+                    path: "\\_SB_.PCI0._ADR",
+                    old: &SnapshotObject::Integer(0x1f0003),
+                    new: &SnapshotObject::Integer(0x1f0004)
+                },
+                SnapshotDiff::Removed("\\_SB_.PCI0._STA", &SnapshotObject::Object(AmlType::Method)),
+                SnapshotDiff::Added("\\_SB_.PCI0._UID", &SnapshotObject::Integer(1)),
+            ]
+        );
+
+        assert_eq!(Snapshot::from_bytes(b"AMLS\x02"), Err(AmlError::InvalidSnapshot));
+        let bytes = context.namespace.snapshot().unwrap();
+        assert_eq!(Snapshot::from_bytes(&bytes[..bytes.len() - 1]), Err(AmlError::InvalidSnapshot));
+    }
+}
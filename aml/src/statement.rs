@@ -1,4 +1,5 @@
 use crate::{
+    name_object::{name_string, super_name},
     opcode::{self, ext_opcode, opcode},
     parser::{
         choice,
@@ -15,10 +16,12 @@ use crate::{
     },
     pkg_length::{pkg_length, PkgLength},
     term_object::{term_arg, term_list},
+    value::{AmlType, AmlValue, DdbHandle},
     AmlContext,
     AmlError,
     DebugVerbosity,
 };
+use log::warn;
 
 pub fn statement_opcode<'a, 'c>() -> impl Parser<'a, 'c, ()>
 where
@@ -26,7 +29,8 @@ where
 {
     /*
      * StatementOpcode := DefBreak | DefBreakPoint | DefContinue | DefFatal | DefIfElse | DefLoad | DefNoop |
-     *                    DefNotify | DefRelease | DefReset | DefReturn | DefSignal | DefSleep | DefStall | DefWhile
+     *                    DefNotify | DefRelease | DefReset | DefReturn | DefSignal | DefSleep | DefStall |
+     *                    DefUnload | DefWhile
      */
     comment_scope(
         DebugVerbosity::AllScopes,
@@ -37,8 +41,16 @@ where
             def_continue(),
             def_fatal(),
             def_if_else(),
+            def_load(),
             def_noop(),
+            def_notify(),
+            def_release(),
+            def_reset(),
             def_return(),
+            def_signal(),
+            def_sleep(),
+            def_stall(),
+            def_unload(),
             def_while()
         ),
     )
@@ -66,8 +78,7 @@ where
 {
     /*
      * DefBreakPoint := 0xcc
-     * TODO: there is no debugger, so this doesn't do anything. If there was, this should stop execution and enter
-     * the AML debugger.
+     * With the `debug` feature, the debugger is called before this is executed, so it doesn't need to do anything.
      */
     opcode(opcode::DEF_BREAKPOINT_OP)
         .then(comment_scope(DebugVerbosity::AllScopes, "DefBreakPoint", id()))
@@ -115,6 +126,30 @@ where
         .discard_result()
 }
 
+fn def_load<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefLoad := ExtOpPrefix 0x20 NameString DDBHandleObject
+     * DDBHandleObject := SuperName
+     *
+     * The NameString refers to the operation region, field, or buffer that contains the definition block.
+     */
+    ext_opcode(opcode::EXT_DEF_LOAD_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefLoad",
+            name_string().then(super_name()).map_with_context(|(name, target), context| {
+                let table = try_with_context!(context, context.read_definition_block(&name));
+                let handle = try_with_context!(context, context.load_table(&table));
+                try_with_context!(context, context.store(target, AmlValue::DdbHandle(handle)));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
 fn def_if_else<'a, 'c>() -> impl Parser<'a, 'c, ()>
 where
     'c: 'a,
@@ -176,6 +211,28 @@ where
     opcode(opcode::DEF_NOOP_OP).then(comment_scope(DebugVerbosity::AllScopes, "DefNoop", id())).discard_result()
 }
 
+fn def_notify<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefNotify := 0x86 NotifyObject NotifyValue
+     * NotifyObject := SuperName => ThermalZone | Processor | Device
+     * NotifyValue := TermArg => Integer
+     */
+    opcode(opcode::DEF_NOTIFY_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefNotify",
+            super_name().then(term_arg()).map_with_context(|(object, value), context| {
+                let value = try_with_context!(context, value.as_integer(context));
+                try_with_context!(context, context.queue_notification(&object, value));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
 fn def_return<'a, 'c>() -> impl Parser<'a, 'c, ()>
 where
     'c: 'a,
@@ -201,6 +258,154 @@ where
         .discard_result()
 }
 
+fn def_release<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefRelease := ExtOpPrefix 0x27 MutexObject
+     * MutexObject := SuperName
+     */
+    ext_opcode(opcode::EXT_DEF_RELEASE_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefRelease",
+            super_name().map_with_context(|mutex, context| {
+                try_with_context!(context, context.release_mutex(&mutex));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
+fn def_reset<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefReset := ExtOpPrefix 0x26 EventObject
+     * EventObject := SuperName
+     */
+    ext_opcode(opcode::EXT_DEF_RESET_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefReset",
+            super_name().map_with_context(|event, context| {
+                try_with_context!(context, context.reset_event(&event));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
+fn def_signal<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefSignal := ExtOpPrefix 0x24 EventObject
+     * EventObject := SuperName
+     */
+    ext_opcode(opcode::EXT_DEF_SIGNAL_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefSignal",
+            super_name().map_with_context(|event, context| {
+                try_with_context!(context, context.signal_event(&event));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
+fn def_sleep<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefSleep := ExtOpPrefix 0x22 MsecTime
+     * MsecTime := TermArg => Integer
+     */
+    ext_opcode(opcode::EXT_DEF_SLEEP_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefSleep",
+            term_arg().map_with_context(|milliseconds, context| {
+                let milliseconds = try_with_context!(context, milliseconds.as_integer(context));
+                context.handler.sleep(milliseconds);
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
+fn def_stall<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefStall := ExtOpPrefix 0x21 UsecTime
+     * UsecTime := TermArg => ByteData
+     *
+     * The spec says stalls should be no longer than 100 microseconds, as the processor busy-waits for them. Like
+     * ACPICA, we allow stalls of up to 255 microseconds, as real firmware makes them.
+     */
+    ext_opcode(opcode::EXT_DEF_STALL_OP)
+        .then(comment_scope(
+            DebugVerbosity::AllScopes,
+            "DefStall",
+            term_arg().map_with_context(|microseconds, context| {
+                let microseconds = try_with_context!(context, microseconds.as_integer(context));
+                if microseconds > 255 {
+                    return (Err(Propagate::Err(AmlError::InvalidStallTime(microseconds))), context);
+                } else if microseconds > 100 {
+                    warn!("AML stalled for {} microseconds, which is more than the spec allows", microseconds);
+                }
+                context.handler.stall(microseconds);
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
+fn def_unload<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefUnload := ExtOpPrefix 0x2a DDBHandleObject
+     * DDBHandleObject := SuperName
+     */
+    ext_opcode(opcode::EXT_DEF_UNLOAD_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefUnload",
+            super_name().map_with_context(|target, context| {
+                /*
+                 * Firmware usually stores the handle returned by `Load` into a named integer, which converts it to
+                 * an integer, so we accept integers as handles too.
+                 */
+                let handle = match try_with_context!(context, context.read_target(&target)) {
+                    AmlValue::DdbHandle(handle) => handle,
+                    AmlValue::Integer(handle) => DdbHandle(handle),
+                    other => {
+                        let current = other.type_of();
+                        return (
+                            Err(Propagate::Err(AmlError::IncompatibleValueConversion {
+                                current,
+                                target: AmlType::DdbHandle,
+                            })),
+                            context,
+                        );
+                    }
+                };
+                try_with_context!(context, context.unload_table(handle));
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
 fn def_while<'a, 'c>() -> impl Parser<'a, 'c, ()>
 where
     'c: 'a,
@@ -228,7 +433,11 @@ where
                         return (Ok(()), context);
                     }
 
+                    let mut iterations = 0;
                     loop {
+                        try_with_context!(context, context.check_loop_iterations(iterations));
+                        iterations += 1;
+
                         match term_list(PkgLength::from_raw_length(body, body.len() as u32).unwrap())
                             .parse(body, context)
                         {
@@ -0,0 +1,410 @@
+//! Support for AML's synchronization objects: `Mutex`es, which are acquired with `Acquire` and released with
+//! `Release`, and `Event`s, which are waited on with `Wait`, and signalled with `Signal` and `Reset`.
+//!
+//! The interpreter tracks which mutexes the executing AML holds, and enforces the rules around their sync levels,
+//! but the blocking itself is delegated to a [`SyncHandler`], so that hosts can integrate it with their scheduler
+//! (e.g. so a mutex can also be held by an OS driver while it talks to the same hardware). By default, a
+//! [`SpinSyncHandler`] is used, which is suitable for single-threaded hosts.
+//!
+//! The Global Lock, which synchronizes the OS with the firmware through the FACS, is acquired by AML either by
+//! acquiring the predefined `\_GL` mutex, or by accessing a field with the `Lock` rule. In both cases, the
+//! interpreter calls [`SyncHandler::acquire_global_lock`] rather than treating it as a normal mutex.
+//!
+//! Serialized control methods can only be executed by one evaluation at a time. Each is treated as an implicit
+//! mutex, identified by the handle of the method and at its sync level, which is acquired through the
+//! `SyncHandler` for the duration of the method.
+
+use crate::{name_object::Target, AmlContext, AmlError, AmlHandle, AmlValue};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use core::sync::atomic::Ordering;
+use spinning_top::Spinlock;
+
+/// Passed as the timeout of an acquire or wait to wait forever.
+pub const WAIT_FOREVER: u16 = 0xffff;
+
+/// Handles the blocking operations on AML's synchronization objects. Mutexes and events are identified by the
+/// handles of their objects in the namespace. Installed with [`AmlContext::install_sync_handler`].
+pub trait SyncHandler: Send + Sync {
+    /// Acquire `mutex`, waiting for up to `timeout` milliseconds for it to become available (or forever, if
+    /// `timeout` is [`WAIT_FOREVER`]). Returns whether the mutex was acquired. The interpreter handles a mutex
+    /// being acquired multiple times by the same AML, so this is only called when the AML doesn't already hold it.
+    fn acquire(&self, mutex: AmlHandle, timeout: u16) -> bool;
+    fn release(&self, mutex: AmlHandle);
+
+    /// Wait for up to `timeout` milliseconds (or forever, if `timeout` is [`WAIT_FOREVER`]) for `event` to be
+    /// signalled, and then consume one of its signals. Returns whether the event was signalled.
+    fn wait(&self, event: AmlHandle, timeout: u16) -> bool;
+    fn signal(&self, event: AmlHandle);
+    /// Clear any outstanding signals of `event`.
+    fn reset(&self, event: AmlHandle);
+
+    /// Acquire the Global Lock, waiting for up to `timeout` milliseconds (or forever, if `timeout` is
+    /// [`WAIT_FOREVER`]). Returns whether it was acquired. Hosts should both exclude other threads of the OS, and
+    /// acquire the lock in the FACS (e.g. with `acpi::Facs::try_acquire_global_lock`), waiting for the firmware
+    /// to release it if it's pending. By default, this does nothing, which is correct for hosts without a FACS.
+    fn acquire_global_lock(&self, _timeout: u16) -> bool {
+        true
+    }
+
+    /// Release the Global Lock. If the firmware was waiting for it, hosts should signal that it's been released
+    /// by setting `GBL_RLS` in the PM1 control register.
+    fn release_global_lock(&self) {}
+}
+
+/// A `SyncHandler` that never blocks, for single-threaded hosts. As nothing else can release a mutex or signal an
+/// event while AML is waiting on it, acquiring a mutex held elsewhere, or waiting on an event that hasn't been
+/// signalled, times out immediately.
+#[derive(Default)]
+pub struct SpinSyncHandler {
+    held_mutexes: Spinlock<BTreeMap<AmlHandle, ()>>,
+    event_signals: Spinlock<BTreeMap<AmlHandle, u64>>,
+}
+
+impl SpinSyncHandler {
+    pub fn new() -> SpinSyncHandler {
+        SpinSyncHandler::default()
+    }
+}
+
+impl SyncHandler for SpinSyncHandler {
+    fn acquire(&self, mutex: AmlHandle, _timeout: u16) -> bool {
+        self.held_mutexes.lock().insert(mutex, ()).is_none()
+    }
+
+    fn release(&self, mutex: AmlHandle) {
+        self.held_mutexes.lock().remove(&mutex);
+    }
+
+    fn wait(&self, event: AmlHandle, _timeout: u16) -> bool {
+        match self.event_signals.lock().get_mut(&event) {
+            Some(signals) if *signals > 0 => {
+                *signals -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn signal(&self, event: AmlHandle) {
+        *self.event_signals.lock().entry(event).or_insert(0) += 1;
+    }
+
+    fn reset(&self, event: AmlHandle) {
+        self.event_signals.lock().remove(&event);
+    }
+}
+
+/// A mutex held by the executing AML.
+#[derive(Debug)]
+pub(crate) struct HeldMutex {
+    handle: AmlHandle,
+    /// The number of times the mutex has been acquired without being released.
+    depth: usize,
+    /// The sync level before the mutex was acquired, which is restored when it's released.
+    previous_sync_level: u8,
+}
+
+impl AmlContext {
+    /// Install the handler used to block on mutexes and events, returning the previous one.
+    pub fn install_sync_handler(&mut self, handler: Box<dyn SyncHandler>) -> Arc<dyn SyncHandler> {
+        core::mem::replace(&mut *self.shared.sync_handler.lock(), Arc::from(handler))
+    }
+
+    fn sync_handler(&self) -> Arc<dyn SyncHandler> {
+        self.shared.sync_handler.lock().clone()
+    }
+
+    /// Acquire the mutex referred to by `target`. Returns whether the acquire timed out (which is the value the
+    /// `Acquire` operator evaluates to).
+    pub(crate) fn acquire_mutex(&mut self, target: &Target, timeout: u16) -> Result<bool, AmlError> {
+        let (handle, sync_level) = self.resolve_mutex(target)?;
+        Ok(!self.acquire_mutex_handle(handle, sync_level, timeout)?)
+    }
+
+    /// Acquire the mutex with the given handle and sync level for the executing AML, which can be an AML `Mutex`,
+    /// or the implicit mutex of a serialized method. Returns whether it was acquired.
+    pub(crate) fn acquire_mutex_handle(
+        &mut self,
+        handle: AmlHandle,
+        sync_level: u8,
+        timeout: u16,
+    ) -> Result<bool, AmlError> {
+        if let Some(held) = self.held_mutexes.iter_mut().find(|held| held.handle == handle) {
+            held.depth += 1;
+            return Ok(true);
+        }
+
+        /*
+         * To prevent deadlocks, a mutex can't be acquired while a mutex with a higher sync level is held.
+         */
+        if sync_level < self.sync_level {
+            return Err(AmlError::MutexSyncLevelTooLow {
+                mutex_level: sync_level,
+                current_level: self.sync_level,
+            });
+        }
+        let acquired = if Some(handle) == self.global_lock {
+            self.sync_handler().acquire_global_lock(timeout)
+        } else {
+            self.sync_handler().acquire(handle, timeout)
+        };
+        if !acquired {
+            return Ok(false);
+        }
+
+        self.held_mutexes.push(HeldMutex { handle, depth: 1, previous_sync_level: self.sync_level });
+        self.sync_level = sync_level;
+        Ok(true)
+    }
+
+    pub(crate) fn release_mutex(&mut self, target: &Target) -> Result<(), AmlError> {
+        let (handle, sync_level) = self.resolve_mutex(target)?;
+        self.release_mutex_handle(handle, sync_level)
+    }
+
+    pub(crate) fn release_mutex_handle(&mut self, handle: AmlHandle, sync_level: u8) -> Result<(), AmlError> {
+        let index =
+            self.held_mutexes.iter().position(|held| held.handle == handle).ok_or(AmlError::MutexNotAcquired)?;
+        if self.held_mutexes[index].depth > 1 {
+            self.held_mutexes[index].depth -= 1;
+            return Ok(());
+        }
+
+        /*
+         * Mutexes must be released in the reverse order of their sync levels, so a mutex can't be released while
+         * one with a higher sync level is still held.
+         */
+        if sync_level < self.sync_level {
+            return Err(AmlError::MutexSyncLevelTooLow {
+                mutex_level: sync_level,
+                current_level: self.sync_level,
+            });
+        }
+
+        let held = self.held_mutexes.remove(index);
+        self.release_handler_mutex(handle);
+        self.sync_level = held.previous_sync_level;
+        Ok(())
+    }
+
+    /// Wait on the event referred to by `target`. Returns whether the wait timed out (which is the value the
+    /// `Wait` operator evaluates to).
+    pub(crate) fn wait_event(&mut self, target: &Target, timeout: u16) -> Result<bool, AmlError> {
+        let handle = self.resolve_event(target)?;
+        Ok(!self.sync_handler().wait(handle, timeout))
+    }
+
+    pub(crate) fn signal_event(&mut self, target: &Target) -> Result<(), AmlError> {
+        let handle = self.resolve_event(target)?;
+        self.sync_handler().signal(handle);
+        Ok(())
+    }
+
+    pub(crate) fn reset_event(&mut self, target: &Target) -> Result<(), AmlError> {
+        let handle = self.resolve_event(target)?;
+        self.sync_handler().reset(handle);
+        Ok(())
+    }
+
+    /// Release mutexes that were held by the executing AML (in the reverse of the order they were acquired),
+    /// e.g. when a method that acquired them fails.
+    pub(crate) fn release_all_mutexes(&mut self, held_mutexes: Vec<HeldMutex>) {
+        for held in held_mutexes.into_iter().rev() {
+            self.release_handler_mutex(held.handle);
+            self.sync_level = held.previous_sync_level;
+        }
+    }
+
+    fn release_handler_mutex(&self, handle: AmlHandle) {
+        if Some(handle) == self.global_lock {
+            self.sync_handler().release_global_lock();
+        } else {
+            self.sync_handler().release(handle);
+        }
+    }
+
+    /// Run `f` with the Global Lock held, if `lock` is set (e.g. to access a field with the `Lock` rule). The lock
+    /// isn't acquired again if the AML already holds it.
+    pub(crate) fn with_global_lock<T, F>(&self, lock: bool, f: F) -> Result<T, AmlError>
+    where
+        F: FnOnce() -> Result<T, AmlError>,
+    {
+        let held = self.field_holds_global_lock.load(Ordering::Acquire)
+            || self.held_mutexes.iter().any(|held| Some(held.handle) == self.global_lock);
+        if !lock || held {
+            return f();
+        }
+
+        let sync_handler = self.sync_handler();
+        if !sync_handler.acquire_global_lock(WAIT_FOREVER) {
+            return Err(AmlError::GlobalLockNotAcquired);
+        }
+        self.field_holds_global_lock.store(true, Ordering::Release);
+        let result = f();
+        self.field_holds_global_lock.store(false, Ordering::Release);
+        sync_handler.release_global_lock();
+        result
+    }
+
+    fn resolve_mutex(&self, target: &Target) -> Result<(AmlHandle, u8), AmlError> {
+        let handle = self.resolve_sync_object(target)?;
+        match self.namespace.get(handle)? {
+            AmlValue::Mutex { sync_level } => Ok((handle, sync_level)),
+            other => Err(AmlError::IncompatibleValueConversion {
+                current: other.type_of(),
+                target: crate::value::AmlType::Mutex,
+            }),
+        }
+    }
+
+    fn resolve_event(&self, target: &Target) -> Result<AmlHandle, AmlError> {
+        let handle = self.resolve_sync_object(target)?;
+        match self.namespace.get(handle)? {
+            AmlValue::Event => Ok(handle),
+            other => Err(AmlError::IncompatibleValueConversion {
+                current: other.type_of(),
+                target: crate::value::AmlType::Event,
+            }),
+        }
+    }
+
+    /// Synchronization objects are identified by their handles, so they must be referred to by name.
+    fn resolve_sync_object(&self, target: &Target) -> Result<AmlHandle, AmlError> {
+        match target {
+            Target::Name(name) => Ok(self.namespace.search(name, &self.current_scope)?.1),
+            _ => Err(AmlError::Unimplemented),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::*, value::Args, AmlName};
+
+    #[test]
+    fn test_mutexes_and_events() {
+        let mut context = make_test_context();
+
+        // Mutex (MTX0, 2), Mutex (MTX1, 5), Event (EVT0)
+        context
+            .parse_table(&[
+                0x5b, 0x01, b'M', b'T', b'X', b'0', 0x02, 0x5b, 0x01, b'M', b'T', b'X', b'1', 0x05, 0x5b, 0x02,
+                b'E', b'V', b'T', b'0',
+            ])
+            .unwrap();
+        let mtx0 = Target::Name(AmlName::from_str("MTX0").unwrap());
+        let mtx1 = Target::Name(AmlName::from_str("MTX1").unwrap());
+        let evt0 = Target::Name(AmlName::from_str("EVT0").unwrap());
+
+        assert_eq!(context.acquire_mutex(&mtx0, WAIT_FOREVER), Ok(false));
+        assert_eq!(context.acquire_mutex(&mtx1, WAIT_FOREVER), Ok(false));
+        assert_eq!(context.acquire_mutex(&mtx1, WAIT_FOREVER), Ok(false));
+
+        // `MTX0` has a lower sync level than `MTX1`, so it can't be released until `MTX1` is
+        assert_eq!(
+            context.release_mutex(&mtx0),
+            Err(AmlError::MutexSyncLevelTooLow { mutex_level: 2, current_level: 5 })
+        );
+        assert_eq!(context.release_mutex(&mtx1), Ok(()));
+        assert_eq!(context.release_mutex(&mtx1), Ok(()));
+        assert_eq!(context.release_mutex(&mtx1), Err(AmlError::MutexNotAcquired));
+        assert_eq!(context.release_mutex(&mtx0), Ok(()));
+
+        assert_eq!(context.wait_event(&evt0, 0), Ok(true));
+        context.signal_event(&evt0).unwrap();
+        context.signal_event(&evt0).unwrap();
+        assert_eq!(context.wait_event(&evt0, 0), Ok(false));
+        context.reset_event(&evt0).unwrap();
+        assert_eq!(context.wait_event(&evt0, 0), Ok(true));
+    }
+
+    /// Blocks until mutexes are released by other threads.
+    #[derive(Default)]
+    struct BlockingSyncHandler(Spinlock<BTreeMap<AmlHandle, ()>>);
+
+    impl SyncHandler for BlockingSyncHandler {
+        fn acquire(&self, mutex: AmlHandle, _timeout: u16) -> bool {
+            while self.0.lock().insert(mutex, ()).is_some() {
+                std::thread::yield_now();
+            }
+            true
+        }
+
+        fn release(&self, mutex: AmlHandle) {
+            self.0.lock().remove(&mutex);
+        }
+
+        fn wait(&self, _event: AmlHandle, _timeout: u16) -> bool {
+            unimplemented!()
+        }
+        fn signal(&self, _event: AmlHandle) {
+            unimplemented!()
+        }
+        fn reset(&self, _event: AmlHandle) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_serialized_methods() {
+        let mut context = make_test_context();
+        context.install_sync_handler(Box::new(BlockingSyncHandler::default()));
+
+        /*
+         * `INC` increments `CNT`, yielding between reading and writing it, so increments are lost if it's executed
+         * by more than one thread at a time. `REC` invokes `INC` while holding its own implicit mutex, and then,
+         * if `Arg0` is non-zero, itself, which is allowed.
+         */
+        let count = AmlName::from_str("\\CNT").unwrap();
+        let inc = AmlName::from_str("\\INC").unwrap();
+        let rec = AmlName::from_str("\\REC").unwrap();
+        context.namespace.add_value(count.clone(), AmlValue::Integer(0)).unwrap();
+        context
+            .namespace
+            .add_value(
+                inc.clone(),
+                AmlValue::native_method(0, true, 0, |context| {
+                    let count = AmlName::from_str("\\CNT").unwrap();
+                    let value = context.namespace.get_by_path(&count)?.as_integer(context)?;
+                    std::thread::yield_now();
+                    let handle = context.namespace.get_handle(&count)?;
+                    context.namespace.set(handle, AmlValue::Integer(value + 1))?;
+                    Ok(AmlValue::Integer(value + 1))
+                }),
+            )
+            .unwrap();
+        context
+            .namespace
+            .add_value(
+                rec.clone(),
+                AmlValue::native_method(1, true, 0, |context| {
+                    context.invoke_method(&AmlName::from_str("\\INC").unwrap(), Args::EMPTY)?;
+                    if context.current_arg(0)?.as_integer(context)? != 0 {
+                        context.invoke(&AmlName::from_str("\\REC").unwrap(), &[0u64.into()])?;
+                    }
+                    Ok(AmlValue::Integer(0))
+                }),
+            )
+            .unwrap();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let mut context = context.fork();
+                let (inc, rec) = (inc.clone(), rec.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        context.invoke_method(&inc, Args::EMPTY).unwrap();
+                        context.invoke(&rec, &[1u64.into()]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Each iteration increments `CNT` three times
+        assert!(matches!(context.namespace.get_by_path(&count), Ok(AmlValue::Integer(600))));
+    }
+}
@@ -17,9 +17,18 @@ use crate::{
         Parser,
         Propagate,
     },
-    pkg_length::{pkg_length, PkgLength},
+    pkg_length::{pkg_length, raw_pkg_length, PkgLength},
     statement::statement_opcode,
-    value::{AmlValue, FieldFlags, MethodCode, MethodFlags, RegionSpace},
+    value::{
+        AmlCode,
+        AmlValue,
+        FieldAccessAttrib,
+        FieldConnection,
+        FieldFlags,
+        MethodCode,
+        MethodFlags,
+        RegionSpace,
+    },
     AmlContext,
     AmlError,
     AmlHandle,
@@ -40,6 +49,14 @@ where
     // TODO: why does this use still_parsing, instead of just taking the whole thing and parsing it til it's empty?
     move |mut input: &'a [u8], mut context: &'c mut AmlContext| {
         while list_length.still_parsing(input) {
+            #[cfg(feature = "debug")]
+            if let Err(err) = context.debug_opcode(input) {
+                return Err((input, context, Propagate::Err(err)));
+            }
+            if let Err(err) = context.consume_op() {
+                return Err((input, context, Propagate::Err(err)));
+            }
+
             // TODO: currently, we ignore the value of the expression. We may need to propagate
             // this.
             let (new_input, new_context, _) = term_object().parse(input, context)?;
@@ -87,9 +104,9 @@ where
     /*
      * NamedObj := DefBankField | DefCreateBitField | DefCreateByteField | DefCreateWordField | DefCreateDWordField |
      *             DefCreateQWordField | DefCreateField | DefDataRegion | DefExternal | DefOpRegion | DefPowerRes |
-     *             DefProcessor | DefThermalZone | DefMethod | DefMutex
+     *             DefProcessor | DefThermalZone | DefMethod | DefMutex | DefEvent
      *
-     * XXX: DefMethod and DefMutex (at least) are not included in any rule in the AML grammar,
+     * XXX: DefMethod, DefMutex, and DefEvent (at least) are not included in any rule in the AML grammar,
      * but are defined in the NamedObj section so we assume they're part of NamedObj
      */
     comment_scope(
@@ -104,13 +121,16 @@ where
             def_create_field(),
             def_op_region(),
             def_field(),
+            def_index_field(),
+            def_bank_field(),
             def_method(),
             def_external(),
             def_device(),
             def_processor(),
             def_power_res(),
             def_thermal_zone(),
-            def_mutex()
+            def_mutex(),
+            def_event()
         ),
     )
 }
@@ -492,50 +512,132 @@ where
      * DefField = ExtOpPrefix 0x81 PkgLength NameString FieldFlags FieldList
      * FieldFlags := ByteData
      */
-    let opregion_as_handle = name_string().map_with_context(|region_name, context| {
-        /*
-         * We search for the opregion that this field is referencing here as we already have the correct starting
-         * scope. If we leave this to later, it becomes much harder as we also need to know the field's scope.
-         */
-        let (_, handle) =
-            try_with_context!(context, context.namespace.search(&region_name, &context.current_scope));
-        (Ok(handle), context)
-    });
-
     ext_opcode(opcode::EXT_DEF_FIELD_OP)
         .then(comment_scope(
             DebugVerbosity::Scopes,
             "DefField",
-            pkg_length().then(opregion_as_handle).then(take()).feed(|((list_length, region_handle), flags)| {
-                move |mut input: &'a [u8], mut context: &'c mut AmlContext| -> ParseResult<'a, 'c, ()> {
-                    /*
-                     * FieldList := Nothing | <FieldElement FieldList>
-                     */
-                    // TODO: can this pattern be expressed as a combinator
-                    let mut current_offset = 0;
-                    while list_length.still_parsing(input) {
-                        let (new_input, new_context, field_length) =
-                            field_element(region_handle, FieldFlags::new(flags), current_offset)
-                                .parse(input, context)?;
-                        input = new_input;
-                        context = new_context;
-                        current_offset += field_length;
-                    }
-
-                    Ok((input, context, ()))
-                }
+            pkg_length().then(name_as_handle()).then(take()).feed(|((list_length, region_handle), flags)| {
+                field_list(list_length, FieldListKind::Region(region_handle), flags)
             }),
         ))
         .discard_result()
 }
 
-/// Parses a `FieldElement`. Takes the current offset within the field list, and returns the length
-/// of the field element parsed.
-pub fn field_element<'a, 'c>(
-    region_handle: AmlHandle,
-    flags: FieldFlags,
-    current_offset: u64,
-) -> impl Parser<'a, 'c, u64>
+pub fn def_index_field<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefIndexField := ExtOpPrefix 0x86 PkgLength NameString NameString FieldFlags FieldList
+     *
+     * The first name is the index register, and the second is the data register.
+     */
+    ext_opcode(opcode::EXT_DEF_INDEX_FIELD_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefIndexField",
+            pkg_length().then(name_as_handle()).then(name_as_handle()).then(take()).feed(
+                |(((list_length, index), data), flags)| {
+                    field_list(list_length, FieldListKind::Index { index, data }, flags)
+                },
+            ),
+        ))
+        .discard_result()
+}
+
+pub fn def_bank_field<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefBankField := ExtOpPrefix 0x87 PkgLength NameString NameString BankValue FieldFlags FieldList
+     * BankValue := TermArg => Integer
+     *
+     * The first name is the operation region, and the second is the bank register.
+     */
+    let bank_value = term_arg().map_with_context(|bank_value, context| {
+        let bank_value = try_with_context!(context, bank_value.as_integer(context));
+        (Ok(bank_value), context)
+    });
+
+    ext_opcode(opcode::EXT_DEF_BANK_FIELD_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefBankField",
+            pkg_length().then(name_as_handle()).then(name_as_handle()).then(bank_value).then(take()).feed(
+                |((((list_length, region), bank), bank_value), flags)| {
+                    field_list(list_length, FieldListKind::Bank { region, bank, bank_value }, flags)
+                },
+            ),
+        ))
+        .discard_result()
+}
+
+/// Parses a `NameString` that refers to an existing object (e.g. the operation region of a field list), and
+/// returns the object's handle.
+fn name_as_handle<'a, 'c>() -> impl Parser<'a, 'c, AmlHandle>
+where
+    'c: 'a,
+{
+    name_string().map_with_context(|name, context| {
+        /*
+         * We search for the object here as we already have the correct starting scope. If we leave this to later,
+         * it becomes much harder as we also need to know the field's scope.
+         */
+        let (_, handle) = try_with_context!(context, context.namespace.search(&name, &context.current_scope));
+        (Ok(handle), context)
+    })
+}
+
+/// Parses a `FieldList`, which takes up the rest of `list_length`, creating fields of the type given by `kind`.
+fn field_list<'a, 'c>(list_length: PkgLength, kind: FieldListKind, flags: u8) -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    move |mut input: &'a [u8], mut context: &'c mut AmlContext| -> ParseResult<'a, 'c, ()> {
+        /*
+         * FieldList := Nothing | <FieldElement FieldList>
+         */
+        // TODO: can this pattern be expressed as a combinator
+        let mut state =
+            FieldListState { flags: FieldFlags::new(flags), access_attrib: None, connection: None, offset: 0 };
+        while list_length.still_parsing(input) {
+            let (new_input, new_context, new_state) = field_element(kind, state).parse(input, context)?;
+            input = new_input;
+            context = new_context;
+            state = new_state;
+        }
+
+        Ok((input, context, ()))
+    }
+}
+
+/// The object the fields of a field list are declared in, which decides the type of the fields it creates.
+#[derive(Clone, Copy, Debug)]
+pub enum FieldListKind {
+    /// The fields of a `Field`, which are in an operation region.
+    Region(AmlHandle),
+    /// The fields of an `IndexField`, which are accessed through an index register and a data register.
+    Index { index: AmlHandle, data: AmlHandle },
+    /// The fields of a `BankField`, which are in the bank of an operation region selected by writing
+    /// `bank_value` to the bank register.
+    Bank { region: AmlHandle, bank: AmlHandle, bank_value: u64 },
+}
+
+/// The state of a field list, which is changed as each of its elements is parsed.
+#[derive(Clone, Debug)]
+pub struct FieldListState {
+    /// The flags of the field list, the access type of which can be changed by an `AccessAs`.
+    pub flags: FieldFlags,
+    pub access_attrib: Option<FieldAccessAttrib>,
+    /// The resource set by the last `Connection` in the field list.
+    pub connection: Option<FieldConnection>,
+    /// The offset, in bits, of the next field.
+    pub offset: u64,
+}
+
+/// Parses a `FieldElement`. Takes the current state of the field list, and returns the state after the element.
+pub fn field_element<'a, 'c>(kind: FieldListKind, state: FieldListState) -> impl Parser<'a, 'c, FieldListState>
 where
     'c: 'a,
 {
@@ -555,40 +657,93 @@ where
      * object (it seems to be defined in ASL). We treat BufferData as if it was encoded like
      * DefBuffer, and this seems to work so far.
      */
-    // TODO: parse ConnectField and ExtendedAccessField
 
     /*
      * Reserved fields shouldn't actually be added to the namespace; they seem to show gaps in
      * the operation region that aren't used for anything.
      */
-    let reserved_field =
-        opcode(opcode::RESERVED_FIELD).then(pkg_length()).map(|((), length)| Ok(length.raw_length as u64));
+    let reserved_field = {
+        let state = state.clone();
+        opcode(opcode::RESERVED_FIELD).then(raw_pkg_length()).map(move |((), length)| {
+            let mut state = state.clone();
+            state.offset += length as u64;
+            Ok(state)
+        })
+    };
+
+    let access_field = {
+        let state = state.clone();
+        opcode(opcode::ACCESS_FIELD).then(take()).then(take()).map(move |(((), access_type), attrib)| {
+            let mut state = state.clone();
+            state.flags = state.flags.with_access_type(access_type);
+            state.access_attrib = FieldAccessAttrib::from_access_field(access_type, attrib)?;
+            Ok(state)
+        })
+    };
 
-    // TODO: work out what to do with an access field
-    // let access_field = opcode(opcode::ACCESS_FIELD)
-    //     .then(take())
-    //     .then(take())
-    //     .map_with_context(|(((), access_type), access_attrib), context| (Ok(    , context));
+    let extended_access_field = {
+        let state = state.clone();
+        opcode(opcode::EXTENDED_ACCESS_FIELD).then(take()).then(take()).then(take()).map(
+            move |((((), access_type), attrib), length)| {
+                let mut state = state.clone();
+                state.flags = state.flags.with_access_type(access_type);
+                state.access_attrib = Some(FieldAccessAttrib::from_extended_access_field(attrib, length)?);
+                Ok(state)
+            },
+        )
+    };
 
-    let named_field = name_seg().then(pkg_length()).map_with_context(move |(name_seg, length), context| {
+    let connect_field = {
+        let state = state.clone();
+        let connection_by_name = name_string().map_with_context(|name, context| {
+            let (_, handle) = try_with_context!(context, context.namespace.search(&name, &context.current_scope));
+            let value = try_with_context!(context, context.namespace.get(handle)).clone();
+            (Ok(value), context)
+        });
+
+        opcode(opcode::CONNECT_FIELD).then(choice!(def_buffer(), connection_by_name)).map_with_context(
+            move |((), resource), context| {
+                let resource = try_with_context!(context, resource.as_buffer(context)).lock().clone();
+                let mut state = state.clone();
+                state.connection = Some(FieldConnection { resource: Arc::new(resource), offset: state.offset });
+                (Ok(state), context)
+            },
+        )
+    };
+
+    let named_field = name_seg().then(raw_pkg_length()).map_with_context(move |(name_seg, length), context| {
+        let (offset, length) = (state.offset, length as u64);
+        let field = match kind {
+            FieldListKind::Region(region) => AmlValue::Field {
+                region,
+                flags: state.flags,
+                offset,
+                length,
+                access_attrib: state.access_attrib,
+                connection: state.connection.clone(),
+            },
+            FieldListKind::Index { index, data } => {
+                AmlValue::IndexField { index, data, flags: state.flags, offset, length }
+            }
+            FieldListKind::Bank { region, bank, bank_value } => {
+                AmlValue::BankField { region, bank, bank_value, flags: state.flags, offset, length }
+            }
+        };
         try_with_context!(
             context,
             context.namespace.add_value_at_resolved_path(
                 AmlName::from_name_seg(name_seg),
                 &context.current_scope,
-                AmlValue::Field {
-                    region: region_handle,
-                    flags,
-                    offset: current_offset,
-                    length: length.raw_length as u64,
-                },
+                field
             )
         );
 
-        (Ok(length.raw_length as u64), context)
+        let mut state = state.clone();
+        state.offset += length;
+        (Ok(state), context)
     });
 
-    choice!(reserved_field, named_field)
+    choice!(reserved_field, access_field, extended_access_field, connect_field, named_field)
 }
 
 pub fn def_method<'a, 'c>() -> impl Parser<'a, 'c, ()>
@@ -612,15 +767,21 @@ where
                     take_to_end_of_pkglength(length).map(move |code| Ok((name.clone(), flags, code)))
                 })
                 .map_with_context(|(name, flags, code), context| {
+                    /*
+                     * The body isn't parsed until the method is invoked, so we just record where it is in the
+                     * stream. If the stream being parsed isn't known, the body is copied instead.
+                     */
+                    let code = match context.current_stream {
+                        Some(ref stream) => AmlCode::in_stream(stream, code),
+                        None => None,
+                    }
+                    .unwrap_or_else(|| AmlCode::new(code));
                     try_with_context!(
                         context,
                         context.namespace.add_value_at_resolved_path(
                             name,
                             &context.current_scope,
-                            AmlValue::Method {
-                                flags: MethodFlags::from(flags),
-                                code: MethodCode::Aml(code.to_vec())
-                            },
+                            AmlValue::Method { flags: MethodFlags::from(flags), code: MethodCode::Aml(code) },
                         )
                     );
                     (Ok(()), context)
@@ -842,7 +1003,7 @@ where
                     context.namespace.add_value_at_resolved_path(
                         name,
                         &context.current_scope,
-                        AmlValue::Mutex { sync_level }
+                        AmlValue::Mutex { sync_level: sync_level & 0x0f }
                     )
                 );
                 (Ok(()), context)
@@ -851,6 +1012,28 @@ where
         .discard_result()
 }
 
+pub fn def_event<'a, 'c>() -> impl Parser<'a, 'c, ()>
+where
+    'c: 'a,
+{
+    /*
+     * DefEvent := ExtOpPrefix 0x02 NameString
+     */
+    ext_opcode(opcode::EXT_DEF_EVENT_OP)
+        .then(comment_scope(
+            DebugVerbosity::Scopes,
+            "DefEvent",
+            name_string().map_with_context(|name, context| {
+                try_with_context!(
+                    context,
+                    context.namespace.add_value_at_resolved_path(name, &context.current_scope, AmlValue::Event)
+                );
+                (Ok(()), context)
+            }),
+        ))
+        .discard_result()
+}
+
 pub fn def_cond_ref_of<'a, 'c>() -> impl Parser<'a, 'c, AmlValue>
 where
     'c: 'a,
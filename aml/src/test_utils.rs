@@ -1,7 +1,13 @@
 use crate::{parser::Propagate, AmlContext, AmlValue, Handler};
 use alloc::boxed::Box;
+use core::sync::atomic::{AtomicU64, Ordering};
 
-struct TestHandler;
+/// Handles the accesses made by tests that don't install their own handlers. Its clock only moves when AML stalls
+/// or sleeps, so tests of timing are deterministic.
+#[derive(Default)]
+struct TestHandler {
+    nanos: AtomicU64,
+}
 
 impl Handler for TestHandler {
     fn read_u8(&self, _address: usize) -> u8 {
@@ -17,16 +23,16 @@ impl Handler for TestHandler {
         unimplemented!()
     }
 
-    fn write_u8(&mut self, _address: usize, _value: u8) {
+    fn write_u8(&self, _address: usize, _value: u8) {
         unimplemented!()
     }
-    fn write_u16(&mut self, _address: usize, _value: u16) {
+    fn write_u16(&self, _address: usize, _value: u16) {
         unimplemented!()
     }
-    fn write_u32(&mut self, _address: usize, _value: u32) {
+    fn write_u32(&self, _address: usize, _value: u32) {
         unimplemented!()
     }
-    fn write_u64(&mut self, _address: usize, _value: u64) {
+    fn write_u64(&self, _address: usize, _value: u64) {
         unimplemented!()
     }
 
@@ -68,10 +74,20 @@ impl Handler for TestHandler {
     fn write_pci_u32(&self, _segment: u16, _bus: u8, device: u8, _function: u8, _offset: u16, _value: u32) {
         unimplemented!()
     }
+
+    fn nanos_since_boot(&self) -> u64 {
+        self.nanos.load(Ordering::Relaxed)
+    }
+    fn stall(&self, microseconds: u64) {
+        self.nanos.fetch_add(microseconds * 1000, Ordering::Relaxed);
+    }
+    fn sleep(&self, milliseconds: u64) {
+        self.nanos.fetch_add(milliseconds * 1_000_000, Ordering::Relaxed);
+    }
 }
 
 pub(crate) fn make_test_context() -> AmlContext {
-    AmlContext::new(Box::new(TestHandler), crate::DebugVerbosity::None)
+    AmlContext::new(Box::new(TestHandler::default()), crate::DebugVerbosity::None)
 }
 
 pub(crate) macro check_err($parse: expr, $error: pat, $remains: expr) {
@@ -142,9 +158,51 @@ pub(crate) fn crudely_cmp_values(a: &AmlValue, b: &AmlValue) -> bool {
             }
             _ => false,
         },
-        AmlValue::Field { region, flags, offset, length } => match b {
-            AmlValue::Field { region: b_region, flags: b_flags, offset: b_offset, length: b_length } => {
-                region == b_region && flags == b_flags && offset == b_offset && length == b_length
+        AmlValue::Field { region, flags, offset, length, access_attrib, connection } => match b {
+            AmlValue::Field {
+                region: b_region,
+                flags: b_flags,
+                offset: b_offset,
+                length: b_length,
+                access_attrib: b_access_attrib,
+                connection: b_connection,
+            } => {
+                region == b_region
+                    && flags == b_flags
+                    && offset == b_offset
+                    && length == b_length
+                    && access_attrib == b_access_attrib
+                    && connection == b_connection
+            }
+            _ => false,
+        },
+        AmlValue::IndexField { index, data, flags, offset, length } => match b {
+            AmlValue::IndexField {
+                index: b_index,
+                data: b_data,
+                flags: b_flags,
+                offset: b_offset,
+                length: b_length,
+            } => {
+                index == b_index && data == b_data && flags == b_flags && offset == b_offset && length == b_length
+            }
+            _ => false,
+        },
+        AmlValue::BankField { region, bank, bank_value, flags, offset, length } => match b {
+            AmlValue::BankField {
+                region: b_region,
+                bank: b_bank,
+                bank_value: b_bank_value,
+                flags: b_flags,
+                offset: b_offset,
+                length: b_length,
+            } => {
+                region == b_region
+                    && bank == b_bank
+                    && bank_value == b_bank_value
+                    && flags == b_flags
+                    && offset == b_offset
+                    && length == b_length
             }
             _ => false,
         },
@@ -159,7 +217,7 @@ pub(crate) fn crudely_cmp_values(a: &AmlValue, b: &AmlValue) -> bool {
                 }
 
                 match (code, b_code) {
-                    (MethodCode::Aml(a), MethodCode::Aml(b)) => a == b,
+                    (MethodCode::Aml(a), MethodCode::Aml(b)) => a.as_bytes() == b.as_bytes(),
                     (MethodCode::Aml(_), MethodCode::Native(_)) => false,
                     (MethodCode::Native(_), MethodCode::Aml(_)) => false,
                     (MethodCode::Native(_), MethodCode::Native(_)) => panic!("Can't compare two native methods"),
@@ -189,9 +247,10 @@ pub(crate) fn crudely_cmp_values(a: &AmlValue, b: &AmlValue) -> bool {
             AmlValue::Mutex { sync_level: b_sync_level } => sync_level == b_sync_level,
             _ => false,
         },
+        AmlValue::Event => matches!(b, AmlValue::Event),
         AmlValue::Package(a) => match b {
             AmlValue::Package(b) => {
-                for (a, b) in a.iter().zip(b) {
+                for (a, b) in a.iter().zip(b.iter()) {
                     if crudely_cmp_values(a, b) == false {
                         return false;
                     }
@@ -211,5 +270,9 @@ pub(crate) fn crudely_cmp_values(a: &AmlValue, b: &AmlValue) -> bool {
             AmlValue::ThermalZone => true,
             _ => false,
         },
+        AmlValue::DdbHandle(a) => match b {
+            AmlValue::DdbHandle(b) => a == b,
+            _ => false,
+        },
     }
 }
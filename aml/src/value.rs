@@ -1,14 +1,17 @@
+pub mod convert;
+
 use crate::{misc::ArgNum, AmlContext, AmlError, AmlHandle, AmlName};
 use alloc::{
     string::{String, ToString},
     sync::Arc,
+    vec,
     vec::Vec,
 };
 use bit_field::BitField;
-use core::{cmp, fmt, fmt::Debug};
+use core::{cmp, fmt, fmt::Debug, ops::Range};
 use spinning_top::Spinlock;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum RegionSpace {
     SystemMemory,
     SystemIo,
@@ -23,6 +26,25 @@ pub enum RegionSpace {
     OemDefined(u8),
 }
 
+impl RegionSpace {
+    /// The ID of the address space, as it's encoded in an `OperationRegion` (and passed to `_REG` methods).
+    pub fn id(&self) -> u8 {
+        match self {
+            RegionSpace::SystemMemory => 0x00,
+            RegionSpace::SystemIo => 0x01,
+            RegionSpace::PciConfig => 0x02,
+            RegionSpace::EmbeddedControl => 0x03,
+            RegionSpace::SMBus => 0x04,
+            RegionSpace::SystemCmos => 0x05,
+            RegionSpace::PciBarTarget => 0x06,
+            RegionSpace::IPMI => 0x07,
+            RegionSpace::GeneralPurposeIo => 0x08,
+            RegionSpace::GenericSerialBus => 0x09,
+            RegionSpace::OemDefined(id) => *id,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FieldAccessType {
     Any,
@@ -61,6 +83,14 @@ impl FieldFlags {
         }
     }
 
+    /// Replace the access type of the flags with the one encoded in the low bits of `access_type` (e.g. from an
+    /// `AccessAs` in a field list).
+    pub(crate) fn with_access_type(self, access_type: u8) -> FieldFlags {
+        let mut flags = self.0;
+        flags.set_bits(0..4, access_type.get_bits(0..4));
+        FieldFlags(flags)
+    }
+
     pub fn lock_rule(&self) -> bool {
         self.0.get_bit(4)
     }
@@ -75,6 +105,69 @@ impl FieldFlags {
     }
 }
 
+/// The protocol used to access a field of a `GenericSerialBus` region, set by an `AccessAs` in its field list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FieldAccessAttrib {
+    Quick,
+    SendReceive,
+    Byte,
+    Word,
+    Block,
+    ProcessCall,
+    BlockProcessCall,
+    /// Transfer the given number of bytes.
+    Bytes(u8),
+    /// Transfer the given number of bytes, without a command.
+    RawBytes(u8),
+    /// Write and then read the given number of bytes, without a command.
+    RawProcessBytes(u8),
+}
+
+impl FieldAccessAttrib {
+    /// Decode the attribute of an `AccessField`. The top two bits of the access type select how `attrib` is
+    /// interpreted. Returns `None` if the field has no attribute.
+    pub(crate) fn from_access_field(access_type: u8, attrib: u8) -> Result<Option<FieldAccessAttrib>, AmlError> {
+        match access_type.get_bits(6..8) {
+            0 => match attrib {
+                0x00 => Ok(None),
+                0x02 => Ok(Some(FieldAccessAttrib::Quick)),
+                0x04 => Ok(Some(FieldAccessAttrib::SendReceive)),
+                0x06 => Ok(Some(FieldAccessAttrib::Byte)),
+                0x08 => Ok(Some(FieldAccessAttrib::Word)),
+                0x0a => Ok(Some(FieldAccessAttrib::Block)),
+                0x0c => Ok(Some(FieldAccessAttrib::ProcessCall)),
+                0x0d => Ok(Some(FieldAccessAttrib::BlockProcessCall)),
+                _ => Err(AmlError::InvalidFieldFlags),
+            },
+            1 => Ok(Some(FieldAccessAttrib::Bytes(attrib))),
+            2 => Ok(Some(FieldAccessAttrib::RawBytes(attrib))),
+            _ => Ok(Some(FieldAccessAttrib::RawProcessBytes(attrib))),
+        }
+    }
+
+    /// Decode the attribute and length of an `ExtendedAccessField`.
+    pub(crate) fn from_extended_access_field(attrib: u8, length: u8) -> Result<FieldAccessAttrib, AmlError> {
+        match attrib {
+            0x0b => Ok(FieldAccessAttrib::Bytes(length)),
+            0x0e => Ok(FieldAccessAttrib::RawBytes(length)),
+            0x0f => Ok(FieldAccessAttrib::RawProcessBytes(length)),
+            _ => Err(AmlError::InvalidFieldFlags),
+        }
+    }
+}
+
+/// The resource a field is connected to by a `Connection` in its field list. This is needed to access fields of
+/// `GeneralPurposeIo` and `GenericSerialBus` regions.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FieldConnection {
+    /// The resource template that describes the connection (a `GpioIo` or `GpioInt` descriptor for GPIO, or a
+    /// serial bus connection descriptor for `GenericSerialBus`).
+    pub resource: Arc<Vec<u8>>,
+    /// The bit offset within the field list at which the connection was made. For GPIO, the offset of a field from
+    /// this is the index of its first pin in the connection's pin list.
+    pub offset: u64,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct MethodFlags(u8);
 
@@ -157,21 +250,75 @@ pub enum AmlType {
     ThermalZone,
 }
 
+/// A stream of AML that's being parsed, which the code of the control methods it defines refers into.
+#[derive(Clone)]
+pub(crate) enum AmlStream {
+    /// A stream that will live as long as the context (e.g. the DSDT, mapped into the kernel), which doesn't need
+    /// to be copied.
+    Static(&'static [u8]),
+    Shared(Arc<[u8]>),
+}
+
+impl AmlStream {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        match self {
+            AmlStream::Static(stream) => stream,
+            AmlStream::Shared(stream) => stream,
+        }
+    }
+}
+
+/// The AML code of a control method. Method bodies aren't copied out of the table they're defined in when it's
+/// loaded - instead, this records where the body is in the table's stream, and the body is parsed when the method
+/// is invoked.
+#[derive(Clone)]
+pub struct AmlCode {
+    stream: AmlStream,
+    range: Range<usize>,
+}
+
+impl AmlCode {
+    /// Make a method body from a copy of `code`.
+    pub fn new(code: &[u8]) -> AmlCode {
+        AmlCode { stream: AmlStream::Shared(code.into()), range: 0..code.len() }
+    }
+
+    /// Make a method body that refers to `code` within `stream`. Returns `None` if `code` isn't part of `stream`.
+    pub(crate) fn in_stream(stream: &AmlStream, code: &[u8]) -> Option<AmlCode> {
+        let start = (code.as_ptr() as usize).checked_sub(stream.as_bytes().as_ptr() as usize)?;
+        let range = start..(start + code.len());
+        (range.end <= stream.as_bytes().len()).then(|| AmlCode { stream: stream.clone(), range })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.stream.as_bytes()[self.range.clone()]
+    }
+
+    pub(crate) fn stream(&self) -> &AmlStream {
+        &self.stream
+    }
+}
+
 #[derive(Clone)]
 pub enum MethodCode {
-    Aml(Vec<u8>),
+    Aml(AmlCode),
     Native(Arc<dyn Fn(&mut AmlContext) -> Result<AmlValue, AmlError> + Send + Sync>),
 }
 
 impl fmt::Debug for MethodCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            MethodCode::Aml(ref code) => write!(f, "AML({:x?})", code),
+            MethodCode::Aml(ref code) => write!(f, "AML({:x?})", code.as_bytes()),
             MethodCode::Native(_) => write!(f, "(native method)"),
         }
     }
 }
 
+/// Refers to a definition block that was loaded with `Load` or `LoadTable` (or by
+/// [`AmlContext::load_table`](crate::AmlContext::load_table)), and which can be unloaded with `Unload`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct DdbHandle(pub(crate) u64);
+
 #[derive(Clone, Debug)]
 pub enum AmlValue {
     Boolean(bool),
@@ -192,6 +339,28 @@ pub enum AmlValue {
         flags: FieldFlags,
         offset: u64,
         length: u64,
+        access_attrib: Option<FieldAccessAttrib>,
+        connection: Option<FieldConnection>,
+    },
+    /// Describes a field unit that's accessed through a pair of index and data registers (which are fields
+    /// themselves). Each unit of the field is accessed by writing its offset (in bytes) to the index register, and
+    /// then accessing the data register.
+    IndexField {
+        index: AmlHandle,
+        data: AmlHandle,
+        flags: FieldFlags,
+        offset: u64,
+        length: u64,
+    },
+    /// Describes a field unit within a banked operation region. Before the field is accessed, `bank_value` is
+    /// written to the bank register `bank` (which is a field itself), to select the bank the field is in.
+    BankField {
+        region: AmlHandle,
+        bank: AmlHandle,
+        bank_value: u64,
+        flags: FieldFlags,
+        offset: u64,
+        length: u64,
     },
     Device,
     Method {
@@ -214,13 +383,16 @@ pub enum AmlValue {
     Mutex {
         sync_level: u8,
     },
-    // TODO: I think this will need to be `Arc`ed as well, as `Index` can be used on both Buffers and Packages
-    Package(Vec<AmlValue>),
+    Event,
+    /// The elements of a package are shared between copies of the package, so reading one is cheap. Modifying the
+    /// elements of a package that's shared with another object copies them first (see [`AmlValue::package_mut`]).
+    Package(Arc<Vec<AmlValue>>),
     PowerResource {
         system_level: u8,
         resource_order: u16,
     },
     ThermalZone,
+    DdbHandle(DdbHandle),
 }
 
 impl AmlValue {
@@ -236,6 +408,14 @@ impl AmlValue {
         AmlValue::Integer(u64::max_value())
     }
 
+    pub fn buffer(bytes: Vec<u8>) -> AmlValue {
+        AmlValue::Buffer(Arc::new(Spinlock::new(bytes)))
+    }
+
+    pub fn package(elements: Vec<AmlValue>) -> AmlValue {
+        AmlValue::Package(Arc::new(elements))
+    }
+
     pub fn native_method<F>(arg_count: u8, serialize: bool, sync_level: u8, f: F) -> AmlValue
     where
         F: (Fn(&mut AmlContext) -> Result<AmlValue, AmlError>) + 'static + Send + Sync,
@@ -250,16 +430,20 @@ impl AmlValue {
             AmlValue::Integer(_) => AmlType::Integer,
             AmlValue::String(_) => AmlType::String,
             AmlValue::OpRegion { .. } => AmlType::OpRegion,
-            AmlValue::Field { .. } => AmlType::FieldUnit,
+            AmlValue::Field { .. } | AmlValue::IndexField { .. } | AmlValue::BankField { .. } => {
+                AmlType::FieldUnit
+            }
             AmlValue::Device => AmlType::Device,
             AmlValue::Method { .. } => AmlType::Method,
             AmlValue::Buffer(_) => AmlType::Buffer,
             AmlValue::BufferField { .. } => AmlType::BufferField,
             AmlValue::Processor { .. } => AmlType::Processor,
             AmlValue::Mutex { .. } => AmlType::Mutex,
+            AmlValue::Event => AmlType::Event,
             AmlValue::Package(_) => AmlType::Package,
             AmlValue::PowerResource { .. } => AmlType::PowerResource,
             AmlValue::ThermalZone => AmlType::ThermalZone,
+            AmlValue::DdbHandle(_) => AmlType::DdbHandle,
         }
     }
 
@@ -274,40 +458,75 @@ impl AmlValue {
     pub fn as_integer(&self, context: &AmlContext) -> Result<u64, AmlError> {
         match self {
             AmlValue::Integer(value) => Ok(*value),
-            AmlValue::Boolean(value) => Ok(if *value { u64::max_value() } else { 0 }),
-            AmlValue::Buffer(ref bytes) => {
-                /*
-                 * "The first 8 bytes of the buffer are converted to an integer, taking the first
-                 * byte as the least significant byte of the integer. A zero-length buffer is
-                 * illegal." - §19.6.140
-                 *
-                 * XXX: Buffers with length `0` appear in real tables, so we return `0` for them.
-                 */
-                let bytes = bytes.lock();
-                let bytes = if bytes.len() > 8 { &bytes[0..8] } else { &bytes[..] };
-
-                Ok(bytes.iter().rev().fold(0u64, |mut i, &popped| {
-                    i <<= 8;
-                    i += popped as u64;
-                    i
-                }))
-            }
+            AmlValue::Boolean(value) => Ok(if *value { u64::MAX } else { 0 }),
+            AmlValue::DdbHandle(DdbHandle(handle)) => Ok(*handle),
+            AmlValue::Buffer(ref bytes) => Ok(convert::buffer_to_integer(&bytes.lock())),
+            AmlValue::String(ref string) => Ok(convert::string_to_integer(string)),
             /*
              * Read from a field or buffer field. These can return either a `Buffer` or an `Integer`, so we make sure to call
              * `as_integer` on the result.
              */
-            AmlValue::Field { .. } => self.read_field(context)?.as_integer(context),
+            AmlValue::Field { .. } | AmlValue::IndexField { .. } | AmlValue::BankField { .. } => {
+                self.read_field(context)?.as_integer(context)
+            }
             AmlValue::BufferField { .. } => self.read_buffer_field(context)?.as_integer(context),
 
             _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::Integer }),
         }
     }
 
+    /// Get the value as an integer, without the `AmlContext` needed to read fields. This is intended for the
+    /// values returned from methods, which are never fields. Buffers are converted to integers as by `as_integer`.
+    pub fn as_u64(&self) -> Result<u64, AmlError> {
+        match self {
+            AmlValue::Boolean(value) => Ok(if *value { u64::max_value() } else { 0 }),
+            AmlValue::Integer(value) => Ok(*value),
+            AmlValue::Buffer(bytes) => Ok(convert::buffer_to_integer(&bytes.lock())),
+            AmlValue::String(string) => Ok(convert::string_to_integer(string)),
+            _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::Integer }),
+        }
+    }
+
+    pub fn as_package(&self) -> Result<&[AmlValue], AmlError> {
+        match self {
+            AmlValue::Package(elements) => Ok(elements),
+            _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::Package }),
+        }
+    }
+
+    /// Get mutable access to the elements of a package. If the elements are shared with another object, they're
+    /// copied first, so the other object isn't affected.
+    pub fn package_mut(&mut self) -> Result<&mut Vec<AmlValue>, AmlError> {
+        match self {
+            AmlValue::Package(elements) => Ok(Arc::make_mut(elements)),
+            _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::Package }),
+        }
+    }
+
+    /// Make a copy of the value to store into a new object, as by `CopyObject` or a store to a `Local` or `Arg`.
+    /// Packages keep sharing their elements until one of the copies is modified, but the contents of buffers must
+    /// be copied, because they can be modified in place through `BufferField`s. A buffer that isn't referred to
+    /// from anywhere else (e.g. the result of a `Concatenate`) is moved into the new object instead.
+    pub fn copy_object(self) -> AmlValue {
+        match self {
+            AmlValue::Buffer(bytes) => match Arc::try_unwrap(bytes) {
+                Ok(bytes) => AmlValue::Buffer(Arc::new(bytes)),
+                Err(bytes) => AmlValue::buffer(bytes.lock().clone()),
+            },
+            value => value,
+        }
+    }
+
     pub fn as_buffer(&self, context: &AmlContext) -> Result<Arc<Spinlock<Vec<u8>>>, AmlError> {
         match self {
             AmlValue::Buffer(ref bytes) => Ok(bytes.clone()),
-            // TODO: implement conversion of String and Integer to Buffer
-            AmlValue::Field { .. } => self.read_field(context)?.as_buffer(context),
+            AmlValue::Integer(_) | AmlValue::Boolean(_) => {
+                Ok(Arc::new(Spinlock::new(convert::integer_to_buffer(self.as_integer(context)?))))
+            }
+            AmlValue::String(ref string) => Ok(Arc::new(Spinlock::new(convert::string_to_buffer(string)))),
+            AmlValue::Field { .. } | AmlValue::IndexField { .. } | AmlValue::BankField { .. } => {
+                self.read_field(context)?.as_buffer(context)
+            }
             AmlValue::BufferField { .. } => self.read_buffer_field(context)?.as_buffer(context),
             _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::Buffer }),
         }
@@ -316,8 +535,14 @@ impl AmlValue {
     pub fn as_string(&self, context: &AmlContext) -> Result<String, AmlError> {
         match self {
             AmlValue::String(ref string) => Ok(string.clone()),
-            // TODO: implement conversion of Buffer to String
-            AmlValue::Field { .. } => self.read_field(context)?.as_string(context),
+            AmlValue::Integer(_) | AmlValue::Boolean(_) => {
+                Ok(convert::integer_to_string(self.as_integer(context)?))
+            }
+            AmlValue::Buffer(ref bytes) => convert::buffer_to_string(&bytes.lock()),
+            AmlValue::Field { .. } | AmlValue::IndexField { .. } | AmlValue::BankField { .. } => {
+                self.read_field(context)?.as_string(context)
+            }
+            AmlValue::BufferField { .. } => self.read_buffer_field(context)?.as_string(context),
             _ => Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::String }),
         }
     }
@@ -397,6 +622,7 @@ impl AmlValue {
         match desired_type {
             AmlType::Integer => self.as_integer(context).map(|value| AmlValue::Integer(value)),
             AmlType::Buffer => self.as_buffer(context).map(|value| AmlValue::Buffer(value)),
+            AmlType::String => self.as_string(context).map(AmlValue::String),
             AmlType::FieldUnit => panic!(
                 "Can't implicitly convert to FieldUnit. This must be special-cased by the caller for now :("
             ),
@@ -407,98 +633,159 @@ impl AmlValue {
     /// Reads from a field of an opregion, returning either a `AmlValue::Integer` or an `AmlValue::Buffer`,
     /// depending on the size of the field.
     pub fn read_field(&self, context: &AmlContext) -> Result<AmlValue, AmlError> {
-        if let AmlValue::Field { region, flags, offset, length } = self {
-            let maximum_access_size = {
-                if let AmlValue::OpRegion { region, .. } = context.namespace.get(*region)? {
-                    match region {
-                        RegionSpace::SystemMemory => 64,
-                        RegionSpace::SystemIo | RegionSpace::PciConfig => 32,
-                        _ => unimplemented!(),
+        match self {
+            AmlValue::Field { region, flags, offset, length, access_attrib, connection } => {
+                /*
+                 * Fields of GPIO, serial bus, and IPMI regions aren't accessed like memory, so they're handled
+                 * separately. Reading an IPMI field sends a request with no data.
+                 */
+                match self.field_region_space(context)? {
+                    RegionSpace::GeneralPurposeIo => {
+                        return context
+                            .read_gpio_field(connection.as_ref(), *offset, *length)
+                            .map(AmlValue::Integer);
+                    }
+                    RegionSpace::GenericSerialBus => {
+                        return context.read_serial_bus_field(connection.as_ref(), *access_attrib, *offset);
                     }
-                } else {
-                    return Err(AmlError::FieldRegionIsNotOpRegion);
+                    RegionSpace::IPMI => return context.ipmi_transaction(*offset, &[]),
+                    _ => (),
                 }
-            };
-            let minimum_access_size = match flags.access_type()? {
-                FieldAccessType::Any => 8,
-                FieldAccessType::Byte => 8,
-                FieldAccessType::Word => 16,
-                FieldAccessType::DWord => 32,
-                FieldAccessType::QWord => 64,
-                FieldAccessType::Buffer => 8, // TODO
-            };
-
-            /*
-             * Find the access size, as either the minimum access size allowed by the region, or the field length
-             * rounded up to the next power-of-2, whichever is larger.
-             */
-            let access_size = u64::max(minimum_access_size, length.next_power_of_two());
 
+                context.with_global_lock(flags.lock_rule(), || {
+                    read_region_field(context, *region, *flags, *offset, *length)
+                })
+            }
             /*
-             * TODO: we need to decide properly how to read from the region itself. Complications:
-             *    - if the region has a minimum access size greater than the desired length, we need to read the
-             *      minimum and mask it (reading a byte from a WordAcc region)
-             *    - if the desired length is larger than we can read, we need to do multiple reads
+             * Each unit of an index field is read by writing its offset (in bytes) to the index register, and
+             * then reading the data register.
              */
-            Ok(AmlValue::Integer(
-                context.read_region(*region, *offset, access_size)?.get_bits(0..(*length as usize)),
-            ))
-        } else {
-            Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::FieldUnit })
+            AmlValue::IndexField { index, data, flags, offset, length } => {
+                context.with_global_lock(flags.lock_rule(), || {
+                    let unit_size = field_unit_size(*flags, *offset, *length)?;
+                    read_field_units(unit_size, *offset, *length, |unit_offset| {
+                        write_register(context, *index, unit_offset / 8)?;
+                        context.namespace.get(*data)?.read_field(context)?.as_integer(context)
+                    })
+                })
+            }
+            AmlValue::BankField { region, bank, bank_value, flags, offset, length } => {
+                context.with_global_lock(flags.lock_rule(), || {
+                    write_register(context, *bank, *bank_value)?;
+                    read_region_field(context, *region, *flags, *offset, *length)
+                })
+            }
+            _ => {
+                Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::FieldUnit })
+            }
         }
     }
 
     pub fn write_field(&mut self, value: AmlValue, context: &mut AmlContext) -> Result<(), AmlError> {
-        /*
-         * If the field's update rule is `Preserve`, we need to read the initial value of the field, so we can
-         * overwrite the correct bits. We destructure the field to do the actual write, so we read from it if
-         * needed here, otherwise the borrow-checker doesn't understand.
-         */
-        let field_update_rule = if let AmlValue::Field { region, flags, offset, length } = self {
-            flags.field_update_rule()?
-        } else {
-            return Err(AmlError::IncompatibleValueConversion {
-                current: self.type_of(),
-                target: AmlType::FieldUnit,
-            });
-        };
-        let mut field_value = match field_update_rule {
-            FieldUpdateRule::Preserve => self.read_field(context)?.as_integer(context)?,
-            FieldUpdateRule::WriteAsOnes => 0xffffffff_ffffffff,
-            FieldUpdateRule::WriteAsZeros => 0x0,
-        };
-
-        if let AmlValue::Field { region, flags, offset, length } = self {
-            let maximum_access_size = {
-                if let AmlValue::OpRegion { region, .. } = context.namespace.get(*region)? {
-                    match region {
-                        RegionSpace::SystemMemory => 64,
-                        RegionSpace::SystemIo | RegionSpace::PciConfig => 32,
-                        _ => unimplemented!(),
-                    }
-                } else {
-                    return Err(AmlError::FieldRegionIsNotOpRegion);
+        if let AmlValue::Field { region, .. } | AmlValue::BankField { region, .. } = self {
+            let region = *region;
+            match self.field_region_space(context)? {
+                RegionSpace::GenericSerialBus | RegionSpace::IPMI => {
+                    return self.store_field(value, context).map(|_| ())
+                }
+                /*
+                 * Resolving the address of a `PciConfig` region can involve running methods, which the rest of
+                 * the write can't do, so we make sure it's resolved first.
+                 */
+                RegionSpace::PciConfig => {
+                    context.resolve_pci_address(region)?;
                 }
-            };
-            let minimum_access_size = match flags.access_type()? {
-                FieldAccessType::Any => 8,
-                FieldAccessType::Byte => 8,
-                FieldAccessType::Word => 16,
-                FieldAccessType::DWord => 32,
-                FieldAccessType::QWord => 64,
-                FieldAccessType::Buffer => 8, // TODO
-            };
+                _ => (),
+            }
+        }
+
+        self.write_field_unit(value, context)
+    }
 
+    /// Write to a field, without running any methods. This means fields can be written as part of accessing
+    /// other fields (e.g. to select the bank of a `BankField`).
+    fn write_field_unit(&self, value: AmlValue, context: &AmlContext) -> Result<(), AmlError> {
+        if let AmlValue::Field { offset, length, connection, .. } = self {
+            if self.field_region_space(context)? == RegionSpace::GeneralPurposeIo {
+                let value = value.as_integer(context)?;
+                return context.write_gpio_field(connection.as_ref(), *offset, *length, value);
+            }
+        }
+
+        let value = value.as_buffer(context)?.lock().clone();
+        match self {
+            AmlValue::Field { region, flags, offset, length, .. } => context
+                .with_global_lock(flags.lock_rule(), || {
+                    write_region_field(context, *region, *flags, *offset, *length, &value)
+                }),
+            AmlValue::IndexField { index, data, flags, offset, length } => {
+                context.with_global_lock(flags.lock_rule(), || {
+                    let unit_size = field_unit_size(*flags, *offset, *length)?;
+                    write_field_units(
+                        unit_size,
+                        *offset,
+                        *length,
+                        flags.field_update_rule()?,
+                        &value,
+                        |unit_offset| {
+                            write_register(context, *index, unit_offset / 8)?;
+                            context.namespace.get(*data)?.read_field(context)?.as_integer(context)
+                        },
+                        |unit_offset, unit| {
+                            write_register(context, *index, unit_offset / 8)?;
+                            write_register(context, *data, unit)
+                        },
+                    )
+                })
+            }
+            AmlValue::BankField { region, bank, bank_value, flags, offset, length } => {
+                context.with_global_lock(flags.lock_rule(), || {
+                    write_register(context, *bank, *bank_value)?;
+                    write_region_field(context, *region, *flags, *offset, *length, &value)
+                })
+            }
+            _ => {
+                Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::FieldUnit })
+            }
+        }
+    }
+
+    /// Write `value` to a field, returning the result of the store (which is what is read back from the field,
+    /// except for serial bus and IPMI fields, where the result of the transfer is returned instead).
+    pub(crate) fn store_field(&mut self, value: AmlValue, context: &mut AmlContext) -> Result<AmlValue, AmlError> {
+        if let AmlValue::Field { offset, access_attrib, connection, .. } = &*self {
             /*
-             * Find the access size, as either the minimum access size allowed by the region, or the field length
-             * rounded up to the next power-of-2, whichever is larger.
+             * Writes to serial bus and IPMI fields are bidirectional - the data returned by the device is the
+             * result of the store, and reading the field back would start another transfer.
              */
-            let access_size = u64::max(minimum_access_size, length.next_power_of_two());
+            match self.field_region_space(context)? {
+                RegionSpace::GenericSerialBus => {
+                    let data = value.as_buffer(context)?.lock().clone();
+                    return context.write_serial_bus_field(connection.as_ref(), *access_attrib, *offset, &data);
+                }
+                RegionSpace::IPMI => {
+                    let data = value.as_buffer(context)?.lock().clone();
+                    return context.ipmi_transaction(*offset, &data);
+                }
+                _ => (),
+            }
+        }
 
-            field_value.set_bits(0..(*length as usize), value.as_integer(context)?);
-            context.write_region(*region, *offset, access_size, field_value)
-        } else {
-            Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::FieldUnit })
+        self.write_field(value, context)?;
+        self.read_field(context)
+    }
+
+    fn field_region_space(&self, context: &AmlContext) -> Result<RegionSpace, AmlError> {
+        match self {
+            AmlValue::Field { region, .. } | AmlValue::BankField { region, .. } => {
+                match context.namespace.get(*region)? {
+                    AmlValue::OpRegion { region, .. } => Ok(region),
+                    _ => Err(AmlError::FieldRegionIsNotOpRegion),
+                }
+            }
+            _ => {
+                Err(AmlError::IncompatibleValueConversion { current: self.type_of(), target: AmlType::FieldUnit })
+            }
         }
     }
 
@@ -617,10 +904,191 @@ impl AmlValue {
     }
 }
 
+/// Find the size of the units (in bits) that a field is accessed in, from its access type. `AnyAcc` fields are
+/// accessed with the smallest access that covers the whole field without crossing an alignment boundary, or with
+/// byte accesses if there isn't one.
+fn field_unit_size(flags: FieldFlags, offset: u64, length: u64) -> Result<u64, AmlError> {
+    Ok(match flags.access_type()? {
+        FieldAccessType::Any => {
+            let last_bit = offset + length.max(1) - 1;
+            [8, 16, 32, 64].iter().copied().find(|size| offset / size == last_bit / size).unwrap_or(8)
+        }
+        FieldAccessType::Byte => 8,
+        FieldAccessType::Word => 16,
+        FieldAccessType::DWord => 32,
+        FieldAccessType::QWord => 64,
+        // Fields of regions that are accessed with buffers (e.g. `SMBus`) are handled separately
+        FieldAccessType::Buffer => 8,
+    })
+}
+
+/// The bits of a field that are in one of the units it's accessed in.
+#[derive(Clone, Debug)]
+struct FieldBits {
+    /// The offset of the unit, in bits.
+    unit_offset: u64,
+    /// The bits within the unit.
+    unit: Range<usize>,
+    /// The same bits within the field.
+    field: Range<usize>,
+}
+
+/// Find the units of `unit_size` bits (aligned to their size) that a field of `length` bits at `offset` is made up
+/// of.
+fn field_units(unit_size: u64, offset: u64, length: u64) -> impl Iterator<Item = FieldBits> {
+    let end = offset + length;
+    let first_unit = offset - offset % unit_size;
+
+    (first_unit..end).step_by(unit_size as usize).map(move |unit_offset| {
+        let start = u64::max(offset, unit_offset);
+        let unit_end = u64::min(end, unit_offset + unit_size);
+        FieldBits {
+            unit_offset,
+            unit: ((start - unit_offset) as usize)..((unit_end - unit_offset) as usize),
+            field: ((start - offset) as usize)..((unit_end - offset) as usize),
+        }
+    })
+}
+
+/// Read a field a unit at a time, with `read_unit` reading the unit at the given offset (in bits). Fields of up to
+/// 64 bits are read as an `Integer`, and larger ones as a `Buffer`.
+fn read_field_units<R>(unit_size: u64, offset: u64, length: u64, mut read_unit: R) -> Result<AmlValue, AmlError>
+where
+    R: FnMut(u64) -> Result<u64, AmlError>,
+{
+    let mut value = vec![0; length.div_ceil(8) as usize];
+    for bits in field_units(unit_size, offset, length) {
+        let unit = read_unit(bits.unit_offset)?;
+        set_buffer_bits(&mut value, bits.field, unit.get_bits(bits.unit));
+    }
+
+    if length <= 64 {
+        Ok(AmlValue::Integer(convert::buffer_to_integer(&value)))
+    } else {
+        Ok(AmlValue::buffer(value))
+    }
+}
+
+/// Write `value` to a field a unit at a time, with `write_unit` writing the unit at the given offset (in bits).
+/// `value` is truncated or zero-extended to the length of the field. The bits of each unit that aren't part of the
+/// field are set according to the field's update rule - for `Preserve`, the unit is first read with `read_unit`.
+fn write_field_units<R, W>(
+    unit_size: u64,
+    offset: u64,
+    length: u64,
+    update_rule: FieldUpdateRule,
+    value: &[u8],
+    mut read_unit: R,
+    mut write_unit: W,
+) -> Result<(), AmlError>
+where
+    R: FnMut(u64) -> Result<u64, AmlError>,
+    W: FnMut(u64, u64) -> Result<(), AmlError>,
+{
+    let unit_mask = if unit_size == 64 { u64::MAX } else { (1 << unit_size) - 1 };
+
+    for bits in field_units(unit_size, offset, length) {
+        let whole_unit = bits.unit.len() as u64 == unit_size;
+        let mut unit = match update_rule {
+            FieldUpdateRule::Preserve if !whole_unit => read_unit(bits.unit_offset)?,
+            FieldUpdateRule::Preserve | FieldUpdateRule::WriteAsZeros => 0,
+            FieldUpdateRule::WriteAsOnes => u64::MAX,
+        };
+        unit.set_bits(bits.unit, get_buffer_bits(value, bits.field));
+        write_unit(bits.unit_offset, unit & unit_mask)?;
+    }
+
+    Ok(())
+}
+
+fn read_region_field(
+    context: &AmlContext,
+    region: AmlHandle,
+    flags: FieldFlags,
+    offset: u64,
+    length: u64,
+) -> Result<AmlValue, AmlError> {
+    let unit_size = field_unit_size(flags, offset, length)?;
+    read_field_units(unit_size, offset, length, |unit_offset| {
+        context.read_region(region, unit_offset / 8, unit_size)
+    })
+}
+
+fn write_region_field(
+    context: &AmlContext,
+    region: AmlHandle,
+    flags: FieldFlags,
+    offset: u64,
+    length: u64,
+    value: &[u8],
+) -> Result<(), AmlError> {
+    let unit_size = field_unit_size(flags, offset, length)?;
+    write_field_units(
+        unit_size,
+        offset,
+        length,
+        flags.field_update_rule()?,
+        value,
+        |unit_offset| context.read_region(region, unit_offset / 8, unit_size),
+        |unit_offset, unit| context.write_region(region, unit_offset / 8, unit_size, unit),
+    )
+}
+
+/// Get up to 64 `bits` of a little-endian buffer. Bits past the end of the buffer are zero.
+fn get_buffer_bits(buffer: &[u8], bits: Range<usize>) -> u64 {
+    let mut value = 0;
+    for (i, bit) in bits.enumerate() {
+        if buffer.get(bit / 8).is_some_and(|byte| byte.get_bit(bit % 8)) {
+            value.set_bit(i, true);
+        }
+    }
+    value
+}
+
+fn set_buffer_bits(buffer: &mut [u8], bits: Range<usize>, value: u64) {
+    for (i, bit) in bits.enumerate() {
+        buffer[bit / 8].set_bit(bit % 8, value.get_bit(i));
+    }
+}
+
+/// Write `value` to the field `register`, which is used to access another field (e.g. the index register of an
+/// `IndexField`, or the bank register of a `BankField`).
+fn write_register(context: &AmlContext, register: AmlHandle, value: u64) -> Result<(), AmlError> {
+    context.namespace.get(register)?.write_field_unit(AmlValue::Integer(value), context)
+}
+
 /// A control method can take up to 7 arguments, each of which is an `AmlValue`.
 #[derive(Clone, Default, Debug)]
 pub struct Args(pub [Option<AmlValue>; 7]);
 
+/// Formats a value as it's shown when AML stores it to the `Debug` object. Objects that aren't data (e.g. devices
+/// and field units) are shown as their type.
+impl fmt::Display for AmlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmlValue::Boolean(value) => write!(f, "{:#x}", *value as u64),
+            AmlValue::Integer(value) => write!(f, "{:#x}", value),
+            AmlValue::String(string) => write!(f, "{:?}", string),
+            AmlValue::Buffer(bytes) => {
+                let bytes = bytes.lock();
+                write!(f, "Buffer({:#x}) {{", bytes.len())?;
+                for byte in bytes.iter() {
+                    write!(f, " {:02x}", byte)?;
+                }
+                f.write_str(" }")
+            }
+            AmlValue::Package(elements) => {
+                write!(f, "Package({:#x}) {{", elements.len())?;
+                for (i, element) in elements.iter().enumerate() {
+                    write!(f, "{}{}", if i == 0 { " " } else { ", " }, element)?;
+                }
+                f.write_str(" }")
+            }
+            other => write!(f, "[{:?}]", other.type_of()),
+        }
+    }
+}
+
 impl Args {
     pub const EMPTY: Self = Self([None, None, None, None, None, None, None]);
 
@@ -654,11 +1122,83 @@ impl Args {
     }
 }
 
+/// An argument to pass to a control method with [`AmlContext::invoke`]. These can be created from Rust integers,
+/// strings, byte slices, and slices of other `ArgValue`s (for packages), or from an existing `AmlValue`.
+#[derive(Clone, Debug)]
+pub enum ArgValue<'a> {
+    Integer(u64),
+    String(&'a str),
+    Buffer(&'a [u8]),
+    Package(&'a [ArgValue<'a>]),
+    Value(AmlValue),
+}
+
+impl<'a> ArgValue<'a> {
+    pub fn to_aml_value(&self) -> AmlValue {
+        match self {
+            ArgValue::Integer(value) => AmlValue::Integer(*value),
+            ArgValue::String(value) => AmlValue::String(value.to_string()),
+            ArgValue::Buffer(bytes) => AmlValue::buffer(bytes.to_vec()),
+            ArgValue::Package(elements) => {
+                AmlValue::package(elements.iter().map(ArgValue::to_aml_value).collect())
+            }
+            ArgValue::Value(value) => value.clone(),
+        }
+    }
+}
+
+macro_rules! arg_value_from_integer {
+    ($($type:ty),*) => {
+        $(
+            impl<'a> From<$type> for ArgValue<'a> {
+                fn from(value: $type) -> Self {
+                    ArgValue::Integer(value as u64)
+                }
+            }
+        )*
+    };
+}
+
+arg_value_from_integer!(u8, u16, u32, u64, usize, bool);
+
+impl<'a> From<&'a str> for ArgValue<'a> {
+    fn from(value: &'a str) -> Self {
+        ArgValue::String(value)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ArgValue<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        ArgValue::Buffer(bytes)
+    }
+}
+
+impl<'a> From<&'a [ArgValue<'a>]> for ArgValue<'a> {
+    fn from(elements: &'a [ArgValue<'a>]) -> Self {
+        ArgValue::Package(elements)
+    }
+}
+
+impl<'a> From<AmlValue> for ArgValue<'a> {
+    fn from(value: AmlValue) -> Self {
+        ArgValue::Value(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::*;
-    use core::cmp::Ordering;
+    use crate::{
+        name_object::Target,
+        sync::{SyncHandler, WAIT_FOREVER},
+        test_utils::*,
+        RegionHandler,
+    };
+    use alloc::{boxed::Box, collections::BTreeMap, vec};
+    use core::{
+        cmp::Ordering,
+        sync::atomic::{self, AtomicUsize},
+    };
 
     #[test]
     fn test_object_cmp() {
@@ -670,4 +1210,315 @@ mod tests {
 
         // TODO: test the other combinations too, as well as conversions to the correct types for the second operand
     }
+
+    #[test]
+    fn test_debug_rendering() {
+        assert_eq!(AmlValue::Integer(0x1234).to_string(), "0x1234");
+        assert_eq!(AmlValue::String(String::from("Hello")).to_string(), "\"Hello\"");
+        assert_eq!(AmlValue::buffer(vec![0x01, 0xab]).to_string(), "Buffer(0x2) { 01 ab }");
+        assert_eq!(
+            AmlValue::package(vec![AmlValue::Integer(1), AmlValue::String(String::from("A")), AmlValue::Device])
+                .to_string(),
+            "Package(0x3) { 0x1, \"A\", [Device] }"
+        );
+
+        // Stores to `Debug` are passed to the handler, which logs them by default
+        let mut context = make_test_context();
+        assert!(matches!(context.store(Target::Debug, AmlValue::Integer(5)), Ok(AmlValue::Integer(5))));
+    }
+
+    #[test]
+    fn test_arg_values() {
+        let elements = [ArgValue::from(4u8), ArgValue::from("PNP0C0A")];
+        let package = ArgValue::from(&elements[..]).to_aml_value();
+
+        let package = package.as_package().unwrap();
+        assert_eq!(package[0].as_u64(), Ok(4));
+        assert!(matches!(package[1], AmlValue::String(ref string) if string == "PNP0C0A"));
+        assert_eq!(ArgValue::from(&[0x34, 0x12][..]).to_aml_value().as_u64(), Ok(0x1234));
+        assert_eq!(ArgValue::from(true).to_aml_value().as_u64(), Ok(1));
+    }
+
+    /// Emulates a SuperIO chip's index and data registers at `0x2e` and `0x2f`, and a banked register at `0x90`,
+    /// with the bank selected by the register at `0x80`.
+    #[derive(Default)]
+    struct SuperIo {
+        index: u64,
+        config: BTreeMap<u64, u64>,
+        bank: u64,
+        banks: BTreeMap<(u64, u64), u64>,
+    }
+
+    struct SuperIoHandler(Arc<Spinlock<SuperIo>>);
+
+    impl RegionHandler for SuperIoHandler {
+        fn read(&self, address: u64, _length: u64) -> Result<u64, AmlError> {
+            let chip = self.0.lock();
+            Ok(match address {
+                0x2e => chip.index,
+                0x2f => chip.config.get(&chip.index).copied().unwrap_or(0),
+                0x80 => chip.bank,
+                _ => chip.banks.get(&(chip.bank, address)).copied().unwrap_or(0),
+            })
+        }
+
+        fn write(&self, address: u64, _length: u64, value: u64) -> Result<(), AmlError> {
+            let mut chip = self.0.lock();
+            match address {
+                0x2e => chip.index = value,
+                0x2f => {
+                    let index = chip.index;
+                    chip.config.insert(index, value);
+                }
+                0x80 => chip.bank = value,
+                _ => {
+                    let bank = chip.bank;
+                    chip.banks.insert((bank, address), value);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_index_and_bank_fields() {
+        let mut context = make_test_context();
+        let chip = Arc::new(Spinlock::new(SuperIo::default()));
+        context.install_region_handler(RegionSpace::SystemIo, Box::new(SuperIoHandler(chip.clone())));
+
+        /*
+         * OperationRegion (SIOI, SystemIO, 0x2e, 1)
+         * OperationRegion (SIOD, SystemIO, 0x2f, 1)
+         * Field (SIOI, ByteAcc, NoLock, Preserve) { INDX, 8 }
+         * Field (SIOD, ByteAcc, NoLock, Preserve) { DATA, 8 }
+         * IndexField (INDX, DATA, ByteAcc, NoLock, Preserve) {
+         *     Offset (0x07),
+         *     LDN_, 8,
+         *     Offset (0x60),
+         *     IOBS, 16,
+         *     ACT0, 1,
+         *     , 3,
+         *     HIGH, 4
+         * }
+         *
+         * OperationRegion (SELR, SystemIO, 0x80, 1)
+         * OperationRegion (BNKR, SystemIO, 0x90, 1)
+         * Field (SELR, ByteAcc, NoLock, Preserve) { BSEL, 8 }
+         * BankField (BNKR, BSEL, 2, ByteAcc, NoLock, Preserve) { BK2A, 8 }
+         * BankField (BNKR, BSEL, 3, ByteAcc, NoLock, Preserve) { BK3A, 8 }
+         */
+        context
+            .parse_table(&[
+                0x5b, 0x80, b'S', b'I', b'O', b'I', 0x01, 0x0a, 0x2e, 0x01, 0x5b, 0x80, b'S', b'I', b'O', b'D',
+                0x01, 0x0a, 0x2f, 0x01, 0x5b, 0x81, 0x0b, b'S', b'I', b'O', b'I', 0x01, b'I', b'N', b'D', b'X',
+                0x08, 0x5b, 0x81, 0x0b, b'S', b'I', b'O', b'D', 0x01, b'D', b'A', b'T', b'A', 0x08, 0x5b, 0x86,
+                0x25, b'I', b'N', b'D', b'X', b'D', b'A', b'T', b'A', 0x01, 0x00, 0x38, b'L', b'D', b'N', b'_',
+                0x08, 0x00, 0x40, 0x2c, b'I', b'O', b'B', b'S', 0x10, b'A', b'C', b'T', b'0', 0x01, 0x00, 0x03,
+                b'H', b'I', b'G', b'H', 0x04, 0x5b, 0x80, b'S', b'E', b'L', b'R', 0x01, 0x0a, 0x80, 0x01, 0x5b,
+                0x80, b'B', b'N', b'K', b'R', 0x01, 0x0a, 0x90, 0x01, 0x5b, 0x81, 0x0b, b'S', b'E', b'L', b'R',
+                0x01, b'B', b'S', b'E', b'L', 0x08, 0x5b, 0x87, 0x11, b'B', b'N', b'K', b'R', b'B', b'S', b'E',
+                b'L', 0x0a, 0x02, 0x01, b'B', b'K', b'2', b'A', 0x08, 0x5b, 0x87, 0x11, b'B', b'N', b'K', b'R',
+                b'B', b'S', b'E', b'L', 0x0a, 0x03, 0x01, b'B', b'K', b'3', b'A', 0x08,
+            ])
+            .unwrap();
+        let name = |name: &str| Target::Name(AmlName::from_str(name).unwrap());
+        let read = |context: &AmlContext, name: &str| {
+            context
+                .namespace
+                .get_by_path(&AmlName::from_str(name).unwrap())
+                .unwrap()
+                .read_field(context)
+                .unwrap()
+                .as_integer(context)
+                .unwrap()
+        };
+
+        context.store(name("\\LDN_"), AmlValue::Integer(0x0a)).unwrap();
+        context.store(name("\\IOBS"), AmlValue::Integer(0x0290)).unwrap();
+        assert_eq!(chip.lock().config.get(&0x07), Some(&0x0a));
+        assert_eq!(chip.lock().config.get(&0x60), Some(&0x90));
+        assert_eq!(chip.lock().config.get(&0x61), Some(&0x02));
+        assert_eq!(read(&context, "\\IOBS"), 0x0290);
+
+        // `ACT0` and `HIGH` share a unit, so writing one should preserve the other
+        chip.lock().config.insert(0x62, 0xff);
+        context.store(name("\\ACT0"), AmlValue::Integer(0)).unwrap();
+        assert_eq!(chip.lock().config.get(&0x62), Some(&0xfe));
+        assert_eq!(read(&context, "\\HIGH"), 0xf);
+
+        context.store(name("\\BK2A"), AmlValue::Integer(0x12)).unwrap();
+        context.store(name("\\BK3A"), AmlValue::Integer(0x34)).unwrap();
+        assert_eq!(chip.lock().banks.get(&(2, 0x90)), Some(&0x12));
+        assert_eq!(chip.lock().banks.get(&(3, 0x90)), Some(&0x34));
+        assert_eq!(read(&context, "\\BK2A"), 0x12);
+        assert_eq!(chip.lock().bank, 2);
+    }
+
+    /// Records the accesses made to a region of 8 bytes of memory at `0x100`.
+    struct AccessRecorder {
+        memory: Spinlock<[u8; 8]>,
+        accesses: Spinlock<Vec<(bool, u64, u64)>>,
+    }
+
+    impl RegionHandler for Arc<AccessRecorder> {
+        fn read(&self, address: u64, length: u64) -> Result<u64, AmlError> {
+            self.accesses.lock().push((false, address, length));
+            let start = (address - 0x100) as usize;
+            Ok(convert::buffer_to_integer(&self.memory.lock()[start..(start + length as usize / 8)]))
+        }
+
+        fn write(&self, address: u64, length: u64, value: u64) -> Result<(), AmlError> {
+            self.accesses.lock().push((true, address, length));
+            let start = (address - 0x100) as usize;
+            let length = length as usize / 8;
+            self.memory.lock()[start..(start + length)].copy_from_slice(&value.to_le_bytes()[..length]);
+            Ok(())
+        }
+    }
+
+    /// Counts the acquires and releases of the Global Lock.
+    #[derive(Default)]
+    struct GlobalLockCounter {
+        acquires: AtomicUsize,
+        releases: AtomicUsize,
+    }
+
+    impl SyncHandler for Arc<GlobalLockCounter> {
+        fn acquire(&self, _mutex: AmlHandle, _timeout: u16) -> bool {
+            true
+        }
+
+        fn release(&self, _mutex: AmlHandle) {}
+
+        fn wait(&self, _event: AmlHandle, _timeout: u16) -> bool {
+            true
+        }
+
+        fn signal(&self, _event: AmlHandle) {}
+
+        fn reset(&self, _event: AmlHandle) {}
+
+        fn acquire_global_lock(&self, _timeout: u16) -> bool {
+            self.acquires.fetch_add(1, atomic::Ordering::Relaxed);
+            true
+        }
+
+        fn release_global_lock(&self) {
+            self.releases.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_field_access() {
+        let mut context = make_test_context();
+        let recorder = Arc::new(AccessRecorder {
+            memory: Spinlock::new([0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17]),
+            accesses: Spinlock::new(Vec::new()),
+        });
+        let lock = Arc::new(GlobalLockCounter::default());
+        context.install_region_handler(RegionSpace::SystemMemory, Box::new(recorder.clone()));
+        context.install_sync_handler(Box::new(lock.clone()));
+
+        /*
+         * OperationRegion (TREG, SystemMemory, 0x100, 8)
+         * Field (TREG, WordAcc, NoLock, WriteAsOnes) { Offset (1), FLD0, 8 }
+         * Field (TREG, DWordAcc, Lock, Preserve) { Offset (3), FLD1, 16 }
+         * Field (TREG, ByteAcc, NoLock, WriteAsZeros) { Offset (6), FLD2, 4 }
+         * Field (TREG, ByteAcc, NoLock, Preserve) { AccessAs (QWordAcc), FLD3, 64 }
+         */
+        context
+            .parse_table(&[
+                0x5b, 0x80, b'T', b'R', b'E', b'G', 0x00, 0x0b, 0x00, 0x01, 0x0a, 0x08, 0x5b, 0x81, 0x0d, b'T',
+                b'R', b'E', b'G', 0x22, 0x00, 0x08, b'F', b'L', b'D', b'0', 0x08, 0x5b, 0x81, 0x0d, b'T', b'R',
+                b'E', b'G', 0x13, 0x00, 0x18, b'F', b'L', b'D', b'1', 0x10, 0x5b, 0x81, 0x0d, b'T', b'R', b'E',
+                b'G', 0x41, 0x00, 0x30, b'F', b'L', b'D', b'2', 0x04, 0x5b, 0x81, 0x0f, b'T', b'R', b'E', b'G',
+                0x01, 0x01, 0x04, 0x00, b'F', b'L', b'D', b'3', 0x40, 0x04,
+            ])
+            .unwrap();
+        let name = |name: &str| Target::Name(AmlName::from_str(name).unwrap());
+        let read = |context: &AmlContext, name: &str| {
+            context
+                .namespace
+                .get_by_path(&AmlName::from_str(name).unwrap())
+                .unwrap()
+                .read_field(context)
+                .unwrap()
+                .as_integer(context)
+                .unwrap()
+        };
+        let accesses = || core::mem::take(&mut *recorder.accesses.lock());
+
+        // A field is accessed in units of its access width, and `WriteAsOnes` sets the rest of the unit. Stores
+        // read the field back, as their result.
+        context.store(name("\\FLD0"), AmlValue::Integer(0xab)).unwrap();
+        assert_eq!(accesses(), vec![(true, 0x100, 16), (false, 0x100, 16)]);
+        assert_eq!(recorder.memory.lock()[0..2], [0xff, 0xab]);
+
+        // `FLD1` is split across two units, which are each read first to preserve their other bits
+        context.store(name("\\FLD1"), AmlValue::Integer(0x1234)).unwrap();
+        assert_eq!(
+            accesses(),
+            vec![
+                (false, 0x100, 32),
+                (true, 0x100, 32),
+                (false, 0x104, 32),
+                (true, 0x104, 32),
+                (false, 0x100, 32),
+                (false, 0x104, 32)
+            ]
+        );
+        assert_eq!(*recorder.memory.lock(), [0xff, 0xab, 0x12, 0x34, 0x12, 0x15, 0x16, 0x17]);
+        assert_eq!(read(&context, "\\FLD1"), 0x1234);
+        assert_eq!(accesses(), vec![(false, 0x100, 32), (false, 0x104, 32)]);
+        assert_eq!(lock.acquires.load(atomic::Ordering::Relaxed), 3);
+        assert_eq!(lock.releases.load(atomic::Ordering::Relaxed), 3);
+
+        context.store(name("\\FLD2"), AmlValue::Integer(0xf)).unwrap();
+        assert_eq!(accesses(), vec![(true, 0x106, 8), (false, 0x106, 8)]);
+        assert_eq!(recorder.memory.lock()[6], 0x0f);
+
+        // `AccessAs` changes the access width of the fields after it
+        assert_eq!(read(&context, "\\FLD3"), 0x17_0f_15_12_34_12_ab_ff);
+        assert_eq!(accesses(), vec![(false, 0x100, 64)]);
+
+        // The Global Lock isn't acquired again for a `Lock` field if the AML already holds `\_GL`
+        let global_lock = name("\\_GL");
+        assert_eq!(context.acquire_mutex(&global_lock, WAIT_FOREVER), Ok(false));
+        assert_eq!(lock.acquires.load(atomic::Ordering::Relaxed), 4);
+        assert_eq!(read(&context, "\\FLD1"), 0x1234);
+        assert_eq!(context.release_mutex(&global_lock), Ok(()));
+        assert_eq!(lock.acquires.load(atomic::Ordering::Relaxed), 4);
+        assert_eq!(lock.releases.load(atomic::Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_shared_values() {
+        let mut context = make_test_context();
+        let buf1 = AmlName::from_str("\\BUF1").unwrap();
+        let buf2 = AmlName::from_str("\\BUF2").unwrap();
+        context.namespace.add_value(buf1.clone(), AmlValue::buffer(vec![1, 2, 3, 4])).unwrap();
+        context.namespace.add_value(buf2.clone(), AmlValue::buffer(vec![0xff; 6])).unwrap();
+        let buf1_data = context.namespace.get_by_path(&buf1).unwrap().as_buffer(&context).unwrap();
+        let buf2_data = context.namespace.get_by_path(&buf2).unwrap().as_buffer(&context).unwrap();
+
+        // Stores into a named buffer copy the contents into it, rather than making it share the source's
+        let value = context.namespace.get_by_path(&buf1).unwrap().clone();
+        context.store(Target::Name(buf2.clone()), value).unwrap();
+        assert_eq!(*buf2_data.lock(), vec![1, 2, 3, 4, 0, 0]);
+        buf1_data.lock()[0] = 5;
+        assert_eq!(buf2_data.lock()[0], 1);
+
+        // Copies of shared buffers don't share their contents
+        let copy = AmlValue::Buffer(buf1_data.clone()).copy_object().as_buffer(&context).unwrap();
+        assert!(!Arc::ptr_eq(&copy, &buf1_data));
+        assert_eq!(*copy.lock(), vec![5, 2, 3, 4]);
+
+        // Packages share their elements until they're modified
+        let package = AmlValue::package(vec![AmlValue::Integer(1), AmlValue::Integer(2)]);
+        let mut copy = package.clone().copy_object();
+        assert!(matches!((&package, &copy), (AmlValue::Package(a), AmlValue::Package(b)) if Arc::ptr_eq(a, b)));
+        copy.package_mut().unwrap()[0] = AmlValue::Integer(3);
+        assert!(matches!(package.as_package().unwrap(), [AmlValue::Integer(1), AmlValue::Integer(2)]));
+        assert!(matches!(copy.as_package().unwrap(), [AmlValue::Integer(3), AmlValue::Integer(2)]));
+    }
 }
@@ -0,0 +1,195 @@
+//! The implicit conversions between the computational data types of AML - `Integer`, `String`, and `Buffer` - that
+//! are applied to the operands of operators, and to values stored into named objects (§19.3.5.7). Where the spec
+//! is vague, or real firmware depends on behaviour it doesn't describe, these follow ACPICA.
+//!
+//! Integers are always 64 bits wide, as they are in definition blocks with a revision of `2` or greater.
+
+use crate::AmlError;
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// The longest string that converting a buffer can produce.
+pub const MAX_STRING_CONVERSION: usize = 200;
+
+/// The largest integer that can be converted to BCD, which is the largest with 16 decimal digits.
+pub const MAX_BCD_VALUE: u64 = 9_999_999_999_999_999;
+
+/// Convert an integer to a buffer of its bytes, least significant first.
+pub fn integer_to_buffer(value: u64) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+/// Convert an integer to a string of 16 upper-case hexadecimal digits, including any leading zeroes.
+pub fn integer_to_string(value: u64) -> String {
+    alloc::format!("{:016X}", value)
+}
+
+/// Convert a buffer to an integer, taking the first 8 bytes of the buffer as the integer with the first byte as
+/// the least significant byte. Shorter buffers are zero-extended.
+///
+/// The spec says that zero-length buffers can't be converted, but they appear in real tables, so we convert them
+/// to `0`.
+pub fn buffer_to_integer(bytes: &[u8]) -> u64 {
+    let mut integer = [0; 8];
+    let length = bytes.len().min(8);
+    integer[..length].copy_from_slice(&bytes[..length]);
+    u64::from_le_bytes(integer)
+}
+
+/// Convert a buffer to a string of two-digit hexadecimal numbers separated by spaces (e.g. `"01 AB 3F"`).
+/// Produces `AmlError::StringConversionTooLong` if the string would be longer than `MAX_STRING_CONVERSION`.
+pub fn buffer_to_string(bytes: &[u8]) -> Result<String, AmlError> {
+    if (bytes.len() * 3).saturating_sub(1) > MAX_STRING_CONVERSION {
+        return Err(AmlError::StringConversionTooLong);
+    }
+
+    let mut string = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i != 0 {
+            string.push(' ');
+        }
+        write!(string, "{:02X}", byte).unwrap();
+    }
+    Ok(string)
+}
+
+/// Convert a string to an integer by interpreting it as a hexadecimal number. Like ACPICA, leading whitespace is
+/// skipped, and the conversion stops at the first character that isn't a hexadecimal digit (which means that a
+/// `0x` prefix converts to `0`). If there are more digits than fit into an integer, the conversion stops before
+/// the digit that would overflow it.
+pub fn string_to_integer(string: &str) -> u64 {
+    let mut value = 0u64;
+    for c in string.trim_start().chars() {
+        let digit = match c.to_digit(16) {
+            Some(digit) => digit as u64,
+            None => break,
+        };
+        if value.leading_zeros() < 4 {
+            break;
+        }
+        value = (value << 4) | digit;
+    }
+    value
+}
+
+/// Convert a string to a buffer of its bytes. Like ACPICA, this includes the null terminator, as firmware depends
+/// on it being copied into the buffer.
+pub fn string_to_buffer(string: &str) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(string.len() + 1);
+    buffer.extend_from_slice(string.as_bytes());
+    buffer.push(0);
+    buffer
+}
+
+/// Copy `source` into the existing contents of a buffer being stored into. The source is truncated if it's
+/// longer than the buffer, and the rest of the buffer is zeroed if it's shorter. A zero-length buffer instead
+/// takes on the length of the source.
+pub fn store_to_buffer(target: &mut Vec<u8>, source: &[u8]) {
+    if target.is_empty() {
+        target.extend_from_slice(source);
+        return;
+    }
+
+    let length = target.len().min(source.len());
+    target[..length].copy_from_slice(&source[..length]);
+    target[length..].fill(0);
+}
+
+/// Convert an integer to packed BCD, with one decimal digit in each nibble, as by `ToBCD`. Produces
+/// `AmlError::InvalidBcdValue` if the integer has more than 16 decimal digits.
+pub fn to_bcd(mut value: u64) -> Result<u64, AmlError> {
+    if value > MAX_BCD_VALUE {
+        return Err(AmlError::InvalidBcdValue(value));
+    }
+
+    let mut bcd = 0;
+    let mut shift = 0;
+    while value != 0 {
+        bcd |= (value % 10) << shift;
+        value /= 10;
+        shift += 4;
+    }
+    Ok(bcd)
+}
+
+/// Convert a packed BCD value to an integer, as by `FromBCD`. Produces `AmlError::InvalidBcdValue` if any nibble
+/// isn't a decimal digit.
+pub fn from_bcd(bcd: u64) -> Result<u64, AmlError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(4).rev() {
+        let digit = (bcd >> shift) & 0xf;
+        if digit > 9 {
+            return Err(AmlError::InvalidBcdValue(bcd));
+        }
+        value = value * 10 + digit;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_integer_conversions() {
+        assert_eq!(integer_to_buffer(0x0102030405060708), vec![0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(integer_to_buffer(0), vec![0; 8]);
+        assert_eq!(integer_to_string(0xabc), "0000000000000ABC");
+        assert_eq!(integer_to_string(u64::MAX), "FFFFFFFFFFFFFFFF");
+    }
+
+    #[test]
+    fn test_buffer_conversions() {
+        assert_eq!(buffer_to_integer(&[]), 0);
+        assert_eq!(buffer_to_integer(&[0x01, 0x02]), 0x0201);
+        assert_eq!(buffer_to_integer(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09]), 0x0807060504030201);
+
+        assert_eq!(buffer_to_string(&[]), Ok(String::new()));
+        assert_eq!(buffer_to_string(&[0x01, 0xab, 0x3f]), Ok(String::from("01 AB 3F")));
+        assert_eq!(buffer_to_string(&[0; 67]).map(|string| string.len()), Ok(200));
+        assert_eq!(buffer_to_string(&[0; 68]), Err(AmlError::StringConversionTooLong));
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        assert_eq!(string_to_integer(""), 0);
+        assert_eq!(string_to_integer("1234"), 0x1234);
+        assert_eq!(string_to_integer("abcDEF"), 0xabcdef);
+        assert_eq!(string_to_integer("  12"), 0x12);
+        assert_eq!(string_to_integer("12G4"), 0x12);
+        assert_eq!(string_to_integer("0x12"), 0);
+        assert_eq!(string_to_integer("000000000000000000001"), 1);
+        assert_eq!(string_to_integer("123456789ABCDEF012"), 0x123456789abcdef0);
+
+        assert_eq!(string_to_buffer(""), vec![0]);
+        assert_eq!(string_to_buffer("AB"), vec![b'A', b'B', 0]);
+    }
+
+    #[test]
+    fn test_store_to_buffer() {
+        let mut target = vec![0xff; 4];
+        store_to_buffer(&mut target, &[1, 2]);
+        assert_eq!(target, vec![1, 2, 0, 0]);
+
+        store_to_buffer(&mut target, &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(target, vec![1, 2, 3, 4]);
+
+        let mut target = vec![];
+        store_to_buffer(&mut target, &[1, 2, 3]);
+        assert_eq!(target, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_bcd() {
+        assert_eq!(to_bcd(0), Ok(0));
+        assert_eq!(to_bcd(1234), Ok(0x1234));
+        assert_eq!(to_bcd(MAX_BCD_VALUE), Ok(0x9999_9999_9999_9999));
+        assert_eq!(to_bcd(MAX_BCD_VALUE + 1), Err(AmlError::InvalidBcdValue(MAX_BCD_VALUE + 1)));
+
+        assert_eq!(from_bcd(0), Ok(0));
+        assert_eq!(from_bcd(0x1234), Ok(1234));
+        assert_eq!(from_bcd(0x9999_9999_9999_9999), Ok(MAX_BCD_VALUE));
+        assert_eq!(from_bcd(0x12a4), Err(AmlError::InvalidBcdValue(0x12a4)));
+    }
+}
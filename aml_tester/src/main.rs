@@ -18,6 +18,9 @@ use std::{
     io::{Read, Write},
     path::{Path, PathBuf},
     process::Command,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
 };
 
 enum CompilationOutcome {
@@ -158,6 +161,7 @@ fn main() -> std::io::Result<()> {
             Err(err) => {
                 println!("{}Failed ({:?}){}", termion::color::Fg(termion::color::Red), err, termion::style::Reset);
                 println!("Namespace: {:#?}", context.namespace);
+                println!("Disassembly:\n{}", aml::disassemble::disassemble(&contents[AML_TABLE_HEADER_LENGTH..]));
                 (passed, failed + 1)
             }
         }
@@ -252,16 +256,16 @@ impl aml::Handler for Handler {
         0
     }
 
-    fn write_u8(&mut self, address: usize, value: u8) {
+    fn write_u8(&self, address: usize, value: u8) {
         println!("write_u8 {address:#x}<-{value:#x}");
     }
-    fn write_u16(&mut self, address: usize, value: u16) {
+    fn write_u16(&self, address: usize, value: u16) {
         println!("write_u16 {address:#x}<-{value:#x}");
     }
-    fn write_u32(&mut self, address: usize, value: u32) {
+    fn write_u32(&self, address: usize, value: u32) {
         println!("write_u32 {address:#x}<-{value:#x}");
     }
-    fn write_u64(&mut self, address: usize, value: u64) {
+    fn write_u64(&self, address: usize, value: u64) {
         println!("write_u64 {address:#x}<-{value:#x}");
     }
 
@@ -310,4 +314,19 @@ impl aml::Handler for Handler {
     fn write_pci_u32(&self, segment: u16, bus: u8, device: u8, function: u8, _offset: u16, value: u32) {
         println!("write_pci_u32 ({segment:#x}, {bus:#x}, {device:#x}, {function:#x})<-{value:#x}");
     }
+
+    fn nanos_since_boot(&self) -> u64 {
+        START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+    fn stall(&self, microseconds: u64) {
+        println!("stall {microseconds}us");
+        thread::sleep(Duration::from_micros(microseconds));
+    }
+    fn sleep(&self, milliseconds: u64) {
+        println!("sleep {milliseconds}ms");
+        thread::sleep(Duration::from_millis(milliseconds));
+    }
 }
+
+/// The time the tester started at, which the AML `Timer` counts from.
+static START: OnceLock<Instant> = OnceLock::new();
@@ -26,6 +26,8 @@ pub enum RsdpError {
     IncorrectSignature,
     InvalidOemId,
     InvalidChecksum,
+    /// The EFI System Table passed to [`Rsdp::from_efi_system_table`] doesn't have the right signature.
+    InvalidEfiSystemTable,
 }
 
 /// The size in bytes of the ACPI 1.0 RSDP.
@@ -84,38 +86,31 @@ impl Rsdp {
     where
         H: AcpiHandler,
     {
-        let rsdp_address = find_search_areas(handler.clone()).iter().find_map(|area| {
-            // Map the search area for the RSDP followed by `RSDP_V2_EXT_LENGTH` bytes so an ACPI 1.0 RSDP at the
-            // end of the area can be read as an `Rsdp` (which always has the size of an ACPI 2.0 RSDP)
-            let mapping = unsafe {
-                handler.map_physical_region::<u8>(area.start, area.end - area.start + RSDP_V2_EXT_LENGTH)
-            };
-
-            let extended_area_bytes =
-                unsafe { slice::from_raw_parts(mapping.virtual_start().as_ptr(), mapping.region_length()) };
-
-            // Search `Rsdp`-sized windows at 16-byte boundaries relative to the base of the area (which is also
-            // aligned to 16 bytes due to the implementation of `find_search_areas`)
-            extended_area_bytes.windows(mem::size_of::<Rsdp>()).step_by(16).find_map(|maybe_rsdp_bytes_slice| {
-                let maybe_rsdp_virt_ptr = maybe_rsdp_bytes_slice.as_ptr().cast::<Rsdp>();
-                let maybe_rsdp_phys_start = maybe_rsdp_virt_ptr as usize
-                    - mapping.virtual_start().as_ptr() as usize
-                    + mapping.physical_start();
-                // SAFETY: `maybe_rsdp_virt_ptr` points to an aligned, readable `Rsdp`-sized value, and the `Rsdp`
-                // struct's fields are always initialized.
-                let maybe_rsdp = unsafe { &*maybe_rsdp_virt_ptr };
-
-                match maybe_rsdp.validate() {
-                    Ok(()) => Some(maybe_rsdp_phys_start),
-                    Err(RsdpError::IncorrectSignature) => None,
-                    Err(e) => {
-                        warn!("Invalid RSDP found at {:#x}: {:?}", maybe_rsdp_phys_start, e);
-
-                        None
-                    }
-                }
-            })
-        });
+        unsafe { Self::search_for_on_bios_with(handler, &BiosSearchOptions::default()) }
+    }
+
+    /// Search for the RSDP on a BIOS system, like [`Rsdp::search_for_on_bios`], but also searching the areas in
+    /// `options.additional_areas` (before the standard ones), and optionally without probing the EBDA. This is
+    /// useful on platforms that put the RSDP somewhere else, such as some coreboot-based and emulated ones.
+    ///
+    /// ### Safety
+    /// As well as the locations probed by `search_for_on_bios` (except the EBDA, if `options.search_ebda` is
+    /// `false`), this reads each of the additional areas, which must be safe to read.
+    pub unsafe fn search_for_on_bios_with<H>(
+        handler: H,
+        options: &BiosSearchOptions<'_>,
+    ) -> Result<PhysicalMapping<H, Rsdp>, RsdpError>
+    where
+        H: AcpiHandler,
+    {
+        let bios_area = RSDP_BIOS_AREA_START..(RSDP_BIOS_AREA_END + 1);
+        let rsdp_address = options
+            .additional_areas
+            .iter()
+            .cloned()
+            .chain(core::iter::once(bios_area))
+            .chain(options.search_ebda.then(|| find_ebda_search_area(handler.clone())))
+            .find_map(|area| unsafe { search_area(&handler, area) });
 
         match rsdp_address {
             Some(address) => {
@@ -126,6 +121,85 @@ impl Rsdp {
         }
     }
 
+    /// Find the RSDP on a UEFI system, from the configuration table of the EFI System Table at `system_table`
+    /// (e.g. the pointer passed to the image's entry point). The ACPI 2.0 entry is preferred, and the ACPI 1.0
+    /// entry is only used if there isn't one. The RSDP is validated before it's returned.
+    ///
+    /// This crate deliberately doesn't depend on the `uefi` crate, so that it can be used by bootloaders and
+    /// kernels that don't, and takes the raw address of the system table instead of its `SystemTable` type. With
+    /// the `uefi` crate, the address is the one returned by `SystemTable::as_ptr`.
+    ///
+    /// ### Safety
+    /// `system_table` must be the physical address of a valid EFI System Table, and the configuration table it
+    /// points to must not have been changed since (e.g. by a call to `InstallConfigurationTable`). The firmware's
+    /// tables use the native pointer width, so this must be called from code running in the same mode as the
+    /// firmware.
+    pub unsafe fn from_efi_system_table<H>(
+        system_table: usize,
+        handler: H,
+    ) -> Result<PhysicalMapping<H, Rsdp>, RsdpError>
+    where
+        H: AcpiHandler,
+    {
+        let system_table_mapping = unsafe {
+            handler.map_physical_region::<EfiSystemTable>(system_table, mem::size_of::<EfiSystemTable>())
+        };
+        let (number_of_entries, configuration_table) = {
+            let system_table = unsafe { system_table_mapping.virtual_start().as_ref() };
+            if system_table.signature != EFI_SYSTEM_TABLE_SIGNATURE {
+                return Err(RsdpError::InvalidEfiSystemTable);
+            }
+
+            (system_table.number_of_table_entries, system_table.configuration_table)
+        };
+        drop(system_table_mapping);
+
+        let entries_length = number_of_entries
+            .checked_mul(mem::size_of::<EfiConfigurationTableEntry>())
+            .ok_or(RsdpError::InvalidEfiSystemTable)?;
+        let entries_mapping = unsafe {
+            handler.map_physical_region::<EfiConfigurationTableEntry>(configuration_table, entries_length)
+        };
+        let entries =
+            unsafe { slice::from_raw_parts(entries_mapping.virtual_start().as_ptr(), number_of_entries) };
+        let find_entry =
+            |guid| entries.iter().find(|entry| entry.vendor_guid == guid).map(|entry| entry.vendor_table);
+        let rsdp_address = find_entry(EFI_ACPI_2_0_TABLE_GUID)
+            .or_else(|| find_entry(EFI_ACPI_1_0_TABLE_GUID))
+            .ok_or(RsdpError::NoValidRsdp)?;
+        drop(entries_mapping);
+
+        let rsdp_mapping = unsafe { handler.map_physical_region::<Rsdp>(rsdp_address, mem::size_of::<Rsdp>()) };
+        rsdp_mapping.validate()?;
+        Ok(rsdp_mapping)
+    }
+
+    /// Construct an ACPI 2.0+ RSDP pointing to the XSDT at `xsdt_address` (and to the RSDT at `rsdt_address`, if
+    /// there is one), with valid checksums. This is useful for building tables, such as for a virtual machine.
+    pub fn new(oem_id: [u8; 6], rsdt_address: u32, xsdt_address: u64) -> Rsdp {
+        let mut rsdp = Rsdp {
+            signature: RSDP_SIGNATURE,
+            checksum: 0,
+            oem_id,
+            revision: 2,
+            rsdt_address,
+            length: mem::size_of::<Rsdp>() as u32,
+            xsdt_address,
+            ext_checksum: 0,
+            reserved: [0; 3],
+        };
+
+        let checksum = |rsdp: &Rsdp, length| {
+            let bytes = unsafe { slice::from_raw_parts(rsdp as *const Rsdp as *const u8, length) };
+            bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)).wrapping_neg()
+        };
+
+        // The checksum of the first 20 bytes has to be correct before the extended checksum (which covers it) is
+        rsdp.checksum = checksum(&rsdp, RSDP_V1_LENGTH);
+        rsdp.ext_checksum = checksum(&rsdp, mem::size_of::<Rsdp>());
+        rsdp
+    }
+
     /// Checks that:
     ///     1) The signature is correct
     ///     2) The checksum is correct
@@ -141,6 +215,12 @@ impl Rsdp {
             return Err(RsdpError::InvalidOemId);
         }
 
+        self.validate_checksum()
+    }
+
+    /// Checks that the checksum is correct, and for Version 2.0+, that the extension checksum is correct. This is
+    /// part of [`Rsdp::validate`], but can be used on its own to check an RSDP with an invalid OEM id.
+    pub fn validate_checksum(&self) -> Result<(), RsdpError> {
         /*
          * `self.length` doesn't exist on ACPI version 1.0, so we mustn't rely on it. Instead,
          * check for version 1.0 and use a hard-coded length instead.
@@ -170,8 +250,11 @@ impl Rsdp {
         self.checksum
     }
 
+    /// The OEM id. If it isn't valid UTF8 (which `validate` checks), this is the part of it before the first
+    /// invalid byte.
     pub fn oem_id(&self) -> &str {
-        str::from_utf8(&self.oem_id).unwrap()
+        str::from_utf8(&self.oem_id)
+            .unwrap_or_else(|err| str::from_utf8(&self.oem_id[..err.valid_up_to()]).unwrap())
     }
 
     pub fn revision(&self) -> u8 {
@@ -198,19 +281,28 @@ impl Rsdp {
     }
 }
 
+/// Options for [`Rsdp::search_for_on_bios_with`].
+#[derive(Clone, Debug)]
+pub struct BiosSearchOptions<'a> {
+    /// Physical address ranges to search for the RSDP, before the standard areas. The RSDP is only looked for at
+    /// 16-byte boundaries within them.
+    pub additional_areas: &'a [Range<usize>],
+    /// Whether to find the EBDA (Extended BIOS Data Area) from the BDA, and search it. If this is `false`, the BDA
+    /// and EBDA are not accessed at all.
+    pub search_ebda: bool,
+}
+
+impl Default for BiosSearchOptions<'_> {
+    fn default() -> Self {
+        BiosSearchOptions { additional_areas: &[], search_ebda: true }
+    }
+}
+
 /// Find the areas we should search for the RSDP in.
 pub fn find_search_areas<H>(handler: H) -> [Range<usize>; 2]
 where
     H: AcpiHandler,
 {
-    /*
-     * Read the base address of the EBDA from its location in the BDA (BIOS Data Area). Not all BIOSs fill this out
-     * unfortunately, so we might not get a sensible result. We shift it left 4, as it's a segment address.
-     */
-    let ebda_start_mapping =
-        unsafe { handler.map_physical_region::<u16>(EBDA_START_SEGMENT_PTR, mem::size_of::<u16>()) };
-    let ebda_start = (*ebda_start_mapping as usize) << 4;
-
     [
         /*
          * The main BIOS area below 1MiB. In practice, from my [Restioson's] testing, the RSDP is more often here
@@ -218,17 +310,75 @@ where
          * from the BDA.
          */
         RSDP_BIOS_AREA_START..(RSDP_BIOS_AREA_END + 1),
-        // Check if base segment ptr is in valid range for EBDA base
-        if (EBDA_EARLIEST_START..EBDA_END).contains(&ebda_start) {
-            // First KiB of EBDA
-            ebda_start..ebda_start + 1024
-        } else {
-            // We don't know where the EBDA starts, so just search the largest possible EBDA
-            EBDA_EARLIEST_START..(EBDA_END + 1)
-        },
+        find_ebda_search_area(handler),
     ]
 }
 
+/// Find the area of the EBDA that should be searched for the RSDP.
+fn find_ebda_search_area<H>(handler: H) -> Range<usize>
+where
+    H: AcpiHandler,
+{
+    /*
+     * Read the base address of the EBDA from its location in the BDA (BIOS Data Area). Not all BIOSs fill this out
+     * unfortunately, so we might not get a sensible result. We shift it left 4, as it's a segment address.
+     */
+    let ebda_start_mapping =
+        unsafe { handler.map_physical_region::<u16>(EBDA_START_SEGMENT_PTR, mem::size_of::<u16>()) };
+    let ebda_start = (*ebda_start_mapping as usize) << 4;
+
+    // Check if base segment ptr is in valid range for EBDA base
+    if (EBDA_EARLIEST_START..EBDA_END).contains(&ebda_start) {
+        // First KiB of EBDA
+        ebda_start..ebda_start + 1024
+    } else {
+        // We don't know where the EBDA starts, so just search the largest possible EBDA
+        EBDA_EARLIEST_START..(EBDA_END + 1)
+    }
+}
+
+/// Search `area` for a valid RSDP at a 16-byte boundary, returning its physical address.
+///
+/// ### Safety
+/// `area`, and the RSDP-sized region after it, must be safe to read.
+unsafe fn search_area<H>(handler: &H, area: Range<usize>) -> Option<usize>
+where
+    H: AcpiHandler,
+{
+    // The RSDP is always at a 16-byte boundary, so start from the first one in the area
+    let start = area.start.checked_add(15)? & !15;
+    if start >= area.end {
+        return None;
+    }
+
+    // Map the search area for the RSDP followed by `RSDP_V2_EXT_LENGTH` bytes so an ACPI 1.0 RSDP at the end of
+    // the area can be read as an `Rsdp` (which always has the size of an ACPI 2.0 RSDP)
+    let mapping = unsafe { handler.map_physical_region::<u8>(start, area.end - start + RSDP_V2_EXT_LENGTH) };
+
+    let extended_area_bytes =
+        unsafe { slice::from_raw_parts(mapping.virtual_start().as_ptr(), mapping.region_length()) };
+
+    // Search `Rsdp`-sized windows at 16-byte boundaries relative to the (aligned) base of the area
+    extended_area_bytes.windows(mem::size_of::<Rsdp>()).step_by(16).find_map(|maybe_rsdp_bytes_slice| {
+        let maybe_rsdp_virt_ptr = maybe_rsdp_bytes_slice.as_ptr().cast::<Rsdp>();
+        let maybe_rsdp_phys_start =
+            maybe_rsdp_virt_ptr as usize - mapping.virtual_start().as_ptr() as usize + mapping.physical_start();
+        // SAFETY: `maybe_rsdp_virt_ptr` points to a readable `Rsdp`-sized value, and the `Rsdp` struct's fields
+        // are always initialized.
+        let maybe_rsdp = unsafe { &*maybe_rsdp_virt_ptr };
+
+        match maybe_rsdp.validate() {
+            Ok(()) => Some(maybe_rsdp_phys_start),
+            Err(RsdpError::IncorrectSignature) => None,
+            Err(e) => {
+                warn!("Invalid RSDP found at {:#x}: {:?}", maybe_rsdp_phys_start, e);
+
+                None
+            }
+        }
+    })
+}
+
 /// This (usually!) contains the base address of the EBDA (Extended Bios Data Area), shifted right by 4
 const EBDA_START_SEGMENT_PTR: usize = 0x40e;
 /// The earliest (lowest) memory address an EBDA (Extended Bios Data Area) can start
@@ -241,3 +391,168 @@ const RSDP_BIOS_AREA_START: usize = 0xe0000;
 const RSDP_BIOS_AREA_END: usize = 0xfffff;
 /// The RSDP (Root System Description Pointer)'s signature, "RSD PTR " (note trailing space)
 const RSDP_SIGNATURE: [u8; 8] = *b"RSD PTR ";
+
+/// The signature of the EFI System Table, "IBI SYST"
+const EFI_SYSTEM_TABLE_SIGNATURE: u64 = 0x5453_5953_2049_4249;
+/// The GUID of the configuration table entry for ACPI 2.0+ structures, `8868e871-e4f1-11d3-bc22-0080c73c8881`, in
+/// the mixed-endian layout used by EFI
+const EFI_ACPI_2_0_TABLE_GUID: [u8; 16] =
+    [0x71, 0xe8, 0x68, 0x88, 0xf1, 0xe4, 0xd3, 0x11, 0xbc, 0x22, 0x00, 0x80, 0xc7, 0x3c, 0x88, 0x81];
+/// The GUID of the configuration table entry for ACPI 1.0 structures, `eb9d2d30-2d88-11d3-9a16-0090273fc14d`
+const EFI_ACPI_1_0_TABLE_GUID: [u8; 16] =
+    [0x30, 0x2d, 0x9d, 0xeb, 0x88, 0x2d, 0xd3, 0x11, 0x9a, 0x16, 0x00, 0x90, 0x27, 0x3f, 0xc1, 0x4d];
+
+/// The fields of the EFI System Table, up to the configuration table. The services and protocols are only needed
+/// for their sizes, so are represented as addresses.
+#[repr(C)]
+struct EfiSystemTable {
+    signature: u64,
+    revision: u32,
+    header_size: u32,
+    crc32: u32,
+    _reserved: u32,
+    firmware_vendor: usize,
+    firmware_revision: u32,
+    console_in_handle: usize,
+    con_in: usize,
+    console_out_handle: usize,
+    con_out: usize,
+    standard_error_handle: usize,
+    std_err: usize,
+    runtime_services: usize,
+    boot_services: usize,
+    number_of_table_entries: usize,
+    configuration_table: usize,
+}
+
+#[repr(C)]
+struct EfiConfigurationTableEntry {
+    vendor_guid: [u8; 16],
+    vendor_table: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::NonNull;
+
+    #[derive(Clone)]
+    struct IdentityHandler;
+
+    impl AcpiHandler for IdentityHandler {
+        unsafe fn map_physical_region<T>(&self, address: usize, size: usize) -> PhysicalMapping<Self, T> {
+            unsafe { PhysicalMapping::new(address, NonNull::new(address as *mut T).unwrap(), size, size, Self) }
+        }
+
+        fn unmap_physical_region<T>(_region: &PhysicalMapping<Self, T>) {}
+    }
+
+    fn system_table(entries: &[EfiConfigurationTableEntry]) -> EfiSystemTable {
+        EfiSystemTable {
+            signature: EFI_SYSTEM_TABLE_SIGNATURE,
+            revision: 0x0002_0046,
+            header_size: mem::size_of::<EfiSystemTable>() as u32,
+            crc32: 0,
+            _reserved: 0,
+            firmware_vendor: 0,
+            firmware_revision: 0,
+            console_in_handle: 0,
+            con_in: 0,
+            console_out_handle: 0,
+            con_out: 0,
+            standard_error_handle: 0,
+            std_err: 0,
+            runtime_services: 0,
+            boot_services: 0,
+            number_of_table_entries: entries.len(),
+            configuration_table: entries.as_ptr() as usize,
+        }
+    }
+
+    #[test]
+    fn test_search_additional_areas() {
+        #[repr(C, align(16))]
+        struct Area([u8; 256]);
+
+        let mut area = Area([0; 256]);
+        let rsdp = Rsdp::new(*b"OEMID ", 0, 0x3000);
+        unsafe { core::ptr::write_unaligned(area.0.as_mut_ptr().add(0x40).cast::<Rsdp>(), rsdp) };
+
+        // Empty areas are skipped, and areas that don't start at a 16-byte boundary are still searched at them
+        let start = area.0.as_ptr() as usize + 1;
+        let options = BiosSearchOptions {
+            additional_areas: &[start..start, start..(start + 255 - RSDP_V2_EXT_LENGTH)],
+            search_ebda: false,
+        };
+        let rsdp = unsafe { Rsdp::search_for_on_bios_with(IdentityHandler, &options) }.unwrap();
+        assert_eq!(rsdp.physical_start(), area.0.as_ptr() as usize + 0x40);
+        assert_eq!(rsdp.xsdt_address(), 0x3000);
+    }
+
+    /// The bytes of a genuine ACPI 1.0 RSDP, which is only 20 bytes long. It's followed by bytes that would be
+    /// the extended fields of a later RSDP, which mustn't be used to validate it.
+    fn rsdp_v1_bytes(oem_id: &[u8; 6], rsdt_address: u32) -> [u8; mem::size_of::<Rsdp>()] {
+        let mut bytes = [0xffu8; mem::size_of::<Rsdp>()];
+        bytes[0..8].copy_from_slice(&RSDP_SIGNATURE);
+        bytes[9..15].copy_from_slice(oem_id);
+        bytes[15] = 0;
+        bytes[16..20].copy_from_slice(&rsdt_address.to_le_bytes());
+        bytes[8] = 0;
+        bytes[8] = bytes[..RSDP_V1_LENGTH].iter().fold(0u8, |sum, &byte| sum.wrapping_sub(byte));
+        bytes
+    }
+
+    #[test]
+    fn test_from_efi_system_table() {
+        let rsdp_v1 = rsdp_v1_bytes(b"OEMID ", 0x1000);
+        let rsdp_v2 = Rsdp::new(*b"OEMID ", 0, 0x2000);
+        assert!(rsdp_v2.validate().is_ok());
+
+        // The ACPI 2.0 entry should be preferred, even if it isn't first
+        let entries = [
+            EfiConfigurationTableEntry { vendor_guid: [0; 16], vendor_table: 0 },
+            EfiConfigurationTableEntry {
+                vendor_guid: EFI_ACPI_1_0_TABLE_GUID,
+                vendor_table: rsdp_v1.as_ptr() as usize,
+            },
+            EfiConfigurationTableEntry {
+                vendor_guid: EFI_ACPI_2_0_TABLE_GUID,
+                vendor_table: &rsdp_v2 as *const Rsdp as usize,
+            },
+        ];
+        let table = system_table(&entries);
+        let rsdp = unsafe { Rsdp::from_efi_system_table(&table as *const _ as usize, IdentityHandler) }.unwrap();
+        assert_eq!(rsdp.xsdt_address(), 0x2000);
+
+        let table = system_table(&entries[..2]);
+        let rsdp = unsafe { Rsdp::from_efi_system_table(&table as *const _ as usize, IdentityHandler) }.unwrap();
+        assert_eq!(rsdp.revision(), 0);
+        assert_eq!(rsdp.rsdt_address(), 0x1000);
+
+        let table = system_table(&entries[..1]);
+        assert!(matches!(
+            unsafe { Rsdp::from_efi_system_table(&table as *const _ as usize, IdentityHandler) },
+            Err(RsdpError::NoValidRsdp)
+        ));
+
+        let table = EfiSystemTable { signature: 0, ..system_table(&entries) };
+        assert!(matches!(
+            unsafe { Rsdp::from_efi_system_table(&table as *const _ as usize, IdentityHandler) },
+            Err(RsdpError::InvalidEfiSystemTable)
+        ));
+    }
+
+    #[test]
+    fn test_from_efi_system_table_v1() {
+        let bytes = rsdp_v1_bytes(b"OEMID ", 0x1000);
+        let entries = [EfiConfigurationTableEntry {
+            vendor_guid: EFI_ACPI_1_0_TABLE_GUID,
+            vendor_table: bytes.as_ptr() as usize,
+        }];
+        let table = system_table(&entries);
+        let rsdp = unsafe { Rsdp::from_efi_system_table(&table as *const _ as usize, IdentityHandler) }.unwrap();
+        assert_eq!(rsdp.revision(), 0);
+        assert_eq!(rsdp.rsdt_address(), 0x1000);
+        assert_eq!(rsdp.oem_id(), "OEMID ");
+    }
+}